@@ -267,8 +267,20 @@ fn generate_struct(
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
 
+                // Byte sizes are stored as a number of bytes but also
+                // accept strings like "50MB"
+                let attrs = if type_str == "Bytes" {
+                    quote! {
+                        #[serde(deserialize_with = "crate::pitchfork_toml::deserialize_byte_size")]
+                        #[schemars(with = "crate::pitchfork_toml::ByteSizeValue")]
+                    }
+                } else {
+                    quote! {}
+                };
+
                 fields.push(quote! {
                     #[doc = #doc]
+                    #attrs
                     pub #field_ident: #rust_type
                 });
             } else {
@@ -344,7 +356,7 @@ fn generate_default_impl(
                     // Use type's default
                     match type_str {
                         "Bool" => quote! { false },
-                        "Integer" => quote! { 0 },
+                        "Integer" | "Bytes" => quote! { 0 },
                         "String" | "Duration" => quote! { String::new() },
                         "Path" => quote! { std::path::PathBuf::new() },
                         _ => quote! { Default::default() },
@@ -385,7 +397,7 @@ fn parse_default(default: &str, typ: &str) -> Result<TokenStream, Box<dyn std::e
             "false" => quote! { false },
             _ => return Err(format!("Invalid bool default: {default}").into()),
         },
-        "Integer" => {
+        "Integer" | "Bytes" => {
             let n: i64 = default
                 .parse()
                 .map_err(|_| format!("Invalid integer default: {default}"))?;
@@ -482,6 +494,19 @@ fn generate_load_from_env(table: &Table, path: &str) -> TokenStream {
                                     }
                                 }
                             },
+                            "Bytes" => quote! {
+                                if std::env::var(#env_var).is_err() {
+                                    if let Ok(val) = std::env::var(#dep_env) {
+                                        eprintln!("pitchfork: warning: {} is deprecated, use {} instead", #dep_env, #env_var);
+                                        if let Some(n) = crate::pitchfork_toml::parse_byte_size(&val)
+                                            .ok()
+                                            .and_then(|n| i64::try_from(n).ok())
+                                        {
+                                            #field_path = n;
+                                        }
+                                    }
+                                }
+                            },
                             "String" => quote! {
                                 if std::env::var(#env_var).is_err() {
                                     if let Ok(val) = std::env::var(#dep_env) {
@@ -525,6 +550,21 @@ fn generate_load_from_env(table: &Table, path: &str) -> TokenStream {
                             }
                             #deprecated_block
                         },
+                        "Bytes" => quote! {
+                            if let Ok(val) = std::env::var(#env_var) {
+                                match crate::pitchfork_toml::parse_byte_size(&val)
+                                    .ok()
+                                    .and_then(|n| i64::try_from(n).ok())
+                                {
+                                    Some(n) => #field_path = n,
+                                    None => eprintln!(
+                                        "pitchfork: warning: invalid byte size {:?} for {}, using default",
+                                        val, #env_var
+                                    ),
+                                }
+                            }
+                            #deprecated_block
+                        },
                         "String" => quote! {
                             if let Ok(val) = std::env::var(#env_var) {
                                 #field_path = val;
@@ -614,9 +654,18 @@ fn generate_partial_struct_and_nested(
                     .get("description")
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
+                let attrs = if type_str == "Bytes" {
+                    quote! {
+                        #[serde(deserialize_with = "crate::pitchfork_toml::deserialize_opt_byte_size")]
+                        #[schemars(with = "Option<crate::pitchfork_toml::ByteSizeValue>")]
+                    }
+                } else {
+                    quote! {}
+                };
                 fields.push(quote! {
                     #[doc = #doc]
                     #[serde(skip_serializing_if = "Option::is_none", default)]
+                    #attrs
                     pub #field_ident: Option<#rust_type>
                 });
             } else {
@@ -740,11 +789,11 @@ fn generate_apply_partial_body(table: &Table, self_path: &str, partial_path: &st
 
                 let setting_name = format!("{}.{}", self_path.trim_start_matches("self."), key);
 
-                // Bool and Integer are Copy; String/Duration/Path need clone.
+                // Bool, Integer and Bytes are Copy; String/Duration/Path need clone.
                 // Duration fields are validated at apply time so that invalid
                 // values from TOML are caught once (consistent with env-var path).
                 let stmt = match type_str {
-                    "Bool" | "Integer" => quote! {
+                    "Bool" | "Integer" | "Bytes" => quote! {
                         if let Some(v) = #partial_field {
                             #self_field = v;
                         }
@@ -950,6 +999,7 @@ fn parse_type(typ: &str) -> Result<TokenStream, Box<dyn std::error::Error>> {
     Ok(match typ {
         "Bool" => quote! { bool },
         "Integer" => quote! { i64 },
+        "Bytes" => quote! { i64 }, // Number of bytes
         "String" => quote! { String },
         "Duration" => quote! { String }, // Stored as humantime string
         "Path" => quote! { std::path::PathBuf },
@@ -0,0 +1,343 @@
+//! ANSI escape sequence handling shared by the log viewers.
+//!
+//! Daemon output (especially under `pty = true`) contains SGR color codes as
+//! well as terminal control sequences (cursor movement, screen clears, window
+//! titles). [`strip_pty_controls`] drops everything except SGR so the output
+//! can be replayed line by line, and [`parse_spans`] turns the remaining SGR
+//! codes into styled text spans that the TUI and web UI render natively.
+
+/// A color selected by an SGR sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// One of the 256 indexed colors. 0–7 are the standard colors, 8–15 the
+    /// bright variants, 16–231 the 6×6×6 cube and 232–255 the grayscale ramp.
+    Indexed(u8),
+    /// A 24-bit truecolor value.
+    Rgb(u8, u8, u8),
+}
+
+/// Text attributes accumulated from SGR sequences.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnsiStyle {
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+/// A run of text sharing a single [`AnsiStyle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiSpan {
+    pub text: String,
+    pub style: AnsiStyle,
+}
+
+impl AnsiStyle {
+    /// Apply the parameters of one SGR (`ESC[...m`) sequence.
+    ///
+    /// An empty parameter list is treated as a reset, matching terminals.
+    /// Unknown parameters are ignored.
+    pub fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Self::default();
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                n @ 30..=37 => self.fg = Some(AnsiColor::Indexed((n - 30) as u8)),
+                39 => self.fg = None,
+                n @ 40..=47 => self.bg = Some(AnsiColor::Indexed((n - 40) as u8)),
+                49 => self.bg = None,
+                n @ 90..=97 => self.fg = Some(AnsiColor::Indexed((n - 90 + 8) as u8)),
+                n @ 100..=107 => self.bg = Some(AnsiColor::Indexed((n - 100 + 8) as u8)),
+                n @ (38 | 48) => {
+                    let (color, consumed) = extended_color(&params[i + 1..]);
+                    if let Some(color) = color {
+                        if n == 38 {
+                            self.fg = Some(color);
+                        } else {
+                            self.bg = Some(color);
+                        }
+                    }
+                    i += consumed;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Parse the tail of a `38`/`48` parameter: `5;n` or `2;r;g;b`.
+/// Returns the color (if well-formed) and how many parameters were consumed.
+fn extended_color(rest: &[u16]) -> (Option<AnsiColor>, usize) {
+    match rest {
+        [5, n, ..] => (u8::try_from(*n).ok().map(AnsiColor::Indexed), 2),
+        [2, r, g, b, ..] => {
+            let rgb = (u8::try_from(*r), u8::try_from(*g), u8::try_from(*b));
+            match rgb {
+                (Ok(r), Ok(g), Ok(b)) => (Some(AnsiColor::Rgb(r, g, b)), 4),
+                _ => (None, 4),
+            }
+        }
+        // Malformed: skip whatever is left so it isn't misread as new attributes.
+        _ => (None, rest.len()),
+    }
+}
+
+fn flatten_params(params: &vte::Params) -> Vec<u16> {
+    params.iter().flat_map(|sub| sub.iter().copied()).collect()
+}
+
+/// Strips PTY control sequences from a string while preserving SGR (color/style) codes.
+///
+/// Removes CSI sequences that control cursor movement, screen clearing, erasing, etc.,
+/// but keeps `\x1b[...m` (SGR) sequences so colors are retained.
+pub fn strip_pty_controls(s: &str) -> String {
+    struct Stripper {
+        result: String,
+    }
+
+    impl vte::Perform for Stripper {
+        fn print(&mut self, c: char) {
+            // C1 controls (e.g. from Latin-1 output) would be read as escape
+            // sequences by some terminals
+            if !c.is_control() {
+                self.result.push(c);
+            }
+        }
+
+        fn execute(&mut self, byte: u8) {
+            // Keep \n and \t; drop other control characters (BEL, BS, CR, etc.)
+            if byte == b'\n' || byte == b'\t' {
+                self.result.push(byte as char);
+            }
+        }
+
+        fn csi_dispatch(
+            &mut self,
+            params: &vte::Params,
+            _intermediates: &[u8],
+            _ignore: bool,
+            action: char,
+        ) {
+            // Keep SGR sequences (final byte 'm')
+            if action == 'm' {
+                self.result.push_str("\x1b[");
+                let mut first = true;
+                for sub in params.iter() {
+                    if !first {
+                        self.result.push(';');
+                    }
+                    first = false;
+                    for (i, &p) in sub.iter().enumerate() {
+                        if i > 0 {
+                            self.result.push(':');
+                        }
+                        self.result.push_str(&p.to_string());
+                    }
+                }
+                self.result.push('m');
+            }
+            // All other CSI sequences (cursor move, clear, erase, etc.) are dropped
+        }
+
+        fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
+            // Drop OSC sequences (e.g. window title)
+        }
+
+        fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {
+            // Drop ESC sequences (e.g. ESC c = reset terminal)
+        }
+
+        fn hook(
+            &mut self,
+            _params: &vte::Params,
+            _intermediates: &[u8],
+            _ignore: bool,
+            _action: char,
+        ) {
+            // Drop DCS hooks
+        }
+
+        fn put(&mut self, _byte: u8) {
+            // Drop DCS data
+        }
+
+        fn unhook(&mut self) {
+            // Drop DCS unhook
+        }
+    }
+
+    let mut parser = vte::Parser::new();
+    let mut stripper = Stripper {
+        result: String::with_capacity(s.len()),
+    };
+    parser.advance(&mut stripper, s.as_bytes());
+    stripper.result
+}
+
+/// Split a line into styled spans according to its SGR codes.
+///
+/// Non-SGR control sequences and other control characters are dropped, tabs
+/// are expanded to four spaces, and adjacent text with the same style is
+/// merged into a single span.
+/// A line without escape codes yields one unstyled span.
+pub fn parse_spans(s: &str) -> Vec<AnsiSpan> {
+    struct Collector {
+        spans: Vec<AnsiSpan>,
+        current: String,
+        style: AnsiStyle,
+    }
+
+    impl Collector {
+        fn flush(&mut self) {
+            if self.current.is_empty() {
+                return;
+            }
+            let text = std::mem::take(&mut self.current);
+            match self.spans.last_mut() {
+                Some(last) if last.style == self.style => last.text.push_str(&text),
+                _ => self.spans.push(AnsiSpan {
+                    text,
+                    style: self.style,
+                }),
+            }
+        }
+    }
+
+    impl vte::Perform for Collector {
+        fn print(&mut self, c: char) {
+            if !c.is_control() {
+                self.current.push(c);
+            }
+        }
+
+        fn execute(&mut self, byte: u8) {
+            if byte == b'\t' {
+                self.current.push_str("    ");
+            }
+        }
+
+        fn csi_dispatch(
+            &mut self,
+            params: &vte::Params,
+            _intermediates: &[u8],
+            _ignore: bool,
+            action: char,
+        ) {
+            if action == 'm' {
+                self.flush();
+                self.style.apply_sgr(&flatten_params(params));
+            }
+        }
+    }
+
+    // Control characters (NUL, BEL, backspace, ...) from binary output would
+    // corrupt the TUI if passed through, so those lines take the slow path too
+    if !s.chars().any(char::is_control) {
+        return vec![AnsiSpan {
+            text: s.to_string(),
+            style: AnsiStyle::default(),
+        }];
+    }
+
+    let mut parser = vte::Parser::new();
+    let mut collector = Collector {
+        spans: Vec::new(),
+        current: String::new(),
+        style: AnsiStyle::default(),
+    };
+    parser.advance(&mut collector, s.as_bytes());
+    collector.flush();
+    collector.spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_pty_controls_keeps_sgr() {
+        let s = "\x1b[2J\x1b[1;1H\x1b]0;title\x07\x1b[32mok\x1b[0m\r";
+        assert_eq!(strip_pty_controls(s), "\x1b[32mok\x1b[0m");
+    }
+
+    #[test]
+    fn test_control_characters_from_binary_output_are_dropped() {
+        let s = "a\0b\x07c\x08\u{9b}2Jd\u{fffd}";
+        assert_eq!(strip_pty_controls(s), "abc2Jd\u{fffd}");
+        let spans = parse_spans(s);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "abc2Jd\u{fffd}");
+    }
+
+    #[test]
+    fn test_parse_spans_plain() {
+        let spans = parse_spans("hello world");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "hello world");
+        assert_eq!(spans[0].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn test_parse_spans_basic_colors() {
+        let spans = parse_spans("\x1b[1;31merror\x1b[0m: \x1b[92mdone");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].text, "error");
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(1)));
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[1].text, ": ");
+        assert_eq!(spans[1].style, AnsiStyle::default());
+        assert_eq!(spans[2].style.fg, Some(AnsiColor::Indexed(10)));
+    }
+
+    #[test]
+    fn test_parse_spans_extended_colors() {
+        let spans = parse_spans("\x1b[38;5;208ma\x1b[48;2;1;2;3mb\x1b[38:2:10:20:30mc");
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(208)));
+        assert_eq!(spans[1].style.bg, Some(AnsiColor::Rgb(1, 2, 3)));
+        assert_eq!(spans[2].style.fg, Some(AnsiColor::Rgb(10, 20, 30)));
+        assert_eq!(spans[2].style.bg, Some(AnsiColor::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_parse_spans_merges_and_drops_controls() {
+        let spans = parse_spans("\x1b[33ma\x1b[Kb\x1b[33mc\tz");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "abc    z");
+    }
+
+    #[test]
+    fn test_apply_sgr_resets() {
+        let mut style = AnsiStyle::default();
+        style.apply_sgr(&[1, 4, 35, 44]);
+        style.apply_sgr(&[22, 24, 39]);
+        assert_eq!(
+            style,
+            AnsiStyle {
+                bg: Some(AnsiColor::Indexed(4)),
+                ..Default::default()
+            }
+        );
+        style.apply_sgr(&[]);
+        assert_eq!(style, AnsiStyle::default());
+    }
+}
@@ -0,0 +1,206 @@
+//! launchd plists for boot start on macOS
+//!
+//! pitchfork writes the plist itself rather than leaving it to
+//! `auto_launcher`, so the job restarts according to
+//! `supervisor.boot_restart`, is throttled by `supervisor.boot_restart_delay`,
+//! and sends the supervisor's stdout/stderr to files in the logs directory
+//! instead of discarding them. The plist lives at the same path
+//! `auto_launcher` uses, so registrations from older versions are still found.
+
+use crate::Result;
+use crate::settings::settings;
+use miette::IntoDiagnostic;
+use std::path::{Path, PathBuf};
+
+/// launchd job label
+pub const LABEL: &str = "pitchfork";
+
+/// `KeepAlive` policy, from `supervisor.boot_restart`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepAlive {
+    Always,
+    OnFailure,
+    Never,
+}
+
+impl KeepAlive {
+    pub fn from_settings() -> Self {
+        match settings().supervisor.boot_restart.as_str() {
+            "always" => KeepAlive::Always,
+            "never" => KeepAlive::Never,
+            "on-failure" => KeepAlive::OnFailure,
+            other => {
+                warn!(
+                    "unknown supervisor.boot_restart {other:?}, expected always, on-failure or never"
+                );
+                KeepAlive::OnFailure
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Plist {
+    pub program_arguments: Vec<String>,
+    pub keep_alive: KeepAlive,
+    /// Seconds between restarts
+    pub throttle_interval: u64,
+    pub stdout_path: PathBuf,
+    pub stderr_path: PathBuf,
+}
+
+impl Plist {
+    /// The boot-start job for `bin`, configured from settings.
+    pub fn for_supervisor(bin: &str) -> Self {
+        let logs_dir = &*crate::env::PITCHFORK_LOGS_DIR;
+        Self {
+            program_arguments: [bin, "supervisor", "run", "--boot"]
+                .map(String::from)
+                .to_vec(),
+            keep_alive: KeepAlive::from_settings(),
+            throttle_interval: settings().supervisor_boot_restart_delay().as_secs().max(1),
+            stdout_path: logs_dir.join("boot.out.log"),
+            stderr_path: logs_dir.join("boot.err.log"),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let args = self
+            .program_arguments
+            .iter()
+            .map(|a| format!("        <string>{}</string>\n", escape(a)))
+            .collect::<String>();
+        let keep_alive = match self.keep_alive {
+            KeepAlive::Always => "<true/>".to_string(),
+            KeepAlive::Never => "<false/>".to_string(),
+            KeepAlive::OnFailure => {
+                "<dict>\n        <key>SuccessfulExit</key>\n        <false/>\n    </dict>"
+                    .to_string()
+            }
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+{args}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    {keep_alive}
+    <key>ThrottleInterval</key>
+    <integer>{throttle}</integer>
+    <key>StandardOutPath</key>
+    <string>{stdout}</string>
+    <key>StandardErrorPath</key>
+    <string>{stderr}</string>
+</dict>
+</plist>
+"#,
+            throttle = self.throttle_interval,
+            stdout = escape(&self.stdout_path.to_string_lossy()),
+            stderr = escape(&self.stderr_path.to_string_lossy()),
+        )
+    }
+}
+
+/// Write the boot-start plist for `bin` to `path`. launchd loads it at the
+/// next login (LaunchAgent) or boot (LaunchDaemon).
+pub fn install(path: &Path, bin: &str) -> Result<()> {
+    let plist = Plist::for_supervisor(bin);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).into_diagnostic()?;
+    }
+    if let Some(parent) = plist.stdout_path.parent() {
+        std::fs::create_dir_all(parent).into_diagnostic()?;
+    }
+    std::fs::write(path, plist.render()).into_diagnostic()
+}
+
+/// The program a plist runs: the first `ProgramArguments` entry.
+pub fn program(plist: &str) -> Option<String> {
+    let (_, rest) = plist.split_once("<key>ProgramArguments</key>")?;
+    let (_, rest) = rest.split_once("<string>")?;
+    let (program, _) = rest.split_once("</string>")?;
+    Some(unescape(program.trim()))
+}
+
+/// The launchd domain boot start registers in.
+#[cfg(target_os = "macos")]
+pub fn domain(system: bool) -> String {
+    if system {
+        "system".to_string()
+    } else {
+        format!("gui/{}", nix::unistd::Uid::effective())
+    }
+}
+
+/// Whether launchd currently has the job loaded in `domain`.
+#[cfg(target_os = "macos")]
+pub fn is_loaded(domain: &str) -> bool {
+    std::process::Command::new("launchctl")
+        .args(["print", &format!("{domain}/{LABEL}")])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plist(keep_alive: KeepAlive) -> Plist {
+        Plist {
+            program_arguments: ["/opt/pitch&fork", "supervisor", "run", "--boot"]
+                .map(String::from)
+                .to_vec(),
+            keep_alive,
+            throttle_interval: 10,
+            stdout_path: PathBuf::from("/logs/boot.out.log"),
+            stderr_path: PathBuf::from("/logs/boot.err.log"),
+        }
+    }
+
+    #[test]
+    fn test_render_keep_alive() {
+        let xml = plist(KeepAlive::OnFailure).render();
+        assert!(xml.contains("<key>SuccessfulExit</key>\n        <false/>"));
+        assert!(xml.contains("<key>ThrottleInterval</key>\n    <integer>10</integer>"));
+        assert!(xml.contains("<string>/logs/boot.out.log</string>"));
+        assert!(xml.contains("<string>/opt/pitch&amp;fork</string>"));
+        assert!(
+            plist(KeepAlive::Always)
+                .render()
+                .contains("<key>KeepAlive</key>\n    <true/>")
+        );
+        assert!(
+            plist(KeepAlive::Never)
+                .render()
+                .contains("<key>KeepAlive</key>\n    <false/>")
+        );
+    }
+
+    #[test]
+    fn test_program_round_trip() {
+        let xml = plist(KeepAlive::Always).render();
+        assert_eq!(program(&xml).as_deref(), Some("/opt/pitch&fork"));
+        assert_eq!(program("<plist></plist>"), None);
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(target_os = "macos")]
+mod launchd;
+
 // ─── Supported platforms (macOS, Linux, Windows) ──────────────────────────
 
 #[cfg(any(target_os = "macos", target_os = "linux", windows))]
@@ -9,6 +12,8 @@ mod imp {
     use auto_launcher::MacOSLaunchMode;
     use auto_launcher::{AutoLaunch, AutoLaunchBuilder};
     use miette::IntoDiagnostic;
+    #[cfg(target_os = "macos")]
+    use std::path::PathBuf;
 
     #[cfg(any(target_os = "macos", target_os = "linux"))]
     fn build_launcher(
@@ -40,6 +45,28 @@ mod imp {
         /// instead of /Library/LaunchDaemons/ for root). Kept only for migration/cleanup.
         #[cfg(target_os = "macos")]
         legacy: AutoLaunch,
+        /// Whether the current level is the system-level LaunchDaemon.
+        #[cfg(target_os = "macos")]
+        system: bool,
+    }
+
+    /// What launchd knows about the boot-start job at the current level.
+    #[cfg(target_os = "macos")]
+    pub struct LaunchdStatus {
+        pub plist_path: PathBuf,
+        /// Whether launchd has the job loaded right now
+        pub loaded: bool,
+        /// The binary the plist runs, if it could be read
+        pub program: Option<String>,
+        /// The binary `pitchfork boot enable` would register now
+        pub expected_program: String,
+    }
+
+    #[cfg(target_os = "macos")]
+    impl LaunchdStatus {
+        pub fn program_matches(&self) -> bool {
+            self.program.as_deref() == Some(self.expected_program.as_str())
+        }
     }
 
     impl BootManager {
@@ -104,6 +131,7 @@ mod imp {
                 current,
                 other,
                 legacy,
+                system: nix::unistd::Uid::effective().is_root(),
             });
 
             #[cfg(not(target_os = "macos"))]
@@ -192,14 +220,47 @@ mod imp {
                 );
             }
 
-            self.current.enable().into_diagnostic()?;
-
             #[cfg(target_os = "macos")]
-            self.cleanup_legacy(true)?;
+            {
+                let bin = env::PITCHFORK_BIN.to_string_lossy();
+                super::launchd::install(&self.plist_path(), &bin)?;
+                self.cleanup_legacy(true)?;
+            }
+
+            #[cfg(not(target_os = "macos"))]
+            self.current.enable().into_diagnostic()?;
 
             Ok(())
         }
 
+        /// Path of the plist for the current level. Matches where
+        /// `auto_launcher` looks, so `is_enabled` keeps working.
+        #[cfg(target_os = "macos")]
+        fn plist_path(&self) -> PathBuf {
+            let file = format!("{}.plist", super::launchd::LABEL);
+            if self.system {
+                PathBuf::from("/Library/LaunchDaemons").join(file)
+            } else {
+                env::HOME_DIR.join("Library/LaunchAgents").join(file)
+            }
+        }
+
+        /// Whether launchd has the current level's job loaded, and whether its
+        /// plist still points at this pitchfork binary.
+        #[cfg(target_os = "macos")]
+        pub fn launchd_status(&self) -> LaunchdStatus {
+            let plist_path = self.plist_path();
+            let program = std::fs::read_to_string(&plist_path)
+                .ok()
+                .and_then(|xml| super::launchd::program(&xml));
+            LaunchdStatus {
+                loaded: super::launchd::is_loaded(&super::launchd::domain(self.system)),
+                program,
+                expected_program: env::PITCHFORK_BIN.to_string_lossy().to_string(),
+                plist_path,
+            }
+        }
+
         /// Remove registrations at *both* levels so cross-level leftovers are also
         /// cleaned up. Also removes legacy macOS LaunchAgentSystem entries when
         /// running as root. Returns Ok even if some entries could not be removed
@@ -1,5 +1,10 @@
+use crate::daemon_id::DaemonId;
+use crate::ipc::client::IpcClient;
 use crate::{Result, env};
+use itertools::Itertools;
+use log::LevelFilter;
 use miette::bail;
+use std::path::{Path, PathBuf};
 
 /// Activate pitchfork in your shell session
 ///
@@ -13,7 +18,7 @@ Activate pitchfork in your shell session
 Generates shell code that enables automatic daemon management when changing
 directories. Required for auto-start/stop features in pitchfork.toml.
 
-Supported shells: bash, zsh, fish
+Supported shells: bash, zsh, fish, nu, elvish
 
 Add to your shell config:
   bash (~/.bashrc):
@@ -23,10 +28,18 @@ Add to your shell config:
     eval \"$(pitchfork activate zsh)\"
 
   fish (~/.config/fish/config.fish):
-    pitchfork activate fish | source"
+    pitchfork activate fish | source
+
+  nu (config.nu):
+    # nushell can't eval generated code, so save it once and source it:
+    #   pitchfork activate nu | save -f ~/.config/nushell/pitchfork.nu
+    source ~/.config/nushell/pitchfork.nu
+
+  elvish (~/.config/elvish/rc.elv):
+    eval (pitchfork activate elvish | slurp)"
 )]
 pub struct Activate {
-    /// Shell to activate (bash, zsh, fish)
+    /// Shell to activate (bash, zsh, fish, nu, elvish)
     #[clap()]
     shell: String,
 }
@@ -34,15 +47,27 @@ pub struct Activate {
 impl Activate {
     pub async fn run(&self) -> Result<()> {
         let pitchfork = env::PITCHFORK_BIN.to_string_lossy().to_string();
+        let report = report_dir().to_string_lossy().to_string();
+        // Each hook counts its directory changes (`--seq`) and prints the
+        // report of the last background `pitchfork cd` before the prompt
         let s = match self.shell.as_str() {
             "bash" => format!(
                 r#"
+__pitchfork_seq=0
 __pitchfork() {{
-    {pitchfork} cd --shell-pid $$
+    __pitchfork_seq=$((__pitchfork_seq + 1))
+    ( {pitchfork} cd --shell-pid $$ --seq $__pitchfork_seq --report "{report}/$$" & )
+}}
+__pitchfork_report() {{
+    if [ -f "{report}/$$" ]; then
+        cat "{report}/$$" >&2
+        rm -f "{report}/$$"
+    fi
 }}
 {}
 {}
 chpwd_functions+=(__pitchfork)
+PROMPT_COMMAND="__pitchfork_report${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
 __pitchfork
 "#,
                 include_str!("../../assets/bash_zsh_support/chpwd/function.sh"),
@@ -50,24 +75,219 @@ __pitchfork
             ),
             "zsh" => format!(
                 r#"
+__pitchfork_seq=0
 __pitchfork() {{
-    {pitchfork} cd --shell-pid $$
+    __pitchfork_seq=$((__pitchfork_seq + 1))
+    {pitchfork} cd --shell-pid $$ --seq $__pitchfork_seq --report "{report}/$$" &!
+}}
+__pitchfork_report() {{
+    if [[ -f "{report}/$$" ]]; then
+        cat "{report}/$$" >&2
+        rm -f "{report}/$$"
+    fi
 }}
 chpwd_functions+=(__pitchfork)
+precmd_functions+=(__pitchfork_report)
 __pitchfork
 "#
             ),
             "fish" => format!(
                 r#"
+set -g __pitchfork_seq 0
 function __pitchfork --on-variable PWD
-    {pitchfork} cd --shell-pid "$fish_pid"
+    set -g __pitchfork_seq (math $__pitchfork_seq + 1)
+    {pitchfork} cd --shell-pid "$fish_pid" --seq $__pitchfork_seq --report "{report}/$fish_pid" &
+    disown
+end
+function __pitchfork_report --on-event fish_prompt
+    if test -f "{report}/$fish_pid"
+        cat "{report}/$fish_pid" >&2
+        rm -f "{report}/$fish_pid"
+    end
 end
 __pitchfork
 "#,
             ),
-            shell => bail!("unsupported shell: {shell}. Supported shells: bash, zsh, fish"),
+            "nu" | "nushell" => format!(
+                r#"
+$env.__PITCHFORK_SEQ = 1
+$env.config = (
+    $env.config
+    | upsert hooks.env_change.PWD {{|config|
+        $config.hooks?.env_change?.PWD? | default [] | append {{|before, after|
+            $env.__PITCHFORK_SEQ += 1
+            let seq = $env.__PITCHFORK_SEQ
+            job spawn {{ ^'{pitchfork}' cd --shell-pid $nu.pid --seq $seq --report $"{report}/($nu.pid)" }} | ignore
+        }}
+    }}
+    | upsert hooks.pre_prompt {{|config|
+        $config.hooks?.pre_prompt? | default [] | append {{||
+            let report = $"{report}/($nu.pid)"
+            if ($report | path exists) {{
+                open --raw $report | print --stderr --no-newline
+                rm $report
+            }}
+        }}
+    }}
+)
+job spawn {{ ^'{pitchfork}' cd --shell-pid $nu.pid --seq 1 --report $"{report}/($nu.pid)" }} | ignore
+"#,
+            ),
+            "elvish" => format!(
+                r#"
+var __pitchfork_seq = 0
+fn __pitchfork {{
+    set __pitchfork_seq = (+ $__pitchfork_seq 1)
+    sh -c '"$0" cd --shell-pid "$1" --seq "$2" --report "$3" &' '{pitchfork}' $pid $__pitchfork_seq '{report}/'$pid
+}}
+fn __pitchfork_report {{
+    sh -c '[ -f "$0" ] || exit 0; cat "$0" >&2; rm -f "$0"' '{report}/'$pid
+}}
+set after-chdir = [$@after-chdir {{|_| __pitchfork }}]
+set edit:before-readline = [$@edit:before-readline {{ __pitchfork_report }}]
+__pitchfork
+"#,
+            ),
+            shell => {
+                bail!("unsupported shell: {shell}. Supported shells: bash, zsh, fish, nu, elvish")
+            }
         };
         println!("{}", s.trim());
         Ok(())
     }
 }
+
+/// Print what the supervisor did for the shell hook once `pitchfork cd` is
+/// done. Daemon starts and autostops are folded into one line, e.g.
+/// `pitchfork: started api (port 3001), db; stopped web`; any other pending
+/// notifications are shown as usual.
+///
+/// The hook runs `pitchfork cd` in the background, so it passes a `report`
+/// file to write this to instead, along with the output of a
+/// `failed_start`. The hook prints the file at the next prompt rather than
+/// over the current one.
+pub(crate) async fn print_cd_summary(
+    ipc: &IpcClient,
+    failed_start: Option<String>,
+    report: Option<&Path>,
+) {
+    let notifications = ipc.get_notifications().await.unwrap_or_default();
+    let (summary, rest) = summarize(notifications);
+    let Some(report) = report else {
+        if let Some(summary) = summary {
+            eprintln!("pitchfork: {summary}");
+        }
+        for (level, msg) in rest {
+            super::log_notification(level, &msg);
+        }
+        return;
+    };
+
+    let mut lines = failed_start
+        .map(|output| format!("{}\n", output.trim_end()))
+        .into_iter()
+        .collect_vec();
+    lines.extend(
+        summary
+            .into_iter()
+            .chain(rest.into_iter().map(|(_, msg)| msg))
+            .map(|msg| format!("pitchfork: {msg}\n")),
+    );
+    if lines.is_empty() {
+        return;
+    }
+    if let Err(err) = write_report(report, &lines.concat()) {
+        debug!("failed to write {}: {err}", report.display());
+    }
+}
+
+/// Add `text` to the report file, keeping whatever the shell hasn't shown
+/// yet. Written through a temp file so the hook never prints half of it.
+fn write_report(report: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(dir) = report.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut content = std::fs::read_to_string(report).unwrap_or_default();
+    content.push_str(text);
+    let temp = report.with_extension("tmp");
+    std::fs::write(&temp, content)?;
+    std::fs::rename(&temp, report)
+}
+
+/// Where `pitchfork cd` leaves its report for the shell hook, one file per
+/// shell PID
+fn report_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| env::HOME_DIR.join(".cache"))
+        .join("pitchfork")
+        .join("cd")
+}
+
+type Notifications = Vec<(LevelFilter, String)>;
+
+fn summarize(notifications: Notifications) -> (Option<String>, Notifications) {
+    let mut started = vec![];
+    let mut stopped = vec![];
+    let mut rest = vec![];
+    for (level, msg) in notifications {
+        if let Some(daemon) = msg.strip_prefix("started ") {
+            started.push(short_name(daemon));
+        } else if let Some(daemon) = msg.strip_prefix("autostopped ") {
+            stopped.push(short_name(daemon));
+        } else {
+            rest.push((level, msg));
+        }
+    }
+    let parts = [("started", started), ("stopped", stopped)]
+        .into_iter()
+        .filter(|(_, daemons)| !daemons.is_empty())
+        .map(|(verb, daemons)| format!("{verb} {}", daemons.join(", ")))
+        .collect::<Vec<_>>();
+    ((!parts.is_empty()).then(|| parts.join("; ")), rest)
+}
+
+/// `project/api (port 3001)` -> `api (port 3001)`
+fn short_name(daemon: &str) -> String {
+    let (id, detail) = match daemon.split_once(' ') {
+        Some((id, detail)) => (id, Some(detail)),
+        None => (daemon, None),
+    };
+    let name = DaemonId::parse(id)
+        .map(|id| id.name().to_string())
+        .unwrap_or_else(|_| id.to_string());
+    match detail {
+        Some(detail) => format!("{name} {detail}"),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(msg: &str) -> (LevelFilter, String) {
+        (LevelFilter::Info, msg.to_string())
+    }
+
+    #[test]
+    fn test_summarize() {
+        let (summary, rest) = summarize(vec![
+            info("started project/api (port 3001)"),
+            info("proxy listening on :7777"),
+            info("started project/db"),
+            info("autostopped other/web"),
+        ]);
+        assert_eq!(
+            summary.as_deref(),
+            Some("started api (port 3001), db; stopped web")
+        );
+        assert_eq!(rest, vec![info("proxy listening on :7777")]);
+    }
+
+    #[test]
+    fn test_summarize_nothing_started() {
+        let (summary, rest) = summarize(vec![]);
+        assert_eq!(summary, None);
+        assert!(rest.is_empty());
+    }
+}
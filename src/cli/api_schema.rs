@@ -77,16 +77,23 @@ impl ApiSchema {
                 Endpoint {
                     path: "/api/daemons/{id}/start",
                     method: "POST",
-                    description: "Start a stopped or failed daemon.",
+                    description: "Start a stopped or failed daemon. A port conflict is returned as `port_conflict` so the caller can retry with `on_conflict`.",
                     path_params: vec![Param {
                         name: "id",
                         type_name: "String",
                         description: "Qualified daemon ID",
                         required: true,
                     }],
-                    query_params: vec![],
+                    query_params: vec![Param {
+                        name: "on_conflict",
+                        type_name: "String",
+                        description: "How to resolve a port conflict: \"kill\" the process holding the port or \"bump\" to the next free port",
+                        required: false,
+                    }],
                     request_body: None,
-                    response_type: Some(r#"{ "ok": bool, "error": String|null }"#),
+                    response_type: Some(
+                        r#"{ "ok": bool, "error": String|null, "port_conflict": { "port": u16, "process": String, "pid": u32 }|null }"#,
+                    ),
                     auth: true,
                 },
                 Endpoint {
@@ -107,16 +114,23 @@ impl ApiSchema {
                 Endpoint {
                     path: "/api/daemons/{id}/restart",
                     method: "POST",
-                    description: "Stop and then start a daemon.",
+                    description: "Stop and then start a daemon. A port conflict is returned as `port_conflict` so the caller can retry with `on_conflict`.",
                     path_params: vec![Param {
                         name: "id",
                         type_name: "String",
                         description: "Qualified daemon ID",
                         required: true,
                     }],
-                    query_params: vec![],
+                    query_params: vec![Param {
+                        name: "on_conflict",
+                        type_name: "String",
+                        description: "How to resolve a port conflict: \"kill\" the process holding the port or \"bump\" to the next free port",
+                        required: false,
+                    }],
                     request_body: None,
-                    response_type: Some(r#"{ "ok": bool, "error": String|null }"#),
+                    response_type: Some(
+                        r#"{ "ok": bool, "error": String|null, "port_conflict": { "port": u16, "process": String, "pid": u32 }|null }"#,
+                    ),
                     auth: true,
                 },
                 Endpoint {
@@ -164,6 +178,70 @@ impl ApiSchema {
                     response_type: Some("text/event-stream"),
                     auth: true,
                 },
+                Endpoint {
+                    path: "/api/logs/search",
+                    method: "GET",
+                    description: "Search the logs of every daemon at once. Returns matching lines newest first; `truncated` is true when more lines matched than `limit`.",
+                    path_params: vec![],
+                    query_params: vec![
+                        Param {
+                            name: "q",
+                            type_name: "String",
+                            description: "Case-insensitive text the message must contain",
+                            required: false,
+                        },
+                        Param {
+                            name: "daemon",
+                            type_name: "String",
+                            description: "Comma-separated qualified daemon IDs (default: all daemons)",
+                            required: false,
+                        },
+                        Param {
+                            name: "level",
+                            type_name: "String",
+                            description: "Minimum level: trace, debug, info, warn or error",
+                            required: false,
+                        },
+                        Param {
+                            name: "since",
+                            type_name: "String",
+                            description: "Start of the time range, e.g. \"30m\" or \"2026-05-31 10:00\"",
+                            required: false,
+                        },
+                        Param {
+                            name: "until",
+                            type_name: "String",
+                            description: "End of the time range, same formats as since",
+                            required: false,
+                        },
+                        Param {
+                            name: "limit",
+                            type_name: "usize",
+                            description: "Maximum lines to return (default: 200, max: 1000)",
+                            required: false,
+                        },
+                    ],
+                    request_body: None,
+                    response_type: Some(
+                        r#"{ "entries": [{ "id": i64, "daemon_id": String, "timestamp": String, "level": String|null, "msg": String|null, "logger": String|null, "stream": "stdout"|"stderr", "message": String }], "truncated": bool }"#,
+                    ),
+                    auth: true,
+                },
+                Endpoint {
+                    path: "/api/events/stream",
+                    method: "GET",
+                    description: "Stream daemon status changes, lifecycle events and supervisor notifications via SSE as they happen. Event names are \"status\", \"event\" and \"notification\"; each data line is a JSON object tagged with \"type\".",
+                    path_params: vec![],
+                    query_params: vec![Param {
+                        name: "daemon",
+                        type_name: "String",
+                        description: "Only stream events about this qualified daemon ID (notifications are skipped)",
+                        required: false,
+                    }],
+                    request_body: None,
+                    response_type: Some("text/event-stream"),
+                    auth: true,
+                },
                 Endpoint {
                     path: "/api/namespaces",
                     method: "GET",
@@ -57,6 +57,11 @@ When run as a normal user: creates a user-level entry
   macOS: ~/Library/LaunchAgents/pitchfork.plist
   Linux: ~/.config/systemd/user/pitchfork.service
 
+On macOS, launchd restarts the supervisor according to
+`settings.supervisor.boot_restart` (KeepAlive) and
+`settings.supervisor.boot_restart_delay` (ThrottleInterval), and its
+stdout/stderr go to boot.out.log and boot.err.log in the logs directory.
+
 If you want the supervisor to run as root but keep state files and IPC sockets
 under a specific user's home directory, configure `settings.supervisor.user`
 in your pitchfork configuration.")]
@@ -72,7 +77,11 @@ automatically on system boot.")]
     #[clap(long_about = "\
 Check boot start status
 
-Reports whether pitchfork is configured to start on system boot.")]
+Reports whether pitchfork is configured to start on system boot.
+
+On macOS, also shows the plist path, whether launchd has the job loaded,
+and warns when the plist runs a different pitchfork binary than this one
+(e.g. after reinstalling pitchfork elsewhere).")]
     Status(BootStatus),
 }
 
@@ -161,6 +170,26 @@ impl BootStatus {
             (false, false) => info!("Boot start is disabled"),
         }
 
+        #[cfg(target_os = "macos")]
+        if current_enabled {
+            let launchd = boot_manager.launchd_status();
+            println!("  plist: {}", launchd.plist_path.display());
+            if launchd.loaded {
+                println!("  launchd: loaded");
+            } else {
+                println!("  launchd: not loaded (it loads at the next login or boot)");
+            }
+            match &launchd.program {
+                Some(program) if launchd.program_matches() => println!("  binary: {program}"),
+                Some(program) => warn!(
+                    "the boot entry runs {program}, but this pitchfork is {}; \
+                    run `pitchfork boot disable && pitchfork boot enable` to update it",
+                    launchd.expected_program
+                ),
+                None => warn!("could not read the binary path from the plist"),
+            }
+        }
+
         Ok(())
     }
 }
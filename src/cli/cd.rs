@@ -1,33 +1,53 @@
 use crate::daemon_id::DaemonId;
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::{PitchforkToml, PitchforkTomlAuto};
+use crate::state_file::StateFile;
 use crate::{Result, env};
 use duct::cmd;
 use itertools::Itertools;
 use miette::IntoDiagnostic;
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Args)]
 #[clap(hide = true, verbatim_doc_comment)]
 pub struct Cd {
     #[clap(long)]
     shell_pid: u32,
+    /// Number of this directory change in the shell, so the supervisor can
+    /// drop changes that arrive out of order
+    #[clap(long)]
+    seq: Option<u64>,
+    /// Write what happened to this file instead of printing it, for the
+    /// shell hook to show at the next prompt
+    #[clap(long)]
+    report: Option<PathBuf>,
 }
 
 impl Cd {
     pub async fn run(&self) -> Result<()> {
-        if let Ok(ipc) = IpcClient::connect(true).await {
-            ipc.update_shell_dir(self.shell_pid, env::CWD.clone())
-                .await?;
+        if let Ok(ipc) = IpcClient::shared(true).await {
+            // The shell hook runs this in the background, so a busy
+            // supervisor doesn't hold up the prompt. A missed update is caught
+            // up by the next directory change; daemons still autostart.
+            if let Err(err) = ipc
+                .update_shell_dir(self.shell_pid, env::CWD.clone(), self.seq)
+                .await
+            {
+                debug!("failed to update shell dir: {err}");
+            }
 
             let pt = PitchforkToml::all_merged()?;
+            let state = StateFile::get();
             let to_start = pt
                 .daemons
                 .into_iter()
                 .filter(|(_id, d)| d.auto.contains(&PitchforkTomlAuto::Start))
+                .filter(|(id, _d)| state.autostart_allowed(id))
                 .map(|(id, _d)| id)
                 .collect_vec();
             if to_start.is_empty() {
+                super::activate::print_cd_summary(&ipc, None, self.report.as_deref()).await;
                 return Ok(());
             }
             let mut args = vec![
@@ -48,10 +68,26 @@ impl Cd {
                 }
                 args.push(id.qualified());
             }
+            let mut failed_start = None;
             if args.len() > 3 {
-                cmd(&*env::PITCHFORK_BIN, args).run().into_diagnostic()?;
+                let start = cmd(&*env::PITCHFORK_BIN, args);
+                if self.report.is_some() {
+                    // Its progress would land on the prompt; the summary
+                    // covers the daemons it started
+                    let output = start
+                        .stderr_to_stdout()
+                        .stdout_capture()
+                        .unchecked()
+                        .run()
+                        .into_diagnostic()?;
+                    if !output.status.success() {
+                        failed_start = Some(String::from_utf8_lossy(&output.stdout).into_owned());
+                    }
+                } else {
+                    start.run().into_diagnostic()?;
+                }
             }
-            super::drain_notifications(&ipc).await;
+            super::activate::print_cd_summary(&ipc, failed_start, self.report.as_deref()).await;
         } else {
             debug!("No daemon running");
         }
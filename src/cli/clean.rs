@@ -1,5 +1,6 @@
 use crate::Result;
 use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::PitchforkToml;
 
 /// Removes stopped/failed daemons from `pitchfork list`
 #[derive(Debug, clap::Args)]
@@ -11,20 +12,28 @@ Removes stopped/failed daemons from `pitchfork list`
 
 Cleans up the daemon list by removing entries for daemons that are no
 longer running. Does not affect running daemons or their configurations.
+Daemons with `sticky = true` in their config are kept along with their
+last run info.
 
 Use this to clear out old entries after stopping daemons manually or
 after daemons have failed.
 
 Examples:
   pitchfork clean                 Remove all stopped/failed entries
+  pitchfork clean --keep backup   Remove them, but keep 'backup'
   pitchfork c                     Alias for 'clean'"
 )]
-pub struct Clean {}
+pub struct Clean {
+    /// Keep these daemons even though they aren't running
+    #[clap(long, value_name = "ID", value_delimiter = ',')]
+    keep: Vec<String>,
+}
 
 impl Clean {
     pub async fn run(&self) -> Result<()> {
-        let ipc = IpcClient::connect(false).await?;
-        ipc.clean().await?;
+        let keep = PitchforkToml::resolve_ids(&self.keep)?;
+        let ipc = IpcClient::shared(false).await?;
+        ipc.clean(&keep).await?;
         Ok(())
     }
 }
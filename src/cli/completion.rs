@@ -1,6 +1,13 @@
-use crate::Result;
+use crate::cli::Cli;
+use crate::{Result, env};
+use clap::CommandFactory;
 use duct::cmd;
-use miette::IntoDiagnostic;
+use miette::{IntoDiagnostic, bail};
+use std::path::PathBuf;
+
+/// Marks the block `--install` writes, so running it again replaces it
+const BLOCK_START: &str = "# >>> pitchfork >>>";
+const BLOCK_END: &str = "# <<< pitchfork <<<";
 
 /// Generates shell completion scripts
 #[derive(Debug, clap::Args)]
@@ -9,9 +16,11 @@ use miette::IntoDiagnostic;
     long_about = "\
 Generates shell completion scripts
 
-Creates tab-completion scripts for your shell. Requires the 'usage' CLI tool.
+Creates tab-completion scripts for your shell. bash, zsh and fish
+completions require the 'usage' CLI tool and complete daemon names;
+nushell and elvish completions are self-contained.
 
-Supported shells: bash, zsh, fish
+Supported shells: bash, zsh, fish, nu, elvish
 
 Installation:
   bash:
@@ -21,21 +30,66 @@ Installation:
     pitchfork completion zsh > ~/.zfunc/_pitchfork
 
   fish:
-    pitchfork completion fish > ~/.config/fish/completions/pitchfork.fish"
+    pitchfork completion fish > ~/.config/fish/completions/pitchfork.fish
+
+  nu:
+    pitchfork completion nu | save -f ~/.config/nushell/pitchfork-completions.nu
+    # then add to config.nu: source ~/.config/nushell/pitchfork-completions.nu
+
+  elvish (~/.config/elvish/rc.elv):
+    eval (pitchfork completion elvish | slurp)
+
+Or let pitchfork add activation and completions to your shell's rc file
+(bash, zsh, fish). The shell defaults to $SHELL; the rc file is backed up
+first, and running it again updates the lines instead of adding them twice:
+  pitchfork completion --install
+  pitchfork completion zsh --install"
 )]
 pub struct Completion {
-    /// Shell to generate completions for (bash, zsh, fish)
-    #[clap()]
-    shell: String,
+    /// Shell to generate completions for (bash, zsh, fish, nu, elvish)
+    #[clap(required_unless_present = "install")]
+    shell: Option<String>,
+    /// Add `pitchfork activate` and completion lines to the shell's rc file
+    #[clap(long)]
+    install: bool,
 }
 
 impl Completion {
     pub async fn run(&self) -> Result<()> {
+        if self.install {
+            return self.install();
+        }
+        let Some(shell) = self.shell.as_deref() else {
+            bail!("a shell is required, e.g. `pitchfork completion zsh`");
+        };
+        // usage-cli doesn't generate nushell or elvish completions, so those
+        // come straight from the clap definition.
+        match shell {
+            "nu" | "nushell" => {
+                clap_complete::generate(
+                    clap_complete_nushell::Nushell,
+                    &mut Cli::command(),
+                    "pitchfork",
+                    &mut std::io::stdout(),
+                );
+                return Ok(());
+            }
+            "elvish" => {
+                clap_complete::generate(
+                    clap_complete::Shell::Elvish,
+                    &mut Cli::command(),
+                    "pitchfork",
+                    &mut std::io::stdout(),
+                );
+                return Ok(());
+            }
+            _ => {}
+        }
         cmd!(
             "usage",
             "g",
             "completion",
-            &self.shell,
+            shell,
             "pitchfork",
             "--usage-cmd",
             "pitchfork usage",
@@ -44,4 +98,122 @@ impl Completion {
         .into_diagnostic()?;
         Ok(())
     }
+
+    fn install(&self) -> Result<()> {
+        let shell = match &self.shell {
+            Some(shell) => shell.clone(),
+            None => detect_shell()?,
+        };
+        let Some(path) = rc_file(&shell) else {
+            bail!("--install supports bash, zsh and fish, not {shell}");
+        };
+        let lines = rc_lines(&shell);
+        let existing = if path.exists() {
+            xx::file::read_to_string(&path)?
+        } else {
+            String::new()
+        };
+        let updated = upsert_block(&existing, &lines);
+        if updated == existing {
+            println!("{} is already set up for pitchfork", path.display());
+            return Ok(());
+        }
+        if path.exists() {
+            let backup = PathBuf::from(format!("{}.pitchfork.bak", path.display()));
+            std::fs::copy(&path, &backup).into_diagnostic()?;
+            println!("Backed up {} to {}", path.display(), backup.display());
+        }
+        xx::file::write(&path, updated)?;
+        println!(
+            "Added pitchfork activation and completions to {}; open a new shell to use them",
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+/// The current shell, from the basename of `$SHELL`
+fn detect_shell() -> Result<String> {
+    let shell = env::var("SHELL").unwrap_or_default();
+    match shell.rsplit('/').next() {
+        Some(name) if !name.is_empty() => Ok(name.to_string()),
+        _ => bail!("could not detect your shell from $SHELL, pass it explicitly"),
+    }
+}
+
+/// The rc file `--install` writes to for `shell`
+fn rc_file(shell: &str) -> Option<PathBuf> {
+    match shell {
+        "bash" => Some(env::HOME_DIR.join(".bashrc")),
+        "zsh" => Some(
+            env::var("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| env::HOME_DIR.clone())
+                .join(".zshrc"),
+        ),
+        "fish" => Some(env::HOME_DIR.join(".config/fish/config.fish")),
+        _ => None,
+    }
+}
+
+/// The activation and completion lines for `shell`
+fn rc_lines(shell: &str) -> String {
+    match shell {
+        "fish" => "pitchfork activate fish | source\npitchfork completion fish | source\n".into(),
+        _ => format!(
+            "eval \"$(pitchfork activate {shell})\"\neval \"$(pitchfork completion {shell})\"\n"
+        ),
+    }
+}
+
+/// Put `lines` in the marked pitchfork block of an rc file, replacing the
+/// block if it's already there and appending it otherwise
+fn upsert_block(rc: &str, lines: &str) -> String {
+    let block = format!("{BLOCK_START}\n{lines}{BLOCK_END}\n");
+    if let Some(start) = rc.find(BLOCK_START)
+        && let Some(len) = rc[start..].find(BLOCK_END)
+    {
+        let mut end = start + len + BLOCK_END.len();
+        if rc[end..].starts_with('\n') {
+            end += 1;
+        }
+        return format!("{}{block}{}", &rc[..start], &rc[end..]);
+    }
+    match rc {
+        "" => block,
+        _ if rc.ends_with('\n') => format!("{rc}\n{block}"),
+        _ => format!("{rc}\n\n{block}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsert_block_appends_once() {
+        let lines = rc_lines("zsh");
+        let rc = "export PATH=~/bin:$PATH\n";
+        let installed = upsert_block(rc, &lines);
+        assert_eq!(
+            installed,
+            "export PATH=~/bin:$PATH\n\n# >>> pitchfork >>>\neval \"$(pitchfork activate zsh)\"\neval \"$(pitchfork completion zsh)\"\n# <<< pitchfork <<<\n"
+        );
+        assert_eq!(upsert_block(&installed, &lines), installed);
+    }
+
+    #[test]
+    fn test_upsert_block_replaces_existing() {
+        let rc = "a\n# >>> pitchfork >>>\nold line\n# <<< pitchfork <<<\nb\n";
+        assert_eq!(
+            upsert_block(rc, &rc_lines("fish")),
+            "a\n# >>> pitchfork >>>\npitchfork activate fish | source\npitchfork completion fish | source\n# <<< pitchfork <<<\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_rc_file_unsupported_shell() {
+        assert!(rc_file("bash").is_some());
+        assert!(rc_file("nu").is_none());
+    }
 }
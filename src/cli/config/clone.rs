@@ -0,0 +1,244 @@
+use super::fmt::{print_diff, write};
+use super::rename::{daemons_table, defining_files, rebuild_daemons};
+use crate::Result;
+use crate::daemon_id::DaemonId;
+use crate::pitchfork_toml::PitchforkToml;
+use miette::{IntoDiagnostic, bail, ensure};
+use std::path::Path;
+use toml_edit::{DocumentMut, InlineTable, Item, Key, Table, Value};
+
+/// Copy a daemon in pitchfork.toml under a new name
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Copy a daemon in pitchfork.toml under a new name
+
+The copy is added right after the daemon in every config file in effect in
+the current directory that defines it, e.g. in both pitchfork.toml and
+pitchfork.local.toml. Comments and formatting are kept. A diff of each
+change is printed before the file is written.
+
+--set changes a setting of the copy, given as a dotted path and a TOML
+value. Values that aren't valid TOML, or don't fit the setting, are used as
+strings. The setting is made in the most specific file defining the daemon.
+
+Examples:
+  pitchfork config clone api api-staging
+  pitchfork config clone api api-staging --set env.PORT=4001
+  pitchfork config clone api api-2 --set port=4002 --set autostop=true"
+)]
+pub struct CloneDaemon {
+    /// Daemon to copy
+    id: String,
+    /// Name of the copy
+    new_name: String,
+    /// Change a setting of the copy
+    #[clap(long = "set", value_name = "KEY=VALUE", value_parser = parse_setting)]
+    set: Vec<(String, String)>,
+}
+
+fn parse_setting(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Invalid setting format: {s}. Expected KEY=value")),
+    }
+}
+
+impl CloneDaemon {
+    pub async fn run(&self) -> Result<()> {
+        let from = PitchforkToml::resolve_id(&self.id)?;
+        let to = DaemonId::try_new(from.namespace(), &self.new_name)?;
+        if PitchforkToml::all_merged()?.daemons.contains_key(&to) {
+            bail!("daemon {to} already exists");
+        }
+        let files = defining_files(&from)?;
+        ensure!(
+            !files.is_empty(),
+            "daemon {from} isn't defined in a config file here"
+        );
+
+        // Check every file before writing any of them
+        let mut changes = vec![];
+        for (i, path) in files.iter().enumerate() {
+            let content = std::fs::read_to_string(path).into_diagnostic()?;
+            let set = if i == files.len() - 1 {
+                self.set.as_slice()
+            } else {
+                &[]
+            };
+            let cloned = clone_in(&content, path, &from, &to, set)?;
+            PitchforkToml::parse_str(&cloned, path)?;
+            changes.push((path, content, cloned));
+        }
+        for (path, content, cloned) in &changes {
+            print_diff(path, content, cloned);
+            write(path, cloned)?;
+        }
+        info!("cloned {from} to {to}");
+        Ok(())
+    }
+}
+
+/// Add a copy of daemon `from` called `to` right after it in a config
+/// file's contents, with `set` applied to the copy
+fn clone_in(
+    content: &str,
+    path: &Path,
+    from: &DaemonId,
+    to: &DaemonId,
+    set: &[(String, String)],
+) -> Result<String> {
+    let mut doc: DocumentMut = content.parse().into_diagnostic()?;
+    let daemons = daemons_table(&mut doc, path)?;
+    rebuild_daemons(daemons, |key, item| {
+        if key.get() != from.name() {
+            return vec![(key, item)];
+        }
+        let mut copy = item.clone();
+        // The copy goes right after the daemon, without its comments
+        match &mut copy {
+            Item::Table(table) => {
+                clear_positions(table);
+                table.decor_mut().set_prefix("\n");
+            }
+            Item::Value(value) => value.decor_mut().set_prefix(" "),
+            _ => {}
+        }
+        vec![(key, item), (Key::new(to.name()), copy)]
+    });
+
+    for (key, value) in set {
+        // Values are TOML if the setting takes them, e.g. port=4001, and
+        // strings otherwise, e.g. env.PORT=4001
+        let typed = value
+            .parse::<Value>()
+            .ok()
+            .and_then(|value| set_in(&mut doc, path, to, key, value).ok())
+            .filter(|doc| PitchforkToml::parse_str(&doc.to_string(), path).is_ok());
+        doc = match typed {
+            Some(doc) => doc,
+            None => set_in(&mut doc, path, to, key, Value::from(value.as_str()))?,
+        };
+    }
+    Ok(doc.to_string())
+}
+
+/// A copy of `doc` with the dotted `key` of daemon `id` set to `value`,
+/// creating the tables on the way as needed
+fn set_in(
+    doc: &mut DocumentMut,
+    path: &Path,
+    id: &DaemonId,
+    key: &str,
+    mut value: Value,
+) -> Result<DocumentMut> {
+    let mut doc = doc.clone();
+    let daemons = daemons_table(&mut doc, path)?;
+    let Some(mut item) = daemons.get_mut(id.name()) else {
+        bail!("daemon {id} isn't defined in {}", path.display());
+    };
+    let keys: Vec<&str> = key.split('.').collect();
+    let (last, parents) = keys.split_last().expect("split always yields a key");
+    for part in parents {
+        let Some(table) = item.as_table_like_mut() else {
+            bail!("cannot set {key}: {part} isn't in a table");
+        };
+        item = table
+            .entry(part)
+            .or_insert(Item::Value(Value::InlineTable(InlineTable::new())));
+    }
+    let Some(table) = item.as_table_like_mut() else {
+        bail!("cannot set {key}: it isn't in a table");
+    };
+    value.decor_mut().clear();
+    table.insert(last, Item::Value(value));
+
+    // The old last entry of an inline table keeps the space before `}`
+    let mut item = daemons_table(&mut doc, path)?.get_mut(id.name());
+    let mut parts = parents.iter();
+    while let Some(found) = item {
+        if let Some(table) = found.as_inline_table_mut() {
+            table.fmt();
+        }
+        item = parts
+            .next()
+            .and_then(|part| found.as_table_like_mut()?.get_mut(part));
+    }
+    Ok(doc)
+}
+
+/// Let `table` and its subtables be placed by where they are inserted
+fn clear_positions(table: &mut Table) {
+    table.set_position(None);
+    for (_, item) in table.iter_mut() {
+        if let Item::Table(table) = item {
+            clear_positions(table);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clone(content: &str, set: &[(&str, &str)]) -> String {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("proj").join("pitchfork.toml");
+        let set: Vec<_> = set
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        clone_in(
+            content,
+            &path,
+            &DaemonId::new("proj", "api"),
+            &DaemonId::new("proj", "api-staging"),
+            &set,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_clone_after_daemon() {
+        let content = r#"# the api
+[daemons.api]
+run = "npm start"
+
+[daemons.api.env]
+PORT = "4000"
+
+[daemons.web]
+run = "npm run dev"
+"#;
+        assert_eq!(
+            clone(content, &[("env.PORT", "4001"), ("port", "4001")]),
+            r#"# the api
+[daemons.api]
+run = "npm start"
+
+[daemons.api.env]
+PORT = "4000"
+
+[daemons.api-staging]
+run = "npm start"
+port = 4001
+
+[daemons.api-staging.env]
+PORT = "4001"
+
+[daemons.web]
+run = "npm run dev"
+"#
+        );
+    }
+
+    #[test]
+    fn test_clone_inline_daemon() {
+        let content = "[daemons]\napi = { run = \"npm start\" }\nweb = { run = \"npm run dev\" }\n";
+        assert_eq!(
+            clone(content, &[("env.PORT", "4001")]),
+            "[daemons]\napi = { run = \"npm start\" }\napi-staging = { run = \"npm start\", env = { PORT = \"4001\" } }\nweb = { run = \"npm run dev\" }\n"
+        );
+    }
+}
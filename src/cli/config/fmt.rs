@@ -0,0 +1,439 @@
+use crate::Result;
+use crate::env;
+use crate::error::FileError;
+use crate::pitchfork_toml::{PitchforkToml, parse_duration};
+use crate::ui::style::{ncyan, ndim, nstyle};
+use miette::{IntoDiagnostic, WrapErr, bail};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml_edit::{DocumentMut, Formatted, Item, Table, TableLike, Value};
+
+/// Format pitchfork.toml files
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Format pitchfork.toml files
+
+Puts keys in the order pitchfork writes them itself, gives every daemon its
+own [daemons.<name>] table and writes durations like \"90 seconds\" or
+\"2min\" with a single unit (\"90s\", \"2m\"). Comments are kept. A diff of
+each change is printed before the file is written.
+
+Without arguments, formats every config file in effect in the current
+directory: pitchfork.toml and pitchfork.local.toml files from here up to
+the root, and ~/.config/pitchfork/config.toml.
+
+Examples:
+  pitchfork config fmt                    Format the config files in effect here
+  pitchfork config fmt pitchfork.toml     Format one file
+  pitchfork config fmt --check            Show what would change; exit 1 if
+                                          anything would"
+)]
+pub struct Fmt {
+    /// Config files to format
+    files: Vec<PathBuf>,
+    /// Don't write anything; exit with status 1 if a file isn't formatted
+    #[clap(long)]
+    check: bool,
+}
+
+impl Fmt {
+    pub async fn run(&self) -> Result<()> {
+        let mut unformatted = 0;
+        for path in config_files(&self.files)? {
+            let content = std::fs::read_to_string(&path).into_diagnostic()?;
+            let formatted = format(&content)
+                .map_err(|e| miette::miette!("failed to parse {}: {e}", path.display()))?;
+            if formatted == content {
+                continue;
+            }
+            unformatted += 1;
+            print_diff(&path, &content, &formatted);
+            if !self.check {
+                write(&path, &formatted)?;
+                info!("formatted {}", path.display());
+            }
+        }
+        if self.check && unformatted > 0 {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+/// Top-level keys, in the order `PitchforkToml::write()` puts them
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "namespace",
+    "daemons",
+    "env",
+    "env_templates",
+    "settings",
+    "slugs",
+    "groups",
+    "namespaces",
+];
+
+/// Daemon keys, in the order `PitchforkToml::write()` puts them. Keys not
+/// listed here keep their place after the known ones.
+const DAEMON_KEYS: &[&str] = &[
+    "run",
+    "kind",
+    "aliases",
+    "auto",
+    "cron",
+    "retry",
+    "success_exit_codes",
+    "no_retry_exit_codes",
+    "ready_delay",
+    "ready_output",
+    "ready_http",
+    "ready_port",
+    "ready_cmd",
+    "ready_log_file",
+    "ready_unix_socket",
+    "ready_when",
+    "fail_output",
+    "expect_long_running",
+    "port",
+    "expected_port",
+    "auto_bump_port",
+    "port_bump_attempts",
+    "boot_start",
+    "start_priority",
+    "start_delay",
+    "depends",
+    "requires",
+    "watch",
+    "watch_mode",
+    "dir",
+    "env",
+    "env_templates",
+    "debug_env",
+    "hooks",
+    "mise",
+    "user",
+    "memory_limit",
+    "cpu_limit",
+    "alerts",
+    "stop_signal",
+    "umask",
+    "limits",
+    "pty",
+    "isolate_network",
+    "restart_on_config_change",
+    "restart_on_binary_change",
+    "restart_with_dependencies",
+    "sticky",
+    "time_retention",
+    "line_retention",
+    "archive_hook",
+    "logs",
+];
+
+/// Keys holding a duration anywhere within a daemon's table
+const DURATION_KEYS: &[&str] = &[
+    "ready_delay",
+    "start_delay",
+    "timeout",
+    "attempt_timeout",
+    "debounce",
+    "duration",
+    "time_retention",
+];
+
+/// The config files to work on: `files`, or else the ones in effect in the
+/// current directory. The system-wide config is left out since it is
+/// usually not the user's to edit.
+pub(super) fn config_files(files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    if !files.is_empty() {
+        for path in files {
+            if !path.is_file() {
+                bail!("{} does not exist", path.display());
+            }
+        }
+        return Ok(files.to_vec());
+    }
+    let paths: Vec<PathBuf> = PitchforkToml::list_paths()
+        .into_iter()
+        .filter(|p| *p != *env::PITCHFORK_GLOBAL_CONFIG_SYSTEM && p.is_file())
+        .collect();
+    if paths.is_empty() {
+        bail!("no pitchfork.toml found in {}", env::CWD.display());
+    }
+    Ok(paths)
+}
+
+/// Normalize a config file's contents, keeping comments
+pub(super) fn format(content: &str) -> std::result::Result<String, toml_edit::TomlError> {
+    let mut doc: DocumentMut = content.parse()?;
+    let root = doc.as_table_mut();
+    if let Some(item) = root.get_mut("daemons") {
+        if let Some(daemons) = item.as_inline_table_mut() {
+            *item = Item::Table(std::mem::take(daemons).into_table());
+        }
+        if let Some(daemons) = item.as_table_mut() {
+            format_daemons(daemons);
+        }
+    }
+    sort_keys(root, TOP_LEVEL_KEYS);
+    let mut position = 0;
+    renumber_tables(root, &mut position);
+    // A daemon table that is now first in the file would start it with a
+    // blank line
+    Ok(doc.to_string().trim_start_matches('\n').to_string())
+}
+
+/// Give every daemon its own `[daemons.<name>]` table and order its keys
+fn format_daemons(daemons: &mut Table) {
+    daemons.set_dotted(false);
+    for (mut key, item) in daemons.iter_mut() {
+        if let Some(daemon) = item.as_inline_table_mut() {
+            let mut table = std::mem::take(daemon).into_table();
+            // Comments above `name = { ... }` go above the new header, after
+            // a blank line
+            let comment = key
+                .leaf_decor()
+                .prefix()
+                .and_then(|p| p.as_str())
+                .unwrap_or("")
+                .trim_start_matches('\n')
+                .to_string();
+            table.decor_mut().set_prefix(format!("\n{comment}"));
+            key.leaf_decor_mut().clear();
+            *item = Item::Table(table);
+        }
+        if let Some(daemon) = item.as_table_mut() {
+            daemon.set_dotted(false);
+            sort_keys(daemon, DAEMON_KEYS);
+            format_durations(daemon);
+        }
+    }
+    // Drop a bare `[daemons]` header, moving any comment above it down to
+    // the first daemon
+    if daemons.iter().all(|(_, item)| item.is_table()) {
+        let prefix = daemons.decor().prefix().cloned();
+        if let Some(prefix) = prefix.filter(|p| p.as_str().is_some_and(|p| p.contains('#')))
+            && let Some((_, Item::Table(first))) = daemons.iter_mut().next()
+        {
+            let first_prefix = first
+                .decor()
+                .prefix()
+                .and_then(|p| p.as_str())
+                .unwrap_or("");
+            let merged = format!("{}{first_prefix}", prefix.as_str().unwrap_or(""));
+            first.decor_mut().set_prefix(merged);
+        }
+        daemons.set_implicit(true);
+        daemons.decor_mut().clear();
+    }
+}
+
+/// Order `table`'s keys by `order`, leaving unknown keys after the known
+/// ones in their current order
+fn sort_keys(table: &mut Table, order: &[&str]) {
+    let rank = |key: &str| order.iter().position(|k| *k == key).unwrap_or(order.len());
+    table.sort_values_by(|a, _, b, _| rank(a.get()).cmp(&rank(b.get())));
+}
+
+/// Write durations with a single unit
+fn format_durations(table: &mut dyn TableLike) {
+    for (key, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => format_duration_value(key.get(), value),
+            Item::Table(table) => format_durations(table),
+            Item::ArrayOfTables(tables) => {
+                for table in tables.iter_mut() {
+                    format_durations(table);
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+fn format_duration_value(key: &str, value: &mut Value) {
+    match value {
+        Value::String(s) if DURATION_KEYS.contains(&key) => {
+            let Some(formatted) = parse_duration(s.value()).ok().and_then(canonical_duration)
+            else {
+                return;
+            };
+            if formatted != *s.value() {
+                let decor = s.decor().clone();
+                *s = Formatted::new(formatted);
+                *s.decor_mut() = decor;
+            }
+        }
+        Value::InlineTable(table) => format_durations(table),
+        Value::Array(values) => {
+            for value in values.iter_mut() {
+                if let Value::InlineTable(table) = value {
+                    format_durations(table);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `d` in the largest unit that divides it exactly, e.g. `"90s"` or `"2m"`.
+/// None for durations finer than a millisecond.
+fn canonical_duration(d: Duration) -> Option<String> {
+    if !d.subsec_nanos().is_multiple_of(1_000_000) {
+        return None;
+    }
+    let ms = d.as_millis();
+    let units = [
+        ("d", 86_400_000),
+        ("h", 3_600_000),
+        ("m", 60_000),
+        ("s", 1_000),
+    ];
+    Some(
+        units
+            .iter()
+            .find(|(_, size)| ms > 0 && ms.is_multiple_of(*size))
+            .map(|(unit, size)| format!("{}{unit}", ms / size))
+            .unwrap_or_else(|| match ms {
+                0 => "0s".to_string(),
+                ms => format!("{ms}ms"),
+            }),
+    )
+}
+
+/// Number the header tables in key order, so they are written in the order
+/// the keys were sorted into
+fn renumber_tables(table: &mut Table, position: &mut isize) {
+    if !table.is_dotted() {
+        table.set_position(Some(*position));
+        *position += 1;
+    }
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(table) => renumber_tables(table, position),
+            Item::ArrayOfTables(tables) => {
+                for table in tables.iter_mut() {
+                    renumber_tables(table, position);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Print the changes between `old` and `new` as a colored unified diff
+pub(super) fn print_diff(path: &Path, old: &str, new: &str) {
+    let diff = similar::TextDiff::from_lines(old, new);
+    println!("{}", ndim(format!("--- {}", path.display())));
+    println!("{}", ndim(format!("+++ {}", path.display())));
+    for hunk in diff.unified_diff().context_radius(2).iter_hunks() {
+        println!("{}", ncyan(hunk.header()));
+        for change in hunk.iter_changes() {
+            let line = change.to_string_lossy();
+            let line = line.trim_end_matches('\n');
+            match change.tag() {
+                similar::ChangeTag::Delete => println!("{}", nstyle(format!("-{line}")).red()),
+                similar::ChangeTag::Insert => println!("{}", nstyle(format!("+{line}")).green()),
+                similar::ChangeTag::Equal => println!(" {line}"),
+            }
+        }
+    }
+}
+
+/// Write `content` to `path` under the config file lock
+pub(super) fn write(path: &Path, content: &str) -> Result<()> {
+    let _lock = xx::fslock::get(path, false)
+        .wrap_err_with(|| format!("failed to acquire lock on {}", path.display()))?;
+    xx::file::write(path, content).map_err(|e| FileError::WriteError {
+        path: path.to_path_buf(),
+        details: Some(e.to_string()),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_orders_keys_and_tables() {
+        let content = r#"# project daemons
+[daemons.web]
+env = { PORT = "3000" }
+# how to start it
+run = "npm run dev"
+
+[settings.general]
+interval = "5s"
+
+[daemons.web.hooks]
+on_ready = "echo ready"
+
+[daemons.api]
+ready_delay = 3
+run = "cargo run"
+"#;
+        let formatted = format(content).unwrap();
+        assert_eq!(
+            formatted,
+            r#"# project daemons
+[daemons.web]
+# how to start it
+run = "npm run dev"
+env = { PORT = "3000" }
+
+[daemons.web.hooks]
+on_ready = "echo ready"
+
+[daemons.api]
+run = "cargo run"
+ready_delay = 3
+
+[settings.general]
+interval = "5s"
+"#
+        );
+        assert_eq!(format(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_inline_daemons() {
+        let content = r#"namespace = "proj"
+
+[daemons]
+# the api server
+api = { run = "cargo run", ready_http = { url = "http://localhost:3000", timeout = "90 seconds" } }
+worker = { run = "./worker", stop_signal = { signal = "SIGINT", timeout = "1500ms" } }
+"#;
+        let formatted = format(content).unwrap();
+        assert_eq!(
+            formatted,
+            r#"namespace = "proj"
+
+# the api server
+[daemons.api]
+run = "cargo run"
+ready_http = { url = "http://localhost:3000", timeout = "90s" }
+
+[daemons.worker]
+run = "./worker"
+stop_signal = { signal = "SIGINT", timeout = "1500ms" }
+"#
+        );
+        assert_eq!(format(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_canonical_duration() {
+        let d = |s| canonical_duration(parse_duration(s).unwrap()).unwrap();
+        assert_eq!(d("120s"), "2m");
+        assert_eq!(d("2 minutes"), "2m");
+        assert_eq!(d("90s"), "90s");
+        assert_eq!(d("1h 30m"), "90m");
+        assert_eq!(d("7d"), "7d");
+        assert_eq!(d("1500ms"), "1500ms");
+        assert_eq!(d("0s"), "0s");
+        assert_eq!(d("30"), "30s");
+    }
+}
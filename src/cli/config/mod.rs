@@ -0,0 +1,34 @@
+mod clone;
+mod fmt;
+mod rename;
+mod schema;
+mod validate;
+
+/// Work with pitchfork.toml configuration files
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Commands {
+    Clone(clone::CloneDaemon),
+    Fmt(fmt::Fmt),
+    Rename(rename::Rename),
+    Schema(schema::Schema),
+    Validate(validate::Validate),
+}
+
+impl Config {
+    pub async fn run(self) -> crate::Result<()> {
+        match self.command {
+            Commands::Clone(clone) => clone.run().await,
+            Commands::Fmt(fmt) => fmt.run().await,
+            Commands::Rename(rename) => rename.run().await,
+            Commands::Schema(schema) => schema.run().await,
+            Commands::Validate(validate) => validate.run().await,
+        }
+    }
+}
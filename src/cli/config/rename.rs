@@ -0,0 +1,282 @@
+use super::fmt::{config_files, print_diff, write};
+use crate::Result;
+use crate::daemon_id::DaemonId;
+use crate::ipc::batch::{StartOptions, update_job_with_result};
+use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::{PitchforkToml, namespace_from_path};
+use miette::{IntoDiagnostic, bail, ensure};
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Key, Table, Value};
+
+/// Rename a daemon in pitchfork.toml
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Rename a daemon in pitchfork.toml
+
+Renames the daemon in every config file in effect in the current directory
+that defines it, e.g. in both pitchfork.toml and pitchfork.local.toml, and
+updates the depends, ready_when and groups entries that refer to it there.
+Comments and formatting are kept. A diff of each change is printed before
+the file is written.
+
+The daemon's state (ports, disabled flags, exit history) and its logs are
+moved to the new name. If the new name already has state or logs, e.g. from
+an earlier daemon of that name, nothing is renamed. A running daemon is
+refused, since the supervisor tracks it by name; with --force it is stopped,
+renamed and started again.
+
+References from other projects' config files aren't updated.
+
+Examples:
+  pitchfork config rename api backend           Rename a stopped daemon
+  pitchfork config rename api backend --force   Restart it under the new name"
+)]
+pub struct Rename {
+    /// Daemon to rename
+    id: String,
+    /// Its new name
+    new_name: String,
+    /// Stop the daemon if it is running, and start it again under the new name
+    #[clap(short, long)]
+    force: bool,
+}
+
+impl Rename {
+    pub async fn run(&self) -> Result<()> {
+        let from = PitchforkToml::resolve_id(&self.id)?;
+        let to = DaemonId::try_new(from.namespace(), &self.new_name)?;
+        ensure!(from != to, "daemon {from} is already called {}", to.name());
+        if defining_files(&from)?.is_empty() {
+            bail!("daemon {from} isn't defined in a config file here");
+        }
+        if PitchforkToml::all_merged()?.daemons.contains_key(&to) {
+            bail!("daemon {to} already exists");
+        }
+
+        // Check every file before writing any of them
+        let mut changes = vec![];
+        for path in config_files(&[])? {
+            let content = std::fs::read_to_string(&path).into_diagnostic()?;
+            let renamed = rename_in(&content, &path, &from, &to)?;
+            if renamed != content {
+                PitchforkToml::parse_str(&renamed, &path)?;
+                changes.push((path, content, renamed));
+            }
+        }
+
+        let ipc = IpcClient::shared(true).await?;
+        let running = ipc
+            .active_daemons()
+            .await?
+            .iter()
+            .any(|d| d.id == from && d.pid.is_some());
+        if running {
+            ensure!(
+                self.force,
+                "daemon {from} is running, stop it first or use --force to restart it under its new name"
+            );
+            ipc.stop(from.clone()).await?;
+        }
+
+        // State and logs first: if the new name already has some, nothing
+        // has been changed yet
+        ipc.rename_daemon(from.clone(), to.clone()).await?;
+        for (i, (path, content, renamed)) in changes.iter().enumerate() {
+            print_diff(path, content, renamed);
+            if let Err(err) = write(path, renamed) {
+                for (path, content, _) in &changes[..i] {
+                    if let Err(err) = write(path, content) {
+                        warn!("failed to restore {}: {err}", path.display());
+                    }
+                }
+                ipc.rename_daemon(to.clone(), from.clone()).await?;
+                return Err(err);
+            }
+        }
+        info!("renamed {from} to {to}");
+
+        if running {
+            let result = ipc.start_daemons(&[to], StartOptions::default()).await?;
+            for update in &result.pending_job_updates {
+                update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+            }
+            clx::progress::stop();
+            clx::progress::clear_jobs();
+            if result.any_failed {
+                std::process::exit(1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The config files in effect in the current directory that define `id`,
+/// in order of precedence, lowest first
+pub(super) fn defining_files(id: &DaemonId) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in config_files(&[])? {
+        if PitchforkToml::read(&path)?.daemons.contains_key(id) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The `[daemons]` table of a config file
+pub(super) fn daemons_table<'a>(doc: &'a mut DocumentMut, path: &Path) -> Result<&'a mut Table> {
+    match doc.get_mut("daemons") {
+        Some(Item::Table(daemons)) => Ok(daemons),
+        _ => bail!(
+            "{} has no [daemons] table; run `pitchfork config fmt` on it first",
+            path.display()
+        ),
+    }
+}
+
+/// Replace each daemon in `daemons` with the entries `f` returns for it,
+/// keeping the order of the rest
+pub(super) fn rebuild_daemons(
+    daemons: &mut Table,
+    mut f: impl FnMut(Key, Item) -> Vec<(Key, Item)>,
+) {
+    let keys: Vec<String> = daemons.iter().map(|(key, _)| key.to_string()).collect();
+    for key in keys {
+        if let Some((key, item)) = daemons.remove_entry(&key) {
+            for (key, item) in f(key, item) {
+                daemons.insert_formatted(&key, item);
+            }
+        }
+    }
+}
+
+/// Rename daemon `from` to `to` in a config file's contents, along with the
+/// references to it, keeping comments and formatting
+fn rename_in(content: &str, path: &Path, from: &DaemonId, to: &DaemonId) -> Result<String> {
+    let mut doc: DocumentMut = content.parse().into_diagnostic()?;
+    // Short names in a file refer to daemons in the file's own namespace
+    let same_namespace = namespace_from_path(path)? == from.namespace();
+    let rename = |value: &mut Value| {
+        let new = match value.as_str() {
+            Some(id) if same_namespace && id == from.name() => to.name().to_string(),
+            Some(id) if id == from.qualified() => to.qualified(),
+            _ => return,
+        };
+        let decor = value.decor().clone();
+        *value = new.into();
+        *value.decor_mut() = decor;
+    };
+    let rename_all = |item: Option<&mut Item>| {
+        if let Some(values) = item.and_then(Item::as_array_mut) {
+            values.iter_mut().for_each(rename);
+        }
+    };
+
+    if doc.get("daemons").is_some() {
+        let daemons = daemons_table(&mut doc, path)?;
+        if same_namespace && daemons.contains_key(from.name()) {
+            rebuild_daemons(daemons, |key, item| {
+                if key.get() == from.name() {
+                    let decor = key.leaf_decor().clone();
+                    vec![(Key::new(to.name()).with_leaf_decor(decor), item)]
+                } else {
+                    vec![(key, item)]
+                }
+            });
+        }
+        for (_, daemon) in daemons.iter_mut() {
+            let Some(daemon) = daemon.as_table_like_mut() else {
+                continue;
+            };
+            rename_all(daemon.get_mut("depends"));
+            if let Some(daemon) = daemon
+                .get_mut("ready_when")
+                .and_then(Item::as_table_like_mut)
+                .and_then(|ready_when| ready_when.get_mut("daemon"))
+                .and_then(Item::as_value_mut)
+            {
+                rename(daemon);
+            }
+        }
+    }
+    if let Some(groups) = doc.get_mut("groups").and_then(Item::as_table_like_mut) {
+        for (_, group) in groups.iter_mut() {
+            rename_all(group.as_table_like_mut().and_then(|g| g.get_mut("daemons")));
+        }
+    }
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_in() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("proj").join("pitchfork.toml");
+        let content = r#"# the database
+[daemons.db]
+run = "postgres"
+
+[daemons.api]
+run = "npm start"
+depends = ["db", "other/db"]
+ready_when = { daemon = "proj/db" }
+
+[daemons.worker]
+# needs the database too
+depends = [ "db" ]
+
+[groups.backend]
+daemons = ["api", "db"]
+"#;
+        let from = DaemonId::new("proj", "db");
+        let to = DaemonId::new("proj", "postgres");
+        assert_eq!(
+            rename_in(content, &path, &from, &to).unwrap(),
+            r#"# the database
+[daemons.postgres]
+run = "postgres"
+
+[daemons.api]
+run = "npm start"
+depends = ["postgres", "other/db"]
+ready_when = { daemon = "proj/postgres" }
+
+[daemons.worker]
+# needs the database too
+depends = [ "postgres" ]
+
+[groups.backend]
+daemons = ["api", "postgres"]
+"#
+        );
+
+        // Another namespace only refers to it by its qualified ID
+        let other = temp.path().join("other").join("pitchfork.toml");
+        let content = "[daemons.web]\ndepends = [\"db\", \"proj/db\"]\n";
+        assert_eq!(
+            rename_in(content, &other, &from, &to).unwrap(),
+            "[daemons.web]\ndepends = [\"db\", \"proj/postgres\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_inline_daemon_keeps_order() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("proj").join("pitchfork.toml");
+        let content = "[daemons]\ndb = { run = \"postgres\" }\napi = { run = \"npm start\" }\n";
+        assert_eq!(
+            rename_in(
+                content,
+                &path,
+                &DaemonId::new("proj", "db"),
+                &DaemonId::new("proj", "postgres")
+            )
+            .unwrap(),
+            "[daemons]\npostgres = { run = \"postgres\" }\napi = { run = \"npm start\" }\n"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use crate::Result;
+use crate::pitchfork_toml::PitchforkToml;
+use schemars::schema_for;
+
+/// Print the JSON Schema for pitchfork.toml
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Print the JSON Schema for pitchfork.toml
+
+The schema covers daemons, settings, groups and the global config's slugs
+and namespaces. Point your editor at it for autocompletion and validation
+while writing config files by hand.
+
+Examples:
+  pitchfork config schema > schema.json
+
+  # In pitchfork.toml, for Even Better TOML (taplo):
+  #:schema ./schema.json"
+)]
+pub struct Schema;
+
+impl Schema {
+    pub async fn run(&self) -> Result<()> {
+        println!("{}", schema_json());
+        Ok(())
+    }
+}
+
+fn schema_json() -> String {
+    let schema = schema_for!(PitchforkToml);
+    serde_json::to_string_pretty(&schema).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_covers_config_sections() {
+        let schema: serde_json::Value = serde_json::from_str(&schema_json()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in [
+            "daemons",
+            "settings",
+            "env",
+            "groups",
+            "slugs",
+            "namespaces",
+        ] {
+            assert!(properties.contains_key(key), "missing {key}");
+        }
+        assert!(schema.get("required").is_none());
+        assert!(schema["$defs"]["PitchforkTomlDaemon"]["properties"]["run"].is_object());
+    }
+}
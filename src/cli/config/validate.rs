@@ -0,0 +1,204 @@
+use super::fmt::{config_files, print_diff, write};
+use crate::Result;
+use crate::error::DependencyError;
+use crate::pitchfork_toml::PitchforkToml;
+use crate::settings::settings;
+use miette::IntoDiagnostic;
+use std::path::PathBuf;
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Key, TableLike, Value};
+
+/// Check pitchfork.toml files for errors
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Check pitchfork.toml files for errors
+
+Reads each file the way pitchfork does and reports what it can't use:
+invalid TOML, unknown daemons in depends, bad ready checks and so on.
+Settings that still work but are deprecated are reported as warnings.
+
+With --fix, deprecated settings are rewritten to their current form, e.g.
+expected_port and auto_bump_port become port. A diff of each change is
+printed before the file is written; comments and formatting are kept.
+
+Without arguments, checks every config file in effect in the current
+directory: pitchfork.toml and pitchfork.local.toml files from here up to
+the root, and ~/.config/pitchfork/config.toml.
+
+Examples:
+  pitchfork config validate                   Check the config files in effect here
+  pitchfork config validate pitchfork.toml    Check one file
+  pitchfork config validate --fix             Also rewrite deprecated settings"
+)]
+pub struct Validate {
+    /// Config files to check
+    files: Vec<PathBuf>,
+    /// Rewrite deprecated settings to their current form
+    #[clap(long)]
+    fix: bool,
+}
+
+impl Validate {
+    pub async fn run(&self) -> Result<()> {
+        let mut invalid = 0;
+        for path in config_files(&self.files)? {
+            let content = std::fs::read_to_string(&path).into_diagnostic()?;
+            if let Err(e) =
+                PitchforkToml::parse_str(&content, &path).and_then(|pt| check_depends(&pt))
+            {
+                eprintln!("{:?}", e.wrap_err(format!("{} is invalid", path.display())));
+                invalid += 1;
+                continue;
+            }
+            let fixed = fix(&content).into_diagnostic()?;
+            if fixed == content {
+                info!("{} is valid", path.display());
+            } else if self.fix {
+                // Never write a file pitchfork can no longer read
+                PitchforkToml::parse_str(&fixed, &path)?;
+                print_diff(&path, &content, &fixed);
+                write(&path, &fixed)?;
+                info!("fixed {}", path.display());
+            } else {
+                warn!(
+                    "{} uses deprecated settings, run `pitchfork config validate --fix` to update them",
+                    path.display()
+                );
+            }
+        }
+        if invalid > 0 {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+/// Check that every daemon in `pt` depends only on daemons that exist,
+/// looking through all the config files in effect in its directory. Daemons
+/// in other namespaces may be defined anywhere, so they aren't checked.
+fn check_depends(pt: &PitchforkToml) -> Result<()> {
+    let Some(dir) = pt.path.as_deref().and_then(|p| p.parent()) else {
+        return Ok(());
+    };
+    let merged = PitchforkToml::all_merged_from(dir)?;
+    for (id, daemon) in &pt.daemons {
+        for dep in &daemon.depends {
+            if dep.namespace() == id.namespace() && !merged.daemons.contains_key(dep) {
+                return Err(DependencyError::MissingDependency {
+                    daemon: id.qualified(),
+                    dependency: dep.qualified(),
+                }
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite deprecated settings in a config file's contents, keeping
+/// comments and formatting
+fn fix(content: &str) -> std::result::Result<String, toml_edit::TomlError> {
+    let mut doc: DocumentMut = content.parse()?;
+    if let Some(daemons) = doc.get_mut("daemons").and_then(Item::as_table_like_mut) {
+        for (_, daemon) in daemons.iter_mut() {
+            if let Some(daemon) = daemon.as_table_like_mut() {
+                fix_port(daemon);
+            }
+        }
+    }
+    Ok(doc.to_string())
+}
+
+/// Replace `expected_port`, `auto_bump_port` and `port_bump_attempts` with
+/// the `port` they amount to. They are ignored when `port` is already set,
+/// so then they are just removed.
+fn fix_port(daemon: &mut dyn TableLike) {
+    // A comment above expected_port stays with port
+    let decor = daemon
+        .key("expected_port")
+        .map(|key| key.leaf_decor().clone())
+        .unwrap_or_default();
+    let expected_port = daemon.remove("expected_port");
+    let auto_bump_port = daemon.remove("auto_bump_port");
+    let port_bump_attempts = daemon.remove("port_bump_attempts");
+    if daemon.contains_key("port") {
+        return;
+    }
+
+    let expect: Vec<i64> = expected_port
+        .as_ref()
+        .and_then(Item::as_array)
+        .map(|ports| ports.iter().filter_map(Value::as_integer).collect())
+        .unwrap_or_default();
+    // Same as the config parser: auto_bump_port without a number of
+    // attempts uses the default from settings
+    let bump = match auto_bump_port.and_then(|b| b.as_bool()) {
+        Some(true) => port_bump_attempts
+            .and_then(|n| n.as_integer())
+            .unwrap_or_else(|| settings().default_port_bump_attempts().into()),
+        _ => 0,
+    };
+    let port = match (expect.as_slice(), bump) {
+        ([], 0) => return,
+        ([port], 0) => Value::from(*port),
+        (ports, 0) => Value::Array(ports.iter().copied().collect::<Array>()),
+        (ports, bump) => {
+            let mut table = InlineTable::new();
+            if !ports.is_empty() {
+                table.insert("expect", ports.iter().copied().collect::<Array>().into());
+            }
+            table.insert("bump", bump.into());
+            Value::InlineTable(table)
+        }
+    };
+    let key = Key::new("port").with_leaf_decor(decor);
+    daemon.entry_format(&key).or_insert(Item::Value(port));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_deprecated_port() {
+        let content = r#"[daemons.api]
+run = "cargo run"
+# the dev server port
+expected_port = [3000]
+auto_bump_port = true
+port_bump_attempts = 5
+
+[daemons.web]
+run = "npm run dev"
+expected_port = [5173, 5174]
+
+[daemons.docs]
+run = "mkdocs serve"
+port = 8000
+expected_port = [8080]
+"#;
+        assert_eq!(
+            fix(content).unwrap(),
+            r#"[daemons.api]
+run = "cargo run"
+# the dev server port
+port = { expect = [3000], bump = 5 }
+
+[daemons.web]
+run = "npm run dev"
+port = [5173, 5174]
+
+[daemons.docs]
+run = "mkdocs serve"
+port = 8000
+"#
+        );
+    }
+
+    #[test]
+    fn test_fix_leaves_valid_config_alone() {
+        let content = "[daemons]\n# api\napi = { run = \"cargo run\", port = 3000 }\n";
+        assert_eq!(fix(content).unwrap(), content);
+    }
+}
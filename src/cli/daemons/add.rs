@@ -9,6 +9,9 @@ use crate::pitchfork_toml::{
 use crate::settings::settings;
 use indexmap::IndexMap;
 use miette::{IntoDiagnostic, bail};
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
 
 /// Add a new daemon to pitchfork.toml
 #[derive(Debug, clap::Args)]
@@ -43,11 +46,14 @@ Examples:
                                   Add to ~/.config/pitchfork/config.toml instead
   pitchfork daemons add worker --run './worker' --cron-schedule '0 * * * *' --cron-immediate
                                   Add cron daemon that triggers immediately
+  echo '[{\"id\":\"api\",\"run\":\"npm start\"}]' | pitchfork daemons add --stdin-json
+                                  Add daemons from a JSON array on stdin
 "
 )]
 pub struct Add {
     /// ID of the daemon to add (e.g., "api" or "namespace/api")
-    pub id: String,
+    #[clap(required_unless_present = "stdin_json")]
+    pub id: Option<String>,
     /// Command to run (can also use positional args)
     #[clap(long)]
     run: Option<String>,
@@ -66,9 +72,10 @@ pub struct Add {
     /// Environment variables in KEY=value format (can be specified multiple times)
     #[clap(long = "env")]
     env: Vec<String>,
-    /// Delay in seconds before considering daemon ready
-    #[clap(long)]
-    ready_delay: Option<u64>,
+    /// Delay before considering daemon ready, in seconds or as a duration
+    /// like "1500ms"
+    #[clap(long, value_parser = crate::pitchfork_toml::parse_duration)]
+    ready_delay: Option<std::time::Duration>,
     /// Regex pattern to match in output for readiness
     #[clap(long)]
     ready_output: Option<String>,
@@ -114,6 +121,12 @@ pub struct Add {
     /// Command to run on any daemon termination (clean exit, crash, or stop)
     #[clap(long)]
     on_exit: Option<String>,
+    /// Command to run when the daemon is killed by the kernel OOM killer
+    #[clap(long)]
+    on_oom: Option<String>,
+    /// Command to run when a resource alert threshold is breached
+    #[clap(long)]
+    on_alert: Option<String>,
     /// Cron schedule expression (6 fields: second minute hour day month weekday)
     #[clap(long)]
     cron_schedule: Option<String>,
@@ -123,6 +136,9 @@ pub struct Add {
     /// Trigger cron immediately on first check (default: deferred until next scheduled time)
     #[clap(long)]
     cron_immediate: bool,
+    /// Never run the cron schedule concurrently with other supervisors sharing the directory
+    #[clap(long)]
+    cron_exclusive: bool,
     /// Write to pitchfork.local.toml instead of pitchfork.toml
     #[clap(long)]
     local: bool,
@@ -132,6 +148,17 @@ pub struct Add {
     /// Write to the user-level global config (~/.config/pitchfork/config.toml)
     #[clap(long)]
     global: bool,
+    /// Read daemons from stdin as JSON instead of flags
+    ///
+    /// Accepts a single object or an array of objects. Each object needs an
+    /// `id` and takes the same fields as a `[daemons.<name>]` table in
+    /// pitchfork.toml, e.g. `{"id": "api", "run": "npm start", "retry": 3}`.
+    /// Nothing is written unless every daemon is valid.
+    #[clap(long, conflicts_with_all = ["id", "run", "args"])]
+    stdin_json: bool,
+    /// Replace daemons that already exist in the config file
+    #[clap(long)]
+    force: bool,
 }
 
 impl Add {
@@ -158,6 +185,48 @@ impl Add {
         };
         pt.path = Some(config_path.clone());
 
+        let canonical_path = tokio::fs::canonicalize(&config_path)
+            .await
+            .unwrap_or_else(|_| config_path.clone());
+        let daemons = if self.stdin_json {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .into_diagnostic()?;
+            Self::daemons_from_json(&input, &canonical_path)?
+        } else {
+            vec![self.daemon_from_flags(&canonical_path)?]
+        };
+
+        let mut seen = HashSet::new();
+        for (daemon_id, _) in &daemons {
+            if !seen.insert(daemon_id) {
+                bail!("daemon {daemon_id} is listed more than once");
+            }
+            if !self.force && pt.daemons.contains_key(daemon_id) {
+                bail!(
+                    "daemon {daemon_id} already exists in {}\nUse --force to replace it",
+                    config_path.display()
+                );
+            }
+        }
+        let added: Vec<DaemonId> = daemons.iter().map(|(id, _)| id.clone()).collect();
+        for (daemon_id, daemon) in daemons {
+            pt.daemons.insert(daemon_id, daemon);
+        }
+        tokio::task::spawn_blocking(move || pt.write())
+            .await
+            .into_diagnostic()?
+            .map_err(|e| miette::miette!("{e}"))?;
+        let path_display = config_path.display();
+        for daemon_id in added {
+            println!("added {daemon_id} to {path_display}");
+        }
+        Ok(())
+    }
+
+    /// Build the daemon described by the command-line flags
+    fn daemon_from_flags(&self, canonical_path: &Path) -> Result<(DaemonId, PitchforkTomlDaemon)> {
         let run_cmd = if let Some(ref run) = self.run {
             run.clone()
         } else if !self.args.is_empty() {
@@ -202,6 +271,8 @@ impl Add {
             || self.on_retry.is_some()
             || self.on_stop.is_some()
             || self.on_exit.is_some()
+            || self.on_oom.is_some()
+            || self.on_alert.is_some()
         {
             Some(PitchforkTomlHooks {
                 on_ready: self.on_ready.clone(),
@@ -209,7 +280,10 @@ impl Add {
                 on_retry: self.on_retry.clone(),
                 on_stop: self.on_stop.clone(),
                 on_exit: self.on_exit.clone(),
+                on_oom: self.on_oom.clone(),
+                on_alert: self.on_alert.clone(),
                 on_output: None,
+                timeout: None,
             })
         } else {
             None
@@ -226,6 +300,7 @@ impl Add {
                 schedule: schedule.clone(),
                 retrigger,
                 immediate: self.cron_immediate,
+                exclusive: self.cron_exclusive,
             })
         } else {
             None
@@ -233,64 +308,113 @@ impl Add {
 
         let boot_start = if self.boot_start { Some(true) } else { None };
 
-        let canonical_path = tokio::fs::canonicalize(&config_path)
-            .await
-            .unwrap_or_else(|_| config_path.clone());
-        let daemon_id = if self.id.contains('/') {
-            DaemonId::parse(&self.id)?
+        let id = self.id.as_deref().unwrap_or_default();
+        let daemon_id = if id.contains('/') {
+            DaemonId::parse(id)?
         } else {
-            let namespace = namespace_from_path(&canonical_path)?;
-            DaemonId::try_new(&namespace, &self.id)?
+            let namespace = namespace_from_path(canonical_path)?;
+            DaemonId::try_new(&namespace, id)?
         };
-        pt.daemons.insert(
-            daemon_id.clone(),
-            PitchforkTomlDaemon {
-                run: run_cmd,
-                auto,
-                cron,
-                retry,
-                ready_delay: self.ready_delay,
-                ready_output: self.ready_output.clone().map(ReadyOutput::new),
-                ready_http: self.ready_http.clone().map(ReadyHttp::new),
-                ready_port: self.ready_port.clone(),
-                ready_cmd: self.ready_cmd.clone().map(ReadyCmd::new),
-                port: {
-                    let expect = self.expected_port.clone();
-                    let bump = match self.bump {
-                        None => PortBump(0),
-                        Some(None) => PortBump(settings().default_port_bump_attempts()),
-                        Some(Some(n)) => PortBump(n),
+        let daemon = PitchforkTomlDaemon {
+            run: run_cmd,
+            auto,
+            cron,
+            retry,
+            ready_delay: self.ready_delay,
+            ready_output: self.ready_output.clone().map(ReadyOutput::new),
+            ready_http: self.ready_http.clone().map(ReadyHttp::new),
+            ready_port: self.ready_port.clone(),
+            ready_cmd: self.ready_cmd.clone().map(ReadyCmd::new),
+            port: {
+                let expect = self.expected_port.clone();
+                let bump = match self.bump {
+                    None => PortBump(0),
+                    Some(None) => PortBump(settings().default_port_bump_attempts()),
+                    Some(Some(n)) => PortBump(n),
+                };
+                PortConfig::from_parts(expect, bump)
+            },
+            boot_start,
+            depends: {
+                let namespace = daemon_id.namespace().to_string();
+                let mut deps = Vec::new();
+                for dep in &self.depends {
+                    let dep_id = if dep.contains('/') {
+                        DaemonId::parse(dep)?
+                    } else {
+                        DaemonId::try_new(&namespace, dep)?
                     };
-                    PortConfig::from_parts(expect, bump)
-                },
-                boot_start,
-                depends: {
-                    let namespace = daemon_id.namespace().to_string();
-                    let mut deps = Vec::new();
-                    for dep in &self.depends {
-                        let dep_id = if dep.contains('/') {
-                            DaemonId::parse(dep)?
-                        } else {
-                            DaemonId::try_new(&namespace, dep)?
-                        };
-                        deps.push(dep_id);
-                    }
-                    deps
-                },
-                watch: self.watch.clone(),
-                dir: self.dir.clone(),
-                env,
-                hooks,
-                ..PitchforkTomlDaemon::default()
+                    deps.push(dep_id);
+                }
+                deps
             },
-        );
-        tokio::task::spawn_blocking(move || pt.write())
-            .await
-            .into_diagnostic()?
-            .map_err(|e| miette::miette!("{e}"))?;
-        let path_display = config_path.display();
-        println!("added {daemon_id} to {path_display}");
-        Ok(())
+            watch: self.watch.clone(),
+            dir: self.dir.clone(),
+            env,
+            hooks,
+            ..PitchforkTomlDaemon::default()
+        };
+        Ok((daemon_id, daemon))
+    }
+
+    /// Parse daemons from a JSON object or array of objects.
+    ///
+    /// Each object is converted to a `[daemons.<name>]` table and parsed like
+    /// pitchfork.toml so the same field names and validation apply.
+    fn daemons_from_json(
+        input: &str,
+        canonical_path: &Path,
+    ) -> Result<Vec<(DaemonId, PitchforkTomlDaemon)>> {
+        let value: serde_json::Value = serde_json::from_str(input)
+            .map_err(|e| miette::miette!("Invalid JSON on stdin: {e}"))?;
+        let objects = match value {
+            serde_json::Value::Array(items) => items,
+            obj @ serde_json::Value::Object(_) => vec![obj],
+            _ => bail!("Expected a JSON object or array of objects on stdin"),
+        };
+        if objects.is_empty() {
+            bail!("No daemons found on stdin");
+        }
+
+        let mut daemons = Vec::new();
+        for obj in objects {
+            let serde_json::Value::Object(mut fields) = obj else {
+                bail!("Expected each daemon to be a JSON object");
+            };
+            let id = match fields.remove("id") {
+                Some(serde_json::Value::String(id)) => id,
+                _ => bail!("Each daemon needs a string \"id\" field"),
+            };
+            let (daemon_id, name) = if id.contains('/') {
+                let daemon_id = DaemonId::parse(&id)?;
+                let name = daemon_id.name().to_string();
+                (Some(daemon_id), name)
+            } else {
+                (None, id.clone())
+            };
+
+            let table: toml::Table = toml::Value::try_from(fields)
+                .ok()
+                .and_then(|v| v.as_table().cloned())
+                .ok_or_else(|| {
+                    miette::miette!("daemon {id} has fields that cannot be stored in TOML")
+                })?;
+            let mut daemons_table = toml::Table::new();
+            daemons_table.insert(name, toml::Value::Table(table));
+            let mut doc = toml::Table::new();
+            doc.insert("daemons".to_string(), toml::Value::Table(daemons_table));
+            let content = toml::to_string(&doc).into_diagnostic()?;
+
+            let parsed = PitchforkToml::parse_str(&content, canonical_path)
+                .map_err(|e| miette::miette!("daemon {id}: {e}"))?;
+            let (parsed_id, daemon) = parsed
+                .daemons
+                .into_iter()
+                .next()
+                .ok_or_else(|| miette::miette!("daemon {id} could not be parsed"))?;
+            daemons.push((daemon_id.unwrap_or(parsed_id), daemon));
+        }
+        Ok(daemons)
     }
 
     fn parse_retry(s: &str) -> Result<Retry> {
@@ -373,6 +497,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_daemons_from_json() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("myproj").join("pitchfork.toml");
+        let input = r#"[
+            {"id": "api", "run": "npm start", "retry": 3, "depends": ["db"]},
+            {"id": "db", "run": "postgres", "ready_port": 5432}
+        ]"#;
+        let daemons = Add::daemons_from_json(input, &path).unwrap();
+        assert_eq!(daemons.len(), 2);
+        let (api_id, api) = &daemons[0];
+        assert_eq!(api_id.name(), "api");
+        assert_eq!(api.run, "npm start");
+        assert_eq!(api.retry.count(), 3);
+        assert_eq!(api.depends[0].name(), "db");
+        assert_eq!(daemons[1].0.name(), "db");
+    }
+
+    #[test]
+    fn test_daemons_from_json_single_object() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pitchfork.toml");
+        let daemons =
+            Add::daemons_from_json(r#"{"id": "other/api", "run": "npm start"}"#, &path).unwrap();
+        assert_eq!(daemons[0].0.qualified(), "other/api");
+    }
+
+    #[test]
+    fn test_daemons_from_json_invalid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pitchfork.toml");
+        assert!(Add::daemons_from_json("[]", &path).is_err());
+        assert!(Add::daemons_from_json(r#"{"run": "npm start"}"#, &path).is_err());
+        assert!(Add::daemons_from_json(r#"{"id": "api"}"#, &path).is_err());
+        assert!(Add::daemons_from_json(r#"{"id": "api", "run": 1}"#, &path).is_err());
+        assert!(Add::daemons_from_json("\"api\"", &path).is_err());
+    }
+
     #[test]
     fn test_parse_cron_retrigger_invalid() {
         assert!(Add::parse_cron_retrigger("invalid").is_err());
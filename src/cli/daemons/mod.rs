@@ -173,6 +173,8 @@ impl Daemons {
                         .map(|(id, daemon)| JsonDaemonConfigEntry {
                             id: id.qualified(),
                             run: daemon.run.clone(),
+                            path: daemon.path.as_ref().map(|p| p.display().to_string()),
+                            overrides: daemon.overrides.as_ref().map(|p| p.display().to_string()),
                         })
                         .collect();
                     print_json(&entries)
@@ -181,7 +183,12 @@ impl Daemons {
                     Ok(())
                 } else {
                     for (id, daemon) in &config.daemons {
-                        println!("{id}\t{}", daemon.run);
+                        match daemon.path.as_ref().filter(|_| daemon.overrides.is_some()) {
+                            Some(path) => {
+                                println!("{id}\t{}\t(overridden in {})", daemon.run, path.display())
+                            }
+                            None => println!("{id}\t{}", daemon.run),
+                        }
                     }
                     Ok(())
                 }
@@ -0,0 +1,96 @@
+use crate::Result;
+use crate::cli::supervisor::{KillOrStopOutcome, resolve_existing_supervisor};
+use crate::fault::{self, FaultConfig};
+use crate::supervisor::SUPERVISOR;
+use miette::ensure;
+use std::time::Duration;
+
+/// Run the supervisor in the foreground with fault injection
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Run the supervisor in the foreground with fault injection
+
+Randomly delays IPC responses, fails state file writes and kills running
+daemons, to check how the supervisor and CLI cope with partial failures.
+Faults come from a seeded generator: the seed is logged on startup, and
+passing it back with --seed replays the same sequence of faults.
+
+The same faults can be enabled for a normally started supervisor with
+PITCHFORK_FAULT_INJECT, e.g. \"seed=42,ipc_delay=0.2:2s,state_write=0.1,kill=0.05\".
+
+Examples:
+  pitchfork debug chaos --kill 0.05
+                                  Kill a daemon on ~5% of refreshes
+  pitchfork debug chaos --ipc-delay 0.5 --ipc-delay-max 3s
+                                  Delay half of IPC responses by up to 3s
+  pitchfork debug chaos --seed 42 --state-write 0.1 --force
+                                  Replace the running supervisor, replay seed 42"
+)]
+pub struct Chaos {
+    /// Seed for the fault generator (random by default)
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Probability (0-1) of delaying each IPC response
+    #[clap(long, value_name = "P", default_value = "0", value_parser = probability)]
+    ipc_delay: f64,
+    /// Longest injected IPC delay
+    #[clap(long, value_name = "DURATION", default_value = "1s", value_parser = humantime::parse_duration)]
+    ipc_delay_max: Duration,
+    /// Probability (0-1) of failing each state file write
+    #[clap(long, value_name = "P", default_value = "0", value_parser = probability)]
+    state_write: f64,
+    /// Probability (0-1) of killing a running daemon on each refresh
+    #[clap(long, value_name = "P", default_value = "0", value_parser = probability)]
+    kill: f64,
+    /// kill existing supervisor
+    #[clap(short, long)]
+    force: bool,
+}
+
+fn probability(s: &str) -> std::result::Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => Err(format!("expected a probability between 0 and 1, got {s:?}")),
+    }
+}
+
+impl Chaos {
+    pub async fn run(&self) -> Result<()> {
+        ensure!(
+            self.ipc_delay > 0.0 || self.state_write > 0.0 || self.kill > 0.0,
+            "no faults enabled; pass at least one of --ipc-delay, --state-write or --kill"
+        );
+
+        let (existing_pid, outcome) = resolve_existing_supervisor(self.force).await?;
+        match outcome {
+            KillOrStopOutcome::StillRunning => {
+                let pid = existing_pid.expect("StillRunning implies a pid exists");
+                warn!(
+                    "Pitchfork supervisor is already running with pid {pid}. Use `--force` to replace it."
+                );
+                return Ok(());
+            }
+            KillOrStopOutcome::Killed => {
+                let pid = existing_pid.expect("Killed implies a pid exists");
+                info!("Killed existing supervisor with pid {pid}");
+            }
+            KillOrStopOutcome::AlreadyDead => {}
+        }
+
+        let mut config = FaultConfig {
+            ipc_delay: self.ipc_delay,
+            ipc_delay_max: self.ipc_delay_max,
+            state_write: self.state_write,
+            kill: self.kill,
+            ..FaultConfig::default()
+        };
+        if let Some(seed) = self.seed {
+            config.seed = seed;
+        }
+        fault::enable(config);
+
+        SUPERVISOR.start(false, false, None, None).await
+    }
+}
@@ -0,0 +1,48 @@
+use crate::Result;
+use crate::ipc::batch::{StartOptions, update_job_with_result};
+use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::PitchforkToml;
+
+/// Restart a daemon in debug mode with its configured env right away
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Disable {
+    /// ID of the daemon to take out of debug mode
+    id: String,
+    /// Suppress startup log output
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl Disable {
+    pub async fn run(&self) -> Result<()> {
+        let id = PitchforkToml::resolve_id(&self.id)?;
+        let ipc = IpcClient::shared(true).await?;
+        let in_debug = ipc
+            .active_daemons()
+            .await?
+            .into_iter()
+            .any(|d| d.id == id && d.env_override_until.is_some() && d.status.is_up());
+        if !in_debug {
+            warn!("Debug logging is not enabled for {id}");
+            return Ok(());
+        }
+
+        let opts = StartOptions {
+            force: true,
+            quiet: self.quiet,
+            ..Default::default()
+        };
+        let result = ipc.start_daemons(std::slice::from_ref(&id), opts).await?;
+        for update in &result.pending_job_updates {
+            update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+        }
+        clx::progress::stop();
+        clx::progress::clear_jobs();
+
+        if result.any_failed {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Result;
+use crate::ipc::batch::{StartOptions, update_job_with_result};
+use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::PitchforkToml;
+use miette::bail;
+use std::time::Duration;
+
+/// Restart a daemon with its debug_env for a limited time
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Restart a daemon with its debug_env for a limited time
+
+The daemon is restarted with the variables from its `debug_env` table
+applied on top of `env`, e.g. RUST_LOG=debug or DEBUG=*. Once --duration
+has passed the supervisor restarts it again with its configured env.
+Restarting the daemon yourself, or `pitchfork debug disable`, ends debug
+logging early.
+
+Examples:
+  pitchfork debug enable api      Debug logging for the next 10 minutes
+  pitchfork debug enable api --duration 1h
+                                  Debug logging for the next hour"
+)]
+pub struct Enable {
+    /// ID of the daemon to restart with debug logging
+    id: String,
+    /// How long to keep debug logging on, in seconds or as a duration like "30m"
+    #[clap(long, default_value = "10m", value_parser = crate::pitchfork_toml::parse_duration)]
+    duration: Duration,
+    /// Suppress startup log output
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl Enable {
+    pub async fn run(&self) -> Result<()> {
+        let id = PitchforkToml::resolve_id(&self.id)?;
+        let config = PitchforkToml::all_merged_all_namespaces()?;
+        let Some(daemon) = config.daemons.get(&id) else {
+            bail!("daemon {id} is not defined in pitchfork.toml");
+        };
+        if daemon.debug_env.as_ref().is_none_or(|env| env.is_empty()) {
+            bail!("daemon {id} has no debug_env configured");
+        }
+        let Ok(duration) = chrono::Duration::from_std(self.duration) else {
+            bail!("--duration is too long");
+        };
+
+        let ipc = IpcClient::shared(true).await?;
+        let opts = StartOptions {
+            force: true,
+            quiet: self.quiet,
+            debug_until: Some(chrono::Local::now() + duration),
+            ..Default::default()
+        };
+        let result = ipc.start_daemons(std::slice::from_ref(&id), opts).await?;
+        for update in &result.pending_job_updates {
+            update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+        }
+        clx::progress::stop();
+        clx::progress::clear_jobs();
+
+        if result.any_failed {
+            std::process::exit(1);
+        }
+        if !self.quiet {
+            println!(
+                "Debug logging enabled for {id} for {}",
+                humantime::format_duration(self.duration)
+            );
+        }
+        Ok(())
+    }
+}
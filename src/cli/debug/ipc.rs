@@ -0,0 +1,108 @@
+use crate::Result;
+use crate::ipc::IpcLogEntry;
+use crate::ipc::client::IpcClient;
+use crate::ui::style::eyellow;
+use crate::ui::table::{Column, ColumnTable};
+use comfy_table::Cell;
+use std::time::Duration;
+
+/// Show the requests the supervisor received recently
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Show the requests the supervisor received recently
+
+Lists the last 200 requests from any pitchfork command, the TUI or the web
+UI, oldest first: when each arrived, what it was for, how long the
+supervisor took to answer and what it answered. Requests it is still
+handling are shown as pending, with how long they have been waiting, so a
+command that hangs shows what it is stuck on.
+
+Run the hanging command with --trace-ipc to see the same exchange from the
+client's side.
+
+Examples:
+  pitchfork debug ipc             List recent requests
+  pitchfork debug ipc --pending   Only requests still being handled
+  pitchfork debug ipc --json      Output as JSON"
+)]
+pub struct Ipc {
+    /// Only show requests still being handled
+    #[clap(long)]
+    pending: bool,
+    /// Output in JSON format
+    #[clap(long)]
+    json: bool,
+}
+
+impl Ipc {
+    pub async fn run(&self) -> Result<()> {
+        let ipc = IpcClient::connect(false).await?;
+        let entries: Vec<_> = ipc
+            .get_ipc_log()
+            .await?
+            .into_iter()
+            .filter(|e| !self.pending || e.response.is_none())
+            .collect();
+        if self.json {
+            return crate::cli::json_output::print_json(&entries);
+        }
+        if entries.is_empty() {
+            info!("no requests recorded");
+            return Ok(());
+        }
+
+        let columns = ["seq", "time", "request", "daemon", "took", "response"];
+        ColumnTable::new(COLUMNS, &[], &columns)?
+            .header(console::user_attended())
+            .print(entries)
+    }
+}
+
+const COLUMNS: &[Column<IpcLogEntry>] = &[
+    Column {
+        name: "seq",
+        header: "#",
+        cell: |e| Cell::new(e.seq),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "time",
+        header: "TIME",
+        cell: |e| Cell::new(e.at.format("%H:%M:%S%.3f").to_string()),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "request",
+        header: "REQUEST",
+        cell: |e| Cell::new(&e.request),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "daemon",
+        header: "DAEMON",
+        cell: |e| Cell::new(e.daemon.as_deref().unwrap_or("")),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "took",
+        header: "TOOK",
+        cell: |e| {
+            Cell::new(match e.elapsed_ms {
+                0 => "<1ms".to_string(),
+                ms => humantime::format_duration(Duration::from_millis(ms)).to_string(),
+            })
+        },
+        sort_key: |_| None,
+    },
+    Column {
+        name: "response",
+        header: "RESPONSE",
+        cell: |e| match &e.response {
+            Some(response) => Cell::new(response),
+            None => Cell::new(eyellow("pending")),
+        },
+        sort_key: |_| None,
+    },
+];
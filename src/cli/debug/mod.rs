@@ -0,0 +1,38 @@
+use crate::Result;
+
+mod chaos;
+mod disable;
+mod enable;
+mod ipc;
+mod state;
+
+/// Debug daemons and pitchfork itself
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Debug {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Commands {
+    #[clap(hide = true)]
+    Chaos(chaos::Chaos),
+    Disable(disable::Disable),
+    Enable(enable::Enable),
+    Ipc(ipc::Ipc),
+    #[clap(hide = true)]
+    State(state::State),
+}
+
+impl Debug {
+    pub async fn run(self) -> Result<()> {
+        match self.command {
+            Commands::Chaos(chaos) => chaos.run().await,
+            Commands::Disable(disable) => disable.run().await,
+            Commands::Enable(enable) => enable.run().await,
+            Commands::Ipc(ipc) => ipc.run().await,
+            Commands::State(state) => state.run().await,
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::Result;
+use crate::daemon::Daemon;
+use crate::daemon_id::DaemonId;
+use crate::daemon_status::DaemonStatus;
+use crate::env;
+use crate::procs::PROCS;
+use crate::state_file::StateFile;
+use miette::{IntoDiagnostic, bail};
+use std::fmt::Display;
+use std::path::Path;
+
+/// Dump the state file and check it for inconsistencies
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Dump the state file and check it for inconsistencies
+
+Prints the parsed state file, then checks every daemon entry against the
+processes actually running on this machine:
+
+  - active (running, waiting or stopping) with no PID
+  - active, but the PID is gone or now belongs to a different process
+  - stopped or errored, but a PID is still recorded
+
+If the file doesn't parse, each daemon entry is parsed on its own to show
+which ones are broken.
+
+With --repair, stale PIDs are cleared and daemons whose process is gone are
+marked as errored. Entries that don't parse are dropped, after the original
+file is copied to state.toml.bak. A stopped daemon whose process is still
+alive is only reported, since clearing its PID would orphan the process.
+
+Exits non-zero if problems remain, so the output can be attached to an issue
+as-is.
+
+Examples:
+  pitchfork debug state           Show the state file and any problems
+  pitchfork debug state --repair  Fix the problems found"
+)]
+pub struct State {
+    /// Fix the problems found
+    #[clap(long)]
+    repair: bool,
+    /// Repair even if the supervisor is running
+    #[clap(short, long)]
+    force: bool,
+}
+
+impl State {
+    pub async fn run(&self) -> Result<()> {
+        let path = &*env::PITCHFORK_STATE_FILE;
+        println!("State file: {}", path.display());
+        if !path.exists() {
+            println!("(does not exist)");
+            return Ok(());
+        }
+
+        let mut state = match StateFile::read(path) {
+            Ok(state) => state,
+            Err(e) => {
+                println!("Failed to parse: {e}");
+                return self.repair_unparseable(path);
+            }
+        };
+        println!();
+        println!(
+            "{}",
+            toml::to_string_pretty(&state).unwrap_or_else(|e| format!("(failed to print: {e})"))
+        );
+
+        let issues = state
+            .daemons
+            .values()
+            .filter_map(|daemon| Some((daemon.id.clone(), check(daemon, probe)?)))
+            .collect::<Vec<_>>();
+        if issues.is_empty() {
+            println!("No problems found");
+            return Ok(());
+        }
+        println!("Problems:");
+        for (id, issue) in &issues {
+            println!("  {id}: {issue}");
+        }
+        if !self.repair {
+            bail!(
+                "found {} problem(s); run `pitchfork debug state --repair` to fix them",
+                issues.len()
+            );
+        }
+
+        self.ensure_supervisor_stopped(
+            state
+                .daemons
+                .get(&DaemonId::pitchfork())
+                .and_then(|d| d.local_pid()),
+        )?;
+        let mut unrepaired = 0;
+        for (id, issue) in &issues {
+            let Some(daemon) = state.daemons.get_mut(id) else {
+                continue;
+            };
+            if issue.repair(daemon) {
+                println!("Repaired {id}");
+            } else {
+                unrepaired += 1;
+            }
+        }
+        state.write()?;
+        if unrepaired > 0 {
+            bail!("{unrepaired} problem(s) need to be fixed by hand");
+        }
+        Ok(())
+    }
+
+    /// Report which daemon entries fail to parse and, with --repair, drop
+    /// them after backing up the file.
+    fn repair_unparseable(&self, path: &Path) -> Result<()> {
+        let raw = xx::file::read_to_string(path)?;
+        let mut doc = match toml::from_str::<toml::Table>(&raw) {
+            Ok(doc) => doc,
+            Err(e) => bail!("state file is not valid TOML: {e}"),
+        };
+        let broken = match doc.get("daemons") {
+            Some(toml::Value::Table(daemons)) => daemons
+                .iter()
+                .filter_map(|(id, value)| {
+                    let err = match DaemonId::parse(id) {
+                        Err(e) => e.to_string(),
+                        Ok(_) => value.clone().try_into::<Daemon>().err()?.to_string(),
+                    };
+                    Some((id.clone(), err))
+                })
+                .collect::<Vec<_>>(),
+            _ => vec![],
+        };
+        if broken.is_empty() {
+            bail!("no individual daemon entry is broken; the file needs to be fixed by hand");
+        }
+        println!("Broken daemon entries:");
+        for (id, err) in &broken {
+            println!("  {id}: {}", err.trim());
+        }
+        if !self.repair {
+            bail!(
+                "found {} broken daemon entries; run `pitchfork debug state --repair` to drop them",
+                broken.len()
+            );
+        }
+        let supervisor_pid = doc
+            .get("daemons")
+            .and_then(|d| d.get(DaemonId::pitchfork().qualified()))
+            .and_then(|d| d.get("pid"))
+            .and_then(|pid| pid.as_integer())
+            .and_then(|pid| u32::try_from(pid).ok());
+        self.ensure_supervisor_stopped(supervisor_pid)?;
+
+        let backup = path.with_extension("toml.bak");
+        std::fs::copy(path, &backup).into_diagnostic()?;
+        if let Some(toml::Value::Table(daemons)) = doc.get_mut("daemons") {
+            for (id, _) in &broken {
+                daemons.remove(id);
+            }
+        }
+        let repaired = toml::to_string(&doc).into_diagnostic()?;
+        xx::file::write(path, repaired)?;
+        StateFile::read(path)?;
+        println!(
+            "Dropped {} daemon entries; the original is at {}",
+            broken.len(),
+            backup.display()
+        );
+        Ok(())
+    }
+
+    fn ensure_supervisor_stopped(&self, supervisor_pid: Option<u32>) -> Result<()> {
+        if let Some(pid) = supervisor_pid
+            && PROCS.is_running(pid)
+            && !self.force
+        {
+            bail!(
+                "the supervisor is running (pid {pid}) and would overwrite the repairs; \
+                 stop it with `pitchfork supervisor stop`, or pass --force"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// What a recorded PID refers to now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Process {
+    Alive,
+    Gone,
+    /// Alive, but with a different start time than the one recorded
+    Reused,
+}
+
+fn probe(pid: u32, start_time: Option<u64>) -> Process {
+    if !PROCS.is_running(pid) {
+        return Process::Gone;
+    }
+    match (start_time, PROCS.start_time(pid)) {
+        (Some(expected), Some(actual)) if expected != actual => Process::Reused,
+        _ => Process::Alive,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Issue {
+    ActiveWithoutPid,
+    ProcessGone(u32),
+    PidReused(u32),
+    InactiveWithPid(u32),
+    InactiveButAlive(u32),
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::ActiveWithoutPid => write!(f, "marked active but has no pid"),
+            Issue::ProcessGone(pid) => write!(f, "marked active but pid {pid} is not running"),
+            Issue::PidReused(pid) => write!(f, "pid {pid} now belongs to a different process"),
+            Issue::InactiveWithPid(pid) => write!(f, "not active but still records pid {pid}"),
+            Issue::InactiveButAlive(pid) => write!(
+                f,
+                "not active but pid {pid} is still running; stop it with `kill {pid}`"
+            ),
+        }
+    }
+}
+
+impl Issue {
+    /// Fix the entry; returns false if this can't be fixed automatically.
+    fn repair(self, daemon: &mut Daemon) -> bool {
+        match self {
+            Issue::InactiveButAlive(_) => return false,
+            Issue::InactiveWithPid(_) => {}
+            Issue::ActiveWithoutPid | Issue::ProcessGone(_) | Issue::PidReused(_) => {
+                daemon.status = match daemon.status {
+                    DaemonStatus::Stopping => DaemonStatus::Stopped,
+                    _ => DaemonStatus::Errored(-1),
+                };
+            }
+        }
+        daemon.pid = None;
+        daemon.start_time = None;
+        true
+    }
+}
+
+/// Check a daemon entry against `probe`. PIDs recorded on another host can't
+/// be checked and are skipped.
+fn check(daemon: &Daemon, probe: impl Fn(u32, Option<u64>) -> Process) -> Option<Issue> {
+    if daemon.remote_host().is_some() {
+        return None;
+    }
+    let active = daemon.status.is_up()
+        || matches!(
+            daemon.status,
+            DaemonStatus::Waiting | DaemonStatus::Stopping
+        );
+    let Some(pid) = daemon.pid else {
+        // A daemon waiting on `requires` has not been spawned yet
+        let spawned = daemon.waiting_for.is_none();
+        return (active && spawned).then_some(Issue::ActiveWithoutPid);
+    };
+    match (active, probe(pid, daemon.start_time)) {
+        (true, Process::Alive) => None,
+        (true, Process::Gone) => Some(Issue::ProcessGone(pid)),
+        (true, Process::Reused) => Some(Issue::PidReused(pid)),
+        (false, Process::Alive) => Some(Issue::InactiveButAlive(pid)),
+        (false, Process::Gone | Process::Reused) => Some(Issue::InactiveWithPid(pid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon(status: DaemonStatus, pid: Option<u32>) -> Daemon {
+        Daemon {
+            id: DaemonId::new("proj", "api"),
+            status,
+            pid,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let alive = |_, _| Process::Alive;
+        let gone = |_, _| Process::Gone;
+        let reused = |_, _| Process::Reused;
+        assert_eq!(check(&daemon(DaemonStatus::Running, Some(1)), alive), None);
+        assert_eq!(check(&daemon(DaemonStatus::Stopped, None), gone), None);
+        let waiting = Daemon {
+            waiting_for: Some("tcp localhost:5432".into()),
+            ..daemon(DaemonStatus::Waiting, None)
+        };
+        assert_eq!(check(&waiting, gone), None);
+        assert_eq!(
+            check(&daemon(DaemonStatus::Running, None), alive),
+            Some(Issue::ActiveWithoutPid)
+        );
+        assert_eq!(
+            check(&daemon(DaemonStatus::Waiting, Some(1)), gone),
+            Some(Issue::ProcessGone(1))
+        );
+        assert_eq!(
+            check(&daemon(DaemonStatus::Running, Some(1)), reused),
+            Some(Issue::PidReused(1))
+        );
+        assert_eq!(
+            check(&daemon(DaemonStatus::Errored(1), Some(1)), gone),
+            Some(Issue::InactiveWithPid(1))
+        );
+        assert_eq!(
+            check(&daemon(DaemonStatus::Stopped, Some(1)), alive),
+            Some(Issue::InactiveButAlive(1))
+        );
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut d = daemon(DaemonStatus::Running, Some(1));
+        assert!(Issue::ProcessGone(1).repair(&mut d));
+        assert!(matches!(d.status, DaemonStatus::Errored(-1)));
+        assert_eq!(d.pid, None);
+
+        let mut d = daemon(DaemonStatus::Stopping, None);
+        assert!(Issue::ActiveWithoutPid.repair(&mut d));
+        assert!(d.status.is_stopped());
+
+        let mut d = daemon(DaemonStatus::Stopped, Some(1));
+        assert!(!Issue::InactiveButAlive(1).repair(&mut d));
+        assert_eq!(d.pid, Some(1));
+    }
+}
@@ -14,21 +14,33 @@ Disables a daemon to prevent it from being started automatically or manually.
 The daemon will remain disabled until 'pitchfork enable' is called.
 Useful for temporarily stopping a service without removing it from config.
 
+With --auto, only automatic starts are blocked: autostart when entering the
+project directory, cron triggers and boot start. 'pitchfork start' still
+works, as do retries and watch restarts of a daemon you started.
+
 Examples:
   pitchfork disable api           Prevent daemon from starting
+  pitchfork disable api --auto    Only start api when asked to
   pitchfork d api                 Alias for 'disable'
   pitchfork list                  Shows 'disabled' status in output"
 )]
 pub struct Disable {
     /// Name of the daemon to disable
     id: String,
+    /// Only block automatic starts (autostart, cron, boot start)
+    #[clap(long)]
+    auto: bool,
 }
 
 impl Disable {
     pub async fn run(&self) -> Result<()> {
         let id = PitchforkToml::resolve_id(&self.id)?;
-        let ipc = IpcClient::connect(false).await?;
-        ipc.disable(id).await?;
+        let ipc = IpcClient::shared(false).await?;
+        if self.auto {
+            ipc.disable_autostart(id).await?;
+        } else {
+            ipc.disable(id).await?;
+        }
         Ok(())
     }
 }
@@ -11,7 +11,8 @@ use crate::pitchfork_toml::PitchforkToml;
 Allow a daemon to start
 
 Re-enables a previously disabled daemon, allowing it to be started manually
-or automatically. Use this after 'pitchfork disable' to restore normal operation.
+or automatically. Use this after 'pitchfork disable' or 'pitchfork disable --auto'
+to restore normal operation.
 
 Examples:
   pitchfork enable api            Enable a disabled daemon
@@ -25,7 +26,7 @@ pub struct Enable {
 impl Enable {
     pub async fn run(&self) -> Result<()> {
         let id = PitchforkToml::resolve_id(&self.id)?;
-        let ipc = IpcClient::connect(false).await?;
+        let ipc = IpcClient::shared(false).await?;
         ipc.enable(id).await?;
         Ok(())
     }
@@ -0,0 +1,292 @@
+use crate::Result;
+use crate::pitchfork_toml::PitchforkToml;
+use crate::settings::{SETTINGS_META, SettingMeta};
+use miette::bail;
+use schemars::schema_for;
+use serde_json::Value;
+
+/// Show the reference for a daemon field or setting
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Show the reference for a daemon field or setting
+
+Prints the documentation for a field of [daemons.<name>] in pitchfork.toml
+or a pitchfork setting: its type, default, environment variable and
+description. It is built into the binary, so it works offline and always
+matches the installed version.
+
+Without a topic, lists every daemon field and setting with a one-line
+summary. A settings group (e.g. `settings.logs`) lists the settings in
+that group.
+
+Examples:
+  pitchfork explain                         List all daemon fields and settings
+  pitchfork explain ready_http              Show the ready_http daemon field
+  pitchfork explain supervisor.stop_timeout Show a setting
+  pitchfork explain settings.logs           List the settings in [settings.logs]"
+)]
+pub struct Explain {
+    /// Daemon field (e.g. ready_http), setting (e.g. supervisor.stop_timeout) or settings group (e.g. settings.logs)
+    topic: Option<String>,
+}
+
+impl Explain {
+    pub async fn run(&self) -> Result<()> {
+        let reference = Reference::load();
+        let out = match &self.topic {
+            Some(topic) => reference.explain(topic)?,
+            None => reference.overview(),
+        };
+        print!("{out}");
+        Ok(())
+    }
+}
+
+/// Daemon fields from the pitchfork.toml JSON schema, whose descriptions come
+/// from the doc comments on `PitchforkTomlDaemon`.
+struct Reference {
+    schema: Value,
+}
+
+impl Reference {
+    fn load() -> Self {
+        let schema = serde_json::to_value(schema_for!(PitchforkToml)).unwrap_or_default();
+        Self { schema }
+    }
+
+    fn daemon_fields(&self) -> Vec<(&str, &Value)> {
+        self.def("PitchforkTomlDaemon")
+            .and_then(|daemon| daemon["properties"].as_object())
+            .map(|props| props.iter().map(|(k, v)| (k.as_str(), v)).collect())
+            .unwrap_or_default()
+    }
+
+    fn def(&self, name: &str) -> Option<&Value> {
+        self.schema["$defs"].get(name)
+    }
+
+    /// Follows a `$ref` to the definition it points at.
+    fn resolve<'a>(&'a self, value: &'a Value) -> &'a Value {
+        value["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/$defs/"))
+            .and_then(|name| self.def(name))
+            .unwrap_or(value)
+    }
+
+    /// A short type such as `string | table` for a schema node.
+    fn type_name(&self, value: &Value) -> String {
+        let value = self.resolve(value);
+        if let Some(variants) = value["anyOf"].as_array().or(value["oneOf"].as_array()) {
+            let mut names = vec![];
+            for name in variants.iter().map(|v| self.type_name(v)) {
+                // `null` variants of optional fields come out empty
+                if !name.is_empty() && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            return names.join(" | ");
+        }
+        let types = match &value["type"] {
+            Value::String(typ) => vec![typ.as_str()],
+            Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => return "any".to_string(),
+        };
+        types
+            .into_iter()
+            .filter(|typ| *typ != "null")
+            .map(|typ| match typ {
+                "array" => format!("[{}]", self.type_name(&value["items"])),
+                "object" => "table".to_string(),
+                typ => typ.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Keys accepted when a field is written as a table, e.g. `url` and
+    /// `status` for `ready_http = { url = "...", status = [200] }`.
+    fn sub_fields<'a>(&'a self, value: &'a Value) -> Vec<(&'a str, &'a Value)> {
+        let value = self.resolve(value);
+        if let Some(props) = value["properties"].as_object() {
+            return props.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        }
+        value["anyOf"]
+            .as_array()
+            .or(value["oneOf"].as_array())
+            .into_iter()
+            .flatten()
+            .map(|variant| self.sub_fields(variant))
+            .find(|fields| !fields.is_empty())
+            .unwrap_or_default()
+    }
+
+    fn overview(&self) -> String {
+        let mut out = String::from("Daemon fields ([daemons.<name>] in pitchfork.toml):\n");
+        for (name, field) in self.daemon_fields() {
+            out.push_str(&format!(
+                "  {name:<20} {}\n",
+                first_line(description(field))
+            ));
+        }
+        out.push_str("\nSettings ([settings] in pitchfork.toml, or environment variables):\n");
+        for (key, meta) in SETTINGS_META.iter() {
+            out.push_str(&format!("  {key:<36} {}\n", first_line(meta.description)));
+        }
+        out.push_str("\nRun 'pitchfork explain <topic>' for details.\n");
+        out
+    }
+
+    fn explain(&self, topic: &str) -> Result<String> {
+        let field_name = topic.strip_prefix("daemons.").unwrap_or(topic);
+        if let Some((name, field)) = self
+            .daemon_fields()
+            .into_iter()
+            .find(|(name, _)| *name == field_name)
+        {
+            return Ok(self.explain_field(name, field));
+        }
+
+        let key = topic.strip_prefix("settings.").unwrap_or(topic);
+        if let Some(meta) = SETTINGS_META.get(key) {
+            return Ok(explain_setting(key, meta));
+        }
+        let group = SETTINGS_META
+            .iter()
+            .filter(|(k, _)| k.starts_with(&format!("{key}.")))
+            .collect::<Vec<_>>();
+        if !group.is_empty() {
+            let mut out = format!("[settings.{key}]\n");
+            for (key, meta) in group {
+                out.push_str(&format!("  {key:<36} {}\n", first_line(meta.description)));
+            }
+            return Ok(out);
+        }
+
+        let mut suggestions = self
+            .daemon_fields()
+            .into_iter()
+            .map(|(name, _)| name)
+            .chain(SETTINGS_META.keys().copied())
+            .filter(|k| crate::cli::settings::levenshtein_distance(key, k) <= 3 || k.contains(key))
+            .collect::<Vec<_>>();
+        if suggestions.is_empty() {
+            bail!(
+                "nothing to explain for '{topic}'. Run 'pitchfork explain' to see all daemon fields and settings"
+            );
+        }
+        suggestions.sort();
+        bail!(
+            "nothing to explain for '{topic}'. Did you mean one of: {}?",
+            suggestions.join(", ")
+        )
+    }
+
+    fn explain_field(&self, name: &str, field: &Value) -> String {
+        let mut out = format!("{name} ({})\n", self.type_name(field));
+        out.push_str("  in [daemons.<name>]\n");
+        if let Some(default) = field.get("default").filter(|d| !is_empty_default(d)) {
+            out.push_str(&format!("  default: {default}\n"));
+        }
+        out.push_str(&format!("\n{}\n", description(field)));
+
+        // The description of the field's type, e.g. the forms `ReadyHttp` takes
+        let accepts = std::iter::once(field)
+            .chain(field["anyOf"].as_array().into_iter().flatten())
+            .map(|value| description(self.resolve(value)))
+            .find(|desc| !desc.is_empty() && *desc != description(field));
+        if let Some(accepts) = accepts {
+            out.push_str(&format!("\nAccepts: {accepts}\n"));
+        }
+
+        let sub_fields = self.sub_fields(field);
+        if !sub_fields.is_empty() {
+            out.push_str("\nFields:\n");
+            for (sub, value) in sub_fields {
+                out.push_str(&format!("  {sub} ({})\n", self.type_name(value)));
+                let desc = first_line(description(value));
+                if !desc.is_empty() {
+                    out.push_str(&format!("    {desc}\n"));
+                }
+            }
+        }
+        out
+    }
+}
+
+fn explain_setting(key: &str, meta: &SettingMeta) -> String {
+    let group = key.rsplit_once('.').map(|(group, _)| group).unwrap_or(key);
+    let mut out = format!("{key} ({})\n", meta.typ);
+    out.push_str(&format!("  in [settings.{group}]\n"));
+    if let Some(env) = meta.env_var {
+        out.push_str(&format!("  env: {env}\n"));
+    }
+    if let Some(default) = meta.default_value.filter(|d| !d.is_empty()) {
+        out.push_str(&format!("  default: {default}\n"));
+    }
+    out.push_str(&format!("\n{}\n", meta.description));
+    out
+}
+
+fn description(value: &Value) -> &str {
+    value["description"].as_str().unwrap_or_default()
+}
+
+fn first_line(desc: &str) -> &str {
+    desc.lines().next().unwrap_or_default()
+}
+
+fn is_empty_default(default: &Value) -> bool {
+    match default {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_daemon_field() {
+        let reference = Reference::load();
+        let out = reference.explain("ready_http").unwrap();
+        assert!(out.starts_with("ready_http (string | table)\n"));
+        assert!(out.contains("HTTP URL to poll for readiness"));
+        assert!(out.contains("\nFields:\n"));
+        assert!(out.contains("  url (string)"));
+        assert_eq!(reference.explain("daemons.ready_http").unwrap(), out);
+
+        let retry = reference.explain("retry").unwrap();
+        assert!(retry.starts_with("retry (boolean | integer)\n"));
+        assert!(retry.contains("  default: 0\n"));
+    }
+
+    #[test]
+    fn test_explain_setting() {
+        let reference = Reference::load();
+        let out = reference.explain("general.log_level").unwrap();
+        assert!(out.starts_with("general.log_level ("));
+        assert!(out.contains("  in [settings.general]\n"));
+        assert_eq!(
+            reference.explain("settings.general.log_level").unwrap(),
+            out
+        );
+
+        let group = reference.explain("general").unwrap();
+        assert!(group.starts_with("[settings.general]\n"));
+        assert!(group.contains("general.log_level"));
+    }
+
+    #[test]
+    fn test_explain_unknown_topic() {
+        let err = Reference::load().explain("ready_htp").unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("Did you mean one of: "));
+        assert!(err.contains("ready_http"));
+    }
+}
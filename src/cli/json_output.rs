@@ -1,3 +1,5 @@
+use crate::daemon::{ExitRecord, StartupRecord};
+use crate::log_store::LogStream;
 use serde::Serialize;
 
 #[derive(Serialize)]
@@ -8,12 +10,32 @@ pub struct JsonListEntry {
     pub pid: Option<u32>,
     pub status: String,
     pub disabled: bool,
+    /// Only automatic starts are disabled
+    pub auto_disabled: bool,
     pub available: bool,
     pub proxy_url: Option<String>,
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub active_port: Option<u16>,
     pub port: Vec<u16>,
+    /// Host the daemon is running on, when that isn't this machine
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// User who started the daemon on a shared supervisor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Resource `alerts` threshold the daemon is currently over
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<String>,
+    /// Why the running process no longer matches what's on disk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<String>,
+    /// Pending retry attempt and when it happens, e.g. `2/5 in 14s`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry: Option<String>,
+    /// The supervisor is not running, so `status` is the last recorded one
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
 }
 
 #[derive(Serialize)]
@@ -27,6 +49,61 @@ pub struct JsonStatusEntry {
     pub active_port: Option<u16>,
     pub port: Vec<u16>,
     pub proxy_url: Option<String>,
+    /// Host the daemon is running on, when that isn't this machine
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// User who started the daemon on a shared supervisor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Task the daemon runs with `mise run <task>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mise_task: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+    /// Humanized `uptime_secs`, e.g. `3m 12s`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cron_secs: Option<u64>,
+    /// Humanized `next_cron_secs`, e.g. `in 5m`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cron: Option<String>,
+    /// Output lines dropped by `log_filter` in the current run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_lines_filtered: Option<u64>,
+    /// Output lines dropped by `logs.rate_limit` in the current run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_lines_dropped: Option<u64>,
+    /// Service from `requires` the daemon is waiting on before it is spawned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waiting_for: Option<String>,
+    /// Seconds until the supervisor retries an errored daemon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry_secs: Option<u64>,
+    /// Pending retry attempt and when it happens, e.g. `2/5 in 14s`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry: Option<String>,
+    /// Resource `alerts` threshold the daemon is currently over
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<String>,
+    /// Why the running process no longer matches what's on disk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<String>,
+    /// Why `watch` directories are polled instead of watched natively
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch_fallback: Option<String>,
+    /// Most recent exits, newest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exits: Vec<ExitRecord>,
+    /// Latest ready latency with its p95, e.g. `ready in 4.2s (p95 6.1s)`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup: Option<String>,
+    /// 95th percentile spawn-to-ready time over `startups`, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ready_p95_ms: Option<u64>,
+    /// Timings of the most recent runs that became ready, newest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub startups: Vec<StartupRecord>,
 }
 
 #[derive(Serialize)]
@@ -40,6 +117,7 @@ pub struct JsonLogEntry {
     pub msg: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logger: Option<String>,
+    pub stream: LogStream,
     /// Parsed structured fields as a JSON object, or null if the line was
     /// not structured (plain text).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -50,6 +128,12 @@ pub struct JsonLogEntry {
 pub struct JsonDaemonConfigEntry {
     pub id: String,
     pub run: String,
+    /// Config file the daemon's effective definition comes from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Base config file a `pitchfork.local.toml` entry overrides field by field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -1,12 +1,16 @@
 use crate::Result;
 use crate::cli::json_output::{JsonListEntry, print_json};
-use crate::daemon_list::get_all_daemons;
+use crate::daemon_list::{DaemonListEntry, get_all_daemons, get_all_daemons_offline};
 use crate::daemon_status::DaemonStatus;
 use crate::ipc::client::IpcClient;
-use crate::pitchfork_toml::PitchforkToml;
-use crate::settings::settings;
-use crate::ui::table::print_table;
-use comfy_table::{Cell, Color, ContentArrangement, Table};
+use crate::pitchfork_toml::{PitchforkToml, SlugEntry};
+use crate::procs::PROCS;
+use crate::settings::{Settings, settings};
+use crate::ui::table::{Column, ColumnTable, SortKey};
+use crate::ui::time;
+use comfy_table::{Cell, Color};
+use indexmap::IndexMap;
+use std::path::PathBuf;
 
 /// Status values accepted by `list --status`.
 ///
@@ -20,9 +24,12 @@ enum StatusFilter {
     Running,
     Stopped,
     Waiting,
+    Starting,
+    Unhealthy,
     Stopping,
     Failed,
     Errored,
+    Completed,
     Available,
     Disabled,
 }
@@ -49,6 +56,16 @@ Example:
   pitchfork list --status running  Show only running daemons
   pitchfork ls --status available --status stopped
                                   Show daemons that are available OR stopped
+  pitchfork list --no-start       Never start the supervisor
+  pitchfork list --columns id,status,port,uptime --sort uptime
+                                  Pick the columns and sort by uptime
+
+Columns: id, status, pid, port, uptime, groups, source (the config file
+defining the daemon) and info (alerts, proxy URL, errors). The default
+is id,status,info.
+
+If the supervisor is not running, the last known state from the state
+file is shown, marked as stale, instead of starting the supervisor.
 
 Output:
   Name    Status
@@ -67,17 +84,37 @@ pub struct List {
 
     /// Filter daemons by status (repeatable for OR logic)
     ///
-    /// Values: running, stopped, waiting, stopping, failed, errored, available, disabled
+    /// Values: running, stopped, waiting, starting, unhealthy, stopping, failed, errored, completed, available, disabled
     #[clap(long, value_enum)]
     status: Vec<StatusFilter>,
+
+    /// Columns to show, comma-separated
+    ///
+    /// Values: id, status, pid, port, uptime, groups, source, info
+    #[clap(long, value_delimiter = ',', value_name = "COLUMN")]
+    columns: Vec<String>,
+
+    /// Sort the table by a column
+    #[clap(long, value_name = "COLUMN")]
+    sort: Option<String>,
+
+    /// Sort in descending order
+    #[clap(long, requires = "sort")]
+    reverse: bool,
+
+    /// Never start or wait for the supervisor; show the last known state if
+    /// it can't be reached
+    #[clap(long)]
+    no_start: bool,
 }
 
 impl List {
     pub async fn run(&self) -> Result<()> {
-        let client = IpcClient::connect(true).await?;
-
+        let (mut entries, stale) = match self.connect().await? {
+            Some(client) => (get_all_daemons(&client).await?, false),
+            None => (get_all_daemons_offline()?, true),
+        };
         let s = settings();
-        let mut entries = get_all_daemons(&client).await?;
         let global_slugs = PitchforkToml::read_global_slugs();
 
         if !self.status.is_empty() {
@@ -89,11 +126,20 @@ impl List {
                         !entry.is_available && matches!(entry.daemon.status, DaemonStatus::Running)
                     }
                     StatusFilter::Stopped => {
-                        !entry.is_available && matches!(entry.daemon.status, DaemonStatus::Stopped)
+                        !entry.is_available
+                            && matches!(entry.daemon.status, DaemonStatus::Stopped)
+                            && !entry.daemon.is_completed()
                     }
                     StatusFilter::Waiting => {
                         !entry.is_available && matches!(entry.daemon.status, DaemonStatus::Waiting)
                     }
+                    StatusFilter::Starting => {
+                        !entry.is_available && matches!(entry.daemon.status, DaemonStatus::Starting)
+                    }
+                    StatusFilter::Unhealthy => {
+                        !entry.is_available
+                            && matches!(entry.daemon.status, DaemonStatus::Unhealthy)
+                    }
                     StatusFilter::Stopping => {
                         !entry.is_available && matches!(entry.daemon.status, DaemonStatus::Stopping)
                     }
@@ -105,6 +151,7 @@ impl List {
                         !entry.is_available
                             && matches!(entry.daemon.status, DaemonStatus::Errored(_))
                     }
+                    StatusFilter::Completed => !entry.is_available && entry.daemon.is_completed(),
                 })
             });
         }
@@ -116,7 +163,7 @@ impl List {
                     let status_text = if entry.is_available {
                         "available".to_string()
                     } else {
-                        entry.daemon.status.to_string()
+                        entry.daemon.status_label()
                     };
                     let proxy_url = if s.proxy.enable
                         && (entry.daemon.active_port.is_some()
@@ -137,92 +184,291 @@ impl List {
                         pid: entry.daemon.pid,
                         status: status_text,
                         disabled: entry.is_disabled,
+                        auto_disabled: entry.is_auto_disabled,
                         available: entry.is_available,
                         proxy_url,
-                        error: entry.daemon.status.error_message(),
+                        error: entry.daemon.error_message(),
                         active_port: entry.daemon.active_port,
                         port: entry.daemon.resolved_port.clone(),
+                        host: entry.daemon.remote_host().map(str::to_string),
+                        owner: entry.daemon.owner.clone(),
+                        alert: entry.daemon.alert.clone(),
+                        drift: entry.daemon.drift.clone(),
+                        next_retry: entry.daemon.retry_summary(chrono::Local::now()),
+                        stale: stale && !entry.is_available,
                     }
                 })
                 .collect();
             return print_json(&json_entries);
         }
 
-        let mut table = Table::new();
-        table
-            .load_preset(comfy_table::presets::NOTHING)
-            .set_content_arrangement(ContentArrangement::Disabled);
-        if !self.hide_header && console::user_attended() {
-            table.set_header(vec!["Name", "Status", ""]);
-        }
+        let table = ColumnTable::new(COLUMNS, &self.columns, DEFAULT_COLUMNS)?
+            .sort_by(self.sort.as_deref(), self.reverse)?
+            .header(!self.hide_header && console::user_attended());
+
+        let uptimes = if table.uses("uptime") {
+            let pids: Vec<u32> = entries
+                .iter()
+                .filter(|e| e.daemon.status.is_up())
+                .filter_map(|e| e.daemon.local_pid())
+                .collect();
+            PROCS.refresh_and_get_batch_stats(&pids)
+        } else {
+            Default::default()
+        };
+        let config = if table.uses("groups") || table.uses("source") {
+            Some(PitchforkToml::all_merged_all_namespaces()?)
+        } else {
+            None
+        };
 
-        for entry in entries {
-            let display_name = entry.id.styled_qualified();
-
-            let status_text = if entry.is_available {
-                "available".to_string()
-            } else {
-                entry.daemon.status.to_string()
-            };
-
-            let status_color = if entry.is_available {
-                Color::Cyan
-            } else {
-                match entry.daemon.status {
-                    DaemonStatus::Failed(_) => Color::Red,
-                    DaemonStatus::Waiting => Color::Yellow,
-                    DaemonStatus::Running => Color::Green,
-                    DaemonStatus::Stopping => Color::Yellow,
-                    DaemonStatus::Stopped => Color::DarkGrey,
-                    DaemonStatus::Errored(_) => Color::Red,
+        let rows = entries
+            .into_iter()
+            .map(|entry| {
+                let uptime_secs = entry
+                    .daemon
+                    .local_pid()
+                    .filter(|_| entry.daemon.status.is_up())
+                    .and_then(|pid| uptimes.get(&pid))
+                    .map(|stats| stats.uptime_secs);
+                let groups = config
+                    .iter()
+                    .flat_map(|pt| &pt.groups)
+                    .filter(|(_, group)| group.daemons.contains(&entry.id))
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                let source = config
+                    .as_ref()
+                    .and_then(|pt| pt.daemons.get(&entry.id))
+                    .and_then(|d| d.path.clone());
+                let (info, info_color) = info_text(&entry, stale, &s, &global_slugs);
+                ListRow {
+                    entry,
+                    info,
+                    info_color,
+                    uptime_secs,
+                    groups,
+                    source,
                 }
-            };
-
-            // Merged "extra" column: disabled marker, proxy URL, and error
-            // message combined into a single headerless cell. These rarely
-            // co-occur, so color follows priority: error > disabled > proxy.
-            let error_msg = entry.daemon.status.error_message().unwrap_or_default();
-            let proxy_url = if s.proxy.enable {
-                let slug =
-                    PitchforkToml::find_slug_for_daemon_in_registry(&entry.id, &global_slugs);
-                build_proxy_url(slug.as_deref(), &s).filter(|_| {
-                    entry.daemon.active_port.is_some() || !entry.daemon.resolved_port.is_empty()
-                })
-            } else {
-                None
-            };
+            })
+            .collect();
 
-            let mut extra_parts: Vec<String> = Vec::new();
-            if entry.is_disabled {
-                extra_parts.push("disabled".to_string());
-            }
-            if let Some(url) = &proxy_url {
-                extra_parts.push(url.clone());
-            }
-            if !error_msg.is_empty() {
-                extra_parts.push(error_msg.clone());
+        table.print(rows)
+    }
+
+    /// Connect to the supervisor, or `None` to list from the state file
+    /// because it isn't running. With `--no-start` a supervisor that can't
+    /// be reached is never started.
+    async fn connect(&self) -> Result<Option<std::sync::Arc<IpcClient>>> {
+        if !crate::supervisor::is_running() {
+            return Ok(None);
+        }
+        if self.no_start {
+            return Ok(IpcClient::shared(false).await.ok());
+        }
+        IpcClient::shared(true).await.map(Some)
+    }
+}
+
+/// Columns `list` shows unless `--columns` is given
+const DEFAULT_COLUMNS: &[&str] = &["id", "status", "info"];
+
+const COLUMNS: &[Column<ListRow>] = &[
+    Column {
+        name: "id",
+        header: "Name",
+        cell: |r| Cell::new(r.entry.id.styled_qualified()),
+        sort_key: |r| Some(SortKey::Text(r.entry.id.qualified())),
+    },
+    Column {
+        name: "status",
+        header: "Status",
+        cell: |r| Cell::new(r.status_text()).fg(r.status_color()),
+        sort_key: |r| Some(SortKey::Text(r.status_text())),
+    },
+    Column {
+        name: "pid",
+        header: "PID",
+        cell: |r| {
+            Cell::new(
+                r.entry
+                    .daemon
+                    .pid
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+            )
+        },
+        sort_key: |r| r.entry.daemon.pid.map(|p| SortKey::Number(p.into())),
+    },
+    Column {
+        name: "port",
+        header: "Port",
+        cell: |r| {
+            Cell::new(
+                r.ports()
+                    .iter()
+                    .map(u16::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )
+        },
+        sort_key: |r| r.ports().first().map(|p| SortKey::Number((*p).into())),
+    },
+    Column {
+        name: "uptime",
+        header: "Uptime",
+        cell: |r| Cell::new(r.uptime_secs.map(time::duration_secs).unwrap_or_default()),
+        sort_key: |r| r.uptime_secs.map(SortKey::Number),
+    },
+    Column {
+        name: "groups",
+        header: "Groups",
+        cell: |r| Cell::new(r.groups.join(",")),
+        sort_key: |r| r.groups.first().map(|g| SortKey::Text(g.clone())),
+    },
+    Column {
+        name: "source",
+        header: "Source",
+        cell: |r| {
+            Cell::new(
+                r.source
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default(),
+            )
+        },
+        sort_key: |r| {
+            r.source
+                .as_ref()
+                .map(|p| SortKey::Text(p.display().to_string()))
+        },
+    },
+    Column {
+        name: "info",
+        header: "",
+        cell: |r| match r.info_color {
+            Some(color) => Cell::new(&r.info).fg(color),
+            None => Cell::new(&r.info),
+        },
+        sort_key: |r| (!r.info.is_empty()).then(|| SortKey::Text(r.info.clone())),
+    },
+];
+
+/// A daemon in the `list` table
+struct ListRow {
+    entry: DaemonListEntry,
+    info: String,
+    info_color: Option<Color>,
+    uptime_secs: Option<u64>,
+    groups: Vec<String>,
+    source: Option<PathBuf>,
+}
+
+impl ListRow {
+    fn status_text(&self) -> String {
+        if self.entry.is_available {
+            "available".to_string()
+        } else {
+            self.entry.daemon.status_label()
+        }
+    }
+
+    fn status_color(&self) -> Color {
+        if self.entry.is_available {
+            Color::Cyan
+        } else if self.entry.daemon.is_completed() {
+            Color::Blue
+        } else {
+            match self.entry.daemon.status {
+                DaemonStatus::Failed(_) => Color::Red,
+                DaemonStatus::Waiting => Color::Yellow,
+                DaemonStatus::Starting => Color::Yellow,
+                DaemonStatus::Running => Color::Green,
+                DaemonStatus::Unhealthy => Color::Magenta,
+                DaemonStatus::Stopping => Color::Yellow,
+                DaemonStatus::Detached => Color::Cyan,
+                DaemonStatus::Stopped => Color::DarkGrey,
+                DaemonStatus::Errored(_) => Color::Red,
             }
-            let extra_text = extra_parts.join("  ");
-
-            let extra_cell = if extra_text.is_empty() {
-                Cell::new("")
-            } else if !error_msg.is_empty() {
-                Cell::new(&extra_text).fg(Color::Red)
-            } else if entry.is_disabled {
-                Cell::new(&extra_text).fg(Color::DarkGrey)
-            } else {
-                Cell::new(&extra_text).fg(Color::Cyan)
-            };
-
-            table.add_row(vec![
-                Cell::new(&display_name),
-                Cell::new(&status_text).fg(status_color),
-                extra_cell,
-            ]);
         }
+    }
+
+    /// The port the daemon listens on, or its configured ports
+    fn ports(&self) -> Vec<u16> {
+        match self.entry.daemon.active_port {
+            Some(port) => vec![port],
+            None => self.entry.daemon.resolved_port.clone(),
+        }
+    }
+}
 
-        print_table(table)
+/// The merged "info" column: disabled marker, alert, proxy URL, and error
+/// message combined into a single headerless cell. These rarely co-occur,
+/// so color follows priority: error > alert > disabled > proxy.
+fn info_text(
+    entry: &DaemonListEntry,
+    stale: bool,
+    s: &Settings,
+    global_slugs: &IndexMap<String, SlugEntry>,
+) -> (String, Option<Color>) {
+    let error_msg = entry.daemon.error_message().unwrap_or_default();
+    let proxy_url = if s.proxy.enable {
+        let slug = PitchforkToml::find_slug_for_daemon_in_registry(&entry.id, global_slugs);
+        build_proxy_url(slug.as_deref(), s).filter(|_| {
+            entry.daemon.active_port.is_some() || !entry.daemon.resolved_port.is_empty()
+        })
+    } else {
+        None
+    };
+
+    let is_stale = stale && !entry.is_available;
+    let mut extra_parts: Vec<String> = Vec::new();
+    if is_stale {
+        extra_parts.push("stale (supervisor not running)".to_string());
+    }
+    if entry.is_disabled {
+        extra_parts.push("disabled".to_string());
+    } else if entry.is_auto_disabled {
+        extra_parts.push("autostart disabled".to_string());
+    }
+    if let Some(host) = entry.daemon.remote_host() {
+        extra_parts.push(format!("on {host}"));
+    }
+    if let Some(owner) = &entry.daemon.owner {
+        extra_parts.push(format!("by {owner}"));
+    }
+    if let Some(req) = &entry.daemon.waiting_for {
+        extra_parts.push(format!("waiting for {req}"));
+    }
+    if let Some(retry) = entry.daemon.retry_summary(chrono::Local::now()) {
+        extra_parts.push(format!("retry {retry}"));
     }
+    if let Some(alert) = &entry.daemon.alert {
+        extra_parts.push(format!("alert: {alert}"));
+    }
+    if let Some(drift) = &entry.daemon.drift {
+        extra_parts.push(format!("stale: {drift}, restart recommended"));
+    }
+    if let Some(url) = &proxy_url {
+        extra_parts.push(url.clone());
+    }
+    if !error_msg.is_empty() {
+        extra_parts.push(error_msg.clone());
+    }
+    let extra_text = extra_parts.join("  ");
+
+    let color = if extra_text.is_empty() {
+        None
+    } else if !error_msg.is_empty() {
+        Some(Color::Red)
+    } else if entry.daemon.alert.is_some() || entry.daemon.drift.is_some() {
+        Some(Color::Yellow)
+    } else if is_stale || entry.is_disabled || entry.is_auto_disabled {
+        Some(Color::DarkGrey)
+    } else {
+        Some(Color::Cyan)
+    };
+    (extra_text, color)
 }
 
 /// Build the proxy URL for a daemon based on its slug and proxy settings.
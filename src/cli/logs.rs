@@ -1,7 +1,10 @@
+use crate::ansi::strip_pty_controls;
 use crate::cli::json_output::{JsonLogEntry, print_json};
+use crate::config_types::LogTimezone;
 use crate::daemon_id::DaemonId;
+use crate::log_store::journal::LogSink;
 use crate::log_store::sqlite::LOG_STORE;
-use crate::log_store::{FieldFilter, LogEntry, LogQuery, LogStore, MessageFilter};
+use crate::log_store::{FieldFilter, LogEntry, LogQuery, LogStore, LogStream, MessageFilter};
 use crate::pitchfork_toml::PitchforkToml;
 use crate::settings::settings;
 use crate::state_file::StateFile;
@@ -11,8 +14,7 @@ use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone};
 use console;
 use itertools::Itertools;
 use miette::IntoDiagnostic;
-use std::collections::BTreeSet;
-use std::fmt::Write as _;
+use std::collections::{BTreeSet, HashMap};
 use std::io::{self, IsTerminal, Write};
 use std::process::{Child, Command, Stdio};
 use std::time::Duration;
@@ -181,7 +183,7 @@ fn write_formatted_log(
             out.push(' ');
         }
         if !single_daemon {
-            let colors_on = !strip_ansi && console::colors_enabled();
+            let colors_on = !strip_ansi && crate::ui::color::stdout_enabled();
             out.push_str(&colored_id_label(&entry.daemon_id, colors_on));
             out.push(' ');
         }
@@ -208,7 +210,7 @@ fn write_formatted_log(
 
         // Daemon ID (multi-daemon mode)
         if !single_daemon {
-            let colors_on = !strip_ansi && console::colors_enabled();
+            let colors_on = !strip_ansi && crate::ui::color::stdout_enabled();
             out.push_str(&colored_id_label(&entry.daemon_id, colors_on));
             out.push(' ');
         }
@@ -283,7 +285,7 @@ fn write_formatted_log(
             out.push(' ');
         }
         if !single_daemon {
-            let colors_on = !strip_ansi && console::colors_enabled();
+            let colors_on = !strip_ansi && crate::ui::color::stdout_enabled();
             out.push_str(&colored_id_label(&entry.daemon_id, colors_on));
             out.push(' ');
         }
@@ -327,6 +329,8 @@ Examples:
   pitchfork logs api worker       Show logs for multiple daemons
   pitchfork logs                  Show logs for all daemons
   pitchfork logs api -n 50        Show last 50 lines
+  pitchfork logs api --tail-bytes 65536
+                                  Show the last 64KiB of logs
   pitchfork logs api --follow     Follow logs in real-time
   pitchfork logs api --since '2024-01-15 10:00:00'
                                   Show logs since a specific time (forward)
@@ -336,6 +340,7 @@ Examples:
                                   Show logs since 10:30:00 until 12:00:00 today
   pitchfork logs api --since 5min Show logs from last 5 minutes
   pitchfork logs api --raw        Output raw log lines without formatting
+  pitchfork logs api --tz UTC     Show timestamps in UTC
   pitchfork logs api --raw -n 100 Output last 100 raw log lines
   pitchfork logs api --clear      Delete logs for 'api'
   pitchfork logs --clear          Delete logs for all daemons"
@@ -355,6 +360,13 @@ pub struct Logs {
     #[clap(short)]
     n: Option<usize>,
 
+    /// Show as many of the last lines as fit in N bytes
+    ///
+    /// Counts the raw log lines, newlines included; only whole lines are
+    /// shown. Can be combined with -n, whichever is smaller wins.
+    #[clap(long, value_name = "N")]
+    tail_bytes: Option<usize>,
+
     /// Show logs in real-time
     #[clap(short = 't', short_alias = 'f', long, visible_alias = "follow")]
     tail: bool,
@@ -406,7 +418,8 @@ pub struct Logs {
     ///
     /// Shows entries at or above the given severity.
     /// For example, `--level warn` shows warn and error.
-    /// Only effective for daemons with log_format json or logfmt.
+    /// Only effective for daemons with log_format json, logfmt or syslog,
+    /// or with a log_level_regex.
     #[clap(long)]
     level: Option<String>,
 
@@ -417,10 +430,21 @@ pub struct Logs {
     #[clap(long, value_name = "KEY=VALUE")]
     field: Vec<String>,
 
+    /// Show only lines the daemon wrote to stdout
+    ///
+    /// Daemons run with `pty = true` have a single merged stream, which is
+    /// recorded as stdout.
+    #[clap(long, conflicts_with = "stderr")]
+    stdout: bool,
+
+    /// Show only lines the daemon wrote to stderr
+    #[clap(long)]
+    stderr: bool,
+
     /// Filter log entries with a jq expression
     ///
     /// Each log entry is serialized as a JSON object with fields:
-    /// timestamp, daemon_id, message, level, msg, logger, fields.
+    /// timestamp, daemon_id, message, level, msg, logger, stream, fields.
     /// Entries for which the expression produces a truthy value are shown.
     #[clap(long, value_name = "EXPR")]
     jq: Option<String>,
@@ -428,6 +452,57 @@ pub struct Logs {
     /// Omit timestamps from log output
     #[clap(long)]
     no_timestamp: bool,
+
+    /// Show timestamps in this timezone: local, UTC, or an IANA name
+    ///
+    /// Overrides each daemon's `log_timezone`. Only changes how timestamps
+    /// are shown; --since and --until are still read as local time.
+    #[clap(long, value_name = "TZ")]
+    tz: Option<LogTimezone>,
+}
+
+/// Timezone each daemon's timestamps are shown in: `--tz` when given,
+/// otherwise the daemon's `log_timezone`, otherwise local time.
+#[derive(Debug, Clone, Default)]
+pub struct LogTimezones {
+    default: LogTimezone,
+    per_daemon: HashMap<String, LogTimezone>,
+}
+
+impl LogTimezones {
+    pub fn resolve(ids: &[DaemonId], tz: Option<LogTimezone>) -> Self {
+        if let Some(tz) = tz {
+            return Self {
+                default: tz,
+                per_daemon: HashMap::new(),
+            };
+        }
+        let config = match PitchforkToml::all_merged() {
+            Ok(config) => config,
+            Err(e) => {
+                debug!("failed to read config for log timezones: {e}");
+                return Self::default();
+            }
+        };
+        let per_daemon = ids
+            .iter()
+            .filter_map(|id| {
+                let tz = config.daemons.get(id)?.logs.as_ref()?.log_timezone?;
+                Some((id.qualified(), tz))
+            })
+            .collect();
+        Self {
+            default: LogTimezone::Local,
+            per_daemon,
+        }
+    }
+
+    fn get(&self, daemon_id: &str) -> LogTimezone {
+        self.per_daemon
+            .get(daemon_id)
+            .copied()
+            .unwrap_or(self.default)
+    }
 }
 
 impl Logs {
@@ -445,6 +520,12 @@ impl Logs {
             return Ok(());
         }
 
+        if !LogSink::get().sqlite() {
+            warn!(
+                "logs.sink is \"journal\": only recent output is kept here, use `journalctl -t <daemon id>` for the full logs"
+            );
+        }
+
         let from = if let Some(since) = self.since.as_ref() {
             Some(parse_time_input(since, true)?)
         } else {
@@ -458,6 +539,7 @@ impl Logs {
 
         let message_filters = self.build_message_filters()?;
         let field_filters = self.build_field_filters()?;
+        let timezones = LogTimezones::resolve(&resolved_ids, self.tz);
 
         // Compile jq filter early so parse errors surface before any query.
         let jq_filter = match self.jq.as_deref() {
@@ -473,6 +555,7 @@ impl Logs {
                 message_filters,
                 field_filters,
                 jq_filter.as_ref(),
+                &timezones,
             );
         }
 
@@ -484,6 +567,21 @@ impl Logs {
         let show_timestamp = settings().logs.timestamp && !self.no_timestamp && !self.raw;
         let has_time_filter = from.is_some() || to.is_some();
 
+        // A plain `-n N` only needs the newest lines, which the supervisor
+        // already holds in memory; skip the database when it has enough.
+        let recent = match self.n {
+            Some(n)
+                if !has_time_filter
+                    && self.tail_bytes.is_none()
+                    && message_filters.is_empty()
+                    && field_filters.is_empty()
+                    && jq_filter.is_none() =>
+            {
+                recent_from_supervisor(&resolved_ids, n).await
+            }
+            _ => None,
+        };
+
         self.query_and_output(
             &resolved_ids,
             from,
@@ -494,6 +592,8 @@ impl Logs {
             single_daemon,
             has_time_filter,
             show_timestamp,
+            &timezones,
+            recent,
         )?;
 
         if self.tail {
@@ -505,6 +605,7 @@ impl Logs {
                 field_filters,
                 jq_filter.as_ref(),
                 show_timestamp,
+                &timezones,
                 self.raw,
             )
             .await?;
@@ -574,6 +675,11 @@ impl Logs {
                 value: value.to_string(),
             });
         }
+        if self.stdout {
+            filters.push(FieldFilter::Stream(LogStream::Stdout));
+        } else if self.stderr {
+            filters.push(FieldFilter::Stream(LogStream::Stderr));
+        }
         Ok(filters)
     }
 
@@ -594,21 +700,37 @@ impl Logs {
         single_daemon: bool,
         has_time_filter: bool,
         show_timestamp: bool,
+        timezones: &LogTimezones,
+        recent: Option<Vec<LogEntry>>,
     ) -> Result<()> {
         let daemon_ids: Vec<String> = resolved_ids.iter().map(|id| id.qualified()).collect();
 
-        let opts = LogQuery {
-            daemon_ids,
-            from,
-            to,
-            limit: if !has_time_filter { self.n } else { None },
-            order_desc: !has_time_filter,
-            after_id: None,
-            message_filters,
-            field_filters,
-            include_structured: jq_filter.is_some() || !self.raw,
+        let mut entries = match recent {
+            // Newest first, to match the descending query below.
+            Some(mut recent) => {
+                recent.reverse();
+                recent
+            }
+            None => {
+                let opts = LogQuery {
+                    daemon_ids,
+                    from,
+                    to,
+                    limit: if !has_time_filter { self.n } else { None },
+                    max_bytes: if !has_time_filter {
+                        self.tail_bytes
+                    } else {
+                        None
+                    },
+                    order_desc: !has_time_filter,
+                    after_id: None,
+                    message_filters,
+                    field_filters,
+                    include_structured: jq_filter.is_some() || !self.raw,
+                };
+                LOG_STORE.query(&opts)?
+            }
         };
-        let mut entries = LOG_STORE.query(&opts)?;
 
         // Apply jq filter if present.
         if let Some(jq) = jq_filter {
@@ -623,6 +745,9 @@ impl Logs {
                     entries = entries.split_off(len - n);
                 }
             }
+            if let Some(max) = self.tail_bytes {
+                keep_last_bytes(&mut entries, max);
+            }
         } else {
             entries.reverse();
         }
@@ -631,7 +756,7 @@ impl Logs {
             return Ok(());
         }
 
-        let strip_ansi = self.raw || !console::colors_enabled();
+        let strip_ansi = self.raw || !crate::ui::color::stdout_enabled();
         let use_pager = !self.tail && !self.no_pager && should_use_pager(entries.len());
         let ts_format = &settings().logs.timestamp_format;
 
@@ -641,7 +766,9 @@ impl Logs {
         let mut write_entries = |w: &mut dyn Write| -> io::Result<()> {
             for entry in &entries {
                 date_buf.clear();
-                write!(date_buf, "{}", entry.timestamp.format(ts_format))
+                timezones
+                    .get(&entry.daemon_id)
+                    .write_timestamp(&mut date_buf, &entry.timestamp, ts_format)
                     .map_err(io::Error::other)?;
                 write_formatted_log(
                     w,
@@ -688,6 +815,7 @@ impl Logs {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn output_json(
         &self,
         resolved_ids: &[DaemonId],
@@ -696,6 +824,7 @@ impl Logs {
         message_filters: Vec<MessageFilter>,
         field_filters: Vec<FieldFilter>,
         jq_filter: Option<&crate::log_jq::JqFilter>,
+        timezones: &LogTimezones,
     ) -> Result<()> {
         let daemon_ids: Vec<String> = resolved_ids.iter().map(|id| id.qualified()).collect();
         let has_time_filter = from.is_some() || to.is_some();
@@ -705,6 +834,11 @@ impl Logs {
             from,
             to,
             limit: if !has_time_filter { self.n } else { None },
+            max_bytes: if !has_time_filter {
+                self.tail_bytes
+            } else {
+                None
+            },
             order_desc: !has_time_filter,
             after_id: None,
             message_filters,
@@ -736,6 +870,9 @@ impl Logs {
         {
             entries = entries.split_off(entries.len() - n);
         }
+        if has_time_filter && let Some(max) = self.tail_bytes {
+            keep_last_bytes(&mut entries, max);
+        }
 
         let json_entries: Vec<JsonLogEntry> = entries
             .into_iter()
@@ -745,12 +882,15 @@ impl Logs {
                     .as_deref()
                     .and_then(|s| serde_json::from_str(s).ok());
                 JsonLogEntry {
-                    timestamp: e.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    timestamp: timezones
+                        .get(&e.daemon_id)
+                        .format(&e.timestamp, "%Y-%m-%d %H:%M:%S"),
                     daemon_id: e.daemon_id,
                     message: console::strip_ansi_codes(&e.message).to_string(),
                     level: e.level,
                     msg: e.msg,
                     logger: e.logger,
+                    stream: e.stream,
                     fields,
                 }
             })
@@ -760,6 +900,61 @@ impl Logs {
     }
 }
 
+/// Drop the oldest of chronologically ordered `entries` until the rest fit in
+/// `max` bytes, the same budget as [`LogQuery::max_bytes`].
+fn keep_last_bytes(entries: &mut Vec<LogEntry>, max: usize) {
+    let mut remaining = max;
+    let keep = entries
+        .iter()
+        .rev()
+        .take_while(|e| {
+            let size = e.message.len() + 1;
+            let fits = size <= remaining;
+            remaining = remaining.saturating_sub(size);
+            fits
+        })
+        .count();
+    entries.drain(..entries.len() - keep);
+}
+
+/// Fetch the last `n` lines for each daemon from the supervisor's in-memory
+/// buffer, merged in chronological order and trimmed to `n`.
+///
+/// Returns `None` (so the caller falls back to SQLite) when the supervisor
+/// isn't running, doesn't support the request, or hasn't buffered `n` lines
+/// for every daemon — older lines may then only exist in the database.
+async fn recent_from_supervisor(ids: &[DaemonId], n: usize) -> Option<Vec<LogEntry>> {
+    if n == 0 || !supervisor_running() {
+        return None;
+    }
+    let ipc = crate::ipc::client::IpcClient::shared(false).await.ok()?;
+    let mut entries = Vec::new();
+    for id in ids {
+        let lines = match ipc.get_recent_logs(id.clone(), n).await {
+            Ok(lines) => lines,
+            Err(e) => {
+                debug!("recent logs unavailable from supervisor: {e}");
+                return None;
+            }
+        };
+        if lines.len() < n {
+            return None;
+        }
+        entries.extend(lines.into_iter().map(|l| l.into_log_entry(id)));
+    }
+    entries.sort_by_key(|e| e.timestamp);
+    let len = entries.len();
+    Some(entries.split_off(len.saturating_sub(n)))
+}
+
+fn supervisor_running() -> bool {
+    StateFile::get()
+        .daemons
+        .get(&DaemonId::pitchfork())
+        .and_then(|d| d.local_pid())
+        .is_some_and(|pid| crate::procs::PROCS.is_running(pid))
+}
+
 fn should_use_pager(line_count: usize) -> bool {
     if !io::stdout().is_terminal() {
         return false;
@@ -913,10 +1108,11 @@ pub async fn tail_logs(
     field_filters: Vec<FieldFilter>,
     jq_filter: Option<&crate::log_jq::JqFilter>,
     show_timestamp: bool,
+    timezones: &LogTimezones,
     raw: bool,
 ) -> Result<()> {
     // Poll SQLite log store for new entries since last known row id.
-    let strip_ansi = raw || !console::colors_enabled();
+    let strip_ansi = raw || !crate::ui::color::stdout_enabled();
 
     let mut states: std::collections::HashMap<String, i64> = names
         .iter()
@@ -947,6 +1143,7 @@ pub async fn tail_logs(
                 from: None,
                 to: None,
                 limit: None,
+                max_bytes: None,
                 order_desc: false,
                 after_id,
                 message_filters: message_filters.clone(),
@@ -1010,7 +1207,9 @@ pub async fn tail_logs(
             let mut date_buf = String::with_capacity(ts_format.len() + 6);
             for entry in &out {
                 date_buf.clear();
-                write!(date_buf, "{}", entry.timestamp.format(ts_format))
+                timezones
+                    .get(&entry.daemon_id)
+                    .write_timestamp(&mut date_buf, &entry.timestamp, ts_format)
                     .map_err(io::Error::other)
                     .into_diagnostic()?;
                 write_formatted_log(
@@ -1041,7 +1240,7 @@ fn parse_datetime(s: &str) -> Result<DateTime<Local>> {
 /// `is_since` indicates whether this is for --since (true) or --until (false).
 /// The "yesterday fallback" only applies to --since: if the time is in the future,
 /// assume the user meant yesterday. For --until, future times are kept as-is.
-fn parse_time_input(s: &str, is_since: bool) -> Result<DateTime<Local>> {
+pub(crate) fn parse_time_input(s: &str, is_since: bool) -> Result<DateTime<Local>> {
     let s = s.trim();
 
     // Try full datetime first (YYYY-MM-DD HH:MM:SS)
@@ -1125,10 +1324,10 @@ pub fn print_error_logs_block(log_lines: &[(String, String, String)]) {
         return;
     }
 
-    let is_tty = std::io::stderr().is_terminal();
+    let colors_enabled = crate::ui::color::stderr_enabled();
     let format_msg = |msg: &str| -> String {
         let stripped = strip_pty_controls(msg);
-        if is_tty {
+        if colors_enabled {
             stripped
         } else {
             console::strip_ansi_codes(&stripped).to_string()
@@ -1145,7 +1344,7 @@ pub fn print_error_logs_block(log_lines: &[(String, String, String)]) {
     if show_id {
         for (date, id, msg) in log_lines {
             let time = date.split(' ').nth(1).unwrap_or(date);
-            let colored = colored_id_label(id, is_tty && console::colors_enabled_stderr());
+            let colored = colored_id_label(id, colors_enabled);
             eprintln!(
                 "{} {} {}",
                 estyle(time).red().dim(),
@@ -1166,8 +1365,11 @@ pub enum ReadyCheckType {
     Output(String),
     Http(String),
     Port(u16),
+    UnixSocket(String),
     Cmd(String),
-    Delay(u64),
+    LogFile(String),
+    Daemon(String),
+    Delay(std::time::Duration),
     Default,
 }
 
@@ -1177,8 +1379,13 @@ impl std::fmt::Display for ReadyCheckType {
             ReadyCheckType::Output(pattern) => write!(f, "output matching '{pattern}'"),
             ReadyCheckType::Http(url) => write!(f, "HTTP {url}"),
             ReadyCheckType::Port(port) => write!(f, "TCP port {port}"),
+            ReadyCheckType::UnixSocket(path) => write!(f, "unix socket {path}"),
             ReadyCheckType::Cmd(cmd) => write!(f, "command '{cmd}'"),
-            ReadyCheckType::Delay(secs) => write!(f, "delay ({secs}s)"),
+            ReadyCheckType::LogFile(path) => write!(f, "log file {path}"),
+            ReadyCheckType::Daemon(when) => write!(f, "daemon {when}"),
+            ReadyCheckType::Delay(delay) => {
+                write!(f, "delay ({})", humantime::format_duration(*delay))
+            }
             ReadyCheckType::Default => write!(f, "default readiness check"),
         }
     }
@@ -1195,8 +1402,7 @@ pub fn create_ready_check_job(
 ) -> std::sync::Arc<clx::progress::ProgressJob> {
     use clx::progress::{ProgressJobBuilder, ProgressJobDoneBehavior, ProgressStatus};
 
-    let is_tty = std::io::stderr().is_terminal();
-    let colors_enabled = is_tty && console::colors_enabled_stderr();
+    let colors_enabled = crate::ui::color::stderr_enabled();
     let id_label = colored_id_label(&daemon_id.qualified(), colors_enabled);
     let show_ts = crate::settings::settings().general.startup_log_timestamps;
 
@@ -1213,10 +1419,11 @@ pub fn create_ready_check_job(
 
     ProgressJobBuilder::new()
         .body(format!(
-            "{} {} waiting for {{{{ check_type }}}}...",
+            "{} {} waiting for {{{{ check_type }}}}...{{{{ elapsed }}}}",
             prefix, id_label
         ))
         .prop("check_type", &check_type.to_string())
+        .prop("elapsed", "")
         .status(ProgressStatus::Running)
         .on_done(ProgressJobDoneBehavior::Keep)
         .start()
@@ -1235,6 +1442,7 @@ pub fn collect_startup_logs(
         from: Some(from),
         to: None,
         limit: None,
+        max_bytes: None,
         order_desc: false,
         after_id: None,
         message_filters: Vec::new(),
@@ -1252,14 +1460,41 @@ pub fn collect_startup_logs(
     Ok(log_lines)
 }
 
+/// Which of a daemon's output lines `start` prints while waiting for it to
+/// become ready.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ShowOutput {
+    /// Every line
+    #[default]
+    All,
+    /// Lines written to stderr or logged at error level
+    Errors,
+    /// No lines, only the ready check progress
+    None,
+}
+
+impl ShowOutput {
+    fn shows(self, entry: &LogEntry) -> bool {
+        match self {
+            ShowOutput::All => true,
+            ShowOutput::Errors => {
+                entry.stream == LogStream::Stderr || entry.level.as_deref() == Some("error")
+            }
+            ShowOutput::None => false,
+        }
+    }
+}
+
 /// Stream startup logs for a daemon to a progress job in real-time.
 ///
 /// Spawns a background tokio task that polls the daemon's log store
-/// and calls `job.println()` for each new line. Returns a watch sender
-/// that stops the streaming when sent `true`.
+/// and calls `job.println()` for each new line that `show` lets through.
+/// Also counts the seconds spent waiting in the job's `elapsed` prop.
+/// Returns a watch sender that stops the streaming when sent `true`.
 pub fn stream_startup_logs(
     daemon_id: &DaemonId,
     job: std::sync::Arc<clx::progress::ProgressJob>,
+    show: ShowOutput,
 ) -> (
     tokio::sync::watch::Sender<bool>,
     tokio::task::JoinHandle<()>,
@@ -1277,6 +1512,7 @@ pub fn stream_startup_logs(
         .query(&LogQuery {
             daemon_ids: vec![id.qualified()],
             limit: Some(1),
+            max_bytes: None,
             order_desc: true,
             ..Default::default()
         })
@@ -1285,8 +1521,7 @@ pub fn stream_startup_logs(
         .unwrap_or(0);
 
     let handle = tokio::spawn(async move {
-        let is_tty = std::io::stderr().is_terminal();
-        let colors_enabled = is_tty && console::colors_enabled_stderr();
+        let colors_enabled = crate::ui::color::stderr_enabled();
         let id_label = colored_id_label(&id.qualified(), colors_enabled);
         let prefix = if show_ts {
             String::new()
@@ -1294,53 +1529,60 @@ pub fn stream_startup_logs(
             edim("•").to_string()
         };
 
-        let mut last_id = anchor_id;
-
-        // Initial fetch: catch any logs already written since the anchor.
-        if let Ok(entries) = LOG_STORE.tail(&id, Some(last_id)) {
-            for entry in &entries {
-                let time = entry.timestamp.format("%H:%M:%S").to_string();
+        // Print the lines logged after `last_id` and advance it past them
+        let print_new = |last_id: &mut i64| {
+            if show == ShowOutput::None {
+                return;
+            }
+            let entries = if show == ShowOutput::Errors {
+                // Levels are only read to pick out errors
+                LOG_STORE.query(&LogQuery {
+                    daemon_ids: vec![id.qualified()],
+                    after_id: Some(*last_id),
+                    include_structured: true,
+                    ..Default::default()
+                })
+            } else {
+                LOG_STORE.tail(&id, Some(*last_id))
+            };
+            let Ok(entries) = entries else {
+                return;
+            };
+            for entry in entries.iter().filter(|e| show.shows(e)) {
                 let msg = strip_pty_controls(&entry.message);
-                let msg = if is_tty {
+                let msg = if colors_enabled {
                     msg
                 } else {
                     console::strip_ansi_codes(&msg).to_string()
                 };
                 let line_prefix = if show_ts {
-                    edim(time).to_string()
+                    edim(entry.timestamp.format("%H:%M:%S").to_string()).to_string()
                 } else {
                     prefix.clone()
                 };
                 job.println(&format!("{} {} {}", line_prefix, id_label, msg));
             }
             if let Some(last) = entries.last() {
-                last_id = last.id;
+                *last_id = last.id;
             }
-        }
+        };
+
+        let mut last_id = anchor_id;
+        let started = std::time::Instant::now();
+        let mut elapsed_secs = 0;
+
+        // Initial fetch: catch any logs already written since the anchor.
+        print_new(&mut last_id);
 
         loop {
             tokio::select! {
                 _ = tokio::time::sleep(Duration::from_millis(200)) => {
-                    if let Ok(entries) = LOG_STORE.tail(&id, Some(last_id)) {
-                        for entry in &entries {
-                            let time = entry.timestamp.format("%H:%M:%S").to_string();
-                            let msg = strip_pty_controls(&entry.message);
-                            let msg = if is_tty {
-                                msg
-                            } else {
-                                console::strip_ansi_codes(&msg).to_string()
-                            };
-                            let line_prefix = if show_ts {
-                                edim(time).to_string()
-                            } else {
-                                prefix.clone()
-                            };
-                            job.println(&format!("{} {} {}", line_prefix, id_label, msg));
-                        }
-                        if let Some(last) = entries.last() {
-                            last_id = last.id;
-                        }
+                    let secs = started.elapsed().as_secs();
+                    if secs > elapsed_secs {
+                        elapsed_secs = secs;
+                        job.prop("elapsed", &format!(" {}", edim(format!("{secs}s"))));
                     }
+                    print_new(&mut last_id);
                 }
                 _ = rx.changed() => {
                     break;
@@ -1349,108 +1591,8 @@ pub fn stream_startup_logs(
         }
 
         // Final drain
-        if let Ok(entries) = LOG_STORE.tail(&id, Some(last_id)) {
-            for entry in &entries {
-                let time = entry.timestamp.format("%H:%M:%S").to_string();
-                let msg = strip_pty_controls(&entry.message);
-                let msg = if is_tty {
-                    msg
-                } else {
-                    console::strip_ansi_codes(&msg).to_string()
-                };
-                let line_prefix = if show_ts {
-                    edim(time).to_string()
-                } else {
-                    prefix.clone()
-                };
-                job.println(&format!("{} {} {}", line_prefix, id_label, msg));
-            }
-        }
+        print_new(&mut last_id);
     });
 
     (tx, handle)
 }
-
-/// Strips PTY control sequences from a string while preserving SGR (color/style) codes.
-///
-/// Removes CSI sequences that control cursor movement, screen clearing, erasing, etc.,
-/// but keeps `\x1b[...m` (SGR) sequences so colors are retained.
-fn strip_pty_controls(s: &str) -> String {
-    struct Stripper {
-        result: String,
-    }
-
-    impl vte::Perform for Stripper {
-        fn print(&mut self, c: char) {
-            self.result.push(c);
-        }
-
-        fn execute(&mut self, byte: u8) {
-            // Keep \n and \t; drop other control characters (BEL, BS, CR, etc.)
-            if byte == b'\n' || byte == b'\t' {
-                self.result.push(byte as char);
-            }
-        }
-
-        fn csi_dispatch(
-            &mut self,
-            params: &vte::Params,
-            _intermediates: &[u8],
-            _ignore: bool,
-            action: char,
-        ) {
-            // Keep SGR sequences (final byte 'm')
-            if action == 'm' {
-                self.result.push_str("\x1b[");
-                let mut first = true;
-                for sub in params.iter() {
-                    if !first {
-                        self.result.push(';');
-                    }
-                    first = false;
-                    for (i, &p) in sub.iter().enumerate() {
-                        if i > 0 {
-                            self.result.push(':');
-                        }
-                        self.result.push_str(&p.to_string());
-                    }
-                }
-                self.result.push('m');
-            }
-            // All other CSI sequences (cursor move, clear, erase, etc.) are dropped
-        }
-
-        fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
-            // Drop OSC sequences (e.g. window title)
-        }
-
-        fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {
-            // Drop ESC sequences (e.g. ESC c = reset terminal)
-        }
-
-        fn hook(
-            &mut self,
-            _params: &vte::Params,
-            _intermediates: &[u8],
-            _ignore: bool,
-            _action: char,
-        ) {
-            // Drop DCS hooks
-        }
-
-        fn put(&mut self, _byte: u8) {
-            // Drop DCS data
-        }
-
-        fn unhook(&mut self) {
-            // Drop DCS unhook
-        }
-    }
-
-    let mut parser = vte::Parser::new();
-    let mut stripper = Stripper {
-        result: String::with_capacity(s.len()),
-    };
-    parser.advance(&mut stripper, s.as_bytes());
-    stripper.result
-}
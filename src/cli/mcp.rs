@@ -152,7 +152,7 @@ impl PitchforkServer {
                     "pid": entry.daemon.pid,
                     "status": status_text,
                     "disabled": entry.is_disabled,
-                    "error": entry.daemon.status.error_message(),
+                    "error": entry.daemon.error_message(),
                 })
             })
             .collect();
@@ -382,6 +382,7 @@ impl PitchforkServer {
                 from: None,
                 to: None,
                 limit: Some(limit),
+                max_bytes: None,
                 order_desc: true,
                 after_id: None,
                 message_filters: Vec::new(),
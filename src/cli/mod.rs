@@ -1,4 +1,5 @@
 use crate::Result;
+use crate::ui::color::{self, ColorChoice};
 use clap::Parser;
 use std::ffi::OsString;
 
@@ -8,21 +9,28 @@ mod boot;
 mod cd;
 mod clean;
 mod completion;
+mod config;
 mod daemons;
+mod debug;
 mod disable;
 mod enable;
+mod explain;
 mod json_output;
 mod list;
 pub mod logs;
 mod mcp;
+mod notifications;
 mod project;
+mod prompt;
 mod proxy;
 mod restart;
+mod retry;
 mod run;
-mod schema;
 mod settings;
+mod snapshot;
 mod sponsors;
 mod start;
+mod state;
 mod status;
 mod stop;
 mod supervisor;
@@ -35,6 +43,25 @@ mod wait;
 struct Cli {
     #[clap(subcommand)]
     command: Commands,
+    /// When to use colors in output
+    ///
+    /// `auto` colors output written to a terminal unless NO_COLOR is set.
+    #[clap(long, global = true, value_enum, default_value_t, value_name = "WHEN")]
+    color: ColorChoice,
+    /// Show another user's daemons on a shared supervisor (root only)
+    ///
+    /// Only applies when the supervisor is shared through
+    /// `settings.supervisor.socket_group`. Without it, root sees every user's
+    /// daemons and other users see only their own.
+    #[clap(long, global = true, value_name = "USER")]
+    user: Option<String>,
+    /// Print every request to the supervisor and its response to stderr
+    ///
+    /// Each message is shown as JSON, and each response with how long it
+    /// took, to see where a command hangs or what it got back. See also
+    /// `pitchfork debug ipc` for the supervisor's side.
+    #[clap(long, global = true)]
+    trace_ipc: bool,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -45,21 +72,28 @@ enum Commands {
     Boot(boot::Boot),
     Cd(cd::Cd),
     Clean(clean::Clean),
+    Config(config::Config),
     Daemons(daemons::Daemons),
+    Debug(debug::Debug),
     Completion(completion::Completion),
     Disable(disable::Disable),
     Enable(enable::Enable),
+    Explain(explain::Explain),
     List(list::List),
     Logs(logs::Logs),
     Mcp(mcp::Mcp),
+    Notifications(notifications::Notifications),
     Proxy(proxy::Proxy),
     Project(project::Project),
+    Prompt(prompt::Prompt),
     Restart(restart::Restart),
+    Retry(retry::Retry),
     Run(run::Run),
-    Schema(schema::Schema),
     Settings(settings::Settings),
+    Snapshot(snapshot::Snapshot),
     Sponsors(sponsors::Sponsors),
     Start(start::Start),
+    State(state::State),
     Status(status::Status),
     Stop(stop::Stop),
     Supervisor(supervisor::Supervisor),
@@ -82,10 +116,20 @@ enum Commands {
 struct StartFallback {
     #[clap(flatten)]
     start: start::Start,
+    /// When to use colors in output
+    #[clap(long, value_enum, default_value_t, value_name = "WHEN")]
+    color: ColorChoice,
 }
 
 pub async fn run() -> Result<()> {
     let args = Cli::parse();
+    color::init(args.color);
+    if let Some(user) = args.user {
+        crate::ipc::client::set_view_user(user);
+    }
+    if args.trace_ipc {
+        crate::ipc::client::set_trace_ipc();
+    }
     let program = std::env::args_os()
         .next()
         .unwrap_or_else(|| "pitchfork".into());
@@ -94,22 +138,29 @@ pub async fn run() -> Result<()> {
         Commands::Boot(boot) => boot.run().await,
         Commands::Cd(cd) => cd.run().await,
         Commands::Clean(clean) => clean.run().await,
+        Commands::Config(config) => config.run().await,
         Commands::Daemons(daemons) => daemons.run().await,
+        Commands::Debug(debug) => debug.run().await,
         Commands::Completion(completion) => completion.run().await,
         Commands::Disable(disable) => disable.run().await,
         Commands::Enable(enable) => enable.run().await,
+        Commands::Explain(explain) => explain.run().await,
         Commands::List(list) => list.run().await,
         Commands::Logs(logs) => logs.run().await,
         Commands::Mcp(mcp) => mcp.run().await,
+        Commands::Notifications(notifications) => notifications.run().await,
         Commands::Proxy(proxy) => proxy.run().await,
         Commands::Project(project) => project.run().await,
+        Commands::Prompt(prompt) => prompt.run().await,
         Commands::Restart(restart) => restart.run().await,
+        Commands::Retry(retry) => retry.run().await,
         Commands::Run(run) => run.run().await,
         Commands::ApiSchema(api_schema) => api_schema.run().await,
-        Commands::Schema(schema) => schema.run().await,
         Commands::Settings(settings) => settings.run().await,
+        Commands::Snapshot(snapshot) => snapshot.run().await,
         Commands::Sponsors(_) => sponsors::Sponsors::run().await,
         Commands::Start(start) => start.run().await,
+        Commands::State(state) => state.run().await,
         Commands::Status(status) => status.run().await,
         Commands::Stop(stop) => stop.run().await,
         Commands::Supervisor(supervisor) => supervisor.run().await,
@@ -119,7 +170,9 @@ pub async fn run() -> Result<()> {
         Commands::Fallback(tokens) => {
             let mut argv = vec![program];
             argv.extend(tokens);
-            StartFallback::parse_from(argv).start.run().await
+            let fallback = StartFallback::parse_from(argv);
+            color::init(fallback.color);
+            fallback.start.run().await
         }
     }
 }
@@ -131,21 +184,26 @@ pub async fn run() -> Result<()> {
 /// to CLI users.  Call this at the end of user-facing commands that connect
 /// to the supervisor via IPC.
 pub(crate) async fn drain_notifications(ipc: &crate::ipc::client::IpcClient) {
-    use log::LevelFilter;
     if let Ok(notifications) = ipc.get_notifications().await {
         for (level, msg) in notifications {
-            match level {
-                LevelFilter::Trace => trace!("{msg}"),
-                LevelFilter::Debug => debug!("{msg}"),
-                LevelFilter::Info => info!("{msg}"),
-                LevelFilter::Warn => warn!("{msg}"),
-                LevelFilter::Error => error!("{msg}"),
-                _ => {}
-            }
+            log_notification(level, &msg);
         }
     }
 }
 
+/// Log a supervisor notification at its own level
+pub(crate) fn log_notification(level: log::LevelFilter, msg: &str) {
+    use log::LevelFilter;
+    match level {
+        LevelFilter::Trace => trace!("{msg}"),
+        LevelFilter::Debug => debug!("{msg}"),
+        LevelFilter::Info => info!("{msg}"),
+        LevelFilter::Warn => warn!("{msg}"),
+        LevelFilter::Error => error!("{msg}"),
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,6 +259,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn color_flag_is_global() {
+        let args = Cli::parse_from(["pitchfork", "list", "--color", "never"]);
+        assert_eq!(args.color, ColorChoice::Never);
+        let args = Cli::parse_from(["pitchfork", "--color=always", "status", "api"]);
+        assert_eq!(args.color, ColorChoice::Always);
+        let args = Cli::parse_from(["pitchfork", "list"]);
+        assert_eq!(args.color, ColorChoice::Auto);
+    }
+
     #[test]
     fn fallback_reparse_as_start() {
         StartFallback::try_parse_from(["pitchfork", "mydaemon", "--force"])
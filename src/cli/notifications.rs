@@ -0,0 +1,142 @@
+use crate::Result;
+use crate::notification_file::{self, StoredNotification};
+use crate::settings::settings;
+use crate::ui::table::{Column, ColumnTable};
+use comfy_table::Cell;
+
+/// List and acknowledge notifications saved by the supervisor
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    args_conflicts_with_subcommands = true,
+    long_about = "\
+List and acknowledge notifications saved by the supervisor
+
+With `supervisor.notify` set to include `file`, the supervisor saves
+notifications (daemon failures, alerts, ...) as they happen, so they reach
+you even when no pitchfork command was running to show them. They stay
+listed until acknowledged.
+
+The notification file is read directly; the supervisor is never contacted,
+so `--count` is cheap enough to run on every prompt.
+
+Examples:
+  pitchfork notifications           List unacknowledged notifications
+  pitchfork notifications --all     Include acknowledged ones
+  pitchfork notifications --count   Print the number of unacknowledged ones
+  pitchfork notifications ack       Acknowledge all of them
+  pitchfork notifications ack 3 4   Acknowledge notifications 3 and 4"
+)]
+pub struct Notifications {
+    #[clap(subcommand)]
+    command: Option<NotificationsCommands>,
+
+    /// Include acknowledged notifications
+    #[clap(short, long)]
+    all: bool,
+    /// Print only the number of unacknowledged notifications, or nothing if there are none
+    #[clap(long, conflicts_with_all = ["all", "json"])]
+    count: bool,
+    /// Output in JSON format
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum NotificationsCommands {
+    Ack(Ack),
+}
+
+/// Acknowledge notifications so they are no longer listed
+#[derive(Debug, clap::Args)]
+pub struct Ack {
+    /// Notification IDs to acknowledge (default: all)
+    ids: Vec<u64>,
+}
+
+impl Notifications {
+    pub async fn run(&self) -> Result<()> {
+        if let Some(NotificationsCommands::Ack(ack)) = &self.command {
+            return ack.run().await;
+        }
+
+        let notifications: Vec<StoredNotification> = notification_file::read()?
+            .into_iter()
+            .filter(|n| self.all || !n.acked)
+            .collect();
+
+        if self.count {
+            if !notifications.is_empty() {
+                println!("{}", notifications.len());
+            }
+            return Ok(());
+        }
+        if self.json {
+            return crate::cli::json_output::print_json(&notifications);
+        }
+        if notifications.is_empty() {
+            if !settings()
+                .supervisor
+                .notify
+                .split(',')
+                .any(|c| c.trim() == "file")
+            {
+                info!("notifications are only saved when supervisor.notify includes \"file\"");
+            }
+            return Ok(());
+        }
+
+        let columns: &[&str] = if self.all {
+            &["id", "time", "level", "message", "acked"]
+        } else {
+            &["id", "time", "level", "message"]
+        };
+        ColumnTable::new(COLUMNS, &[], columns)?
+            .header(console::user_attended())
+            .print(notifications)
+    }
+}
+
+const COLUMNS: &[Column<StoredNotification>] = &[
+    Column {
+        name: "id",
+        header: "ID",
+        cell: |n| Cell::new(n.id),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "time",
+        header: "TIME",
+        cell: |n| Cell::new(n.at.format("%Y-%m-%d %H:%M:%S").to_string()),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "level",
+        header: "LEVEL",
+        cell: |n| Cell::new(&n.level),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "message",
+        header: "MESSAGE",
+        cell: |n| Cell::new(&n.message),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "acked",
+        header: "ACKED",
+        cell: |n| Cell::new(if n.acked { "yes" } else { "" }),
+        sort_key: |_| None,
+    },
+];
+
+impl Ack {
+    pub async fn run(&self) -> Result<()> {
+        let acked = notification_file::ack(&self.ids)?;
+        for id in self.ids.iter().filter(|id| !acked.contains(id)) {
+            warn!("notification {id} not found or already acknowledged");
+        }
+        info!("acknowledged {} notification(s)", acked.len());
+        Ok(())
+    }
+}
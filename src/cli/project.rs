@@ -1,13 +1,15 @@
 use crate::daemon_id::DaemonId;
+use crate::ipc::ProjectSessionInfo;
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::{PitchforkToml, PitchforkTomlAuto};
-use crate::ui::table::print_table;
+use crate::state_file::StateFile;
+use crate::ui::table::{Column, ColumnTable};
 use crate::{Result, env};
 use miette::IntoDiagnostic;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
-use comfy_table::{Cell, Color, ContentArrangement, Table};
+use comfy_table::{Cell, Color};
 
 /// Project session management for IDE and workspace integrations.
 #[derive(Debug, clap::Args)]
@@ -109,13 +111,15 @@ impl Enter {
         // do not leave a half-entered session behind.
         let pt = PitchforkToml::all_merged_from(&target_dir)?;
 
-        let ipc = IpcClient::connect(true).await?;
+        let ipc = IpcClient::shared(true).await?;
         ipc.project_enter(self.pid, target_dir.clone()).await?;
 
+        let state = StateFile::get();
         let to_start: Vec<DaemonId> = pt
             .daemons
             .into_iter()
             .filter(|(_, d)| d.auto.contains(&PitchforkTomlAuto::Start))
+            .filter(|(id, _)| state.autostart_allowed(id))
             .map(|(id, _)| id)
             .collect();
 
@@ -160,7 +164,7 @@ impl Enter {
 impl Leave {
     pub async fn run(&self) -> Result<()> {
         let target_dir = resolve_directory(&self.directory)?;
-        let ipc = IpcClient::connect(true).await?;
+        let ipc = IpcClient::shared(true).await?;
         ipc.project_leave(self.pid, target_dir).await?;
         super::drain_notifications(&ipc).await;
         Ok(())
@@ -169,7 +173,7 @@ impl Leave {
 
 impl List {
     pub async fn run(&self) -> Result<()> {
-        let ipc = IpcClient::connect(true).await?;
+        let ipc = IpcClient::shared(true).await?;
         let mut sessions = ipc.get_project_sessions().await?;
         // Stable ordering: by PID, then directory.
         sessions.sort_by(|a, b| a.pid.cmp(&b.pid).then(a.directory.cmp(&b.directory)));
@@ -178,39 +182,55 @@ impl List {
             return crate::cli::json_output::print_json(&sessions);
         }
 
-        let mut table = Table::new();
-        table
-            .load_preset(comfy_table::presets::NOTHING)
-            .set_content_arrangement(ContentArrangement::Disabled);
-        if console::user_attended() {
-            table.set_header(vec!["PID", "DIRECTORY", "STATUS", "TITLE"]);
-        }
-
-        for s in &sessions {
-            let pid = s.pid.to_string();
-            let directory = s.directory.display().to_string();
-            let (status_text, status_color) = if s.alive {
-                ("alive".to_string(), Color::Green)
-            } else {
-                ("dead".to_string(), Color::Red)
-            };
-            // Prefer the recorded title for display; fall back to the current
-            // title if no snapshot was taken (shouldn't normally happen since
-            // enter always records one for a live PID).
-            let title = s
-                .liveness_title
-                .clone()
-                .or_else(|| s.current_title.clone())
-                .unwrap_or_default();
-
-            table.add_row(vec![
-                Cell::new(&pid),
-                Cell::new(&directory),
-                Cell::new(&status_text).fg(status_color),
-                Cell::new(&title),
-            ]);
-        }
-
-        print_table(table)
+        ColumnTable::new(
+            SESSION_COLUMNS,
+            &[],
+            &["pid", "directory", "status", "title"],
+        )?
+        .header(console::user_attended())
+        .print(sessions)
     }
 }
+
+const SESSION_COLUMNS: &[Column<ProjectSessionInfo>] = &[
+    Column {
+        name: "pid",
+        header: "PID",
+        cell: |s| Cell::new(s.pid),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "directory",
+        header: "DIRECTORY",
+        cell: |s| Cell::new(s.directory.display()),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "status",
+        header: "STATUS",
+        cell: |s| {
+            if s.alive {
+                Cell::new("alive").fg(Color::Green)
+            } else {
+                Cell::new("dead").fg(Color::Red)
+            }
+        },
+        sort_key: |_| None,
+    },
+    Column {
+        name: "title",
+        header: "TITLE",
+        // Prefer the recorded title for display; fall back to the current
+        // title if no snapshot was taken (shouldn't normally happen since
+        // enter always records one for a live PID).
+        cell: |s| {
+            Cell::new(
+                s.liveness_title
+                    .as_deref()
+                    .or(s.current_title.as_deref())
+                    .unwrap_or_default(),
+            )
+        },
+        sort_key: |_| None,
+    },
+];
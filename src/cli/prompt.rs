@@ -0,0 +1,101 @@
+use crate::Result;
+use crate::daemon_status::DaemonStatus;
+use crate::ipc::client::IpcClient;
+use crate::state_file::StateFile;
+
+/// Print a compact status summary for the shell prompt
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Print a compact status summary for the shell prompt
+
+Counts the daemons defined in the pitchfork.toml files for the current
+directory by status, e.g. `3▲ 1◐ 1✖`:
+
+  ▲  running
+  ◐  starting or stopping
+  ✖  errored, failed or unhealthy
+
+Stopped daemons aren't counted, and nothing is printed when no daemon is
+running or has failed, so the prompt stays clean outside active projects.
+
+Only the state file is read; the supervisor is never contacted, so this
+is cheap enough to run on every prompt.
+
+Example starship module (~/.config/starship.toml):
+
+  [custom.pitchfork]
+  command = \"pitchfork prompt\"
+  when = true
+  format = \"[$output]($style) \"
+  style = \"bold red\""
+)]
+pub struct Prompt {}
+
+impl Prompt {
+    pub async fn run(&self) -> Result<()> {
+        // A broken config shouldn't break the prompt
+        let ids = match IpcClient::get_local_configured_daemons() {
+            Ok(ids) => ids,
+            Err(e) => {
+                debug!("failed to read config for prompt: {e}");
+                return Ok(());
+            }
+        };
+        let state = StateFile::get();
+        let summary = summarize(
+            ids.iter()
+                .filter_map(|id| state.daemons.get(id))
+                .map(|d| &d.status),
+        );
+        if !summary.is_empty() {
+            println!("{summary}");
+        }
+        Ok(())
+    }
+}
+
+fn summarize<'a>(statuses: impl IntoIterator<Item = &'a DaemonStatus>) -> String {
+    let (mut running, mut pending, mut failed) = (0, 0, 0);
+    for status in statuses {
+        match status {
+            DaemonStatus::Running | DaemonStatus::Detached => running += 1,
+            DaemonStatus::Waiting | DaemonStatus::Starting | DaemonStatus::Stopping => pending += 1,
+            DaemonStatus::Unhealthy | DaemonStatus::Errored(_) | DaemonStatus::Failed(_) => {
+                failed += 1
+            }
+            DaemonStatus::Stopped => {}
+        }
+    }
+    [(running, "▲"), (pending, "◐"), (failed, "✖")]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, symbol)| format!("{count}{symbol}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let statuses = [
+            DaemonStatus::Running,
+            DaemonStatus::Running,
+            DaemonStatus::Stopped,
+            DaemonStatus::Errored(1),
+            DaemonStatus::Running,
+        ];
+        assert_eq!(summarize(&statuses), "3▲ 1✖");
+        assert_eq!(summarize(&[DaemonStatus::Waiting]), "1◐");
+        assert_eq!(
+            summarize(&[DaemonStatus::Starting, DaemonStatus::Unhealthy]),
+            "1◐ 1✖"
+        );
+        assert_eq!(summarize(&[DaemonStatus::Stopped]), "");
+        assert_eq!(summarize(&[]), "");
+    }
+}
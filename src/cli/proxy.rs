@@ -476,7 +476,7 @@ impl Add {
         PitchforkToml::add_slug_with_namespace(slug, Some(&resolved_ns), stored_daemon)?;
 
         // Notify the supervisor so it can update mDNS records.
-        if let Ok(client) = crate::ipc::client::IpcClient::connect(false).await {
+        if let Ok(client) = crate::ipc::client::IpcClient::shared(false).await {
             let _ = client.sync_mdns().await;
         }
 
@@ -526,7 +526,7 @@ impl Remove {
             println!("Removed slug '{}'", self.slug);
 
             // Notify the supervisor so it can update mDNS records.
-            if let Ok(client) = crate::ipc::client::IpcClient::connect(false).await {
+            if let Ok(client) = crate::ipc::client::IpcClient::shared(false).await {
                 let _ = client.sync_mdns().await;
             }
         } else {
@@ -4,7 +4,6 @@ use crate::ipc::batch::{StartOptions, update_job_with_result};
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::PitchforkToml;
 use miette::ensure;
-use std::sync::Arc;
 
 /// Restarts a daemon (stops then starts it)
 #[derive(Debug, clap::Args)]
@@ -14,7 +13,9 @@ use std::sync::Arc;
 Restarts a daemon (stops then starts it)
 
 Equivalent to 'start --force' - stops the daemon (SIGTERM) then starts it again
-from the pitchfork.toml configuration with dependency resolution.
+from the pitchfork.toml configuration with dependency resolution. Changes to
+the daemon's command, env keys, ports or watch globs since its last run are
+listed as it starts.
 
 Examples:
   pitchfork restart api           Restart a single daemon
@@ -63,9 +64,10 @@ pub struct Restart {
         conflicts_with = "all"
     )]
     global: bool,
-    /// Delay in seconds before considering daemon ready (default: 3 seconds)
-    #[clap(long)]
-    delay: Option<u64>,
+    /// Delay before considering daemon ready, in seconds or as a duration
+    /// like "1500ms" (default: 3 seconds)
+    #[clap(long, value_parser = crate::pitchfork_toml::parse_duration)]
+    delay: Option<std::time::Duration>,
     /// Wait until output matches this regex pattern before considering daemon ready
     #[clap(long)]
     output: Option<String>,
@@ -90,7 +92,7 @@ impl Restart {
             "At least one daemon ID, --group, or one of --all / --local / --global must be provided"
         );
 
-        let ipc = Arc::new(IpcClient::connect(true).await?);
+        let ipc = IpcClient::shared(true).await?;
 
         let ids: Vec<DaemonId> = if self.all {
             ipc.get_running_daemons().await?
@@ -0,0 +1,47 @@
+use crate::Result;
+use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::PitchforkToml;
+
+/// Retry an errored daemon now, or cancel its pending retries
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Retry an errored daemon now, or cancel its pending retries
+
+Daemons with `retry` configured are retried automatically after they crash,
+but only on the supervisor's next check and after backing off. 'retry'
+starts the next attempt immediately instead. It also works for daemons that
+have used up their retries, and counts as another attempt.
+
+With --cancel, pending retries are cleared and the daemon stays errored
+until it is started again.
+
+Examples:
+  pitchfork retry api             Retry api now
+  pitchfork retry api worker      Retry several daemons
+  pitchfork retry --cancel api    Stop retrying api"
+)]
+pub struct Retry {
+    /// ID of the daemon(s) to retry
+    #[clap(required = true)]
+    id: Vec<String>,
+    /// Clear pending retries instead of retrying now
+    #[clap(long)]
+    cancel: bool,
+}
+
+impl Retry {
+    pub async fn run(&self) -> Result<()> {
+        let ids = PitchforkToml::resolve_ids(&self.id)?;
+        let ipc = IpcClient::shared(true).await?;
+        for id in ids {
+            if self.cancel {
+                ipc.cancel_retry(id).await?;
+            } else {
+                ipc.retry(id).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
-use crate::cli::logs::{ReadyCheckType, create_ready_check_job, stream_startup_logs};
+use crate::cli::logs::{ReadyCheckType, ShowOutput, create_ready_check_job, stream_startup_logs};
 use crate::ipc::batch::{StartOptions, update_job_with_result};
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::PitchforkToml;
 use crate::settings::settings;
+use crate::supervisor::hooks::hook_command;
 use crate::{Result, env};
 use miette::bail;
 
@@ -31,7 +32,10 @@ Examples:
   pitchfork run api --http http://localhost:8080/health -- ./server
                                 Wait for HTTP endpoint to return 2xx
   pitchfork run api --port 8080 -- ./server
-                                Wait for TCP port to be listening"
+                                Wait for TCP port to be listening
+  pitchfork run api --ci --port 8080 --then 'npm test' -- ./server
+                                Start, wait until ready, run tests, stop,
+                                and exit with the test command's status"
 )]
 pub struct Run {
     /// Name of the daemon to run
@@ -45,9 +49,10 @@ pub struct Run {
     /// Number of times to retry on error exit
     #[clap(long, default_value = "0")]
     retry: u32,
-    /// Delay in seconds before considering daemon ready (default: 3 seconds)
-    #[clap(short, long)]
-    delay: Option<u64>,
+    /// Delay before considering daemon ready, in seconds or as a duration
+    /// like "1500ms" (default: 3 seconds)
+    #[clap(short, long, value_parser = crate::pitchfork_toml::parse_duration)]
+    delay: Option<std::time::Duration>,
     /// Wait until output matches this regex pattern before considering daemon ready
     #[clap(short, long)]
     output: Option<String>,
@@ -69,6 +74,15 @@ pub struct Run {
     /// Suppress startup log output
     #[clap(short, long)]
     quiet: bool,
+    /// CI mode: wait for readiness, run the --then command, then stop the daemon
+    ///
+    /// Exits with the --then command's status. Without --then, only checks that
+    /// the daemon becomes ready before stopping it.
+    #[clap(long)]
+    ci: bool,
+    /// Command to run once the daemon is ready (requires --ci)
+    #[clap(long, requires = "ci", value_name = "COMMAND")]
+    then: Option<String>,
 }
 
 impl Run {
@@ -77,7 +91,7 @@ impl Run {
             bail!("No command provided");
         }
 
-        let ipc = IpcClient::connect(true).await?;
+        let ipc = IpcClient::shared(true).await?;
 
         let opts = StartOptions {
             force: self.force,
@@ -97,6 +111,7 @@ impl Run {
             },
             retry: Some(crate::config_types::Retry(self.retry)),
             quiet: self.quiet,
+            ..Default::default()
         };
 
         // Resolve ID, allowing unconfigured short IDs as ad-hoc global daemons.
@@ -112,8 +127,8 @@ impl Run {
                 ReadyCheckType::Port(port)
             } else if let Some(ref cmd) = self.cmd {
                 ReadyCheckType::Cmd(cmd.clone())
-            } else if let Some(secs) = self.delay {
-                ReadyCheckType::Delay(secs)
+            } else if let Some(delay) = self.delay {
+                ReadyCheckType::Delay(delay)
             } else {
                 ReadyCheckType::Default
             };
@@ -124,15 +139,26 @@ impl Run {
 
         // Start streaming logs for this daemon
         let (log_stop_tx, log_handle) = if let Some(ref job) = job {
-            let (tx, handle) = stream_startup_logs(&daemon_id, job.clone());
+            let (tx, handle) = stream_startup_logs(&daemon_id, job.clone(), ShowOutput::All);
             (Some(tx), Some(handle))
         } else {
             (None, None)
         };
 
-        let result = ipc
-            .run_adhoc(daemon_id.clone(), self.run.clone(), env::CWD.clone(), opts)
-            .await;
+        let run = ipc.run_adhoc(daemon_id.clone(), self.run.clone(), env::CWD.clone(), opts);
+        let result = if self.ci {
+            // Nothing else would stop the daemon once this command is gone
+            tokio::select! {
+                result = run => result,
+                _ = tokio::signal::ctrl_c() => {
+                    clx::progress::stop();
+                    stop_ci_daemon(&ipc, &daemon_id).await;
+                    std::process::exit(130);
+                }
+            }
+        } else {
+            run.await
+        };
 
         match result {
             Ok(result) => {
@@ -149,6 +175,16 @@ impl Run {
                 clx::progress::stop();
                 clx::progress::clear_jobs();
 
+                if self.ci {
+                    let code = if result.exit_code.is_some() {
+                        1
+                    } else {
+                        self.run_then(&result.resolved_ports).await
+                    };
+                    stop_ci_daemon(&ipc, &daemon_id).await;
+                    std::process::exit(code);
+                }
+
                 if result.exit_code.is_some() {
                     std::process::exit(1);
                 }
@@ -165,10 +201,65 @@ impl Run {
                 update_job_with_result(job.as_deref(), &daemon_id, &Err(e));
                 clx::progress::stop();
                 clx::progress::clear_jobs();
+                if self.ci {
+                    stop_ci_daemon(&ipc, &daemon_id).await;
+                }
                 std::process::exit(1);
             }
         }
 
         Ok(())
     }
+
+    /// Run the `--then` command in the current directory and return its exit
+    /// code. The daemon's resolved ports are exported as `PORT`/`PORT0..N`,
+    /// matching what the daemon itself sees.
+    async fn run_then(&self, resolved_ports: &[u16]) -> i32 {
+        let Some(then) = &self.then else {
+            return 0;
+        };
+        let mut cmd = match hook_command(then) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                error!("{e}");
+                return 1;
+            }
+        };
+        cmd.current_dir(&*env::CWD);
+        if let Some(port) = resolved_ports.first() {
+            cmd.env("PORT", port.to_string());
+        }
+        for (i, port) in resolved_ports.iter().enumerate() {
+            cmd.env(format!("PORT{i}"), port.to_string());
+        }
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                error!("failed to run {then:?}: {e}");
+                return 1;
+            }
+        };
+        tokio::select! {
+            status = child.wait() => match status {
+                Ok(status) => status.code().unwrap_or(1),
+                Err(e) => {
+                    error!("failed to run {then:?}: {e}");
+                    1
+                }
+            },
+            // Interrupted: the caller stops the daemon
+            _ = tokio::signal::ctrl_c() => {
+                let _ = child.kill().await;
+                130
+            }
+        }
+    }
+}
+
+/// Stop the daemon started by `--ci`. Failures are logged rather than
+/// returned so the exit code still reflects the `--then` command.
+async fn stop_ci_daemon(ipc: &IpcClient, id: &crate::daemon_id::DaemonId) {
+    if let Err(e) = ipc.stop(id.clone()).await {
+        error!("failed to stop {id} after CI run: {e}");
+    }
 }
@@ -317,6 +317,11 @@ fn validate_setting_value(key: &str, value: &str) -> Result<()> {
         "Integer" if value.parse::<i64>().is_err() => {
             bail!("invalid integer value '{value}' for '{key}'. Expected a number");
         }
+        "Bytes" if parse_bytes_value(value).is_err() => {
+            bail!(
+                "invalid size '{value}' for '{key}'. Expected a size like '64KiB', '10MB' or a number of bytes"
+            );
+        }
         "Duration" if humantime::parse_duration(value).is_err() => {
             bail!(
                 "invalid duration value '{value}' for '{key}'. Expected a duration like '10s', '5m', '1h', '500ms'"
@@ -378,6 +383,8 @@ fn get_ipc_value(g: &crate::settings::SettingsIpc, field: &str) -> String {
         "request_timeout" => g.request_timeout.clone(),
         "rate_limit" => g.rate_limit.to_string(),
         "rate_limit_window" => g.rate_limit_window.clone(),
+        "compress_threshold" => g.compress_threshold.to_string(),
+        "chunk_size" => g.chunk_size.to_string(),
         _ => String::new(),
     }
 }
@@ -387,6 +394,8 @@ fn get_logs_value(g: &crate::settings::SettingsLogs, field: &str) -> String {
         "time_retention" => g.time_retention.clone(),
         "line_retention" => g.line_retention.to_string(),
         "log_format" => g.log_format.clone(),
+        "rate_limit" => g.rate_limit.to_string(),
+        "overflow" => g.overflow.clone(),
         _ => String::new(),
     }
 }
@@ -435,11 +444,17 @@ fn get_supervisor_value(g: &crate::settings::SettingsSupervisor, field: &str) ->
         "cron_check_interval" => g.cron_check_interval.clone(),
         "watch_interval" => g.watch_interval.clone(),
         "watch_poll_interval" => g.watch_poll_interval.clone(),
+        "watch_fallback_poll_interval" => g.watch_fallback_poll_interval.clone(),
         "http_client_timeout" => g.http_client_timeout.clone(),
+        "health_check_interval" => g.health_check_interval.clone(),
+        "drift_check_interval" => g.drift_check_interval.clone(),
+        "hook_timeout" => g.hook_timeout.clone(),
+        "ready_cmd_timeout" => g.ready_cmd_timeout.clone(),
         "port_bump_attempts" => g.port_bump_attempts.to_string(),
         "container" => g.container.to_string(),
         "cleanup_orphans" => g.cleanup_orphans.to_string(),
         "user" => g.user.clone(),
+        "socket_group" => g.socket_group.clone(),
         "cpu_violation_threshold" => g.cpu_violation_threshold.to_string(),
         _ => String::new(),
     }
@@ -504,6 +519,13 @@ fn parse_int_value(value: &str) -> Result<i64> {
         .map_err(|_| miette::miette!("invalid integer value '{value}'. Expected a number"))
 }
 
+fn parse_bytes_value(value: &str) -> Result<i64> {
+    crate::pitchfork_toml::parse_byte_size(value)
+        .ok()
+        .and_then(|n| i64::try_from(n).ok())
+        .ok_or_else(|| miette::miette!("invalid size '{value}'. Expected a size like '64KiB'"))
+}
+
 fn apply_general_value(
     partial: &mut crate::settings::SettingsGeneralPartial,
     field: &str,
@@ -538,6 +560,8 @@ fn apply_ipc_value(
         "request_timeout" => partial.request_timeout = Some(value.to_string()),
         "rate_limit" => partial.rate_limit = Some(parse_int_value(value)?),
         "rate_limit_window" => partial.rate_limit_window = Some(value.to_string()),
+        "compress_threshold" => partial.compress_threshold = Some(parse_bytes_value(value)?),
+        "chunk_size" => partial.chunk_size = Some(parse_bytes_value(value)?),
         _ => bail!("unknown ipc setting '{field}'"),
     }
     let _ = typ;
@@ -554,6 +578,8 @@ fn apply_logs_value(
         "time_retention" => partial.time_retention = Some(value.to_string()),
         "line_retention" => partial.line_retention = Some(parse_int_value(value)?),
         "log_format" => partial.log_format = Some(value.to_string()),
+        "rate_limit" => partial.rate_limit = Some(parse_bytes_value(value)?),
+        "overflow" => partial.overflow = Some(value.to_string()),
         _ => bail!("unknown logs setting '{field}'"),
     }
     let _ = typ;
@@ -630,11 +656,19 @@ fn apply_supervisor_value(
         "cron_check_interval" => partial.cron_check_interval = Some(value.to_string()),
         "watch_interval" => partial.watch_interval = Some(value.to_string()),
         "watch_poll_interval" => partial.watch_poll_interval = Some(value.to_string()),
+        "watch_fallback_poll_interval" => {
+            partial.watch_fallback_poll_interval = Some(value.to_string())
+        }
         "http_client_timeout" => partial.http_client_timeout = Some(value.to_string()),
+        "health_check_interval" => partial.health_check_interval = Some(value.to_string()),
+        "drift_check_interval" => partial.drift_check_interval = Some(value.to_string()),
+        "hook_timeout" => partial.hook_timeout = Some(value.to_string()),
+        "ready_cmd_timeout" => partial.ready_cmd_timeout = Some(value.to_string()),
         "port_bump_attempts" => partial.port_bump_attempts = Some(parse_int_value(value)?),
         "container" => partial.container = Some(parse_bool_value(value)?),
         "cleanup_orphans" => partial.cleanup_orphans = Some(parse_bool_value(value)?),
         "user" => partial.user = Some(value.to_string()),
+        "socket_group" => partial.socket_group = Some(value.to_string()),
         "cpu_violation_threshold" => {
             partial.cpu_violation_threshold = Some(parse_int_value(value)?)
         }
@@ -672,7 +706,7 @@ fn apply_proxy_value(
     Ok(())
 }
 
-fn levenshtein_distance(a: &str, b: &str) -> usize {
+pub(crate) fn levenshtein_distance(a: &str, b: &str) -> usize {
     let a_len = a.chars().count();
     let b_len = b.chars().count();
     if a_len == 0 {
@@ -709,7 +743,7 @@ fn levenshtein_distance(a: &str, b: &str) -> usize {
 /// silently succeeds (settings will be fresh on next supervisor start).
 async fn notify_supervisor_reload() {
     use crate::ipc::client::IpcClient;
-    match IpcClient::connect(false).await {
+    match IpcClient::shared(false).await {
         Ok(ipc) => {
             if let Err(e) = ipc.reload_config().await {
                 debug!("failed to notify supervisor of config reload: {e}");
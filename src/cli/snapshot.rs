@@ -0,0 +1,375 @@
+use crate::daemon::Daemon;
+use crate::daemon_id::DaemonId;
+use crate::error::FileError;
+use crate::ipc::batch::{StartOptions, update_job_with_result};
+use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::PitchforkToml;
+use crate::state_file::StateFile;
+use crate::ui::table::{Column, ColumnTable};
+use crate::{Result, env};
+use chrono::{DateTime, Local};
+use comfy_table::Cell;
+use miette::{IntoDiagnostic, bail, ensure};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// Save and restore the set of running daemons
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Save and restore the set of running daemons
+
+A snapshot records which daemons are running and the options they were
+started with. Restoring it brings the machine back to exactly that set:
+daemons missing from it are started and daemons not in it are stopped.
+
+Useful when switching between projects, or to bring a working set back
+after a reboot without configuring boot_start.
+
+Examples:
+  pitchfork snapshot save work    Save the running daemons as 'work'
+  pitchfork snapshot restore work Start/stop daemons to match 'work'
+  pitchfork snapshot list         List saved snapshots"
+)]
+pub struct Snapshot {
+    #[clap(subcommand)]
+    command: SnapshotCommands,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum SnapshotCommands {
+    Save(Save),
+    Restore(Restore),
+    List(List),
+}
+
+/// Save the currently running daemons as a named snapshot
+#[derive(Debug, clap::Args)]
+pub struct Save {
+    /// Name of the snapshot
+    name: String,
+    /// Overwrite an existing snapshot with the same name
+    #[clap(short, long)]
+    force: bool,
+}
+
+/// Start and stop daemons so the running set matches a snapshot
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Restore {
+    /// Name of the snapshot
+    name: String,
+    /// Only start missing daemons; leave daemons not in the snapshot running
+    #[clap(long)]
+    keep_extra: bool,
+}
+
+/// List saved snapshots
+#[derive(Debug, clap::Args)]
+pub struct List {
+    /// Output in JSON format
+    #[clap(long)]
+    json: bool,
+}
+
+/// On-disk snapshot, stored as `<state dir>/snapshots/<name>.toml`.
+///
+/// Daemons are stored in the same shape as the state file, with runtime-only
+/// fields (pid, status, resolved ports, ...) cleared.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct SnapshotFile {
+    created_at: DateTime<Local>,
+    #[serde(default)]
+    daemons: BTreeMap<DaemonId, Daemon>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct SnapshotSummary {
+    name: String,
+    created_at: DateTime<Local>,
+    daemons: Vec<String>,
+}
+
+impl Snapshot {
+    pub async fn run(&self) -> Result<()> {
+        match &self.command {
+            SnapshotCommands::Save(save) => save.run().await,
+            SnapshotCommands::Restore(restore) => restore.run().await,
+            SnapshotCommands::List(list) => list.run().await,
+        }
+    }
+}
+
+fn snapshots_dir() -> PathBuf {
+    env::PITCHFORK_STATE_DIR.join("snapshots")
+}
+
+/// Snapshot names become file names, so keep them to a safe character set.
+fn snapshot_path(name: &str) -> Result<PathBuf> {
+    ensure!(
+        !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')),
+        "invalid snapshot name {name:?}: use letters, digits, '-', '_' and '.'"
+    );
+    Ok(snapshots_dir().join(format!("{name}.toml")))
+}
+
+/// The options needed to start the daemon again. Its ports are resolved
+/// again when it starts.
+fn snapshot_daemon(daemon: Daemon) -> Daemon {
+    Daemon {
+        resolved_port: vec![],
+        ..daemon.without_runtime_state()
+    }
+}
+
+impl SnapshotFile {
+    fn read(name: &str) -> Result<Self> {
+        let path = snapshot_path(name)?;
+        if !path.exists() {
+            bail!("snapshot {name:?} not found (see `pitchfork snapshot list`)");
+        }
+        let raw = xx::file::read_to_string(&path)?;
+        toml::from_str(&raw)
+            .map_err(|e| miette::miette!("failed to parse snapshot {}: {e}", path.display()))
+    }
+
+    fn write(&self, name: &str) -> Result<()> {
+        let path = snapshot_path(name)?;
+        let raw = toml::to_string(self).map_err(|e| FileError::SerializeError {
+            path: path.clone(),
+            source: e,
+        })?;
+        StateFile::write_raw(&path, &raw)
+    }
+}
+
+impl Save {
+    pub async fn run(&self) -> Result<()> {
+        let path = snapshot_path(&self.name)?;
+        if path.exists() && !self.force {
+            bail!(
+                "snapshot {:?} already exists, use --force to overwrite",
+                self.name
+            );
+        }
+
+        let ipc = IpcClient::shared(true).await?;
+        let daemons: BTreeMap<DaemonId, Daemon> = ipc
+            .active_daemons()
+            .await?
+            .into_iter()
+            .filter(|d| d.status.is_up() || d.status.is_waiting())
+            .map(|d| (d.id.clone(), snapshot_daemon(d)))
+            .collect();
+
+        if daemons.is_empty() {
+            warn!("No daemons are running, saving an empty snapshot");
+        }
+
+        let count = daemons.len();
+        SnapshotFile {
+            created_at: Local::now(),
+            daemons,
+        }
+        .write(&self.name)?;
+        info!("saved {count} daemon(s) to snapshot {}", self.name);
+        Ok(())
+    }
+}
+
+impl Restore {
+    pub async fn run(&self) -> Result<()> {
+        let snapshot = SnapshotFile::read(&self.name)?;
+        let ipc = IpcClient::shared(true).await?;
+        let running: HashSet<DaemonId> = ipc.get_running_daemons().await?.into_iter().collect();
+        let mut any_failed = false;
+
+        if !self.keep_extra {
+            let extra: Vec<DaemonId> = running
+                .iter()
+                .filter(|id| !snapshot.daemons.contains_key(*id))
+                .cloned()
+                .collect();
+            if !extra.is_empty() {
+                any_failed |= ipc.stop_daemons(&extra).await?.any_failed;
+            }
+        }
+
+        // Daemons that are still configured start from their current config,
+        // with dependency ordering. Ad-hoc daemons (`pitchfork run`) only exist
+        // in the snapshot, so they are started from the recorded options.
+        let pt = PitchforkToml::all_merged_all_namespaces()?;
+        let (configured, adhoc): (Vec<&Daemon>, Vec<&Daemon>) = snapshot
+            .daemons
+            .values()
+            .filter(|d| !running.contains(&d.id))
+            .partition(|d| pt.daemons.contains_key(&d.id));
+
+        if !configured.is_empty() {
+            let ids: Vec<DaemonId> = configured.iter().map(|d| d.id.clone()).collect();
+            let result = ipc.start_daemons(&ids, StartOptions::default()).await?;
+            for update in &result.pending_job_updates {
+                update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+            }
+            clx::progress::stop();
+            clx::progress::clear_jobs();
+            any_failed |= result.any_failed;
+        }
+
+        for daemon in adhoc {
+            let Some(cmd) = daemon.cmd.clone() else {
+                warn!("Ad-hoc daemon {} has no saved command, skipping", daemon.id);
+                continue;
+            };
+            let mut opts = daemon.to_run_options(cmd);
+            opts.wait_ready = true;
+            match ipc.run(opts).await {
+                Ok(result) if result.started => info!("started {}", daemon.id),
+                Ok(result) => {
+                    any_failed = true;
+                    match result.error_message {
+                        Some(msg) => error!("Daemon {} failed to start: {msg}", daemon.id),
+                        None => error!("Daemon {} failed to start", daemon.id),
+                    }
+                }
+                Err(e) => {
+                    any_failed = true;
+                    error!("Daemon {} failed to start: {e}", daemon.id);
+                }
+            }
+        }
+
+        super::drain_notifications(&ipc).await;
+
+        if any_failed {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+impl List {
+    pub async fn run(&self) -> Result<()> {
+        let mut snapshots = vec![];
+        let dir = snapshots_dir();
+        if dir.exists() {
+            for entry in std::fs::read_dir(&dir).into_diagnostic()? {
+                let path = entry.into_diagnostic()?.path();
+                if path.extension().is_none_or(|ext| ext != "toml") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                match SnapshotFile::read(name) {
+                    Ok(snapshot) => snapshots.push(SnapshotSummary {
+                        name: name.to_string(),
+                        created_at: snapshot.created_at,
+                        daemons: snapshot.daemons.keys().map(|id| id.qualified()).collect(),
+                    }),
+                    Err(e) => warn!("{e}"),
+                }
+            }
+        }
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if self.json {
+            return crate::cli::json_output::print_json(&snapshots);
+        }
+
+        ColumnTable::new(SNAPSHOT_COLUMNS, &[], &["name", "created", "daemons"])?
+            .header(console::user_attended())
+            .print(snapshots)
+    }
+}
+
+const SNAPSHOT_COLUMNS: &[Column<SnapshotSummary>] = &[
+    Column {
+        name: "name",
+        header: "NAME",
+        cell: |s| Cell::new(&s.name),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "created",
+        header: "CREATED",
+        cell: |s| Cell::new(s.created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+        sort_key: |_| None,
+    },
+    Column {
+        name: "daemons",
+        header: "DAEMONS",
+        cell: |s| Cell::new(s.daemons.join(", ")),
+        sort_key: |_| None,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_path_rejects_unsafe_names() {
+        assert!(snapshot_path("work").is_ok());
+        assert!(snapshot_path("client-a_v2.1").is_ok());
+        assert!(snapshot_path("").is_err());
+        assert!(snapshot_path("../etc").is_err());
+        assert!(snapshot_path(".hidden").is_err());
+        assert!(snapshot_path("a/b").is_err());
+        assert!(snapshot_path("with space").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_daemon_clears_runtime_fields() {
+        let daemon = Daemon {
+            id: DaemonId::new("proj", "api"),
+            pid: Some(1234),
+            shell_pid: Some(99),
+            cmd: Some(vec!["./server".into()]),
+            retry_count: 2,
+            resolved_port: vec![3000],
+            active_port: Some(3000),
+            next_retry_at: Some(Local::now()),
+            owner: Some("alice".into()),
+            drift: Some("binary replaced".into()),
+            ..Default::default()
+        };
+        let snap = snapshot_daemon(daemon);
+        assert_eq!(snap.pid, None);
+        assert_eq!(snap.shell_pid, None);
+        assert_eq!(snap.retry_count, 0);
+        assert!(snap.resolved_port.is_empty());
+        assert_eq!(snap.active_port, None);
+        assert_eq!(snap.next_retry_at, None);
+        assert_eq!(snap.owner, None);
+        assert_eq!(snap.drift, None);
+        assert_eq!(snap.cmd, Some(vec!["./server".to_string()]));
+    }
+
+    #[test]
+    fn test_snapshot_file_roundtrip() {
+        let id = DaemonId::new("proj", "api");
+        let mut daemons = BTreeMap::new();
+        daemons.insert(
+            id.clone(),
+            snapshot_daemon(Daemon {
+                id: id.clone(),
+                cmd: Some(vec!["./server".into()]),
+                run: Some("./server".into()),
+                ..Default::default()
+            }),
+        );
+        let file = SnapshotFile {
+            created_at: Local::now(),
+            daemons,
+        };
+        let raw = toml::to_string(&file).unwrap();
+        let parsed: SnapshotFile = toml::from_str(&raw).unwrap();
+        assert_eq!(parsed.daemons[&id].run.as_deref(), Some("./server"));
+    }
+}
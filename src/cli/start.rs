@@ -1,20 +1,32 @@
 use crate::Result;
 use crate::cli::list::build_proxy_url;
+use crate::cli::logs::ShowOutput;
 use crate::daemon_id::DaemonId;
-use crate::ipc::batch::{StartOptions, update_job_with_result};
+use crate::ipc::batch::{
+    ConflictResolution, PortConflict, ReadyReport, SkipReason, StartOptions, StartResult,
+    update_job_with_result,
+};
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::PitchforkToml;
 use crate::settings::settings;
+use crate::ui::prompt;
 use crate::ui::style::{ncyan, ndim};
+use crate::ui::table::{Column, ColumnTable};
+use comfy_table::{Cell, Color};
 use miette::ensure;
-use std::sync::Arc;
 
 /// Shared long help for the `start` command and its implicit fallback form.
 pub(crate) const LONG_ABOUT: &str = "\
 Starts a daemon from a pitchfork.toml file
 
 Daemons are defined in pitchfork.toml with a `[daemons.<name>]` section.
-The command waits for the daemon to be ready before returning.
+The command waits for the daemon to be ready before returning, showing
+how long it has been waiting. When the ready checks give up, the daemon
+fails with the checks it was waiting for and its last line of output.
+
+Daemons that don't depend on each other start and wait for readiness at
+the same time. After starting several daemons, a report lists how long
+each took to become ready, or the check it failed and its last error.
 
 Examples:
   pitchfork start api           Start a single daemon
@@ -24,13 +36,34 @@ Examples:
   pitchfork start -g            Start all global daemons in config.toml
   pitchfork start -a            Start all daemons (local and global)
   pitchfork start api -f        Restart daemon if already running
+  pitchfork start -a --only-stopped
+                                Start only the daemons that aren't running
+  pitchfork start -a --except db,cache
+                                Start all daemons but db and cache
   pitchfork start api --delay 5 Wait 5 seconds for daemon to be ready
   pitchfork start api --output 'Listening on'
                                 Wait for output pattern before ready
   pitchfork start api --http http://localhost:8080/health
                                 Wait for HTTP endpoint to return 2xx
   pitchfork start api --port 8080
-                                Wait for TCP port to be listening";
+                                Wait for TCP port to be listening
+  pitchfork start api --wait-timeout 30s
+                                Fail if the daemon isn't ready in 30s
+  pitchfork start api --kill-conflicts
+                                Kill whatever holds the daemon's ports
+  pitchfork start api --env DEBUG=1 --env PORT=4000
+                                Override env vars for this run only
+  pitchfork start -a --show-output errors
+                                Only print stderr and error lines
+                                while waiting
+
+Daemons left out because they are disabled, already running or excluded
+with --except are listed in the report as skipped, with the reason.
+Excluded daemons aren't started as dependencies either.
+
+When a port the daemon needs is taken and the terminal is interactive,
+pitchfork offers to kill the process holding it or to move the daemon
+to the next free port.";
 
 /// Starts a daemon from a pitchfork.toml file
 #[derive(Debug, clap::Args)]
@@ -82,9 +115,17 @@ pub struct Start {
     /// Stop the daemon if it is already running
     #[clap(short, long)]
     force: bool,
-    /// Delay in seconds before considering daemon ready (default: 3 seconds)
-    #[clap(long)]
-    delay: Option<u64>,
+    /// Skip daemons that are already running instead of restarting them
+    #[clap(long, conflicts_with = "force")]
+    only_stopped: bool,
+    /// Daemons not to start, even as dependencies (comma-separated or
+    /// specified multiple times)
+    #[clap(long, value_name = "ID", value_delimiter = ',')]
+    except: Vec<String>,
+    /// Delay before considering daemon ready, in seconds or as a duration
+    /// like "1500ms" (default: 3 seconds)
+    #[clap(long, value_parser = crate::pitchfork_toml::parse_duration)]
+    delay: Option<std::time::Duration>,
     /// Wait until output matches this regex pattern before considering daemon ready
     #[clap(long)]
     output: Option<String>,
@@ -97,6 +138,10 @@ pub struct Start {
     /// Shell command to poll for readiness (exit code 0 = ready)
     #[clap(long)]
     cmd: Option<String>,
+    /// Give up on the ready checks after this long, in seconds or as a
+    /// duration like "30s", and stop the daemon
+    #[clap(long, value_name = "DURATION", value_parser = crate::pitchfork_toml::parse_duration)]
+    wait_timeout: Option<std::time::Duration>,
     /// Ports the daemon is expected to bind to (can be specified multiple times)
     #[clap(long, value_delimiter = ',')]
     expected_port: Vec<u16>,
@@ -106,6 +151,26 @@ pub struct Start {
     /// Suppress startup log output
     #[clap(short, long)]
     quiet: bool,
+    /// Which of the daemon's output lines to print while waiting for it to
+    /// be ready
+    #[clap(long, value_name = "LINES", default_value = "all")]
+    show_output: ShowOutput,
+    /// Kill processes holding the daemon's ports instead of failing
+    #[clap(long)]
+    kill_conflicts: bool,
+    /// Set an environment variable for this run only, on top of the daemon's
+    /// configured env (can be specified multiple times)
+    #[clap(long, value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(String, String)>,
+}
+
+fn parse_env_var(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!(
+            "Invalid environment variable format: {s}. Expected KEY=value"
+        )),
+    }
 }
 
 impl Start {
@@ -115,7 +180,7 @@ impl Start {
             "At least one daemon ID, --group, or one of --all / --local / --global must be provided"
         );
 
-        let ipc = Arc::new(IpcClient::connect(true).await?);
+        let ipc = IpcClient::shared(true).await?;
 
         // Compute daemon IDs to start
         let ids: Vec<DaemonId> = if self.all {
@@ -141,6 +206,7 @@ impl Start {
             http: self.http.clone(),
             port: self.port,
             cmd: self.cmd.clone(),
+            wait_timeout: self.wait_timeout,
             expected_port: (!self.expected_port.is_empty()).then_some(self.expected_port.clone()),
             auto_bump_port: match self.bump {
                 None => None,
@@ -150,21 +216,46 @@ impl Start {
                 Some(Some(n)) => Some(crate::config_types::PortBump(n)),
             },
             quiet: self.quiet,
+            show_output: self.show_output,
+            kill_conflicts: self.kill_conflicts,
+            env: (!self.env.is_empty()).then(|| self.env.iter().cloned().collect()),
+            only_stopped: self.only_stopped,
+            except: PitchforkToml::resolve_ids(&self.except)?,
             ..Default::default()
         };
 
-        let result = ipc.start_daemons(&ids, opts).await?;
+        let mut result = ipc.start_daemons(&ids, opts.clone()).await?;
+        finish_jobs(&result);
 
-        // Apply all deferred job status updates.
-        // Log streaming was already stopped inside each spawn task,
-        // so println() won't race with the render thread here.
-        for update in &result.pending_job_updates {
-            update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+        // Offer to resolve port conflicts, then start again. Daemons that are
+        // running by now are skipped, so only the failed ones (and dependents
+        // that were never started) are retried.
+        let conflicts = result.port_conflicts();
+        if !conflicts.is_empty()
+            && !self.quiet
+            && prompt::interactive()
+            && let Some(resolution) = ask_conflict_resolution(&conflicts)
+        {
+            let mut retry_opts = StartOptions {
+                force: false,
+                ..opts
+            };
+            resolution.apply(&mut retry_opts);
+            let retry = ipc.start_daemons(&ids, retry_opts).await?;
+            finish_jobs(&retry);
+            result.started.extend(retry.started);
+            result.any_failed = retry.any_failed;
+            // The retry's outcome replaces the first attempt's in the report
+            result
+                .pending_job_updates
+                .retain(|u| !retry.pending_job_updates.iter().any(|r| r.id == u.id));
+            result.pending_job_updates.extend(retry.pending_job_updates);
         }
 
-        // Stop progress display (renders final frame with all job statuses)
-        clx::progress::stop();
-        clx::progress::clear_jobs();
+        let report = result.ready_report();
+        if !self.quiet && report.len() + result.skipped.len() > 1 {
+            print_ready_report(&report, &result.skipped)?;
+        }
 
         // Show proxy URLs for successful daemons (unless --quiet)
         if !self.quiet {
@@ -193,8 +284,11 @@ impl Start {
         }
 
         // Surface any pending supervisor notifications (e.g. proxy bind failure)
-        // so the user sees them immediately after starting daemons.
-        super::drain_notifications(&ipc).await;
+        // so the user sees them immediately after starting daemons. Starts from
+        // the shell hook leave them for `pitchfork cd` to sum up.
+        if self.shell_pid.is_none() {
+            super::drain_notifications(&ipc).await;
+        }
 
         if result.any_failed {
             std::process::exit(1);
@@ -202,3 +296,110 @@ impl Start {
         Ok(())
     }
 }
+
+/// Apply deferred job status updates and stop the progress display.
+///
+/// Log streaming was already stopped inside each spawn task, so println()
+/// won't race with the render thread here.
+fn finish_jobs(result: &StartResult) {
+    for update in &result.pending_job_updates {
+        update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+    }
+    // Renders the final frame with all job statuses
+    clx::progress::stop();
+    clx::progress::clear_jobs();
+}
+
+/// Print how long each daemon took to become ready, or why it didn't,
+/// followed by the daemons that weren't started.
+fn print_ready_report(report: &[ReadyReport], skipped: &[(DaemonId, SkipReason)]) -> Result<()> {
+    let rows = report
+        .iter()
+        .map(ReportRow::Started)
+        .chain(
+            skipped
+                .iter()
+                .map(|(id, reason)| ReportRow::Skipped(id, reason)),
+        )
+        .collect();
+    let columns = report_columns();
+    ColumnTable::new(
+        &columns,
+        &[],
+        &["name", "ready", "failed_check", "last_error"],
+    )?
+    .header(console::user_attended())
+    .print(rows)
+}
+
+enum ReportRow<'a> {
+    Started(&'a ReadyReport),
+    Skipped(&'a DaemonId, &'a SkipReason),
+}
+
+/// The columns of the ready report. A function rather than a const so the
+/// rows can borrow from the start result.
+fn report_columns<'a>() -> [Column<ReportRow<'a>>; 4] {
+    [
+        Column {
+            name: "name",
+            header: "Name",
+            cell: |row| match row {
+                ReportRow::Started(entry) => Cell::new(entry.id.styled_qualified()),
+                ReportRow::Skipped(id, _) => Cell::new(id.styled_qualified()),
+            },
+            sort_key: |_| None,
+        },
+        Column {
+            name: "ready",
+            header: "Ready",
+            cell: |row| match row {
+                ReportRow::Started(entry) => match entry.ready_after {
+                    Some(d) => Cell::new(format!("{:.1}s", d.as_secs_f64())).fg(Color::Green),
+                    None => Cell::new("failed").fg(Color::Red),
+                },
+                ReportRow::Skipped(_, reason) => {
+                    Cell::new(format!("skipped ({reason})")).fg(Color::DarkGrey)
+                }
+            },
+            sort_key: |_| None,
+        },
+        Column {
+            name: "failed_check",
+            header: "Failed check",
+            cell: |row| match row {
+                ReportRow::Started(entry) => Cell::new(entry.failed_check.as_deref().unwrap_or("")),
+                ReportRow::Skipped(..) => Cell::new(""),
+            },
+            sort_key: |_| None,
+        },
+        Column {
+            name: "last_error",
+            header: "Last error",
+            cell: |row| match row {
+                ReportRow::Started(entry) => Cell::new(entry.last_error.as_deref().unwrap_or("")),
+                ReportRow::Skipped(..) => Cell::new(""),
+            },
+            sort_key: |_| None,
+        },
+    ]
+}
+
+fn ask_conflict_resolution(conflicts: &[(DaemonId, PortConflict)]) -> Option<ConflictResolution> {
+    for (id, conflict) in conflicts {
+        eprintln!("{} {id}: {conflict}", ndim("↳"));
+    }
+    let choice = prompt::choose(
+        "How should the port conflict be resolved?",
+        &[
+            ('a', "abort"),
+            ('k', "kill the process and start again"),
+            ('b', "use the next free port"),
+        ],
+    )?;
+    match choice {
+        'k' => Some(ConflictResolution::Kill),
+        'b' => Some(ConflictResolution::Bump),
+        _ => None,
+    }
+}
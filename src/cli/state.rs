@@ -0,0 +1,278 @@
+use crate::daemon::Daemon;
+use crate::daemon_id::DaemonId;
+use crate::ipc::batch::{StartOptions, update_job_with_result};
+use crate::ipc::client::IpcClient;
+use crate::pitchfork_toml::PitchforkToml;
+use crate::procs::PROCS;
+use crate::state_file::StateFile;
+use crate::{Result, env};
+use chrono::{DateTime, Local};
+use miette::{IntoDiagnostic, bail, ensure};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Version of the export format, bumped on incompatible changes
+const EXPORT_VERSION: u32 = 1;
+
+/// Export and import daemon state to move it to another machine
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Export and import daemon state to move it to another machine
+
+An export holds every daemon pitchfork knows about with the options it was
+started with, the disabled sets, the ports each daemon was given, and which
+daemons were running. Process details (PIDs, hosts, exit history) are left
+out, since they only mean something on the machine they came from.
+
+Importing validates each daemon against the pitchfork.toml files available
+here: daemons no config defines are skipped unless --force is passed. The
+supervisor must be stopped, since it would overwrite the imported state.
+
+Examples:
+  pitchfork state export > state.json     Save state to a file
+  pitchfork state import state.json       Restore it on the new machine
+  pitchfork state import --start state.json
+                                          ...and start the daemons that were running
+  cat state.json | pitchfork state import -"
+)]
+pub struct State {
+    #[clap(subcommand)]
+    command: StateCommands,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum StateCommands {
+    Export(Export),
+    Import(Import),
+}
+
+/// Print the daemon state as JSON
+#[derive(Debug, clap::Args)]
+pub struct Export {}
+
+/// Restore daemon state from an export
+#[derive(Debug, clap::Args)]
+pub struct Import {
+    /// Export file to read, or `-` for stdin
+    file: PathBuf,
+    /// Import daemons that no pitchfork.toml defines
+    #[clap(short, long)]
+    force: bool,
+    /// Start the daemons that were running when the state was exported
+    #[clap(long)]
+    start: bool,
+}
+
+/// The `state export` format
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct StateExport {
+    version: u32,
+    exported_at: DateTime<Local>,
+    #[serde(default)]
+    daemons: BTreeMap<DaemonId, Daemon>,
+    #[serde(default)]
+    disabled: BTreeSet<DaemonId>,
+    #[serde(default)]
+    auto_disabled: BTreeSet<DaemonId>,
+    /// Daemons that were running or starting when the state was exported
+    #[serde(default)]
+    running: BTreeSet<DaemonId>,
+}
+
+impl State {
+    pub async fn run(&self) -> Result<()> {
+        match &self.command {
+            StateCommands::Export(export) => export.run().await,
+            StateCommands::Import(import) => import.run().await,
+        }
+    }
+}
+
+impl Export {
+    pub async fn run(&self) -> Result<()> {
+        let state = StateFile::read(&*env::PITCHFORK_STATE_FILE)?;
+        let pitchfork_id = DaemonId::pitchfork();
+        let daemons = state
+            .daemons
+            .into_values()
+            .filter(|d| d.id != pitchfork_id)
+            .collect::<Vec<_>>();
+        let export = StateExport {
+            version: EXPORT_VERSION,
+            exported_at: Local::now(),
+            running: daemons
+                .iter()
+                .filter(|d| d.status.is_up())
+                .map(|d| d.id.clone())
+                .collect(),
+            daemons: daemons
+                .into_iter()
+                .map(|d| (d.id.clone(), d.without_runtime_state()))
+                .collect(),
+            disabled: state.disabled,
+            auto_disabled: state.auto_disabled,
+        };
+        crate::cli::json_output::print_json(&export)
+    }
+}
+
+impl Import {
+    pub async fn run(&self) -> Result<()> {
+        let raw = if self.file.as_os_str() == "-" {
+            let mut raw = String::new();
+            std::io::stdin()
+                .read_to_string(&mut raw)
+                .into_diagnostic()?;
+            raw
+        } else {
+            xx::file::read_to_string(&self.file)?
+        };
+        let export: StateExport = serde_json::from_str(&raw)
+            .map_err(|e| miette::miette!("failed to parse state export: {e}"))?;
+        ensure!(
+            export.version == EXPORT_VERSION,
+            "unsupported state export version {} (expected {EXPORT_VERSION})",
+            export.version
+        );
+
+        let mut state = StateFile::read(&*env::PITCHFORK_STATE_FILE)?;
+        if let Some(pid) = state
+            .daemons
+            .get(&DaemonId::pitchfork())
+            .and_then(|d| d.local_pid())
+            && PROCS.is_running(pid)
+        {
+            bail!(
+                "the supervisor is running (pid {pid}) and would overwrite the imported state; \
+                 stop it with `pitchfork supervisor stop` first"
+            );
+        }
+
+        let pt = PitchforkToml::all_merged_all_namespaces()?;
+        let known = |id: &DaemonId| self.force || pt.daemons.contains_key(id);
+        let (daemons, unknown): (Vec<Daemon>, Vec<Daemon>) =
+            export.daemons.into_values().partition(|d| known(&d.id));
+        if !unknown.is_empty() {
+            let ids = unknown.iter().map(|d| d.id.qualified()).collect::<Vec<_>>();
+            warn!(
+                "skipping {} daemon(s) no pitchfork.toml defines here (use --force to import them): {}",
+                ids.len(),
+                ids.join(", ")
+            );
+        }
+
+        let mut imported = 0;
+        for daemon in daemons {
+            if state
+                .daemons
+                .get(&daemon.id)
+                .is_some_and(|d| d.status.is_up())
+            {
+                warn!("{} is already running here, keeping its state", daemon.id);
+                continue;
+            }
+            state.insert_daemon(&daemon.id.clone(), daemon.without_runtime_state());
+            imported += 1;
+        }
+        for id in export.disabled.iter().filter(|id| known(id)) {
+            state.disable_daemon(id);
+        }
+        for id in export.auto_disabled.iter().filter(|id| known(id)) {
+            state.disable_daemon_autostart(id);
+        }
+        state.write()?;
+        info!("imported {imported} daemon(s)");
+
+        if self.start {
+            let ids: Vec<DaemonId> = export
+                .running
+                .into_iter()
+                .filter(|id| pt.daemons.contains_key(id))
+                .collect();
+            if !ids.is_empty() {
+                let ipc = IpcClient::shared(true).await?;
+                let result = ipc.start_daemons(&ids, StartOptions::default()).await?;
+                for update in &result.pending_job_updates {
+                    update_job_with_result(update.job.as_deref(), &update.id, &update.run_result);
+                }
+                clx::progress::stop();
+                clx::progress::clear_jobs();
+                super::drain_notifications(&ipc).await;
+                if result.any_failed {
+                    std::process::exit(1);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_types::Retry;
+    use crate::daemon_status::DaemonStatus;
+
+    #[test]
+    fn test_export_daemon_keeps_ports() {
+        let now = Local::now();
+        let daemon = Daemon {
+            id: DaemonId::new("proj", "api"),
+            pid: Some(42),
+            host: Some("old-laptop".to_string()),
+            status: DaemonStatus::Errored(1),
+            resolved_port: vec![3001],
+            active_port: Some(3001),
+            run: Some("npm start".to_string()),
+            retry: Retry(3),
+            retry_count: 2,
+            next_retry_at: Some(now),
+            last_exit_success: Some(false),
+            owner: Some("alice".to_string()),
+            alert: Some("memory 1.6GB above 1.5GB".to_string()),
+            ..Default::default()
+        };
+        let exported = daemon.without_runtime_state();
+        assert_eq!(exported.pid, None);
+        assert_eq!(exported.host, None);
+        assert!(!exported.status.is_up());
+        assert!(!exported.status.is_errored());
+        assert_eq!(exported.active_port, None);
+        assert_eq!(exported.retry_count, 0);
+        assert_eq!(exported.next_retry_at, None);
+        assert_eq!(exported.last_exit_success, None);
+        assert_eq!(exported.owner, None);
+        assert_eq!(exported.alert, None);
+        assert_eq!(exported.retry, Retry(3));
+        assert_eq!(exported.resolved_port, vec![3001]);
+        assert_eq!(exported.run.as_deref(), Some("npm start"));
+    }
+
+    #[test]
+    fn test_export_round_trips_through_json() {
+        let id = DaemonId::new("proj", "api");
+        let export = StateExport {
+            version: EXPORT_VERSION,
+            exported_at: Local::now(),
+            daemons: BTreeMap::from([(
+                id.clone(),
+                Daemon {
+                    id: id.clone(),
+                    resolved_port: vec![3001],
+                    ..Default::default()
+                },
+            )]),
+            disabled: BTreeSet::from([id.clone()]),
+            auto_disabled: BTreeSet::new(),
+            running: BTreeSet::from([id.clone()]),
+        };
+        let json = serde_json::to_string(&export).unwrap();
+        let parsed: StateExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.daemons[&id].resolved_port, vec![3001]);
+        assert!(parsed.disabled.contains(&id));
+        assert!(parsed.running.contains(&id));
+    }
+}
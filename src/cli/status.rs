@@ -3,9 +3,18 @@ use crate::cli::json_output::{JsonStatusEntry, print_json};
 use crate::cli::list::build_proxy_url;
 use crate::daemon::Daemon;
 use crate::daemon_list::build_placeholder_daemon;
+use crate::daemon_status::DaemonStatus;
+use crate::ipc::client::{IpcClient, view_user};
 use crate::pitchfork_toml::PitchforkToml;
+use crate::procs::PROCS;
 use crate::settings::settings;
 use crate::state_file::StateFile;
+use crate::supervisor::{mise, users};
+use crate::ui::time;
+use chrono::Local;
+
+/// Exits listed under "Last exits"
+const RECENT_EXITS_SHOWN: usize = 5;
 
 /// Display the status of a daemon
 #[derive(Debug, clap::Args)]
@@ -18,13 +27,22 @@ Display the status of a daemon
 Shows detailed information about a single daemon including its PID and
 current status (running, stopped, failed, etc.).
 
-Example:
+The exit code reflects the daemon's health, so scripts can gate on it:
+  0  running, or a task that completed
+  1  stopped or not ready yet (starting, waiting, stopping, not started)
+  2  errored, failed or unhealthy
+
+Examples:
   pitchfork status api
+  pitchfork status -q api && curl localhost:3000
+                                 Only continue if api is running
 
 Output:
   Name: api
   PID: 12345
-  Status: running"
+  Status: running
+  Uptime: 3m 12s
+  Startup: ready in 4.2s (p95 6.1s)"
 )]
 pub struct Status {
     /// Name of the daemon to check
@@ -32,6 +50,33 @@ pub struct Status {
     /// Output in JSON format
     #[clap(long)]
     json: bool,
+    /// Print nothing, only set the exit code
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+/// The exit code for a daemon's status: 0 when running (or a completed
+/// task), 1 when stopped or not ready yet, 2 when errored or unhealthy.
+fn exit_code(daemon: &Daemon, is_available: bool) -> i32 {
+    if is_available {
+        return 1;
+    }
+    match daemon.status {
+        DaemonStatus::Running | DaemonStatus::Detached => 0,
+        DaemonStatus::Stopped if daemon.is_completed() => 0,
+        DaemonStatus::Errored(_) | DaemonStatus::Failed(_) | DaemonStatus::Unhealthy => 2,
+        DaemonStatus::Stopped
+        | DaemonStatus::Starting
+        | DaemonStatus::Waiting
+        | DaemonStatus::Stopping => 1,
+    }
+}
+
+fn exit(code: i32) -> Result<()> {
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
 }
 
 impl Status {
@@ -44,17 +89,53 @@ impl Status {
             .unwrap_or_default();
 
         // Try state file first, then fall back to config for "available" daemons.
-        let (daemon, is_available): (Daemon, bool) =
-            match StateFile::get().daemons.get(&qualified_id) {
-                Some(d) => (d.clone(), d.config_registered),
-                None => {
-                    let config = PitchforkToml::all_merged_all_namespaces()?;
-                    match config.daemons.get(&qualified_id) {
-                        Some(dc) => (build_placeholder_daemon(&qualified_id, dc), true),
-                        None => miette::bail!("Daemon {} not found", qualified_id),
-                    }
+        // Users of a shared supervisor get their daemons over IPC instead.
+        let state_daemon = if users::shared() || view_user().is_some() {
+            let ipc = IpcClient::shared(false).await?;
+            let (daemons, _) = ipc.user_daemons().await?;
+            daemons.into_iter().find(|d| d.id == qualified_id)
+        } else {
+            StateFile::get().daemons.get(&qualified_id).cloned()
+        };
+        let (daemon, is_available, mise_task): (Daemon, bool, Option<String>) = match state_daemon {
+            Some(d) => {
+                let is_available = d.config_registered;
+                let mise_task = d.run.as_deref().and_then(mise::task_name);
+                (d, is_available, mise_task)
+            }
+            None => {
+                let config = PitchforkToml::all_merged_all_namespaces()?;
+                match config.daemons.get(&qualified_id) {
+                    Some(dc) => (
+                        build_placeholder_daemon(&qualified_id, dc),
+                        true,
+                        mise::task_name(&dc.run),
+                    ),
+                    None => miette::bail!("Daemon {} not found", qualified_id),
                 }
-            };
+            }
+        };
+
+        let code = exit_code(&daemon, is_available);
+        if self.quiet {
+            return exit(code);
+        }
+
+        let now = Local::now();
+        let uptime_secs = daemon
+            .local_pid()
+            .filter(|_| daemon.status.is_up())
+            .and_then(|pid| {
+                PROCS
+                    .refresh_and_get_batch_stats(&[pid])
+                    .get(&pid)
+                    .map(|stats| stats.uptime_secs)
+            });
+        let next_cron = daemon
+            .cron_schedule
+            .as_deref()
+            .and_then(|schedule| time::next_cron_fire(schedule, now));
+        let retry = daemon.retry_summary(now);
 
         if self.json {
             let s = settings();
@@ -75,24 +156,97 @@ impl Status {
                 status: if is_available {
                     "available".to_string()
                 } else {
-                    daemon.status.to_string()
+                    daemon.status_label()
                 },
                 active_port: daemon.active_port,
                 port: daemon.resolved_port.clone(),
                 proxy_url,
+                host: daemon.remote_host().map(str::to_string),
+                owner: daemon.owner.clone(),
+                mise_task: mise_task.clone(),
+                uptime_secs,
+                uptime: uptime_secs.map(time::duration_secs),
+                next_cron_secs: next_cron.map(|next| time::secs_between(now, next)),
+                next_cron: next_cron.map(|next| time::until(next, now)),
+                log_lines_filtered: (daemon.log_lines_filtered > 0)
+                    .then_some(daemon.log_lines_filtered),
+                log_lines_dropped: (daemon.log_lines_dropped > 0)
+                    .then_some(daemon.log_lines_dropped),
+                waiting_for: daemon.waiting_for.clone(),
+                next_retry_secs: retry
+                    .as_ref()
+                    .and(daemon.next_retry_at)
+                    .map(|at| time::secs_between(now, at)),
+                next_retry: retry.clone(),
+                alert: daemon.alert.clone(),
+                drift: daemon.drift.clone(),
+                watch_fallback: daemon.watch_fallback.clone(),
+                exits: daemon.exits.iter().rev().cloned().collect(),
+                startup: daemon.startup_summary(),
+                ready_p95_ms: daemon.ready_p95_ms(),
+                startups: daemon.startups.iter().rev().cloned().collect(),
             };
-            return print_json(&entry);
+            print_json(&entry)?;
+            return exit(code);
         }
 
         println!("Name: {qualified_id}");
         if let Some(pid) = &daemon.pid {
             println!("PID: {pid}");
         }
+        if let Some(host) = daemon.remote_host() {
+            println!("Host: {host}");
+        }
+        if let Some(owner) = &daemon.owner {
+            println!("Owner: {owner}");
+        }
         if is_available {
             println!("Status: available");
+        } else if daemon.is_completed() {
+            println!("Status: {}", console::style("completed").blue());
         } else {
             println!("Status: {}", daemon.status.style());
         }
+        if let Some(task) = &mise_task {
+            println!("mise task: {task}");
+        }
+        if let Some(req) = &daemon.waiting_for {
+            println!("Waiting for: {req}");
+        }
+        if let Some(retry) = &retry {
+            println!("Retry: {retry}");
+        }
+        if let Some(alert) = &daemon.alert {
+            println!("Alert: {}", console::style(alert).yellow());
+        }
+        if let Some(drift) = &daemon.drift {
+            println!(
+                "Stale: {}",
+                console::style(format!("{drift}, restart recommended")).yellow()
+            );
+        }
+        if let Some(reason) = &daemon.watch_fallback {
+            println!(
+                "Watch: {}",
+                console::style(format!("polling ({reason})")).yellow()
+            );
+        }
+        if let Some(secs) = uptime_secs {
+            println!("Uptime: {}", time::duration_secs(secs));
+        }
+        if let Some(startup) = daemon.startup_summary() {
+            println!("Startup: {startup}");
+        }
+        if let Some(last) = daemon.last_cron_triggered {
+            println!("Last cron run: {}", time::ago(last, now));
+        }
+        if let Some(next) = next_cron {
+            println!(
+                "Next cron run: {} ({})",
+                time::until(next, now),
+                next.format("%Y-%m-%d %H:%M:%S")
+            );
+        }
         if let Some(port) = daemon.active_port {
             println!("Port: {port} (active)");
         } else if !daemon.resolved_port.is_empty() {
@@ -104,6 +258,34 @@ impl Status {
                 .join(", ");
             println!("Port: {ports}");
         }
+        if let Some(env_override) = &daemon.env_override {
+            let keys = env_override.keys().cloned().collect::<Vec<_>>().join(", ");
+            match daemon.env_override_until {
+                Some(until) => {
+                    println!("Env override (until {}): {keys}", until.format("%H:%M:%S"))
+                }
+                None => println!("Env override (this run): {keys}"),
+            }
+        }
+        if daemon.log_lines_filtered > 0 {
+            println!("Filtered log lines: {}", daemon.log_lines_filtered);
+        }
+        if daemon.log_lines_dropped > 0 {
+            println!(
+                "Dropped log lines: {}",
+                console::style(format!(
+                    "{} (over logs.rate_limit)",
+                    daemon.log_lines_dropped
+                ))
+                .yellow()
+            );
+        }
+        if !daemon.exits.is_empty() {
+            println!("Last exits:");
+            for exit in daemon.exits.iter().rev().take(RECENT_EXITS_SHOWN) {
+                println!("  {}: {exit}", time::ago(exit.at, now));
+            }
+        }
         let s = settings();
         if s.proxy.enable && (daemon.active_port.is_some() || !daemon.resolved_port.is_empty()) {
             let slug =
@@ -112,6 +294,36 @@ impl Status {
                 println!("Proxy: {url}");
             }
         }
-        Ok(())
+        exit(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitchfork_toml::DaemonKind;
+
+    fn with_status(status: DaemonStatus) -> Daemon {
+        Daemon {
+            status,
+            ..Daemon::default()
+        }
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(&with_status(DaemonStatus::Running), false), 0);
+        assert_eq!(exit_code(&with_status(DaemonStatus::Running), true), 1);
+        assert_eq!(exit_code(&with_status(DaemonStatus::Stopped), false), 1);
+        assert_eq!(exit_code(&with_status(DaemonStatus::Starting), false), 1);
+        assert_eq!(exit_code(&with_status(DaemonStatus::Errored(1)), false), 2);
+        assert_eq!(exit_code(&with_status(DaemonStatus::Unhealthy), false), 2);
+
+        let completed = Daemon {
+            kind: DaemonKind::Task,
+            last_exit_success: Some(true),
+            ..with_status(DaemonStatus::Stopped)
+        };
+        assert_eq!(exit_code(&completed, false), 0);
     }
 }
@@ -3,7 +3,6 @@ use crate::daemon_id::DaemonId;
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::PitchforkToml;
 use miette::ensure;
-use std::sync::Arc;
 
 /// Sends a stop signal to a daemon
 #[derive(Debug, clap::Args)]
@@ -80,7 +79,7 @@ impl Stop {
             "At least one daemon ID, --group, or one of --all / --local / --global must be provided"
         );
 
-        let ipc = Arc::new(IpcClient::connect(false).await?);
+        let ipc = IpcClient::shared(false).await?;
 
         let ids: Vec<DaemonId> = if self.all {
             ipc.get_running_daemons().await?
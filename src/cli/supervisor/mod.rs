@@ -5,6 +5,9 @@ use crate::pitchfork_toml::StopSignal;
 use crate::procs::PROCS;
 use crate::state_file::StateFile;
 
+mod pause;
+mod relay;
+mod resume;
 mod run;
 mod start;
 mod status;
@@ -30,6 +33,10 @@ pub struct Supervisor {
 
 #[derive(Debug, clap::Subcommand)]
 enum Commands {
+    Pause(pause::Pause),
+    #[clap(hide = true)]
+    Relay(relay::Relay),
+    Resume(resume::Resume),
     Run(run::Run),
     Start(start::Start),
     Status(status::Status),
@@ -39,6 +46,9 @@ enum Commands {
 impl Supervisor {
     pub async fn run(self) -> Result<()> {
         match self.command {
+            Commands::Pause(pause) => pause.run().await,
+            Commands::Relay(relay) => relay.run().await,
+            Commands::Resume(resume) => resume.run().await,
             Commands::Run(run) => run.run().await,
             Commands::Start(start) => start.run().await,
             Commands::Status(status) => status.run().await,
@@ -76,7 +86,7 @@ pub fn existing_supervisor_pid() -> Result<Option<u32>> {
     Ok(sf
         .daemons
         .get(&DaemonId::pitchfork())
-        .and_then(|daemon| daemon.pid))
+        .and_then(|daemon| daemon.local_pid()))
 }
 
 pub async fn resolve_existing_supervisor(force: bool) -> Result<(Option<u32>, KillOrStopOutcome)> {
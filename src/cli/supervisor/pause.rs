@@ -0,0 +1,36 @@
+use crate::Result;
+use crate::ipc::client::IpcClient;
+
+/// Pause automatic starts and restarts, leaving running daemons alone
+#[derive(Debug, clap::Args)]
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Pause automatic starts and restarts, leaving running daemons alone
+
+While paused, the supervisor doesn't trigger cron daemons, retry crashed
+daemons, autostart daemons on cd or at boot, or restart daemons when
+watched files or their config change. Daemons keep running, and
+'pitchfork start', 'stop' and 'restart' still work.
+
+Cron schedules keep advancing while paused, so runs missed meanwhile
+are skipped. The pause lasts until 'pitchfork supervisor resume', even
+if the supervisor restarts.
+
+Example:
+  pitchfork supervisor pause
+  pitchfork supervisor resume"
+)]
+pub struct Pause {}
+
+impl Pause {
+    pub async fn run(&self) -> Result<()> {
+        let ipc = IpcClient::connect(false).await?;
+        if ipc.set_paused(true).await? {
+            info!("Paused automatic starts and restarts");
+        } else {
+            info!("Supervisor is already paused");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use crate::Result;
+use crate::daemon_id::DaemonId;
+use crate::log_store::LogStream;
+
+/// Writes the output of detached daemons to the logs
+// Started by `supervisor stop --keep-daemons` with the daemons' output
+// descriptors; exits once every daemon has closed its output.
+#[derive(Debug, clap::Args)]
+pub struct Relay {
+    /// Output to read, as the descriptor, stdout or stderr, and the daemon
+    #[clap(long = "output", value_name = "FD:STREAM:DAEMON", required = true)]
+    outputs: Vec<String>,
+}
+
+impl Relay {
+    pub async fn run(&self) -> Result<()> {
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| parse_output(output))
+            .collect::<Result<Vec<_>>>()?;
+        #[cfg(unix)]
+        return crate::supervisor::detach::relay(outputs).await;
+        #[cfg(not(unix))]
+        {
+            let _ = outputs;
+            miette::bail!("relaying daemon output is only supported on Unix")
+        }
+    }
+}
+
+fn parse_output(output: &str) -> Result<(i32, LogStream, DaemonId)> {
+    let invalid = || miette::miette!("invalid output {output:?}, expected FD:STREAM:DAEMON");
+    let mut parts = output.splitn(3, ':');
+    let (Some(fd), Some(stream), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let fd = fd.parse().map_err(|_| invalid())?;
+    let stream = match stream {
+        "stdout" => LogStream::Stdout,
+        "stderr" => LogStream::Stderr,
+        _ => return Err(invalid()),
+    };
+    Ok((fd, stream, DaemonId::parse(id)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let (fd, stream, id) = parse_output("7:stderr:project-a/api").unwrap();
+        assert_eq!(fd, 7);
+        assert_eq!(stream, LogStream::Stderr);
+        assert_eq!(id, DaemonId::new("project-a", "api"));
+        assert!(parse_output("7:stdout").is_err());
+        assert!(parse_output("x:stdout:project-a/api").is_err());
+        assert!(parse_output("7:stdin:project-a/api").is_err());
+    }
+}
@@ -0,0 +1,19 @@
+use crate::Result;
+use crate::ipc::client::IpcClient;
+
+/// Resume automatic starts and restarts after `supervisor pause`
+#[derive(Debug, clap::Args)]
+#[clap()]
+pub struct Resume {}
+
+impl Resume {
+    pub async fn run(&self) -> Result<()> {
+        let ipc = IpcClient::connect(false).await?;
+        if ipc.set_paused(false).await? {
+            info!("Resumed automatic starts and restarts");
+        } else {
+            info!("Supervisor is not paused");
+        }
+        Ok(())
+    }
+}
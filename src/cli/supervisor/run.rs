@@ -1,6 +1,8 @@
 use crate::Result;
 use crate::cli::supervisor::{KillOrStopOutcome, resolve_existing_supervisor};
 use crate::supervisor::SUPERVISOR;
+use crate::supervisor::startup::{self, StartupState};
+use std::path::PathBuf;
 
 /// Runs the internal pitchfork daemon in the foreground
 #[derive(Debug, clap::Args)]
@@ -20,10 +22,25 @@ pub struct Run {
     /// Serve web UI under a path prefix (e.g. "ps" serves at /ps/)
     #[clap(long, env = "PITCHFORK_WEB_PATH")]
     web_path: Option<String>,
+    /// File to report startup progress to the client that spawned us
+    #[clap(long, hide = true)]
+    startup_marker: Option<PathBuf>,
 }
 
 impl Run {
     pub async fn run(&self) -> Result<()> {
+        if let Some(marker) = &self.startup_marker {
+            startup::set_marker(marker);
+            startup::mark(StartupState::Starting);
+        }
+        let result = self.start().await;
+        if let Err(e) = &result {
+            startup::mark(StartupState::Failed(e.to_string()));
+        }
+        result
+    }
+
+    async fn start(&self) -> Result<()> {
         let (existing_pid, outcome) = resolve_existing_supervisor(self.force).await?;
         match outcome {
             KillOrStopOutcome::StillRunning => {
@@ -47,8 +47,10 @@ impl Start {
         // Passing true would cause connect() to call start_if_not_running(),
         // which races with the freshly spawned process writing its state file
         // and may spawn a second supervisor.
-        IpcClient::connect(false).await?;
-        info!("Supervisor started");
+        IpcClient::connect(false)
+            .await?
+            .print_startup_banner()
+            .await;
 
         let s = settings();
         if s.proxy.enable && s.proxy.https {
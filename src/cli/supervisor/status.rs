@@ -8,8 +8,11 @@ pub struct Status {}
 
 impl Status {
     pub async fn run(&self) -> Result<()> {
-        IpcClient::connect(false).await?;
+        let ipc = IpcClient::connect(false).await?;
         info!("Pitchfork daemon is running");
+        if ipc.get_supervisor_info().await?.paused {
+            warn!("Automatic starts and restarts are paused (pitchfork supervisor resume)");
+        }
         Ok(())
     }
 }
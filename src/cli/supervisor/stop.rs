@@ -1,17 +1,47 @@
 use crate::Result;
 use crate::cli::supervisor::KillOrStopOutcome;
-use crate::cli::supervisor::resolve_existing_supervisor;
+use crate::cli::supervisor::{existing_supervisor_pid, resolve_existing_supervisor};
 use crate::daemon_id::DaemonId;
 use crate::env;
+use crate::ipc::client::IpcClient;
+use crate::procs::PROCS;
 use crate::state_file::StateFile;
+use std::time::Duration;
+
+/// How long to wait for the supervisor to exit after a --keep-daemons stop
+const KEEP_DAEMONS_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Stops the internal pitchfork daemon running in the background
 #[derive(Debug, clap::Args)]
-#[clap()]
-pub struct Stop {}
+#[clap(
+    verbatim_doc_comment,
+    long_about = "\
+Stops the internal pitchfork daemon running in the background
+
+Running daemons are stopped along with it. With --keep-daemons they are
+left running instead and shown as detached; the next supervisor to start
+adopts them, so the supervisor can be restarted (e.g. after an upgrade)
+without restarting any daemons. On Unix their output keeps going to the
+logs in the meantime.
+
+Examples:
+  pitchfork supervisor stop                   Stop the supervisor and all daemons
+  pitchfork supervisor stop --keep-daemons    Stop only the supervisor"
+)]
+pub struct Stop {
+    /// Leave daemons running, for the next supervisor to adopt
+    #[clap(long)]
+    keep_daemons: bool,
+}
 
 impl Stop {
     pub async fn run(&self) -> Result<()> {
+        if self.keep_daemons
+            && let Some(pid) = existing_supervisor_pid()?
+            && PROCS.is_running(pid)
+        {
+            return self.stop_keeping_daemons(pid).await;
+        }
         let (existing_pid, outcome) = resolve_existing_supervisor(true).await?;
         let Some(pid) = existing_pid else {
             warn!("Pitchfork daemon is not running");
@@ -35,4 +65,19 @@ impl Stop {
         }
         Ok(())
     }
+
+    /// Ask the supervisor to shut down on its own, since a signal would
+    /// stop its daemons too, and wait for it to exit
+    async fn stop_keeping_daemons(&self, pid: u32) -> Result<()> {
+        IpcClient::connect(false).await?.shutdown(true).await?;
+        let started = std::time::Instant::now();
+        while PROCS.is_running(pid) {
+            if started.elapsed() > KEEP_DAEMONS_TIMEOUT {
+                miette::bail!("pitchfork daemon with pid {pid} is still running");
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        info!("Stopped pitchfork daemon with pid {pid}, daemons left running");
+        Ok(())
+    }
 }
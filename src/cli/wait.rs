@@ -46,6 +46,7 @@ impl Wait {
 
         let tail_names = vec![qualified_id.clone()];
         tokio::spawn(async move {
+            let timezones = logs::LogTimezones::resolve(&tail_names, None);
             logs::tail_logs(
                 &tail_names,
                 true,
@@ -54,6 +55,7 @@ impl Wait {
                 Vec::new(),
                 None,
                 settings().logs.timestamp,
+                &timezones,
                 false,
             )
             .await
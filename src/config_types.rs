@@ -3,6 +3,7 @@
 //! These are thin wrappers (newtypes) around primitives with custom
 //! serialization, validation, or display logic.
 
+use indexmap::IndexMap;
 use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -126,11 +127,13 @@ pub trait BoolOrU32: Sized + Copy + From<u32> + Into<u32> {
 // Shared readiness timeout helpers
 // ---------------------------------------------------------------------------
 
-/// Parse a humantime duration string (e.g. "30s", "5m") into a `Duration`.
-/// Returns `Ok(None)` when the input is `None`.
+/// Parse a duration string (e.g. "30s", "5m", or bare seconds) into a
+/// `Duration`. Returns `Ok(None)` when the input is `None`.
 fn parse_timeout(raw: &Option<String>) -> std::result::Result<Option<std::time::Duration>, String> {
-    raw.as_ref()
-        .map(|s| humantime::parse_duration(s).map_err(|e| format!("invalid timeout: {e}")))
+    raw.as_deref()
+        .map(|s| {
+            crate::pitchfork_toml::parse_duration(s).map_err(|e| format!("invalid timeout: {e}"))
+        })
         .transpose()
 }
 
@@ -185,6 +188,297 @@ impl JsonSchema for CpuLimit {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Umask
+// ---------------------------------------------------------------------------
+
+/// File mode creation mask for a daemon, written as an octal string
+/// (e.g. `"027"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[display("{:03o}", _0)]
+#[serde(try_from = "String")]
+pub struct Umask(pub u32);
+
+impl TryFrom<String> for Umask {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        let digits = s.trim();
+        if digits.is_empty() || digits.len() > 4 {
+            return Err(format!("invalid umask {s:?}: expected octal like \"027\""));
+        }
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|mask| *mask <= 0o7777)
+            .map(Self)
+            .ok_or_else(|| format!("invalid umask {s:?}: expected octal like \"027\""))
+    }
+}
+
+impl Serialize for Umask {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl JsonSchema for Umask {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("Umask")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "File mode creation mask as an octal string (e.g. '027')",
+            "pattern": "^[0-7]{1,4}$"
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LogTimezone
+// ---------------------------------------------------------------------------
+
+/// Timezone log timestamps are displayed in: `"local"`, `"UTC"`, or an IANA
+/// name like `"Europe/Berlin"`. Entries are stored as instants, so this only
+/// changes how they are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum LogTimezone {
+    #[default]
+    Local,
+    Utc,
+    Named(chrono_tz::Tz),
+}
+
+impl LogTimezone {
+    /// Write `ts` in this timezone using a strftime `format`.
+    pub fn write_timestamp(
+        &self,
+        w: &mut impl std::fmt::Write,
+        ts: &chrono::DateTime<chrono::Local>,
+        format: &str,
+    ) -> std::fmt::Result {
+        match self {
+            Self::Local => write!(w, "{}", ts.format(format)),
+            Self::Utc => write!(w, "{}", ts.with_timezone(&chrono::Utc).format(format)),
+            Self::Named(tz) => write!(w, "{}", ts.with_timezone(tz).format(format)),
+        }
+    }
+
+    /// Format `ts` in this timezone using a strftime `format`.
+    pub fn format(&self, ts: &chrono::DateTime<chrono::Local>, format: &str) -> String {
+        let mut out = String::new();
+        let _ = self.write_timestamp(&mut out, ts, format);
+        out
+    }
+}
+
+impl std::str::FromStr for LogTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            s if s.eq_ignore_ascii_case("local") => Ok(Self::Local),
+            s if s.eq_ignore_ascii_case("utc") || s == "Z" => Ok(Self::Utc),
+            s => s.parse().map(Self::Named).map_err(|_| {
+                format!("invalid timezone {s:?}: expected \"local\", \"UTC\", or an IANA name like \"Europe/Berlin\"")
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for LogTimezone {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for LogTimezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Utc => write!(f, "UTC"),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl Serialize for LogTimezone {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl JsonSchema for LogTimezone {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("LogTimezone")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "Timezone for log timestamps: 'local', 'UTC', or an IANA name (e.g. 'Europe/Berlin')"
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LogEncoding
+// ---------------------------------------------------------------------------
+
+/// How output bytes that aren't valid UTF-8 are written to the log.
+#[derive(
+    Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LogEncoding {
+    /// Replace invalid sequences with U+FFFD.
+    #[default]
+    Utf8,
+    /// Keep invalid bytes as `\xNN` escapes, so the original bytes can be
+    /// recovered from the log.
+    Escape,
+    /// Decode every byte as Latin-1 (ISO-8859-1), for tools that don't
+    /// write UTF-8 at all.
+    Latin1,
+}
+
+impl LogEncoding {
+    /// Decode one line of output. Never fails: bytes that can't be decoded
+    /// are replaced or escaped.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Escape => {
+                let mut out = String::with_capacity(bytes.len());
+                for chunk in bytes.utf8_chunks() {
+                    out.push_str(chunk.valid());
+                    for b in chunk.invalid() {
+                        out.push_str(&format!("\\x{b:02x}"));
+                    }
+                }
+                out
+            }
+            Self::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// StartDelay
+// ---------------------------------------------------------------------------
+
+/// Pause before a daemon is spawned in a batch start, written as a humantime
+/// string (e.g. `"3s"`) or a number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "crate::pitchfork_toml::DurationValue")]
+pub struct StartDelay(pub std::time::Duration);
+
+impl TryFrom<crate::pitchfork_toml::DurationValue> for StartDelay {
+    type Error = String;
+
+    fn try_from(v: crate::pitchfork_toml::DurationValue) -> std::result::Result<Self, Self::Error> {
+        v.parse()
+            .map(Self)
+            .map_err(|e| format!("invalid start_delay: {e}"))
+    }
+}
+
+impl std::fmt::Display for StartDelay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", humantime::format_duration(self.0))
+    }
+}
+
+impl Serialize for StartDelay {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl JsonSchema for StartDelay {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("StartDelay")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": ["string", "integer"],
+            "description": "Pause before the daemon is spawned when starting several daemons at once (e.g. '3s', '500ms', or a number of seconds)",
+            "minimum": 0
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ResourceLimits
+// ---------------------------------------------------------------------------
+
+/// Resource limits applied with `setrlimit` before the daemon starts (Unix only).
+///
+/// Each value sets the soft limit; the hard limit is raised to match when it
+/// is lower, which requires the supervisor to run as root.
+/// ```toml
+/// limits = { nofile = 65536, core = 0 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceLimits {
+    /// Maximum number of open file descriptors (`RLIMIT_NOFILE`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub nofile: Option<u64>,
+    /// Maximum core file size in bytes; `0` disables core dumps (`RLIMIT_CORE`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub core: Option<u64>,
+    /// Maximum number of processes for the daemon's user (`RLIMIT_NPROC`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub nproc: Option<u64>,
+    /// Maximum stack size in bytes (`RLIMIT_STACK`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stack: Option<u64>,
+    /// Maximum locked memory in bytes (`RLIMIT_MEMLOCK`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub memlock: Option<u64>,
+}
+
+// ---------------------------------------------------------------------------
+// Alerts
+// ---------------------------------------------------------------------------
+
+/// Resource usage thresholds that raise an alert without stopping the daemon.
+///
+/// An alert fires once usage has stayed above a threshold for `for` (default:
+/// immediately), and clears when usage drops back below it.
+/// ```toml
+/// [daemons.api.alerts]
+/// memory_above = "1.5GB"
+/// for = "2m"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Alerts {
+    /// Alert when the daemon's memory (RSS) is above this size (e.g. "1.5GB")
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub memory_above: Option<MemoryLimit>,
+    /// Alert when the daemon's CPU usage is above this percentage (e.g. 150
+    /// for 1.5 cores)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cpu_above: Option<CpuLimit>,
+    /// How long usage must stay above a threshold before the alert fires
+    /// (e.g. "2m")
+    #[serde(
+        rename = "for",
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::pitchfork_toml::opt_duration"
+    )]
+    #[schemars(with = "Option<crate::pitchfork_toml::DurationValue>")]
+    pub duration: Option<std::time::Duration>,
+}
+
 // ---------------------------------------------------------------------------
 // ReadyHttp
 // ---------------------------------------------------------------------------
@@ -195,6 +489,7 @@ impl JsonSchema for CpuLimit {
 /// ```toml
 /// ready_http = "http://localhost:3000/health"  # shorthand, any 2xx response
 /// ready_http = { url = "http://localhost:3000/health", status = [200, 401], timeout = "30s" }
+/// ready_http = { url = "http://localhost:3000/health", method = "POST", headers = { Authorization = "Bearer x" }, expect_body_regex = "ok" }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ReadyHttp {
@@ -204,17 +499,26 @@ pub struct ReadyHttp {
     /// Optional overall polling timeout. When set, the HTTP readiness check stops
     /// after this deadline and the daemon fails if no other check succeeds.
     pub timeout: Option<std::time::Duration>,
+    /// Request method, uppercased. `None` means GET.
+    pub method: Option<String>,
+    /// Headers sent with every request, e.g. `Authorization`.
+    pub headers: IndexMap<String, String>,
+    /// Regex the response body must match, on top of an accepted status.
+    pub expect_body_regex: Option<String>,
 }
 
 impl ReadyHttp {
     pub fn new(url: impl Into<String>) -> Self {
         Self {
             url: url.into(),
-            status: vec![],
-            timeout: None,
+            ..Self::default()
         }
     }
 
+    pub fn method(&self) -> &str {
+        self.method.as_deref().unwrap_or("GET")
+    }
+
     pub fn accepts_status(&self, status: u16) -> bool {
         if self.status.is_empty() {
             (200..=299).contains(&status)
@@ -226,6 +530,10 @@ impl ReadyHttp {
 
 impl std::fmt::Display for ReadyHttp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Headers are left out, they often carry credentials
+        if let Some(method) = &self.method {
+            write!(f, "{method} ")?;
+        }
         if self.status.is_empty() {
             f.write_str(&self.url)
         } else {
@@ -244,9 +552,23 @@ impl std::fmt::Display for ReadyHttp {
 #[doc(hidden)]
 pub struct ReadyHttpRaw {
     url: String,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    method: Option<String>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    headers: IndexMap<String, String>,
+    #[serde(
+        default,
+        alias = "expect_status",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     status: Vec<u16>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    expect_body_regex: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
     timeout: Option<String>,
 }
 
@@ -266,16 +588,45 @@ impl StringOrStruct for ReadyHttp {
                 ));
             }
         }
+        let method = match raw.method {
+            Some(method) => {
+                let method = method.to_ascii_uppercase();
+                reqwest::Method::from_bytes(method.as_bytes())
+                    .map_err(|_| format!("invalid ready_http method: {method}"))?;
+                Some(method)
+            }
+            None => None,
+        };
+        for (name, value) in &raw.headers {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("invalid ready_http header name: {name}"))?;
+            // Values may still hold templates, so only reject what no
+            // rendering can fix
+            if value.contains(['\r', '\n']) {
+                return Err(format!("ready_http header {name} must be a single line"));
+            }
+        }
+        if let Some(pattern) = &raw.expect_body_regex {
+            regex::Regex::new(pattern)
+                .map_err(|e| format!("invalid ready_http expect_body_regex: {e}"))?;
+        }
         let timeout = parse_timeout(&raw.timeout)?;
         Ok(Self {
             url: raw.url,
             status: raw.status,
             timeout,
+            method,
+            headers: raw.headers,
+            expect_body_regex: raw.expect_body_regex,
         })
     }
 
     fn is_shorthand(&self) -> bool {
-        self.status.is_empty() && self.timeout.is_none()
+        self.status.is_empty()
+            && self.timeout.is_none()
+            && self.method.is_none()
+            && self.headers.is_empty()
+            && self.expect_body_regex.is_none()
     }
 
     fn to_short(&self) -> String {
@@ -285,7 +636,10 @@ impl StringOrStruct for ReadyHttp {
     fn to_raw(&self) -> ReadyHttpRaw {
         ReadyHttpRaw {
             url: self.url.clone(),
+            method: self.method.clone(),
+            headers: self.headers.clone(),
             status: self.status.clone(),
+            expect_body_regex: self.expect_body_regex.clone(),
             timeout: format_timeout(self.timeout),
         }
     }
@@ -310,19 +664,31 @@ impl JsonSchema for ReadyHttp {
 
     fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
         schemars::json_schema!({
-            "description": "HTTP readiness check: a URL string accepting any 2xx response, or { url, status, timeout } object with exact accepted status codes and optional overall polling timeout",
+            "description": "HTTP readiness check: a URL string accepting any 2xx response, or { url, method, headers, status, expect_body_regex, timeout } object with exact accepted status codes and optional overall polling timeout",
             "oneOf": [
                 { "type": "string", "description": "HTTP URL to poll for readiness; any 2xx response is ready" },
                 {
                     "type": "object",
                     "properties": {
                         "url": { "type": "string", "description": "HTTP URL to poll for readiness" },
+                        "method": { "type": "string", "description": "HTTP request method (default: GET)" },
+                        "headers": {
+                            "type": "object",
+                            "description": "Headers sent with every request, e.g. Authorization. Values can use templates like {{ env.TOKEN }}.",
+                            "additionalProperties": { "type": "string" }
+                        },
                         "status": {
                             "type": "array",
                             "description": "Exact HTTP status codes that indicate readiness. Omit to accept any 2xx response.",
                             "items": { "type": "integer", "minimum": 100, "maximum": 599 }
                         },
-                        "timeout": { "type": "string", "description": "Overall readiness polling timeout (e.g. '30s', '5m'). Distinct from per-request http_client_timeout." }
+                        "expect_status": {
+                            "type": "array",
+                            "description": "Alias of status",
+                            "items": { "type": "integer", "minimum": 100, "maximum": 599 }
+                        },
+                        "expect_body_regex": { "type": "string", "description": "Regex the response body must match for the check to pass" },
+                        "timeout": { "type": ["string", "integer"], "description": "Overall readiness polling timeout (e.g. '30s', '5m'). Distinct from per-request http_client_timeout." }
                     },
                     "required": ["url"]
                 }
@@ -341,6 +707,7 @@ impl JsonSchema for ReadyHttp {
 /// ```toml
 /// ready_cmd = "pg_isready -h localhost"                        # shorthand, no timeout
 /// ready_cmd = { run = "pg_isready -h localhost", timeout = "30s" } # full
+/// ready_cmd = { run = "./check.sh", attempt_timeout = "5s" }       # bound each run
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ReadyCmd {
@@ -349,6 +716,10 @@ pub struct ReadyCmd {
     /// Optional overall polling timeout. When set, the command readiness check stops
     /// after this deadline and the daemon fails if no other check succeeds.
     pub timeout: Option<std::time::Duration>,
+    /// Optional limit for a single run of the command. A run that exceeds it
+    /// has its process group killed and counts as a failed attempt. Defaults
+    /// to `supervisor.ready_cmd_timeout`.
+    pub attempt_timeout: Option<std::time::Duration>,
 }
 
 impl ReadyCmd {
@@ -356,6 +727,7 @@ impl ReadyCmd {
         Self {
             run: run.into(),
             timeout: None,
+            attempt_timeout: None,
         }
     }
 }
@@ -370,8 +742,18 @@ impl std::fmt::Display for ReadyCmd {
 #[doc(hidden)]
 pub struct ReadyCmdRaw {
     run: String,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
     timeout: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
+    attempt_timeout: Option<String>,
 }
 
 impl StringOrStruct for ReadyCmd {
@@ -384,14 +766,16 @@ impl StringOrStruct for ReadyCmd {
 
     fn from_raw(raw: ReadyCmdRaw) -> std::result::Result<Self, String> {
         let timeout = parse_timeout(&raw.timeout)?;
+        let attempt_timeout = parse_timeout(&raw.attempt_timeout)?;
         Ok(Self {
             run: raw.run,
             timeout,
+            attempt_timeout,
         })
     }
 
     fn is_shorthand(&self) -> bool {
-        self.timeout.is_none()
+        self.timeout.is_none() && self.attempt_timeout.is_none()
     }
 
     fn to_short(&self) -> String {
@@ -402,6 +786,7 @@ impl StringOrStruct for ReadyCmd {
         ReadyCmdRaw {
             run: self.run.clone(),
             timeout: format_timeout(self.timeout),
+            attempt_timeout: format_timeout(self.attempt_timeout),
         }
     }
 }
@@ -425,14 +810,15 @@ impl JsonSchema for ReadyCmd {
 
     fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
         schemars::json_schema!({
-            "description": "Command readiness check: a shell command string, or { run, timeout } object with an optional overall polling timeout",
+            "description": "Command readiness check: a shell command string, or { run, timeout, attempt_timeout } object with optional overall polling and per-run timeouts",
             "oneOf": [
                 { "type": "string", "description": "Shell command that returns exit code 0 when ready" },
                 {
                     "type": "object",
                     "properties": {
                         "run": { "type": "string", "description": "Shell command that returns exit code 0 when ready" },
-                        "timeout": { "type": "string", "description": "Overall readiness polling timeout (e.g. '30s', '5m')" }
+                        "timeout": { "type": ["string", "integer"], "description": "Overall readiness polling timeout (e.g. '30s', '5m')" },
+                        "attempt_timeout": { "type": ["string", "integer"], "description": "Limit for a single run of the command (e.g. '5s'). Defaults to supervisor.ready_cmd_timeout." }
                     },
                     "required": ["run"]
                 }
@@ -535,7 +921,11 @@ pub struct ReadyPortRaw {
     port: Option<u16>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     template: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
     timeout: Option<String>,
 }
 
@@ -637,7 +1027,7 @@ impl JsonSchema for ReadyPort {
                     "properties": {
                         "port": { "type": "integer", "minimum": 1, "maximum": 65535, "description": "TCP port number to check for readiness" },
                         "template": { "type": "string", "description": "Tera template that renders to a port number" },
-                        "timeout": { "type": "string", "description": "Overall readiness polling timeout (e.g. '30s', '5m')" }
+                        "timeout": { "type": ["string", "integer"], "description": "Overall readiness polling timeout (e.g. '30s', '5m')" }
                     },
                     "oneOf": [
                         { "required": ["port"] },
@@ -688,7 +1078,11 @@ impl std::fmt::Display for ReadyOutput {
 #[doc(hidden)]
 pub struct ReadyOutputRaw {
     pattern: String,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
     timeout: Option<String>,
 }
 
@@ -750,7 +1144,7 @@ impl JsonSchema for ReadyOutput {
                     "type": "object",
                     "properties": {
                         "pattern": { "type": "string", "description": "Regex pattern matched against ANSI-stripped stdout/stderr lines" },
-                        "timeout": { "type": "string", "description": "Overall readiness polling timeout (e.g. '30s', '5m')" }
+                        "timeout": { "type": ["string", "integer"], "description": "Overall readiness polling timeout (e.g. '30s', '5m')" }
                     },
                     "required": ["pattern"]
                 }
@@ -759,6 +1153,349 @@ impl JsonSchema for ReadyOutput {
     }
 }
 
+// ---------------------------------------------------------------------------
+// ReadyLogFile
+// ---------------------------------------------------------------------------
+
+/// Log file readiness check, for daemons that write their own log files
+/// instead of stdout.
+///
+/// ```toml
+/// ready_log_file = { path = "logs/server.log", pattern = "Listening on" }
+/// ready_log_file = { path = "logs/server.log", pattern = "Listening on", timeout = "30s" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "ReadyLogFileRaw", into = "ReadyLogFileRaw")]
+pub struct ReadyLogFile {
+    /// File to tail, relative to the daemon's `dir`.
+    pub path: String,
+    /// Regex pattern matched against ANSI-stripped lines appended to the file.
+    pub pattern: String,
+    /// Optional overall polling timeout. When set, the log file readiness check stops
+    /// after this deadline and the daemon fails if no other check succeeds.
+    pub timeout: Option<std::time::Duration>,
+}
+
+impl std::fmt::Display for ReadyLogFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.pattern)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+#[doc(hidden)]
+pub struct ReadyLogFileRaw {
+    path: String,
+    pattern: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
+    timeout: Option<String>,
+}
+
+impl TryFrom<ReadyLogFileRaw> for ReadyLogFile {
+    type Error = String;
+
+    fn try_from(raw: ReadyLogFileRaw) -> std::result::Result<Self, Self::Error> {
+        if raw.path.is_empty() {
+            return Err("ready_log_file path must not be empty".into());
+        }
+        Ok(Self {
+            path: raw.path,
+            pattern: raw.pattern,
+            timeout: parse_timeout(&raw.timeout)?,
+        })
+    }
+}
+
+impl From<ReadyLogFile> for ReadyLogFileRaw {
+    fn from(rf: ReadyLogFile) -> Self {
+        Self {
+            path: rf.path,
+            pattern: rf.pattern,
+            timeout: format_timeout(rf.timeout),
+        }
+    }
+}
+
+impl JsonSchema for ReadyLogFile {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("ReadyLogFile")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Log file readiness check: { path, pattern, timeout } object that tails a file the daemon writes itself",
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "File to tail, relative to the daemon's dir" },
+                "pattern": { "type": "string", "description": "Regex pattern matched against ANSI-stripped lines appended to the file" },
+                "timeout": { "type": ["string", "integer"], "description": "Overall readiness polling timeout (e.g. '30s', '5m')" }
+            },
+            "required": ["path", "pattern"],
+            "additionalProperties": false
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ReadyWhen
+// ---------------------------------------------------------------------------
+
+/// Readiness tied to another pitchfork daemon, for wrappers (e.g. a proxy)
+/// that are only useful once their upstream is. The supervisor watches the
+/// upstream's status directly, so nothing is polled.
+///
+/// ```toml
+/// ready_when = { daemon = "db", state = "ready" }
+/// ready_when = { daemon = "infra/db", state = "started", timeout = "1m" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "ReadyWhenRaw", into = "ReadyWhenRaw")]
+pub struct ReadyWhen {
+    /// Upstream daemon: `name` in the same namespace, or `namespace/name`.
+    pub daemon: String,
+    /// State the upstream daemon must reach.
+    pub state: ReadyWhenState,
+    /// Optional overall timeout. When set, the check stops after this
+    /// deadline and the daemon fails if no other check succeeds.
+    pub timeout: Option<std::time::Duration>,
+}
+
+/// Upstream state a `ready_when` check waits for
+#[derive(
+    Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadyWhenState {
+    /// The upstream passed its own ready checks (status `running`).
+    #[default]
+    Ready,
+    /// The upstream's process is up, ready or not.
+    Started,
+}
+
+impl ReadyWhen {
+    /// The upstream daemon's ID, resolving a bare name in `namespace`
+    pub fn daemon_id(&self, namespace: &str) -> crate::Result<crate::daemon_id::DaemonId> {
+        if self.daemon.contains('/') {
+            crate::daemon_id::DaemonId::parse(&self.daemon)
+        } else {
+            crate::daemon_id::DaemonId::try_new(namespace, &self.daemon)
+        }
+    }
+}
+
+impl std::fmt::Display for ReadyWhenState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ready => write!(f, "ready"),
+            Self::Started => write!(f, "started"),
+        }
+    }
+}
+
+impl std::fmt::Display for ReadyWhen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.daemon, self.state)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+#[doc(hidden)]
+pub struct ReadyWhenRaw {
+    daemon: String,
+    #[serde(default)]
+    state: ReadyWhenState,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
+    timeout: Option<String>,
+}
+
+impl TryFrom<ReadyWhenRaw> for ReadyWhen {
+    type Error = String;
+
+    fn try_from(raw: ReadyWhenRaw) -> std::result::Result<Self, Self::Error> {
+        if raw.daemon.is_empty() {
+            return Err("ready_when daemon must not be empty".into());
+        }
+        Ok(Self {
+            daemon: raw.daemon,
+            state: raw.state,
+            timeout: parse_timeout(&raw.timeout)?,
+        })
+    }
+}
+
+impl From<ReadyWhen> for ReadyWhenRaw {
+    fn from(rw: ReadyWhen) -> Self {
+        Self {
+            daemon: rw.daemon,
+            state: rw.state,
+            timeout: format_timeout(rw.timeout),
+        }
+    }
+}
+
+impl JsonSchema for ReadyWhen {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("ReadyWhen")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Readiness tied to another pitchfork daemon: { daemon, state, timeout } object, ready once the upstream daemon reaches the given state",
+            "type": "object",
+            "properties": {
+                "daemon": { "type": "string", "description": "Upstream daemon: 'name' in the same namespace or 'namespace/name'" },
+                "state": { "type": "string", "enum": ["ready", "started"], "description": "'ready' waits for the upstream's own ready checks (default), 'started' only for its process" },
+                "timeout": { "type": ["string", "integer"], "description": "Overall readiness timeout (e.g. '30s', '5m')" }
+            },
+            "required": ["daemon"],
+            "additionalProperties": false
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Requirement
+// ---------------------------------------------------------------------------
+
+/// An external service that must be reachable before a daemon is spawned.
+///
+/// ```toml
+/// requires = [
+///     { tcp = "localhost:5432" },
+///     { http = "http://localhost:9200", timeout = "2m" },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "RequirementRaw", into = "RequirementRaw")]
+pub struct Requirement {
+    pub target: RequirementTarget,
+    /// How long to keep polling before giving up. `None` uses
+    /// [`Requirement::DEFAULT_TIMEOUT`].
+    pub timeout: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequirementTarget {
+    /// `host:port` that must accept a TCP connection
+    Tcp(String),
+    /// URL that must answer with a non-5xx response
+    Http(String),
+}
+
+impl Requirement {
+    pub const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+    pub fn timeout(&self) -> std::time::Duration {
+        self.timeout.unwrap_or(Self::DEFAULT_TIMEOUT)
+    }
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.target {
+            RequirementTarget::Tcp(addr) => write!(f, "tcp {addr}"),
+            RequirementTarget::Http(url) => write!(f, "http {url}"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+#[doc(hidden)]
+pub struct RequirementRaw {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tcp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    http: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
+    timeout: Option<String>,
+}
+
+impl TryFrom<RequirementRaw> for Requirement {
+    type Error = String;
+
+    fn try_from(raw: RequirementRaw) -> std::result::Result<Self, Self::Error> {
+        let target = match (raw.tcp, raw.http) {
+            (Some(addr), None) => {
+                let valid = addr
+                    .rsplit_once(':')
+                    .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+                if !valid {
+                    return Err(format!(
+                        "invalid requires tcp address {addr:?}: expected \"host:port\""
+                    ));
+                }
+                RequirementTarget::Tcp(addr)
+            }
+            (None, Some(url)) => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(format!(
+                        "invalid requires http url {url:?}: expected http:// or https://"
+                    ));
+                }
+                RequirementTarget::Http(url)
+            }
+            _ => return Err("each requires entry needs exactly one of `tcp` or `http`".into()),
+        };
+        Ok(Self {
+            target,
+            timeout: parse_timeout(&raw.timeout)?,
+        })
+    }
+}
+
+impl From<Requirement> for RequirementRaw {
+    fn from(req: Requirement) -> Self {
+        let (tcp, http) = match req.target {
+            RequirementTarget::Tcp(addr) => (Some(addr), None),
+            RequirementTarget::Http(url) => (None, Some(url)),
+        };
+        Self {
+            tcp,
+            http,
+            timeout: format_timeout(req.timeout),
+        }
+    }
+}
+
+impl JsonSchema for Requirement {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("Requirement")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "External service that must be reachable before the daemon starts: { tcp = \"host:port\" } or { http = \"url\" }, with an optional timeout",
+            "type": "object",
+            "properties": {
+                "tcp": { "type": "string", "description": "host:port that must accept a TCP connection" },
+                "http": { "type": "string", "description": "URL that must answer with a non-5xx response" },
+                "timeout": { "type": ["string", "integer"], "description": "How long to wait for the service (e.g. '30s', '5m'). Default: 30s" }
+            },
+            "oneOf": [
+                { "required": ["tcp"] },
+                { "required": ["http"] }
+            ],
+            "additionalProperties": false
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // StopSignal
 // ---------------------------------------------------------------------------
@@ -875,7 +1612,11 @@ pub struct StopConfig {
 #[doc(hidden)]
 pub struct StopConfigRaw {
     signal: StopSignal,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
     timeout: Option<String>,
 }
 
@@ -940,7 +1681,7 @@ impl JsonSchema for StopConfig {
                     "type": "object",
                     "properties": {
                         "signal": generator.subschema_for::<StopSignal>(),
-                        "timeout": { "type": "string", "description": "Graceful shutdown timeout (e.g. '500ms', '3s')" }
+                        "timeout": { "type": ["string", "integer"], "description": "Graceful shutdown timeout (e.g. '500ms', '3s')" }
                     },
                     "required": ["signal"]
                 }
@@ -1013,6 +1754,26 @@ impl JsonSchema for Retry {
 // WatchMode
 // ---------------------------------------------------------------------------
 
+/// Whether an entry is a long-running daemon or a one-shot task.
+#[derive(
+    Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonKind {
+    /// Runs until stopped; exiting on its own is a failure or a clean stop.
+    #[default]
+    Daemon,
+    /// Runs to completion (e.g. a migration). It is ready once it exits
+    /// successfully, and dependents start only after that.
+    Task,
+}
+
+impl DaemonKind {
+    pub fn is_task(&self) -> bool {
+        *self == DaemonKind::Task
+    }
+}
+
 /// File watch backend mode for daemon `watch` patterns.
 #[derive(
     Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq, JsonSchema,
@@ -1068,6 +1829,9 @@ pub struct PitchforkTomlCron {
     /// Whether to trigger immediately on first check when no prior trigger is recorded.
     /// When false (default), the first trigger is deferred until the next scheduled time.
     pub immediate: bool,
+    /// Take a lease file in the daemon's directory before triggering, so that
+    /// supervisors sharing the directory never run the schedule concurrently.
+    pub exclusive: bool,
 }
 
 impl JsonSchema for PitchforkTomlCron {
@@ -1077,7 +1841,7 @@ impl JsonSchema for PitchforkTomlCron {
 
     fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
         schemars::json_schema!({
-            "description": "Cron scheduling: a cron expression string, or { schedule, retrigger, immediate } object",
+            "description": "Cron scheduling: a cron expression string, or { schedule, retrigger, immediate, exclusive } object",
             "oneOf": [
                 { "type": "string", "description": "Cron expression (e.g. '0 * * * *')" },
                 {
@@ -1085,7 +1849,8 @@ impl JsonSchema for PitchforkTomlCron {
                     "properties": {
                         "schedule": { "type": "string", "description": "Cron expression" },
                         "retrigger": generator.subschema_for::<CronRetrigger>(),
-                        "immediate": { "type": "boolean", "description": "Trigger immediately on first check (default: false)" }
+                        "immediate": { "type": "boolean", "description": "Trigger immediately on first check (default: false)" },
+                        "exclusive": { "type": "boolean", "description": "Hold a lease file in the daemon's directory while running, so that other supervisors sharing it skip the schedule (default: false)" }
                     },
                     "required": ["schedule"]
                 }
@@ -1102,6 +1867,8 @@ pub struct PitchforkTomlCronRaw {
     retrigger: CronRetrigger,
     #[serde(default)]
     immediate: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    exclusive: bool,
 }
 
 impl StringOrStruct for PitchforkTomlCron {
@@ -1113,6 +1880,7 @@ impl StringOrStruct for PitchforkTomlCron {
             schedule,
             retrigger: CronRetrigger::default(),
             immediate: false,
+            exclusive: false,
         }
     }
 
@@ -1121,11 +1889,12 @@ impl StringOrStruct for PitchforkTomlCron {
             schedule: raw.schedule,
             retrigger: raw.retrigger,
             immediate: raw.immediate,
+            exclusive: raw.exclusive,
         })
     }
 
     fn is_shorthand(&self) -> bool {
-        self.retrigger == CronRetrigger::default() && !self.immediate
+        self.retrigger == CronRetrigger::default() && !self.immediate && !self.exclusive
     }
 
     fn to_short(&self) -> String {
@@ -1137,6 +1906,7 @@ impl StringOrStruct for PitchforkTomlCron {
             schedule: self.schedule.clone(),
             retrigger: self.retrigger,
             immediate: self.immediate,
+            exclusive: self.exclusive,
         }
     }
 }
@@ -1187,8 +1957,9 @@ pub struct OnOutputHook {
     pub filter: Option<String>,
     /// Fire when a line of output matches this regular expression
     pub regex: Option<String>,
-    /// Minimum time between successive firings (humantime, e.g. `"500ms"`).
-    /// Defaults to `"1000ms"`.
+    /// Minimum time between successive firings (humantime, e.g. `"500ms"`,
+    /// or a number of seconds). Defaults to `"1000ms"`.
+    #[schemars(with = "Option<crate::pitchfork_toml::DurationValue>")]
     pub debounce: Option<String>,
 }
 
@@ -1200,7 +1971,11 @@ pub struct OnOutputHookRaw {
     filter: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     regex: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::pitchfork_toml::deserialize_duration_string"
+    )]
     debounce: Option<String>,
 }
 
@@ -1274,7 +2049,7 @@ impl OnOutputHook {
             })?;
         }
         if let Some(ref d) = self.debounce {
-            humantime::parse_duration(d).map_err(|e| {
+            crate::pitchfork_toml::parse_duration(d).map_err(|e| {
                 miette::miette!(
                     "daemon {daemon_name}: on_output.debounce {d:?} is not a valid duration: {e}"
                 )
@@ -1287,7 +2062,7 @@ impl OnOutputHook {
     pub fn debounce_duration(&self) -> std::time::Duration {
         self.debounce
             .as_deref()
-            .and_then(|s| humantime::parse_duration(s).ok())
+            .and_then(|s| crate::pitchfork_toml::parse_duration(s).ok())
             .unwrap_or(std::time::Duration::from_millis(1000))
     }
 }
@@ -1314,9 +2089,24 @@ pub struct PitchforkTomlHooks {
     /// Command to run on any daemon termination (clean exit, crash, or stop)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub on_exit: Option<String>,
+    /// Command to run when the daemon is killed by the kernel OOM killer
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub on_oom: Option<String>,
+    /// Command to run when a threshold in `alerts` is breached
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub on_alert: Option<String>,
     /// Hook triggered when the daemon produces matching output
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub on_output: Option<OnOutputHook>,
+    /// How long any one hook command may run before its process group is
+    /// killed (e.g. "30s"). Defaults to `supervisor.hook_timeout`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::pitchfork_toml::opt_duration"
+    )]
+    #[schemars(with = "Option<crate::pitchfork_toml::DurationValue>")]
+    pub timeout: Option<std::time::Duration>,
 }
 
 // ---------------------------------------------------------------------------
@@ -1,8 +1,9 @@
 use crate::daemon_id::DaemonId;
 use crate::daemon_status::DaemonStatus;
 use crate::pitchfork_toml::{
-    CpuLimit, CronRetrigger, Dir, MemoryLimit, PortConfig, ReadyCmd, ReadyHttp, ReadyOutput,
-    ReadyPort, Retry, StopConfig, WatchMode,
+    Alerts, CpuLimit, CronRetrigger, DaemonKind, Dir, MemoryLimit, PortConfig, ReadyCmd, ReadyHttp,
+    ReadyLogFile, ReadyOutput, ReadyPort, ReadyWhen, Requirement, ResourceLimits, Retry,
+    StopConfig, Umask, WatchMode,
 };
 use indexmap::IndexMap;
 use std::fmt::Display;
@@ -34,6 +35,76 @@ pub fn is_valid_daemon_id(id: &str) -> bool {
     }
 }
 
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// Overlay a one-time `start --env` override on top of the configured env.
+fn merge_env(
+    env: &Option<IndexMap<String, String>>,
+    env_override: &Option<IndexMap<String, String>>,
+) -> Option<IndexMap<String, String>> {
+    match (env, env_override) {
+        (Some(env), Some(overlay)) => {
+            let mut merged = env.clone();
+            merged.extend(overlay.iter().map(|(k, v)| (k.clone(), v.clone())));
+            Some(merged)
+        }
+        (env, None) => env.clone(),
+        (None, overlay) => overlay.clone(),
+    }
+}
+
+/// Exits kept in `Daemon::exits`; older ones are dropped.
+pub const EXIT_HISTORY_LEN: usize = 10;
+
+/// Startups kept in `Daemon::startups`; older ones are dropped.
+pub const STARTUP_HISTORY_LEN: usize = 20;
+
+/// Ready delay used by `start`/`run` when no readiness check is configured.
+pub const DEFAULT_READY_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// One past exit of a daemon process.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExitRecord {
+    pub at: chrono::DateTime<chrono::Local>,
+    /// Exit code, or -1 if the process was killed by a signal or the code is unknown.
+    pub code: i32,
+    /// Signal that terminated the process (Unix only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub signal: Option<i32>,
+    /// How long the process ran, in seconds.
+    pub runtime_secs: u64,
+    /// `exit` (clean), `fail`, `stop` (stopped by pitchfork), or `killed`
+    /// (ended by a signal pitchfork didn't send)
+    pub reason: String,
+}
+
+impl Display for ExitRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.signal {
+            Some(signal) => write!(f, "{} (signal {signal})", self.reason)?,
+            None => write!(f, "{} (code {})", self.reason, self.code)?,
+        }
+        write!(
+            f,
+            " after {}",
+            crate::ui::time::duration_secs(self.runtime_secs)
+        )
+    }
+}
+
+/// Timings of one run that became ready.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StartupRecord {
+    pub at: chrono::DateTime<chrono::Local>,
+    /// Milliseconds from the start request to the process being spawned
+    /// (port checks, stale process cleanup, etc.).
+    pub start_ms: u64,
+    /// Milliseconds from the process being spawned to its ready check passing.
+    pub ready_ms: u64,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct Daemon {
     pub id: DaemonId,
@@ -45,6 +116,10 @@ pub struct Daemon {
     /// from an unrelated process.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub start_time: Option<u64>,
+    /// Host the `pid` belongs to. State written by older versions has no
+    /// host and is treated as local.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub host: Option<String>,
     pub shell_pid: Option<u32>,
     pub status: DaemonStatus,
     pub dir: Option<PathBuf>,
@@ -53,6 +128,8 @@ pub struct Daemon {
     /// Original shell command string, persisted for retry/watch restarts.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub run: Option<String>,
+    #[serde(default)]
+    pub kind: DaemonKind,
     pub autostop: bool,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cron_schedule: Option<String>,
@@ -61,15 +138,35 @@ pub struct Daemon {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cron_immediate: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cron_exclusive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     pub last_cron_triggered: Option<chrono::DateTime<chrono::Local>>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub last_exit_success: Option<bool>,
+    /// The last `EXIT_HISTORY_LEN` exits, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub exits: Vec<ExitRecord>,
+    /// The last `STARTUP_HISTORY_LEN` runs that became ready, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub startups: Vec<StartupRecord>,
     #[serde(default)]
     pub retry: Retry,
     #[serde(default)]
     pub retry_count: u32,
+    /// When the supervisor makes the next retry attempt, while the daemon is
+    /// errored with retries left.
     #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub ready_delay: Option<u64>,
+    pub next_retry_at: Option<chrono::DateTime<chrono::Local>>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub success_exit_codes: Vec<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub no_retry_exit_codes: Vec<i32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::pitchfork_toml::opt_duration"
+    )]
+    pub ready_delay: Option<std::time::Duration>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ready_output: Option<ReadyOutput>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -78,6 +175,16 @@ pub struct Daemon {
     pub ready_port: Option<ReadyPort>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ready_cmd: Option<ReadyCmd>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_log_file: Option<ReadyLogFile>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_unix_socket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_when: Option<ReadyWhen>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fail_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expect_long_running: Option<bool>,
     /// Port configuration (expected ports and auto-bump settings)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub port: Option<PortConfig>,
@@ -96,8 +203,24 @@ pub struct Daemon {
     pub proxy: Option<bool>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub depends: Vec<DaemonId>,
+    /// External services that must be reachable before the daemon is spawned.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub requires: Vec<Requirement>,
+    /// The requirement the supervisor is currently waiting on, while the
+    /// daemon is `waiting` and not yet spawned.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub waiting_for: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub env: Option<IndexMap<String, String>>,
+    /// Environment passed with `start --env` for the current run only. It
+    /// overlays `env` and is dropped the next time the daemon is started
+    /// without it.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub env_override: Option<IndexMap<String, String>>,
+    /// When the supervisor restarts the daemon without `env_override`, for
+    /// overrides set by `pitchfork debug enable --duration`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub env_override_until: Option<chrono::DateTime<chrono::Local>>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub watch: Vec<String>,
     #[serde(default)]
@@ -118,24 +241,81 @@ pub struct Daemon {
     /// Unix user to run this daemon as.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub user: Option<String>,
+    /// User who started the daemon through a shared supervisor (see
+    /// `settings.supervisor.socket_group`). Set by the supervisor from the
+    /// connection's credentials; `None` for single-user supervisors.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub owner: Option<String>,
     /// Memory limit for the daemon process (e.g. "50MB", "1GiB")
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub memory_limit: Option<MemoryLimit>,
     /// CPU usage limit as a percentage (e.g. 80 for 80%, 200 for 2 cores)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cpu_limit: Option<CpuLimit>,
+    /// Resource usage thresholds that raise an alert
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alerts: Option<Alerts>,
     /// Unix signal to send for graceful shutdown (default: SIGTERM)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub stop_signal: Option<StopConfig>,
+    /// File mode creation mask for the daemon process.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub umask: Option<Umask>,
+    /// Resource limits for the daemon process.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub limits: Option<ResourceLimits>,
     /// Archive hook command invoked before retention prunes this daemon's logs.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub archive_hook: Option<String>,
     /// Log format for this daemon.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub log_format: Option<String>,
+    /// Regex classifying the level of lines the log format doesn't.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_level_regex: Option<String>,
+    /// Patterns for output lines dropped before they are logged.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub log_filter: Vec<String>,
+    /// How output that isn't valid UTF-8 is logged.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_encoding: Option<crate::config_types::LogEncoding>,
+    /// Output lines dropped by `log_filter` since the daemon last started.
+    #[serde(skip_serializing_if = "is_zero", default)]
+    pub log_lines_filtered: u64,
+    /// Output lines dropped by `logs.rate_limit` since the daemon last started.
+    #[serde(skip_serializing_if = "is_zero", default)]
+    pub log_lines_dropped: u64,
+    /// The `alerts` threshold the daemon is currently over, e.g.
+    /// "memory 1.6GB above 1.5GB". Cleared when usage drops back or the
+    /// daemon restarts.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alert: Option<String>,
+    /// Why the running process no longer matches what's on disk, e.g.
+    /// "binary /usr/bin/app was replaced". Cleared when the daemon restarts.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub drift: Option<String>,
+    /// Why the supervisor polls this daemon's `watch` directories instead of
+    /// using native file watching, e.g. "native watch limit reached".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub watch_fallback: Option<String>,
     /// Allocate a pseudo-terminal for the daemon process.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub pty: Option<bool>,
+    /// Run the daemon in its own network namespace (Linux only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub isolate_network: Option<bool>,
+    /// Restart the daemon when its config entry changes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_on_config_change: Option<bool>,
+    /// Restart the daemon when the executable it runs changes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_on_binary_change: Option<bool>,
+    /// Restart the daemon when one of its `depends` is restarted.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_with_dependencies: Option<bool>,
+    /// Keep the daemon's state entry when `pitchfork clean` runs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sticky: Option<bool>,
     /// True for daemons auto-registered from config by the cron watcher,
     /// not yet started. Treated as "available" by list/status/stats.
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
@@ -150,6 +330,8 @@ pub struct RunOptions {
     /// Falls back to joining `cmd` when None (e.g. ad-hoc `pitchfork run -- cmd args`).
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub run: Option<String>,
+    #[serde(default)]
+    pub kind: DaemonKind,
     pub force: bool,
     pub shell_pid: Option<u32>,
     pub dir: Dir,
@@ -157,19 +339,48 @@ pub struct RunOptions {
     pub cron_schedule: Option<String>,
     pub cron_retrigger: Option<CronRetrigger>,
     pub cron_immediate: Option<bool>,
+    pub cron_exclusive: Option<bool>,
     pub retry: Retry,
     pub retry_count: u32,
-    pub ready_delay: Option<u64>,
+    /// Exit codes treated as a clean exit in addition to 0.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub success_exit_codes: Vec<i32>,
+    /// Exit codes that are never retried.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub no_retry_exit_codes: Vec<i32>,
+    #[serde(default, with = "crate::pitchfork_toml::opt_duration")]
+    pub ready_delay: Option<std::time::Duration>,
     pub ready_output: Option<ReadyOutput>,
     pub ready_http: Option<ReadyHttp>,
     pub ready_port: Option<ReadyPort>,
     pub ready_cmd: Option<ReadyCmd>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_log_file: Option<ReadyLogFile>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_unix_socket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_when: Option<ReadyWhen>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fail_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expect_long_running: Option<bool>,
     pub port: Option<PortConfig>,
     pub wait_ready: bool,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub depends: Vec<DaemonId>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub requires: Vec<Requirement>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub env: Option<IndexMap<String, String>>,
+    /// Environment passed with `start --env` for the current run only. It
+    /// overlays `env` and is dropped the next time the daemon is started
+    /// without it.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub env_override: Option<IndexMap<String, String>>,
+    /// When the supervisor restarts the daemon without `env_override`, for
+    /// overrides set by `pitchfork debug enable --duration`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub env_override_until: Option<chrono::DateTime<chrono::Local>>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub watch: Vec<String>,
     #[serde(default)]
@@ -191,30 +402,262 @@ pub struct RunOptions {
     /// Unix user to run this daemon as.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub user: Option<String>,
+    /// User the daemon is started for on a shared supervisor. Ignored when
+    /// sent by a client; the supervisor fills it in from the connection.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub owner: Option<String>,
     /// Memory limit for the daemon process (e.g. "50MB", "1GiB")
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub memory_limit: Option<MemoryLimit>,
     /// CPU usage limit as a percentage (e.g. 80 for 80%, 200 for 2 cores)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cpu_limit: Option<CpuLimit>,
+    /// Resource usage thresholds that raise an alert
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alerts: Option<Alerts>,
     /// Unix signal to send for graceful shutdown (default: SIGTERM)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub stop_signal: Option<StopConfig>,
+    /// File mode creation mask for the daemon process.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub umask: Option<Umask>,
+    /// Resource limits for the daemon process.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub limits: Option<ResourceLimits>,
     /// Archive hook command invoked before retention prunes this daemon's logs.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub archive_hook: Option<String>,
-    /// Log format for this daemon: `json`, `logfmt`, `auto`, or `text`.
+    /// Log format for this daemon: `json`, `logfmt`, `syslog`, `auto`, or `text`.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub log_format: Option<String>,
+    /// Regex classifying the level of lines the log format doesn't.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_level_regex: Option<String>,
+    /// Regex patterns for output lines dropped before they are logged.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub log_filter: Vec<String>,
+    /// How output that isn't valid UTF-8 is logged.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_encoding: Option<crate::config_types::LogEncoding>,
     /// Hook triggered when the daemon produces matching output
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub on_output_hook: Option<crate::pitchfork_toml::OnOutputHook>,
     /// Allocate a pseudo-terminal for the daemon process.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub pty: Option<bool>,
+    /// Run the daemon in its own network namespace (Linux only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub isolate_network: Option<bool>,
+    /// Restart the daemon when its config entry changes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_on_config_change: Option<bool>,
+    /// Restart the daemon when the executable it runs changes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_on_binary_change: Option<bool>,
+    /// Restart the daemon when one of its `depends` is restarted.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_with_dependencies: Option<bool>,
+    /// Keep the daemon's state entry when `pitchfork clean` runs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sticky: Option<bool>,
+    /// Terminate processes holding the daemon's ports before starting it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub kill_conflicts: bool,
+    /// Give up on the ready checks after this long (`start --wait-timeout`)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::pitchfork_toml::opt_duration"
+    )]
+    pub wait_timeout: Option<std::time::Duration>,
+    /// Other daemons restarted by the same request. A restart cascade
+    /// leaves them, and their dependents, to their own restarts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub restart_batch: Vec<DaemonId>,
+}
+
+impl RunOptions {
+    /// The configured environment with any one-time override applied on top.
+    pub fn effective_env(&self) -> Option<IndexMap<String, String>> {
+        merge_env(&self.env, &self.env_override)
+    }
 }
 
 impl Daemon {
+    /// The daemon's options without the state of a particular run: its
+    /// process, status, exit and retry history, runtime overrides and the
+    /// supervisor's notes about it. Used for snapshots and state exports.
+    /// The ports it was given are kept.
+    pub fn without_runtime_state(self) -> Daemon {
+        // Exhaustive so a new field has to be sorted into one or the other
+        let Daemon {
+            id,
+            dir,
+            cmd,
+            run,
+            kind,
+            autostop,
+            cron_schedule,
+            cron_retrigger,
+            cron_immediate,
+            cron_exclusive,
+            retry,
+            success_exit_codes,
+            no_retry_exit_codes,
+            ready_delay,
+            ready_output,
+            ready_http,
+            ready_port,
+            ready_cmd,
+            ready_log_file,
+            ready_unix_socket,
+            ready_when,
+            fail_output,
+            expect_long_running,
+            port,
+            resolved_port,
+            slug,
+            proxy,
+            depends,
+            requires,
+            env,
+            watch,
+            watch_mode,
+            watch_base_dir,
+            mise,
+            user,
+            memory_limit,
+            cpu_limit,
+            alerts,
+            stop_signal,
+            umask,
+            limits,
+            archive_hook,
+            log_format,
+            log_level_regex,
+            log_filter,
+            log_encoding,
+            pty,
+            isolate_network,
+            restart_on_config_change,
+            restart_on_binary_change,
+            restart_with_dependencies,
+            sticky,
+            config_registered,
+            title: _,
+            pid: _,
+            start_time: _,
+            host: _,
+            shell_pid: _,
+            status: _,
+            last_cron_triggered: _,
+            last_exit_success: _,
+            exits: _,
+            startups: _,
+            retry_count: _,
+            next_retry_at: _,
+            active_port: _,
+            waiting_for: _,
+            env_override: _,
+            env_override_until: _,
+            owner: _,
+            log_lines_filtered: _,
+            log_lines_dropped: _,
+            alert: _,
+            drift: _,
+            watch_fallback: _,
+        } = self;
+        Daemon {
+            id,
+            dir,
+            cmd,
+            run,
+            kind,
+            autostop,
+            cron_schedule,
+            cron_retrigger,
+            cron_immediate,
+            cron_exclusive,
+            retry,
+            success_exit_codes,
+            no_retry_exit_codes,
+            ready_delay,
+            ready_output,
+            ready_http,
+            ready_port,
+            ready_cmd,
+            ready_log_file,
+            ready_unix_socket,
+            ready_when,
+            fail_output,
+            expect_long_running,
+            port,
+            resolved_port,
+            slug,
+            proxy,
+            depends,
+            requires,
+            env,
+            watch,
+            watch_mode,
+            watch_base_dir,
+            mise,
+            user,
+            memory_limit,
+            cpu_limit,
+            alerts,
+            stop_signal,
+            umask,
+            limits,
+            archive_hook,
+            log_format,
+            log_level_regex,
+            log_filter,
+            log_encoding,
+            pty,
+            isolate_network,
+            restart_on_config_change,
+            restart_on_binary_change,
+            restart_with_dependencies,
+            sticky,
+            config_registered,
+            ..Default::default()
+        }
+    }
+
+    /// 95th percentile of the ready latency over `startups`, in milliseconds.
+    pub fn ready_p95_ms(&self) -> Option<u64> {
+        let mut ready: Vec<u64> = self.startups.iter().map(|s| s.ready_ms).collect();
+        ready.sort_unstable();
+        // Nearest-rank: the smallest value at or above 95% of the samples
+        let rank = (ready.len() * 95).div_ceil(100);
+        ready.get(rank.checked_sub(1)?).copied()
+    }
+
+    /// Latest ready latency with the p95 over the history, e.g.
+    /// "ready in 4.2s (p95 6.1s)"
+    pub fn startup_summary(&self) -> Option<String> {
+        let last = self.startups.last()?;
+        let mut summary = format!("ready in {}", crate::ui::time::duration_ms(last.ready_ms));
+        if self.startups.len() > 1
+            && let Some(p95) = self.ready_p95_ms()
+        {
+            summary.push_str(&format!(" (p95 {})", crate::ui::time::duration_ms(p95)));
+        }
+        Some(summary)
+    }
+
+    /// Whether output lines get a level when they are logged, from a
+    /// structured `log_format` or a `log_level_regex`
+    pub fn classifies_log_levels(&self) -> bool {
+        let settings = crate::settings::settings();
+        let format = self
+            .log_format
+            .as_deref()
+            .unwrap_or(&settings.logs.log_format);
+        self.log_level_regex.is_some() || matches!(format, "json" | "logfmt" | "syslog")
+    }
+
     /// Build RunOptions from persisted daemon state.
     ///
     /// Carries over all configuration fields from the daemon state.
@@ -240,6 +683,7 @@ impl Daemon {
             id: self.id.clone(),
             cmd,
             run: self.run.clone(),
+            kind: self.kind,
             force: false,
             shell_pid: self.shell_pid,
             dir: Dir(self.dir.clone().unwrap_or_else(|| crate::env::CWD.clone())),
@@ -247,17 +691,28 @@ impl Daemon {
             cron_schedule: self.cron_schedule.clone(),
             cron_retrigger: self.cron_retrigger,
             cron_immediate: self.cron_immediate,
+            cron_exclusive: self.cron_exclusive,
             retry: self.retry,
             retry_count: self.retry_count,
+            success_exit_codes: self.success_exit_codes.clone(),
+            no_retry_exit_codes: self.no_retry_exit_codes.clone(),
             ready_delay: self.ready_delay,
             ready_output: self.ready_output.clone(),
             ready_http: self.ready_http.clone(),
             ready_port: self.ready_port.clone(),
             ready_cmd: self.ready_cmd.clone(),
+            ready_log_file: self.ready_log_file.clone(),
+            ready_unix_socket: self.ready_unix_socket.clone(),
+            ready_when: self.ready_when.clone(),
+            fail_output: self.fail_output.clone(),
+            expect_long_running: self.expect_long_running,
             port: self.port.clone(),
             wait_ready: false,
             depends: self.depends.clone(),
+            requires: self.requires.clone(),
             env: self.env.clone(),
+            env_override: self.env_override.clone(),
+            env_override_until: self.env_override_until,
             watch: self.watch.clone(),
             watch_mode: self.watch_mode,
             watch_base_dir: self.watch_base_dir.clone(),
@@ -265,14 +720,192 @@ impl Daemon {
             slug: self.slug.clone(),
             proxy: self.proxy,
             user: self.user.clone(),
+            owner: self.owner.clone(),
             memory_limit: self.memory_limit,
             cpu_limit: self.cpu_limit,
+            alerts: self.alerts,
             stop_signal: self.stop_signal,
+            umask: self.umask,
+            limits: self.limits,
             archive_hook: self.archive_hook.clone(),
             log_format: self.log_format.clone(),
+            log_level_regex: self.log_level_regex.clone(),
+            log_filter: self.log_filter.clone(),
+            log_encoding: self.log_encoding,
             on_output_hook,
             pty: self.pty,
+            isolate_network: self.isolate_network,
+            restart_on_config_change: self.restart_on_config_change,
+            restart_on_binary_change: self.restart_on_binary_change,
+            restart_with_dependencies: self.restart_with_dependencies,
+            sticky: self.sticky,
+            kill_conflicts: false,
+            wait_timeout: None,
+            restart_batch: vec![],
+        }
+    }
+
+    /// The daemon's environment with any one-time override applied on top.
+    pub fn effective_env(&self) -> Option<IndexMap<String, String>> {
+        merge_env(&self.env, &self.env_override)
+    }
+
+    /// Whether the daemon last errored with one of its `no_retry_exit_codes`,
+    /// in which case it must not be retried.
+    pub fn exited_with_no_retry_code(&self) -> bool {
+        matches!(
+            self.status,
+            DaemonStatus::Errored(code) if self.no_retry_exit_codes.contains(&code)
+        )
+    }
+
+    /// Whether the supervisor will retry the daemon in the background.
+    pub fn retry_pending(&self) -> bool {
+        self.status.is_errored()
+            && !self.exited_with_no_retry_code()
+            && self.pid.is_none()
+            && self.retry.count() > 0
+            && self.retry_count < self.retry.count()
+    }
+
+    /// The pending retry attempt and when it happens, e.g. "2/5 in 14s"
+    pub fn retry_summary(&self, now: chrono::DateTime<chrono::Local>) -> Option<String> {
+        if !self.retry_pending() {
+            return None;
+        }
+        let attempt = if self.retry.is_infinite() {
+            (self.retry_count + 1).to_string()
+        } else {
+            format!("{}/{}", self.retry_count + 1, self.retry.count())
+        };
+        Some(match self.next_retry_at {
+            Some(at) if at > now => format!("{attempt} {}", crate::ui::time::until(at, now)),
+            _ => format!("{attempt} due"),
+        })
+    }
+
+    /// Whether the recorded `pid` is a process on this machine.
+    pub fn is_local(&self) -> bool {
+        self.host
+            .as_deref()
+            .is_none_or(|host| host == crate::env::HOSTNAME.as_str())
+    }
+
+    /// The recorded `pid`, if it belongs to this machine. PIDs recorded by
+    /// another host sharing the state directory must never be checked or
+    /// signalled here.
+    pub fn local_pid(&self) -> Option<u32> {
+        self.pid.filter(|_| self.is_local())
+    }
+
+    /// A task whose last run exited successfully.
+    pub fn is_completed(&self) -> bool {
+        self.kind.is_task() && self.status.is_stopped() && self.last_exit_success == Some(true)
+    }
+
+    /// The status to show for the daemon: `completed` for a finished task,
+    /// otherwise its status.
+    pub fn status_label(&self) -> String {
+        if self.is_completed() {
+            "completed".to_string()
+        } else {
+            self.status.to_string()
+        }
+    }
+
+    /// Why the daemon errored or failed, naming the signal when something
+    /// outside pitchfork killed it.
+    pub fn error_message(&self) -> Option<String> {
+        let message = self.status.error_message()?;
+        match (&self.status, self.exits.last()) {
+            (DaemonStatus::Errored(_), Some(exit)) if exit.reason == "killed" => {
+                match exit.signal {
+                    Some(signal) => Some(format!("killed externally (signal {signal})")),
+                    None => Some("killed externally".to_string()),
+                }
+            }
+            _ => Some(message),
+        }
+    }
+
+    /// The host the daemon is running on, if that isn't this machine.
+    pub fn remote_host(&self) -> Option<&str> {
+        self.host
+            .as_deref()
+            .filter(|_| self.pid.is_some() && !self.is_local())
+    }
+
+    /// What differs between the run the daemon was last started with and
+    /// `opts`: its command, env keys, ports and watch globs, one line each.
+    /// Env values are left out as they often hold secrets. Empty if the
+    /// daemon has never run.
+    pub fn config_changes(&self, opts: &RunOptions) -> Vec<String> {
+        let mut changes = vec![];
+        let Some(old_cmd) = self
+            .run
+            .clone()
+            .or_else(|| self.cmd.as_deref().map(shell_words::join))
+        else {
+            return changes;
+        };
+        let new_cmd = opts
+            .run
+            .clone()
+            .unwrap_or_else(|| shell_words::join(&opts.cmd));
+        if old_cmd != new_cmd {
+            changes.push(format!("command: {old_cmd} → {new_cmd}"));
+        }
+
+        let old_env = self.effective_env().unwrap_or_default();
+        let new_env = opts.effective_env().unwrap_or_default();
+        let mut env = vec![];
+        for (key, value) in &new_env {
+            match old_env.get(key) {
+                None => env.push(format!("+{key}")),
+                Some(old) if old != value => env.push(format!("~{key}")),
+                Some(_) => {}
+            }
         }
+        env.extend(
+            old_env
+                .keys()
+                .filter(|key| !new_env.contains_key(*key))
+                .map(|key| format!("-{key}")),
+        );
+        if !env.is_empty() {
+            changes.push(format!("env: {}", env.join(", ")));
+        }
+
+        let ports = |port: &Option<PortConfig>| match port {
+            Some(port) if !port.expect.is_empty() => port
+                .expect
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => "none".to_string(),
+        };
+        let (old_ports, new_ports) = (ports(&self.port), ports(&opts.port));
+        if old_ports != new_ports {
+            changes.push(format!("ports: {old_ports} → {new_ports}"));
+        }
+
+        let watch = opts
+            .watch
+            .iter()
+            .filter(|glob| !self.watch.contains(glob))
+            .map(|glob| format!("+{glob}"))
+            .chain(
+                self.watch
+                    .iter()
+                    .filter(|glob| !opts.watch.contains(glob))
+                    .map(|glob| format!("-{glob}")),
+            )
+            .collect::<Vec<_>>();
+        if !watch.is_empty() {
+            changes.push(format!("watch: {}", watch.join(", ")));
+        }
+        changes
     }
 }
 
@@ -286,6 +919,104 @@ impl Display for Daemon {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_startup_summary() {
+        let mut daemon = Daemon::default();
+        assert_eq!(daemon.startup_summary(), None);
+        assert_eq!(daemon.ready_p95_ms(), None);
+
+        let startup = |ready_ms| StartupRecord {
+            at: chrono::Local::now(),
+            start_ms: 5,
+            ready_ms,
+        };
+        daemon.startups.push(startup(4_200));
+        assert_eq!(daemon.startup_summary().as_deref(), Some("ready in 4.2s"));
+
+        daemon.startups = (1..=20).map(|i| startup(i * 1000)).collect();
+        daemon.startups.push(startup(500));
+        assert_eq!(daemon.ready_p95_ms(), Some(19_000));
+        assert_eq!(
+            daemon.startup_summary().as_deref(),
+            Some("ready in 500ms (p95 19.0s)")
+        );
+    }
+
+    #[test]
+    fn test_retry_summary() {
+        let now = chrono::Local::now();
+        let mut daemon = Daemon {
+            status: DaemonStatus::Errored(1),
+            retry: Retry(5),
+            retry_count: 1,
+            next_retry_at: Some(now + chrono::Duration::seconds(14)),
+            ..Default::default()
+        };
+        assert_eq!(daemon.retry_summary(now).as_deref(), Some("2/5 in 14s"));
+
+        daemon.next_retry_at = Some(now - chrono::Duration::seconds(1));
+        assert_eq!(daemon.retry_summary(now).as_deref(), Some("2/5 due"));
+
+        daemon.retry = Retry::INFINITE;
+        assert_eq!(daemon.retry_summary(now).as_deref(), Some("2 due"));
+
+        daemon.retry = Retry(1);
+        assert_eq!(daemon.retry_summary(now), None);
+    }
+
+    #[test]
+    fn test_without_runtime_state() {
+        let now = chrono::Local::now();
+        let options = Daemon {
+            id: DaemonId::new("proj", "api"),
+            run: Some("npm start".to_string()),
+            cmd: Some(vec!["npm".to_string(), "start".to_string()]),
+            retry: Retry(3),
+            resolved_port: vec![3000],
+            env: Some(IndexMap::from([("A".to_string(), "1".to_string())])),
+            ..Default::default()
+        };
+        let daemon = Daemon {
+            title: Some("node".to_string()),
+            pid: Some(42),
+            start_time: Some(7),
+            host: Some("other".to_string()),
+            shell_pid: Some(9),
+            status: DaemonStatus::Errored(1),
+            last_cron_triggered: Some(now),
+            last_exit_success: Some(false),
+            exits: vec![ExitRecord {
+                at: now,
+                code: 1,
+                signal: None,
+                runtime_secs: 3,
+                reason: "fail".to_string(),
+            }],
+            startups: vec![StartupRecord {
+                at: now,
+                start_ms: 1,
+                ready_ms: 2,
+            }],
+            retry_count: 2,
+            next_retry_at: Some(now),
+            active_port: Some(3000),
+            waiting_for: Some("postgres".to_string()),
+            env_override: Some(IndexMap::from([("B".to_string(), "2".to_string())])),
+            env_override_until: Some(now),
+            owner: Some("alice".to_string()),
+            log_lines_filtered: 5,
+            log_lines_dropped: 6,
+            alert: Some("memory".to_string()),
+            drift: Some("binary replaced".to_string()),
+            watch_fallback: Some("polling".to_string()),
+            ..options.clone()
+        };
+        assert_eq!(
+            serde_json::to_value(daemon.without_runtime_state()).unwrap(),
+            serde_json::to_value(options).unwrap()
+        );
+    }
+
     #[test]
     fn test_valid_daemon_ids() {
         // Short IDs
@@ -348,4 +1079,139 @@ mod tests {
         assert!(!is_valid_daemon_id("app@host"));
         assert!(!is_valid_daemon_id("app:8080"));
     }
+
+    #[test]
+    fn test_pid_from_other_host_is_not_local() {
+        let local = Daemon {
+            pid: Some(42),
+            host: Some(crate::env::HOSTNAME.clone()),
+            ..Default::default()
+        };
+        assert_eq!(local.local_pid(), Some(42));
+        assert_eq!(local.remote_host(), None);
+
+        // State written before hosts were recorded is treated as local
+        let legacy = Daemon {
+            pid: Some(42),
+            ..Default::default()
+        };
+        assert_eq!(legacy.local_pid(), Some(42));
+
+        let remote = Daemon {
+            pid: Some(42),
+            host: Some(format!("{}-other", *crate::env::HOSTNAME)),
+            ..Default::default()
+        };
+        assert_eq!(remote.local_pid(), None);
+        assert!(remote.remote_host().is_some());
+    }
+
+    #[test]
+    fn test_completed_task() {
+        let task = Daemon {
+            kind: DaemonKind::Task,
+            status: DaemonStatus::Stopped,
+            last_exit_success: Some(true),
+            ..Default::default()
+        };
+        assert!(task.is_completed());
+        assert_eq!(task.status_label(), "completed");
+
+        let failed = Daemon {
+            status: DaemonStatus::Errored(1),
+            last_exit_success: Some(false),
+            ..task.clone()
+        };
+        assert!(!failed.is_completed());
+        assert_eq!(failed.status_label(), "errored");
+
+        // A daemon that exits cleanly is just stopped
+        let daemon = Daemon {
+            kind: DaemonKind::Daemon,
+            ..task
+        };
+        assert!(!daemon.is_completed());
+        assert_eq!(daemon.status_label(), "stopped");
+    }
+
+    #[test]
+    fn test_error_message_external_kill() {
+        let exit = |reason: &str| ExitRecord {
+            at: chrono::Local::now(),
+            code: -1,
+            signal: Some(9),
+            runtime_secs: 5,
+            reason: reason.to_string(),
+        };
+        let killed = Daemon {
+            status: DaemonStatus::Errored(-1),
+            exits: vec![exit("killed")],
+            ..Default::default()
+        };
+        assert_eq!(
+            killed.error_message().as_deref(),
+            Some("killed externally (signal 9)")
+        );
+
+        let failed = Daemon {
+            exits: vec![exit("fail")],
+            ..killed.clone()
+        };
+        assert_eq!(failed.error_message().as_deref(), Some("unknown exit code"));
+
+        // A stopped daemon has no error, however its last run ended
+        let stopped = Daemon {
+            status: DaemonStatus::Stopped,
+            ..killed
+        };
+        assert_eq!(stopped.error_message(), None);
+    }
+
+    #[test]
+    fn test_config_changes() {
+        let env = |pairs: &[(&str, &str)]| {
+            Some(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<IndexMap<_, _>>(),
+            )
+        };
+        let daemon = Daemon {
+            run: Some("node server.js".to_string()),
+            env: env(&[("PORT", "3000"), ("DEBUG", "1"), ("OLD", "x")]),
+            port: PortConfig::from_parts(vec![3000], Default::default()),
+            watch: vec!["src/**/*.js".to_string()],
+            ..Default::default()
+        };
+
+        let same = RunOptions {
+            run: daemon.run.clone(),
+            env: daemon.env.clone(),
+            port: daemon.port.clone(),
+            watch: daemon.watch.clone(),
+            ..Default::default()
+        };
+        assert!(daemon.config_changes(&same).is_empty());
+
+        let changed = RunOptions {
+            run: Some("node server.js --inspect".to_string()),
+            env: env(&[("PORT", "3001"), ("DEBUG", "1"), ("NEW", "y")]),
+            port: PortConfig::from_parts(vec![3001], Default::default()),
+            watch: vec!["src/**/*.ts".to_string()],
+            ..same
+        };
+        assert_eq!(
+            daemon.config_changes(&changed),
+            vec![
+                "command: node server.js → node server.js --inspect",
+                "env: ~PORT, +NEW, -OLD",
+                "ports: 3000 → 3001",
+                "watch: +src/**/*.ts, -src/**/*.js",
+            ]
+        );
+
+        // A daemon that never ran has nothing to compare against
+        assert!(Daemon::default().config_changes(&changed).is_empty());
+    }
 }
@@ -5,6 +5,7 @@ use crate::daemon_status::DaemonStatus;
 use crate::ipc::client::IpcClient;
 use crate::pitchfork_toml::PitchforkToml;
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 /// Represents a daemon entry that can be either tracked (from state file) or available (from config only)
 #[derive(Debug, Clone)]
@@ -12,7 +13,13 @@ pub struct DaemonListEntry {
     pub id: DaemonId,
     pub daemon: Daemon,
     pub is_disabled: bool,
+    /// Only automatic starts are disabled (`pitchfork disable --auto`)
+    pub is_auto_disabled: bool,
     pub is_available: bool, // true if daemon is only in config, not in state
+    /// Alternative names from the daemon's config (`aliases`)
+    pub aliases: Vec<String>,
+    /// The config file that defines the daemon, if it's in a loaded config
+    pub config_path: Option<PathBuf>,
 }
 
 /// Get a unified list of all daemons from IPC client and config
@@ -33,14 +40,40 @@ pub struct DaemonListEntry {
 pub async fn get_all_daemons(client: &IpcClient) -> Result<Vec<DaemonListEntry>> {
     let config = PitchforkToml::all_merged()?;
 
-    // Read state file to get all daemons (including failed ones)
-    let state_file = crate::state_file::StateFile::read(&*crate::env::PITCHFORK_STATE_FILE)?;
-    let state_daemons: Vec<Daemon> = state_file.daemons.values().cloned().collect();
+    // Read state file to get all daemons (including failed ones). Users of a
+    // shared supervisor cannot read it and get their daemons over IPC.
+    let (state_daemons, auto_disabled): (Vec<Daemon>, Vec<DaemonId>) =
+        if crate::supervisor::users::shared() || crate::ipc::client::view_user().is_some() {
+            client.user_daemons().await?
+        } else {
+            let state_file =
+                crate::state_file::StateFile::read(&*crate::env::PITCHFORK_STATE_FILE)?;
+            (
+                state_file.daemons.into_values().collect(),
+                state_file.auto_disabled.into_iter().collect(),
+            )
+        };
 
     let disabled_daemons = client.get_disabled_daemons().await?;
     let disabled_set: HashSet<DaemonId> = disabled_daemons.into_iter().collect();
+    let auto_disabled_set: HashSet<DaemonId> = auto_disabled.into_iter().collect();
+
+    build_daemon_list(state_daemons, disabled_set, auto_disabled_set, config)
+}
+
+/// Get a unified list of all daemons from the state file alone, without
+/// talking to the supervisor
+///
+/// Used by `pitchfork list` when the supervisor is not running: statuses are
+/// the last ones the supervisor recorded and may be stale.
+pub fn get_all_daemons_offline() -> Result<Vec<DaemonListEntry>> {
+    let config = PitchforkToml::all_merged()?;
+    let state_file = crate::state_file::StateFile::read(&*crate::env::PITCHFORK_STATE_FILE)?;
+    let state_daemons: Vec<Daemon> = state_file.daemons.into_values().collect();
+    let disabled_set: HashSet<DaemonId> = state_file.disabled.into_iter().collect();
+    let auto_disabled_set: HashSet<DaemonId> = state_file.auto_disabled.into_iter().collect();
 
-    build_daemon_list(state_daemons, disabled_set, config)
+    build_daemon_list(state_daemons, disabled_set, auto_disabled_set, config)
 }
 
 /// Get a unified list of all daemons from supervisor directly (for Web UI)
@@ -62,9 +95,11 @@ pub async fn get_all_daemons_direct(
     let state_file = supervisor.state_file.lock().await;
     let state_daemons: Vec<Daemon> = state_file.daemons.values().cloned().collect();
     let disabled_set: HashSet<DaemonId> = state_file.disabled.clone().into_iter().collect();
+    let auto_disabled_set: HashSet<DaemonId> =
+        state_file.auto_disabled.clone().into_iter().collect();
     drop(state_file); // Release lock early
 
-    build_daemon_list(state_daemons, disabled_set, config)
+    build_daemon_list(state_daemons, disabled_set, auto_disabled_set, config)
 }
 
 /// Look up a single daemon by ID from state + config (for Web UI show handler).
@@ -84,15 +119,23 @@ pub async fn get_daemon_direct(
     let state_file = supervisor.state_file.lock().await;
     if let Some(daemon) = state_file.daemons.get(id).cloned() {
         let is_disabled = state_file.disabled.contains(id);
+        let is_auto_disabled = state_file.auto_disabled.contains(id);
         drop(state_file);
+        let config_path = PitchforkToml::all_merged()
+            .ok()
+            .and_then(|c| source_config(&c, id));
         return Ok(Some(DaemonListEntry {
             id: id.clone(),
             is_available: daemon.config_registered,
             daemon,
             is_disabled,
+            is_auto_disabled,
+            aliases: Vec::new(),
+            config_path,
         }));
     }
     let is_disabled = state_file.disabled.contains(id);
+    let is_auto_disabled = state_file.auto_disabled.contains(id);
     drop(state_file);
 
     // Not in state — look in local config
@@ -102,7 +145,10 @@ pub async fn get_daemon_direct(
             id: id.clone(),
             daemon: build_placeholder_daemon(id, daemon_config),
             is_disabled,
+            is_auto_disabled,
             is_available: true,
+            aliases: daemon_config.aliases.clone(),
+            config_path: daemon_config.source_config().map(PathBuf::from),
         }));
     }
 
@@ -116,7 +162,10 @@ pub async fn get_daemon_direct(
                         id: id.clone(),
                         daemon: build_placeholder_daemon(id, daemon_config),
                         is_disabled,
+                        is_auto_disabled,
                         is_available: true,
+                        aliases: daemon_config.aliases.clone(),
+                        config_path: daemon_config.source_config().map(PathBuf::from),
                     }));
                 }
             }
@@ -150,6 +199,7 @@ pub fn build_placeholder_daemon(
         proxy: None,
         memory_limit: daemon_config.memory_limit,
         cpu_limit: daemon_config.cpu_limit,
+        alerts: daemon_config.alerts,
         ..Daemon::default()
     }
 }
@@ -158,6 +208,7 @@ pub fn build_placeholder_daemon(
 fn build_daemon_list(
     state_daemons: Vec<Daemon>,
     disabled_set: HashSet<DaemonId>,
+    auto_disabled_set: HashSet<DaemonId>,
     config: PitchforkToml,
 ) -> Result<Vec<DaemonListEntry>> {
     let mut entries = Vec::new();
@@ -166,6 +217,24 @@ fn build_daemon_list(
     // Skip the supervisor itself
     let pitchfork_id = DaemonId::pitchfork();
 
+    // Load registered namespaces up front: they list their available daemons
+    // below, and also tell state daemons from those projects their source.
+    let namespace_configs: Vec<PitchforkToml> = PitchforkToml::read_global_namespaces()
+        .into_iter()
+        .filter_map(
+            |(ns_name, entry)| match PitchforkToml::all_merged_from(&entry.dir) {
+                Ok(ns_config) => Some(ns_config),
+                Err(e) => {
+                    log::warn!(
+                        "Failed to load namespace '{ns_name}' from {}: {e}",
+                        entry.dir.display()
+                    );
+                    None
+                }
+            },
+        )
+        .collect();
+
     // First, add all daemons from state file
     for daemon in state_daemons {
         if daemon.id == pitchfork_id {
@@ -177,11 +246,22 @@ fn build_daemon_list(
         // No override needed here — daemon_list consumers call .unwrap_or(settings()...) themselves.
 
         seen_ids.insert(daemon.id.clone());
+        let aliases = config
+            .daemons
+            .get(&daemon.id)
+            .map(|d| d.aliases.clone())
+            .unwrap_or_default();
+        let config_path = std::iter::once(&config)
+            .chain(&namespace_configs)
+            .find_map(|c| source_config(c, &daemon.id));
         entries.push(DaemonListEntry {
             id: daemon.id.clone(),
             is_disabled: disabled_set.contains(&daemon.id),
+            is_auto_disabled: auto_disabled_set.contains(&daemon.id),
             is_available: daemon.config_registered,
             daemon,
+            aliases,
+            config_path,
         });
     }
 
@@ -197,38 +277,42 @@ fn build_daemon_list(
             id: daemon_id.clone(),
             daemon: placeholder,
             is_disabled: disabled_set.contains(daemon_id),
+            is_auto_disabled: auto_disabled_set.contains(daemon_id),
             is_available: true,
+            aliases: daemon_config.aliases.clone(),
+            config_path: daemon_config.source_config().map(PathBuf::from),
         });
         seen_ids.insert(daemon_id.clone());
     }
 
     // Add daemons from registered namespaces
-    let namespaces = PitchforkToml::read_global_namespaces();
-    for (ns_name, entry) in namespaces {
-        match PitchforkToml::all_merged_from(&entry.dir) {
-            Ok(ns_config) => {
-                for (daemon_id, daemon_config) in &ns_config.daemons {
-                    if *daemon_id == pitchfork_id || seen_ids.contains(daemon_id) {
-                        continue;
-                    }
-                    let placeholder = build_placeholder_daemon(daemon_id, daemon_config);
-                    entries.push(DaemonListEntry {
-                        id: daemon_id.clone(),
-                        daemon: placeholder,
-                        is_disabled: disabled_set.contains(daemon_id),
-                        is_available: true,
-                    });
-                    seen_ids.insert(daemon_id.clone());
-                }
-            }
-            Err(e) => {
-                log::warn!(
-                    "Failed to load namespace '{ns_name}' from {}: {e}",
-                    entry.dir.display()
-                );
+    for ns_config in &namespace_configs {
+        for (daemon_id, daemon_config) in &ns_config.daemons {
+            if *daemon_id == pitchfork_id || seen_ids.contains(daemon_id) {
+                continue;
             }
+            let placeholder = build_placeholder_daemon(daemon_id, daemon_config);
+            entries.push(DaemonListEntry {
+                id: daemon_id.clone(),
+                daemon: placeholder,
+                is_disabled: disabled_set.contains(daemon_id),
+                is_auto_disabled: auto_disabled_set.contains(daemon_id),
+                is_available: true,
+                aliases: daemon_config.aliases.clone(),
+                config_path: daemon_config.source_config().map(PathBuf::from),
+            });
+            seen_ids.insert(daemon_id.clone());
         }
     }
 
     Ok(entries)
 }
+
+/// The config file in `config` that defines `id`, if any
+fn source_config(config: &PitchforkToml, id: &DaemonId) -> Option<PathBuf> {
+    config
+        .daemons
+        .get(id)
+        .and_then(|d| d.source_config())
+        .map(PathBuf::from)
+}
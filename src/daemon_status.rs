@@ -7,8 +7,15 @@ use serde::{Deserialize, Serialize};
 pub enum DaemonStatus {
     Failed(String),
     Waiting,
+    /// Spawned, but its ready check hasn't passed yet
+    Starting,
     Running,
+    /// Running, but failing its periodic health check
+    Unhealthy,
     Stopping,
+    /// Left running by `supervisor stop --keep-daemons`, to be adopted by
+    /// the next supervisor
+    Detached,
     /// Exit code of the process, or -1 if unknown.
     Errored(i32),
     #[default]
@@ -21,13 +28,28 @@ impl DaemonStatus {
         match self {
             DaemonStatus::Failed(_) => console::style(s).red().to_string(),
             DaemonStatus::Waiting => console::style(s).yellow().to_string(),
+            DaemonStatus::Starting => console::style(s).yellow().to_string(),
             DaemonStatus::Running => console::style(s).green().to_string(),
+            DaemonStatus::Unhealthy => console::style(s).magenta().to_string(),
             DaemonStatus::Stopping => console::style(s).yellow().to_string(),
+            DaemonStatus::Detached => console::style(s).cyan().to_string(),
             DaemonStatus::Stopped => console::style(s).dim().to_string(),
             DaemonStatus::Errored(_) => console::style(s).red().to_string(),
         }
     }
 
+    /// Whether the daemon's process is alive: running, still starting,
+    /// running but unhealthy, or left running without a supervisor.
+    pub fn is_up(&self) -> bool {
+        matches!(
+            self,
+            DaemonStatus::Starting
+                | DaemonStatus::Running
+                | DaemonStatus::Unhealthy
+                | DaemonStatus::Detached
+        )
+    }
+
     pub fn error_message(&self) -> Option<String> {
         match self {
             DaemonStatus::Failed(msg) => Some(msg.clone()),
@@ -47,13 +69,28 @@ mod tests {
             ("running", DaemonStatus::Running),
             ("stopped", DaemonStatus::Stopped),
             ("waiting", DaemonStatus::Waiting),
+            ("starting", DaemonStatus::Starting),
+            ("unhealthy", DaemonStatus::Unhealthy),
             ("stopping", DaemonStatus::Stopping),
+            ("detached", DaemonStatus::Detached),
             ("failed", DaemonStatus::Failed("some error".to_string())),
             ("errored", DaemonStatus::Errored(1)),
             ("errored_unknown", DaemonStatus::Errored(-1)),
         ]
     }
 
+    #[test]
+    fn test_is_up() {
+        assert!(DaemonStatus::Starting.is_up());
+        assert!(DaemonStatus::Running.is_up());
+        assert!(DaemonStatus::Unhealthy.is_up());
+        assert!(DaemonStatus::Detached.is_up());
+        assert!(!DaemonStatus::Waiting.is_up());
+        assert!(!DaemonStatus::Stopping.is_up());
+        assert!(!DaemonStatus::Stopped.is_up());
+        assert!(!DaemonStatus::Errored(1).is_up());
+    }
+
     #[test]
     fn test_daemon_status_json_roundtrip() {
         for (name, status) in all_variants() {
@@ -22,7 +22,9 @@ pub struct DependencyOrder {
 /// - Level 1: daemons that only depend on level 0
 /// - Level N: daemons that only depend on levels 0..(N-1)
 ///
-/// Daemons within the same level can be started in parallel.
+/// Daemons within the same level can be started in parallel. Each level is
+/// ordered by descending `start_priority`, then by ID, which is the order the
+/// batch planner spawns (and staggers) them in.
 pub fn resolve_dependencies(
     requested: &[DaemonId],
     all_daemons: &IndexMap<DaemonId, PitchforkTomlDaemon>,
@@ -106,8 +108,7 @@ pub fn resolve_dependencies(
         .map(|(id, _)| id.clone())
         .collect();
 
-    // Sort for deterministic order
-    current_level.sort();
+    sort_level(&mut current_level, all_daemons);
 
     while !current_level.is_empty() {
         let mut next_level = Vec::new();
@@ -133,7 +134,7 @@ pub fn resolve_dependencies(
         }
 
         levels.push(current_level);
-        next_level.sort(); // Sort for deterministic order
+        sort_level(&mut next_level, all_daemons);
         current_level = next_level;
     }
 
@@ -150,6 +151,15 @@ pub fn resolve_dependencies(
     Ok(DependencyOrder { levels })
 }
 
+/// Sort a level for a deterministic start order: higher `start_priority`
+/// first, ties broken by ID.
+fn sort_level(level: &mut [DaemonId], all_daemons: &IndexMap<DaemonId, PitchforkTomlDaemon>) {
+    level.sort_by_cached_key(|id| {
+        let priority = all_daemons.get(id).and_then(|d| d.start_priority);
+        (std::cmp::Reverse(priority.unwrap_or(0)), id.clone())
+    });
+}
+
 /// Compute the order in which daemons should be stopped, respecting
 /// reverse dependency order (dependents first, then their dependencies).
 ///
@@ -414,4 +424,33 @@ mod tests {
         assert!(result.levels[1].contains(&id("api")));
         assert!(result.levels[1].contains(&id("worker")));
     }
+
+    #[test]
+    fn test_start_priority_orders_within_level() {
+        let mut daemons = IndexMap::new();
+        daemons.insert(id("db"), make_daemon(vec![]));
+        daemons.insert(
+            id("search"),
+            PitchforkTomlDaemon {
+                start_priority: Some(10),
+                ..make_daemon(vec![])
+            },
+        );
+        daemons.insert(
+            id("cache"),
+            PitchforkTomlDaemon {
+                start_priority: Some(-1),
+                ..make_daemon(vec![])
+            },
+        );
+        daemons.insert(id("api"), make_daemon(vec!["db"]));
+
+        let all_ids: Vec<DaemonId> = daemons.keys().cloned().collect();
+        let result = resolve_dependencies(&all_ids, &daemons).unwrap();
+
+        // Priority never moves a daemon ahead of its dependencies
+        assert_eq!(result.levels.len(), 2);
+        assert_eq!(result.levels[0], vec![id("search"), id("db"), id("cache")]);
+        assert_eq!(result.levels[1], vec![id("api")]);
+    }
 }
@@ -83,6 +83,31 @@ pub static IPC_SOCK_MAIN: Lazy<PathBuf> = Lazy::new(|| IPC_SOCK_DIR.join("main.s
 pub static ORIGINAL_PATH: Lazy<Option<String>> = Lazy::new(|| var("PATH").ok());
 pub static IPC_JSON: Lazy<bool> = Lazy::new(|| !var_false("IPC_JSON"));
 
+/// Name of this machine, recorded next to daemon PIDs in the state file so a
+/// state directory shared between hosts (e.g. an NFS home directory) never
+/// has another machine's PIDs checked or signalled locally.
+pub static HOSTNAME: Lazy<String> = Lazy::new(|| {
+    #[cfg(unix)]
+    let hostname = nix::unistd::gethostname()
+        .ok()
+        .and_then(|h| h.into_string().ok());
+    #[cfg(windows)]
+    let hostname = var("COMPUTERNAME").ok();
+    hostname
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+});
+
+/// Editor for editing config as text: `$VISUAL`, then `$EDITOR`, then `vi`
+/// (`notepad` on Windows). May include arguments, e.g. `code --wait`.
+pub static EDITOR: Lazy<String> = Lazy::new(|| {
+    var("VISUAL")
+        .or_else(|_| var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+});
+
 fn var_path(name: &str) -> Option<PathBuf> {
     var(name).map(PathBuf::from).ok()
 }
@@ -249,6 +249,40 @@ pub enum ConfigParseError {
         ns: String,
     },
 
+    #[error(
+        "alias '{alias}' of daemon '{daemon}' conflicts with {conflict} ({})",
+        path.display()
+    )]
+    #[diagnostic(
+        code(pitchfork::config::alias_conflict),
+        url("https://pitchfork.jdx.dev/configuration#aliases"),
+        help(
+            "aliases must be unique within a namespace and must not match the name of another daemon"
+        )
+    )]
+    AliasConflict {
+        alias: String,
+        daemon: String,
+        conflict: String,
+        path: PathBuf,
+    },
+
+    #[error(
+        "daemon '{daemon}' uses undefined env template '{template}' ({})",
+        path.display()
+    )]
+    #[diagnostic(
+        code(pitchfork::config::unknown_env_template),
+        url("https://pitchfork.jdx.dev/configuration#shared-environment")
+    )]
+    UnknownEnvTemplate {
+        template: String,
+        daemon: String,
+        path: PathBuf,
+        #[help]
+        suggestion: Option<String>,
+    },
+
     #[error(
         "invalid namespace '{namespace}' in {}: {reason}",
         path.display()
@@ -341,12 +375,22 @@ pub enum IpcError {
         help: String,
     },
 
+    #[error("supervisor failed to start: {reason}")]
+    #[diagnostic(
+        code(pitchfork::ipc::supervisor_failed),
+        url("https://pitchfork.jdx.dev/supervisor"),
+        help(
+            "run `pitchfork supervisor run` to start it in the foreground and see the full error"
+        )
+    )]
+    SupervisorFailed { reason: String },
+
     #[error("IPC request timed out after {seconds}s")]
     #[diagnostic(
         code(pitchfork::ipc::timeout),
         url("https://pitchfork.jdx.dev/supervisor"),
         help(
-            "the supervisor may be unresponsive or overloaded.\nCheck supervisor status: pitchfork supervisor status\nView logs: pitchfork logs"
+            "the supervisor may be unresponsive or overloaded.\nCheck supervisor status: pitchfork supervisor status\nView logs: pitchfork logs\nWait longer with PITCHFORK_IPC_TIMEOUT (e.g. 30s)"
         )
     )]
     Timeout { seconds: u64 },
@@ -385,6 +429,13 @@ pub enum IpcError {
     #[error("IPC message is invalid: {reason}")]
     #[diagnostic(code(pitchfork::ipc::invalid_message))]
     InvalidMessage { reason: String },
+
+    #[error("supervisor could not decode the request: {reason}")]
+    #[diagnostic(
+        code(pitchfork::ipc::protocol_error),
+        help("this may indicate a version mismatch between the CLI and supervisor")
+    )]
+    ProtocolError { reason: String },
 }
 
 /// A collection of multiple errors that occurred during validation or processing.
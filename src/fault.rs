@@ -0,0 +1,235 @@
+//! Fault injection for chaos testing the supervisor
+//!
+//! Hidden, for development only. When the supervisor starts with
+//! `PITCHFORK_FAULT_INJECT` set (or via `pitchfork debug chaos`), it randomly:
+//!
+//! - delays IPC responses (`ipc_delay=P` or `ipc_delay=P:MAX`, up to `MAX`,
+//!   default 1s)
+//! - fails state file writes (`state_write=P`)
+//! - SIGKILLs a running daemon on each refresh tick (`kill=P`)
+//!
+//! `P` is a probability between 0 and 1. Faults are drawn from a seeded PRNG
+//! (`seed=N`, random by default and always logged), so a run can be replayed:
+//!
+//! ```text
+//! PITCHFORK_FAULT_INJECT="seed=42,ipc_delay=0.2:2s,state_write=0.1,kill=0.05"
+//! ```
+//!
+//! Only the supervisor process injects faults; CLI commands that inherit the
+//! variable are unaffected.
+
+use crate::Result;
+use miette::{bail, miette};
+use once_cell::sync::OnceCell;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static FAULTS: OnceCell<FaultInjector> = OnceCell::new();
+
+const DEFAULT_IPC_DELAY_MAX: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultConfig {
+    pub seed: u64,
+    pub ipc_delay: f64,
+    pub ipc_delay_max: Duration,
+    pub state_write: f64,
+    pub kill: f64,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            seed: random_seed(),
+            ipc_delay: 0.0,
+            ipc_delay_max: DEFAULT_IPC_DELAY_MAX,
+            state_write: 0.0,
+            kill: 0.0,
+        }
+    }
+}
+
+impl FaultConfig {
+    /// Parse a `PITCHFORK_FAULT_INJECT` spec such as
+    /// `seed=42,ipc_delay=0.2:2s,state_write=0.1,kill=0.05`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut config = Self::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| miette!("invalid fault spec {part:?}, expected key=value"))?;
+            match key.trim() {
+                "seed" => {
+                    config.seed = value
+                        .parse()
+                        .map_err(|_| miette!("invalid fault seed {value:?}"))?
+                }
+                "ipc_delay" => {
+                    let (p, max) = match value.split_once(':') {
+                        Some((p, max)) => (
+                            p,
+                            humantime::parse_duration(max)
+                                .map_err(|e| miette!("invalid ipc_delay maximum {max:?}: {e}"))?,
+                        ),
+                        None => (value, DEFAULT_IPC_DELAY_MAX),
+                    };
+                    config.ipc_delay = parse_probability(key, p)?;
+                    config.ipc_delay_max = max;
+                }
+                "state_write" => config.state_write = parse_probability(key, value)?,
+                "kill" => config.kill = parse_probability(key, value)?,
+                other => bail!(
+                    "unknown fault {other:?}, expected one of: seed, ipc_delay, state_write, kill"
+                ),
+            }
+        }
+        Ok(config)
+    }
+
+    /// The spec that reproduces this configuration.
+    pub fn to_spec(&self) -> String {
+        format!(
+            "seed={},ipc_delay={}:{},state_write={},kill={}",
+            self.seed,
+            self.ipc_delay,
+            humantime::format_duration(self.ipc_delay_max),
+            self.state_write,
+            self.kill
+        )
+    }
+}
+
+fn parse_probability(key: &str, value: &str) -> Result<f64> {
+    match value.trim().parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => bail!("invalid probability for {key}: {value:?} (expected 0 to 1)"),
+    }
+}
+
+fn random_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ u64::from(std::process::id()).rotate_left(32)
+}
+
+#[derive(Debug)]
+struct FaultInjector {
+    config: FaultConfig,
+    rng: Mutex<SplitMix64>,
+}
+
+impl FaultInjector {
+    fn roll(&self, probability: f64) -> bool {
+        probability > 0.0 && self.rng.lock().unwrap().next_f64() < probability
+    }
+}
+
+/// Small deterministic PRNG; the same seed yields the same fault sequence.
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Enable fault injection with `config`. Has no effect if faults were already
+/// enabled.
+pub fn enable(config: FaultConfig) {
+    let spec = config.to_spec();
+    let rng = Mutex::new(SplitMix64(config.seed));
+    if FAULTS.set(FaultInjector { config, rng }).is_ok() {
+        warn!("fault injection enabled: PITCHFORK_FAULT_INJECT=\"{spec}\"");
+    }
+}
+
+/// Enable fault injection from `PITCHFORK_FAULT_INJECT`, if set. Called by the
+/// supervisor on startup.
+pub fn enable_from_env() {
+    let Ok(spec) = std::env::var("PITCHFORK_FAULT_INJECT") else {
+        return;
+    };
+    match FaultConfig::parse(&spec) {
+        Ok(config) => enable(config),
+        Err(e) => error!("ignoring PITCHFORK_FAULT_INJECT: {e}"),
+    }
+}
+
+/// How long to hold back the next IPC response, if at all.
+pub fn ipc_delay() -> Option<Duration> {
+    let faults = FAULTS.get()?;
+    if !faults.roll(faults.config.ipc_delay) {
+        return None;
+    }
+    let fraction = faults.rng.lock().unwrap().next_f64();
+    Some(faults.config.ipc_delay_max.mul_f64(fraction))
+}
+
+/// Whether the next state file write should fail.
+pub fn fail_state_write() -> bool {
+    FAULTS
+        .get()
+        .is_some_and(|faults| faults.roll(faults.config.state_write))
+}
+
+/// Which of `count` running daemons to kill on this tick, if any.
+pub fn daemon_to_kill(count: usize) -> Option<usize> {
+    let faults = FAULTS.get()?;
+    if count == 0 || !faults.roll(faults.config.kill) {
+        return None;
+    }
+    Some((faults.rng.lock().unwrap().next_u64() % count as u64) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let config =
+            FaultConfig::parse("seed=42, ipc_delay=0.2:2s,state_write=0.1,kill=1").unwrap();
+        assert_eq!(
+            config,
+            FaultConfig {
+                seed: 42,
+                ipc_delay: 0.2,
+                ipc_delay_max: Duration::from_secs(2),
+                state_write: 0.1,
+                kill: 1.0,
+            }
+        );
+        assert_eq!(FaultConfig::parse(&config.to_spec()).unwrap(), config);
+
+        assert!(FaultConfig::parse("kill=2").is_err());
+        assert!(FaultConfig::parse("explode=0.5").is_err());
+        assert!(FaultConfig::parse("seed").is_err());
+    }
+
+    #[test]
+    fn test_same_seed_same_faults() {
+        let rolls = |seed| {
+            let injector = FaultInjector {
+                config: FaultConfig::default(),
+                rng: Mutex::new(SplitMix64(seed)),
+            };
+            (0..100).map(|_| injector.roll(0.3)).collect::<Vec<_>>()
+        };
+        assert_eq!(rolls(7), rolls(7));
+        assert_ne!(rolls(7), rolls(8));
+        let hits = rolls(7).into_iter().filter(|hit| *hit).count();
+        assert!((10..60).contains(&hits), "{hits} hits");
+    }
+}
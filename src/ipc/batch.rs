@@ -3,8 +3,8 @@
 //! This module provides batch operations that can be used by CLI, TUI, and Web UI.
 
 use crate::Result;
-use crate::cli::logs::{ReadyCheckType, create_ready_check_job, stream_startup_logs};
-use crate::daemon::RunOptions;
+use crate::cli::logs::{ReadyCheckType, ShowOutput, create_ready_check_job, stream_startup_logs};
+use crate::daemon::{DEFAULT_READY_DELAY, RunOptions};
 use crate::daemon_id::DaemonId;
 use crate::deps::{compute_reverse_stop_order, resolve_dependencies};
 use crate::ipc::client::IpcClient;
@@ -15,7 +15,6 @@ use crate::pitchfork_toml::{
 use chrono::{DateTime, Local};
 use indexmap::IndexMap;
 use std::collections::{HashMap, HashSet};
-use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -28,12 +27,91 @@ pub struct RunResult {
     pub resolved_ports: Vec<u16>,
     /// Error message if the daemon failed to start
     pub error_message: Option<String>,
+    /// Set when the daemon failed to start because a port was taken
+    pub port_conflict: Option<PortConflict>,
+    /// Whether the ready checks gave up before the daemon became ready
+    pub timed_out: bool,
+    /// How long the start request took, i.e. the time to ready when waiting
+    pub elapsed: std::time::Duration,
+    /// What the daemon failed on, e.g. the ready checks it gave up on
+    pub failed_check: Option<String>,
+    /// The last line the daemon printed, if it failed
+    pub last_output: Option<String>,
+}
+
+/// A process holding a port a daemon needs
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PortConflict {
+    pub port: u16,
+    pub process: String,
+    pub pid: u32,
+}
+
+impl std::fmt::Display for PortConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "port {} is already in use by process '{}' (PID: {})",
+            self.port, self.process, self.pid
+        )
+    }
+}
+
+/// How to get past a port conflict when starting a daemon again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictResolution {
+    /// Terminate the process holding the port
+    Kill,
+    /// Move the daemon to the next free port
+    Bump,
+}
+
+impl ConflictResolution {
+    /// Adjust `opts` so that starting again resolves the conflict this way.
+    pub fn apply(self, opts: &mut StartOptions) {
+        match self {
+            ConflictResolution::Kill => opts.kill_conflicts = true,
+            ConflictResolution::Bump => {
+                opts.auto_bump_port = Some(crate::config_types::PortBump(
+                    crate::settings::settings().default_port_bump_attempts(),
+                ))
+            }
+        }
+    }
+}
+
+/// Why a batch start left a daemon out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The daemon is disabled
+    Disabled,
+    /// The daemon was excluded with `except`
+    Excluded,
+    /// The daemon is already running and wasn't restarted
+    AlreadyRunning,
+    /// The daemon is a task that already completed
+    Completed,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SkipReason::Disabled => "disabled",
+            SkipReason::Excluded => "excluded",
+            SkipReason::AlreadyRunning => "already running",
+            SkipReason::Completed => "already completed",
+        })
+    }
 }
 
 /// Result of batch start operation
 pub struct StartResult {
     /// Daemons that were successfully started (id, start_time, resolved_ports)
     pub started: Vec<(DaemonId, DateTime<Local>, Vec<u16>)>,
+    /// Daemons that were left out, requested ones and dependencies alike,
+    /// in the order they were considered
+    pub skipped: Vec<(DaemonId, SkipReason)>,
     /// Whether any daemon failed to start
     pub any_failed: bool,
     /// Deferred job status updates — caller must apply these
@@ -41,6 +119,65 @@ pub struct StartResult {
     pub pending_job_updates: Vec<PendingJobUpdate>,
 }
 
+/// How starting one daemon went, for the report printed after starting
+/// several at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadyReport {
+    pub id: DaemonId,
+    /// How long it took to become ready, None if it failed
+    pub ready_after: Option<std::time::Duration>,
+    /// What it failed on, e.g. the ready checks it gave up on
+    pub failed_check: Option<String>,
+    /// Its last line of output, or the error when there is none
+    pub last_error: Option<String>,
+}
+
+impl StartResult {
+    /// One entry per daemon this start tried, in the order they finished
+    /// each dependency level. Daemons that were already running are left out.
+    pub fn ready_report(&self) -> Vec<ReadyReport> {
+        self.pending_job_updates
+            .iter()
+            .filter_map(|update| {
+                let id = update.id.clone();
+                match &update.run_result {
+                    Ok(rr) if rr.started => Some(ReadyReport {
+                        id,
+                        ready_after: Some(rr.elapsed),
+                        failed_check: None,
+                        last_error: None,
+                    }),
+                    // Already running
+                    Ok(rr) if rr.exit_code.is_none() => None,
+                    Ok(rr) => Some(ReadyReport {
+                        id,
+                        ready_after: None,
+                        failed_check: rr.failed_check.clone(),
+                        last_error: rr.last_output.clone().or_else(|| rr.error_message.clone()),
+                    }),
+                    Err(e) => Some(ReadyReport {
+                        id,
+                        ready_after: None,
+                        failed_check: None,
+                        last_error: Some(e.to_string()),
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Daemons that failed to start because a port was taken
+    pub fn port_conflicts(&self) -> Vec<(DaemonId, PortConflict)> {
+        self.pending_job_updates
+            .iter()
+            .filter_map(|update| {
+                let conflict = update.run_result.as_ref().ok()?.port_conflict.clone()?;
+                Some((update.id.clone(), conflict))
+            })
+            .collect()
+    }
+}
+
 /// Result of batch stop operation
 #[derive(Debug)]
 pub struct StopResult {
@@ -76,7 +213,7 @@ pub struct StartOptions {
     /// Shell PID for autostop tracking
     pub shell_pid: Option<u32>,
     /// Override ready delay
-    pub delay: Option<u64>,
+    pub delay: Option<std::time::Duration>,
     /// Override ready output pattern
     pub output: Option<String>,
     /// Override ready HTTP endpoint
@@ -93,6 +230,23 @@ pub struct StartOptions {
     pub retry: Option<crate::config_types::Retry>,
     /// Suppress output (ready check hints, startup logs)
     pub quiet: bool,
+    /// Which startup log lines to print while waiting for readiness
+    pub show_output: ShowOutput,
+    /// Terminate processes holding the daemon's ports instead of failing
+    pub kill_conflicts: bool,
+    /// Environment overlaid on the configured env for this run only
+    pub env: Option<IndexMap<String, String>>,
+    /// Apply the daemon's `debug_env` on top of `env` until this time, when
+    /// the supervisor restarts it with its configured env again
+    pub debug_until: Option<DateTime<Local>>,
+    /// Give up on the ready checks after this long
+    pub wait_timeout: Option<std::time::Duration>,
+    /// All daemons restarted by this request, set by `start_daemons`
+    pub restart_batch: Vec<DaemonId>,
+    /// Leave daemons that are already running alone, even with `force`
+    pub only_stopped: bool,
+    /// Daemons not to start, whether requested or needed as a dependency
+    pub except: Vec<DaemonId>,
 }
 
 /// Build RunOptions from a daemon configuration and start options.
@@ -112,11 +266,23 @@ pub fn build_run_options(
 
     let mut run_opts = daemon_config.to_run_options(id, cmd);
     run_opts.wait_ready = true;
-    run_opts.ready_delay = run_opts.ready_delay.or(Some(3));
+    run_opts.ready_delay = run_opts.ready_delay.or(Some(DEFAULT_READY_DELAY));
 
     if let Some(opts) = overrides {
         run_opts.shell_pid = opts.shell_pid;
         run_opts.force = opts.force;
+        run_opts.kill_conflicts = opts.kill_conflicts;
+        run_opts.env_override = opts.env.clone();
+        if let Some(until) = opts.debug_until {
+            let mut debug_env = daemon_config.debug_env.clone().unwrap_or_default();
+            debug_env.extend(opts.env.clone().unwrap_or_default());
+            run_opts.env_override = Some(debug_env);
+            run_opts.env_override_until = Some(until);
+        }
+        run_opts.wait_timeout = opts.wait_timeout;
+        if opts.force {
+            run_opts.restart_batch = opts.restart_batch.clone();
+        }
         run_opts.ready_delay = opts.delay.or(run_opts.ready_delay);
         run_opts.ready_output =
             merge_ready_output_override(run_opts.ready_output, opts.output.clone());
@@ -172,8 +338,13 @@ pub(crate) fn render_daemon_config(
 
     let mut ctx =
         crate::template::TemplateContext::new(id, daemon_config, &resolved_daemons, &pt.daemons);
-    crate::template::render_daemon_templates(daemon_config, &mut ctx, pt.env.as_ref())
-        .map_err(|e| miette::miette!("Template render error for daemon {id}: {e}"))
+    crate::template::env_defaults(
+        pt.env.as_ref(),
+        &pt.env_templates,
+        &daemon_config.env_templates,
+    )
+    .and_then(|env| crate::template::render_daemon_templates(daemon_config, &mut ctx, env.as_ref()))
+    .map_err(|e| miette::miette!("Template render error for daemon {id}: {e}"))
 }
 
 fn merge_ready_http_override(
@@ -226,15 +397,38 @@ fn ready_check_type(opts: &RunOptions) -> ReadyCheckType {
         ReadyCheckType::Http(http.url.clone())
     } else if let Some(port) = opts.ready_port.as_ref().and_then(|p| p.as_port()) {
         ReadyCheckType::Port(port)
+    } else if let Some(ref socket) = opts.ready_unix_socket {
+        ReadyCheckType::UnixSocket(socket.clone())
     } else if let Some(ref cmd) = opts.ready_cmd {
         ReadyCheckType::Cmd(cmd.run.clone())
-    } else if let Some(secs) = opts.ready_delay {
-        ReadyCheckType::Delay(secs)
+    } else if let Some(ref log_file) = opts.ready_log_file {
+        ReadyCheckType::LogFile(log_file.path.clone())
+    } else if let Some(ref when) = opts.ready_when {
+        ReadyCheckType::Daemon(when.to_string())
+    } else if let Some(delay) = opts.ready_delay {
+        ReadyCheckType::Delay(delay)
     } else {
         ReadyCheckType::Default
     }
 }
 
+/// Print what changed since the daemon's last run, so a restart tells why
+/// it may behave differently
+fn print_config_changes(job: &clx::progress::ProgressJob, id: &DaemonId, run_opts: &RunOptions) {
+    let Some(daemon) = crate::state_file::StateFile::get().daemons.get(id) else {
+        return;
+    };
+    let id_label =
+        crate::cli::logs::colored_id_label(&id.qualified(), crate::ui::color::stderr_enabled());
+    for change in daemon.config_changes(run_opts) {
+        job.println(&format!(
+            "{} {id_label} {}",
+            crate::ui::style::edim("~"),
+            crate::ui::style::edim(change)
+        ));
+    }
+}
+
 /// Update a progress job's body and status based on the IPC run result.
 ///
 /// Sets the body to a styled success or failure message and transitions
@@ -250,11 +444,8 @@ pub fn update_job_with_result(
 ) {
     use clx::progress::ProgressStatus;
 
-    let id_label = {
-        let is_tty = std::io::stderr().is_terminal();
-        let colors_enabled = is_tty && console::colors_enabled_stderr();
-        crate::cli::logs::colored_id_label(&id.qualified(), colors_enabled)
-    };
+    let id_label =
+        crate::cli::logs::colored_id_label(&id.qualified(), crate::ui::color::stderr_enabled());
 
     let show_ts = crate::settings::settings().general.startup_log_timestamps;
     // When timestamps are off, the body uses {{spinner()}} which renders
@@ -298,8 +489,10 @@ pub fn update_job_with_result(
                     job.remove();
                     return;
                 }
+                // A timeout's exit code is made up, the reason says it all
                 let exit_info = run_result
                     .exit_code
+                    .filter(|_| !run_result.timed_out)
                     .map(|c| format!(" (exit code {c})"))
                     .unwrap_or_default();
                 let error_detail = run_result
@@ -380,7 +573,7 @@ impl IpcClient {
             .active_daemons()
             .await?
             .iter()
-            .filter(|d| d.status.is_running() || d.status.is_waiting())
+            .filter(|d| d.status.is_up() || d.status.is_waiting())
             .map(|d| d.id.clone())
             .collect())
     }
@@ -420,10 +613,18 @@ impl IpcClient {
     pub async fn start_daemons(
         self: &Arc<Self>,
         ids: &[DaemonId],
-        opts: StartOptions,
+        mut opts: StartOptions,
     ) -> Result<StartResult> {
         let pt = PitchforkToml::all_merged_all_namespaces()?;
+        if opts.force && !opts.only_stopped {
+            opts.restart_batch = ids
+                .iter()
+                .filter(|id| !opts.except.contains(id))
+                .cloned()
+                .collect();
+        }
         let disabled_daemons = self.get_disabled_daemons().await?;
+        let mut skipped: Vec<(DaemonId, SkipReason)> = Vec::new();
 
         // Get all active daemons for ad-hoc restart support
         let all_daemons = self.active_daemons().await?;
@@ -433,12 +634,17 @@ impl IpcClient {
             .map(|d| (d.id.clone(), d))
             .collect();
 
-        // Filter out disabled daemons from the requested list
+        // Filter out excluded and disabled daemons from the requested list
         let requested_ids: Vec<DaemonId> = ids
             .iter()
             .filter(|id| {
-                if disabled_daemons.contains(id) {
+                if opts.except.contains(id) {
+                    debug!("Skipping excluded daemon {id}");
+                    skipped.push(((*id).clone(), SkipReason::Excluded));
+                    false
+                } else if disabled_daemons.contains(id) {
                     warn!("Daemon {id} is disabled");
+                    skipped.push(((*id).clone(), SkipReason::Disabled));
                     false
                 } else {
                     true
@@ -450,6 +656,7 @@ impl IpcClient {
         if requested_ids.is_empty() {
             return Ok(StartResult {
                 started: vec![],
+                skipped,
                 any_failed: false,
                 pending_job_updates: vec![],
             });
@@ -465,14 +672,19 @@ impl IpcClient {
         let active_daemons = self.active_daemons().await?;
         let running_daemons: HashSet<DaemonId> = active_daemons
             .iter()
-            .filter(|d| d.status.is_running() || d.status.is_waiting())
+            .filter(|d| d.status.is_up() || d.status.is_waiting())
+            .map(|d| d.id.clone())
+            .collect();
+        // Tasks have no PID once they complete, so they come from the state file
+        let completed_tasks: HashSet<DaemonId> = crate::state_file::StateFile::get()
+            .daemons
+            .values()
+            .filter(|d| d.is_completed())
             .map(|d| d.id.clone())
             .collect();
         let running_ports_map: HashMap<DaemonId, Vec<u16>> = active_daemons
             .into_iter()
-            .filter(|d| {
-                (d.status.is_running() || d.status.is_waiting()) && !d.resolved_port.is_empty()
-            })
+            .filter(|d| (d.status.is_up() || d.status.is_waiting()) && !d.resolved_port.is_empty())
             .map(|d| (d.id, d.resolved_port))
             .collect();
 
@@ -501,29 +713,41 @@ impl IpcClient {
                 let to_start: Vec<DaemonId> = level
                     .iter()
                     .filter(|&id| {
-                        // Skip disabled daemons (dependencies might be disabled)
-                        if disabled_daemons.contains(id) {
-                            warn!("Skipping disabled daemon {id} (dependency)");
+                        // Requested ones were already left out above, this
+                        // catches dependencies
+                        if skipped.iter().any(|(s, _)| s == id) {
                             return false;
                         }
-
-                        // Skip already running daemons unless they are explicitly requested
-                        // with force=true
-                        if running_daemons.contains(id) {
-                            if opts.force && explicitly_requested.contains(id) {
+                        let reason = if opts.except.contains(id) {
+                            debug!("Skipping excluded daemon {id} (dependency)");
+                            SkipReason::Excluded
+                        } else if disabled_daemons.contains(id) {
+                            warn!("Skipping disabled daemon {id} (dependency)");
+                            SkipReason::Disabled
+                        } else if running_daemons.contains(id) {
+                            // Skip already running daemons unless they are
+                            // explicitly requested with force=true
+                            if opts.force && !opts.only_stopped && explicitly_requested.contains(id)
+                            {
                                 debug!("Force restarting explicitly requested daemon: {id}");
-                                true // Allow restart if force is set AND explicitly requested
+                                return true;
+                            }
+                            if explicitly_requested.contains(id) && !opts.only_stopped {
+                                info!("Daemon {id} is already running, use --force to restart");
                             } else {
-                                if explicitly_requested.contains(id) {
-                                    info!("Daemon {id} is already running, use --force to restart");
-                                } else {
-                                    debug!("Skipping already running daemon {id}");
-                                }
-                                false
+                                debug!("Skipping already running daemon {id}");
                             }
+                            SkipReason::AlreadyRunning
+                        } else if completed_tasks.contains(id) && !explicitly_requested.contains(id)
+                        {
+                            // Its earlier run still satisfies dependents
+                            debug!("Skipping completed task {id}");
+                            SkipReason::Completed
                         } else {
-                            true
-                        }
+                            return true;
+                        };
+                        skipped.push((id.clone(), reason));
+                        false
                     })
                     .cloned()
                     .collect();
@@ -538,10 +762,18 @@ impl IpcClient {
                     continue;
                 }
 
-                // Start all daemons in this level concurrently
+                // Start all daemons in this level concurrently, in priority
+                // order, pausing for `start_delay` between spawns
                 let mut tasks = Vec::new();
                 for id in to_start {
                     if let Some(daemon_config) = pt.daemons.get(&id) {
+                        if let Some(delay) = daemon_config.start_delay
+                            && !tasks.is_empty()
+                        {
+                            debug!("waiting {delay} before starting {id}");
+                            tokio::time::sleep(delay.0).await;
+                        }
+
                         // Render Tera templates with context from previously started daemons
                         let mut rendered_config = daemon_config.clone();
                         let mut template_ctx = crate::template::TemplateContext::new(
@@ -550,11 +782,18 @@ impl IpcClient {
                             &resolved_ports_map,
                             &pt.daemons,
                         );
-                        match crate::template::render_daemon_templates(
-                            &mut rendered_config,
-                            &mut template_ctx,
+                        match crate::template::env_defaults(
                             pt.env.as_ref(),
-                        ) {
+                            &pt.env_templates,
+                            &daemon_config.env_templates,
+                        )
+                        .and_then(|env| {
+                            crate::template::render_daemon_templates(
+                                &mut rendered_config,
+                                &mut template_ctx,
+                                env.as_ref(),
+                            )
+                        }) {
                             Ok(()) => {}
                             Err(e) => {
                                 error!("Template render error for daemon {id}: {e}");
@@ -639,12 +878,13 @@ impl IpcClient {
             for id in adhoc_ids {
                 // Skip already running daemons unless force is set
                 if running_daemons.contains(&id) {
-                    if opts.force && explicitly_requested.contains(&id) {
+                    if opts.force && !opts.only_stopped && explicitly_requested.contains(&id) {
                         debug!("Force restarting ad-hoc daemon: {id}");
                     } else {
-                        if explicitly_requested.contains(&id) {
+                        if explicitly_requested.contains(&id) && !opts.only_stopped {
                             info!("Ad-hoc daemon {id} is already running, use --force to restart");
                         }
+                        skipped.push((id, SkipReason::AlreadyRunning));
                         continue;
                     }
                 }
@@ -714,11 +954,25 @@ impl IpcClient {
 
         Ok(StartResult {
             started: successful_daemons,
+            skipped,
             any_failed,
             pending_job_updates,
         })
     }
 
+    /// A connection of its own for a start that runs alongside others.
+    /// Requests on one connection are answered one at a time, so starts
+    /// sharing it would wait for each other to become ready.
+    async fn dedicated(self: Arc<Self>) -> Arc<Self> {
+        match Self::connect(false).await {
+            Ok(client) => Arc::new(client),
+            Err(e) => {
+                debug!("failed to open another IPC connection, sharing this one: {e}");
+                self
+            }
+        }
+    }
+
     /// Spawn a task to start a single daemon
     ///
     /// This encapsulates the start logic for one daemon, allowing parallel execution
@@ -733,12 +987,18 @@ impl IpcClient {
         is_explicitly_requested: bool,
         opts: &StartOptions,
     ) -> tokio::task::JoinHandle<SpawnTaskResult> {
-        // Build options with force only if explicitly requested
+        // Build options with force and env overrides only if explicitly requested,
+        // so dependencies started alongside keep their configured env
         let mut start_opts = opts.clone();
         start_opts.force = opts.force && is_explicitly_requested;
+        if !is_explicitly_requested {
+            start_opts.env = None;
+            start_opts.debug_until = None;
+        }
 
         let run_opts = build_run_options(&id, daemon_config, Some(&start_opts));
         let quiet = opts.quiet;
+        let show_output = opts.show_output;
 
         tokio::spawn(async move {
             let run_opts = match run_opts {
@@ -756,6 +1016,9 @@ impl IpcClient {
 
             let job = if !quiet {
                 let job = create_ready_check_job(&id, &check_type);
+                if run_opts.force {
+                    print_config_changes(&job, &id, &run_opts);
+                }
                 Some(job)
             } else {
                 None
@@ -763,13 +1026,13 @@ impl IpcClient {
 
             // Start streaming logs for this daemon
             let (log_stop_tx, log_handle) = if let Some(ref job) = job {
-                let (tx, handle) = stream_startup_logs(&id, job.clone());
+                let (tx, handle) = stream_startup_logs(&id, job.clone(), show_output);
                 (Some(tx), Some(handle))
             } else {
                 (None, None)
             };
 
-            let result = ipc.run(run_opts).await;
+            let result = ipc.dedicated().await.run(run_opts).await;
 
             // Stop log streaming and wait for the task to fully exit
             if let Some(tx) = &log_stop_tx {
@@ -813,16 +1076,21 @@ impl IpcClient {
         let retry = opts.retry.unwrap_or_default();
         let shell_pid = opts.shell_pid;
         let quiet = opts.quiet;
+        let show_output = opts.show_output;
+        let kill_conflicts = opts.kill_conflicts;
+        let wait_timeout = opts.wait_timeout;
+        let env_override = opts.env.clone().filter(|_| is_explicitly_requested);
 
         tokio::spawn(async move {
             let run_opts = RunOptions {
                 id: id.clone(),
                 cmd,
                 force,
+                kill_conflicts,
                 shell_pid,
                 dir: crate::config_types::Dir(dir),
                 retry,
-                ready_delay: delay.or(Some(3)),
+                ready_delay: delay.or(Some(DEFAULT_READY_DELAY)),
                 ready_output: output.map(ReadyOutput::new),
                 ready_http: http,
                 ready_port: port.map(ReadyPort::new),
@@ -832,7 +1100,9 @@ impl IpcClient {
                     auto_bump_port.unwrap_or_default(),
                 ),
                 wait_ready: true,
+                wait_timeout,
                 env,
+                env_override,
                 watch: vec![],
                 watch_base_dir: None,
                 mise: None,
@@ -845,6 +1115,9 @@ impl IpcClient {
 
             let job = if !quiet {
                 let job = create_ready_check_job(&id, &check_type);
+                if run_opts.force {
+                    print_config_changes(&job, &id, &run_opts);
+                }
                 Some(job)
             } else {
                 None
@@ -852,13 +1125,13 @@ impl IpcClient {
 
             // Start streaming logs for this daemon
             let (log_stop_tx, log_handle) = if let Some(ref job) = job {
-                let (tx, handle) = stream_startup_logs(&id, job.clone());
+                let (tx, handle) = stream_startup_logs(&id, job.clone(), show_output);
                 (Some(tx), Some(handle))
             } else {
                 (None, None)
             };
 
-            let result = ipc.run(run_opts).await;
+            let result = ipc.dedicated().await.run(run_opts).await;
 
             // Stop log streaming and wait for the task to fully exit
             if let Some(tx) = &log_stop_tx {
@@ -898,7 +1171,7 @@ impl IpcClient {
     /// Handles:
     /// - ID resolution
     /// - Config reading and command parsing
-    /// - `RunOptions` construction with correct defaults (`wait_ready=true`, `ready_delay=Some(3)`)
+    /// - `RunOptions` construction with correct defaults (`wait_ready=true`, `ready_delay=Some(3s)`)
     /// - IPC communication with supervisor
     ///
     /// `overrides` can be used by the CLI to override config defaults with command-line flags.
@@ -925,16 +1198,19 @@ impl IpcClient {
         self.run(run_opts).await
     }
 
-    /// Restart a single daemon by stopping then starting it.
-    ///
-    /// Stop errors are ignored (daemon may not be running).
+    /// Restart a single daemon by starting it with `force`, which stops it
+    /// first if it's running. Like `pitchfork restart`, this also restarts
+    /// dependents with `restart_with_dependencies`.
     pub async fn restart_daemon(
         &self,
         id: &DaemonId,
         overrides: Option<&StartOptions>,
     ) -> Result<RunResult> {
-        let _ = self.stop(id.clone()).await;
-        self.start_daemon(id, overrides).await
+        let opts = StartOptions {
+            force: true,
+            ..overrides.cloned().unwrap_or_default()
+        };
+        self.start_daemon(id, Some(&opts)).await
     }
 
     /// Stop daemons by ID with dependency resolution
@@ -950,7 +1226,7 @@ impl IpcClient {
             .active_daemons()
             .await?
             .iter()
-            .filter(|d| d.status.is_running() || d.status.is_waiting())
+            .filter(|d| d.status.is_up() || d.status.is_waiting())
             .map(|d| d.id.clone())
             .collect();
 
@@ -1030,9 +1306,10 @@ impl IpcClient {
             cmd,
             shell_pid: opts.shell_pid,
             force: opts.force,
+            kill_conflicts: opts.kill_conflicts,
             dir: crate::config_types::Dir(dir),
             retry: opts.retry.unwrap_or_default(),
-            ready_delay: opts.delay.or(Some(3)),
+            ready_delay: opts.delay.or(Some(DEFAULT_READY_DELAY)),
             ready_output: opts.output.map(ReadyOutput::new),
             ready_http: merge_ready_http_override(None, opts.http),
             ready_port: opts.port.map(ReadyPort::new),
@@ -1042,6 +1319,7 @@ impl IpcClient {
                 opts.auto_bump_port.unwrap_or_default(),
             ),
             wait_ready: true,
+            wait_timeout: opts.wait_timeout,
             mise: None,
             slug: None,
             proxy: None,
@@ -1092,12 +1370,64 @@ mod tests {
 
     use super::*;
 
+    fn run_result(started: bool, exit_code: Option<i32>) -> RunResult {
+        RunResult {
+            started,
+            exit_code,
+            start_time: Local::now(),
+            resolved_ports: vec![],
+            error_message: exit_code.map(|code| format!("failed with exit code {code}")),
+            port_conflict: None,
+            timed_out: false,
+            elapsed: Duration::from_millis(1500),
+            failed_check: exit_code.map(|code| format!("exited with code {code}")),
+            last_output: None,
+        }
+    }
+
+    #[test]
+    fn test_ready_report() {
+        let update = |name: &str, run_result| PendingJobUpdate {
+            job: None,
+            id: DaemonId::new("proj", name),
+            run_result,
+        };
+        let result = StartResult {
+            started: vec![],
+            skipped: vec![],
+            any_failed: true,
+            pending_job_updates: vec![
+                update("api", Ok(run_result(true, None))),
+                update("running", Ok(run_result(false, None))),
+                update("worker", Ok(run_result(false, Some(2)))),
+                update("db", Err(miette::miette!("connection reset"))),
+            ],
+        };
+
+        let report = result.ready_report();
+        assert_eq!(report.len(), 3);
+        assert_eq!(report[0].ready_after, Some(Duration::from_millis(1500)));
+        assert_eq!(report[0].failed_check, None);
+        assert_eq!(report[1].id.name(), "worker");
+        assert_eq!(report[1].ready_after, None);
+        assert_eq!(
+            report[1].failed_check.as_deref(),
+            Some("exited with code 2")
+        );
+        assert_eq!(
+            report[1].last_error.as_deref(),
+            Some("failed with exit code 2")
+        );
+        assert_eq!(report[2].last_error.as_deref(), Some("connection reset"));
+    }
+
     #[test]
     fn http_override_preserves_configured_status_codes() {
         let configured = Some(ReadyHttp {
             url: "http://localhost:3000/original".to_string(),
             status: vec![401],
-            timeout: None,
+            headers: IndexMap::from([("Authorization".to_string(), "Bearer x".to_string())]),
+            ..ReadyHttp::default()
         });
 
         let ready_http =
@@ -1106,6 +1436,7 @@ mod tests {
 
         assert_eq!(ready_http.url, "http://localhost:3000/health");
         assert_eq!(ready_http.status, vec![401]);
+        assert_eq!(ready_http.headers["Authorization"], "Bearer x");
         assert!(ready_http.accepts_status(401));
         assert!(!ready_http.accepts_status(200));
     }
@@ -1118,7 +1449,7 @@ mod tests {
             ready_http: Some(ReadyHttp {
                 url: "http://localhost:3000/original".to_string(),
                 status: vec![401],
-                timeout: None,
+                ..ReadyHttp::default()
             }),
             ..PitchforkTomlDaemon::default()
         };
@@ -1181,6 +1512,39 @@ mod tests {
         assert_eq!(ready_output.timeout, Some(Duration::from_secs(45)));
     }
 
+    #[test]
+    fn build_run_options_overlays_debug_env_until_expiry() {
+        let id = DaemonId::try_new("project", "api").unwrap();
+        let env = |pairs: &[(&str, &str)]| -> IndexMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let daemon_config = PitchforkTomlDaemon {
+            run: "echo ready".to_string(),
+            env: Some(env(&[("RUST_LOG", "info"), ("PORT", "3000")])),
+            debug_env: Some(env(&[("RUST_LOG", "debug")])),
+            ..PitchforkTomlDaemon::default()
+        };
+
+        let plain = build_run_options(&id, &daemon_config, Some(&StartOptions::default())).unwrap();
+        assert_eq!(plain.env_override, None);
+        assert_eq!(plain.effective_env().unwrap()["RUST_LOG"], "info");
+
+        let until = Local::now();
+        let opts = StartOptions {
+            env: Some(env(&[("PORT", "4000")])),
+            debug_until: Some(until),
+            ..StartOptions::default()
+        };
+        let run_opts = build_run_options(&id, &daemon_config, Some(&opts)).unwrap();
+        assert_eq!(run_opts.env_override_until, Some(until));
+        let effective = run_opts.effective_env().unwrap();
+        assert_eq!(effective["RUST_LOG"], "debug");
+        assert_eq!(effective["PORT"], "4000");
+    }
+
     #[test]
     fn test_resolve_daemon_dir_none() {
         // No dir set, config at /projects/myapp/pitchfork.toml -> /projects/myapp
@@ -1,43 +1,190 @@
 use crate::daemon::{Daemon, RunOptions};
 use crate::daemon_id::DaemonId;
 use crate::error::IpcError;
-use crate::ipc::batch::RunResult;
+use crate::ipc::batch::{PortConflict, RunResult};
+use crate::ipc::frame::{
+    self, FRAMES_FEATURE, LENGTH_PREFIX_FEATURE, PROTOCOL_ERROR_FEATURE, Reassembler,
+};
 use crate::ipc::{IpcRequest, IpcResponse, deserialize, fs_name, serialize};
 use crate::settings::settings;
+use crate::ui::style::{ecyan, ered};
 use crate::{Result, supervisor};
 use exponential_backoff::Backoff;
 use interprocess::local_socket::tokio::{RecvHalf, SendHalf};
 use interprocess::local_socket::traits::tokio::Stream;
 use miette::Context;
 use std::path::PathBuf;
-use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::sync::{Mutex, OnceCell};
 use uuid::Uuid;
 
+/// User picked with the global `--user` flag
+static VIEW_USER: OnceLock<String> = OnceLock::new();
+
+/// Connection reused by every command of this process, see `IpcClient::shared`
+static SHARED: OnceCell<Arc<IpcClient>> = OnceCell::const_new();
+
+/// Set with the global `--trace-ipc` flag
+static TRACE_IPC: AtomicBool = AtomicBool::new(false);
+
+/// Look at the daemons of `user` on a shared supervisor instead of the
+/// caller's own. Called once after argument parsing.
+pub fn set_view_user(user: String) {
+    let _ = VIEW_USER.set(user);
+}
+
+/// User picked with the global `--user` flag
+pub fn view_user() -> Option<&'static str> {
+    VIEW_USER.get().map(String::as_str)
+}
+
+/// Print every request and response of this process to stderr. Called once
+/// after argument parsing.
+pub fn set_trace_ipc() {
+    TRACE_IPC.store(true, Ordering::Relaxed);
+}
+
+/// A request being traced with `--trace-ipc`
+struct IpcTrace {
+    /// Numbers the requests of this process, to pair them with responses
+    n: u64,
+    started: Instant,
+}
+
+impl IpcTrace {
+    fn start(msg: &IpcRequest) -> Option<Self> {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        if !TRACE_IPC.load(Ordering::Relaxed) {
+            return None;
+        }
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        eprintln!("{} {}", ecyan(format!("ipc → #{n} {msg}")), pretty(msg));
+        Some(Self {
+            n,
+            started: Instant::now(),
+        })
+    }
+
+    fn finish(self, rsp: &Result<IpcResponse>) {
+        let Self { n, started } = self;
+        let elapsed = format!("{:.1?}", started.elapsed());
+        match rsp {
+            Ok(rsp) => eprintln!(
+                "{} {}",
+                ecyan(format!("ipc ← #{n} {rsp} ({elapsed})")),
+                pretty(rsp)
+            ),
+            Err(err) => eprintln!("{} {err}", ered(format!("ipc ✗ #{n} after {elapsed}:"))),
+        }
+    }
+}
+
+/// A message as indented JSON, which reads better than `Debug` output for
+/// the large ones like `RunOptions`
+fn pretty<T: serde::Serialize + std::fmt::Debug>(msg: &T) -> String {
+    serde_json::to_string_pretty(msg).unwrap_or_else(|_| format!("{msg:#?}"))
+}
+
+/// Whether a send failed because the supervisor closed the connection
+fn is_broken_pipe(err: &miette::Report) -> bool {
+    matches!(
+        err.downcast_ref::<IpcError>(),
+        Some(IpcError::SendFailed { source }) if matches!(
+            source.kind(),
+            std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::NotConnected
+        )
+    )
+}
+
 pub struct IpcClient {
     _id: String,
     recv: Mutex<BufReader<RecvHalf>>,
     send: Mutex<SendHalf>,
+    /// Held for a whole request/response round trip. The supervisor answers
+    /// requests as they finish, not in the order they were sent, so a
+    /// connection can only have one request in flight.
+    in_flight: Mutex<()>,
+    /// The supervisor answered in length-prefixed frames, so requests are
+    /// sent that way too
+    length_prefix: AtomicBool,
+    /// A read failed partway (timeout, bad frame), so the next response on
+    /// this connection may belong to an earlier request. The next request
+    /// reconnects first.
+    stale: AtomicBool,
 }
 
 impl IpcClient {
+    /// The connection to the supervisor for this CLI invocation. It is opened
+    /// on first use and reused by every later request, instead of each
+    /// command connecting (and handshaking) on its own. Long-lived clients
+    /// like the TUI, web UI and MCP server open their own with `connect`.
+    pub async fn shared(autostart: bool) -> Result<Arc<Self>> {
+        SHARED
+            .get_or_try_init(|| async { Self::connect(autostart).await.map(Arc::new) })
+            .await
+            .cloned()
+    }
+
     pub async fn connect(autostart: bool) -> Result<Self> {
-        if autostart {
-            supervisor::start_if_not_running()?;
-        }
+        let started = autostart && supervisor::start_if_not_running()?;
         let id = Uuid::new_v4().to_string();
         let client = Self::connect_(&id, "main").await?;
         trace!("Connected to IPC socket");
+        client.handshake().await?;
+        debug!("Connected to IPC main");
+        if started {
+            client.print_startup_banner().await;
+        }
+        Ok(client)
+    }
+
+    /// Tell the user about the supervisor this client just started in the
+    /// background, so it doesn't go unnoticed.
+    pub async fn print_startup_banner(&self) {
+        match self.get_supervisor_info().await {
+            Ok(info) => info!(
+                "started supervisor v{} (pid {}) on {}, {} boot daemon(s) started",
+                info.version, info.pid, info.socket, info.boot_daemons_started
+            ),
+            Err(e) => debug!("failed to get supervisor info: {e}"),
+        }
+    }
+
+    /// Reconnect after the supervisor dropped the connection, e.g. because
+    /// it was restarted since the last request
+    async fn reconnect(&self) -> Result<()> {
+        let Self { recv, send, .. } = Self::connect_(&self._id, "main").await?;
+        *self.recv.lock().await = recv.into_inner();
+        *self.send.lock().await = send.into_inner();
+        self.length_prefix.store(false, Ordering::Relaxed);
+        self.stale.store(false, Ordering::Relaxed);
+        self.handshake().await
+    }
+
+    async fn handshake(&self) -> Result<()> {
         let client_version = env!("CARGO_PKG_VERSION").to_string();
+        let timeout = settings().ipc_request_timeout();
 
         // Try ConnectV2 first (supervisor that knows about it will return ConnectOk with its version).
         // If the supervisor is older and doesn't recognize ConnectV2, it will return Error,
         // and we fall back to the legacy Connect handshake.
-        let rsp = client
-            .request(IpcRequest::ConnectV2 {
-                version: client_version.clone(),
-            })
+        let rsp = self
+            .exchange(
+                IpcRequest::ConnectV2 {
+                    version: client_version.clone(),
+                    features: vec![
+                        FRAMES_FEATURE.to_string(),
+                        LENGTH_PREFIX_FEATURE.to_string(),
+                        PROTOCOL_ERROR_FEATURE.to_string(),
+                    ],
+                },
+                timeout,
+            )
             .await?;
         match rsp {
             IpcResponse::ConnectOk {
@@ -53,7 +200,7 @@ impl IpcClient {
             IpcResponse::Error(_) => {
                 // Old supervisor doesn't recognize ConnectV2 — fall back to legacy Connect
                 debug!("Supervisor did not recognize ConnectV2, falling back to legacy Connect");
-                let rsp = client.request(IpcRequest::Connect).await?;
+                let rsp = self.exchange(IpcRequest::Connect, timeout).await?;
                 if !rsp.is_ok() {
                     return Err(IpcError::UnexpectedResponse {
                         expected: "Ok".to_string(),
@@ -74,8 +221,7 @@ impl IpcClient {
                 .into());
             }
         }
-        debug!("Connected to IPC main");
-        Ok(client)
+        Ok(())
     }
 
     async fn connect_(id: &str, name: &str) -> Result<Self> {
@@ -114,6 +260,7 @@ impl IpcClient {
             for duration in Backoff::new(connect_attempts, connect_min_delay, connect_max_delay) {
                 match interprocess::local_socket::tokio::Stream::connect(fs_name(name)?).await {
                     Ok(conn) => {
+                        supervisor::startup::clear();
                         let (recv, send) = conn.split();
                         let recv = BufReader::new(recv);
 
@@ -121,9 +268,17 @@ impl IpcClient {
                             _id: id.to_string(),
                             recv: Mutex::new(recv),
                             send: Mutex::new(send),
+                            in_flight: Mutex::new(()),
+                            length_prefix: AtomicBool::new(false),
+                            stale: AtomicBool::new(false),
                         });
                     }
                     Err(err) => {
+                        // A supervisor we spawned may have reported why it
+                        // won't come up; no point waiting for it
+                        if let Some(reason) = supervisor::startup::failure() {
+                            return Err(IpcError::SupervisorFailed { reason }.into());
+                        }
                         if let Some(duration) = duration {
                             debug!(
                                 "Failed to connect to IPC socket: {err:?}, retrying in {duration:?}"
@@ -164,15 +319,29 @@ impl IpcClient {
         })
     }
 
+    /// Send a handshake message and read the answer
+    async fn exchange(&self, msg: IpcRequest, timeout: Duration) -> Result<IpcResponse> {
+        let trace = IpcTrace::start(&msg);
+        let rsp = match self.send(msg).await {
+            Ok(()) => self.read(timeout).await,
+            Err(err) => Err(err),
+        };
+        if let Some(trace) = trace {
+            trace.finish(&rsp);
+        }
+        rsp
+    }
+
     pub async fn send(&self, msg: IpcRequest) -> Result<()> {
-        let mut msg = serialize(&msg)?;
-        if msg.contains(&0) {
+        let msg = serialize(&msg)?;
+        let length_prefix = self.length_prefix.load(Ordering::Relaxed);
+        if !length_prefix && msg.contains(&0) {
             return Err(IpcError::InvalidMessage {
                 reason: "message contains null byte".to_string(),
             }
             .into());
         }
-        msg.push(0);
+        let msg = frame::finish(msg, length_prefix);
         let mut send = self.send.lock().await;
         send.write_all(&msg)
             .await
@@ -182,23 +351,46 @@ impl IpcClient {
 
     async fn read(&self, timeout: Duration) -> Result<IpcResponse> {
         let mut recv = self.recv.lock().await;
-        let mut bytes = Vec::new();
-        match tokio::time::timeout(timeout, recv.read_until(0, &mut bytes)).await {
-            Ok(Ok(_)) => {}
+        let read = tokio::time::timeout(timeout, Self::read_message(&mut recv)).await;
+        let (bytes, length_prefixed) = match read {
+            Ok(Ok(message)) => message,
             Ok(Err(err)) => {
-                return Err(IpcError::ReadFailed { source: err }.into());
+                self.stale.store(true, Ordering::Relaxed);
+                return Err(err);
             }
             Err(_) => {
+                self.stale.store(true, Ordering::Relaxed);
                 return Err(IpcError::Timeout {
                     seconds: timeout.as_secs(),
                 }
                 .into());
             }
+        };
+        if length_prefixed {
+            self.length_prefix.store(true, Ordering::Relaxed);
+        }
+        match deserialize(&bytes).wrap_err("failed to deserialize IPC response")? {
+            IpcResponse::ProtocolError { error } => {
+                Err(IpcError::ProtocolError { reason: error }.into())
+            }
+            rsp => Ok(rsp),
         }
-        if bytes.is_empty() {
-            return Err(IpcError::ConnectionClosed.into());
+    }
+
+    /// Read one complete message, and whether it came length-prefixed.
+    /// Large responses arrive as several chunk frames, which are read and
+    /// reassembled here.
+    async fn read_message(recv: &mut BufReader<RecvHalf>) -> Result<(Vec<u8>, bool)> {
+        let mut reassembler = Reassembler::default();
+        loop {
+            let frame = frame::read_frame(recv, frame::MAX_MESSAGE_SIZE)
+                .await
+                .map_err(|err| IpcError::ReadFailed { source: err })?
+                .ok_or(IpcError::ConnectionClosed)?;
+            if let Some(message) = reassembler.push(&frame.body, frame.length_prefixed)? {
+                return Ok((message, frame.length_prefixed));
+            }
         }
-        deserialize(&bytes).wrap_err("failed to deserialize IPC response")
     }
 
     pub(crate) async fn request(&self, msg: IpcRequest) -> Result<IpcResponse> {
@@ -218,8 +410,39 @@ impl IpcClient {
         msg: IpcRequest,
         timeout: Duration,
     ) -> Result<IpcResponse> {
-        self.send(msg).await?;
-        self.read(timeout).await
+        let trace = IpcTrace::start(&msg);
+        let rsp = self.round_trip(msg, timeout).await;
+        if let Some(trace) = trace {
+            trace.finish(&rsp);
+        }
+        rsp
+    }
+
+    async fn round_trip(&self, msg: IpcRequest, timeout: Duration) -> Result<IpcResponse> {
+        let _in_flight = self.in_flight.lock().await;
+        if self.stale.swap(false, Ordering::Relaxed) {
+            debug!("IPC connection out of sync after a failed read, reconnecting");
+            if let Err(err) = self.reconnect().await {
+                self.stale.store(true, Ordering::Relaxed);
+                return Err(err);
+            }
+        }
+        // A request that couldn't be written never reached the supervisor,
+        // so it is safe to send it again once on a fresh connection
+        if let Err(err) = self.send(msg.clone()).await {
+            if !is_broken_pipe(&err) {
+                return Err(err);
+            }
+            debug!("IPC connection lost ({err}), reconnecting");
+            self.reconnect().await?;
+            self.send(msg).await?;
+        }
+        // A request dropped while waiting, e.g. on Ctrl-C, leaves its
+        // response on the connection for the next request to read
+        self.stale.store(true, Ordering::Relaxed);
+        let rsp = self.read(timeout).await?;
+        self.stale.store(false, Ordering::Relaxed);
+        Ok(rsp)
     }
 
     // =========================================================================
@@ -244,15 +467,84 @@ impl IpcClient {
     }
 
     pub async fn disable(&self, id: DaemonId) -> Result<bool> {
+        self.disable_with(id, false).await
+    }
+
+    /// Disable only automatic starts (autostart, cron, boot start); manual
+    /// starts still work.
+    pub async fn disable_autostart(&self, id: DaemonId) -> Result<bool> {
+        self.disable_with(id, true).await
+    }
+
+    async fn disable_with(&self, id: DaemonId, auto: bool) -> Result<bool> {
         let id_str = id.qualified();
-        let rsp = self.request(IpcRequest::Disable { id: id.clone() }).await?;
+        let rsp = self
+            .request(IpcRequest::Disable {
+                id: id.clone(),
+                auto,
+            })
+            .await?;
         match rsp {
             IpcResponse::Yes => {
-                info!("Disabled daemon {id_str}");
+                if auto {
+                    info!("Disabled autostart for daemon {id_str}");
+                } else {
+                    info!("Disabled daemon {id_str}");
+                }
                 Ok(true)
             }
             IpcResponse::No => {
-                info!("Daemon {id_str} already disabled");
+                if auto {
+                    info!("Autostart for daemon {id_str} already disabled");
+                } else {
+                    info!("Daemon {id_str} already disabled");
+                }
+                Ok(false)
+            }
+            IpcResponse::Error(error) => Err(miette::miette!(error)),
+            rsp => Err(Self::unexpected_response("Yes or No", &rsp).into()),
+        }
+    }
+
+    /// Retry an errored or failed daemon right away instead of after its
+    /// retry backoff (`pitchfork retry`). Returns false if it was already
+    /// running.
+    pub async fn retry(&self, id: DaemonId) -> Result<bool> {
+        let rsp = self.request(IpcRequest::Retry { id: id.clone() }).await?;
+        match rsp {
+            IpcResponse::DaemonStart { .. } | IpcResponse::DaemonReady { .. } => {
+                info!("Retrying daemon {id}");
+                Ok(true)
+            }
+            IpcResponse::DaemonAlreadyRunning => {
+                warn!("Daemon {id} already running");
+                Ok(false)
+            }
+            IpcResponse::DaemonFailed { error } => {
+                Err(miette::miette!("failed to retry daemon {id}: {error}"))
+            }
+            IpcResponse::PortConflict { port, process, pid } => Err(miette::miette!(
+                "failed to retry daemon {id}: {}",
+                PortConflict { port, process, pid }
+            )),
+            IpcResponse::Error(error) => Err(miette::miette!(error)),
+            rsp => Err(Self::unexpected_response("DaemonStart", &rsp).into()),
+        }
+    }
+
+    /// Clear an errored daemon's pending retries. Returns false if none
+    /// were pending.
+    pub async fn cancel_retry(&self, id: DaemonId) -> Result<bool> {
+        let rsp = self
+            .request(IpcRequest::CancelRetry { id: id.clone() })
+            .await?;
+        match rsp {
+            IpcResponse::Yes => {
+                info!("Cancelled pending retries of daemon {id}");
+                Ok(true)
+            }
+            IpcResponse::No => {
+                info!("Daemon {id} has no pending retries");
                 Ok(false)
             }
             IpcResponse::Error(error) => Err(miette::miette!(error)),
@@ -280,7 +572,16 @@ impl IpcClient {
                 .ready_http
                 .as_ref()
                 .is_some_and(|h| h.timeout.is_none())
-            || opts.ready_cmd.as_ref().is_some_and(|c| c.timeout.is_none());
+            || opts.ready_cmd.as_ref().is_some_and(|c| c.timeout.is_none())
+            || opts.ready_unix_socket.is_some()
+            || opts
+                .ready_log_file
+                .as_ref()
+                .is_some_and(|f| f.timeout.is_none())
+            || opts
+                .ready_when
+                .as_ref()
+                .is_some_and(|w| w.timeout.is_none());
         let timeout = if has_unbounded_check {
             Duration::from_secs(3600)
         } else {
@@ -311,12 +612,42 @@ impl IpcClient {
                         .map(|d| d.as_secs())
                         .unwrap_or(0),
                 )
-                .max(opts.ready_delay.unwrap_or(3));
-            Duration::from_secs(max_deadline + 60)
+                .max(
+                    opts.ready_log_file
+                        .as_ref()
+                        .and_then(|f| f.timeout)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                )
+                .max(
+                    opts.ready_when
+                        .as_ref()
+                        .and_then(|w| w.timeout)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                )
+                .max(
+                    opts.ready_delay
+                        .unwrap_or(crate::daemon::DEFAULT_READY_DELAY)
+                        .as_secs(),
+                );
+            // Requirements are waited for one after another before spawning
+            let requires: u64 = opts.requires.iter().map(|r| r.timeout().as_secs()).sum();
+            Duration::from_secs(max_deadline + requires + 60)
         };
+        // `--wait-timeout` bounds the wait in the supervisor
+        let timeout = match opts.wait_timeout {
+            Some(wait) => {
+                let requires: Duration = opts.requires.iter().map(|r| r.timeout()).sum();
+                timeout.min(wait + requires + Duration::from_secs(60))
+            }
+            None => timeout,
+        };
+        let requested_at = std::time::Instant::now();
         let rsp = self
             .request_with_timeout(IpcRequest::Run(opts.clone()), timeout)
             .await?;
+        let elapsed = requested_at.elapsed();
 
         match rsp {
             IpcResponse::DaemonStart { daemon } => {
@@ -327,6 +658,11 @@ impl IpcClient {
                     start_time,
                     resolved_ports: daemon.resolved_port.clone(),
                     error_message: None,
+                    port_conflict: None,
+                    timed_out: false,
+                    elapsed,
+                    failed_check: None,
+                    last_output: None,
                 })
             }
             IpcResponse::DaemonReady { daemon } => {
@@ -337,10 +673,16 @@ impl IpcClient {
                     start_time,
                     resolved_ports: daemon.resolved_port.clone(),
                     error_message: None,
+                    port_conflict: None,
+                    timed_out: false,
+                    elapsed,
+                    failed_check: None,
+                    last_output: None,
                 })
             }
             IpcResponse::DaemonFailedWithCode { exit_code } => {
                 let code = exit_code.unwrap_or(1);
+                let last_output = last_output_since(&opts.id, start_time);
                 Ok(RunResult {
                     started: false,
                     exit_code: Some(code),
@@ -350,6 +692,35 @@ impl IpcClient {
                         "Daemon {} failed with exit code {}",
                         opts.id, code
                     )),
+                    port_conflict: None,
+                    timed_out: false,
+                    elapsed,
+                    failed_check: Some(format!("exited with code {code}")),
+                    last_output,
+                })
+            }
+            IpcResponse::DaemonNotReady { reason } => {
+                // The last thing the daemon printed usually says what it is stuck on
+                let last_output = last_output_since(&opts.id, start_time);
+                let last_output_suffix = last_output
+                    .as_ref()
+                    .map(|msg| format!(" (last output: {msg})"))
+                    .unwrap_or_default();
+                // Exit code 124 follows `timeout(1)`
+                Ok(RunResult {
+                    started: false,
+                    exit_code: Some(124),
+                    start_time,
+                    resolved_ports: Vec::new(),
+                    error_message: Some(format!(
+                        "Daemon {} not ready: {reason}{last_output_suffix}",
+                        opts.id
+                    )),
+                    port_conflict: None,
+                    timed_out: true,
+                    elapsed,
+                    failed_check: Some(reason),
+                    last_output,
                 })
             }
             IpcResponse::DaemonAlreadyRunning => {
@@ -360,6 +731,11 @@ impl IpcClient {
                     start_time,
                     resolved_ports: Vec::new(),
                     error_message: None,
+                    port_conflict: None,
+                    timed_out: false,
+                    elapsed,
+                    failed_check: None,
+                    last_output: None,
                 })
             }
             IpcResponse::DaemonFailed { error } => Ok(RunResult {
@@ -368,17 +744,28 @@ impl IpcClient {
                 start_time,
                 resolved_ports: Vec::new(),
                 error_message: Some(format!("Failed to start daemon {}: {}", opts.id, error)),
+                port_conflict: None,
+                timed_out: false,
+                elapsed,
+                failed_check: Some("spawn".to_string()),
+                last_output: None,
             }),
-            IpcResponse::PortConflict { port, process, pid } => Ok(RunResult {
-                started: false,
-                exit_code: Some(1),
-                start_time,
-                resolved_ports: Vec::new(),
-                error_message: Some(format!(
-                    "Failed to start daemon {}: port {} is already in use by process '{}' (PID: {})",
-                    opts.id, port, process, pid
-                )),
-            }),
+            IpcResponse::PortConflict { port, process, pid } => {
+                let conflict = PortConflict { port, process, pid };
+                let failed_check = Some(format!("port {port}"));
+                Ok(RunResult {
+                    started: false,
+                    exit_code: Some(1),
+                    start_time,
+                    resolved_ports: Vec::new(),
+                    error_message: Some(format!("Failed to start daemon {}: {conflict}", opts.id)),
+                    port_conflict: Some(conflict),
+                    timed_out: false,
+                    elapsed,
+                    failed_check,
+                    last_output: None,
+                })
+            }
             IpcResponse::NoAvailablePort {
                 start_port,
                 attempts,
@@ -391,12 +778,22 @@ impl IpcClient {
                     "Failed to start daemon {}: could not find an available port after {} attempts starting from {}",
                     opts.id, attempts, start_port
                 )),
+                port_conflict: None,
+                timed_out: false,
+                elapsed,
+                failed_check: Some(format!("port {start_port}")),
+                last_output: None,
             }),
             rsp => Err(Self::unexpected_response("DaemonStart or DaemonReady", &rsp).into()),
         }
     }
 
     pub async fn active_daemons(&self) -> Result<Vec<Daemon>> {
+        if view_user().is_some() {
+            let (mut daemons, _) = self.user_daemons().await?;
+            daemons.retain(|d| d.pid.is_some());
+            return Ok(daemons);
+        }
         let rsp = self.request(IpcRequest::GetActiveDaemons).await?;
         match rsp {
             IpcResponse::ActiveDaemons(daemons) => Ok(daemons),
@@ -404,13 +801,42 @@ impl IpcClient {
         }
     }
 
-    pub async fn update_shell_dir(&self, shell_pid: u32, dir: PathBuf) -> Result<()> {
+    /// State file daemons of the `--user` user (or the caller) and the
+    /// auto-disabled set, from a shared supervisor
+    pub async fn user_daemons(&self) -> Result<(Vec<Daemon>, Vec<DaemonId>)> {
         let rsp = self
-            .request(IpcRequest::UpdateShellDir {
-                shell_pid,
-                dir: dir.clone(),
+            .request(IpcRequest::GetUserDaemons {
+                user: view_user().map(str::to_string),
             })
             .await?;
+        match rsp {
+            IpcResponse::UserDaemons {
+                daemons,
+                auto_disabled,
+            } => Ok((daemons, auto_disabled)),
+            IpcResponse::Error(error) => Err(miette::miette!("{error}")),
+            rsp => Err(Self::unexpected_response("UserDaemons", &rsp).into()),
+        }
+    }
+
+    /// Report a shell's new directory. Uses the short `general.shell_hook_timeout`
+    /// since this runs on every `cd` from the shell hook.
+    pub async fn update_shell_dir(
+        &self,
+        shell_pid: u32,
+        dir: PathBuf,
+        seq: Option<u64>,
+    ) -> Result<()> {
+        let rsp = self
+            .request_with_timeout(
+                IpcRequest::UpdateShellDir {
+                    shell_pid,
+                    dir: dir.clone(),
+                    seq,
+                },
+                settings().general_shell_hook_timeout(),
+            )
+            .await?;
         match rsp {
             IpcResponse::Ok => {
                 trace!("updated shell dir for pid {shell_pid} to {}", dir.display());
@@ -420,8 +846,12 @@ impl IpcClient {
         Ok(())
     }
 
-    pub async fn clean(&self) -> Result<()> {
-        let rsp = self.request(IpcRequest::Clean).await?;
+    pub async fn clean(&self, keep: &[DaemonId]) -> Result<()> {
+        let rsp = self
+            .request(IpcRequest::Clean {
+                keep: keep.to_vec(),
+            })
+            .await?;
         match rsp {
             IpcResponse::Ok => {
                 info!("Cleaned up stopped/failed daemons");
@@ -519,6 +949,57 @@ impl IpcClient {
         }
     }
 
+    /// Startup metadata of the supervisor.
+    pub async fn get_supervisor_info(&self) -> Result<crate::ipc::SupervisorInfo> {
+        let rsp = self.request(IpcRequest::GetSupervisorInfo).await?;
+        match rsp {
+            IpcResponse::SupervisorInfo(info) => Ok(info),
+            rsp => Err(Self::unexpected_response("SupervisorInfo", &rsp).into()),
+        }
+    }
+
+    /// Pause or resume automatic starts and restarts. Returns false if the
+    /// supervisor was already paused or running normally.
+    pub async fn set_paused(&self, paused: bool) -> Result<bool> {
+        let rsp = self.request(IpcRequest::SetPaused { paused }).await?;
+        match rsp {
+            IpcResponse::Yes => Ok(true),
+            IpcResponse::No => Ok(false),
+            IpcResponse::Error(error) => Err(miette::miette!(error)),
+            rsp => Err(Self::unexpected_response("Yes or No", &rsp).into()),
+        }
+    }
+
+    /// Ask the supervisor to stop, leaving its daemons running if
+    /// `keep_daemons`. Returns once it has accepted; it exits shortly after.
+    pub async fn shutdown(&self, keep_daemons: bool) -> Result<()> {
+        let rsp = self.request(IpcRequest::Shutdown { keep_daemons }).await?;
+        match rsp {
+            IpcResponse::Ok => Ok(()),
+            IpcResponse::Error(error) => Err(miette::miette!(error)),
+            rsp => Err(Self::unexpected_response("Ok", &rsp).into()),
+        }
+    }
+
+    /// Move a stopped daemon's state and logs to the ID it was renamed to
+    pub async fn rename_daemon(&self, from: DaemonId, to: DaemonId) -> Result<()> {
+        let rsp = self.request(IpcRequest::RenameDaemon { from, to }).await?;
+        match rsp {
+            IpcResponse::Ok => Ok(()),
+            IpcResponse::Error(error) => Err(miette::miette!(error)),
+            rsp => Err(Self::unexpected_response("Ok", &rsp).into()),
+        }
+    }
+
+    /// Requests the supervisor received recently, oldest first
+    pub async fn get_ipc_log(&self) -> Result<Vec<crate::ipc::IpcLogEntry>> {
+        let rsp = self.request(IpcRequest::GetIpcLog).await?;
+        match rsp {
+            IpcResponse::IpcLog(entries) => Ok(entries),
+            rsp => Err(Self::unexpected_response("IpcLog", &rsp).into()),
+        }
+    }
+
     /// List all tracked project sessions with live liveness status.
     pub async fn get_project_sessions(&self) -> Result<Vec<crate::ipc::ProjectSessionInfo>> {
         let rsp = self.request(IpcRequest::GetProjectSessions).await?;
@@ -528,6 +1009,22 @@ impl IpcClient {
         }
     }
 
+    /// Fetch up to `lines` of a daemon's most recent output from the
+    /// supervisor's in-memory buffer, oldest first.
+    pub async fn get_recent_logs(
+        &self,
+        id: DaemonId,
+        lines: usize,
+    ) -> Result<Vec<crate::log_store::recent::RecentLogLine>> {
+        let rsp = self
+            .request(IpcRequest::GetRecentLogs { id, lines })
+            .await?;
+        match rsp {
+            IpcResponse::RecentLogs(lines) => Ok(lines),
+            rsp => Err(Self::unexpected_response("RecentLogs", &rsp).into()),
+        }
+    }
+
     /// Stop a single daemon (low-level operation)
     pub async fn stop(&self, id: DaemonId) -> Result<bool> {
         let id_str = id.qualified();
@@ -565,3 +1062,12 @@ impl IpcClient {
         }
     }
 }
+
+/// The last non-empty line a daemon printed since `since`, without colors
+fn last_output_since(id: &DaemonId, since: chrono::DateTime<chrono::Local>) -> Option<String> {
+    crate::cli::logs::collect_startup_logs(id, since)
+        .ok()
+        .and_then(|lines| lines.into_iter().next_back())
+        .map(|(_, _, msg)| console::strip_ansi_codes(&msg).trim().to_string())
+        .filter(|msg| !msg.is_empty())
+}
@@ -0,0 +1,371 @@
+//! Chunked, compressed framing for large IPC messages.
+//!
+//! Every IPC message is normally a single serialized value followed by a null
+//! byte. A response that serializes to more than `ipc.compress_threshold`
+//! bytes is instead deflate-compressed, base64-encoded (so it stays free of
+//! null bytes) and split into chunk frames of at most `ipc.chunk_size` bytes:
+//!
+//! ```text
+//! \x01<index>/<count> <base64 data>\0
+//! ```
+//!
+//! Chunk frames are only sent to clients that announced [`FRAMES_FEATURE`] in
+//! their `ConnectV2` handshake, so older CLIs keep receiving plain messages.
+//! Readers reassemble the chunks before deserializing.
+//!
+//! Clients that announce [`LENGTH_PREFIX_FEATURE`] get every frame with a
+//! length prefix instead of a null terminator:
+//!
+//! ```text
+//! \x02<u32 big-endian length><frame>
+//! ```
+//!
+//! so a frame can't be cut short by a stray null byte and its size is known
+//! (and bounded) before it is read. The supervisor answers the handshake in
+//! that format, which is how the client learns it may send requests that
+//! way too. Readers accept both formats on any frame.
+
+use crate::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use miette::{bail, ensure};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+/// Feature name a client sends in `ConnectV2` to accept chunk frames.
+pub(crate) const FRAMES_FEATURE: &str = "frames";
+
+/// Feature name a client sends in `ConnectV2` to accept length-prefixed
+/// frames.
+pub(crate) const LENGTH_PREFIX_FEATURE: &str = "length-prefix";
+
+/// Feature name a client sends in `ConnectV2` to accept
+/// `IpcResponse::ProtocolError` for requests the supervisor can't decode.
+pub(crate) const PROTOCOL_ERROR_FEATURE: &str = "protocol-error";
+
+/// First byte of a chunk frame. Never the first byte of a plain JSON or
+/// MessagePack message.
+const CHUNK_MARKER: u8 = 0x01;
+
+/// First byte of a length-prefixed frame. Like [`CHUNK_MARKER`], never the
+/// first byte of a plain message.
+const LENGTH_MARKER: u8 = 0x02;
+
+/// Upper bound on a reassembled, decompressed message, so a corrupt or
+/// hostile stream can't exhaust memory.
+pub(crate) const MAX_MESSAGE_SIZE: usize = 256 * 1024 * 1024;
+
+/// Upper bound on a single request frame read by the supervisor. Requests
+/// are small; anything near this is a broken or hostile client.
+pub(crate) const MAX_REQUEST_SIZE: usize = 16 * 1024 * 1024;
+
+/// One frame read off a connection, without its terminator or prefix.
+#[derive(Debug)]
+pub(crate) struct Frame {
+    pub body: Vec<u8>,
+    /// The frame was length-prefixed rather than null-terminated
+    pub length_prefixed: bool,
+}
+
+/// Read one frame of at most `max` bytes in either format. Returns `None`
+/// when the peer closed the connection between frames. An oversized frame
+/// is an `InvalidData` error; the stream can't be resynchronized after it.
+pub(crate) async fn read_frame<R: AsyncBufRead + Unpin>(
+    recv: &mut R,
+    max: usize,
+) -> std::io::Result<Option<Frame>> {
+    let first = match recv.fill_buf().await?.first() {
+        Some(b) => *b,
+        None => return Ok(None),
+    };
+    if first == LENGTH_MARKER {
+        recv.consume(1);
+        let len = recv.read_u32().await? as usize;
+        if len > max {
+            return Err(oversized(len, max));
+        }
+        let mut body = vec![0; len];
+        recv.read_exact(&mut body).await?;
+        return Ok(Some(Frame {
+            body,
+            length_prefixed: true,
+        }));
+    }
+
+    let mut body = Vec::new();
+    loop {
+        let buf = recv.fill_buf().await?;
+        if buf.is_empty() {
+            // EOF in the middle of a frame: hand back what arrived, like
+            // `read_until` did, and let the caller fail to decode it
+            break;
+        }
+        let (chunk, done) = match buf.iter().position(|b| *b == 0) {
+            Some(i) => (&buf[..i], Some(i + 1)),
+            None => (buf, None),
+        };
+        if body.len() + chunk.len() > max {
+            return Err(oversized(body.len() + chunk.len(), max));
+        }
+        body.extend_from_slice(chunk);
+        let consumed = done.unwrap_or(chunk.len());
+        recv.consume(consumed);
+        if done.is_some() {
+            break;
+        }
+    }
+    Ok(Some(Frame {
+        body,
+        length_prefixed: false,
+    }))
+}
+
+fn oversized(len: usize, max: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("IPC frame of {len} bytes exceeds the {max} byte limit"),
+    )
+}
+
+/// Terminate a frame body: a length prefix, or a trailing null byte.
+pub(crate) fn finish(body: Vec<u8>, length_prefix: bool) -> Vec<u8> {
+    if length_prefix {
+        let len = u32::try_from(body.len()).unwrap_or(u32::MAX);
+        let mut frame = Vec::with_capacity(body.len() + 5);
+        frame.push(LENGTH_MARKER);
+        frame.extend_from_slice(&len.to_be_bytes());
+        frame.extend(body);
+        frame
+    } else {
+        let mut frame = body;
+        frame.push(0);
+        frame
+    }
+}
+
+/// Split a serialized message into the frames to write, each terminated by a
+/// null byte or, with `length_prefix`, preceded by its length. Messages at or
+/// below `compress_threshold` (or any message when it is 0) are sent as a
+/// single plain frame.
+pub(crate) fn encode(
+    payload: Vec<u8>,
+    compress_threshold: usize,
+    chunk_size: usize,
+    length_prefix: bool,
+) -> Vec<Vec<u8>> {
+    if compress_threshold == 0 || payload.len() <= compress_threshold {
+        return vec![finish(payload, length_prefix)];
+    }
+    let compressed = miniz_oxide::deflate::compress_to_vec(&payload, 6);
+    let encoded = BASE64.encode(compressed);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(chunk_size.max(1)).collect();
+    let count = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut frame = vec![CHUNK_MARKER];
+            frame.extend_from_slice(format!("{index}/{count} ").as_bytes());
+            frame.extend_from_slice(chunk);
+            finish(frame, length_prefix)
+        })
+        .collect()
+}
+
+/// Reassembles chunk frames read from one connection.
+#[derive(Debug, Default)]
+pub(crate) struct Reassembler {
+    encoded: Vec<u8>,
+    next_index: usize,
+}
+
+impl Reassembler {
+    /// Feed one frame (a null-terminated one with or without its
+    /// terminator). Returns the complete serialized message once all of its
+    /// frames have arrived.
+    pub(crate) fn push(&mut self, frame: &[u8], length_prefixed: bool) -> Result<Option<Vec<u8>>> {
+        // A length-prefixed body may well end in a null byte of its own
+        let frame = match length_prefixed {
+            true => frame,
+            false => frame.strip_suffix(&[0]).unwrap_or(frame),
+        };
+        let Some(chunk) = frame.strip_prefix(&[CHUNK_MARKER]) else {
+            ensure!(
+                self.next_index == 0,
+                "IPC message interrupted after chunk {} of a chunked message",
+                self.next_index
+            );
+            return Ok(Some(frame.to_vec()));
+        };
+
+        let Some(space) = chunk.iter().position(|b| *b == b' ') else {
+            bail!("malformed IPC chunk frame: missing header");
+        };
+        let header = std::str::from_utf8(&chunk[..space]).unwrap_or_default();
+        let Some((index, count)) = header
+            .split_once('/')
+            .and_then(|(i, c)| Some((i.parse::<usize>().ok()?, c.parse::<usize>().ok()?)))
+        else {
+            bail!("malformed IPC chunk frame header {header:?}");
+        };
+        ensure!(
+            index == self.next_index && index < count,
+            "IPC chunk {index}/{count} arrived out of order (expected chunk {})",
+            self.next_index
+        );
+
+        self.encoded.extend_from_slice(&chunk[space + 1..]);
+        ensure!(
+            self.encoded.len() <= MAX_MESSAGE_SIZE,
+            "chunked IPC message exceeds {MAX_MESSAGE_SIZE} bytes"
+        );
+        if index + 1 < count {
+            self.next_index += 1;
+            return Ok(None);
+        }
+
+        let encoded = std::mem::take(&mut self.encoded);
+        self.next_index = 0;
+        let compressed = BASE64
+            .decode(encoded)
+            .map_err(|e| miette::miette!("invalid base64 in chunked IPC message: {e}"))?;
+        let payload =
+            miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_MESSAGE_SIZE)
+                .map_err(|e| miette::miette!("failed to decompress chunked IPC message: {e}"))?;
+        Ok(Some(payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(payload: &[u8], threshold: usize, chunk_size: usize) -> (usize, Vec<u8>) {
+        let frames = encode(payload.to_vec(), threshold, chunk_size, false);
+        let count = frames.len();
+        let mut reassembler = Reassembler::default();
+        let mut result = None;
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.last(), Some(&0));
+            assert!(!frame[..frame.len() - 1].contains(&0));
+            let out = reassembler.push(frame, false).unwrap();
+            assert_eq!(out.is_some(), i + 1 == count);
+            result = out;
+        }
+        (count, result.unwrap())
+    }
+
+    #[test]
+    fn test_small_message_is_plain() {
+        let payload = br#"{"RecentLogs":[]}"#;
+        let frames = encode(payload.to_vec(), 1024, 64, false);
+        assert_eq!(frames, vec![[payload.as_slice(), &[0]].concat()]);
+        assert_eq!(roundtrip(payload, 1024, 64), (1, payload.to_vec()));
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_compression() {
+        let payload = vec![b'x'; 10_000];
+        assert_eq!(roundtrip(&payload, 0, 16), (1, payload));
+    }
+
+    #[test]
+    fn test_large_message_is_chunked_and_compressed() {
+        let payload: Vec<u8> = (0..50_000)
+            .flat_map(|i| format!("{{\"line\":\"log line {i}\"}},").into_bytes())
+            .collect();
+        let (count, out) = roundtrip(&payload, 1024, 4096);
+        assert!(count > 1);
+        assert_eq!(out, payload);
+    }
+
+    #[test]
+    fn test_out_of_order_chunk_is_rejected() {
+        let payload = vec![b'a'; 100_000];
+        let frames = encode(payload, 10, 8, false);
+        assert!(frames.len() > 2);
+        let mut reassembler = Reassembler::default();
+        assert!(reassembler.push(&frames[1], false).is_err());
+    }
+
+    #[test]
+    fn test_plain_frame_during_chunked_message_is_rejected() {
+        let frames = encode(vec![b'a'; 100_000], 10, 8, false);
+        let mut reassembler = Reassembler::default();
+        reassembler.push(&frames[0], false).unwrap();
+        assert!(reassembler.push(b"\"Ok\"\0", false).is_err());
+    }
+
+    async fn read_all(stream: &[u8], max: usize) -> std::io::Result<Vec<Frame>> {
+        let mut recv = tokio::io::BufReader::with_capacity(7, stream);
+        let mut frames = vec![];
+        while let Some(frame) = read_frame(&mut recv, max).await? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_mixed_formats() {
+        // A length-prefixed frame may carry null bytes
+        let payload = b"\x81\xa3Run\x00\x00".to_vec();
+        let mut stream = encode(b"\"Ok\"".to_vec(), 0, 64, false).concat();
+        stream.extend(encode(payload.clone(), 0, 64, true).concat());
+        stream.extend(encode(b"\"Yes\"".to_vec(), 0, 64, false).concat());
+
+        let frames = read_all(&stream, 1024).await.unwrap();
+        let bodies: Vec<_> = frames.iter().map(|f| f.body.as_slice()).collect();
+        assert_eq!(bodies, vec![b"\"Ok\"".as_slice(), &payload, b"\"Yes\""]);
+        let prefixed: Vec<_> = frames.iter().map(|f| f.length_prefixed).collect();
+        assert_eq!(prefixed, vec![false, true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_chunked_length_prefixed() {
+        let payload: Vec<u8> = (0..20_000)
+            .flat_map(|i| i.to_string().into_bytes())
+            .collect();
+        let stream = encode(payload.clone(), 10, 512, true).concat();
+        let mut reassembler = Reassembler::default();
+        let mut out = None;
+        for frame in read_all(&stream, 1024).await.unwrap() {
+            assert!(frame.length_prefixed);
+            out = reassembler.push(&frame.body, true).unwrap();
+        }
+        assert_eq!(out, Some(payload));
+    }
+
+    #[tokio::test]
+    async fn test_length_prefixed_body_ending_in_null() {
+        let id = crate::daemon_id::DaemonId::new("proj", "api");
+        let request = crate::ipc::IpcRequest::GetRecentLogs { id, lines: 0 };
+        let payload = rmp_serde::to_vec(&request).unwrap();
+        assert_eq!(payload.last(), Some(&0));
+
+        let stream = encode(payload.clone(), 0, 64, true).concat();
+        let frames = read_all(&stream, 1024).await.unwrap();
+        let [frame] = frames.as_slice() else {
+            panic!("expected one frame, got {frames:?}");
+        };
+        let mut reassembler = Reassembler::default();
+        let message = reassembler
+            .push(&frame.body, frame.length_prefixed)
+            .unwrap();
+        assert_eq!(message.as_ref(), Some(&payload));
+        let decoded: crate::ipc::IpcRequest = rmp_serde::from_slice(&message.unwrap()).unwrap();
+        assert!(matches!(
+            decoded,
+            crate::ipc::IpcRequest::GetRecentLogs { lines: 0, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_rejects_oversized() {
+        let stream = encode(vec![b'a'; 100], 0, 64, true).concat();
+        let err = read_all(&stream, 50).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let stream = encode(vec![b'a'; 100], 0, 64, false).concat();
+        let err = read_all(&stream, 50).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -2,6 +2,7 @@ use crate::Result;
 use crate::daemon::{Daemon, RunOptions};
 use crate::daemon_id::DaemonId;
 use crate::env;
+use crate::log_store::recent::RecentLogLine;
 use interprocess::local_socket::Name;
 #[cfg(unix)]
 use interprocess::local_socket::{GenericFilePath, ToFsName};
@@ -12,6 +13,7 @@ use std::path::PathBuf;
 
 pub(crate) mod batch;
 pub(crate) mod client;
+pub(crate) mod frame;
 pub(crate) mod server;
 
 // #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, strum::Display, strum::EnumIs)]
@@ -37,23 +39,53 @@ pub enum IpcRequest {
     /// (unit variant) stays unchanged for backward compatibility with older supervisors.
     ConnectV2 {
         version: String,
+        /// Optional protocol features the client understands (e.g. chunked
+        /// responses). Older supervisors ignore this field.
+        #[serde(default)]
+        features: Vec<String>,
+    },
+    Clean {
+        /// Daemons to keep even though they aren't running
+        #[serde(default)]
+        keep: Vec<DaemonId>,
     },
-    Clean,
     Stop {
         id: DaemonId,
     },
     GetActiveDaemons,
     GetDisabledDaemons,
+    /// All daemons in the state file that belong to `user` (the caller when
+    /// `None`) on a shared supervisor, for clients that cannot read the
+    /// state file themselves.
+    GetUserDaemons {
+        user: Option<String>,
+    },
     Run(RunOptions),
     Enable {
         id: DaemonId,
     },
     Disable {
         id: DaemonId,
+        /// Only block automatic starts (autostart, cron, boot start).
+        /// Older clients don't send it and get a full disable.
+        #[serde(default)]
+        auto: bool,
+    },
+    /// Retry an errored daemon now, skipping the retry backoff
+    Retry {
+        id: DaemonId,
+    },
+    /// Clear an errored daemon's pending retries
+    CancelRetry {
+        id: DaemonId,
     },
     UpdateShellDir {
         shell_pid: u32,
         dir: PathBuf,
+        /// Counts the shell's directory changes, so an update that arrives
+        /// after a newer one is dropped. Older shell hooks don't send it.
+        #[serde(default)]
+        seq: Option<u64>,
     },
     GetNotifications,
     /// Notify the supervisor that the slug registry has changed (e.g. `proxy add/remove`).
@@ -75,13 +107,87 @@ pub enum IpcRequest {
     /// List all tracked project sessions with live liveness status filled in
     /// by the supervisor.
     GetProjectSessions,
-    /// Invalid request (failed to deserialize)
-    #[serde(skip)]
-    Invalid {
-        error: String,
+    /// Fetch up to `lines` of a daemon's most recent output from the
+    /// supervisor's in-memory ring buffer.
+    GetRecentLogs {
+        id: DaemonId,
+        lines: usize,
+    },
+    /// Startup metadata of the supervisor, for the banner shown by a client
+    /// that just started it.
+    GetSupervisorInfo,
+    /// Pause or resume automatic starts and restarts of all daemons.
+    /// Answered with `Yes` if that changed the supervisor's state.
+    SetPaused {
+        paused: bool,
+    },
+    /// Requests the supervisor received recently, including ones it is
+    /// still handling (`pitchfork debug ipc`).
+    GetIpcLog,
+    /// Stop the supervisor. With `keep_daemons` its daemons are left
+    /// running and marked detached, for the next supervisor to adopt.
+    Shutdown {
+        keep_daemons: bool,
+    },
+    /// Move a stopped daemon's state and logs to the ID it was renamed to
+    /// (`pitchfork config rename`).
+    RenameDaemon {
+        from: DaemonId,
+        to: DaemonId,
     },
 }
 
+impl IpcRequest {
+    /// The daemon a request acts on, if any
+    pub fn daemon_id(&self) -> Option<&DaemonId> {
+        match self {
+            Self::Stop { id }
+            | Self::Enable { id }
+            | Self::Disable { id, .. }
+            | Self::Retry { id }
+            | Self::CancelRetry { id }
+            | Self::GetRecentLogs { id, .. }
+            | Self::RenameDaemon { from: id, .. } => Some(id),
+            Self::Run(opts) => Some(&opts.id),
+            _ => None,
+        }
+    }
+}
+
+/// Startup metadata of the running supervisor, returned by `GetSupervisorInfo`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SupervisorInfo {
+    pub pid: u32,
+    pub version: String,
+    /// The main IPC socket (named pipe on Windows)
+    pub socket: String,
+    /// Daemons with `boot_start = true` started by `supervisor run --boot`
+    pub boot_daemons_started: u32,
+    /// Automatic starts and restarts are paused (`supervisor pause`)
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// A request the supervisor received, returned by `GetIpcLog`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IpcLogEntry {
+    /// Increases by one per request since the supervisor started
+    pub seq: u64,
+    pub at: chrono::DateTime<chrono::Local>,
+    /// Request kind, e.g. `Run` or `GetActiveDaemons`
+    pub request: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub daemon: Option<String>,
+    /// UID of the client, where peer credentials are available
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub uid: Option<u32>,
+    /// Time spent handling it, so far if it is still pending
+    pub elapsed_ms: u64,
+    /// Response kind, or `Error: <message>`; `None` while still pending
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub response: Option<String>,
+}
+
 /// A snapshot of a single project session, returned by `GetProjectSessions`.
 ///
 /// `liveness_title` is the title recorded at enter time. `alive` and
@@ -108,9 +214,20 @@ pub enum IpcResponse {
     Yes,
     No,
     Error(String),
+    /// The request could not be decoded; the connection stays usable. Only
+    /// sent to clients that announced `protocol-error` in their handshake,
+    /// older ones get `Error` instead.
+    ProtocolError {
+        error: String,
+    },
     Notifications(Vec<(log::LevelFilter, String)>),
     ActiveDaemons(Vec<Daemon>),
     DisabledDaemons(Vec<DaemonId>),
+    /// State file daemons visible to the caller (response to `GetUserDaemons`)
+    UserDaemons {
+        daemons: Vec<Daemon>,
+        auto_disabled: Vec<DaemonId>,
+    },
     DaemonAlreadyRunning,
     DaemonStart {
         daemon: Daemon,
@@ -135,6 +252,10 @@ pub enum IpcResponse {
     DaemonFailedWithCode {
         exit_code: Option<i32>,
     },
+    /// The daemon's ready checks gave up before it became ready
+    DaemonNotReady {
+        reason: String,
+    },
     /// Process was not running but had a PID record (unexpected exit)
     DaemonWasNotRunning,
     /// mDNS sync completed (or was a no-op if LAN mode is disabled)
@@ -150,7 +271,26 @@ pub enum IpcResponse {
     DaemonNotFound,
     /// Snapshot of all project sessions (response to `GetProjectSessions`).
     ProjectSessions(Vec<ProjectSessionInfo>),
+    /// Recent log lines for a daemon, oldest first (response to `GetRecentLogs`).
+    RecentLogs(Vec<RecentLogLine>),
+    /// Startup metadata of the supervisor (response to `GetSupervisorInfo`).
+    SupervisorInfo(SupervisorInfo),
+    /// Recent requests, oldest first (response to `GetIpcLog`).
+    IpcLog(Vec<IpcLogEntry>),
 }
+
+/// The main socket as shown to users: its path, or the pipe name on Windows.
+pub(crate) fn main_socket_display() -> String {
+    #[cfg(unix)]
+    {
+        env::IPC_SOCK_MAIN.display().to_string()
+    }
+    #[cfg(windows)]
+    {
+        format!(r"\\.\pipe\{}", pipe_name("main"))
+    }
+}
+
 fn fs_name(name: &str) -> Result<Name<'_>> {
     // Unix: use a filesystem path for the AF_UNIX socket.
     #[cfg(unix)]
@@ -159,33 +299,37 @@ fn fs_name(name: &str) -> Result<Name<'_>> {
         let fs_name = path.to_fs_name::<GenericFilePath>().into_diagnostic()?;
         Ok(fs_name)
     }
-    // Windows: named pipes use a flat namespace (\\.\pipe\<name>) that
-    // cannot contain path separators. Derive a unique pipe name from the
-    // state directory to preserve test isolation when multiple supervisors
-    // run concurrently with different PITCHFORK_STATE_DIR values.
-    //
-    // Use a hash of the state directory path rather than character replacement
-    // to guarantee injectivity: `C:\a.b` and `C:\a\b` would both flatten to
-    // `C--a-b` with the old approach, causing pipe name collisions.
     #[cfg(windows)]
     {
-        let state_dir = env::PITCHFORK_STATE_DIR.to_string_lossy();
-        // FNV-1a hash: deterministic, stable across Rust versions.
-        // DefaultHasher's algorithm is not guaranteed stable, which would
-        // break IPC if the CLI and supervisor were ever compiled with
-        // different toolchains.
-        let mut hash: u64 = 0xcbf29ce484222325;
-        for byte in state_dir.bytes() {
-            hash ^= byte as u64;
-            hash = hash.wrapping_mul(0x100000001b3);
-        }
-        let pipe_name = format!("pitchfork-{hash:016x}-{name}");
-        Ok(pipe_name
+        Ok(pipe_name(name)
             .to_ns_name::<GenericNamespaced>()
             .into_diagnostic()?)
     }
 }
 
+// Windows: named pipes use a flat namespace (\\.\pipe\<name>) that
+// cannot contain path separators. Derive a unique pipe name from the
+// state directory to preserve test isolation when multiple supervisors
+// run concurrently with different PITCHFORK_STATE_DIR values.
+//
+// Use a hash of the state directory path rather than character replacement
+// to guarantee injectivity: `C:\a.b` and `C:\a\b` would both flatten to
+// `C--a-b` with the old approach, causing pipe name collisions.
+#[cfg(windows)]
+fn pipe_name(name: &str) -> String {
+    let state_dir = env::PITCHFORK_STATE_DIR.to_string_lossy();
+    // FNV-1a hash: deterministic, stable across Rust versions.
+    // DefaultHasher's algorithm is not guaranteed stable, which would
+    // break IPC if the CLI and supervisor were ever compiled with
+    // different toolchains.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in state_dir.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("pitchfork-{hash:016x}-{name}")
+}
+
 fn serialize<T: serde::Serialize>(msg: &T) -> Result<Vec<u8>> {
     if *env::IPC_JSON {
         serde_json::to_vec(msg)
@@ -198,17 +342,17 @@ fn serialize<T: serde::Serialize>(msg: &T) -> Result<Vec<u8>> {
     }
 }
 
+/// Decode a message as read by [`frame::read_frame`] and
+/// [`frame::Reassembler`], which leave off null terminators
 fn deserialize<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
-    let mut bytes = bytes.to_vec();
-    bytes.pop();
-    let preview = std::str::from_utf8(&bytes).unwrap_or("<binary>");
+    let preview = std::str::from_utf8(bytes).unwrap_or("<binary>");
     trace!("msg: {preview:?}");
     if *env::IPC_JSON {
-        serde_json::from_slice(&bytes)
+        serde_json::from_slice(bytes)
             .into_diagnostic()
             .wrap_err("failed to deserialize IPC JSON response")
     } else {
-        rmp_serde::from_slice(&bytes)
+        rmp_serde::from_slice(bytes)
             .into_diagnostic()
             .wrap_err("failed to deserialize IPC MessagePack response")
     }
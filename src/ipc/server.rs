@@ -1,3 +1,4 @@
+use crate::ipc::frame::{self, FRAMES_FEATURE, LENGTH_PREFIX_FEATURE, PROTOCOL_ERROR_FEATURE};
 use crate::ipc::{IpcRequest, IpcResponse, deserialize, fs_name, serialize};
 use crate::settings::settings;
 use crate::{Result, env};
@@ -6,8 +7,10 @@ use interprocess::local_socket::tokio::{RecvHalf, SendHalf};
 use interprocess::local_socket::traits::tokio::Listener;
 use interprocess::local_socket::traits::tokio::Stream;
 use miette::{IntoDiagnostic, bail, miette};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncWriteExt, BufReader};
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::oneshot;
 
@@ -48,9 +51,67 @@ impl RateLimiter {
     }
 }
 
+/// Undecodable requests in a row after which a connection is dropped
+const MAX_PROTOCOL_ERRORS: u32 = 3;
+
+/// Protocol features a client announced in its `ConnectV2` handshake
+#[derive(Debug, Default)]
+struct PeerFeatures {
+    /// Can reassemble chunked responses
+    frames: AtomicBool,
+    /// Reads length-prefixed frames
+    length_prefix: AtomicBool,
+    /// Understands `IpcResponse::ProtocolError`
+    protocol_error: AtomicBool,
+}
+
+impl PeerFeatures {
+    fn record(&self, req: &IpcRequest) {
+        if let IpcRequest::ConnectV2 { features, .. } = req {
+            let has = |name: &str| features.iter().any(|f| f == name);
+            self.frames.store(has(FRAMES_FEATURE), Ordering::Relaxed);
+            self.length_prefix
+                .store(has(LENGTH_PREFIX_FEATURE), Ordering::Relaxed);
+            self.protocol_error
+                .store(has(PROTOCOL_ERROR_FEATURE), Ordering::Relaxed);
+        }
+    }
+
+    /// The response to a request that couldn't be decoded
+    fn protocol_error(&self, error: String) -> IpcResponse {
+        if self.protocol_error.load(Ordering::Relaxed) {
+            IpcResponse::ProtocolError { error }
+        } else {
+            IpcResponse::Error(format!("Invalid request: {error}"))
+        }
+    }
+}
+
 pub struct IpcServer {
     // clients: Mutex<HashMap<String, interprocess::local_socket::tokio::Stream>>,
-    rx: Receiver<(IpcRequest, Sender<IpcResponse>)>,
+    rx: Receiver<(IpcRequest, Sender<IpcResponse>, IpcPeer)>,
+}
+
+/// The client on the other end of an IPC connection
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpcPeer {
+    /// UID from the socket's peer credentials; `None` where unavailable
+    pub uid: Option<u32>,
+}
+
+impl IpcPeer {
+    #[cfg(unix)]
+    fn of(stream: &interprocess::local_socket::tokio::Stream) -> Self {
+        use interprocess::local_socket::traits::StreamCommon;
+        Self {
+            uid: stream.peer_creds().ok().and_then(|c| c.euid()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn of<T>(_stream: &T) -> Self {
+        Self::default()
+    }
 }
 
 /// Handle for triggering graceful shutdown of the IPC server
@@ -88,8 +149,12 @@ impl IpcServer {
         // This ensures the socket is created with 0600 permissions from the start.
         // Note: IpcServer::new() is called during supervisor startup before other async
         // tasks are spawned, so the brief umask change won't affect concurrent operations.
+        //
+        // A shared supervisor also lets its socket group connect (0660).
+        #[cfg(unix)]
+        let socket_gid = crate::supervisor::users::socket_group_id()?;
         #[cfg(unix)]
-        let old_umask = unsafe { libc::umask(0o077) };
+        let old_umask = unsafe { libc::umask(if socket_gid.is_some() { 0o117 } else { 0o077 }) };
 
         let listener_result = opts.create_tokio();
 
@@ -115,6 +180,18 @@ impl IpcServer {
                 let _ = chown_path(&env::IPC_SOCK_MAIN, uid, gid);
                 debug!("chowned IPC socket to uid={uid} gid={gid}");
             }
+            if let Some(gid) = socket_gid {
+                use std::os::unix::fs::PermissionsExt;
+                // uid -1 leaves the owner unchanged
+                chown_path(&env::IPC_SOCK_DIR, u32::MAX, gid).into_diagnostic()?;
+                chown_path(&env::IPC_SOCK_MAIN, u32::MAX, gid).into_diagnostic()?;
+                std::fs::set_permissions(
+                    &*env::IPC_SOCK_DIR,
+                    std::fs::Permissions::from_mode(0o750),
+                )
+                .into_diagnostic()?;
+                debug!("shared IPC socket with gid={gid}");
+            }
         }
 
         tokio::spawn(async move {
@@ -129,13 +206,21 @@ impl IpcServer {
                         match result {
                             Ok(stream) => {
                                 trace!("Client accepted");
+                                let peer = IpcPeer::of(&stream);
                                 let (recv, send) = stream.split();
-                                let mut incoming_chan = Self::read_messages_chan(recv);
-                                let outgoing_chan = Self::send_messages_chan(send);
+                                // Filled in by the client's handshake
+                                let features = Arc::new(PeerFeatures::default());
+                                let outgoing_chan =
+                                    Self::send_messages_chan(send, features.clone());
+                                let mut incoming_chan = Self::read_messages_chan(
+                                    recv,
+                                    features,
+                                    outgoing_chan.clone(),
+                                );
                                 let tx = tx.clone();
                                 tokio::spawn(async move {
                                     while let Some(req) = incoming_chan.recv().await {
-                                        if let Err(err) = tx.send((req, outgoing_chan.clone())).await {
+                                        if let Err(err) = tx.send((req, outgoing_chan.clone(), peer)).await {
                                             debug!("Failed to send message: {err:?}");
                                             break;
                                         }
@@ -162,27 +247,34 @@ impl IpcServer {
         Ok((server, handle))
     }
 
-    async fn send(send: &mut SendHalf, msg: IpcResponse) -> Result<()> {
-        let mut msg = serialize(&msg)?;
-        if msg.contains(&0) {
+    async fn send(send: &mut SendHalf, msg: IpcResponse, features: &PeerFeatures) -> Result<()> {
+        let msg = serialize(&msg)?;
+        let length_prefix = features.length_prefix.load(Ordering::Relaxed);
+        if !length_prefix && msg.contains(&0) {
             bail!("IPC message contains null byte");
         }
-        msg.push(0);
-        send.write_all(&msg).await.into_diagnostic()?;
-        Ok(())
-    }
-
-    /// Read raw bytes from socket until null terminator (without deserializing)
-    async fn read_raw_message(recv: &mut BufReader<RecvHalf>) -> Result<Option<Vec<u8>>> {
-        let mut bytes = Vec::new();
-        recv.read_until(0, &mut bytes).await.into_diagnostic()?;
-        if bytes.is_empty() {
-            return Ok(None);
+        let s = settings();
+        let compress_threshold = if features.frames.load(Ordering::Relaxed) {
+            usize::try_from(s.ipc.compress_threshold).unwrap_or(0)
+        } else {
+            0
+        };
+        let chunk_size = usize::try_from(s.ipc.chunk_size).unwrap_or(1024 * 1024);
+        for frame in frame::encode(msg, compress_threshold, chunk_size, length_prefix) {
+            send.write_all(&frame).await.into_diagnostic()?;
         }
-        Ok(Some(bytes))
+        Ok(())
     }
 
-    fn read_messages_chan(recv: RecvHalf) -> Receiver<IpcRequest> {
+    /// Read requests off a connection. Requests that can't be decoded are
+    /// answered here on `outgoing` and never reach the supervisor; a client
+    /// that keeps sending them, or sends a frame that can't be read at all,
+    /// is disconnected.
+    fn read_messages_chan(
+        recv: RecvHalf,
+        features: Arc<PeerFeatures>,
+        outgoing: Sender<IpcResponse>,
+    ) -> Receiver<IpcRequest> {
         let mut recv = BufReader::new(recv);
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         tokio::spawn(async move {
@@ -214,6 +306,7 @@ impl IpcServer {
                 }
             };
             let mut rate_limiter = RateLimiter::new(max_requests, window_ms);
+            let mut protocol_errors = 0;
 
             loop {
                 // Check rate limit BEFORE reading to avoid wasting CPU on deserialization
@@ -222,12 +315,20 @@ impl IpcServer {
                 let is_rate_limited = !rate_limiter.check();
 
                 // Read raw bytes from socket
-                let bytes = match Self::read_raw_message(&mut recv).await {
-                    Ok(Some(bytes)) => bytes,
+                let frame = match frame::read_frame(&mut recv, frame::MAX_REQUEST_SIZE).await {
+                    Ok(Some(frame)) => frame,
                     Ok(None) => {
                         trace!("Client disconnected");
                         break;
                     }
+                    Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+                        // The rest of the stream can't be split into frames
+                        warn!("Disconnecting IPC client: {err}");
+                        let _ = outgoing
+                            .send(features.protocol_error(err.to_string()))
+                            .await;
+                        break;
+                    }
                     Err(err) => {
                         // I/O errors are not rate-limited (they indicate connection issues)
                         debug!("Failed to read from socket: {err:?}");
@@ -242,17 +343,27 @@ impl IpcServer {
                 }
 
                 // Deserialize the message
-                let msg = match deserialize(&bytes) {
+                let msg = match deserialize(&frame.body) {
                     Ok(msg) => {
                         trace!("Received message: {msg:?}");
+                        protocol_errors = 0;
+                        features.record(&msg);
                         msg
                     }
                     Err(err) => {
-                        // Send an Invalid request so the handler can respond with an error
                         warn!("Failed to deserialize message: {err:?}");
-                        IpcRequest::Invalid {
-                            error: format!("{err:#}"),
+                        protocol_errors += 1;
+                        let rsp = features.protocol_error(format!("{err:#}"));
+                        if outgoing.send(rsp).await.is_err() {
+                            break;
                         }
+                        if protocol_errors >= MAX_PROTOCOL_ERRORS {
+                            warn!(
+                                "Disconnecting IPC client after {protocol_errors} undecodable requests in a row"
+                            );
+                            break;
+                        }
+                        continue;
                     }
                 };
 
@@ -266,7 +377,7 @@ impl IpcServer {
         rx
     }
 
-    fn send_messages_chan(mut send: SendHalf) -> Sender<IpcResponse> {
+    fn send_messages_chan(mut send: SendHalf, features: Arc<PeerFeatures>) -> Sender<IpcResponse> {
         let (tx, mut rx) = tokio::sync::mpsc::channel(1);
         tokio::spawn(async move {
             loop {
@@ -280,7 +391,11 @@ impl IpcServer {
                         break;
                     }
                 };
-                if let Err(err) = Self::send(&mut send, msg).await {
+                if let Some(delay) = crate::fault::ipc_delay() {
+                    debug!("fault injection: delaying IPC response by {delay:?}");
+                    tokio::time::sleep(delay).await;
+                }
+                if let Err(err) = Self::send(&mut send, msg, &features).await {
                     // Broken-pipe / reset is expected when a client disconnects normally
                     // Traverse the error source chain to find the original io::Error
                     // since miette wraps it in a DiagnosticError
@@ -314,7 +429,7 @@ impl IpcServer {
         tx
     }
 
-    pub async fn read(&mut self) -> Result<(IpcRequest, Sender<IpcResponse>)> {
+    pub async fn read(&mut self) -> Result<(IpcRequest, Sender<IpcResponse>, IpcPeer)> {
         self.rx
             .recv()
             .await
@@ -0,0 +1,224 @@
+//! Windows job objects for daemon process trees
+//!
+//! Windows has no `setsid` equivalent: once the shell that launched a daemon
+//! exits, the processes it started are no longer linked to the daemon PID, so
+//! `taskkill /T` misses them and they keep running (and holding ports). Each
+//! daemon is therefore spawned suspended, assigned to its own job object and
+//! only then resumed, so everything it starts belongs to the job and
+//! `TerminateJobObject` stops the whole tree at once.
+//!
+//! Jobs live only as long as the supervisor. They are created without
+//! `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`, so daemons outlive a supervisor
+//! restart as they do on Unix; daemons adopted after a restart are stopped
+//! with `taskkill /T` instead.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle, RawHandle};
+use std::sync::Mutex;
+use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, TH32CS_SNAPTHREAD, THREADENTRY32, Thread32First, Thread32Next,
+};
+use windows_sys::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JOBOBJECT_BASIC_PROCESS_ID_LIST,
+    JobObjectBasicProcessIdList, QueryInformationJobObject, TerminateJobObject,
+};
+use windows_sys::Win32::System::Threading::{
+    CREATE_SUSPENDED, OpenThread, ResumeThread, THREAD_SUSPEND_RESUME,
+};
+
+/// Creation flags for spawning a daemon that is then passed to [`adopt`]
+pub(crate) const SPAWN_FLAGS: u32 = CREATE_SUSPENDED;
+
+/// Most PIDs read back from a job by [`members`]
+const MAX_MEMBERS: usize = 1024;
+
+/// Job object per daemon PID
+static JOBS: Lazy<Mutex<HashMap<u32, OwnedHandle>>> = Lazy::new(Default::default);
+
+/// Put a process spawned with [`SPAWN_FLAGS`] into a new job object, then
+/// resume it. The process is resumed even if the job cannot be set up (e.g.
+/// the supervisor itself runs in a job that forbids nesting); it is then
+/// stopped with `taskkill /T` like before.
+pub(crate) fn adopt(pid: u32, process: RawHandle) {
+    match create_job(process) {
+        Ok(job) => {
+            JOBS.lock().unwrap().insert(pid, job);
+        }
+        Err(err) => warn!("failed to put process {pid} in a job object: {err}"),
+    }
+    if let Err(err) = resume(pid) {
+        error!("failed to resume process {pid}: {err}");
+    }
+}
+
+/// Terminate every process in the daemon's job. Returns false if the daemon
+/// has no job or it could not be terminated, so the caller falls back to
+/// `taskkill /T`.
+pub(crate) fn terminate(pid: u32) -> bool {
+    let Some(job) = JOBS.lock().unwrap().remove(&pid) else {
+        return false;
+    };
+    if unsafe { TerminateJobObject(job.as_raw_handle(), 1) } == 0 {
+        warn!(
+            "failed to terminate job of process {pid}: {}",
+            std::io::Error::last_os_error()
+        );
+        return false;
+    }
+    true
+}
+
+/// Drop the job of a daemon whose main process has exited. Like a process
+/// group on Unix, anything it left running is not stopped.
+pub(crate) fn forget(pid: u32) {
+    JOBS.lock().unwrap().remove(&pid);
+}
+
+/// PIDs of all processes in the daemon's job, including the daemon itself,
+/// or `None` if it has no job. Unlike walking parent PIDs this still finds
+/// processes whose parent has exited.
+pub(crate) fn members(pid: u32) -> Option<Vec<u32>> {
+    let jobs = JOBS.lock().unwrap();
+    let job = jobs.get(&pid)?;
+
+    // The list is a header of two u32 counts followed by usize PIDs; back it
+    // with usizes so the PIDs are aligned.
+    let header = size_of::<u32>() * 2 / size_of::<usize>();
+    let mut buf = vec![0usize; header + MAX_MEMBERS];
+    let list = buf.as_mut_ptr().cast::<JOBOBJECT_BASIC_PROCESS_ID_LIST>();
+    let ok = unsafe {
+        QueryInformationJobObject(
+            job.as_raw_handle(),
+            JobObjectBasicProcessIdList,
+            list.cast(),
+            (buf.len() * size_of::<usize>()) as u32,
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        debug!(
+            "failed to list job of process {pid}: {}",
+            std::io::Error::last_os_error()
+        );
+        return None;
+    }
+    let count = unsafe { (*list).NumberOfProcessIdsInList } as usize;
+    let ids = unsafe { std::slice::from_raw_parts((*list).ProcessIdList.as_ptr(), count) };
+    Some(ids.iter().map(|&id| id as u32).collect())
+}
+
+fn create_job(process: RawHandle) -> std::io::Result<OwnedHandle> {
+    let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+    if handle.is_null() {
+        return Err(std::io::Error::last_os_error());
+    }
+    let job = unsafe { OwnedHandle::from_raw_handle(handle) };
+    if unsafe { AssignProcessToJobObject(job.as_raw_handle(), process) } == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(job)
+}
+
+/// Resume the threads of a process spawned suspended. A new process has a
+/// single thread, but std does not expose its handle, so it is looked up.
+fn resume(pid: u32) -> std::io::Result<()> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Err(std::io::Error::last_os_error());
+    }
+    let snapshot = unsafe { OwnedHandle::from_raw_handle(snapshot) };
+
+    let mut entry: THREADENTRY32 = unsafe { std::mem::zeroed() };
+    entry.dwSize = size_of::<THREADENTRY32>() as u32;
+    let mut resumed = 0;
+    let mut more = unsafe { Thread32First(snapshot.as_raw_handle(), &mut entry) } != 0;
+    while more {
+        if entry.th32OwnerProcessID == pid {
+            let thread = unsafe { OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID) };
+            if thread.is_null() {
+                return Err(std::io::Error::last_os_error());
+            }
+            let thread = unsafe { OwnedHandle::from_raw_handle(thread) };
+            if unsafe { ResumeThread(thread.as_raw_handle()) } == u32::MAX {
+                return Err(std::io::Error::last_os_error());
+            }
+            resumed += 1;
+        }
+        more = unsafe { Thread32Next(snapshot.as_raw_handle(), &mut entry) } != 0;
+    }
+    if resumed == 0 {
+        return Err(std::io::Error::other("no threads found"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procs::PROCS;
+    use std::os::windows::process::CommandExt;
+    use std::process::{Child, Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    /// Start `cmd /c <script>` suspended and adopt it into a job
+    fn spawn_in_job(script: &str) -> Child {
+        let child = Command::new("cmd")
+            .args(["/c", script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .creation_flags(SPAWN_FLAGS | 0x08000000) // CREATE_NO_WINDOW
+            .spawn()
+            .expect("failed to spawn cmd");
+        adopt(child.id(), child.as_raw_handle());
+        child
+    }
+
+    /// Wait until the job holds a process other than `pid`
+    fn wait_for_descendant(pid: u32) -> Vec<u32> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let tree = members(pid).expect("process should have a job");
+            if tree.iter().any(|&m| m != pid) || Instant::now() > deadline {
+                return tree;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn terminate_stops_the_whole_tree() {
+        let mut child = spawn_in_job("ping -n 60 127.0.0.1 >NUL");
+        let pid = child.id();
+        let tree = wait_for_descendant(pid);
+        assert!(tree.len() >= 2, "ping did not start: {tree:?}");
+
+        assert!(terminate(pid));
+        let _ = child.wait();
+        std::thread::sleep(Duration::from_millis(200));
+        for member in tree {
+            assert!(!PROCS.is_running(member), "process {member} survived");
+        }
+        assert!(members(pid).is_none());
+    }
+
+    #[test]
+    fn members_include_processes_whose_parent_exited() {
+        let mut child = spawn_in_job("start /b ping -n 60 127.0.0.1 >NUL");
+        let pid = child.id();
+        let _ = child.wait();
+        let orphans: Vec<u32> = wait_for_descendant(pid)
+            .into_iter()
+            .filter(|&m| m != pid)
+            .collect();
+        assert!(!orphans.is_empty(), "orphaned ping not found in job");
+
+        assert!(terminate(pid));
+        std::thread::sleep(Duration::from_millis(200));
+        for orphan in orphans {
+            assert!(!PROCS.is_running(orphan), "process {orphan} survived");
+        }
+    }
+}
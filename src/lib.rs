@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate log;
 
+pub mod ansi;
 pub mod boot_manager;
 pub mod cli;
 pub mod config_types;
@@ -11,11 +12,15 @@ pub mod daemon_status;
 pub mod deps;
 pub mod env;
 pub mod error;
+pub mod fault;
 pub mod ipc;
+#[cfg(windows)]
+pub mod job_object;
 pub mod log_jq;
 pub mod log_parse;
 pub mod log_store;
 pub mod logger;
+pub mod notification_file;
 pub mod pitchfork_toml;
 pub mod procs;
 pub mod proxy;
@@ -24,6 +29,8 @@ pub mod shell;
 pub mod state_file;
 pub mod supervisor;
 pub mod template;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 pub mod tui;
 pub mod ui;
 pub mod watch_files;
@@ -43,7 +43,7 @@ impl JqFilter {
     /// this entry.
     ///
     /// The entry is serialized to a JSON object with all available fields
-    /// (timestamp, daemon_id, message, level, msg, logger, fields).
+    /// (timestamp, daemon_id, message, level, msg, logger, stream, fields).
     fn matches(&self, entry: &LogEntry) -> bool {
         let json = serialize_entry(entry);
         let input = match parse_single(json.as_bytes()) {
@@ -78,6 +78,7 @@ fn serialize_entry(entry: &LogEntry) -> String {
         "level": entry.level,
         "msg": entry.msg,
         "logger": entry.logger,
+        "stream": entry.stream,
         "fields": fields,
     });
     obj.to_string()
@@ -1,9 +1,12 @@
 //! Structured log line parsing.
 //!
 //! Parses daemon stdout/stderr lines into structured fields (level, msg,
-//! logger, fields_json) based on the configured `log_format`. Supports JSON
-//! and logfmt formats.
+//! logger, fields_json) based on the configured `log_format`. Supports JSON,
+//! logfmt and syslog-style `<N>` priority prefixes. Plain-text lines can be
+//! given a level with a per-daemon `log_level_regex`.
 
+use crate::log_store::LogStream;
+use regex::Regex;
 use serde_json::{Map, Value};
 
 /// Result of parsing a single log line.
@@ -25,6 +28,8 @@ pub struct ParsedLog {
     /// `None` for plain-text or logfmt lines (logfmt fields are also stored
     /// here as a JSON object string).
     pub fields_json: Option<String>,
+    /// The output stream the line was read from; set by the caller.
+    pub stream: LogStream,
 }
 
 impl ParsedLog {
@@ -44,7 +49,7 @@ const MAX_PARSE_LINE_LEN: usize = 65536;
 
 /// Parse a log line according to the given format string.
 ///
-/// Format values: `"json"`, `"logfmt"`, `"text"` (or any other
+/// Format values: `"json"`, `"logfmt"`, `"syslog"`, `"text"` (or any other
 /// value is treated as text). Parse failures fall back to plain text.
 pub fn parse(line: &str, format: &str) -> ParsedLog {
     if line.len() > MAX_PARSE_LINE_LEN {
@@ -53,10 +58,30 @@ pub fn parse(line: &str, format: &str) -> ParsedLog {
     match format {
         "json" => parse_json(line).unwrap_or_else(|| ParsedLog::plain(line)),
         "logfmt" => parse_logfmt(line).unwrap_or_else(|| ParsedLog::plain(line)),
+        "syslog" => parse_syslog(line).unwrap_or_else(|| ParsedLog::plain(line)),
         _ => ParsedLog::plain(line),
     }
 }
 
+/// Level of a line according to a daemon's `log_level_regex`.
+///
+/// The level is taken from the `level` capture group, or the first group if
+/// there is no group by that name, and normalized like structured levels.
+/// Single letters (`E`, `W`, `I`, `D`, `T`, `F`) as printed by glog and
+/// Android are accepted too.
+pub fn level_from_regex(re: &Regex, line: &str) -> Option<String> {
+    let caps = re.captures(line)?;
+    let raw = caps.name("level").or_else(|| caps.get(1))?.as_str();
+    normalize_level_str(raw).or_else(|| match raw {
+        "E" | "F" => Some("error".into()),
+        "W" => Some("warn".into()),
+        "I" => Some("info".into()),
+        "D" => Some("debug".into()),
+        "T" | "V" => Some("trace".into()),
+        _ => None,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // JSON parsing
 // ---------------------------------------------------------------------------
@@ -79,6 +104,7 @@ fn parse_json(line: &str) -> Option<ParsedLog> {
         msg,
         logger,
         fields_json: Some(fields_json),
+        ..Default::default()
     })
 }
 
@@ -126,6 +152,32 @@ fn parse_logfmt(line: &str) -> Option<ParsedLog> {
         msg,
         logger,
         fields_json: Some(fields_json),
+        ..Default::default()
+    })
+}
+
+// ---------------------------------------------------------------------------
+// syslog parsing
+// ---------------------------------------------------------------------------
+
+/// Parse a `<N>` priority prefix, as written by daemons logging for syslog or
+/// systemd (`sd-daemon(3)`). The severity is the priority modulo 8; an RFC 5424
+/// version number after the prefix is skipped.
+fn parse_syslog(line: &str) -> Option<ParsedLog> {
+    let rest = line.strip_prefix('<')?;
+    let (pri, rest) = rest.split_once('>')?;
+    if pri.is_empty() || pri.len() > 3 {
+        return None;
+    }
+    let pri: u8 = pri.parse().ok().filter(|p| *p <= 191)?;
+    let level = normalize_level_value(&Value::from(pri % 8));
+    let msg = rest.strip_prefix("1 ").unwrap_or(rest).trim_start();
+
+    Some(ParsedLog {
+        message: line.to_string(),
+        level,
+        msg: Some(msg.to_string()),
+        ..Default::default()
     })
 }
 
@@ -408,4 +460,42 @@ mod tests {
         assert_eq!(parsed.level.as_deref(), Some("info"));
         assert_eq!(parsed.msg, None); // top-level has no msg/message/event
     }
+
+    #[test]
+    fn test_syslog_priority() {
+        let parsed = parse("<3>disk failure", "syslog");
+        assert_eq!(parsed.level.as_deref(), Some("error"));
+        assert_eq!(parsed.msg.as_deref(), Some("disk failure"));
+
+        // facility 4 (auth), severity 6 (info), RFC 5424 version
+        let parsed = parse("<38>1 2024-01-01T00:00:00Z host app - - - hi", "syslog");
+        assert_eq!(parsed.level.as_deref(), Some("info"));
+
+        for line in ["no prefix", "<>empty", "<999>too big", "<abc>nan"] {
+            let parsed = parse(line, "syslog");
+            assert!(parsed.level.is_none(), "{line}");
+            assert_eq!(parsed.message, line);
+        }
+    }
+
+    #[test]
+    fn test_level_from_regex() {
+        let re = Regex::new(r"^\[(?P<level>\w+)\]").unwrap();
+        assert_eq!(
+            level_from_regex(&re, "[WARNING] disk 90% full").as_deref(),
+            Some("warn")
+        );
+        // A URL mentioning "error" is not an error
+        assert_eq!(
+            level_from_regex(&re, "[INFO] GET /error-page 200").as_deref(),
+            Some("info")
+        );
+        assert_eq!(level_from_regex(&re, "no level here"), None);
+
+        let glog = Regex::new(r"^([EWIF])\d{4}").unwrap();
+        assert_eq!(
+            level_from_regex(&glog, "E0102 12:00:00 main.go:1] boom").as_deref(),
+            Some("error")
+        );
+    }
 }
@@ -0,0 +1,220 @@
+//! Degraded logging while the disk holding the log store is full.
+//!
+//! Without this, every batch written while the disk is full fails and logs
+//! an error, flooding the supervisor log. Instead, the first "disk full"
+//! failure switches logging into degraded mode: batches are held in memory
+//! (bounded per daemon) and a single warning is logged. Writes are retried at
+//! most every [`PROBE_INTERVAL`]; the first one that succeeds writes the held
+//! lines back, in order, and leaves degraded mode.
+
+use crate::Result;
+use crate::daemon_id::DaemonId;
+use crate::log_parse::ParsedLog;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub static DISK_FULL: Lazy<DiskFullGuard> = Lazy::new(DiskFullGuard::default);
+
+/// How often a write is attempted while degraded
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Lines held per daemon while degraded; older lines are dropped beyond this.
+const HELD_LINES_PER_DAEMON: usize = 10_000;
+
+/// Whether `err` means the disk holding the log store is full: SQLite's
+/// `SQLITE_FULL` or `ENOSPC` from the filesystem.
+pub fn is_disk_full(err: &miette::Report) -> bool {
+    err.chain().any(|e| {
+        if let Some(e) = e.downcast_ref::<rusqlite::Error>() {
+            return e.sqlite_error_code() == Some(rusqlite::ErrorCode::DiskFull);
+        }
+        if let Some(e) = e.downcast_ref::<std::io::Error>() {
+            return e.kind() == std::io::ErrorKind::StorageFull;
+        }
+        // `into_diagnostic` hides the source error's type, so fall back to
+        // the messages SQLite and the OS use.
+        let msg = e.to_string();
+        msg.contains("database or disk is full") || msg.contains("No space left on device")
+    })
+}
+
+/// A change in logging mode, to be reported to the user.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Transition {
+    Degraded,
+    Recovered { written: usize, dropped: usize },
+}
+
+impl std::fmt::Display for Transition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transition::Degraded => write!(
+                f,
+                "log disk is full: daemon output is kept in memory until space frees up"
+            ),
+            Transition::Recovered {
+                written,
+                dropped: 0,
+            } => write!(
+                f,
+                "log disk has space again: logging resumed ({written} held lines written)"
+            ),
+            Transition::Recovered { written, dropped } => write!(
+                f,
+                "log disk has space again: logging resumed ({written} held lines written, {dropped} lost)"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DiskFullGuard {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When the last write was attempted while degraded; `None` when healthy.
+    last_probe: Option<Instant>,
+    held: HashMap<DaemonId, VecDeque<ParsedLog>>,
+    dropped: usize,
+}
+
+impl State {
+    fn hold(&mut self, daemon_id: &DaemonId, batch: impl IntoIterator<Item = ParsedLog>) {
+        let held = self.held.entry(daemon_id.clone()).or_default();
+        for line in batch {
+            if held.len() >= HELD_LINES_PER_DAEMON {
+                held.pop_front();
+                self.dropped += 1;
+            }
+            held.push_back(line);
+        }
+    }
+}
+
+impl DiskFullGuard {
+    /// Write `batch` for `daemon_id` with `write`, or hold it in memory while
+    /// the disk is full. Returns the transition when the logging mode changes.
+    pub fn append(
+        &self,
+        daemon_id: &DaemonId,
+        batch: Vec<ParsedLog>,
+        mut write: impl FnMut(&DaemonId, &[ParsedLog]) -> Result<()>,
+    ) -> Option<Transition> {
+        let mut state = self.state.lock().unwrap();
+        let Some(last_probe) = state.last_probe else {
+            return match write(daemon_id, &batch) {
+                Ok(()) => None,
+                Err(e) if is_disk_full(&e) => {
+                    warn!("log disk is full, holding daemon output in memory until space frees up");
+                    state.hold(daemon_id, batch);
+                    state.last_probe = Some(Instant::now());
+                    Some(Transition::Degraded)
+                }
+                Err(e) => {
+                    error!("Failed to write batch to log for daemon {daemon_id}: {e}");
+                    None
+                }
+            };
+        };
+
+        state.hold(daemon_id, batch);
+        if last_probe.elapsed() < PROBE_INTERVAL {
+            return None;
+        }
+        state.last_probe = Some(Instant::now());
+
+        let mut written = 0;
+        let ids: Vec<DaemonId> = state.held.keys().cloned().collect();
+        for id in ids {
+            let mut lines = state.held.remove(&id).unwrap_or_default();
+            match write(&id, lines.make_contiguous()) {
+                Ok(()) => written += lines.len(),
+                Err(e) if is_disk_full(&e) => {
+                    state.held.insert(id, lines);
+                    return None;
+                }
+                Err(e) => error!("Failed to write batch to log for daemon {id}: {e}"),
+            }
+        }
+
+        state.last_probe = None;
+        let dropped = std::mem::take(&mut state.dropped);
+        info!("log disk has space again, wrote {written} held log lines");
+        Some(Transition::Recovered { written, dropped })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(msgs: &[&str]) -> Vec<ParsedLog> {
+        msgs.iter()
+            .map(|m| ParsedLog {
+                message: m.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn disk_full() -> miette::Report {
+        miette::miette!("database or disk is full")
+    }
+
+    #[test]
+    fn test_is_disk_full() {
+        assert!(is_disk_full(&disk_full()));
+        #[cfg(unix)]
+        {
+            let enospc = std::io::Error::from_raw_os_error(libc::ENOSPC);
+            assert!(is_disk_full(&miette::miette!("{enospc}")));
+        }
+        assert!(!is_disk_full(&miette::miette!("database is locked")));
+    }
+
+    #[test]
+    fn test_degrades_once_and_recovers_in_order() {
+        let guard = DiskFullGuard::default();
+        let id = DaemonId::new("proj", "api");
+        let mut stored: Vec<String> = vec![];
+
+        let full = |_: &DaemonId, _: &[ParsedLog]| -> Result<()> { Err(disk_full()) };
+        assert_eq!(
+            guard.append(&id, lines(&["a"]), full),
+            Some(Transition::Degraded)
+        );
+        // Subsequent failures don't report again
+        assert_eq!(guard.append(&id, lines(&["b"]), full), None);
+
+        // Pretend the probe interval has passed
+        guard.state.lock().unwrap().last_probe = Some(Instant::now() - PROBE_INTERVAL);
+        let transition = guard.append(&id, lines(&["c"]), |_, batch| {
+            stored.extend(batch.iter().map(|l| l.message.clone()));
+            Ok(())
+        });
+        assert_eq!(
+            transition,
+            Some(Transition::Recovered {
+                written: 3,
+                dropped: 0
+            })
+        );
+        assert_eq!(stored, vec!["a", "b", "c"]);
+        assert!(guard.state.lock().unwrap().held.is_empty());
+    }
+
+    #[test]
+    fn test_other_errors_do_not_degrade() {
+        let guard = DiskFullGuard::default();
+        let id = DaemonId::new("proj", "api");
+        let locked = |_: &DaemonId, _: &[ParsedLog]| -> Result<()> {
+            Err(miette::miette!("database is locked"))
+        };
+        assert_eq!(guard.append(&id, lines(&["a"]), locked), None);
+        assert!(guard.state.lock().unwrap().last_probe.is_none());
+    }
+}
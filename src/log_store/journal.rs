@@ -0,0 +1,173 @@
+//! systemd journal sink for daemon output.
+//!
+//! `logs.sink` chooses where the supervisor writes daemon output: the SQLite
+//! log store (`sqlite`, the default), the systemd journal (`journal`), or
+//! both. Journal entries are sent over the journal's native protocol and
+//! tagged with `SYSLOG_IDENTIFIER=<daemon id>`, so
+//! `journalctl -t myproject/api` shows a single daemon.
+//!
+//! The journal is write-only from pitchfork's side: `pitchfork logs`, the TUI
+//! and the web UI read the SQLite store, so with `journal` alone they only see
+//! the supervisor's in-memory recent lines. The journal is only available on
+//! Linux; elsewhere `journal` falls back to `sqlite`.
+
+use crate::Result;
+use crate::daemon_id::DaemonId;
+use crate::log_parse::ParsedLog;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SINK: Lazy<LogSink> = Lazy::new(LogSink::from_settings);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogSink {
+    Sqlite,
+    Journal,
+    Both,
+}
+
+impl LogSink {
+    /// The sink configured by `logs.sink`.
+    pub fn get() -> Self {
+        *SINK
+    }
+
+    fn from_settings() -> Self {
+        let sink = crate::settings::settings().logs.sink.clone();
+        let parsed = match sink.as_str() {
+            "journal" => LogSink::Journal,
+            "both" => LogSink::Both,
+            "sqlite" => LogSink::Sqlite,
+            other => {
+                warn!("unknown logs.sink {other:?}, expected sqlite, journal or both");
+                LogSink::Sqlite
+            }
+        };
+        if parsed != LogSink::Sqlite && !cfg!(target_os = "linux") {
+            warn!("logs.sink = {sink:?} needs the systemd journal (Linux only), using sqlite");
+            return LogSink::Sqlite;
+        }
+        parsed
+    }
+
+    pub fn sqlite(self) -> bool {
+        matches!(self, LogSink::Sqlite | LogSink::Both)
+    }
+
+    pub fn journal(self) -> bool {
+        matches!(self, LogSink::Journal | LogSink::Both)
+    }
+}
+
+/// Send a batch of output lines for `daemon_id` to the journal. Failures are
+/// warned about once and otherwise only logged at debug level, so a missing
+/// journal doesn't flood the supervisor log.
+pub fn send_batch(daemon_id: &DaemonId, entries: &[ParsedLog]) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if let Err(e) = try_send_batch(daemon_id, entries) {
+        if WARNED.swap(true, Ordering::Relaxed) {
+            debug!("failed to send logs for daemon {daemon_id} to the journal: {e}");
+        } else {
+            warn!("failed to send logs for daemon {daemon_id} to the systemd journal: {e}");
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn try_send_batch(daemon_id: &DaemonId, entries: &[ParsedLog]) -> Result<()> {
+    use miette::IntoDiagnostic;
+    use std::os::unix::net::UnixDatagram;
+
+    const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+    static SOCKET: Lazy<std::io::Result<UnixDatagram>> = Lazy::new(UnixDatagram::unbound);
+
+    let socket = SOCKET
+        .as_ref()
+        .map_err(|e| miette::miette!("failed to create journal socket: {e}"))?;
+    for entry in entries {
+        socket
+            .send_to(&encode(daemon_id, entry), JOURNAL_SOCKET)
+            .into_diagnostic()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn try_send_batch(_daemon_id: &DaemonId, _entries: &[ParsedLog]) -> Result<()> {
+    Ok(())
+}
+
+/// Longest message sent in one datagram; longer lines are truncated. Larger
+/// entries would need to be passed through a memfd.
+#[cfg(any(target_os = "linux", test))]
+const MAX_MESSAGE_LEN: usize = 48 * 1024;
+
+/// Encode one entry in the journal's native datagram format.
+#[cfg(any(target_os = "linux", test))]
+fn encode(daemon_id: &DaemonId, entry: &ParsedLog) -> Vec<u8> {
+    let mut message = entry.message.as_str();
+    if message.len() > MAX_MESSAGE_LEN {
+        let mut end = MAX_MESSAGE_LEN;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message = &message[..end];
+    }
+    let priority = match entry.level.as_deref() {
+        Some("error") => "3",
+        Some("warn") => "4",
+        Some("debug") | Some("trace") => "7",
+        _ => "6",
+    };
+    let mut buf = Vec::with_capacity(message.len() + 128);
+    push_field(&mut buf, "MESSAGE", message);
+    push_field(&mut buf, "PRIORITY", priority);
+    push_field(&mut buf, "SYSLOG_IDENTIFIER", &daemon_id.qualified());
+    push_field(&mut buf, "PITCHFORK_NAMESPACE", daemon_id.namespace());
+    push_field(&mut buf, "PITCHFORK_DAEMON", daemon_id.name());
+    buf
+}
+
+/// Values containing a newline use the length-prefixed binary form.
+#[cfg(any(target_os = "linux", test))]
+fn push_field(buf: &mut Vec<u8>, name: &str, value: &str) {
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let id = DaemonId::new("proj", "api");
+        let entry = ParsedLog {
+            message: "disk almost full".to_string(),
+            level: Some("warn".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            String::from_utf8(encode(&id, &entry)).unwrap(),
+            "MESSAGE=disk almost full\nPRIORITY=4\nSYSLOG_IDENTIFIER=proj/api\n\
+             PITCHFORK_NAMESPACE=proj\nPITCHFORK_DAEMON=api\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_multiline_value() {
+        let mut buf = vec![];
+        push_field(&mut buf, "MESSAGE", "a\nb");
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(buf, expected);
+    }
+}
@@ -20,6 +20,27 @@ pub struct LogEntry {
     /// The full parsed JSON object as a string, for `json_extract` queries.
     /// `None` for plain-text lines that were not parsed.
     pub fields_json: Option<String>,
+    /// The output stream the line was read from.
+    pub stream: LogStream,
+}
+
+/// Output stream a log line was read from. With `pty = true` both streams
+/// arrive merged through the terminal and are recorded as stdout.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, strum::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum LogStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    pub fn is_stdout(&self) -> bool {
+        *self == LogStream::Stdout
+    }
 }
 
 /// A filter applied to the message text of log entries.
@@ -44,6 +65,8 @@ pub enum FieldFilter {
     LevelMin(String),
     /// Match entries where `json_extract(fields_json, '$.key') = value`.
     FieldEq { key: String, value: String },
+    /// Match entries read from one output stream.
+    Stream(LogStream),
 }
 
 /// Levels at or above the given threshold, ordered low→high.
@@ -59,7 +82,6 @@ pub fn levels_at_or_above(min: &str) -> Vec<&'static str> {
 }
 
 impl MessageFilter {
-    #[allow(dead_code)]
     pub fn contains(pattern: impl Into<String>) -> Self {
         Self::Contains {
             pattern: pattern.into(),
@@ -90,6 +112,10 @@ pub struct LogQuery {
     pub from: Option<DateTime<Local>>,
     pub to: Option<DateTime<Local>>,
     pub limit: Option<usize>,
+    /// Stop once the returned messages add up to this many bytes, counting a
+    /// newline per line. Only whole lines are returned. Rows are read lazily
+    /// in query order, so with `order_desc` only the newest rows are read.
+    pub max_bytes: Option<usize>,
     pub order_desc: bool,
     pub after_id: Option<i64>,
     /// Filters applied to the message text. Multiple filters are combined with OR.
@@ -223,6 +249,7 @@ pub trait LogStore: Send + Sync {
             from: None,
             to: None,
             limit: Some(1),
+            max_bytes: None,
             order_desc: true,
             after_id: None,
             message_filters: Vec::new(),
@@ -244,4 +271,7 @@ pub trait LogStore: Send + Sync {
     }
 }
 
+pub mod disk_full;
+pub mod journal;
+pub mod recent;
 pub mod sqlite;
@@ -0,0 +1,230 @@
+//! In-memory ring buffer of the most recent log lines per daemon.
+//!
+//! Lives in the supervisor process and is filled by the daemon monitoring
+//! task as output batches are flushed (stamped with the time each line was
+//! read), so recent output can be served over
+//! IPC (`IpcRequest::GetRecentLogs`) without clients reading the SQLite log
+//! store themselves.
+
+use crate::Result;
+use crate::daemon_id::DaemonId;
+use crate::log_parse::ParsedLog;
+use crate::log_store::sqlite::LOG_STORE;
+use crate::log_store::{LogEntry, LogStore, LogStream};
+use chrono::{DateTime, Local};
+use miette::IntoDiagnostic;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+pub static RECENT_LOGS: Lazy<RecentLogs> = Lazy::new(RecentLogs::default);
+
+/// A captured log line as returned by `GetRecentLogs`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecentLogLine {
+    pub timestamp: DateTime<Local>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub msg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub logger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fields_json: Option<String>,
+    #[serde(skip_serializing_if = "LogStream::is_stdout", default)]
+    pub stream: LogStream,
+}
+
+impl RecentLogLine {
+    /// Convert into a [`LogEntry`] for the shared log formatting code.
+    /// The line never went through SQLite, so it has no row id.
+    pub fn into_log_entry(self, daemon_id: &DaemonId) -> LogEntry {
+        LogEntry {
+            id: 0,
+            daemon_id: daemon_id.qualified(),
+            timestamp: self.timestamp,
+            message: self.message,
+            level: self.level,
+            msg: self.msg,
+            logger: self.logger,
+            fields_json: self.fields_json,
+            stream: self.stream,
+        }
+    }
+}
+
+/// Up to `lines` of the most recent lines the supervisor holds for `id`,
+/// oldest first, less anything `pitchfork logs --clear` removed since.
+/// Only the supervisor process fills the buffer.
+pub async fn recent_lines(id: &DaemonId, lines: usize) -> Result<Vec<RecentLogLine>> {
+    let generation = {
+        let id = id.clone();
+        tokio::task::spawn_blocking(move || LOG_STORE.last_clear_generation(&id))
+            .await
+            .into_diagnostic()??
+            .unwrap_or(0)
+    };
+    Ok(RECENT_LOGS.recent(id, lines, generation))
+}
+
+#[derive(Debug, Default)]
+struct Buffer {
+    /// Each line with the log store's clear generation when it was pushed,
+    /// so `pitchfork logs --clear` (which writes SQLite directly) also
+    /// empties the in-memory copy
+    lines: VecDeque<(u64, RecentLogLine)>,
+}
+
+#[derive(Debug, Default)]
+pub struct RecentLogs {
+    buffers: Mutex<HashMap<DaemonId, Buffer>>,
+}
+
+impl RecentLogs {
+    /// Append a batch of lines read at the given times, evicting the oldest
+    /// beyond `capacity`. `clear_generation` is the log store's current
+    /// clear generation for the daemon. A capacity of 0 disables the buffer.
+    pub fn push_batch(
+        &self,
+        daemon_id: &DaemonId,
+        entries: &[(DateTime<Local>, ParsedLog)],
+        clear_generation: u64,
+        capacity: usize,
+    ) {
+        if capacity == 0 || entries.is_empty() {
+            return;
+        }
+        let mut buffers = self.buffers.lock().unwrap();
+        let buffer = buffers.entry(daemon_id.clone()).or_default();
+        for (timestamp, entry) in entries {
+            if buffer.lines.len() >= capacity {
+                buffer.lines.pop_front();
+            }
+            let line = RecentLogLine {
+                timestamp: *timestamp,
+                message: entry.message.clone(),
+                level: entry.level.clone(),
+                msg: entry.msg.clone(),
+                logger: entry.logger.clone(),
+                fields_json: entry.fields_json.clone(),
+                stream: entry.stream,
+            };
+            // Batches are flushed concurrently, so one may arrive after a
+            // later one; keep the lines in the order they were read
+            let at = buffer
+                .lines
+                .partition_point(|(_, l)| l.timestamp <= line.timestamp);
+            buffer.lines.insert(at, (clear_generation, line));
+        }
+        // Capacity may have been lowered by a settings reload.
+        while buffer.lines.len() > capacity {
+            buffer.lines.pop_front();
+        }
+    }
+
+    /// Return up to `lines` of the most recent lines, oldest first.
+    ///
+    /// `clear_generation` is the log store's current clear generation for
+    /// the daemon; lines pushed under an older one were cleared since and
+    /// are dropped.
+    pub fn recent(
+        &self,
+        daemon_id: &DaemonId,
+        lines: usize,
+        clear_generation: u64,
+    ) -> Vec<RecentLogLine> {
+        let mut buffers = self.buffers.lock().unwrap();
+        let Some(buffer) = buffers.get_mut(daemon_id) else {
+            return vec![];
+        };
+        buffer
+            .lines
+            .retain(|(generation, _)| *generation >= clear_generation);
+        let skip = buffer.lines.len().saturating_sub(lines);
+        buffer
+            .lines
+            .iter()
+            .skip(skip)
+            .map(|(_, line)| line.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(n: usize) -> Vec<(DateTime<Local>, ParsedLog)> {
+        (0..n)
+            .map(|i| {
+                let line = ParsedLog {
+                    message: format!("line {i}"),
+                    level: None,
+                    msg: None,
+                    logger: None,
+                    fields_json: None,
+                    stream: LogStream::Stdout,
+                };
+                (Local::now(), line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ring_evicts_oldest() {
+        let ring = RecentLogs::default();
+        let id = DaemonId::new("proj", "api");
+        ring.push_batch(&id, &lines(5), 0, 3);
+        let recent = ring.recent(&id, 10, 0);
+        let msgs: Vec<_> = recent.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(msgs, vec!["line 2", "line 3", "line 4"]);
+    }
+
+    #[test]
+    fn test_recent_returns_last_n() {
+        let ring = RecentLogs::default();
+        let id = DaemonId::new("proj", "api");
+        ring.push_batch(&id, &lines(5), 0, 100);
+        let recent = ring.recent(&id, 2, 0);
+        let msgs: Vec<_> = recent.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(msgs, vec!["line 3", "line 4"]);
+    }
+
+    #[test]
+    fn test_clear_generation_resets_buffer() {
+        let ring = RecentLogs::default();
+        let id = DaemonId::new("proj", "api");
+        ring.push_batch(&id, &lines(3), 4, 100);
+        // Cleared before anything read them
+        assert!(ring.recent(&id, 10, 5).is_empty());
+        ring.push_batch(&id, &lines(1), 5, 100);
+        assert_eq!(ring.recent(&id, 10, 5).len(), 1);
+        ring.push_batch(&id, &lines(2), 5, 100);
+        assert_eq!(ring.recent(&id, 10, 5).len(), 3);
+        assert!(ring.recent(&id, 10, 6).is_empty());
+    }
+
+    #[test]
+    fn test_late_batch_keeps_read_order() {
+        let ring = RecentLogs::default();
+        let id = DaemonId::new("proj", "api");
+        let mut batch = lines(4);
+        for (i, (timestamp, _)) in batch.iter_mut().enumerate() {
+            *timestamp += chrono::Duration::seconds(i as i64);
+        }
+        ring.push_batch(&id, &batch[2..], 0, 100);
+        ring.push_batch(&id, &batch[..2], 0, 100);
+        let recent = ring.recent(&id, 10, 0);
+        let msgs: Vec<_> = recent.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(msgs, vec!["line 0", "line 1", "line 2", "line 3"]);
+    }
+
+    #[test]
+    fn test_zero_capacity_disables() {
+        let ring = RecentLogs::default();
+        let id = DaemonId::new("proj", "api");
+        ring.push_batch(&id, &lines(3), 0, 0);
+        assert!(ring.recent(&id, 10, 0).is_empty());
+    }
+}
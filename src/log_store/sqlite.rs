@@ -2,7 +2,8 @@ use crate::Result;
 use crate::daemon_id::DaemonId;
 use crate::log_parse::ParsedLog;
 use crate::log_store::{
-    ArchiveHook, FieldFilter, LogEntry, LogQuery, LogStore, MessageFilter, escape_like_pattern,
+    ArchiveHook, FieldFilter, LogEntry, LogQuery, LogStore, LogStream, MessageFilter,
+    escape_like_pattern,
 };
 use chrono::{DateTime, Local, TimeZone};
 use log::error;
@@ -90,7 +91,8 @@ impl SqliteLogStore {
                 level       TEXT,
                 msg         TEXT,
                 logger      TEXT,
-                fields_json TEXT
+                fields_json TEXT,
+                stream      TEXT
             );",
             [],
         )
@@ -122,7 +124,7 @@ impl SqliteLogStore {
                 .into_diagnostic()?;
             rows.filter_map(|r| r.ok()).collect()
         };
-        for col in ["level", "msg", "logger", "fields_json"] {
+        for col in ["level", "msg", "logger", "fields_json", "stream"] {
             if !existing_cols.iter().any(|c| c == col) {
                 conn.execute(
                     &format!("ALTER TABLE log_entries ADD COLUMN {col} TEXT"),
@@ -136,6 +138,12 @@ impl SqliteLogStore {
             [],
         )
         .into_diagnostic()?;
+        // Level searches across every daemon, e.g. the web UI's log search
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_level_ts ON log_entries(level, timestamp);",
+            [],
+        )
+        .into_diagnostic()?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS log_clear_generations (
@@ -160,6 +168,7 @@ impl SqliteLogStore {
         let msg: Option<String> = row.get(5)?;
         let logger: Option<String> = row.get(6)?;
         let fields_json: Option<String> = row.get(7)?;
+        let stream: Option<String> = row.get(8)?;
         let timestamp = Local
             .timestamp_millis_opt(ts_millis)
             .single()
@@ -173,9 +182,22 @@ impl SqliteLogStore {
             msg,
             logger,
             fields_json,
+            stream: match stream.as_deref() {
+                Some("stderr") => LogStream::Stderr,
+                _ => LogStream::Stdout,
+            },
         })
     }
 
+    /// Value of the `stream` column; stdout is stored as NULL so rows
+    /// written before the column existed read back as stdout.
+    fn stream_column(stream: LogStream) -> Option<&'static str> {
+        match stream {
+            LogStream::Stdout => None,
+            LogStream::Stderr => Some("stderr"),
+        }
+    }
+
     fn archive_entries(
         &self,
         entries: &[LogEntry],
@@ -306,7 +328,7 @@ impl SqliteLogStore {
                     let conn = self.conn.lock().unwrap();
                     let mut stmt = conn
                         .prepare(
-                            "SELECT id, daemon_id, timestamp, message, level, msg, logger, fields_json FROM log_entries
+                            "SELECT id, daemon_id, timestamp, message, level, msg, logger, fields_json, stream FROM log_entries
                              WHERE daemon_id = ?1 AND timestamp < ?2
                              ORDER BY timestamp ASC, id ASC
                              LIMIT ?3",
@@ -390,7 +412,7 @@ impl SqliteLogStore {
                     let conn = self.conn.lock().unwrap();
                     let mut stmt = conn
                         .prepare(
-                            "SELECT id, daemon_id, timestamp, message, level, msg, logger, fields_json FROM log_entries
+                            "SELECT id, daemon_id, timestamp, message, level, msg, logger, fields_json, stream FROM log_entries
                              WHERE daemon_id = ?1
                              ORDER BY timestamp ASC, id ASC
                              LIMIT ?2",
@@ -436,6 +458,71 @@ impl SqliteLogStore {
         }
     }
 
+    /// Move a daemon's log entries to a new ID, after it was renamed, along
+    /// with its log directory if it has one. Fails without moving anything if
+    /// the new ID already has logs. Streams showing it are told to refresh.
+    pub fn rename_daemon(&self, from: &DaemonId, to: &DaemonId) -> Result<()> {
+        let (from_safe, to_safe) = (from.safe_path(), to.safe_path());
+        let from_dir = crate::env::PITCHFORK_LOGS_DIR.join(&from_safe);
+        let to_dir = crate::env::PITCHFORK_LOGS_DIR.join(&to_safe);
+        let conflict = || {
+            miette::miette!(
+                "daemon {to} already has logs; clear them with `pitchfork logs {to} --clear`"
+            )
+        };
+        if to_dir.exists() {
+            return Err(conflict());
+        }
+        {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction().into_diagnostic()?;
+            let to_has_logs: bool = tx
+                .query_row(
+                    "SELECT EXISTS(SELECT 1 FROM log_entries WHERE daemon_id = ?1)",
+                    params![to.qualified()],
+                    |row| row.get(0),
+                )
+                .into_diagnostic()?;
+            if to_has_logs {
+                return Err(conflict());
+            }
+            tx.execute(
+                "UPDATE log_entries SET daemon_id = ?2 WHERE daemon_id = ?1",
+                params![from.qualified(), to.qualified()],
+            )
+            .into_diagnostic()?;
+            tx.execute(
+                "DELETE FROM log_clear_generations WHERE daemon_id = ?1",
+                params![from.qualified()],
+            )
+            .into_diagnostic()?;
+            tx.execute(
+                "INSERT INTO log_clear_generations (daemon_id, generation)
+                 VALUES (?1, 1)
+                 ON CONFLICT(daemon_id) DO UPDATE SET generation = generation + 1",
+                params![to.qualified()],
+            )
+            .into_diagnostic()?;
+            tx.commit().into_diagnostic()?;
+        }
+
+        if !from_dir.is_dir() {
+            return Ok(());
+        }
+        std::fs::rename(&from_dir, &to_dir).into_diagnostic()?;
+        // Files inside are named after the directory, e.g. `<id>.log`
+        for path in xx::file::ls(&to_dir)? {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if let Some(rest) = name.strip_prefix(&from_safe) {
+                std::fs::rename(&path, to_dir.join(format!("{to_safe}{rest}")))
+                    .into_diagnostic()?;
+            }
+        }
+        Ok(())
+    }
+
     /// Migrate existing text logs for a daemon into SQLite.
     ///
     /// Reads the legacy text file line-by-line (streaming) and inserts in
@@ -628,6 +715,13 @@ impl SqliteLogStore {
                     ));
                     query_params.push(Box::new(value.clone()));
                 }
+                FieldFilter::Stream(stream) => match Self::stream_column(*stream) {
+                    Some(value) => {
+                        conditions.push(format!("stream = ?{}", query_params.len() + 1));
+                        query_params.push(Box::new(value.to_string()));
+                    }
+                    None => conditions.push("stream IS NULL".to_string()),
+                },
             }
         }
 
@@ -645,9 +739,9 @@ impl SqliteLogStore {
             .unwrap_or_default();
 
         let columns = if opts.include_structured {
-            "id, daemon_id, timestamp, message, level, msg, logger, fields_json"
+            "id, daemon_id, timestamp, message, level, msg, logger, fields_json, stream"
         } else {
-            "id, daemon_id, timestamp, message, NULL, NULL, NULL, NULL"
+            "id, daemon_id, timestamp, message, NULL, NULL, NULL, NULL, stream"
         };
 
         let sql = format!(
@@ -668,6 +762,11 @@ impl SqliteLogStore {
         if opts.after_id.is_some() {
             return false;
         }
+        // A byte budget is met by reading rows in order until it runs out,
+        // which sharding would defeat.
+        if opts.max_bytes.is_some() {
+            return false;
+        }
         let limit = opts.limit.unwrap_or(usize::MAX);
         if limit < PARALLEL_QUERY_THRESHOLD {
             return false;
@@ -745,7 +844,7 @@ impl SqliteLogStore {
                         }
 
                         let (sql, query_params) = Self::build_query_sql(&opts, Some((start, end)));
-                        Self::execute_built_query(&conn, &sql, &query_params)
+                        Self::execute_built_query(&conn, &sql, &query_params, None)
                     })
                 })
                 .map(|h| h.join().unwrap())
@@ -773,10 +872,14 @@ impl SqliteLogStore {
     }
 
     /// Execute a built SQL query and collect results into LogEntry.
+    ///
+    /// With `max_bytes`, rows stop being read once the next message would
+    /// exceed the budget (see [`LogQuery::max_bytes`]).
     fn execute_built_query(
         conn: &Connection,
         sql: &str,
         query_params: &[Box<dyn rusqlite::ToSql>],
+        max_bytes: Option<usize>,
     ) -> Result<Vec<LogEntry>> {
         let mut stmt = conn.prepare(sql).into_diagnostic()?;
         let params_ref: Vec<&dyn rusqlite::ToSql> =
@@ -785,8 +888,15 @@ impl SqliteLogStore {
             .query_map(params_ref.as_slice(), Self::row_to_entry)
             .into_diagnostic()?;
         let mut entries = Vec::new();
+        let mut remaining = max_bytes.unwrap_or(usize::MAX);
         for row in rows {
-            entries.push(row.into_diagnostic()?);
+            let entry = row.into_diagnostic()?;
+            let size = entry.message.len() + 1;
+            if size > remaining {
+                break;
+            }
+            remaining -= size;
+            entries.push(entry);
         }
         Ok(entries)
     }
@@ -842,9 +952,9 @@ impl LogStore for SqliteLogStore {
         let conn = self.conn.lock().unwrap();
         let _ = conn
             .execute(
-                "INSERT INTO log_entries (daemon_id, timestamp, message, level, msg, logger, fields_json) \
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![id, ts, parsed.message, parsed.level, parsed.msg, parsed.logger, parsed.fields_json],
+                "INSERT INTO log_entries (daemon_id, timestamp, message, level, msg, logger, fields_json, stream) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![id, ts, parsed.message, parsed.level, parsed.msg, parsed.logger, parsed.fields_json, Self::stream_column(parsed.stream)],
             )
             .into_diagnostic()?;
         Ok(())
@@ -862,8 +972,8 @@ impl LogStore for SqliteLogStore {
         {
             let mut stmt = tx
                 .prepare(
-                    "INSERT INTO log_entries (daemon_id, timestamp, message, level, msg, logger, fields_json) \
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    "INSERT INTO log_entries (daemon_id, timestamp, message, level, msg, logger, fields_json, stream) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 )
                 .into_diagnostic()?;
             for (idx, entry) in entries.iter().enumerate() {
@@ -875,7 +985,8 @@ impl LogStore for SqliteLogStore {
                     entry.level,
                     entry.msg,
                     entry.logger,
-                    entry.fields_json
+                    entry.fields_json,
+                    Self::stream_column(entry.stream)
                 ])
                 .into_diagnostic()?;
             }
@@ -897,7 +1008,7 @@ impl LogStore for SqliteLogStore {
         // Single-threaded path.
         let conn = self.conn.lock().unwrap();
         let (sql, query_params) = Self::build_query_sql(opts, None);
-        Self::execute_built_query(&conn, &sql, &query_params)
+        Self::execute_built_query(&conn, &sql, &query_params, opts.max_bytes)
     }
 
     fn tail(&self, daemon_id: &DaemonId, after_id: Option<i64>) -> Result<Vec<LogEntry>> {
@@ -906,6 +1017,7 @@ impl LogStore for SqliteLogStore {
             from: None,
             to: None,
             limit: None,
+            max_bytes: None,
             order_desc: false,
             after_id,
             message_filters: Vec::new(),
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate log;
 
+mod ansi;
 mod boot_manager;
 mod cli;
 mod config_types;
@@ -11,11 +12,15 @@ mod daemon_status;
 mod deps;
 mod env;
 mod error;
+mod fault;
 mod ipc;
+#[cfg(windows)]
+mod job_object;
 mod log_jq;
 mod log_parse;
 mod log_store;
 mod logger;
+mod notification_file;
 mod pitchfork_toml;
 mod procs;
 mod proxy;
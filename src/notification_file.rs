@@ -0,0 +1,162 @@
+//! Notifications saved by the supervisor for `pitchfork notifications`
+//!
+//! With `supervisor.notify` including `file`, every notification at or above
+//! `supervisor.notify_level` is appended here as a JSON line, so failures are
+//! kept until acknowledged even when no pitchfork command drains them. The
+//! file is read directly, without the supervisor, which keeps
+//! `pitchfork notifications --count` cheap enough for a shell prompt.
+
+use crate::{Result, env};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Notifications kept in the file; the oldest are dropped first.
+const MAX_KEPT: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredNotification {
+    pub id: u64,
+    pub at: DateTime<Local>,
+    /// `error`, `warn`, `info`, `debug` or `trace`
+    pub level: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub acked: bool,
+}
+
+pub fn path() -> PathBuf {
+    env::PITCHFORK_STATE_DIR.join("notifications.jsonl")
+}
+
+/// Read all stored notifications, oldest first. Lines that don't parse are skipped.
+pub fn read() -> Result<Vec<StoredNotification>> {
+    read_from(&path())
+}
+
+/// Store a notification and return it with its assigned id
+pub fn append(level: log::LevelFilter, message: &str) -> Result<StoredNotification> {
+    let path = path();
+    let _lock = xx::fslock::get(&path, false)?;
+    let mut entries = read_from(&path)?;
+    let notification = StoredNotification {
+        id: entries.last().map_or(1, |n| n.id + 1),
+        at: Local::now(),
+        level: level.to_string().to_lowercase(),
+        message: message.to_string(),
+        acked: false,
+    };
+    entries.push(notification.clone());
+    if entries.len() > MAX_KEPT {
+        entries.drain(..entries.len() - MAX_KEPT);
+    }
+    write_to(&path, &entries)?;
+    Ok(notification)
+}
+
+/// Mark notifications as acknowledged, or all of them when `ids` is empty.
+/// Returns the ids that were newly acknowledged.
+pub fn ack(ids: &[u64]) -> Result<Vec<u64>> {
+    let path = path();
+    let _lock = xx::fslock::get(&path, false)?;
+    let mut entries = read_from(&path)?;
+    let acked = ack_entries(&mut entries, ids);
+    if !acked.is_empty() {
+        write_to(&path, &entries)?;
+    }
+    Ok(acked)
+}
+
+fn ack_entries(entries: &mut [StoredNotification], ids: &[u64]) -> Vec<u64> {
+    entries
+        .iter_mut()
+        .filter(|n| !n.acked && (ids.is_empty() || ids.contains(&n.id)))
+        .map(|n| {
+            n.acked = true;
+            n.id
+        })
+        .collect()
+}
+
+fn read_from(path: &std::path::Path) -> Result<Vec<StoredNotification>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let raw = xx::file::read_to_string(path)?;
+    Ok(parse(&raw))
+}
+
+fn parse(raw: &str) -> Vec<StoredNotification> {
+    raw.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(n) => Some(n),
+            Err(e) => {
+                debug!("skipping unreadable notification line: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn write_to(path: &std::path::Path, entries: &[StoredNotification]) -> Result<()> {
+    let mut raw = String::new();
+    for n in entries {
+        raw.push_str(&serde_json::to_string(n).map_err(|e| miette::miette!("{e}"))?);
+        raw.push('\n');
+    }
+    let temp_path = path.with_extension("jsonl.tmp");
+    xx::file::write(&temp_path, raw)?;
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| miette::miette!("failed to rename {} into place: {e}", temp_path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(id: u64, acked: bool) -> StoredNotification {
+        StoredNotification {
+            id,
+            at: Local::now(),
+            level: "error".to_string(),
+            message: format!("daemon {id} failed"),
+            acked,
+        }
+    }
+
+    #[test]
+    fn test_parse_skips_unreadable_lines() {
+        let line = serde_json::to_string(&notification(1, false)).unwrap();
+        let parsed = parse(&format!("{line}\nnot json\n\n"));
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].id, 1);
+        assert!(!parsed[0].acked);
+    }
+
+    #[test]
+    fn test_ack_entries() {
+        let mut entries = vec![
+            notification(1, true),
+            notification(2, false),
+            notification(3, false),
+        ];
+        assert_eq!(ack_entries(&mut entries, &[1, 3]), vec![3]);
+        assert!(!entries[1].acked);
+        assert_eq!(ack_entries(&mut entries, &[]), vec![2]);
+        assert!(entries.iter().all(|n| n.acked));
+    }
+
+    #[test]
+    fn test_write_and_read_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notifications.jsonl");
+        let entries = vec![notification(1, false), notification(2, true)];
+        write_to(&path, &entries).unwrap();
+        let read = read_from(&path).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].message, "daemon 1 failed");
+        assert!(read[1].acked);
+    }
+}
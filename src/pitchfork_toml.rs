@@ -5,17 +5,167 @@ use crate::settings::settings;
 use crate::state_file::StateFile;
 use crate::{Result, env};
 use indexmap::IndexMap;
-use miette::Context;
+use miette::{Context, IntoDiagnostic};
 use schemars::JsonSchema;
 use std::path::{Path, PathBuf};
 
 // Re-export config value types so existing `use crate::pitchfork_toml::X` paths keep working.
 pub use crate::config_types::{
-    CpuLimit, CronRetrigger, Dir, MemoryLimit, OnOutputHook, PitchforkTomlAuto, PitchforkTomlCron,
-    PitchforkTomlHooks, PortBump, PortConfig, ReadyCmd, ReadyHttp, ReadyOutput, ReadyPort, Retry,
-    StopConfig, StopSignal, WatchMode,
+    Alerts, CpuLimit, CronRetrigger, DaemonKind, Dir, LogEncoding, LogTimezone, MemoryLimit,
+    OnOutputHook, PitchforkTomlAuto, PitchforkTomlCron, PitchforkTomlHooks, PortBump, PortConfig,
+    ReadyCmd, ReadyHttp, ReadyLogFile, ReadyOutput, ReadyPort, ReadyWhen, ReadyWhenState,
+    Requirement, RequirementTarget, ResourceLimits, Retry, StartDelay, StopConfig, StopSignal,
+    Umask, WatchMode,
 };
 
+// ---------------------------------------------------------------------------
+// Duration values
+// ---------------------------------------------------------------------------
+
+/// Parse a duration from the config: a humantime string (`"1500ms"`, `"2m"`)
+/// or a bare number of seconds, which is what `ready_delay` has always taken.
+pub fn parse_duration(s: &str) -> std::result::Result<std::time::Duration, String> {
+    let s = s.trim();
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(std::time::Duration::from_secs(secs));
+    }
+    humantime::parse_duration(s).map_err(|e| format!("invalid duration {s:?}: {e}"))
+}
+
+/// A duration as written in the config, before it is parsed.
+///
+/// Every duration field deserializes through this type so they all accept the
+/// same forms: `ready_delay = 3`, `ready_delay = "1500ms"`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+pub enum DurationValue {
+    Secs(u64),
+    Text(String),
+}
+
+impl DurationValue {
+    pub fn parse(&self) -> std::result::Result<std::time::Duration, String> {
+        match self {
+            Self::Secs(secs) => Ok(std::time::Duration::from_secs(*secs)),
+            Self::Text(s) => parse_duration(s),
+        }
+    }
+}
+
+impl JsonSchema for DurationValue {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("DurationValue")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A duration, as a humantime string (e.g. '1500ms', '30s', '2m') or a number of seconds",
+            "type": ["string", "integer"],
+            "minimum": 0
+        })
+    }
+}
+
+/// Serde `with` module for `Option<Duration>` fields. Reads any
+/// [`DurationValue`]; whole seconds are written back as a bare number so
+/// files stay readable by older versions, anything finer as humantime.
+pub mod opt_duration {
+    use super::DurationValue;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(d) if d.subsec_nanos() == 0 => s.serialize_u64(d.as_secs()),
+            Some(d) => s.collect_str(&humantime::format_duration(*d)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        Option::<DurationValue>::deserialize(d)?
+            .map(|v| v.parse())
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Deserializer for duration fields that are kept as strings (log retention,
+/// readiness timeouts, hook debounce). A bare number is normalized to
+/// `"<n>s"`; strings are left for the field's own validation.
+pub fn deserialize_duration_string<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    use serde::Deserialize;
+    match Option::<DurationValue>::deserialize(d)? {
+        None => Ok(None),
+        Some(DurationValue::Secs(secs)) => Ok(Some(format!("{secs}s"))),
+        Some(DurationValue::Text(s)) => Ok(Some(s)),
+    }
+}
+
+/// Parse a byte size from the config: a bare number of bytes or a
+/// human-readable size (`"50MB"`, `"1MiB"`).
+pub fn parse_byte_size(s: &str) -> std::result::Result<u64, String> {
+    humanbyte::parse(s.trim()).map_err(|e| format!("invalid byte size {s:?}: {e}"))
+}
+
+/// A byte size as written in the config, before it is parsed: `65536` or
+/// `"64KiB"`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ByteSizeValue {
+    Bytes(u64),
+    Text(String),
+}
+
+impl ByteSizeValue {
+    pub fn parse(&self) -> std::result::Result<u64, String> {
+        match self {
+            Self::Bytes(bytes) => Ok(*bytes),
+            Self::Text(s) => parse_byte_size(s),
+        }
+    }
+}
+
+impl JsonSchema for ByteSizeValue {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("ByteSizeValue")
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A byte size, as a string (e.g. '50MB', '1MiB') or a number of bytes",
+            "type": ["string", "integer"],
+            "minimum": 0
+        })
+    }
+}
+
+/// Deserializer for byte-size settings, which are kept as a number of bytes.
+pub fn deserialize_byte_size<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<i64, D::Error> {
+    use serde::Deserialize;
+    let bytes = ByteSizeValue::deserialize(d)?
+        .parse()
+        .map_err(serde::de::Error::custom)?;
+    i64::try_from(bytes).map_err(serde::de::Error::custom)
+}
+
+/// [`deserialize_byte_size`] for settings that may be left unset.
+pub fn deserialize_opt_byte_size<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<Option<i64>, D::Error> {
+    use serde::Deserialize;
+    Option::<ByteSizeValue>::deserialize(d)?
+        .map(|v| {
+            let bytes = v.parse().map_err(serde::de::Error::custom)?;
+            i64::try_from(bytes).map_err(serde::de::Error::custom)
+        })
+        .transpose()
+}
+
 /// Raw slug entry as read from TOML (uses String for dir path).
 /// Format in global config:
 /// ```toml
@@ -23,7 +173,11 @@ pub use crate::config_types::{
 /// api = { dir = "/home/user/my-api", daemon = "server" }
 /// docs = { dir = "/home/user/docs-site" }  # daemon defaults to slug name
 /// ```
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[schemars(
+    rename = "SlugEntry",
+    description = "Proxy slug pointing at a project directory or namespace (global config only)"
+)]
 pub struct SlugEntryRaw {
     /// Project directory containing the pitchfork.toml
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -74,8 +228,13 @@ impl SlugEntry {
 /// [groups.backend]
 /// daemons = ["api", "worker"]
 /// ```
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[schemars(
+    rename = "GroupEntry",
+    description = "Named group of daemons for batch operations"
+)]
 pub struct GroupEntryRaw {
+    /// Daemons in the group, as short names or qualified `namespace/name` IDs
     pub daemons: Vec<String>,
 }
 
@@ -90,7 +249,11 @@ pub struct GroupEntry {
 /// [namespaces.myproject]
 /// dir = "/home/user/projects/myproject"
 /// ```
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[schemars(
+    rename = "NamespaceEntry",
+    description = "Project directory registered under a namespace name (global config only)"
+)]
 pub struct NamespaceEntryRaw {
     /// Project directory containing the pitchfork.toml
     pub dir: String,
@@ -114,6 +277,9 @@ struct PitchforkTomlRaw {
     /// Per-daemon `env` overrides these. Values support Tera templates.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub env: Option<IndexMap<String, String>>,
+    /// Named env sets that daemons opt into with `env_templates = [...]`.
+    #[serde(skip_serializing_if = "IndexMap::is_empty", default)]
+    pub env_templates: IndexMap<String, IndexMap<String, String>>,
     #[serde(default)]
     pub settings: Option<SettingsPartial>,
     /// Slug registry (only meaningful in global config).
@@ -135,12 +301,37 @@ struct PitchforkTomlRaw {
 /// `line_retention`, `archive_hook`) and the global `[settings.logs]` defaults.
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct PitchforkTomlDaemonLogs {
-    /// Log line format: `json`, `logfmt`, or `text`.
+    /// Log line format: `json`, `logfmt`, `syslog`, or `text`.
     /// Defaults to `text` (no parsing).
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub log_format: Option<String>,
-    /// Maximum age of log entries to keep (e.g. "7d", "30d").
+    /// Regex classifying the level of lines the log format doesn't, e.g.
+    /// `'^\[(?P<level>\w+)\]'`. The level is read from the `level` capture
+    /// group (or the first group) and used for highlighting and `--level`.
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_level_regex: Option<String>,
+    /// Regex patterns for output lines to drop before they are written to
+    /// the log, e.g. noisy startup banners. Dropped lines still count for
+    /// `ready_output` and `on_output`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub log_filter: Vec<String>,
+    /// Timezone `pitchfork logs` shows this daemon's timestamps in: `local`
+    /// (default), `UTC`, or an IANA name like `Europe/Berlin`. Overridden
+    /// by `pitchfork logs --tz`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_timezone: Option<LogTimezone>,
+    /// How output that isn't valid UTF-8 is logged: `utf8` (default)
+    /// replaces invalid bytes with U+FFFD, `escape` keeps them as `\xNN`,
+    /// and `latin1` decodes every byte as Latin-1.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_encoding: Option<LogEncoding>,
+    /// Maximum age of log entries to keep (e.g. "7d", "30d").
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "deserialize_duration_string"
+    )]
+    #[schemars(with = "Option<DurationValue>")]
     pub time_retention: Option<String>,
     /// Maximum number of log entries to keep per daemon.
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -154,19 +345,31 @@ pub struct PitchforkTomlDaemonLogs {
 ///
 /// Note: This struct mirrors `PitchforkTomlDaemon` but uses `Vec<String>` for `depends`
 /// (before namespace resolution) and has serde attributes for TOML serialization.
-/// When adding new fields, remember to update both structs and the conversion code
-/// in `read()` and `write()`.
+/// When adding new fields, remember to update both structs, the conversion code
+/// in `read()` and `write()`, and the key order in `pitchfork config fmt`.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct PitchforkTomlDaemonRaw {
     pub run: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kind: Option<DaemonKind>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub aliases: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub auto: Vec<PitchforkTomlAuto>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cron: Option<PitchforkTomlCron>,
     #[serde(default)]
     pub retry: Retry,
-    #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub ready_delay: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub success_exit_codes: Vec<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub no_retry_exit_codes: Vec<i32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "opt_duration"
+    )]
+    pub ready_delay: Option<std::time::Duration>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ready_output: Option<ReadyOutput>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -175,6 +378,16 @@ struct PitchforkTomlDaemonRaw {
     pub ready_port: Option<ReadyPort>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ready_cmd: Option<ReadyCmd>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_log_file: Option<ReadyLogFile>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_unix_socket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ready_when: Option<ReadyWhen>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fail_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expect_long_running: Option<bool>,
     /// New port configuration (preferred)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub port: Option<PortConfig>,
@@ -189,9 +402,15 @@ struct PitchforkTomlDaemonRaw {
     pub port_bump_attempts: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub boot_start: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub start_priority: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub start_delay: Option<StartDelay>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub depends: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub requires: Vec<Requirement>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub watch: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub watch_mode: Option<WatchMode>,
@@ -199,6 +418,10 @@ struct PitchforkTomlDaemonRaw {
     pub dir: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub env: Option<IndexMap<String, String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub env_templates: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub debug_env: Option<IndexMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub hooks: Option<PitchforkTomlHooks>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -212,15 +435,43 @@ struct PitchforkTomlDaemonRaw {
     /// CPU usage limit as a percentage (e.g. 80 for 80%, 200 for 2 cores)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cpu_limit: Option<CpuLimit>,
+    /// Resource usage thresholds that raise an alert
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alerts: Option<Alerts>,
     /// Unix signal to send for graceful shutdown (default: SIGTERM)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub stop_signal: Option<StopConfig>,
+    /// File mode creation mask (e.g. "027")
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub umask: Option<Umask>,
+    /// Resource limits (e.g. `{ nofile = 65536, core = 0 }`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub limits: Option<ResourceLimits>,
     /// Allocate a pseudo-terminal for the daemon process.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub pty: Option<bool>,
+    /// Run the daemon in its own network namespace (Linux only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub isolate_network: Option<bool>,
+    /// Restart the daemon when its config entry changes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_on_config_change: Option<bool>,
+    /// Restart the daemon when the executable it runs changes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_on_binary_change: Option<bool>,
+    /// Restart the daemon when one of its `depends` is restarted.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub restart_with_dependencies: Option<bool>,
+    /// Keep the daemon's state entry when `pitchfork clean` runs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sticky: Option<bool>,
     /// Maximum age of log entries to keep (e.g. "7d", "30d").
     /// Overrides the global `settings.logs.time_retention` when set.
-    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "deserialize_duration_string"
+    )]
     pub time_retention: Option<String>,
     /// Maximum number of log entries to keep per daemon.
     /// Overrides the global `settings.logs.line_retention` when set.
@@ -250,6 +501,12 @@ pub struct PitchforkToml {
     /// templates (e.g. `{{ daemons.api.port }}`, `{{ settings.proxy.tld }}`).
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub env: Option<IndexMap<String, String>>,
+    /// Named sets of environment variables that daemons opt into with
+    /// `env_templates = ["name", ...]`. They are layered over `[env]` in the
+    /// order listed, and the daemon's own `env` overrides them. Values support
+    /// Tera templates like `env`.
+    #[serde(default)]
+    pub env_templates: IndexMap<String, IndexMap<String, String>>,
     /// Optional explicit namespace declared in this file.
     ///
     /// This applies to per-file read/write flows. Merged configs may contain
@@ -269,14 +526,17 @@ pub struct PitchforkToml {
     /// Maps slug names to their project directory and optional daemon name.
     /// Only populated from global config files (`~/.config/pitchfork/config.toml`
     /// or `/etc/pitchfork/config.toml`).
-    #[schemars(skip)]
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, SlugEntryRaw>")]
     pub slugs: IndexMap<String, SlugEntry>,
     /// Named groups of daemons for batch operations.
-    #[schemars(skip)]
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, GroupEntryRaw>")]
     pub groups: IndexMap<String, GroupEntry>,
     /// Namespace registry (merged from global config files).
     /// Maps namespace names to their project directory.
-    #[schemars(skip)]
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, NamespaceEntryRaw>")]
     pub namespaces: IndexMap<String, NamespaceEntry>,
     #[schemars(skip)]
     pub path: Option<PathBuf>,
@@ -303,6 +563,71 @@ fn sibling_base_config(path: &Path) -> Option<PathBuf> {
     path.parent().map(|p| p.join("pitchfork.toml"))
 }
 
+/// Overlay `local` onto `base`: keys set in `local` win, and nested tables
+/// (`env`, `hooks`, ...) are merged key by key.
+fn overlay_table(base: &toml::Table, local: &toml::Table) -> toml::Table {
+    let mut merged = base.clone();
+    for (key, value) in local {
+        let value = match (merged.get(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(local)) => {
+                toml::Value::Table(overlay_table(base, local))
+            }
+            _ => value.clone(),
+        };
+        merged.insert(key.clone(), value);
+    }
+    merged
+}
+
+/// Inverse of [`overlay_table`]: drop everything `merged` has in common
+/// with `base`, leaving only the overrides.
+fn strip_table(base: &toml::Table, merged: &mut toml::Table) {
+    merged.retain(|key, value| match (base.get(key), value) {
+        (Some(toml::Value::Table(base)), toml::Value::Table(merged)) => {
+            strip_table(base, merged);
+            !merged.is_empty()
+        }
+        (Some(base), value) => base != value,
+        (None, _) => true,
+    });
+}
+
+/// Parse a `pitchfork.local.toml` whose sibling `pitchfork.toml` is `base`.
+/// Each `[daemons.<name>]` that `base` also defines is overlaid onto that
+/// definition, so the local file only needs the fields it changes. Returns
+/// the names of the overlaid daemons.
+fn parse_local_overlay(
+    content: &str,
+    path: &Path,
+    base: &Path,
+) -> Result<(PitchforkTomlRaw, Vec<String>)> {
+    let parse_error =
+        |e: toml::de::Error| ConfigParseError::from_toml_error(path, content.to_string(), e);
+    let mut doc: toml::Table = toml::from_str(content).map_err(parse_error)?;
+    let base_content = std::fs::read_to_string(base).map_err(|e| FileError::ReadError {
+        path: base.to_path_buf(),
+        source: e,
+    })?;
+    let base_doc: toml::Table = toml::from_str(&base_content)
+        .map_err(|e| ConfigParseError::from_toml_error(base, base_content.clone(), e))?;
+
+    let mut overlaid = Vec::new();
+    if let Some(toml::Value::Table(base_daemons)) = base_doc.get("daemons")
+        && let Some(toml::Value::Table(daemons)) = doc.get_mut("daemons")
+    {
+        for (name, daemon) in daemons.iter_mut() {
+            if let (Some(toml::Value::Table(base)), toml::Value::Table(local)) =
+                (base_daemons.get(name), &*daemon)
+            {
+                *daemon = toml::Value::Table(overlay_table(base, local));
+                overlaid.push(name.clone());
+            }
+        }
+    }
+    let raw = doc.try_into().map_err(parse_error)?;
+    Ok((raw, overlaid))
+}
+
 fn parse_namespace_override_from_content(path: &Path, content: &str) -> Result<Option<String>> {
     use toml::Value;
 
@@ -464,13 +789,8 @@ impl PitchforkToml {
             if let Some(dir) = entry.resolve_dir()
                 && let Ok(project_config) = Self::all_merged_from(&dir)
             {
-                // Find daemon by short name in that project
-                let matches: Vec<DaemonId> = project_config
-                    .daemons
-                    .keys()
-                    .filter(|id| id.name() == daemon_name)
-                    .cloned()
-                    .collect();
+                // Find daemon by short name or alias in that project
+                let matches = project_config.find_by_name(daemon_name);
                 match matches.as_slice() {
                     [] => {}
                     [id] => return Ok(vec![id.clone()]),
@@ -490,12 +810,7 @@ impl PitchforkToml {
         }
 
         // Look for matching qualified IDs in the config
-        let matches: Vec<DaemonId> = self
-            .daemons
-            .keys()
-            .filter(|id| id.name() == user_id)
-            .cloned()
-            .collect();
+        let matches = self.find_by_name(user_id);
 
         if matches.is_empty() {
             // No config matches. Search state file for any daemon with matching short name.
@@ -520,6 +835,96 @@ impl PitchforkToml {
         Ok(matches)
     }
 
+    /// Finds the configured daemons whose short name or one of whose `aliases`
+    /// is `name`.
+    ///
+    /// All short ID resolution goes through this, so aliases are accepted
+    /// wherever a daemon ID is.
+    pub fn find_by_name(&self, name: &str) -> Vec<DaemonId> {
+        self.daemons
+            .iter()
+            .filter(|(id, d)| id.name() == name || d.aliases.iter().any(|a| a == name))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Finds the configured daemon `name` refers to within `namespace`: the
+    /// daemon with that short name, or else the one with that alias.
+    fn find_in_namespace(&self, name: &str, namespace: &str) -> Result<Option<DaemonId>> {
+        // Use try_new to validate user input
+        let id = DaemonId::try_new(namespace, name)?;
+        if self.daemons.contains_key(&id) {
+            return Ok(Some(id));
+        }
+        Ok(self
+            .find_by_name(name)
+            .into_iter()
+            .find(|id| id.namespace() == namespace))
+    }
+
+    /// Checks that every alias is a valid daemon name that refers to a single
+    /// daemon: it must not repeat a daemon name or another alias in the same
+    /// namespace.
+    fn validate_aliases(&self) -> Result<()> {
+        use std::collections::HashMap;
+
+        let mut taken: HashMap<(&str, &str), String> = self
+            .daemons
+            .keys()
+            .map(|id| {
+                (
+                    (id.namespace(), id.name()),
+                    format!("daemon '{}'", id.name()),
+                )
+            })
+            .collect();
+        for (id, daemon) in &self.daemons {
+            let path = daemon.path.clone().unwrap_or_default();
+            for alias in &daemon.aliases {
+                if let Err(e) = DaemonId::try_new(id.namespace(), alias) {
+                    return Err(ConfigParseError::InvalidDaemonName {
+                        name: alias.clone(),
+                        path,
+                        reason: e.to_string(),
+                    }
+                    .into());
+                }
+                let owner = format!("an alias of daemon '{}'", id.name());
+                if let Some(conflict) = taken.insert((id.namespace(), alias.as_str()), owner) {
+                    return Err(ConfigParseError::AliasConflict {
+                        alias: alias.clone(),
+                        daemon: id.name().to_string(),
+                        conflict,
+                        path,
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every `env_templates` entry names a template defined in
+    /// `[env_templates]`.
+    fn validate_env_templates(&self) -> Result<()> {
+        for (id, daemon) in &self.daemons {
+            for name in &daemon.env_templates {
+                if !self.env_templates.contains_key(name) {
+                    let suggestion =
+                        find_similar_daemon(name, self.env_templates.keys().map(|s| s.as_str()));
+                    return Err(ConfigParseError::UnknownEnvTemplate {
+                        template: name.clone(),
+                        daemon: id.name().to_string(),
+                        path: daemon.path.clone().unwrap_or_default(),
+                        suggestion,
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Finds all daemons in the persisted state file whose short name matches `short_name`.
     ///
     /// Logs a warning if the state file exists but cannot be read or parsed.
@@ -593,12 +998,7 @@ impl PitchforkToml {
             if let Some(dir) = entry.resolve_dir()
                 && let Ok(project_config) = Self::all_merged_from(&dir)
             {
-                let matches: Vec<DaemonId> = project_config
-                    .daemons
-                    .keys()
-                    .filter(|id| id.name() == daemon_name)
-                    .cloned()
-                    .collect();
+                let matches = project_config.find_by_name(daemon_name);
                 match matches.as_slice() {
                     [] => {}
                     [id] => return Ok(id.clone()),
@@ -618,10 +1018,8 @@ impl PitchforkToml {
         }
 
         // Try to find the daemon in the current namespace first
-        // Use try_new to validate user input
-        let preferred_id = DaemonId::try_new(current_namespace, user_id)?;
-        if self.daemons.contains_key(&preferred_id) {
-            return Ok(preferred_id);
+        if let Some(id) = self.find_in_namespace(user_id, current_namespace)? {
+            return Ok(id);
         }
 
         // Fall back to any matching daemon
@@ -644,12 +1042,14 @@ impl PitchforkToml {
 
         // If not found in current namespace or merged config matches, only fall back
         // to global when it is explicitly configured.
-        let global_id = DaemonId::try_new("global", user_id)?;
-        if self.daemons.contains_key(&global_id) {
-            return Ok(global_id);
+        if let Some(id) = self.find_in_namespace(user_id, "global")? {
+            return Ok(id);
         }
 
-        let suggestion = find_similar_daemon(user_id, self.daemons.keys().map(|id| id.name()));
+        let names = self.daemons.iter().flat_map(|(id, d)| {
+            std::iter::once(id.name()).chain(d.aliases.iter().map(String::as_str))
+        });
+        let suggestion = find_similar_daemon(user_id, names);
         Err(DependencyError::DaemonNotFound {
             name: user_id.to_string(),
             suggestion,
@@ -702,9 +1102,8 @@ impl PitchforkToml {
         let config = Self::all_merged()?;
         let ns = Self::namespace_for_dir(&env::CWD)?;
 
-        let preferred_id = DaemonId::try_new(&ns, user_id)?;
-        if config.daemons.contains_key(&preferred_id) {
-            return Ok(preferred_id);
+        if let Some(id) = config.find_in_namespace(user_id, &ns)? {
+            return Ok(id);
         }
 
         let matches = config.resolve_daemon_id(user_id)?;
@@ -884,6 +1283,10 @@ impl PitchforkToml {
                             pt.daemons.insert(daemon_id, daemon_config);
                         }
                     }
+                    // Env templates the namespace's daemons refer to
+                    for (name, template) in ns_config.env_templates {
+                        pt.env_templates.entry(name).or_insert(template);
+                    }
                     // Merge namespace-level settings so daemon-local
                     // overrides (e.g. hooks, env defaults) are available.
                     pt.settings.merge_from(&ns_config.settings);
@@ -954,6 +1357,12 @@ impl PitchforkToml {
                 Err(e) => return Err(e.wrap_err(format!("error reading {}", p.display()))),
             }
         }
+        // Aliases are checked per file on read; check again across the files
+        // that share a namespace
+        pt.validate_aliases()?;
+        // Env templates may come from any file in the chain (e.g. the global
+        // config), so references can only be checked once everything is merged
+        pt.validate_env_templates()?;
         Ok(pt)
     }
 }
@@ -963,6 +1372,7 @@ impl PitchforkToml {
         Self {
             daemons: Default::default(),
             env: None,
+            env_templates: IndexMap::new(),
             namespace: None,
             settings: SettingsPartial::default(),
             slugs: IndexMap::new(),
@@ -972,16 +1382,25 @@ impl PitchforkToml {
         }
     }
 
-    /// Parse TOML content as a [`PitchforkToml`] without touching the filesystem.
+    /// Parse TOML content as a [`PitchforkToml`] without reading `path` itself.
     ///
     /// Applies the same namespace derivation and daemon validation as [`read()`] but
     /// uses the provided `content` directly instead of reading from disk.  `path` is
-    /// used only for namespace derivation and error messages.
+    /// used for namespace derivation and error messages, and for a
+    /// `pitchfork.local.toml`, to find the sibling `pitchfork.toml` whose daemons
+    /// it overrides.
     ///
     /// This is useful for validating user-edited content before saving it.
     pub fn parse_str(content: &str, path: &Path) -> Result<Self> {
-        let raw_config: PitchforkTomlRaw = toml::from_str(content)
-            .map_err(|e| ConfigParseError::from_toml_error(path, content.to_string(), e))?;
+        let base_config = sibling_base_config(path).filter(|p| p.exists());
+        let (raw_config, overlaid) = match &base_config {
+            Some(base) => parse_local_overlay(content, path, base)?,
+            None => {
+                let raw: PitchforkTomlRaw = toml::from_str(content)
+                    .map_err(|e| ConfigParseError::from_toml_error(path, content.to_string(), e))?;
+                (raw, Vec::new())
+            }
+        };
 
         let namespace = {
             let base_explicit = sibling_base_config(path)
@@ -1055,6 +1474,24 @@ impl PitchforkToml {
                 };
                 depends.push(dep_id);
             }
+            if let Some(ready_when) = &raw_daemon.ready_when {
+                let reason = match ready_when.daemon_id(&namespace) {
+                    Ok(id) if id.namespace() == namespace && id.name() == short_name => {
+                        Some("a daemon can't wait on itself".to_string())
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some(e.to_string()),
+                };
+                if let Some(reason) = reason {
+                    return Err(ConfigParseError::InvalidDependency {
+                        daemon: short_name.clone(),
+                        dependency: ready_when.daemon.clone(),
+                        path: path.to_path_buf(),
+                        reason,
+                    }
+                    .into());
+                }
+            }
 
             // Resolve port config: prefer new `port` field, fall back to deprecated fields
             let has_deprecated = !raw_daemon.expected_port.is_empty()
@@ -1090,32 +1527,57 @@ impl PitchforkToml {
 
             let daemon = PitchforkTomlDaemon {
                 run: raw_daemon.run,
+                kind: raw_daemon.kind.unwrap_or_default(),
+                aliases: raw_daemon.aliases,
                 auto: raw_daemon.auto,
                 cron: raw_daemon.cron,
                 retry: raw_daemon.retry,
+                success_exit_codes: raw_daemon.success_exit_codes,
+                no_retry_exit_codes: raw_daemon.no_retry_exit_codes,
                 ready_delay: raw_daemon.ready_delay,
                 ready_output: raw_daemon.ready_output,
                 ready_http: raw_daemon.ready_http,
                 ready_port: raw_daemon.ready_port,
                 ready_cmd: raw_daemon.ready_cmd,
+                ready_log_file: raw_daemon.ready_log_file,
+                ready_unix_socket: raw_daemon.ready_unix_socket,
+                ready_when: raw_daemon.ready_when,
+                fail_output: raw_daemon.fail_output,
+                expect_long_running: raw_daemon.expect_long_running,
                 port,
                 boot_start: raw_daemon.boot_start,
+                start_priority: raw_daemon.start_priority,
+                start_delay: raw_daemon.start_delay,
                 depends,
+                requires: raw_daemon.requires,
                 watch: raw_daemon.watch,
                 watch_mode: raw_daemon.watch_mode.unwrap_or_default(),
                 dir: raw_daemon.dir,
                 env: raw_daemon.env,
+                env_templates: raw_daemon.env_templates,
+                debug_env: raw_daemon.debug_env,
                 hooks: raw_daemon.hooks,
                 mise: raw_daemon.mise,
                 user: raw_daemon.user,
                 memory_limit: raw_daemon.memory_limit,
                 cpu_limit: raw_daemon.cpu_limit,
+                alerts: raw_daemon.alerts,
                 stop_signal: raw_daemon.stop_signal,
+                umask: raw_daemon.umask,
+                limits: raw_daemon.limits,
                 pty: raw_daemon.pty,
+                isolate_network: raw_daemon.isolate_network,
+                restart_on_config_change: raw_daemon.restart_on_config_change,
+                restart_on_binary_change: raw_daemon.restart_on_binary_change,
+                restart_with_dependencies: raw_daemon.restart_with_dependencies,
+                sticky: raw_daemon.sticky,
                 time_retention: raw_daemon.time_retention,
                 line_retention: raw_daemon.line_retention,
                 archive_hook: raw_daemon.archive_hook,
                 logs: raw_daemon.logs,
+                overrides: base_config
+                    .clone()
+                    .filter(|_| overlaid.contains(&short_name)),
                 path: Some(path.to_path_buf()),
             };
             pt.daemons.insert(id, daemon);
@@ -1126,8 +1588,9 @@ impl PitchforkToml {
             pt.settings = settings;
         }
 
-        // Copy top-level env
+        // Copy top-level env and env templates
         pt.env = raw_config.env;
+        pt.env_templates = raw_config.env_templates;
 
         // Copy slugs registry (only meaningful in global config files)
         for (slug, entry) in raw_config.slugs {
@@ -1178,6 +1641,7 @@ impl PitchforkToml {
             pt.groups.insert(group_name, GroupEntry { daemons });
         }
 
+        pt.validate_aliases()?;
         Ok(pt)
     }
 
@@ -1211,6 +1675,20 @@ impl PitchforkToml {
     /// calling this method. This is used by `register_slug` which needs to
     /// hold a single lock across a read-modify-write cycle.
     fn write_unlocked(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Err(FileError::NoPath.into());
+        };
+        let raw_str = self.to_toml_string()?;
+        xx::file::write(path, &raw_str).map_err(|e| FileError::WriteError {
+            path: path.clone(),
+            details: Some(e.to_string()),
+        })?;
+        Ok(())
+    }
+
+    /// Serialize the config the way [`write()`](Self::write) stores it in its
+    /// file. The path is still required, to determine the namespace.
+    pub fn to_toml_string(&self) -> Result<String> {
         if let Some(path) = &self.path {
             // Determine the namespace for this config file
             let config_namespace = if path.exists() {
@@ -1225,6 +1703,7 @@ impl PitchforkToml {
             let mut raw = PitchforkTomlRaw {
                 namespace: self.namespace.clone(),
                 env: self.env.clone(),
+                env_templates: self.env_templates.clone(),
                 settings: (!self.settings.is_empty()).then(|| self.settings.clone()),
                 ..PitchforkTomlRaw::default()
             };
@@ -1241,14 +1720,23 @@ impl PitchforkToml {
                 let port = daemon.port.as_ref();
                 let raw_daemon = PitchforkTomlDaemonRaw {
                     run: daemon.run.clone(),
+                    kind: daemon.kind.is_task().then_some(daemon.kind),
+                    aliases: daemon.aliases.clone(),
                     auto: daemon.auto.clone(),
                     cron: daemon.cron.clone(),
                     retry: daemon.retry,
+                    success_exit_codes: daemon.success_exit_codes.clone(),
+                    no_retry_exit_codes: daemon.no_retry_exit_codes.clone(),
                     ready_delay: daemon.ready_delay,
                     ready_output: daemon.ready_output.clone(),
                     ready_http: daemon.ready_http.clone(),
                     ready_port: daemon.ready_port.clone(),
                     ready_cmd: daemon.ready_cmd.clone(),
+                    ready_log_file: daemon.ready_log_file.clone(),
+                    ready_unix_socket: daemon.ready_unix_socket.clone(),
+                    ready_when: daemon.ready_when.clone(),
+                    fail_output: daemon.fail_output.clone(),
+                    expect_long_running: daemon.expect_long_running,
                     port: port.cloned(),
                     // Deprecated fields: written for backward compatibility with older pitchfork versions
                     expected_port: port.map(|p| p.expect.clone()).unwrap_or_default(),
@@ -1257,6 +1745,8 @@ impl PitchforkToml {
                         .filter(|p| p.auto_bump())
                         .map(|p| p.max_bump_attempts()),
                     boot_start: daemon.boot_start,
+                    start_priority: daemon.start_priority,
+                    start_delay: daemon.start_delay,
                     // Preserve cross-namespace dependencies: use qualified ID if namespace differs,
                     // otherwise use short name
                     depends: daemon
@@ -1270,6 +1760,7 @@ impl PitchforkToml {
                             }
                         })
                         .collect(),
+                    requires: daemon.requires.clone(),
                     watch: daemon.watch.clone(),
                     watch_mode: match daemon.watch_mode {
                         WatchMode::Native => None,
@@ -1277,13 +1768,23 @@ impl PitchforkToml {
                     },
                     dir: daemon.dir.clone(),
                     env: daemon.env.clone(),
+                    env_templates: daemon.env_templates.clone(),
+                    debug_env: daemon.debug_env.clone(),
                     hooks: daemon.hooks.clone(),
                     mise: daemon.mise,
                     user: daemon.user.clone(),
                     memory_limit: daemon.memory_limit,
                     cpu_limit: daemon.cpu_limit,
+                    alerts: daemon.alerts,
                     stop_signal: daemon.stop_signal,
+                    umask: daemon.umask,
+                    limits: daemon.limits,
                     pty: daemon.pty,
+                    isolate_network: daemon.isolate_network,
+                    restart_on_config_change: daemon.restart_on_config_change,
+                    restart_on_binary_change: daemon.restart_on_binary_change,
+                    restart_with_dependencies: daemon.restart_with_dependencies,
+                    sticky: daemon.sticky,
                     time_retention: daemon.time_retention.clone(),
                     line_retention: daemon.line_retention,
                     archive_hook: daemon.archive_hook.clone(),
@@ -1335,15 +1836,38 @@ impl PitchforkToml {
                 );
             }
 
-            let raw_str = toml::to_string(&raw).map_err(|e| FileError::SerializeError {
+            let serialize_error = |e| FileError::SerializeError {
                 path: path.clone(),
                 source: e,
-            })?;
-            xx::file::write(path, &raw_str).map_err(|e| FileError::WriteError {
-                path: path.clone(),
-                details: Some(e.to_string()),
-            })?;
-            Ok(())
+            };
+            let base_config = sibling_base_config(path).filter(|p| p.exists());
+            let overlaid = self
+                .daemons
+                .iter()
+                .filter(|(_, d)| d.overrides.is_some() && d.overrides == base_config)
+                .map(|(id, _)| id.name())
+                .collect::<Vec<_>>();
+            let Some(base_config) = base_config.filter(|_| !overlaid.is_empty()) else {
+                return Ok(toml::to_string(&raw).map_err(serialize_error)?);
+            };
+
+            // Write only the fields that overrides change from the base
+            // definition, serialized the same way so formatting can't differ
+            let base_doc: toml::Table =
+                toml::from_str(&Self::read(&base_config)?.to_toml_string()?).into_diagnostic()?;
+            let mut doc = toml::Table::try_from(&raw).map_err(serialize_error)?;
+            if let Some(toml::Value::Table(base_daemons)) = base_doc.get("daemons")
+                && let Some(toml::Value::Table(daemons)) = doc.get_mut("daemons")
+            {
+                for name in overlaid {
+                    if let (Some(toml::Value::Table(base)), Some(toml::Value::Table(daemon))) =
+                        (base_daemons.get(name), daemons.get_mut(name))
+                    {
+                        strip_table(base, daemon);
+                    }
+                }
+            }
+            Ok(toml::to_string(&doc).map_err(serialize_error)?)
         } else {
             Err(FileError::NoPath.into())
         }
@@ -1364,6 +1888,10 @@ impl PitchforkToml {
                 merged.insert(k, v);
             }
         }
+        // Merge env templates - a later definition replaces the whole template
+        for (name, template) in pt.env_templates {
+            self.env_templates.insert(name, template);
+        }
         // Merge slugs - pt's values override self's values
         for (slug, entry) in pt.slugs {
             self.slugs.insert(slug, entry);
@@ -1588,6 +2116,17 @@ pub struct PitchforkTomlDaemon {
     /// The command to run. Prepend with 'exec' to avoid shell process overhead.
     #[schemars(example = example_run_command())]
     pub run: String,
+    /// `daemon` (default) for a long-running process, or `task` for one that
+    /// runs to completion, like a migration. A task is ready once it exits
+    /// successfully, so dependents start only after that, and it shows as
+    /// `completed` afterwards instead of stopped.
+    #[schemars(default)]
+    pub kind: DaemonKind,
+    /// Alternative names accepted wherever this daemon's ID is expected
+    /// (e.g. `["web", "frontend"]`). Aliases must not collide with other
+    /// daemon names or aliases in the same namespace.
+    #[schemars(default)]
+    pub aliases: Vec<String>,
     /// Automatic start/stop behavior based on shell hooks
     #[schemars(default)]
     pub auto: Vec<PitchforkTomlAuto>,
@@ -1597,8 +2136,18 @@ pub struct PitchforkTomlDaemon {
     /// Can be a number (e.g., `3`) or `true` for infinite retries.
     #[schemars(default)]
     pub retry: Retry,
-    /// Delay in seconds before considering the daemon ready
-    pub ready_delay: Option<u64>,
+    /// Exit codes treated as a clean exit, like 0. A daemon exiting with one
+    /// of these is marked stopped and is not retried.
+    #[schemars(default)]
+    pub success_exit_codes: Vec<i32>,
+    /// Exit codes that mark the daemon as errored without retrying, even if
+    /// `retry` has attempts left.
+    #[schemars(default)]
+    pub no_retry_exit_codes: Vec<i32>,
+    /// Delay before considering the daemon ready, as a number of seconds or a
+    /// humantime string (e.g. `3`, `"1500ms"`)
+    #[schemars(with = "Option<DurationValue>")]
+    pub ready_delay: Option<std::time::Duration>,
     /// Regex pattern to match in ANSI-stripped stdout/stderr to determine readiness
     pub ready_output: Option<ReadyOutput>,
     /// HTTP URL to poll for readiness. Accepts any 2xx response by default, or configured statuses.
@@ -1609,13 +2158,41 @@ pub struct PitchforkTomlDaemon {
     pub ready_port: Option<ReadyPort>,
     /// Shell command to poll for readiness (exit code 0 = ready)
     pub ready_cmd: Option<ReadyCmd>,
+    /// File the daemon writes its own logs to, and a regex pattern that marks
+    /// readiness when a new line in it matches
+    pub ready_log_file: Option<ReadyLogFile>,
+    /// Unix socket the daemon listens on; it is ready once a connection to the
+    /// socket succeeds. Relative paths are resolved against the daemon's `dir`.
+    pub ready_unix_socket: Option<String>,
+    /// Another pitchfork daemon and the state it must reach, e.g.
+    /// `{ daemon = "db", state = "ready" }`. The supervisor watches the
+    /// upstream's status, so no endpoint is polled.
+    pub ready_when: Option<ReadyWhen>,
+    /// Regex pattern for a fatal error in ANSI-stripped stdout/stderr (e.g.
+    /// `"EADDRINUSE|panicked at"`). A matching line before the daemon is
+    /// ready fails the start right away, with the line as the reason.
+    pub fail_output: Option<String>,
+    /// Treat the daemon as a long-running service: exiting before it is
+    /// ready, or within 10 seconds of starting, fails it even with a
+    /// successful exit code (e.g. a server that forked into the background).
+    pub expect_long_running: Option<bool>,
     /// Port configuration: expected ports and auto-bump settings
     pub port: Option<PortConfig>,
     /// Whether to start this daemon automatically on system boot
     pub boot_start: Option<bool>,
+    /// Order among daemons that start at the same time (same dependency level,
+    /// or at boot). Higher values start first. Default: 0
+    pub start_priority: Option<i32>,
+    /// Pause before spawning this daemon when it isn't the first of a batch
+    /// start, to stagger heavy daemons (e.g. "3s")
+    pub start_delay: Option<StartDelay>,
     /// List of daemon IDs that must be started before this one
     #[schemars(default)]
     pub depends: Vec<DaemonId>,
+    /// External services (`{ tcp = "host:port" }` or `{ http = "url" }`) that
+    /// must be reachable before the daemon is spawned
+    #[schemars(default)]
+    pub requires: Vec<Requirement>,
     /// File patterns to watch for changes
     #[schemars(default)]
     pub watch: Vec<String>,
@@ -1630,6 +2207,13 @@ pub struct PitchforkTomlDaemon {
     pub dir: Option<String>,
     /// Environment variables to set for the daemon process
     pub env: Option<IndexMap<String, String>>,
+    /// Names of `[env_templates]` to apply to this daemon, in order. Later
+    /// templates override earlier ones, and `env` overrides them all.
+    #[schemars(default)]
+    pub env_templates: Vec<String>,
+    /// Extra environment variables applied on top of `env` while debug logging
+    /// is enabled with `pitchfork debug enable`
+    pub debug_env: Option<IndexMap<String, String>>,
     /// Lifecycle hooks (on_ready, on_fail, on_retry)
     pub hooks: Option<PitchforkTomlHooks>,
     /// Wrap this daemon's command with `mise x --` for tool/env setup.
@@ -1643,13 +2227,45 @@ pub struct PitchforkTomlDaemon {
     /// CPU usage limit as a percentage (e.g. 80 for 80%, 200 for 2 cores).
     /// The supervisor periodically monitors CPU usage and kills the process if it exceeds the limit.
     pub cpu_limit: Option<CpuLimit>,
+    /// Memory and CPU thresholds that fire the `on_alert` hook and mark the
+    /// daemon with a warning once exceeded for `for`, without stopping it.
+    pub alerts: Option<Alerts>,
     /// Stop signal and optional per-daemon timeout. Accepts a signal name string
     /// or `{ signal = "...", timeout = "..." }` object.
     pub stop_signal: Option<StopConfig>,
-    /// Allocate a pseudo-terminal for the daemon process.
+    /// File mode creation mask for the daemon process, as an octal string
+    /// (e.g. `"027"`). Unix only.
+    pub umask: Option<Umask>,
+    /// Resource limits (`setrlimit`) for the daemon process, e.g.
+    /// `{ nofile = 65536, core = 0 }`. Unix only.
+    pub limits: Option<ResourceLimits>,
+    /// Allocate a pseudo-terminal for the daemon process so it keeps colored,
+    /// line-buffered output as if run interactively (Unix only).
     pub pty: Option<bool>,
+    /// Run the daemon in its own network namespace so it can listen on its
+    /// configured ports even when another daemon already uses them. The
+    /// daemon sees its `port.expect` ports while pitchfork forwards a free
+    /// host port to each. Linux only; requires `pasta` (from passt).
+    pub isolate_network: Option<bool>,
+    /// Restart the daemon when its entry in the config file changes, e.g.
+    /// its command, env or ready checks. Edits to other daemons, comments
+    /// and formatting don't trigger a restart.
+    pub restart_on_config_change: Option<bool>,
+    /// Restart the daemon when the executable its `run` command starts is
+    /// rebuilt or replaced, e.g. by `cargo build`. Changes are debounced and
+    /// a binary whose contents didn't change doesn't trigger a restart.
+    pub restart_on_binary_change: Option<bool>,
+    /// Restart the daemon when one of its `depends` is restarted, e.g. a
+    /// web server that has to reconnect after `db` comes back. Restarts
+    /// cascade through dependents that set this too.
+    pub restart_with_dependencies: Option<bool>,
+    /// Keep the daemon in `pitchfork list` with its last run info when
+    /// `pitchfork clean` removes other stopped daemons. Useful for
+    /// rarely-run cron jobs.
+    pub sticky: Option<bool>,
     /// Maximum age of log entries to keep (e.g. "7d", "30d").
     /// Overrides the global `settings.logs.time_retention` when set.
+    #[schemars(with = "Option<DurationValue>")]
     pub time_retention: Option<String>,
     /// Maximum number of log entries to keep per daemon.
     /// Overrides the global `settings.logs.line_retention` when set.
@@ -1659,11 +2275,22 @@ pub struct PitchforkTomlDaemon {
     pub archive_hook: Option<String>,
     /// Per-daemon log configuration sub-table.
     pub logs: Option<PitchforkTomlDaemonLogs>,
+    /// For a daemon from a `pitchfork.local.toml` that only overrides some
+    /// fields, the sibling `pitchfork.toml` that defines the rest.
+    #[schemars(skip)]
+    pub overrides: Option<PathBuf>,
     #[schemars(skip)]
     pub path: Option<PathBuf>,
 }
 
 impl PitchforkTomlDaemon {
+    /// The config file that defines this daemon: the sibling `pitchfork.toml`
+    /// for an entry that only overrides it from `pitchfork.local.toml`,
+    /// otherwise the file it was read from.
+    pub fn source_config(&self) -> Option<&Path> {
+        self.overrides.as_deref().or(self.path.as_deref())
+    }
+
     /// Build RunOptions from this daemon configuration.
     ///
     /// Carries over all config fields and resolves the working directory.
@@ -1695,6 +2322,7 @@ impl PitchforkTomlDaemon {
             id: id.clone(),
             cmd,
             run: Some(self.run.clone()),
+            kind: self.kind,
             force: false,
             shell_pid: None,
             dir: Dir(dir),
@@ -1702,16 +2330,25 @@ impl PitchforkTomlDaemon {
             cron_schedule: self.cron.as_ref().map(|c| c.schedule.clone()),
             cron_retrigger: self.cron.as_ref().map(|c| c.retrigger),
             cron_immediate: self.cron.as_ref().map(|c| c.immediate),
+            cron_exclusive: self.cron.as_ref().map(|c| c.exclusive),
             retry: self.retry,
             retry_count: 0,
+            success_exit_codes: self.success_exit_codes.clone(),
+            no_retry_exit_codes: self.no_retry_exit_codes.clone(),
             ready_delay: self.ready_delay,
             ready_output: self.ready_output.clone(),
             ready_http: self.ready_http.clone(),
             ready_port: self.ready_port.clone(),
             ready_cmd: self.ready_cmd.clone(),
+            ready_log_file: self.ready_log_file.clone(),
+            ready_unix_socket: self.ready_unix_socket.clone(),
+            ready_when: self.ready_when.clone(),
+            fail_output: self.fail_output.clone(),
+            expect_long_running: self.expect_long_running,
             port: self.port.clone(),
             wait_ready: false,
             depends: self.depends.clone(),
+            requires: self.requires.clone(),
             env: self.env.clone(),
             watch: self.watch.clone(),
             watch_mode: self.watch_mode,
@@ -1724,15 +2361,36 @@ impl PitchforkTomlDaemon {
             user: self.user.clone(),
             memory_limit: self.memory_limit,
             cpu_limit: self.cpu_limit,
+            alerts: self.alerts,
             stop_signal: self.stop_signal,
+            umask: self.umask,
+            limits: self.limits,
             archive_hook: self
                 .logs
                 .as_ref()
                 .and_then(|l| l.archive_hook.clone())
                 .or_else(|| self.archive_hook.clone()),
             log_format: self.logs.as_ref().and_then(|l| l.log_format.clone()),
+            log_level_regex: self.logs.as_ref().and_then(|l| l.log_level_regex.clone()),
+            log_encoding: self.logs.as_ref().and_then(|l| l.log_encoding),
+            log_filter: self
+                .logs
+                .as_ref()
+                .map(|l| l.log_filter.clone())
+                .unwrap_or_default(),
             on_output_hook: self.hooks.as_ref().and_then(|h| h.on_output.clone()),
             pty: self.pty,
+            isolate_network: self.isolate_network,
+            restart_on_config_change: self.restart_on_config_change,
+            restart_on_binary_change: self.restart_on_binary_change,
+            restart_with_dependencies: self.restart_with_dependencies,
+            sticky: self.sticky,
+            kill_conflicts: false,
+            owner: None,
+            env_override: None,
+            env_override_until: None,
+            wait_timeout: None,
+            restart_batch: vec![],
         }
     }
 }
@@ -1744,6 +2402,16 @@ fn example_run_command() -> &'static str {
 mod tests {
     use super::*;
     use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3"), Ok(Duration::from_secs(3)));
+        assert_eq!(parse_duration(" 1500ms "), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("3 parsecs").is_err());
+    }
 
     #[test]
     fn test_daemon_user_parses_and_flows_to_run_options() {
@@ -1793,6 +2461,39 @@ user = "postgres"
         assert_eq!(daemon.user.as_deref(), Some("postgres"));
     }
 
+    #[test]
+    fn test_to_toml_string_single_daemon() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("my-project").join("pitchfork.toml");
+        let config = PitchforkToml::parse_str(
+            r#"
+[daemons.api]
+run = "node server.js"
+
+[daemons.api.env]
+PORT = "3000"
+
+[daemons.api.hooks]
+on_ready = "echo ready"
+"#,
+            &path,
+        )
+        .unwrap();
+
+        // Serializing doesn't touch the file and round-trips the fields the
+        // TUI form can't show
+        let raw = config.to_toml_string().unwrap();
+        assert!(!path.exists());
+        assert!(raw.starts_with("[daemons.api]\n"), "got:\n{raw}");
+        let parsed = PitchforkToml::parse_str(&raw, &path).unwrap();
+        let daemon = parsed
+            .daemons
+            .get(&DaemonId::new("my-project", "api"))
+            .unwrap();
+        assert_eq!(daemon.env.as_ref().unwrap()["PORT"], "3000");
+        assert!(daemon.hooks.as_ref().unwrap().on_ready.is_some());
+    }
+
     #[test]
     fn test_settings_write_roundtrip() {
         let temp = tempfile::tempdir().unwrap();
@@ -1854,4 +2555,78 @@ user = "postgres"
         assert_eq!(parsed.settings.web.auto_start, Some(true));
         assert!(parsed.slugs.contains_key("api"));
     }
+
+    #[test]
+    fn test_aliases_resolve_to_daemon() {
+        let pt = PitchforkToml::parse_str(
+            r#"
+[daemons.frontend-dev-server]
+run = "npm run dev"
+aliases = ["web", "frontend"]
+
+[daemons.api]
+run = "node server.js"
+"#,
+            Path::new("/tmp/my-project/pitchfork.toml"),
+        )
+        .unwrap();
+
+        let id = DaemonId::new("my-project", "frontend-dev-server");
+        assert_eq!(pt.find_by_name("web"), vec![id.clone()]);
+        assert_eq!(pt.find_by_name("frontend-dev-server"), vec![id.clone()]);
+        assert_eq!(
+            pt.resolve_daemon_id_with_namespace("frontend", "my-project")
+                .unwrap(),
+            id
+        );
+        assert_eq!(
+            pt.resolve_daemon_id_with_namespace("api", "my-project")
+                .unwrap(),
+            DaemonId::new("my-project", "api")
+        );
+    }
+
+    #[test]
+    fn test_alias_conflicts_rejected() {
+        let path = Path::new("/tmp/my-project/pitchfork.toml");
+        let err = PitchforkToml::parse_str(
+            r#"
+[daemons.web]
+run = "npm run dev"
+
+[daemons.api]
+run = "node server.js"
+aliases = ["web"]
+"#,
+            path,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("conflicts with daemon 'web'"),
+            "{err}"
+        );
+
+        let err = PitchforkToml::parse_str(
+            r#"
+[daemons.web]
+run = "npm run dev"
+aliases = ["front"]
+
+[daemons.api]
+run = "node server.js"
+aliases = ["front"]
+"#,
+            path,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("conflicts with an alias"), "{err}");
+
+        assert!(
+            PitchforkToml::parse_str(
+                "[daemons.web]\nrun = \"npm run dev\"\naliases = [\"a/b\"]\n",
+                path,
+            )
+            .is_err()
+        );
+    }
 }
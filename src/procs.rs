@@ -3,7 +3,7 @@ use crate::Result;
 use crate::settings::settings;
 use miette::IntoDiagnostic;
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 #[cfg(target_os = "linux")]
 use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
 #[cfg(windows)]
@@ -25,6 +25,9 @@ type ProcessNames = HashMap<u32, (String, Option<String>)>;
 
 pub struct Procs {
     system: Mutex<sysinfo::System>,
+    /// PIDs pitchfork sent a signal to, so their exit isn't mistaken for an
+    /// outside kill
+    signalled: Mutex<HashSet<u32>>,
 }
 
 pub static PROCS: Lazy<Procs> = Lazy::new(Procs::new);
@@ -51,9 +54,26 @@ impl Procs {
         // PIDs) or refresh_processes() (for full-system stats) explicitly.
         Self {
             system: Mutex::new(sysinfo::System::new()),
+            signalled: Mutex::new(HashSet::new()),
         }
     }
 
+    fn mark_signalled(&self, pid: u32) {
+        self.signalled
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(pid);
+    }
+
+    /// Whether pitchfork signalled `pid` itself. Forgets the PID, so call
+    /// this once, after the process has exited.
+    pub fn take_signalled(&self, pid: u32) -> bool {
+        self.signalled
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&pid)
+    }
+
     fn lock_system(&self) -> std::sync::MutexGuard<'_, sysinfo::System> {
         self.system.lock().unwrap_or_else(|poisoned| {
             warn!("System mutex was poisoned, recovering");
@@ -106,15 +126,18 @@ impl Procs {
     }
 
     /// Walk the /proc tree to find all descendant PIDs.
-    /// Kept for diagnostics/status display; no longer used in the kill path.
-    #[allow(dead_code)]
+    /// Used for OOM detection and diagnostics; not in the kill path.
     pub fn all_children(&self, pid: u32) -> Vec<u32> {
+        #[cfg(windows)]
+        if let Some(members) = crate::job_object::members(pid) {
+            return members.into_iter().filter(|&m| m != pid).collect();
+        }
         let system = self.lock_system();
         let all = system.processes();
         let mut children = vec![];
         for (child_pid, process) in all {
             let mut process = process;
-            while let Some(parent) = process.parent() {
+            while let Some(parent) = live_parent(process, all) {
                 if parent == sysinfo::Pid::from_u32(pid) {
                     children.push(child_pid.as_u32());
                     break;
@@ -138,6 +161,9 @@ impl Procs {
         let mut process_info: ProcessNames = HashMap::new();
 
         for (pid, proc) in all {
+            if proc.thread_kind().is_some() {
+                continue;
+            }
             let pid_u32 = pid.as_u32();
             process_info.insert(
                 pid_u32,
@@ -147,7 +173,7 @@ impl Procs {
                 ),
             );
 
-            if let Some(ppid) = proc.parent() {
+            if let Some(ppid) = live_parent(proc, all) {
                 parent_to_children
                     .entry(ppid.as_u32())
                     .or_default()
@@ -163,6 +189,7 @@ impl Procs {
         stop_signal: i32,
         stop_timeout: Option<std::time::Duration>,
     ) -> Result<bool> {
+        self.mark_signalled(pid);
         tokio::task::spawn_blocking(move || {
             PROCS.kill_process_group(pid, stop_signal, stop_timeout, None)
         })
@@ -183,6 +210,7 @@ impl Procs {
         stop_signal: i32,
         stop_timeout: Option<std::time::Duration>,
     ) -> Result<bool> {
+        self.mark_signalled(pid);
         tokio::task::spawn_blocking(move || {
             PROCS.kill_process_group(pid, stop_signal, stop_timeout, Some(expected_start_time))
         })
@@ -354,7 +382,7 @@ impl Procs {
         }
 
         let exit_timeout = stop_timeout.unwrap_or_else(|| settings().supervisor_stop_timeout());
-        let checks = ((exit_timeout.as_millis().max(1) + 49) / 50) as usize;
+        let checks = exit_timeout.as_millis().max(1).div_ceil(50) as usize;
         for _ in 0..checks {
             if members.iter().all(|(_, pidfd)| !pidfd_is_running(pidfd)) {
                 return Ok(true);
@@ -415,6 +443,7 @@ impl Procs {
         stop_signal: i32,
         stop_timeout: Option<std::time::Duration>,
     ) -> Result<bool> {
+        self.mark_signalled(pid);
         tokio::task::spawn_blocking(move || PROCS.kill(pid, stop_signal, stop_timeout))
             .await
             .into_diagnostic()?
@@ -442,7 +471,14 @@ impl Procs {
         #[cfg(windows)]
         {
             let _ = (stop_signal, stop_timeout);
-            // Use taskkill /F /T to kill the entire process tree.
+            // Daemons spawned by this supervisor run in a job object, which
+            // also covers processes whose parent already exited.
+            if crate::job_object::terminate(pid) {
+                debug!("terminated job of process {pid}");
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                return Ok(true);
+            }
+            // Otherwise use taskkill /F /T to kill the entire process tree.
             // sysinfo's process.kill() only kills the main process, leaving
             // child processes (e.g. python3 spawned by sh -c) orphaned and
             // still holding ports. The /T flag kills all descendant processes.
@@ -613,18 +649,7 @@ impl Procs {
             .unwrap_or(0);
 
         // Build parent → children map once for all daemons
-        let mut children_map: std::collections::HashMap<sysinfo::Pid, Vec<sysinfo::Pid>> =
-            std::collections::HashMap::new();
-        for (child_pid, child) in processes {
-            // Skip Linux userland threads: they report the same memory as their parent process,
-            // so including them would cause massive double-counting.
-            if child.thread_kind().is_some() {
-                continue;
-            }
-            if let Some(ppid) = child.parent() {
-                children_map.entry(ppid).or_default().push(*child_pid);
-            }
-        }
+        let children_map = children_map(processes);
 
         pids.iter()
             .map(|&pid| {
@@ -640,23 +665,17 @@ impl Procs {
                     uptime_secs: now.saturating_sub(root.start_time()),
                     disk_read_bytes: root_disk.read_bytes,
                     disk_write_bytes: root_disk.written_bytes,
+                    process_count: 1,
                 };
 
-                // BFS from root_pid to find all descendants
-                let mut queue = std::collections::VecDeque::new();
-                if let Some(direct_children) = children_map.get(&root_pid) {
-                    queue.extend(direct_children);
-                }
-                while let Some(child_pid) = queue.pop_front() {
+                for child_pid in descendants(root_pid, &children_map) {
                     if let Some(child) = processes.get(&child_pid) {
                         let disk = child.disk_usage();
                         stats.cpu_percent += child.cpu_usage();
                         stats.memory_bytes += child.memory();
                         stats.disk_read_bytes += disk.read_bytes;
                         stats.disk_write_bytes += disk.written_bytes;
-                    }
-                    if let Some(grandchildren) = children_map.get(&child_pid) {
-                        queue.extend(grandchildren);
+                        stats.process_count += 1;
                     }
                 }
 
@@ -713,29 +732,19 @@ impl Procs {
             uptime_secs: now.saturating_sub(p.start_time()),
             disk_read_bytes: root_disk.read_bytes,
             disk_write_bytes: root_disk.written_bytes,
+            process_count: 1,
         };
 
-        let mut children_map: HashMap<sysinfo::Pid, Vec<sysinfo::Pid>> = HashMap::new();
-        for (child_pid, child) in processes {
-            if let Some(ppid) = child.parent() {
-                children_map.entry(ppid).or_default().push(*child_pid);
-            }
-        }
+        let children_map = children_map(processes);
 
-        let mut queue = std::collections::VecDeque::new();
-        if let Some(direct_children) = children_map.get(&root_pid) {
-            queue.extend(direct_children);
-        }
-        while let Some(child_pid) = queue.pop_front() {
+        for child_pid in descendants(root_pid, &children_map) {
             if let Some(child) = processes.get(&child_pid) {
                 let disk = child.disk_usage();
                 aggregate_stats.cpu_percent += child.cpu_usage();
                 aggregate_stats.memory_bytes += child.memory();
                 aggregate_stats.disk_read_bytes += disk.read_bytes;
                 aggregate_stats.disk_write_bytes += disk.written_bytes;
-            }
-            if let Some(grandchildren) = children_map.get(&child_pid) {
-                queue.extend(grandchildren);
+                aggregate_stats.process_count += 1;
             }
         }
 
@@ -749,6 +758,139 @@ impl Procs {
             thread_count: p.tasks().map(|t| t.len()).unwrap_or(0),
         })
     }
+
+    /// PIDs of a process tree, root first, each with its start time in
+    /// seconds since the epoch. Call [`Self::refresh_processes`] first.
+    pub fn process_tree(&self, pid: u32) -> Vec<(u32, u64)> {
+        let system = self.lock_system();
+        let processes = system.processes();
+        let root_pid = sysinfo::Pid::from_u32(pid);
+        let Some(root) = processes.get(&root_pid) else {
+            return vec![];
+        };
+        let children_map = children_map(processes);
+        let mut tree = vec![(pid, root.start_time())];
+        for child_pid in descendants(root_pid, &children_map) {
+            if let Some(child) = processes.get(&child_pid) {
+                tree.push((child_pid.as_u32(), child.start_time()));
+            }
+        }
+        tree
+    }
+
+    /// Per-process usage of a daemon's process tree, root first and each
+    /// process followed by its children. Unlike [`Self::get_stats`] nothing
+    /// is aggregated: every entry only counts the process itself.
+    pub fn get_process_breakdown(&self, pid: u32) -> Vec<ProcessUsage> {
+        let system = self.lock_system();
+        let processes = system.processes();
+        let root_pid = sysinfo::Pid::from_u32(pid);
+        if !processes.contains_key(&root_pid) {
+            return Vec::new();
+        }
+        let children_map = children_map(processes);
+
+        let mut order = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut stack = vec![(root_pid, 0)];
+        while let Some((pid, depth)) = stack.pop() {
+            if !seen.insert(pid) {
+                continue;
+            }
+            order.push((pid, depth));
+            if let Some(children) = children_map.get(&pid) {
+                let mut children = children.clone();
+                children.sort();
+                stack.extend(children.into_iter().rev().map(|c| (c, depth + 1)));
+            }
+        }
+        // Members of a Windows job object whose parent has exited are not in
+        // the tree; list them under the root
+        for pid in descendants(root_pid, &children_map) {
+            if seen.insert(pid) {
+                order.push((pid, 1));
+            }
+        }
+
+        order
+            .into_iter()
+            .filter_map(|(pid, depth)| {
+                let p = processes.get(&pid)?;
+                Some(ProcessUsage {
+                    pid: pid.as_u32(),
+                    name: p.name().to_string_lossy().to_string(),
+                    depth,
+                    cpu_percent: p.cpu_usage(),
+                    memory_bytes: p.memory(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parent → children map of all processes.
+///
+/// Linux userland threads are skipped: they report the same memory as their
+/// parent process, so including them would cause massive double-counting.
+fn children_map(
+    processes: &HashMap<sysinfo::Pid, sysinfo::Process>,
+) -> HashMap<sysinfo::Pid, Vec<sysinfo::Pid>> {
+    let mut children_map: HashMap<sysinfo::Pid, Vec<sysinfo::Pid>> = HashMap::new();
+    for (child_pid, child) in processes {
+        if child.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(ppid) = live_parent(child, processes) {
+            children_map.entry(ppid).or_default().push(*child_pid);
+        }
+    }
+    children_map
+}
+
+/// Parent of `process`, if it is still the process that started it.
+///
+/// Windows does not reparent orphans, so a process keeps the PID of its
+/// exited parent, which may since have been reused by an unrelated process.
+/// A parent that started after its child must be such a reuse.
+fn live_parent(
+    process: &sysinfo::Process,
+    processes: &HashMap<sysinfo::Pid, sysinfo::Process>,
+) -> Option<sysinfo::Pid> {
+    let ppid = process.parent()?;
+    let parent = processes.get(&ppid)?;
+    (parent.start_time() <= process.start_time()).then_some(ppid)
+}
+
+/// All descendants of `root`, breadth first.
+///
+/// On Windows the daemon's job object is used when it has one, since it also
+/// holds processes whose parent has exited.
+fn descendants(
+    root: sysinfo::Pid,
+    children_map: &HashMap<sysinfo::Pid, Vec<sysinfo::Pid>>,
+) -> Vec<sysinfo::Pid> {
+    #[cfg(windows)]
+    if let Some(members) = crate::job_object::members(root.as_u32()) {
+        return members
+            .into_iter()
+            .map(sysinfo::Pid::from_u32)
+            .filter(|&m| m != root)
+            .collect();
+    }
+    let mut found = Vec::new();
+    let mut queue: std::collections::VecDeque<_> = children_map
+        .get(&root)
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+    while let Some(pid) = queue.pop_front() {
+        found.push(pid);
+        if let Some(grandchildren) = children_map.get(&pid) {
+            queue.extend(grandchildren);
+        }
+    }
+    found
 }
 
 #[cfg(target_os = "linux")]
@@ -994,6 +1136,8 @@ pub struct ProcessStats {
     pub uptime_secs: u64,
     pub disk_read_bytes: u64,
     pub disk_write_bytes: u64,
+    /// Processes in the tree, including the root
+    pub process_count: usize,
 }
 
 impl ProcessStats {
@@ -1006,7 +1150,7 @@ impl ProcessStats {
     }
 
     pub fn uptime_display(&self) -> String {
-        format_duration(self.uptime_secs)
+        crate::ui::time::duration_secs(self.uptime_secs)
     }
 
     pub fn disk_read_display(&self) -> String {
@@ -1018,6 +1162,27 @@ impl ProcessStats {
     }
 }
 
+/// A single process of a daemon's tree, see [`Procs::get_process_breakdown`]
+#[derive(Debug, Clone)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    /// Distance from the daemon's root process
+    pub depth: usize,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+impl ProcessUsage {
+    pub fn memory_display(&self) -> String {
+        format_bytes(self.memory_bytes)
+    }
+
+    pub fn cpu_display(&self) -> String {
+        format!("{:.1}%", self.cpu_percent)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExtendedProcessStats {
     pub name: String,
@@ -1033,22 +1198,6 @@ fn format_bytes(bytes: u64) -> String {
     humanbyte::to_string(bytes, humanbyte::Format::IEC)
 }
 
-fn format_duration(secs: u64) -> String {
-    if secs < 60 {
-        format!("{secs}s")
-    } else if secs < 3600 {
-        format!("{}m {}s", secs / 60, secs % 60)
-    } else if secs < 86400 {
-        let hours = secs / 3600;
-        let mins = (secs % 3600) / 60;
-        format!("{hours}h {mins}m")
-    } else {
-        let days = secs / 86400;
-        let hours = (secs % 86400) / 3600;
-        format!("{days}d {hours}h")
-    }
-}
-
 fn format_bytes_per_sec(bytes: u64) -> String {
     format!("{}/s", humanbyte::to_string(bytes, humanbyte::Format::IEC))
 }
@@ -1280,5 +1429,16 @@ mod tests {
              descendant RSS: {descendant_memory}, reported RSS: {}",
             stats.memory_bytes
         );
+        assert_eq!(stats.process_count, 1 + child_pids.len());
+
+        let breakdown = procs.get_process_breakdown(parent_pid);
+        assert_eq!(breakdown[0].pid, parent_pid);
+        assert_eq!(breakdown[0].depth, 0);
+        assert_eq!(breakdown.len(), stats.process_count);
+        assert!(breakdown[1..].iter().all(|p| p.depth > 0));
+        assert_eq!(
+            breakdown.iter().map(|p| p.memory_bytes).sum::<u64>(),
+            stats.memory_bytes
+        );
     }
 }
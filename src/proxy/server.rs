@@ -1393,7 +1393,7 @@ async fn resolve_target(host: &str, tld: &str) -> ResolveResult {
         .iter()
         .filter(|(id, d)| {
             id.name() == daemon_name
-                && d.status.is_running()
+                && d.status.is_up()
                 && match &expected_namespace {
                     Some(ns) => id.namespace() == ns,
                     None => true,
@@ -1591,8 +1591,11 @@ async fn try_auto_start_inner(
         };
 
         if let Some(d) = daemons.get(daemon_id) {
-            if d.status.is_running() {
-                if let Some(port) = d.active_port.or_else(|| d.resolved_port.first().copied()) {
+            if d.status.is_up() {
+                // Keep polling while the daemon's ready check is still pending
+                if !d.status.is_starting()
+                    && let Some(port) = d.active_port.or_else(|| d.resolved_port.first().copied())
+                {
                     log::info!("Auto-start: daemon {daemon_id} is ready on port {port}");
                     return ResolveResult::Ready(port);
                 }
@@ -116,6 +116,10 @@ mod tests {
         assert_eq!(settings.general.interval, "10s");
         assert_eq!(settings.general.log_level, "info");
 
+        // Test logs settings
+        assert_eq!(settings.logs.rate_limit, 0);
+        assert_eq!(settings.logs.overflow, "drop");
+
         // Test IPC settings
         assert_eq!(settings.ipc.connect_attempts, 5);
         assert_eq!(settings.ipc.request_timeout, "5s");
@@ -197,6 +201,27 @@ bind_port = 8080
         assert_eq!(settings.tui.refresh_rate, "2s");
     }
 
+    #[test]
+    fn test_byte_sizes_accept_units() {
+        let toml_content = r#"
+[logs]
+rate_limit = "1MiB"
+
+[ipc]
+compress_threshold = 4096
+chunk_size = "2 MB"
+"#;
+
+        let settings: Settings = toml::from_str(toml_content).unwrap();
+        assert_eq!(settings.logs.rate_limit, 1024 * 1024);
+        assert_eq!(settings.ipc.compress_threshold, 4096);
+        assert_eq!(settings.ipc.chunk_size, 2_000_000);
+
+        let partial: SettingsPartial = toml::from_str("[logs]\nrate_limit = \"10KiB\"").unwrap();
+        assert_eq!(partial.logs.rate_limit, Some(10 * 1024));
+        assert!(toml::from_str::<SettingsPartial>("[logs]\nrate_limit = \"lots\"").is_err());
+    }
+
     #[test]
     fn test_partial_config_uses_defaults() {
         // Test that missing sections use defaults
@@ -335,6 +360,19 @@ log_level = "warn"
             settings.supervisor_http_client_timeout(),
             Duration::from_secs(5)
         );
+        assert_eq!(
+            settings.supervisor_health_check_interval(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            settings.supervisor_drift_check_interval(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(settings.supervisor_hook_timeout(), Duration::from_secs(300));
+        assert_eq!(
+            settings.supervisor_ready_cmd_timeout(),
+            Duration::from_secs(30)
+        );
     }
 
     #[test]
@@ -1,5 +1,6 @@
-use crate::daemon::Daemon;
+use crate::daemon::{Daemon, EXIT_HISTORY_LEN, ExitRecord, STARTUP_HISTORY_LEN, StartupRecord};
 use crate::daemon_id::DaemonId;
+use crate::daemon_status::DaemonStatus;
 use crate::error::FileError;
 use crate::{Result, env};
 use once_cell::sync::Lazy;
@@ -11,10 +12,21 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct StateFile {
-    #[serde(default)]
+    /// Daemons by ID. Those started by a user of a shared supervisor are
+    /// kept in that user's `[users.<name>]` namespace in the file.
+    #[serde(flatten, with = "user_namespaces")]
     pub daemons: BTreeMap<DaemonId, Daemon>,
     #[serde(default)]
     pub disabled: BTreeSet<DaemonId>,
+    /// Daemons that may only be started manually: autostart on `cd`, cron
+    /// and boot start skip them, `pitchfork start` does not.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
+    pub auto_disabled: BTreeSet<DaemonId>,
+    /// Set by `pitchfork supervisor pause`: no daemon is started or
+    /// restarted on its own (autostart, cron, boot start, retries, file
+    /// watches) until `pitchfork supervisor resume`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paused: bool,
     #[serde(default)]
     pub shell_dirs: BTreeMap<String, PathBuf>,
     /// Project sessions keyed by host PID (as string, matching `shell_dirs`)
@@ -48,6 +60,8 @@ impl StateFile {
         Self {
             daemons: Default::default(),
             disabled: Default::default(),
+            auto_disabled: Default::default(),
+            paused: false,
             shell_dirs: Default::default(),
             project_sessions: Default::default(),
             path,
@@ -250,9 +264,40 @@ impl StateFile {
         }
     }
 
+    /// Move a daemon's entry and its disabled flags to a new ID and mark the
+    /// state dirty. Fails without changing anything if the new ID already
+    /// has an entry or flag. Returns true if anything changed.
+    pub fn rename_daemon(&mut self, from: &DaemonId, to: &DaemonId) -> Result<bool> {
+        if self.daemons.contains_key(to) {
+            miette::bail!("daemon {to} already has saved state; remove it with `pitchfork clean`");
+        }
+        if self.disabled.contains(to) || self.auto_disabled.contains(to) {
+            miette::bail!("daemon {to} is marked disabled; enable it first");
+        }
+        let mut changed = false;
+        if let Some(mut daemon) = self.daemons.remove(from) {
+            daemon.id = to.clone();
+            self.daemons.insert(to.clone(), daemon);
+            changed = true;
+        }
+        if self.disabled.remove(from) {
+            self.disabled.insert(to.clone());
+            changed = true;
+        }
+        if self.auto_disabled.remove(from) {
+            self.auto_disabled.insert(to.clone());
+            changed = true;
+        }
+        if changed {
+            self.mark_dirty();
+        }
+        Ok(changed)
+    }
+
     /// Disable a daemon (add to disabled set) and mark the state dirty.
     /// Returns true if the daemon was not already disabled.
     pub fn disable_daemon(&mut self, id: &DaemonId) -> bool {
+        self.auto_disabled.remove(id);
         let inserted = self.disabled.insert(id.clone());
         if inserted {
             self.mark_dirty();
@@ -260,16 +305,45 @@ impl StateFile {
         inserted
     }
 
-    /// Enable a daemon (remove from disabled set) and mark the state dirty.
-    /// Returns true if the daemon was previously disabled.
+    /// Disable only automatic starts of a daemon (add to the auto-disabled
+    /// set, replacing a full disable) and mark the state dirty.
+    /// Returns true if the daemon was not already auto-disabled.
+    pub fn disable_daemon_autostart(&mut self, id: &DaemonId) -> bool {
+        self.disabled.remove(id);
+        let inserted = self.auto_disabled.insert(id.clone());
+        if inserted {
+            self.mark_dirty();
+        }
+        inserted
+    }
+
+    /// Enable a daemon (remove from both disabled sets) and mark the state
+    /// dirty. Returns true if the daemon was previously disabled.
     pub fn enable_daemon(&mut self, id: &DaemonId) -> bool {
-        let removed = self.disabled.remove(id);
+        let removed = self.disabled.remove(id) | self.auto_disabled.remove(id);
         if removed {
             self.mark_dirty();
         }
         removed
     }
 
+    /// Whether the supervisor or shell hook may start a daemon on its own
+    /// (autostart, cron, boot start).
+    pub fn autostart_allowed(&self, id: &DaemonId) -> bool {
+        !self.paused && !self.disabled.contains(id) && !self.auto_disabled.contains(id)
+    }
+
+    /// Pause or resume automatic starts and restarts of all daemons and
+    /// mark the state dirty. Returns true if the state changed.
+    pub fn set_paused(&mut self, paused: bool) -> bool {
+        let changed = self.paused != paused;
+        if changed {
+            self.paused = paused;
+            self.mark_dirty();
+        }
+        changed
+    }
+
     /// Set the active port for a daemon and mark the state dirty.
     /// Returns true if the daemon was found and updated.
     pub fn set_active_port(&mut self, id: &DaemonId, port: u16) -> bool {
@@ -282,6 +356,124 @@ impl StateFile {
         }
     }
 
+    /// Append an exit to a daemon's history, dropping the oldest beyond
+    /// `EXIT_HISTORY_LEN`, and mark the state dirty.
+    pub fn record_exit(&mut self, id: &DaemonId, exit: ExitRecord) {
+        if let Some(d) = self.daemons.get_mut(id) {
+            d.exits.push(exit);
+            let excess = d.exits.len().saturating_sub(EXIT_HISTORY_LEN);
+            d.exits.drain(..excess);
+            self.mark_dirty();
+        }
+    }
+
+    /// Append a ready run's timings to a daemon's history, dropping the
+    /// oldest beyond `STARTUP_HISTORY_LEN`, and mark the state dirty.
+    pub fn record_startup(&mut self, id: &DaemonId, startup: StartupRecord) {
+        if let Some(d) = self.daemons.get_mut(id) {
+            d.startups.push(startup);
+            let excess = d.startups.len().saturating_sub(STARTUP_HISTORY_LEN);
+            d.startups.drain(..excess);
+            self.mark_dirty();
+        }
+    }
+
+    /// Move a live daemon between `starting`, `running` and `unhealthy`.
+    /// Only applies while `pid` is still the daemon's process and it is up, so
+    /// a late probe result can't revive a daemon that was stopped or
+    /// restarted in the meantime. Returns true if the status changed.
+    pub fn set_up_status(&mut self, id: &DaemonId, pid: u32, status: DaemonStatus) -> bool {
+        if let Some(d) = self.daemons.get_mut(id)
+            && d.pid == Some(pid)
+            && d.status.is_up()
+            && d.status.to_string() != status.to_string()
+        {
+            d.status = status;
+            self.mark_dirty();
+            return true;
+        }
+        false
+    }
+
+    /// Record how many output lines `log_filter` dropped for a daemon's
+    /// current run, marking the state dirty if the count changed.
+    pub fn set_log_lines_filtered(&mut self, id: &DaemonId, count: u64) {
+        if let Some(d) = self.daemons.get_mut(id)
+            && d.log_lines_filtered != count
+        {
+            d.log_lines_filtered = count;
+            self.mark_dirty();
+        }
+    }
+
+    /// Record how many output lines `logs.rate_limit` dropped for a daemon's
+    /// current run, marking the state dirty if the count changed.
+    pub fn set_log_lines_dropped(&mut self, id: &DaemonId, count: u64) {
+        if let Some(d) = self.daemons.get_mut(id)
+            && d.log_lines_dropped != count
+        {
+            d.log_lines_dropped = count;
+            self.mark_dirty();
+        }
+    }
+
+    /// Set or clear the alert a daemon's process is raising, marking the
+    /// state dirty if it changed. Only applies while `pid` is still the
+    /// daemon's process. Returns true if the alert changed.
+    pub fn set_alert(&mut self, id: &DaemonId, pid: u32, alert: Option<String>) -> bool {
+        if let Some(d) = self.daemons.get_mut(id)
+            && d.pid == Some(pid)
+            && d.alert != alert
+        {
+            d.alert = alert;
+            self.mark_dirty();
+            return true;
+        }
+        false
+    }
+
+    /// Mark a daemon's process as drifted from what's on disk, as long as
+    /// `pid` is still its process. Returns true if it changed.
+    pub fn set_drift(&mut self, id: &DaemonId, pid: u32, drift: Option<String>) -> bool {
+        if let Some(d) = self.daemons.get_mut(id)
+            && d.pid == Some(pid)
+            && d.drift != drift
+        {
+            d.drift = drift;
+            self.mark_dirty();
+            return true;
+        }
+        false
+    }
+
+    /// Set why a daemon's watch directories are polled rather than watched
+    /// natively. Returns true if it changed.
+    pub fn set_watch_fallback(&mut self, id: &DaemonId, reason: Option<String>) -> bool {
+        if let Some(d) = self.daemons.get_mut(id)
+            && d.watch_fallback != reason
+        {
+            d.watch_fallback = reason;
+            self.mark_dirty();
+            return true;
+        }
+        false
+    }
+
+    /// Drop a daemon's one-time env override and mark the state dirty.
+    /// Returns true if the daemon had an override.
+    pub fn clear_env_override(&mut self, id: &DaemonId) -> bool {
+        if let Some(d) = self.daemons.get_mut(id)
+            && (d.env_override.is_some() || d.env_override_until.is_some())
+        {
+            d.env_override = None;
+            d.env_override_until = None;
+            self.mark_dirty();
+            true
+        } else {
+            false
+        }
+    }
+
     /// Clear the active port for a daemon and mark the state dirty.
     /// Returns true if the daemon was found and updated.
     pub fn clear_active_port(&mut self, id: &DaemonId) -> bool {
@@ -398,6 +590,9 @@ impl StateFile {
     /// avoid unnecessary I/O. Used during shutdown and migration where async
     /// flushing is not available.
     pub fn write(&self) -> Result<()> {
+        if crate::fault::fail_state_write() {
+            miette::bail!("fault injection: failed to write {}", self.path.display());
+        }
         let canonical_path = normalized_lock_path(&self.path);
         let _lock = xx::fslock::get(&canonical_path, false)?;
         let raw = toml::to_string(self).map_err(|e| FileError::SerializeError {
@@ -457,6 +652,66 @@ impl StateFile {
     }
 }
 
+/// (De)serialize [`StateFile::daemons`] with each owner's daemons under
+/// `users.<owner>.daemons` and the rest under `daemons`
+mod user_namespaces {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Namespaced<D> {
+        #[serde(default = "BTreeMap::new")]
+        daemons: BTreeMap<DaemonId, D>,
+        #[serde(default = "BTreeMap::new", skip_serializing_if = "BTreeMap::is_empty")]
+        users: BTreeMap<String, UserNamespace<D>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct UserNamespace<D> {
+        #[serde(default = "BTreeMap::new")]
+        daemons: BTreeMap<DaemonId, D>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        daemons: &BTreeMap<DaemonId, Daemon>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut namespaced = Namespaced {
+            daemons: BTreeMap::new(),
+            users: BTreeMap::new(),
+        };
+        for (id, daemon) in daemons {
+            let namespace = match &daemon.owner {
+                Some(owner) => {
+                    &mut namespaced
+                        .users
+                        .entry(owner.clone())
+                        .or_insert_with(|| UserNamespace {
+                            daemons: BTreeMap::new(),
+                        })
+                        .daemons
+                }
+                None => &mut namespaced.daemons,
+            };
+            namespace.insert(id.clone(), daemon);
+        }
+        namespaced.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<BTreeMap<DaemonId, Daemon>, D::Error> {
+        let Namespaced { mut daemons, users } = Namespaced::<Daemon>::deserialize(deserializer)?;
+        for (owner, namespace) in users {
+            for (id, mut daemon) in namespace.daemons {
+                daemon.owner = Some(owner.clone());
+                daemons.insert(id, daemon);
+            }
+        }
+        Ok(daemons)
+    }
+}
+
 fn normalized_lock_path(path: &Path) -> PathBuf {
     if let Ok(canonical) = path.canonicalize() {
         return canonical;
@@ -477,6 +732,42 @@ mod tests {
     use super::*;
     use crate::daemon_status::DaemonStatus;
 
+    #[test]
+    fn test_rename_daemon() {
+        let mut state = StateFile::new(PathBuf::from("/tmp/test.toml"));
+        let old = DaemonId::new("proj", "api");
+        let new = DaemonId::new("proj", "api-v2");
+        state.insert_daemon(
+            &old,
+            Daemon {
+                id: old.clone(),
+                resolved_port: vec![3000],
+                ..Daemon::default()
+            },
+        );
+        state.disable_daemon_autostart(&old);
+
+        assert!(state.rename_daemon(&old, &new).unwrap());
+        assert!(!state.daemons.contains_key(&old));
+        let daemon = &state.daemons[&new];
+        assert_eq!(daemon.id, new);
+        assert_eq!(daemon.resolved_port, vec![3000]);
+        assert_eq!(state.auto_disabled, BTreeSet::from([new.clone()]));
+        assert!(
+            !state
+                .rename_daemon(&old, &DaemonId::new("proj", "other"))
+                .unwrap()
+        );
+
+        // Whatever the new ID already had is kept, and nothing is moved
+        let other = DaemonId::new("proj", "other");
+        state.disable_daemon(&other);
+        assert!(state.rename_daemon(&new, &other).is_err());
+        assert!(state.rename_daemon(&other, &new).is_err());
+        assert!(state.daemons.contains_key(&new));
+        assert!(state.disabled.contains(&other));
+    }
+
     #[test]
     fn test_state_file_toml_roundtrip_stopped() {
         let mut state = StateFile::new(PathBuf::from("/tmp/test.toml"));
@@ -510,6 +801,35 @@ mod tests {
         assert_eq!(daemon.user.as_deref(), Some("postgres"));
     }
 
+    #[test]
+    fn test_daemons_namespaced_by_owner() {
+        let mut state = StateFile::new(PathBuf::from("/tmp/test.toml"));
+        for owner in [None, Some("alice"), Some("bob")] {
+            let id = DaemonId::new("project", owner.unwrap_or("boot"));
+            let daemon = Daemon {
+                id: id.clone(),
+                owner: owner.map(str::to_string),
+                ..Daemon::default()
+            };
+            state.daemons.insert(id, daemon);
+        }
+
+        let toml_str = toml::to_string(&state).unwrap();
+        assert!(toml_str.contains("[daemons.\"project/boot\"]"));
+        assert!(toml_str.contains("[users.alice.daemons.\"project/alice\"]"));
+        assert!(toml_str.contains("[users.bob.daemons.\"project/bob\"]"));
+
+        let parsed: StateFile = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.daemons.len(), 3);
+        let owner = |name: &str| {
+            let id = DaemonId::new("project", name);
+            parsed.daemons[&id].owner.clone()
+        };
+        assert_eq!(owner("boot"), None);
+        assert_eq!(owner("alice").as_deref(), Some("alice"));
+        assert_eq!(owner("bob").as_deref(), Some("bob"));
+    }
+
     #[test]
     fn test_looks_like_old_format_bare_names() {
         let old = r#"
@@ -724,4 +1044,103 @@ status = "stopped"
         assert!(!state.project_sessions.contains_key("5678"));
         assert_eq!(state.iter_project_sessions().len(), 2);
     }
+
+    #[test]
+    fn test_paused_blocks_autostart() {
+        let mut state = StateFile::new(PathBuf::from("/tmp/state.toml"));
+        let id = DaemonId::new("project", "api");
+        assert!(state.set_paused(true));
+        assert!(!state.set_paused(true));
+        assert!(!state.autostart_allowed(&id));
+
+        let raw = toml::to_string(&state).unwrap();
+        assert!(raw.contains("paused = true"));
+        let parsed: StateFile = toml::from_str(&raw).unwrap();
+        assert!(parsed.paused);
+
+        assert!(state.set_paused(false));
+        assert!(state.autostart_allowed(&id));
+        assert!(!toml::to_string(&state).unwrap().contains("paused"));
+    }
+
+    #[test]
+    fn test_disable_autostart_only() {
+        let mut state = StateFile::new(PathBuf::from("/tmp/state.toml"));
+        let id = DaemonId::new("project", "api");
+        assert!(state.autostart_allowed(&id));
+
+        assert!(state.disable_daemon_autostart(&id));
+        assert!(!state.autostart_allowed(&id));
+        assert!(!state.disabled.contains(&id));
+
+        // A full disable replaces the autostart-only one
+        assert!(state.disable_daemon(&id));
+        assert!(!state.auto_disabled.contains(&id));
+
+        assert!(state.enable_daemon(&id));
+        assert!(state.autostart_allowed(&id));
+        assert!(!state.enable_daemon(&id));
+
+        // Older state files without the set still parse
+        let parsed: StateFile = toml::from_str("disabled = []").unwrap();
+        assert!(parsed.auto_disabled.is_empty());
+    }
+
+    #[test]
+    fn test_record_exit_keeps_bounded_history() {
+        let mut state = StateFile::new(PathBuf::from("/tmp/test.toml"));
+        let id = DaemonId::new("project", "api");
+        state.daemons.insert(
+            id.clone(),
+            Daemon {
+                id: id.clone(),
+                ..Daemon::default()
+            },
+        );
+        for code in 0..EXIT_HISTORY_LEN as i32 + 3 {
+            state.record_exit(
+                &id,
+                ExitRecord {
+                    at: chrono::Local::now(),
+                    code,
+                    signal: None,
+                    runtime_secs: 1,
+                    reason: "fail".into(),
+                },
+            );
+        }
+        let exits = &state.daemons[&id].exits;
+        assert_eq!(exits.len(), EXIT_HISTORY_LEN);
+        assert_eq!(exits[0].code, 3);
+        assert_eq!(exits.last().unwrap().code, EXIT_HISTORY_LEN as i32 + 2);
+
+        let parsed: StateFile = toml::from_str(&toml::to_string(&state).unwrap()).unwrap();
+        assert_eq!(parsed.daemons[&id].exits, *exits);
+    }
+
+    #[test]
+    fn test_set_up_status() {
+        let mut state = StateFile::new(PathBuf::from("/tmp/test.toml"));
+        let id = DaemonId::new("project", "api");
+        state.daemons.insert(
+            id.clone(),
+            Daemon {
+                id: id.clone(),
+                pid: Some(42),
+                status: DaemonStatus::Starting,
+                ..Daemon::default()
+            },
+        );
+        // A stale pid is ignored
+        assert!(!state.set_up_status(&id, 7, DaemonStatus::Running));
+        assert!(state.set_up_status(&id, 42, DaemonStatus::Running));
+        assert!(!state.set_up_status(&id, 42, DaemonStatus::Running));
+        assert!(state.set_up_status(&id, 42, DaemonStatus::Unhealthy));
+        assert!(state.daemons[&id].status.is_unhealthy());
+
+        // Once stopped, probes can't bring it back
+        state.daemons.get_mut(&id).unwrap().status = DaemonStatus::Stopped;
+        assert!(!state.set_up_status(&id, 42, DaemonStatus::Running));
+        assert!(state.daemons[&id].status.is_stopped());
+    }
 }
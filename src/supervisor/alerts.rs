@@ -0,0 +1,144 @@
+//! Resource usage alerts (`[daemons.<name>.alerts]`)
+//!
+//! The interval watcher samples each daemon's memory and CPU usage. When a
+//! sample is over one of the daemon's `alerts` thresholds, the breach starts;
+//! once it has lasted for the alert's `for` duration the alert fires: the
+//! daemon is marked with the alert in list/status/TUI/web, a notification is
+//! queued and the `on_alert` hook runs. The daemon itself is left running.
+//! The first sample back under every threshold clears the alert.
+
+use super::hooks::{HookType, fire_hook};
+use super::{Supervisor, events};
+use crate::daemon::Daemon;
+use crate::daemon_id::DaemonId;
+use crate::pitchfork_toml::Alerts;
+use crate::procs::ProcessStats;
+use std::collections::HashMap;
+use tokio::time::Instant;
+
+/// A daemon process currently over one of its alert thresholds
+#[derive(Debug)]
+pub(crate) struct Breach {
+    pid: u32,
+    since: Instant,
+    fired: bool,
+}
+
+/// Describe the first threshold `stats` is over, e.g. "memory 1.6GB above 1.5GB"
+fn breached(alerts: &Alerts, stats: &ProcessStats) -> Option<String> {
+    if let Some(limit) = alerts.memory_above
+        && stats.memory_bytes > limit.0
+    {
+        return Some(format!("memory {} above {limit}", stats.memory_display()));
+    }
+    if let Some(limit) = alerts.cpu_above
+        && stats.cpu_percent > limit.0
+    {
+        return Some(format!("cpu {} above {limit}", stats.cpu_display()));
+    }
+    None
+}
+
+impl Supervisor {
+    /// Evaluate one stats sample against a daemon's alert thresholds.
+    pub(crate) async fn check_alerts(
+        &self,
+        daemon: &Daemon,
+        pid: u32,
+        stats: &ProcessStats,
+        breaches: &mut HashMap<DaemonId, Breach>,
+    ) {
+        let id = &daemon.id;
+        let alert = daemon
+            .alerts
+            .as_ref()
+            .and_then(|alerts| breached(alerts, stats));
+        let Some(alert) = alert else {
+            if breaches.remove(id).is_some_and(|b| b.fired)
+                && self.state_file.lock().await.set_alert(id, pid, None)
+            {
+                info!("daemon {id} is back under its alert thresholds");
+                events::record(id, "alert_cleared", None);
+            }
+            return;
+        };
+
+        let now = Instant::now();
+        let breach = breaches.entry(id.clone()).or_insert(Breach {
+            pid,
+            since: now,
+            fired: false,
+        });
+        // A restarted daemon starts its breach over
+        if breach.pid != pid {
+            *breach = Breach {
+                pid,
+                since: now,
+                fired: false,
+            };
+        }
+        let duration = daemon.alerts.and_then(|a| a.duration).unwrap_or_default();
+        if breach.fired || now.duration_since(breach.since) < duration {
+            return;
+        }
+        breach.fired = true;
+
+        warn!("daemon {id} (pid {pid}) alert: {alert}");
+        self.state_file
+            .lock()
+            .await
+            .set_alert(id, pid, Some(alert.clone()));
+        self.add_notification(
+            Some(id),
+            log::LevelFilter::Warn,
+            format!("daemon {id}: {alert}"),
+        )
+        .await;
+        fire_hook(
+            HookType::OnAlert,
+            id.clone(),
+            daemon.dir.clone().unwrap_or_default(),
+            daemon.retry_count,
+            daemon.effective_env(),
+            vec![("PITCHFORK_ALERT".to_string(), alert)],
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitchfork_toml::{CpuLimit, MemoryLimit};
+
+    fn stats(memory_bytes: u64, cpu_percent: f32) -> ProcessStats {
+        ProcessStats {
+            cpu_percent,
+            memory_bytes,
+            uptime_secs: 0,
+            disk_read_bytes: 0,
+            disk_write_bytes: 0,
+            process_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_breached() {
+        let alerts = Alerts {
+            memory_above: Some(MemoryLimit(1000)),
+            cpu_above: Some(CpuLimit(50.0)),
+            duration: None,
+        };
+        assert_eq!(breached(&alerts, &stats(1000, 50.0)), None);
+        assert!(
+            breached(&alerts, &stats(2000, 10.0))
+                .unwrap()
+                .starts_with("memory ")
+        );
+        assert_eq!(
+            breached(&alerts, &stats(10, 75.0)).as_deref(),
+            Some("cpu 75.0% above 50%")
+        );
+        assert_eq!(breached(&Alerts::default(), &stats(u64::MAX, 1000.0)), None);
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Handles automatic stopping of daemons when shells leave directories,
 //! and starting daemons configured with `boot_start = true`.
+//!
+//! Directory changes reported by the shell hook are queued and applied in
+//! batches, so a burst of `cd`s only evaluates autostops once per shell and
+//! never makes the hook wait on a busy supervisor.
 
-use super::Supervisor;
+use super::{SUPERVISOR, Supervisor, users};
 use crate::Result;
 use crate::daemon_id::DaemonId;
 use crate::ipc::IpcResponse;
@@ -11,8 +15,17 @@ use crate::pitchfork_toml::PitchforkToml;
 use crate::settings::settings;
 use log::LevelFilter::Info;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tokio::time;
 
+/// Quiet period after the last directory change before the batch is applied
+const SHELL_DIR_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Upper bound on how long a continuous stream of directory changes can
+/// delay applying the batch
+const SHELL_DIR_MAX_DELAY: Duration = Duration::from_secs(1);
+
 /// Whether `path` is equal to or nested inside `base`.
 ///
 /// Tries a direct `starts_with` first (fast path for paths that share the same
@@ -41,15 +54,119 @@ fn canonicalize_pair(a: &Path, b: &Path) -> Option<(PathBuf, PathBuf)> {
 }
 
 impl Supervisor {
-    /// Handle shell leaving a directory - schedule autostops for daemons
-    pub(crate) async fn leave_dir(&self, dir: &Path) -> Result<()> {
+    /// Record the directory a shell moved to, affecting only `user`'s daemons
+    /// (all for `None`). Only the latest directory per shell is kept, going
+    /// by `seq` when the hook sends one, since hooks run in the background
+    /// and can arrive out of order; [`Self::shell_dir_watch`] applies it
+    /// shortly after.
+    pub(crate) async fn queue_shell_dir(
+        &self,
+        shell_pid: u32,
+        dir: PathBuf,
+        seq: Option<u64>,
+        user: Option<String>,
+    ) {
+        if let Some(seq) = seq {
+            let mut seqs = self.shell_dir_seqs.lock().await;
+            let latest = seqs.entry(shell_pid).or_default();
+            if seq <= *latest {
+                debug!(
+                    "dropped shell dir {} for pid {shell_pid}: change {seq} arrived after {latest}",
+                    dir.display()
+                );
+                return;
+            }
+            *latest = seq;
+        }
+        trace!("queued shell dir for pid {shell_pid}: {}", dir.display());
+        self.pending_shell_dirs
+            .lock()
+            .await
+            .insert(shell_pid, (dir, user));
+        self.shell_dir_changed.notify_one();
+    }
+
+    /// Apply queued shell directory changes once they settle: wait until no
+    /// new change has arrived for [`SHELL_DIR_DEBOUNCE`] (but no longer than
+    /// [`SHELL_DIR_MAX_DELAY`]), then apply the whole batch.
+    pub(crate) fn shell_dir_watch(&self) {
+        tokio::spawn(async move {
+            loop {
+                SUPERVISOR.shell_dir_changed.notified().await;
+                let deadline = time::Instant::now() + SHELL_DIR_MAX_DELAY;
+                loop {
+                    let quiet_until = (time::Instant::now() + SHELL_DIR_DEBOUNCE).min(deadline);
+                    tokio::select! {
+                        _ = SUPERVISOR.shell_dir_changed.notified() => {}
+                        _ = time::sleep_until(quiet_until) => break,
+                    }
+                }
+                if let Err(err) = SUPERVISOR.apply_shell_dirs().await {
+                    error!("failed to apply shell directory changes: {err}");
+                    // Failed changes are requeued; don't retry them in a tight loop
+                    time::sleep(SHELL_DIR_MAX_DELAY).await;
+                }
+            }
+        });
+    }
+
+    /// Apply all queued shell directory changes.
+    ///
+    /// Every shell's new directory is recorded (and cancels autostops there)
+    /// before any previous directory is left, so two shells swapping
+    /// directories in the same batch don't schedule autostops for each other.
+    /// Changes that fail to apply are queued again and retried shortly after.
+    async fn apply_shell_dirs(&self) -> Result<()> {
+        let pending = std::mem::take(&mut *self.pending_shell_dirs.lock().await);
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut left = vec![];
+        let mut failed = vec![];
+        let mut first_err = None;
+        for (shell_pid, (dir, user)) in pending {
+            let prev = self.get_shell_dir(shell_pid).await;
+            if let Err(err) = self.set_shell_dir(shell_pid, dir.clone()).await {
+                failed.push((shell_pid, (dir, user)));
+                first_err.get_or_insert(err);
+                continue;
+            }
+            // Cancel any pending autostops for daemons in the new directory
+            self.cancel_pending_autostops_for_dir(&dir, user.as_deref())
+                .await;
+            if let Some(prev) = prev
+                && prev != dir
+            {
+                left.push((prev, user));
+            }
+        }
+        for (prev, user) in left {
+            if let Err(err) = self.leave_dir(&prev, user.as_deref()).await {
+                first_err.get_or_insert(err);
+            }
+        }
+        if !failed.is_empty() {
+            // A newer directory reported by the same shell meanwhile wins
+            let mut queued = self.pending_shell_dirs.lock().await;
+            for (shell_pid, change) in failed {
+                queued.entry(shell_pid).or_insert(change);
+            }
+            self.shell_dir_changed.notify_one();
+        }
+        self.refresh().await?;
+        first_err.map_or(Ok(()), Err)
+    }
+
+    /// Handle shell leaving a directory - schedule autostops for daemons of
+    /// `user` (all daemons for `None`)
+    pub(crate) async fn leave_dir(&self, dir: &Path, user: Option<&str>) -> Result<()> {
         debug!("left dir {}", dir.display());
         let active_dirs = self.get_active_directories().await;
         debug!("active directories after leaving {dir:?}: {active_dirs:?}");
         let autostop_delay = settings().general_autostop_delay();
 
         for daemon in self.active_daemons().await {
-            if !daemon.autostop {
+            if !daemon.autostop || !users::owned_by(&daemon, user) {
                 continue;
             }
             // if this daemon's dir is within the left dir
@@ -67,8 +184,12 @@ impl Supervisor {
                         // No delay configured, stop immediately
                         info!("autostopping {daemon}");
                         self.stop(&daemon.id).await?;
-                        self.add_notification(Info, format!("autostopped {daemon}"))
-                            .await;
+                        self.add_notification(
+                            Some(&daemon.id),
+                            Info,
+                            format!("autostopped {daemon}"),
+                        )
+                        .await;
                     } else {
                         // Schedule autostop with delay
                         let stop_at = time::Instant::now() + autostop_delay;
@@ -87,10 +208,11 @@ impl Supervisor {
         Ok(())
     }
 
-    /// Cancel any pending autostop for daemons in the given directory
+    /// Cancel any pending autostop for daemons in the given directory (only
+    /// `user`'s own, or all for `None`)
     /// Also cancels autostops for daemons in parent directories (e.g., entering /project/subdir
     /// cancels pending autostop for daemon in /project)
-    pub(crate) async fn cancel_pending_autostops_for_dir(&self, dir: &Path) {
+    pub(crate) async fn cancel_pending_autostops_for_dir(&self, dir: &Path, user: Option<&str>) {
         let mut pending = self.pending_autostops.lock().await;
         let daemons_to_cancel: Vec<DaemonId> = {
             let state_file = self.state_file.lock().await;
@@ -98,11 +220,12 @@ impl Supervisor {
                 .daemons
                 .iter()
                 .filter(|(_id, d)| {
-                    d.dir.as_ref().is_some_and(|daemon_dir| {
-                        // Cancel if entering a directory inside or equal to daemon's directory
-                        // OR if daemon is in a subdirectory of the entered directory
-                        dirs_overlap(dir, daemon_dir)
-                    })
+                    users::owned_by(d, user)
+                        && d.dir.as_ref().is_some_and(|daemon_dir| {
+                            // Cancel if entering a directory inside or equal to daemon's directory
+                            // OR if daemon is in a subdirectory of the entered directory
+                            dirs_overlap(dir, daemon_dir)
+                        })
                 })
                 .map(|(id, _)| id.clone())
                 .collect()
@@ -137,7 +260,7 @@ impl Supervisor {
             // Check if daemon is still running and should be stopped
             if let Some(daemon) = self.get_daemon(&daemon_id).await
                 && daemon.autostop
-                && daemon.status.is_running()
+                && daemon.status.is_up()
             {
                 // Verify no active directory is in the daemon's directory
                 let active_dirs = self.get_active_directories().await;
@@ -154,8 +277,12 @@ impl Supervisor {
                     }
                     info!("autostopping {daemon_id} (after delay)");
                     self.stop(&daemon_id).await?;
-                    self.add_notification(Info, format!("autostopped {daemon_id}"))
-                        .await;
+                    self.add_notification(
+                        Some(&daemon_id),
+                        Info,
+                        format!("autostopped {daemon_id}"),
+                    )
+                    .await;
                 }
             }
         }
@@ -165,13 +292,26 @@ impl Supervisor {
     /// Start daemons configured with `boot_start = true`
     pub(crate) async fn start_boot_daemons(&self) -> Result<()> {
         info!("Scanning for boot_start daemons");
+        if self.state_file.lock().await.paused {
+            info!("Supervisor is paused, not starting boot_start daemons");
+            return Ok(());
+        }
         let pt = PitchforkToml::all_merged_all_namespaces()?;
 
-        let boot_daemons: Vec<_> = pt
-            .daemons
-            .iter()
-            .filter(|(_id, d)| d.boot_start.unwrap_or(false))
-            .collect();
+        let mut boot_daemons: Vec<_> = {
+            let state_file = self.state_file.lock().await;
+            pt.daemons
+                .iter()
+                .filter(|(_id, d)| d.boot_start.unwrap_or(false))
+                .filter(|(id, _d)| {
+                    let allowed = state_file.autostart_allowed(id);
+                    if !allowed {
+                        info!("Skipping disabled boot daemon: {id}");
+                    }
+                    allowed
+                })
+                .collect()
+        };
 
         if boot_daemons.is_empty() {
             info!("No daemons configured with boot_start = true");
@@ -179,8 +319,16 @@ impl Supervisor {
         }
 
         info!("Found {} daemon(s) to start at boot", boot_daemons.len());
+        // Stable, so equal priorities keep their config order
+        boot_daemons.sort_by_key(|(_id, d)| std::cmp::Reverse(d.start_priority.unwrap_or(0)));
 
-        for (id, daemon) in boot_daemons {
+        for (i, (id, daemon)) in boot_daemons.into_iter().enumerate() {
+            if let Some(delay) = daemon.start_delay
+                && i > 0
+            {
+                debug!("waiting {delay} before starting boot daemon {id}");
+                self.clock.sleep(delay.0).await;
+            }
             info!("Starting boot daemon: {id}");
 
             let cmd = match shell_words::split(&daemon.run) {
@@ -197,6 +345,7 @@ impl Supervisor {
             match self.run(run_opts).await {
                 Ok(IpcResponse::DaemonStart { .. }) | Ok(IpcResponse::DaemonReady { .. }) => {
                     info!("Successfully started boot daemon: {id}");
+                    self.boot_daemons_started.fetch_add(1, Ordering::Relaxed);
                 }
                 Ok(IpcResponse::DaemonAlreadyRunning) => {
                     info!("Boot daemon already running: {id}");
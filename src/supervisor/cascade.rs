@@ -0,0 +1,218 @@
+//! Restart propagation through `depends`
+//!
+//! When a running daemon is restarted, the running daemons that depend on it
+//! and set `restart_with_dependencies` are restarted too, once it's up again.
+//! The whole cascade is planned from the daemon that was restarted, so each
+//! dependent restarts at most once, even if `depends` forms a cycle.
+
+use super::{SUPERVISOR, Supervisor, events};
+use crate::daemon::Daemon;
+use crate::daemon_id::DaemonId;
+use crate::daemon_status::DaemonStatus;
+use crate::settings::settings;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::time;
+
+impl Supervisor {
+    /// Restart the dependents of `id`, which was just restarted, in the
+    /// background. Daemons in `restart_batch` are being restarted by the
+    /// same request and are left out along with their dependents.
+    pub(crate) fn spawn_restart_cascade(id: DaemonId, restart_batch: Vec<DaemonId>) {
+        tokio::spawn(async move { SUPERVISOR.restart_dependents(&id, &restart_batch).await });
+    }
+
+    async fn restart_dependents(&self, root: &DaemonId, restart_batch: &[DaemonId]) {
+        let daemons: Vec<Daemon> = self
+            .state_file
+            .lock()
+            .await
+            .daemons
+            .values()
+            .cloned()
+            .collect();
+        let levels = cascade_levels(root, &daemons, restart_batch);
+        if levels.is_empty() {
+            return;
+        }
+        // A dependent whose pid changes before its turn was restarted some
+        // other way meanwhile (e.g. by its file watch) and is skipped.
+        let pids: HashMap<&DaemonId, Option<u32>> =
+            daemons.iter().map(|d| (&d.id, d.pid)).collect();
+        let names = levels
+            .iter()
+            .flatten()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        info!("daemon {root} restarted, restarting its dependents: {names}");
+        events::record(root, "cascade", Some(format!("restarting {names}")));
+
+        if !self.wait_until_up(root).await {
+            warn!("daemon {root} didn't come back up, not restarting its dependents");
+            events::record(
+                root,
+                "cascade",
+                Some(format!("cancelled, {root} didn't come back up")),
+            );
+            return;
+        }
+        let reason = format!("because {root} restarted");
+        for level in levels {
+            for id in &level {
+                let pid = self.get_daemon(id).await.and_then(|d| d.pid);
+                if pids.get(id).copied().flatten() != pid {
+                    debug!("daemon {id} was already restarted, skipping it in the cascade");
+                    continue;
+                }
+                if let Err(e) = self.restart_running_daemon_with(id, &reason, None).await {
+                    error!("Failed to restart daemon {id} {reason}: {e}");
+                }
+            }
+            // Dependents further down wait for this level to be ready
+            for id in &level {
+                self.wait_until_up(id).await;
+            }
+        }
+    }
+
+    /// Wait for a daemon that is (re)starting to pass its ready check.
+    /// Returns false if it fails or stops instead.
+    async fn wait_until_up(&self, id: &DaemonId) -> bool {
+        let interval = settings().supervisor_ready_check_interval();
+        loop {
+            match self.get_daemon(id).await.map(|d| d.status) {
+                Some(DaemonStatus::Running | DaemonStatus::Unhealthy) => return true,
+                Some(DaemonStatus::Waiting | DaemonStatus::Starting | DaemonStatus::Stopping) => {}
+                _ => return false,
+            }
+            time::sleep(interval).await;
+        }
+    }
+}
+
+/// The running daemons to restart after `root` restarted, grouped into
+/// levels in dependency order: each level only depends on `root` and on
+/// earlier levels. Propagation stops at daemons that don't set
+/// `restart_with_dependencies`, and at daemons in `skip`. Daemons that
+/// depend on each other in a cycle share the last level.
+fn cascade_levels(root: &DaemonId, daemons: &[Daemon], skip: &[DaemonId]) -> Vec<Vec<DaemonId>> {
+    let follows = |d: &Daemon| {
+        d.restart_with_dependencies == Some(true)
+            && d.pid.is_some()
+            && d.status.is_up()
+            && !skip.contains(&d.id)
+    };
+    let mut in_cascade: HashSet<&DaemonId> = HashSet::from([root]);
+    let mut members: Vec<&Daemon> = vec![];
+    let mut queue = VecDeque::from([root]);
+    while let Some(dep) = queue.pop_front() {
+        for d in daemons {
+            if follows(d) && d.depends.contains(dep) && in_cascade.insert(&d.id) {
+                members.push(d);
+                queue.push_back(&d.id);
+            }
+        }
+    }
+
+    let mut done: HashSet<&DaemonId> = HashSet::from([root]);
+    let mut levels = vec![];
+    while !members.is_empty() {
+        let (ready, rest): (Vec<&Daemon>, Vec<&Daemon>) = members.into_iter().partition(|d| {
+            d.depends
+                .iter()
+                .all(|dep| done.contains(dep) || !in_cascade.contains(dep))
+        });
+        let mut level: Vec<DaemonId> = if ready.is_empty() {
+            rest.iter().map(|d| d.id.clone()).collect()
+        } else {
+            ready.iter().map(|d| d.id.clone()).collect()
+        };
+        level.sort();
+        done.extend(ready.iter().map(|d| &d.id));
+        members = if ready.is_empty() { vec![] } else { rest };
+        levels.push(level);
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon(name: &str, depends: &[&str], follows: bool) -> Daemon {
+        Daemon {
+            id: DaemonId::new("proj", name),
+            pid: Some(1),
+            status: DaemonStatus::Running,
+            depends: depends.iter().map(|d| DaemonId::new("proj", *d)).collect(),
+            restart_with_dependencies: follows.then_some(true),
+            ..Daemon::default()
+        }
+    }
+
+    fn names(levels: Vec<Vec<DaemonId>>) -> Vec<Vec<String>> {
+        levels
+            .into_iter()
+            .map(|level| level.iter().map(|id| id.name().to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_cascade_follows_dependency_order() {
+        let daemons = vec![
+            daemon("db", &[], false),
+            daemon("web", &["api", "db"], true),
+            daemon("api", &["db"], true),
+            daemon("worker", &["db"], false),
+            daemon("cache", &[], true),
+        ];
+        let root = DaemonId::new("proj", "db");
+        assert_eq!(
+            names(cascade_levels(&root, &daemons, &[])),
+            vec![vec!["api"], vec!["web"]]
+        );
+    }
+
+    #[test]
+    fn test_cascade_stops_at_daemons_without_the_option() {
+        let mut stopped = daemon("admin", &["api"], true);
+        stopped.status = DaemonStatus::Stopped;
+        stopped.pid = None;
+        let daemons = vec![
+            daemon("api", &["db"], false),
+            daemon("web", &["api"], true),
+            stopped,
+        ];
+        let root = DaemonId::new("proj", "db");
+        assert!(cascade_levels(&root, &daemons, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_cascade_with_cycle_restarts_each_daemon_once() {
+        let daemons = vec![
+            daemon("db", &["web"], true),
+            daemon("api", &["db", "web"], true),
+            daemon("web", &["api"], true),
+        ];
+        let root = DaemonId::new("proj", "db");
+        assert_eq!(
+            names(cascade_levels(&root, &daemons, &[])),
+            vec![vec!["api", "web"]]
+        );
+    }
+
+    #[test]
+    fn test_cascade_skips_daemons_restarted_by_the_same_request() {
+        let daemons = vec![
+            daemon("api", &["db"], true),
+            daemon("web", &["api"], true),
+            daemon("worker", &["db"], true),
+        ];
+        let root = DaemonId::new("proj", "db");
+        let skip = [root.clone(), DaemonId::new("proj", "api")];
+        assert_eq!(
+            names(cascade_levels(&root, &daemons, &skip)),
+            vec![vec!["worker"]]
+        );
+    }
+}
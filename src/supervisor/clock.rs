@@ -0,0 +1,81 @@
+//! Time source for cron scheduling and retry backoff
+//!
+//! The supervisor reads the time through a [`Clock`] instead of calling
+//! `chrono::Local::now()` and `tokio::time::sleep` directly. Sleeps always go
+//! through tokio's timer, so under `#[tokio::test(start_paused = true)]` they
+//! complete as soon as the runtime is idle. [`VirtualClock`] derives the wall
+//! clock from tokio's clock too, so `tokio::time::advance` fast-forwards cron
+//! schedules and backoff together without real waiting.
+
+use chrono::{DateTime, Local};
+use std::time::Duration;
+use tokio::time;
+
+pub(crate) trait Clock: Send + Sync {
+    /// Current wall-clock time
+    fn now(&self) -> DateTime<Local>;
+
+    /// Sleep for `duration` of this clock's time
+    fn sleep(&self, duration: Duration) -> time::Sleep {
+        time::sleep(duration)
+    }
+}
+
+/// The real system clock
+#[derive(Debug, Default)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A wall clock that starts at a fixed time and advances with tokio's
+/// (pausable) clock.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct VirtualClock {
+    start: DateTime<Local>,
+    started_at: time::Instant,
+}
+
+#[cfg(test)]
+impl VirtualClock {
+    pub(crate) fn new(start: DateTime<Local>) -> Self {
+        Self {
+            start,
+            started_at: time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Clock for VirtualClock {
+    fn now(&self) -> DateTime<Local> {
+        let elapsed = time::Instant::now() - self.started_at;
+        self.start + chrono::Duration::from_std(elapsed).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_virtual_clock_follows_paused_time() {
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let clock = VirtualClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        time::advance(Duration::from_secs(90)).await;
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+
+        // An hour-long sleep completes instantly in virtual time
+        let real = std::time::Instant::now();
+        clock.sleep(Duration::from_secs(3600)).await;
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(3690));
+        assert!(real.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,229 @@
+//! Cron leases (`cron.exclusive`)
+//!
+//! Supervisors that share a project directory (two users, two machines on a
+//! network filesystem) each see the same cron schedule and would each fire it.
+//! For exclusive schedules, a lease file next to the daemon records who fired
+//! the last run and until when that run holds it. A supervisor only triggers
+//! when no one else holds the lease and nobody fired the current scheduled
+//! time yet. The holder renews the lease while its run is alive and releases
+//! it on exit; a supervisor that dies mid-run lets it lapse after the TTL.
+
+use crate::daemon_id::DaemonId;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A guard file older than this is left over from a crashed supervisor.
+const STALE_GUARD: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CronLease {
+    /// `host:pid` of the supervisor that fired the run
+    pub holder: String,
+    /// When the run was fired
+    pub fired_at: DateTime<Local>,
+    /// When the lease lapses unless renewed
+    pub expires_at: DateTime<Local>,
+}
+
+/// Outcome of trying to take the lease for a scheduled run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Acquire {
+    /// The lease is ours; fire the run.
+    Acquired,
+    /// Another supervisor's run still holds the lease.
+    Held(String),
+    /// Another supervisor already fired this scheduled time.
+    AlreadyFired(String),
+}
+
+/// Identifies this supervisor in lease files.
+pub(crate) fn holder() -> String {
+    format!("{}:{}", *crate::env::HOSTNAME, std::process::id())
+}
+
+/// The lease file for `id`, kept in the daemon's directory.
+pub(crate) fn lease_path(dir: &Path, id: &DaemonId) -> PathBuf {
+    dir.join(format!(".pitchfork-cron-{}.lease", id.safe_path()))
+}
+
+/// Take the lease at `path` for the run scheduled at `slot`, holding it
+/// until `now + ttl`.
+pub(crate) fn acquire(
+    path: &Path,
+    holder: &str,
+    slot: DateTime<Local>,
+    now: DateTime<Local>,
+    ttl: Duration,
+) -> std::io::Result<Acquire> {
+    with_guard(path, || {
+        if let Some(lease) = read(path) {
+            if lease.holder != holder && lease.expires_at > now {
+                return Ok(Acquire::Held(lease.holder));
+            }
+            if lease.fired_at >= slot {
+                return Ok(Acquire::AlreadyFired(lease.holder));
+            }
+        }
+        write(
+            path,
+            &CronLease {
+                holder: holder.to_string(),
+                fired_at: now,
+                expires_at: now + chrono::Duration::from_std(ttl).unwrap_or_default(),
+            },
+        )?;
+        Ok(Acquire::Acquired)
+    })
+}
+
+/// Push the expiry of a lease we hold to `now + ttl` while our run is alive.
+pub(crate) fn renew(
+    path: &Path,
+    holder: &str,
+    now: DateTime<Local>,
+    ttl: Duration,
+) -> std::io::Result<()> {
+    update_own(path, holder, |lease| {
+        lease.expires_at = now + chrono::Duration::from_std(ttl).unwrap_or_default();
+    })
+}
+
+/// Let a lease we hold lapse now that our run has exited. The file stays so
+/// that `fired_at` still marks the scheduled time as done.
+pub(crate) fn release(path: &Path, holder: &str, now: DateTime<Local>) -> std::io::Result<()> {
+    update_own(path, holder, |lease| {
+        lease.expires_at = lease.expires_at.min(now);
+    })
+}
+
+fn update_own(path: &Path, holder: &str, f: impl FnOnce(&mut CronLease)) -> std::io::Result<()> {
+    if read(path).is_none_or(|l| l.holder != holder) {
+        return Ok(());
+    }
+    with_guard(path, || {
+        let Some(mut lease) = read(path).filter(|l| l.holder == holder) else {
+            return Ok(());
+        };
+        let before = lease.clone();
+        f(&mut lease);
+        if lease != before {
+            write(path, &lease)?;
+        }
+        Ok(())
+    })
+}
+
+fn read(path: &Path) -> Option<CronLease> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    toml::from_str(&raw).ok()
+}
+
+fn write(path: &Path, lease: &CronLease) -> std::io::Result<()> {
+    let raw = toml::to_string(lease).map_err(std::io::Error::other)?;
+    let tmp = with_suffix(path, &format!(".tmp-{}", std::process::id()));
+    std::fs::write(&tmp, raw)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Run `f` while holding `<path>.lock`, created exclusively, so that two
+/// supervisors can't both read a free lease and then both write it.
+fn with_guard<T>(path: &Path, f: impl FnOnce() -> std::io::Result<T>) -> std::io::Result<T> {
+    let guard = with_suffix(path, ".lock");
+    let mut file = match create_guard(&guard) {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && is_stale(&guard) => {
+            let _ = std::fs::remove_file(&guard);
+            create_guard(&guard)?
+        }
+        r => r?,
+    };
+    let _ = writeln!(file, "{}", holder());
+    let result = f();
+    let _ = std::fs::remove_file(&guard);
+    result
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+fn create_guard(guard: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(guard)
+}
+
+fn is_stale(guard: &Path) -> bool {
+    std::fs::metadata(guard)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age > STALE_GUARD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn at(secs: i64) -> DateTime<Local> {
+        Local.timestamp_opt(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_second_supervisor_skips_held_and_fired_slots() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = lease_path(dir.path(), &DaemonId::new("proj", "backup"));
+
+        assert_eq!(
+            acquire(&path, "a:1", at(0), at(1), TTL).unwrap(),
+            Acquire::Acquired
+        );
+        // B checks a few seconds later while A's run is still going
+        assert_eq!(
+            acquire(&path, "b:2", at(0), at(5), TTL).unwrap(),
+            Acquire::Held("a:1".into())
+        );
+        // A's run exits; B still must not fire the same scheduled time
+        release(&path, "a:1", at(10)).unwrap();
+        assert_eq!(
+            acquire(&path, "b:2", at(0), at(12), TTL).unwrap(),
+            Acquire::AlreadyFired("a:1".into())
+        );
+        // The next scheduled time goes to whoever checks first
+        assert_eq!(
+            acquire(&path, "b:2", at(60), at(61), TTL).unwrap(),
+            Acquire::Acquired
+        );
+        assert_eq!(read(&path).unwrap().holder, "b:2");
+        assert!(!with_suffix(&path, ".lock").exists());
+    }
+
+    #[test]
+    fn test_lease_lapses_unless_renewed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = lease_path(dir.path(), &DaemonId::new("proj", "backup"));
+
+        acquire(&path, "a:1", at(0), at(0), TTL).unwrap();
+        renew(&path, "a:1", at(50), TTL).unwrap();
+        // Only the holder can renew or release
+        release(&path, "b:2", at(55)).unwrap();
+        assert_eq!(read(&path).unwrap().expires_at, at(110));
+        assert_eq!(
+            acquire(&path, "b:2", at(100), at(100), TTL).unwrap(),
+            Acquire::Held("a:1".into())
+        );
+        // A crashed and stopped renewing
+        assert_eq!(
+            acquire(&path, "b:2", at(120), at(120), TTL).unwrap(),
+            Acquire::Acquired
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Leaving daemons running while the supervisor restarts
+//!
+//! `pitchfork supervisor stop --keep-daemons` shuts down only the
+//! supervisor. Its daemons keep running and are marked
+//! [`DaemonStatus::Detached`] in the state file, and the next supervisor
+//! adopts them: each one that is still the process that was recorded is
+//! marked running again and watched until it exits. An adopted daemon is
+//! not the new supervisor's child, so its exit code is unknown.
+//!
+//! Daemon output goes through pipes (or a PTY) read by the supervisor. With
+//! nobody reading them a daemon would block on a full pipe, or be killed by
+//! SIGPIPE, soon after the supervisor is gone. On Unix the supervisor
+//! therefore keeps a copy of each daemon's output descriptors and hands
+//! them to a `pitchfork supervisor relay` process when it stops, which goes
+//! on writing the output to the log store until the daemons exit.
+
+use super::{SUPERVISOR, Supervisor, events, process_identity_matches, reset_daemon_state};
+use crate::daemon_id::DaemonId;
+use crate::daemon_status::DaemonStatus;
+use crate::procs::PROCS;
+use crate::supervisor::state::UpsertDaemonOpts;
+use std::time::Duration;
+use tokio::time;
+#[cfg(unix)]
+use {
+    crate::log_store::LogStream,
+    once_cell::sync::Lazy,
+    std::collections::HashMap,
+    std::os::fd::{AsRawFd, BorrowedFd, OwnedFd},
+    std::sync::Mutex,
+};
+
+/// How often an adopted daemon is checked for having exited
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Copies of a daemon process's output descriptors
+#[cfg(unix)]
+struct KeptOutput {
+    pid: u32,
+    fds: Vec<(LogStream, OwnedFd)>,
+}
+
+/// The output kept for each daemon's current process
+#[cfg(unix)]
+static OUTPUTS: Lazy<Mutex<HashMap<DaemonId, KeptOutput>>> = Lazy::new(Default::default);
+
+/// Keep a copy of the output descriptors of daemon `id`, running as `pid`,
+/// to hand to the relay if the supervisor stops with `--keep-daemons`
+#[cfg(unix)]
+pub(crate) fn keep_output(id: &DaemonId, pid: u32, fds: Vec<(LogStream, BorrowedFd<'_>)>) {
+    let fds = fds
+        .into_iter()
+        .filter_map(|(stream, fd)| match fd.try_clone_to_owned() {
+            Ok(fd) => Some((stream, fd)),
+            Err(e) => {
+                debug!("daemon {id}: failed to keep a copy of its {stream}: {e}");
+                None
+            }
+        })
+        .collect();
+    OUTPUTS
+        .lock()
+        .unwrap()
+        .insert(id.clone(), KeptOutput { pid, fds });
+}
+
+/// Drop the descriptors kept for daemon `id` once its process `pid` exited
+#[cfg(unix)]
+pub(crate) fn forget_output(id: &DaemonId, pid: u32) {
+    let mut outputs = OUTPUTS.lock().unwrap();
+    if outputs.get(id).is_some_and(|kept| kept.pid == pid) {
+        outputs.remove(id);
+    }
+}
+
+/// Start a `pitchfork supervisor relay` process reading the output of
+/// daemons `ids`. It inherits the kept descriptors and runs in its own
+/// session, so it outlives the supervisor.
+#[cfg(unix)]
+fn relay_output(ids: &[DaemonId]) {
+    use std::os::unix::process::CommandExt;
+
+    let outputs = OUTPUTS.lock().unwrap();
+    let mut cmd = std::process::Command::new(&*crate::env::PITCHFORK_BIN);
+    cmd.args(["supervisor", "relay"]);
+    let mut fds = vec![];
+    for id in ids {
+        let Some(kept) = outputs.get(id) else {
+            continue;
+        };
+        for (stream, fd) in &kept.fds {
+            let fd = fd.as_raw_fd();
+            cmd.arg("--output")
+                .arg(format!("{fd}:{stream}:{}", id.qualified()));
+            fds.push(fd);
+        }
+    }
+    if fds.is_empty() {
+        return;
+    }
+    cmd.stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // SAFETY: setsid and fcntl are async-signal-safe, and the closure
+    // doesn't allocate
+    unsafe {
+        cmd.pre_exec(move || {
+            nix::unistd::setsid().map_err(std::io::Error::from)?;
+            for fd in &fds {
+                if libc::fcntl(*fd, libc::F_SETFD, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+    match cmd.spawn() {
+        Ok(child) => info!("relaying output of detached daemons (pid {})", child.id()),
+        Err(e) => warn!("failed to start output relay, detached daemons may lose output: {e}"),
+    }
+}
+
+impl Supervisor {
+    /// Mark daemons `ids` detached, for the next supervisor to adopt, and
+    /// hand their output to the relay
+    pub(crate) async fn detach_daemons(&self, ids: &[DaemonId]) {
+        for id in ids {
+            let Some(pid) = self.get_daemon(id).await.and_then(|d| d.local_pid()) else {
+                continue;
+            };
+            info!("leaving daemon {id} (pid {pid}) running");
+            let _ = self
+                .upsert_daemon(
+                    UpsertDaemonOpts::builder(id.clone())
+                        .set(|o| {
+                            o.pid = Some(pid);
+                            o.status = DaemonStatus::Detached;
+                        })
+                        .build(),
+                )
+                .await;
+        }
+        #[cfg(unix)]
+        relay_output(ids);
+    }
+
+    /// Take over the daemons a previous supervisor left running. Those
+    /// that exited meanwhile, or whose PID now belongs to another process,
+    /// are marked stopped.
+    pub(crate) async fn adopt_detached_daemons(&self) {
+        let detached: Vec<_> = {
+            let state = self.state_file.lock().await;
+            state
+                .daemons
+                .values()
+                .filter(|d| d.status.is_detached())
+                .cloned()
+                .collect()
+        };
+        for daemon in detached {
+            let id = &daemon.id;
+            let Some(pid) = daemon.local_pid() else {
+                reset_daemon_state(self, id).await;
+                continue;
+            };
+            PROCS.refresh_pids(&[pid]);
+            if !PROCS.is_running(pid) {
+                warn!("detached daemon {id} (pid {pid}) exited while the supervisor was stopped");
+                reset_daemon_state(self, id).await;
+                continue;
+            }
+            if !process_identity_matches(
+                daemon.start_time,
+                daemon.title.as_deref(),
+                PROCS.start_time(pid),
+                PROCS.title(pid).as_deref(),
+            ) {
+                warn!(
+                    "pid {pid} recorded for detached daemon {id} belongs to a different process now; resetting state"
+                );
+                reset_daemon_state(self, id).await;
+                continue;
+            }
+            info!("adopting detached daemon {id} (pid {pid})");
+            let _ = self
+                .upsert_daemon(
+                    UpsertDaemonOpts::builder(id.clone())
+                        .set(|o| {
+                            o.pid = Some(pid);
+                            o.status = DaemonStatus::Running;
+                        })
+                        .build(),
+                )
+                .await;
+            watch_adopted(id.clone(), pid);
+        }
+    }
+}
+
+/// Update the state of adopted daemon `id` once its process `pid` exits.
+/// It isn't our child, so there is no exit status to wait for.
+fn watch_adopted(id: DaemonId, pid: u32) {
+    tokio::spawn(async move {
+        let mut interval = time::interval(ADOPTED_POLL_INTERVAL);
+        while PROCS.is_running(pid) {
+            interval.tick().await;
+        }
+        // stop() records the exit of a daemon it stopped itself
+        let Some(daemon) = SUPERVISOR.get_daemon(&id).await else {
+            return;
+        };
+        if daemon.pid != Some(pid) || daemon.status.is_stopping() || daemon.status.is_stopped() {
+            return;
+        }
+        warn!("adopted daemon {id} (pid {pid}) exited");
+        events::record(
+            &id,
+            "exit",
+            Some("adopted process exited, exit code unknown".to_string()),
+        );
+        let _ = SUPERVISOR
+            .upsert_daemon(
+                UpsertDaemonOpts::builder(id.clone())
+                    .set(|o| {
+                        o.pid = None;
+                        o.status = DaemonStatus::Errored(-1);
+                        o.last_exit_success = Some(false);
+                    })
+                    .build(),
+            )
+            .await;
+    });
+}
+
+/// Write the output read from `outputs` to the log store until every daemon
+/// has closed its end. Runs in the `pitchfork supervisor relay` process, so
+/// it doesn't apply `logs.rate_limit` or fire `on_output` hooks.
+#[cfg(unix)]
+pub async fn relay(outputs: Vec<(std::os::fd::RawFd, LogStream, DaemonId)>) -> crate::Result<()> {
+    use super::lifecycle::get_or_compile_regex;
+    use super::output::OutputLines;
+    use crate::log_parse::{self, ParsedLog};
+    use crate::log_store::LogStore;
+    use crate::log_store::journal::{self, LogSink};
+    use crate::log_store::sqlite::LOG_STORE;
+    use crate::settings::settings;
+    use crate::state_file::StateFile;
+    use miette::IntoDiagnostic;
+    use std::os::fd::FromRawFd;
+    use tokio::net::unix::pipe;
+
+    const BATCH_SIZE: usize = 100;
+    const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+    let state = StateFile::read(&*crate::env::PITCHFORK_STATE_FILE)?;
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<(DaemonId, ParsedLog)>(256);
+    for (fd, stream, id) in outputs {
+        // Pipes are already non-blocking, as tokio set them up in the
+        // supervisor; a PTY master isn't
+        // SAFETY: fcntl only reads and sets the descriptor's status flags
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags == -1 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } == -1
+        {
+            miette::bail!("file descriptor {fd} for daemon {id} is not usable");
+        }
+        // SAFETY: the supervisor passed us this descriptor to own
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let reader = pipe::Receiver::from_owned_fd_unchecked(fd).into_diagnostic()?;
+        let daemon = state.daemons.get(&id);
+        let encoding = daemon.and_then(|d| d.log_encoding).unwrap_or_default();
+        let format = daemon
+            .and_then(|d| d.log_format.clone())
+            .unwrap_or_else(|| settings().logs.log_format.clone());
+        let level_regex = daemon
+            .and_then(|d| d.log_level_regex.as_deref())
+            .and_then(get_or_compile_regex);
+        let filter: Vec<_> = daemon
+            .map(|d| {
+                d.log_filter
+                    .iter()
+                    .filter_map(|p| get_or_compile_regex(p))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut lines = OutputLines::new(tokio::io::BufReader::new(reader), encoding);
+            while let Ok(Some(line)) = lines.next_line().await {
+                let line_clean = console::strip_ansi_codes(&line);
+                if filter.iter().any(|re| re.is_match(&line_clean)) {
+                    continue;
+                }
+                let mut parsed = log_parse::parse(&line, &format);
+                if parsed.level.is_none()
+                    && let Some(re) = &level_regex
+                {
+                    parsed.level = log_parse::level_from_regex(re, &line_clean);
+                }
+                if tx
+                    .send((id.clone(), ParsedLog { stream, ..parsed }))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+    // The channel closes once every reader has reached the end of its output
+    drop(tx);
+
+    let sink = LogSink::get();
+    let flush = |buffer: &mut Vec<(DaemonId, ParsedLog)>| {
+        let mut batches: HashMap<DaemonId, Vec<ParsedLog>> = HashMap::new();
+        for (id, log) in buffer.drain(..) {
+            batches.entry(id).or_default().push(log);
+        }
+        tokio::task::spawn_blocking(move || {
+            for (id, batch) in batches {
+                if sink.journal() {
+                    journal::send_batch(&id, &batch);
+                }
+                if sink.sqlite()
+                    && let Err(e) = LOG_STORE.append_structured_batch(&id, &batch)
+                {
+                    warn!("failed to store output of daemon {id}: {e}");
+                }
+            }
+        })
+    };
+    let mut buffer = Vec::with_capacity(BATCH_SIZE);
+    let mut interval = time::interval(FLUSH_INTERVAL);
+    loop {
+        tokio::select! {
+            log = rx.recv() => match log {
+                Some(log) => {
+                    buffer.push(log);
+                    if buffer.len() >= BATCH_SIZE {
+                        flush(&mut buffer).await.into_diagnostic()?;
+                    }
+                }
+                None => break,
+            },
+            _ = interval.tick() => {
+                if !buffer.is_empty() {
+                    flush(&mut buffer).await.into_diagnostic()?;
+                }
+            }
+        }
+    }
+    if !buffer.is_empty() {
+        flush(&mut buffer).await.into_diagnostic()?;
+    }
+    debug!("all detached daemons closed their output, relay exiting");
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Flagging daemons whose binary or working directory changed on disk
+//!
+//! A `git checkout` or a rebuild can delete or replace what a running daemon
+//! was started from, leaving it running old code until someone restarts it.
+//! Every `supervisor.drift_check_interval`, each running daemon's process
+//! tree is checked through `/proc`:
+//!
+//! - its working directory (`/proc/<pid>/cwd`) was deleted;
+//! - its binary (`/proc/<pid>/exe`) was deleted or replaced by a new file; or
+//! - its binary was modified after the process started.
+//!
+//! A hit sets the daemon's `drift` note, shown as "stale, restart recommended"
+//! in `list` and `status`, and records a `stale` event. The process is left
+//! alone, and the note clears when it is restarted. Other platforms have no
+//! `/proc`, so the check never starts there.
+
+use super::{SUPERVISOR, Supervisor, events};
+use crate::Result;
+use crate::daemon::Daemon;
+use crate::procs::PROCS;
+use crate::settings::settings;
+use tokio::time;
+
+impl Supervisor {
+    /// Start the drift watcher, unless `drift_check_interval` is zero
+    pub(crate) fn drift_watch(&self) -> Result<()> {
+        let interval = settings().supervisor_drift_check_interval();
+        if interval.is_zero() || !cfg!(target_os = "linux") {
+            return Ok(());
+        }
+        tokio::spawn(async move {
+            let mut interval = time::interval(interval);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                SUPERVISOR.check_drift().await;
+            }
+        });
+        Ok(())
+    }
+
+    async fn check_drift(&self) {
+        let daemons: Vec<Daemon> = {
+            let state = self.state_file.lock().await;
+            state
+                .daemons
+                .values()
+                .filter(|d| d.local_pid().is_some() && d.drift.is_none())
+                .cloned()
+                .collect()
+        };
+        if daemons.is_empty() {
+            return;
+        }
+        PROCS.refresh_processes();
+        for daemon in daemons {
+            let Some(pid) = daemon.local_pid() else {
+                continue;
+            };
+            let Some(reason) = PROCS
+                .process_tree(pid)
+                .into_iter()
+                .find_map(|(pid, started)| process_drift(pid, started))
+            else {
+                continue;
+            };
+            let changed =
+                self.state_file
+                    .lock()
+                    .await
+                    .set_drift(&daemon.id, pid, Some(reason.clone()));
+            if changed {
+                let id = &daemon.id;
+                warn!("daemon {id} (pid {pid}) is stale: {reason}, restart recommended");
+                events::record(id, "stale", Some(reason));
+            }
+        }
+    }
+}
+
+/// Why the process `pid`, started at `started` (seconds since the epoch),
+/// no longer matches what's on disk, if it doesn't.
+#[cfg(target_os = "linux")]
+fn process_drift(pid: u32, started: u64) -> Option<String> {
+    use std::time::UNIX_EPOCH;
+
+    let proc_dir = std::path::PathBuf::from(format!("/proc/{pid}"));
+    if let Ok(cwd) = std::fs::read_link(proc_dir.join("cwd"))
+        && let Some(dir) = deleted_target(&cwd.to_string_lossy())
+    {
+        return Some(format!("working directory {dir} was deleted"));
+    }
+    let exe = std::fs::read_link(proc_dir.join("exe")).ok()?;
+    let exe = exe.to_string_lossy();
+    // Binaries executed from an anonymous memfd are always "deleted"
+    if exe.starts_with("/memfd:") {
+        return None;
+    }
+    if let Some(exe) = deleted_target(&exe) {
+        return Some(format!("binary {exe} was replaced"));
+    }
+    let modified = std::fs::metadata(exe.as_ref()).ok()?.modified().ok()?;
+    let modified = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    // A start time of 0 means sysinfo couldn't read it
+    if started > 0 && modified > started {
+        return Some(format!("binary {exe} changed"));
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_drift(_pid: u32, _started: u64) -> Option<String> {
+    None
+}
+
+/// The original path of a `/proc/<pid>/{cwd,exe}` link target whose file
+/// was deleted, which the kernel marks with a ` (deleted)` suffix.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn deleted_target(target: &str) -> Option<&str> {
+    target.strip_suffix(" (deleted)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deleted_target() {
+        assert_eq!(
+            deleted_target("/home/me/app (deleted)"),
+            Some("/home/me/app")
+        );
+        assert_eq!(deleted_target("/home/me/app"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_drift_deleted_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .current_dir(dir.path())
+            .spawn()
+            .unwrap();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(process_drift(child.id(), now), None);
+        let path = dir.path().to_string_lossy().to_string();
+        dir.close().unwrap();
+        let reason = process_drift(child.id(), now);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(
+            reason,
+            Some(format!("working directory {path} was deleted"))
+        );
+    }
+}
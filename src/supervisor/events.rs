@@ -0,0 +1,209 @@
+//! Recent lifecycle events per daemon
+//!
+//! A small in-memory history of what happened to each daemon (started, ready,
+//! retried, exited, ...), shown on the web UI's daemon page. It is not
+//! persisted: the log store remains the durable record.
+//!
+//! The same events, plus status changes and notifications, are also broadcast
+//! live to subscribers of `/api/events/stream`.
+
+use crate::daemon_id::DaemonId;
+use crate::daemon_status::DaemonStatus;
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Events kept per daemon; older ones are dropped.
+const MAX_EVENTS_PER_DAEMON: usize = 50;
+
+/// Live events buffered per subscriber before a slow one starts missing them.
+const STREAM_CAPACITY: usize = 256;
+
+static EVENTS: Lazy<Mutex<HashMap<DaemonId, VecDeque<DaemonEvent>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static STREAM: Lazy<broadcast::Sender<StreamEvent>> =
+    Lazy::new(|| broadcast::channel(STREAM_CAPACITY).0);
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct DaemonEvent {
+    pub(crate) at: DateTime<Local>,
+    /// `start`, `ready`, `retry`, `fail`, `stop`, `exit`, `killed`, `oom`,
+    /// `restart`, `cascade` when its restart restarts its dependents, `timeout`
+    /// when a hook or `ready_cmd` run was killed for taking too long, `stale`
+    /// when its binary or working directory changed on disk, `throttled` when
+    /// its output went over `logs.rate_limit`, or `config` when the daemon was
+    /// started with a different config than last time
+    pub(crate) kind: String,
+    pub(crate) message: Option<String>,
+}
+
+/// A live event pushed to `/api/events/stream` subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum StreamEvent {
+    /// A daemon moved from one status to another
+    Status {
+        id: String,
+        at: DateTime<Local>,
+        status: String,
+        previous: Option<String>,
+    },
+    /// A lifecycle event, as recorded with [`record`]
+    Event {
+        id: String,
+        #[serde(flatten)]
+        event: DaemonEvent,
+    },
+    /// A supervisor notification, as shown by the shell hook
+    Notification {
+        at: DateTime<Local>,
+        level: String,
+        message: String,
+    },
+}
+
+impl StreamEvent {
+    /// SSE event name: `status`, `event` or `notification`
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Status { .. } => "status",
+            Self::Event { .. } => "event",
+            Self::Notification { .. } => "notification",
+        }
+    }
+
+    /// Qualified id of the daemon this is about, if any
+    pub(crate) fn daemon_id(&self) -> Option<&str> {
+        match self {
+            Self::Status { id, .. } | Self::Event { id, .. } => Some(id),
+            Self::Notification { .. } => None,
+        }
+    }
+}
+
+/// Record an event for `id`.
+pub(crate) fn record(id: &DaemonId, kind: &str, message: Option<String>) {
+    let event = DaemonEvent {
+        at: Local::now(),
+        kind: kind.to_string(),
+        message,
+    };
+    {
+        let mut events = EVENTS.lock().unwrap();
+        let daemon_events = events.entry(id.clone()).or_default();
+        if daemon_events.len() >= MAX_EVENTS_PER_DAEMON {
+            daemon_events.pop_front();
+        }
+        daemon_events.push_back(event.clone());
+    }
+    publish(StreamEvent::Event {
+        id: id.qualified(),
+        event,
+    });
+}
+
+/// Broadcast a status change for `id`. Nothing is sent if the status is
+/// unchanged.
+pub(crate) fn status_changed(
+    id: &DaemonId,
+    previous: Option<&DaemonStatus>,
+    status: &DaemonStatus,
+) {
+    let previous = previous.map(|s| s.to_string());
+    let status = status.to_string();
+    if previous.as_ref() == Some(&status) {
+        return;
+    }
+    publish(StreamEvent::Status {
+        id: id.qualified(),
+        at: Local::now(),
+        status,
+        previous,
+    });
+}
+
+/// Broadcast a supervisor notification.
+pub(crate) fn notification(level: log::LevelFilter, message: &str) {
+    publish(StreamEvent::Notification {
+        at: Local::now(),
+        level: level.as_str().to_lowercase(),
+        message: message.to_string(),
+    });
+}
+
+/// Receive live events from now on.
+pub(crate) fn subscribe() -> broadcast::Receiver<StreamEvent> {
+    STREAM.subscribe()
+}
+
+fn publish(event: StreamEvent) {
+    // Fails only when nobody is subscribed, which is the common case
+    let _ = STREAM.send(event);
+}
+
+/// Events recorded for `id`, newest first.
+pub(crate) fn recent(id: &DaemonId) -> Vec<DaemonEvent> {
+    EVENTS
+        .lock()
+        .unwrap()
+        .get(id)
+        .map(|events| events.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_is_newest_first_and_bounded() {
+        let id = DaemonId::new("events-test", "api");
+        for i in 0..MAX_EVENTS_PER_DAEMON + 5 {
+            record(&id, "exit", Some(format!("exit code {i}")));
+        }
+        record(&id, "start", None);
+
+        let events = recent(&id);
+        assert_eq!(events.len(), MAX_EVENTS_PER_DAEMON);
+        assert_eq!(events[0].kind, "start");
+        assert_eq!(
+            events[1].message.as_deref(),
+            Some(format!("exit code {}", MAX_EVENTS_PER_DAEMON + 4).as_str())
+        );
+        assert!(recent(&DaemonId::new("events-test", "other")).is_empty());
+    }
+
+    #[test]
+    fn test_subscribers_receive_events_and_status_changes() {
+        let id = DaemonId::new("events-test", "stream");
+        let mut rx = subscribe();
+
+        record(&id, "start", Some("pid 42".into()));
+        status_changed(&id, Some(&DaemonStatus::Running), &DaemonStatus::Running);
+        status_changed(&id, None, &DaemonStatus::Running);
+        notification(log::LevelFilter::Warn, "disk is almost full");
+
+        let mut received = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            // Other tests may publish concurrently
+            if event.daemon_id().is_none_or(|d| d == id.qualified()) {
+                received.push(event);
+            }
+        }
+        let names: Vec<_> = received.iter().map(|e| e.name()).collect();
+        assert_eq!(names, ["event", "status", "notification"]);
+        let json = serde_json::to_value(&received[0]).unwrap();
+        assert_eq!(json["type"], "event");
+        assert_eq!(json["id"], "events-test/stream");
+        assert_eq!(json["kind"], "start");
+        let json = serde_json::to_value(&received[1]).unwrap();
+        assert_eq!(json["status"], "running");
+        assert!(json["previous"].is_null());
+        let json = serde_json::to_value(&received[2]).unwrap();
+        assert_eq!(json["level"], "warn");
+    }
+}
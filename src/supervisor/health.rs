@@ -0,0 +1,236 @@
+//! Periodic health checks (`unhealthy` status)
+//!
+//! Once a daemon is ready, its `ready_http`, `ready_port`, or `ready_unix_socket`
+//! check keeps being probed every `supervisor.health_check_interval`. A failed
+//! probe marks the daemon `unhealthy` and the next passing one marks it
+//! `running` again. The process itself is left alone either way.
+
+use super::{SUPERVISOR, Supervisor, events};
+use crate::Result;
+use crate::config_types::ReadyHttp;
+use crate::daemon::Daemon;
+use crate::daemon_status::DaemonStatus;
+use crate::settings::settings;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time;
+
+impl Supervisor {
+    /// Start the health watcher, unless `health_check_interval` is zero
+    pub(crate) fn health_watch(&self) -> Result<()> {
+        let s = settings();
+        let interval = s.supervisor_health_check_interval();
+        if interval.is_zero() {
+            return Ok(());
+        }
+        let probe_timeout = s.supervisor_http_client_timeout();
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder()
+                .timeout(probe_timeout)
+                .build()
+                .unwrap_or_default();
+            let mut interval = time::interval(interval);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                SUPERVISOR.check_health(&client, probe_timeout).await;
+            }
+        });
+        Ok(())
+    }
+
+    async fn check_health(&self, client: &reqwest::Client, timeout: Duration) {
+        let daemons: Vec<Daemon> = {
+            let state = self.state_file.lock().await;
+            state
+                .daemons
+                .values()
+                .filter(|d| matches!(d.status, DaemonStatus::Running | DaemonStatus::Unhealthy))
+                .filter(|d| d.local_pid().is_some() && has_health_check(d))
+                .cloned()
+                .collect()
+        };
+        for daemon in daemons {
+            let Some(pid) = daemon.local_pid() else {
+                continue;
+            };
+            let healthy = probe(&daemon, client, timeout).await;
+            let status = if healthy {
+                DaemonStatus::Running
+            } else {
+                DaemonStatus::Unhealthy
+            };
+            let changed =
+                self.state_file
+                    .lock()
+                    .await
+                    .set_up_status(&daemon.id, pid, status.clone());
+            if !changed {
+                continue;
+            }
+            events::status_changed(&daemon.id, Some(&daemon.status), &status);
+            let id = &daemon.id;
+            if healthy {
+                info!("daemon {id} is healthy again");
+                events::record(id, "healthy", None);
+            } else {
+                warn!("daemon {id} failed its health check");
+                events::record(id, "unhealthy", None);
+            }
+        }
+    }
+}
+
+fn has_health_check(daemon: &Daemon) -> bool {
+    daemon.ready_http.is_some()
+        || daemon.ready_port.as_ref().is_some_and(|p| p.port.is_some())
+        || daemon.ready_unix_socket.is_some()
+}
+
+/// Probe a daemon's HTTP check, falling back to its TCP port check and then
+/// its unix socket check
+async fn probe(daemon: &Daemon, client: &reqwest::Client, timeout: Duration) -> bool {
+    if let Some(http) = &daemon.ready_http {
+        return check_http(client, http).await.is_ok();
+    }
+    if let Some(port) = daemon.ready_port.as_ref().and_then(|p| p.port) {
+        return matches!(
+            time::timeout(timeout, tokio::net::TcpStream::connect(("127.0.0.1", port))).await,
+            Ok(Ok(_))
+        );
+    }
+    match &daemon.ready_unix_socket {
+        Some(socket) => time::timeout(timeout, unix_socket_listening(Path::new(socket)))
+            .await
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+/// Send the request a `ready_http` check describes. Returns the response
+/// status if it passes, or why it doesn't.
+pub(super) async fn check_http(
+    client: &reqwest::Client,
+    http: &ReadyHttp,
+) -> std::result::Result<reqwest::StatusCode, String> {
+    let method =
+        reqwest::Method::from_bytes(http.method().as_bytes()).map_err(|e| e.to_string())?;
+    let mut request = client.request(method, &http.url);
+    for (name, value) in &http.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !http.accepts_status(status.as_u16()) {
+        return Err(format!("status {status}"));
+    }
+    if let Some(pattern) = &http.expect_body_regex {
+        let re = regex::Regex::new(pattern).map_err(|e| e.to_string())?;
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        if !re.is_match(&body) {
+            return Err(format!("status {status}, body does not match {pattern:?}"));
+        }
+    }
+    Ok(status)
+}
+
+/// Whether something accepts connections on the unix socket at `path`.
+/// Always false on platforms without unix sockets.
+pub(super) async fn unix_socket_listening(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        tokio::net::UnixStream::connect(path).await.is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitchfork_toml::ReadyPort;
+
+    #[tokio::test]
+    async fn test_probe_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let daemon = Daemon {
+            ready_port: Some(ReadyPort::new(port)),
+            ..Daemon::default()
+        };
+        assert!(has_health_check(&daemon));
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let client = reqwest::Client::new();
+        let timeout = Duration::from_secs(1);
+        assert!(probe(&daemon, &client, timeout).await);
+        drop(listener);
+        assert!(!probe(&daemon, &client, timeout).await);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_probe_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.sock");
+        let daemon = Daemon {
+            ready_unix_socket: Some(path.to_string_lossy().to_string()),
+            ..Daemon::default()
+        };
+        assert!(has_health_check(&daemon));
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let client = reqwest::Client::new();
+        let timeout = Duration::from_secs(1);
+        assert!(!probe(&daemon, &client, timeout).await);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        assert!(probe(&daemon, &client, timeout).await);
+        drop(listener);
+        std::fs::remove_file(&path).unwrap();
+        assert!(!probe(&daemon, &client, timeout).await);
+    }
+
+    #[tokio::test]
+    async fn test_check_http_request() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let mut requests = vec![];
+            for body in ["status: ok", "status: starting"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        let http = ReadyHttp {
+            url: format!("http://127.0.0.1:{port}/health"),
+            method: Some("POST".to_string()),
+            headers: [("Authorization".to_string(), "Bearer abc".to_string())].into(),
+            expect_body_regex: Some("status: ok".to_string()),
+            ..ReadyHttp::default()
+        };
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let client = reqwest::Client::new();
+        assert!(check_http(&client, &http).await.is_ok());
+        let err = check_http(&client, &http).await.unwrap_err();
+        assert!(err.contains("body does not match"), "{err}");
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("post /health "));
+        assert!(requests[0].contains("authorization: bearer abc"));
+    }
+
+    #[test]
+    fn test_no_health_check() {
+        assert!(!has_health_check(&Daemon::default()));
+    }
+}
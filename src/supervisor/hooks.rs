@@ -9,10 +9,13 @@ use crate::daemon_id::DaemonId;
 use crate::pitchfork_toml::PitchforkToml;
 use crate::settings::settings;
 use crate::supervisor::SUPERVISOR;
+use crate::supervisor::events;
+use crate::supervisor::lifecycle::run_as_daemon_user;
 use crate::{env, pitchfork_toml, template};
 use indexmap::IndexMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// The type of lifecycle hook to fire
 #[allow(clippy::enum_variant_names)]
@@ -22,6 +25,23 @@ pub(crate) enum HookType {
     OnRetry,
     OnStop,
     OnExit,
+    OnOom,
+    OnAlert,
+}
+
+impl HookType {
+    /// Event kind recorded in the daemon's recent events
+    fn event_kind(&self) -> &'static str {
+        match self {
+            HookType::OnReady => "ready",
+            HookType::OnFail => "fail",
+            HookType::OnRetry => "retry",
+            HookType::OnStop => "stop",
+            HookType::OnExit => "exit",
+            HookType::OnOom => "oom",
+            HookType::OnAlert => "alert",
+        }
+    }
 }
 
 impl std::fmt::Display for HookType {
@@ -32,6 +52,8 @@ impl std::fmt::Display for HookType {
             HookType::OnRetry => write!(f, "on_retry"),
             HookType::OnStop => write!(f, "on_stop"),
             HookType::OnExit => write!(f, "on_exit"),
+            HookType::OnOom => write!(f, "on_oom"),
+            HookType::OnAlert => write!(f, "on_alert"),
         }
     }
 }
@@ -46,6 +68,8 @@ fn get_hook_cmd(
         HookType::OnRetry => h.on_retry.clone(),
         HookType::OnStop => h.on_stop.clone(),
         HookType::OnExit => h.on_exit.clone(),
+        HookType::OnOom => h.on_oom.clone(),
+        HookType::OnAlert => h.on_alert.clone(),
     })
 }
 
@@ -53,7 +77,7 @@ fn get_hook_cmd(
 /// setting (same shell used for daemon `run` commands). Returns an error
 /// if the shell setting is empty or unparseable, matching daemon startup
 /// validation — callers should log and skip the hook.
-fn hook_command(cmd: &str) -> Result<tokio::process::Command> {
+pub(crate) fn hook_command(cmd: &str) -> Result<tokio::process::Command> {
     let shell_setting = settings().general.shell.clone();
     match shell_words::split(&shell_setting) {
         Ok(parts) if !parts.is_empty() => {
@@ -64,7 +88,7 @@ fn hook_command(cmd: &str) -> Result<tokio::process::Command> {
             Ok(command)
         }
         Ok(_) => Err(miette::miette!(
-            "general.shell setting is empty, cannot run hook"
+            "general.shell setting is empty, cannot run {cmd:?}"
         )),
         Err(e) => Err(miette::miette!(
             "failed to parse general.shell setting {shell_setting:?}: {e}"
@@ -72,6 +96,99 @@ fn hook_command(cmd: &str) -> Result<tokio::process::Command> {
     }
 }
 
+/// Start `command` in a new process group so that everything it spawns can be
+/// killed together if it runs past its timeout.
+pub(crate) fn own_process_group(command: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    command.process_group(0);
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// SIGKILL the process group led by `child` (see [`own_process_group`]) and
+/// reap the child.
+pub(crate) async fn kill_process_group(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let ret = unsafe { libc::killpg(pid as i32, libc::SIGKILL) };
+        if ret == -1 {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                warn!("failed to send SIGKILL to process group {pid}: {err}");
+            }
+        }
+    }
+    let _ = child.start_kill();
+    let _ = child.wait().await;
+}
+
+/// Timeout for a daemon's hooks: `hooks.timeout`, falling back to
+/// `supervisor.hook_timeout`. `None` when the resolved value is zero.
+fn hook_timeout(pt: &PitchforkToml, daemon_id: &DaemonId) -> Option<Duration> {
+    let timeout = pt
+        .daemons
+        .get(daemon_id)
+        .and_then(|d| d.hooks.as_ref())
+        .and_then(|h| h.timeout)
+        .unwrap_or_else(|| settings().supervisor_hook_timeout());
+    (!timeout.is_zero()).then_some(timeout)
+}
+
+/// Run a prepared hook command to completion as the daemon's user, killing
+/// its process group and recording a `timeout` event if it outlives `timeout`.
+async fn run_hook_command(
+    mut command: tokio::process::Command,
+    hook_name: &str,
+    daemon_id: &DaemonId,
+    timeout: Option<Duration>,
+) {
+    own_process_group(&mut command);
+    let user = SUPERVISOR
+        .get_daemon(daemon_id)
+        .await
+        .and_then(|daemon| daemon.user);
+    if let Err(e) = run_as_daemon_user(&mut command, user.as_deref()) {
+        error!("not running {hook_name} hook for daemon {daemon_id}: {e}");
+        return;
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            error!("failed to execute {hook_name} hook for daemon {daemon_id}: {e}");
+            return;
+        }
+    };
+
+    let status = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => status,
+            Err(_) => {
+                kill_process_group(&mut child).await;
+                let after = humantime::format_duration(timeout);
+                warn!("{hook_name} hook for daemon {daemon_id} timed out after {after}, killed");
+                events::record(
+                    daemon_id,
+                    "timeout",
+                    Some(format!("{hook_name} hook after {after}")),
+                );
+                return;
+            }
+        },
+        None => child.wait().await,
+    };
+
+    match status {
+        Ok(status) => {
+            if !status.success() {
+                warn!("{hook_name} hook for daemon {daemon_id} exited with {status}");
+            }
+        }
+        Err(e) => {
+            error!("failed to execute {hook_name} hook for daemon {daemon_id}: {e}");
+        }
+    }
+}
+
 /// Fire a hook command as a fire-and-forget tokio task.
 ///
 /// Reads the hook command from fresh config (`PitchforkToml::all_merged()`),
@@ -88,6 +205,21 @@ pub(crate) async fn fire_hook(
     daemon_env: Option<IndexMap<String, String>>,
     extra_env: Vec<(String, String)>,
 ) {
+    // Every lifecycle transition passes through here, whether or not a hook
+    // is configured for it, so this is also where it's recorded as an event.
+    let message = match hook_type {
+        HookType::OnRetry => Some(format!("attempt {retry_count}")),
+        HookType::OnAlert => extra_env
+            .iter()
+            .find(|(key, _)| key == "PITCHFORK_ALERT")
+            .map(|(_, alert)| alert.clone()),
+        _ => extra_env
+            .iter()
+            .find(|(key, _)| key == "PITCHFORK_EXIT_CODE")
+            .map(|(_, code)| format!("exit code {code}")),
+    };
+    events::record(&daemon_id, hook_type.event_kind(), message);
+
     let handle = tokio::spawn(async move {
         let pt = PitchforkToml::all_merged_all_namespaces().unwrap_or_else(|e| {
             warn!("Failed to load config for hook '{hook_type}': {e}");
@@ -99,6 +231,7 @@ pub(crate) async fn fire_hook(
             .and_then(|d| get_hook_cmd(&d.hooks, &hook_type));
 
         let Some(cmd) = hook_cmd else { return };
+        let timeout = hook_timeout(&pt, &daemon_id);
 
         // Render Tera templates in hook command with context from state file
         let cmd = match render_hook_template(&cmd, &daemon_id, &pt).await {
@@ -142,16 +275,7 @@ pub(crate) async fn fire_hook(
             command.env(key, value);
         }
 
-        match command.status().await {
-            Ok(status) => {
-                if !status.success() {
-                    warn!("{hook_type} hook for daemon {daemon_id} exited with {status}");
-                }
-            }
-            Err(e) => {
-                error!("failed to execute {hook_type} hook for daemon {daemon_id}: {e}");
-            }
-        }
+        run_hook_command(command, &hook_type.to_string(), &daemon_id, timeout).await;
     });
 
     // Register the handle so supervisor shutdown can await it.
@@ -216,16 +340,8 @@ pub(crate) async fn fire_output_hook(
             .env("PITCHFORK_RETRY_COUNT", retry_count.to_string())
             .env("PITCHFORK_MATCHED_LINE", &matched_line);
 
-        match command.status().await {
-            Ok(status) => {
-                if !status.success() {
-                    warn!("on_output hook for daemon {daemon_id} exited with {status}");
-                }
-            }
-            Err(e) => {
-                error!("failed to execute on_output hook for daemon {daemon_id}: {e}");
-            }
-        }
+        let timeout = hook_timeout(&pt, &daemon_id);
+        run_hook_command(command, "on_output", &daemon_id, timeout).await;
     });
 
     let mut tasks = SUPERVISOR.hook_tasks.lock().await;
@@ -266,10 +382,16 @@ async fn render_hook_template(
         &pt.daemons,
     );
 
-    // Merge top-level env with per-daemon env (per-daemon wins), render the
-    // values, and expose them as `{{ env.X }}` for hook templates.
-    if let Some(rendered_env) = template::render_env(
+    // Merge top-level env and env templates with per-daemon env (per-daemon
+    // wins), render the values, and expose them as `{{ env.X }}` for hook
+    // templates.
+    let defaults = template::env_defaults(
         pt.env.as_ref(),
+        &pt.env_templates,
+        daemon_config.map_or(&[], |d| d.env_templates.as_slice()),
+    )?;
+    if let Some(rendered_env) = template::render_env(
+        defaults.as_ref(),
         daemon_config.and_then(|d| d.env.as_ref()),
         &ctx,
     )? {
@@ -2,11 +2,15 @@
 //!
 //! Handles incoming IPC requests from CLI clients and routes them to the appropriate handlers.
 
-use super::{SUPERVISOR, Supervisor};
+use super::users::{self, Caller};
+use super::{SUPERVISOR, Supervisor, ipc_log};
 use crate::Result;
-use crate::ipc::server::IpcServer;
-use crate::ipc::{IpcRequest, IpcResponse};
+use crate::daemon_id::DaemonId;
+use crate::ipc::server::{IpcPeer, IpcServer};
+use crate::ipc::{IpcRequest, IpcResponse, SupervisorInfo};
+use crate::log_store::recent;
 use miette::IntoDiagnostic;
+use std::sync::atomic::Ordering;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -14,7 +18,7 @@ impl Supervisor {
     /// Main IPC connection watch loop - reads and dispatches requests
     pub(crate) async fn conn_watch(&self, mut ipc: IpcServer) -> ! {
         loop {
-            let (msg, send) = match ipc.read().await {
+            let (msg, send, peer) = match ipc.read().await {
                 Ok(msg) => msg,
                 Err(e) => {
                     error!("failed to accept connection: {e:?}");
@@ -22,11 +26,16 @@ impl Supervisor {
                 }
             };
             debug!("received message: {msg:?}");
+            // Reading the log shouldn't push out what it is meant to show
+            let seq = (!msg.is_get_ipc_log()).then(|| ipc_log::started(&msg, peer));
             tokio::spawn(async move {
                 let rsp = SUPERVISOR
-                    .handle_ipc(msg)
+                    .handle_ipc(msg, peer)
                     .await
                     .unwrap_or_else(|err| IpcResponse::Error(err.to_string()));
+                if let Some(seq) = seq {
+                    ipc_log::finished(seq, &rsp);
+                }
                 if let Err(err) = send.send(rsp).await {
                     debug!("failed to send message: {err:?}");
                 }
@@ -35,18 +44,31 @@ impl Supervisor {
     }
 
     /// Handle a single IPC request and return the appropriate response
-    pub(crate) async fn handle_ipc(&self, req: IpcRequest) -> Result<IpcResponse> {
-        let rsp = match req {
-            IpcRequest::Invalid { error } => {
-                warn!("Invalid IPC request: {error}");
-                return Ok(IpcResponse::Error(format!("Invalid request: {error}")));
+    pub(crate) async fn handle_ipc(&self, req: IpcRequest, peer: IpcPeer) -> Result<IpcResponse> {
+        // On a shared supervisor users may only act on their own daemons
+        let caller = Caller::from_peer(peer);
+        // Cleaning and shell or project changes only touch a user's own daemons
+        let own = caller.as_ref().filter(|c| !c.admin).map(|c| c.name.clone());
+        if let Some(caller) = &caller
+            && let Some(id) = req.daemon_id()
+        {
+            let daemon = self.get_daemon(id).await;
+            if !caller.may_manage(daemon.as_ref()) {
+                let error = match daemon.and_then(|d| d.owner) {
+                    Some(owner) => format!("daemon {id} belongs to user '{owner}'"),
+                    None => format!("daemon {id} was not started by user '{}'", caller.name),
+                };
+                return Ok(IpcResponse::Error(error));
             }
+        }
+        let rsp = match req {
             IpcRequest::Connect => {
                 debug!("received connect message (legacy, no version info)");
                 IpcResponse::Ok
             }
             IpcRequest::ConnectV2 {
                 version: client_version,
+                ..
             } => {
                 debug!("received connect message (client version: {client_version})");
                 if client_version != VERSION {
@@ -63,8 +85,18 @@ impl Supervisor {
                 // id is already DaemonId, no validation needed
                 self.stop(&id).await?
             }
-            IpcRequest::Run(opts) => {
+            IpcRequest::Run(mut opts) => {
                 // opts.id is already DaemonId, no validation needed
+                // The owner is never taken from the client
+                opts.owner = None;
+                if let Some(caller) = &caller {
+                    // Whatever holds the port may be root's or another user's
+                    if opts.kill_conflicts && !caller.admin {
+                        miette::bail!("only root can kill port conflicts on a shared supervisor");
+                    }
+                    let existing_owner = self.get_daemon(&opts.id).await.and_then(|d| d.owner);
+                    caller.claim(&mut opts, existing_owner);
+                }
                 self.run(opts).await?
             }
             IpcRequest::Enable { id } => {
@@ -75,40 +107,86 @@ impl Supervisor {
                     IpcResponse::No
                 }
             }
-            IpcRequest::Disable { id } => {
+            IpcRequest::Disable { id, auto } => {
                 // id is already DaemonId, no validation needed
-                if self.disable(&id).await? {
+                if self.disable(&id, auto).await? {
+                    IpcResponse::Yes
+                } else {
+                    IpcResponse::No
+                }
+            }
+            IpcRequest::Retry { id } => self.retry_now(&id).await?,
+            IpcRequest::CancelRetry { id } => {
+                if self.cancel_retry(&id).await? {
                     IpcResponse::Yes
                 } else {
                     IpcResponse::No
                 }
             }
             IpcRequest::GetActiveDaemons => {
-                let daemons = self.active_daemons().await;
+                let mut daemons = self.active_daemons().await;
+                if let Some(caller) = &caller {
+                    let view = caller.view(None)?;
+                    daemons.retain(|d| users::visible_to(d, view.as_deref()));
+                }
                 IpcResponse::ActiveDaemons(daemons)
             }
+            IpcRequest::GetUserDaemons { user } => {
+                let Some(caller) = &caller else {
+                    miette::bail!(
+                        "the supervisor is not shared between users (settings.supervisor.socket_group)"
+                    );
+                };
+                let view = caller.view(user.as_deref())?;
+                let pitchfork_id = DaemonId::pitchfork();
+                let state_file = self.state_file.lock().await;
+                let daemons = state_file
+                    .daemons
+                    .values()
+                    .filter(|d| d.id != pitchfork_id && users::visible_to(d, view.as_deref()))
+                    .cloned()
+                    .collect();
+                let auto_disabled = state_file
+                    .auto_disabled
+                    .iter()
+                    .filter(|id| users::id_visible_to(&state_file.daemons, id, view.as_deref()))
+                    .cloned()
+                    .collect();
+                IpcResponse::UserDaemons {
+                    daemons,
+                    auto_disabled,
+                }
+            }
             IpcRequest::GetNotifications => {
-                let notifications = self.get_notifications().await;
+                // Everyone, admins included, only takes their own daemons'
+                let user = caller.as_ref().map(|c| c.name.as_str());
+                let notifications = self.get_notifications(user).await;
                 IpcResponse::Notifications(notifications)
             }
-            IpcRequest::UpdateShellDir { shell_pid, dir } => {
-                let prev = self.get_shell_dir(shell_pid).await;
-                self.set_shell_dir(shell_pid, dir.clone()).await?;
-                // Cancel any pending autostops for daemons in the new directory
-                self.cancel_pending_autostops_for_dir(&dir).await;
-                if let Some(prev) = prev {
-                    self.leave_dir(&prev).await?;
-                }
-                self.refresh().await?;
+            IpcRequest::UpdateShellDir {
+                shell_pid,
+                dir,
+                seq,
+            } => {
+                // Acknowledge right away so the shell prompt isn't held up;
+                // the change is applied by the shell dir watcher.
+                self.queue_shell_dir(shell_pid, dir, seq, own).await;
                 IpcResponse::Ok
             }
-            IpcRequest::Clean => {
-                self.clean().await?;
+            IpcRequest::Clean { keep } => {
+                self.clean(&keep, own.as_deref()).await?;
                 IpcResponse::Ok
             }
             IpcRequest::GetDisabledDaemons => {
-                let disabled = self.state_file.lock().await.disabled.clone();
-                IpcResponse::DisabledDaemons(disabled.into_iter().collect())
+                let state_file = self.state_file.lock().await;
+                let mut disabled: Vec<_> = state_file.disabled.iter().cloned().collect();
+                if let Some(caller) = &caller {
+                    let view = caller.view(None)?;
+                    disabled.retain(|id| {
+                        users::id_visible_to(&state_file.daemons, id, view.as_deref())
+                    });
+                }
+                IpcResponse::DisabledDaemons(disabled)
             }
             IpcRequest::SyncMdns => {
                 self.sync_mdns().await;
@@ -126,7 +204,8 @@ impl Supervisor {
             IpcRequest::ProjectEnter { pid, dir } => {
                 debug!("handling project enter pid {pid} dir {}", dir.display());
                 let prev = self.enter_project_session(pid, dir.clone()).await?;
-                self.cancel_pending_autostops_for_dir(&dir).await;
+                self.cancel_pending_autostops_for_dir(&dir, own.as_deref())
+                    .await;
                 // When re-entering (prev.is_some()), the new session keeps the
                 // directory active, so leave_dir would be a no-op. Skip it to
                 // avoid unnecessary autostop evaluation.
@@ -140,7 +219,7 @@ impl Supervisor {
                     debug!(
                         "project leave removed session pid {pid}, evaluating {left_dir:?} for autostop"
                     );
-                    self.leave_dir(&left_dir).await?;
+                    self.leave_dir(&left_dir, own.as_deref()).await?;
                 } else {
                     debug!(
                         "project leave: session pid {pid} dir {} not found",
@@ -154,6 +233,54 @@ impl Supervisor {
                 let sessions = self.get_project_sessions_info().await;
                 IpcResponse::ProjectSessions(sessions)
             }
+            IpcRequest::GetRecentLogs { id, lines } => {
+                IpcResponse::RecentLogs(recent::recent_lines(&id, lines).await?)
+            }
+            IpcRequest::GetSupervisorInfo => IpcResponse::SupervisorInfo(SupervisorInfo {
+                pid: std::process::id(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                socket: crate::ipc::main_socket_display(),
+                boot_daemons_started: self.boot_daemons_started.load(Ordering::Relaxed),
+                paused: self.state_file.lock().await.paused,
+            }),
+            IpcRequest::SetPaused { paused } => {
+                if caller.as_ref().is_some_and(|c| !c.admin) {
+                    miette::bail!("only root can pause or resume a shared supervisor");
+                }
+                if self.set_paused(paused).await? {
+                    IpcResponse::Yes
+                } else {
+                    IpcResponse::No
+                }
+            }
+            IpcRequest::GetIpcLog => {
+                // Other users' requests stay private on a shared supervisor
+                let uid = match &caller {
+                    Some(caller) if !caller.admin => Some(peer.uid.unwrap_or(u32::MAX)),
+                    _ => None,
+                };
+                IpcResponse::IpcLog(ipc_log::recent(uid))
+            }
+            IpcRequest::Shutdown { keep_daemons } => {
+                if caller.as_ref().is_some_and(|c| !c.admin) {
+                    miette::bail!("only root can stop a shared supervisor");
+                }
+                // Answer before the IPC server is shut down
+                tokio::spawn(async move { SUPERVISOR.shutdown(keep_daemons).await });
+                IpcResponse::Ok
+            }
+            IpcRequest::RenameDaemon { from, to } => {
+                // `from` was checked above; the new ID mustn't be another user's
+                if let Some(caller) = &caller
+                    && !caller.may_manage(self.get_daemon(&to).await.as_ref())
+                {
+                    return Ok(IpcResponse::Error(format!(
+                        "daemon {to} belongs to another user"
+                    )));
+                }
+                self.rename_daemon(&from, &to).await?;
+                IpcResponse::Ok
+            }
         };
         // Ensure state is flushed to disk before returning the response
         // so that CLI commands reading StateFile::get() see fresh data.
@@ -0,0 +1,109 @@
+//! Recent IPC activity
+//!
+//! The supervisor keeps the last requests it received, with how long each
+//! took and what it answered. Requests still being handled are included, so
+//! when a CLI command hangs `pitchfork debug ipc` shows what it is waiting
+//! on. Like the event history, this is in memory only.
+
+use crate::ipc::server::IpcPeer;
+use crate::ipc::{IpcLogEntry, IpcRequest, IpcResponse};
+use chrono::Local;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Requests kept; older ones are dropped.
+const MAX_ENTRIES: usize = 200;
+
+static LOG: Lazy<Mutex<IpcLog>> = Lazy::new(|| Mutex::new(IpcLog::default()));
+
+#[derive(Default)]
+struct IpcLog {
+    next_seq: u64,
+    entries: VecDeque<(Instant, IpcLogEntry)>,
+}
+
+/// Record a request as it arrives. Returns its sequence number, to pass to
+/// [`finished`] once it is answered.
+pub(crate) fn started(req: &IpcRequest, peer: IpcPeer) -> u64 {
+    let mut log = LOG.lock().unwrap();
+    log.next_seq += 1;
+    let seq = log.next_seq;
+    if log.entries.len() >= MAX_ENTRIES {
+        log.entries.pop_front();
+    }
+    log.entries.push_back((
+        Instant::now(),
+        IpcLogEntry {
+            seq,
+            at: Local::now(),
+            request: req.to_string(),
+            daemon: req.daemon_id().map(|id| id.qualified()),
+            uid: peer.uid,
+            elapsed_ms: 0,
+            response: None,
+        },
+    ));
+    seq
+}
+
+/// Record the response to request `seq`
+pub(crate) fn finished(seq: u64, rsp: &IpcResponse) {
+    let mut log = LOG.lock().unwrap();
+    // Entries are in sequence order, and old ones may have been dropped
+    let Some((received, entry)) = log.entries.iter_mut().rev().find(|(_, e)| e.seq == seq) else {
+        return;
+    };
+    entry.elapsed_ms = received.elapsed().as_millis() as u64;
+    entry.response = Some(match rsp {
+        IpcResponse::Error(error) => format!("Error: {error}"),
+        rsp => rsp.to_string(),
+    });
+}
+
+/// Recent requests, oldest first. Only those from `uid` when given.
+pub(crate) fn recent(uid: Option<u32>) -> Vec<IpcLogEntry> {
+    LOG.lock()
+        .unwrap()
+        .entries
+        .iter()
+        .filter(|(_, e)| uid.is_none() || e.uid == uid)
+        .map(|(received, entry)| {
+            let mut entry = entry.clone();
+            if entry.response.is_none() {
+                entry.elapsed_ms = received.elapsed().as_millis() as u64;
+            }
+            entry
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon_id::DaemonId;
+
+    #[test]
+    fn test_pending_and_finished_requests() {
+        // Other tests may record requests concurrently, so look only at
+        // this test's uid
+        let peer = IpcPeer { uid: Some(4242) };
+        let id = DaemonId::new("ipc-log-test", "api");
+        let stop = started(&IpcRequest::Stop { id: id.clone() }, peer);
+        let pending = started(&IpcRequest::GetActiveDaemons, peer);
+        finished(stop, &IpcResponse::Error("no such daemon".into()));
+
+        let entries = recent(Some(4242));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].request, "Stop");
+        assert_eq!(entries[0].daemon.as_deref(), Some("ipc-log-test/api"));
+        assert_eq!(
+            entries[0].response.as_deref(),
+            Some("Error: no such daemon")
+        );
+        assert_eq!(entries[1].seq, pending);
+        assert_eq!(entries[1].request, "GetActiveDaemons");
+        assert_eq!(entries[1].response, None);
+    }
+}
@@ -3,29 +3,44 @@
 //! Contains the core `run()`, `run_once()`, and `stop()` methods for daemon process management.
 
 use super::hooks::{self, HookType, fire_hook};
+use super::log_limit::LogLimiter;
+use super::output::OutputLines;
+use super::ready_file::LogFileTail;
+use super::requires::Requirements;
+use super::retry::retry_backoff;
 use super::{SUPERVISOR, Supervisor};
-use crate::daemon::RunOptions;
+use super::{detach, events, oom};
+use crate::daemon::{ExitRecord, RunOptions, StartupRecord};
 use crate::daemon_id::DaemonId;
 use crate::daemon_status::DaemonStatus;
 use crate::error::PortError;
 use crate::ipc::IpcResponse;
-use crate::log_store::LogStore;
+use crate::log_store::disk_full::{DISK_FULL, Transition};
+use crate::log_store::journal::{self, LogSink};
+use crate::log_store::recent::RECENT_LOGS;
 use crate::log_store::sqlite::LOG_STORE;
-use crate::pitchfork_toml::{ReadyCmd, ReadyHttp, ReadyOutput, ReadyPort};
+use crate::log_store::{LogStore, LogStream};
+#[cfg(unix)]
+use crate::pitchfork_toml::ResourceLimits;
+use crate::pitchfork_toml::StopSignal;
+use crate::pitchfork_toml::{
+    ReadyCmd, ReadyHttp, ReadyLogFile, ReadyOutput, ReadyPort, ReadyWhen, ReadyWhenState,
+};
 use crate::procs::PROCS;
 use crate::settings::settings;
 use crate::shell::Shell;
 use crate::supervisor::state::UpsertDaemonOpts;
 use crate::{Result, env};
+use indexmap::IndexMap;
 use miette::IntoDiagnostic;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashMap;
 #[cfg(unix)]
 use std::ffi::CString;
+use std::path::Path;
 use std::sync::{Arc, atomic};
 use std::time::Duration;
-use tokio::io::AsyncBufReadExt;
 use tokio::select;
 use tokio::sync::oneshot;
 use tokio::time;
@@ -34,6 +49,10 @@ use tokio::time;
 static REGEX_CACHE: Lazy<std::sync::Mutex<HashMap<String, Regex>>> =
     Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
 
+/// How long a daemon with `expect_long_running` must stay up for a clean
+/// exit not to count as a failure
+const LONG_RUNNING_GRACE: Duration = Duration::from_secs(10);
+
 #[cfg(unix)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum RunIdentity {
@@ -77,10 +96,20 @@ struct CmdProbe {
 /// Spawn a readiness command probe and return a handle that can be used to wait
 /// for the exit status or cancel the probe.
 ///
-/// The probe is started with `kill_on_drop(true)` as a cancellation fallback. The
-/// spawned task waits for the process to exit; if cancellation is requested, it
-/// kills the child and waits for it to reap before reporting the result.
-fn spawn_cmd_probe(id: &DaemonId, cmd: &str, dir: &std::path::Path) -> CmdProbe {
+/// The probe is started in its own process group with `kill_on_drop(true)` as a
+/// cancellation fallback. The spawned task waits for the process to exit; if
+/// cancellation is requested, it kills the group and waits for the child to reap
+/// before reporting the result. A probe still running after `timeout` is killed
+/// the same way, recorded as a `timeout` event, and reported as an error.
+/// The probe runs as the daemon's `user`.
+fn spawn_cmd_probe(
+    id: &DaemonId,
+    cmd: &str,
+    dir: &std::path::Path,
+    env: Option<&IndexMap<String, String>>,
+    timeout: Option<Duration>,
+    user: Option<&str>,
+) -> CmdProbe {
     // Use the configured general.shell setting (same as daemon run and hooks)
     // instead of default_for_platform(). On Windows, default_for_platform()
     // returns Shell::Cmd which cannot parse Unix-style commands like
@@ -97,12 +126,18 @@ fn spawn_cmd_probe(id: &DaemonId, cmd: &str, dir: &std::path::Path) -> CmdProbe
         }
         _ => Shell::default_for_platform().command(cmd),
     };
+    if let Some(env) = env {
+        command.envs(env);
+    }
     command
         .current_dir(dir)
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .kill_on_drop(true);
-    let mut child = match command.spawn() {
+    hooks::own_process_group(&mut command);
+    let spawned =
+        run_as_daemon_user(&mut command, user).and_then(|()| command.spawn().into_diagnostic());
+    let mut child = match spawned {
         Ok(child) => child,
         Err(e) => {
             warn!("daemon {id}: failed to spawn readiness command probe: {e}");
@@ -121,14 +156,29 @@ fn spawn_cmd_probe(id: &DaemonId, cmd: &str, dir: &std::path::Path) -> CmdProbe
     let (cancel_tx, mut cancel_rx) = tokio::sync::oneshot::channel();
     let (result_tx, result_rx) = tokio::sync::oneshot::channel();
 
+    let id = id.clone();
     tokio::spawn(async move {
         let status = tokio::select! {
             status = child.wait() => status,
             _ = &mut cancel_rx => {
-                let mut child = child;
-                let _ = child.kill().await;
+                hooks::kill_process_group(&mut child).await;
                 child.wait().await
             }
+            _ = async {
+                match timeout {
+                    Some(t) => time::sleep(t).await,
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                hooks::kill_process_group(&mut child).await;
+                let after = humantime::format_duration(timeout.unwrap_or_default());
+                warn!("daemon {id}: ready_cmd timed out after {after}, killed");
+                events::record(&id, "timeout", Some(format!("ready_cmd after {after}")));
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("ready_cmd timed out after {after}"),
+                ))
+            }
         };
         let _ = result_tx.send(status);
     });
@@ -139,6 +189,15 @@ fn spawn_cmd_probe(id: &DaemonId, cmd: &str, dir: &std::path::Path) -> CmdProbe
     }
 }
 
+/// Per-run limit for a `ready_cmd` probe: `attempt_timeout`, falling back to
+/// `supervisor.ready_cmd_timeout`. `None` when the resolved value is zero.
+fn ready_cmd_attempt_timeout(cmd: &ReadyCmd) -> Option<Duration> {
+    let timeout = cmd
+        .attempt_timeout
+        .unwrap_or_else(|| settings().supervisor_ready_cmd_timeout());
+    (!timeout.is_zero()).then_some(timeout)
+}
+
 /// Cancel an active command probe and clear its handle.
 fn stop_cmd_probe_state(probe: &mut Option<CmdProbe>) {
     if let Some(p) = probe.take() {
@@ -148,8 +207,9 @@ fn stop_cmd_probe_state(probe: &mut Option<CmdProbe>) {
 
 /// Returns true if any configured readiness check can still succeed.
 /// A check with no timeout is unbounded; a timed check can still succeed until its
-/// deadline fires. `ready_delay` is only used as a fallback when no other check is
-/// configured, so it is not counted here.
+/// deadline fires. A unix socket check has no timeout, so it never runs out.
+/// `ready_delay` is only used as a fallback when no other check is configured, so
+/// it is not counted here.
 #[allow(clippy::too_many_arguments)]
 fn any_ready_check_remaining(
     ready_output: Option<&ReadyOutput>,
@@ -160,18 +220,182 @@ fn any_ready_check_remaining(
     http_exhausted: bool,
     ready_cmd: Option<&ReadyCmd>,
     cmd_exhausted: bool,
+    ready_log_file: Option<&ReadyLogFile>,
+    log_file_exhausted: bool,
+    ready_unix_socket: Option<&Path>,
+    ready_when: Option<&ReadyWhen>,
+    when_exhausted: bool,
 ) -> bool {
     ready_output.is_some_and(|o| o.timeout.is_none() || !output_exhausted)
         || ready_port.is_some_and(|p| p.timeout.is_none() || !port_exhausted)
         || ready_http.is_some_and(|h| h.timeout.is_none() || !http_exhausted)
         || ready_cmd.is_some_and(|c| c.timeout.is_none() || !cmd_exhausted)
+        || ready_log_file.is_some_and(|f| f.timeout.is_none() || !log_file_exhausted)
+        || ready_unix_socket.is_some()
+        || ready_when.is_some_and(|w| w.timeout.is_none() || !when_exhausted)
+}
+
+/// Whether the `ready_when` upstream daemon has reached `state`
+async fn upstream_reached(upstream: &DaemonId, state: ReadyWhenState) -> bool {
+    let state_file = SUPERVISOR.state_file.lock().await;
+    state_file
+        .daemons
+        .get(upstream)
+        .is_some_and(|d| match state {
+            ReadyWhenState::Ready => matches!(d.status, DaemonStatus::Running),
+            ReadyWhenState::Started => d.status.is_up(),
+        })
+}
+
+/// Why a daemon started with `wait_ready` did not become ready
+enum ReadyFailure {
+    /// The process exited first
+    Exited(Option<i32>),
+    /// Every ready check gave up, or `wait_timeout` ran out
+    TimedOut(String),
+    /// A line matched `fail_output`
+    FailOutput(String),
+    /// The process exited cleanly, but `expect_long_running` is set
+    ExitedEarly(i32),
+}
+
+/// Whether a clean exit fails a daemon with `expect_long_running`: it
+/// exited before it was ready or within [`LONG_RUNNING_GRACE`] of spawning.
+fn exited_too_early(ready: bool, ran_for: Duration) -> bool {
+    !ready || ran_for < LONG_RUNNING_GRACE
+}
+
+/// Why a daemon with `expect_long_running` failed after a clean exit
+fn exited_early_message(exit_code: i32) -> String {
+    format!(
+        "exited with code {exit_code} right after starting, but expect_long_running is set; \
+         make sure the command stays in the foreground (e.g. drop a --daemon or --detach flag)"
+    )
+}
+
+impl ReadyFailure {
+    fn timed_out(checks: &str, waited: Duration) -> Self {
+        // Whole seconds read better, except for sub-second timeouts
+        let waited = if waited >= Duration::from_secs(1) {
+            Duration::from_secs(waited.as_secs())
+        } else {
+            Duration::from_millis(waited.as_millis() as u64)
+        };
+        Self::TimedOut(format!(
+            "gave up waiting for {checks} after {}",
+            humantime::format_duration(waited)
+        ))
+    }
+}
+
+/// The ready checks a daemon waits on, e.g. "TCP port 5432 or HTTP http://localhost:8080/health"
+#[allow(clippy::too_many_arguments)]
+fn describe_ready_checks(
+    output: Option<&ReadyOutput>,
+    port: Option<u16>,
+    unix_socket: Option<&Path>,
+    http: Option<&ReadyHttp>,
+    cmd: Option<&ReadyCmd>,
+    log_file: Option<&ReadyLogFile>,
+    when: Option<&ReadyWhen>,
+    delay: Option<Duration>,
+) -> String {
+    let mut checks = vec![];
+    if let Some(output) = output {
+        checks.push(format!("output matching '{}'", output.pattern));
+    }
+    if let Some(port) = port {
+        checks.push(format!("TCP port {port}"));
+    }
+    if let Some(socket) = unix_socket {
+        checks.push(format!("unix socket {}", socket.display()));
+    }
+    if let Some(http) = http {
+        checks.push(format!("HTTP {}", http.url));
+    }
+    if let Some(cmd) = cmd {
+        checks.push(format!("command '{}'", cmd.run));
+    }
+    if let Some(log_file) = log_file {
+        checks.push(format!("log file {}", log_file.path));
+    }
+    if let Some(when) = when {
+        checks.push(format!("daemon {when}"));
+    }
+    match (checks.is_empty(), delay) {
+        (false, _) => checks.join(" or "),
+        (true, Some(delay)) => format!("delay ({})", humantime::format_duration(delay)),
+        (true, None) => "readiness".to_string(),
+    }
+}
+
+/// Flip a daemon from `starting` to `running` once its ready check passes.
+async fn mark_ready(
+    id: &DaemonId,
+    pid: u32,
+    start_latency: Duration,
+    spawned_at: std::time::Instant,
+) {
+    let mut state_file = SUPERVISOR.state_file.lock().await;
+    let previous = state_file.daemons.get(id).map(|d| d.status.clone());
+    if state_file.set_up_status(id, pid, DaemonStatus::Running) {
+        events::status_changed(id, previous.as_ref(), &DaemonStatus::Running);
+        state_file.record_startup(
+            id,
+            StartupRecord {
+                at: chrono::Local::now(),
+                start_ms: start_latency.as_millis() as u64,
+                ready_ms: spawned_at.elapsed().as_millis() as u64,
+            },
+        );
+    }
+}
+
+/// Whether a daemon exit counts as clean: exit code 0, or one of the
+/// daemon's `success_exit_codes`.
+fn is_clean_exit(status: &std::process::ExitStatus, success_exit_codes: &[i32]) -> bool {
+    status.success()
+        || status
+            .code()
+            .is_some_and(|c| success_exit_codes.contains(&c))
+}
+
+/// Signal that terminated the process, if it was killed by one.
+/// Why a daemon's process exited: `stop` when pitchfork stopped it, `exit`
+/// for a clean exit, `killed` when a signal pitchfork didn't send ended it
+/// (e.g. someone ran `kill`), and `fail` otherwise.
+fn exit_reason(
+    status: &std::io::Result<std::process::ExitStatus>,
+    is_stopping: bool,
+    signalled: bool,
+    success_exit_codes: &[i32],
+) -> &'static str {
+    match status {
+        // child.wait() can also fail while stopping (e.g. sysinfo reaped the process)
+        _ if is_stopping => "stop",
+        Ok(s) if is_clean_exit(s, success_exit_codes) => "exit",
+        _ if !signalled && exit_signal(status).is_some() => "killed",
+        _ => "fail",
+    }
+}
+
+fn exit_signal(status: &std::io::Result<std::process::ExitStatus>) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.as_ref().ok().and_then(|s| s.signal())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
 }
 
 impl Supervisor {
     /// Run a daemon, handling retries if configured
     pub async fn run(&self, opts: RunOptions) -> Result<IpcResponse> {
         let id = &opts.id;
-        let cmd = opts.cmd.clone();
 
         // Clear any pending autostop for this daemon since it's being started
         {
@@ -182,7 +406,24 @@ impl Supervisor {
         }
 
         let daemon = self.get_daemon(id).await;
+        let mut restarted = false;
         if let Some(daemon) = daemon {
+            let changes = daemon.config_changes(&opts);
+            if !changes.is_empty() {
+                info!(
+                    "daemon {id} changed since its last run: {}",
+                    changes.join("; ")
+                );
+                events::record(id, "config", Some(changes.join("; ")));
+            }
+            if let Some(host) = daemon.remote_host()
+                && !daemon.status.is_stopped()
+            {
+                warn!("daemon {id} is already running on host {host}");
+                return Ok(IpcResponse::Error(format!(
+                    "daemon {id} is already running on host {host}; stop it there first"
+                )));
+            }
             // Stopping state is treated as "not running" - the monitoring task will clean it up
             // Only check for Running state with a valid PID
             if !daemon.status.is_stopping()
@@ -192,13 +433,54 @@ impl Supervisor {
                 if opts.force {
                     self.stop(id).await?;
                     info!("run: stop completed for daemon {id}");
+                    restarted = true;
                 } else {
                     warn!("daemon {id} already running with pid {pid}");
                     return Ok(IpcResponse::DaemonAlreadyRunning);
                 }
+            } else if let Some(req) = &daemon.waiting_for {
+                if opts.force {
+                    self.stop(id).await?;
+                } else {
+                    warn!("daemon {id} is already waiting for {req}");
+                    return Ok(IpcResponse::DaemonAlreadyRunning);
+                }
             }
         }
 
+        match self.wait_for_requirements(&opts).await? {
+            Requirements::Met => {}
+            Requirements::Unreachable(error) => {
+                self.upsert_daemon(
+                    UpsertDaemonOpts::from_run_options(&opts, DaemonStatus::Failed(error.clone()))
+                        .build(),
+                )
+                .await?;
+                return Ok(IpcResponse::DaemonFailed { error });
+            }
+            Requirements::Cancelled(error) => return Ok(IpcResponse::DaemonFailed { error }),
+        }
+
+        // Dependents with `restart_with_dependencies` follow a restart
+        let cascade_from = restarted.then(|| (id.clone(), opts.restart_batch.clone()));
+        let response = self.run_with_retries(opts).await?;
+        if let Some((id, restart_batch)) = cascade_from
+            && matches!(
+                response,
+                IpcResponse::DaemonStart { .. } | IpcResponse::DaemonReady { .. }
+            )
+        {
+            Self::spawn_restart_cascade(id, restart_batch);
+        }
+        Ok(response)
+    }
+
+    /// Run a daemon, retrying it until it's ready if `wait_ready` is set
+    /// and retries are configured
+    async fn run_with_retries(&self, opts: RunOptions) -> Result<IpcResponse> {
+        let id = &opts.id;
+        let cmd = opts.cmd.clone();
+
         // If wait_ready is true and retry is configured, implement retry loop
         if opts.wait_ready && opts.retry.count() > 0 {
             // Use saturating_add to avoid overflow when retry = u32::MAX (infinite)
@@ -214,29 +496,36 @@ impl Supervisor {
                     IpcResponse::DaemonReady { daemon } => {
                         return Ok(IpcResponse::DaemonReady { daemon });
                     }
-                    IpcResponse::DaemonFailedWithCode { exit_code } => {
+                    IpcResponse::DaemonFailedWithCode { exit_code }
+                        if exit_code.is_some_and(|c| opts.no_retry_exit_codes.contains(&c)) =>
+                    {
+                        info!("daemon {id} failed with a no-retry exit code, not retrying");
+                        return Ok(IpcResponse::DaemonFailedWithCode { exit_code });
+                    }
+                    IpcResponse::DaemonFailedWithCode { .. }
+                    | IpcResponse::DaemonNotReady { .. } => {
                         if attempt < opts.retry.count() {
-                            let backoff_secs = 2u64.saturating_pow(attempt).min(3600);
+                            let backoff = retry_backoff(attempt);
                             info!(
                                 "daemon {id} failed (attempt {}/{}), retrying in {}s",
                                 attempt + 1,
                                 max_attempts,
-                                backoff_secs
+                                backoff.as_secs()
                             );
                             fire_hook(
                                 HookType::OnRetry,
                                 id.clone(),
                                 opts.dir.0.clone(),
                                 attempt + 1,
-                                opts.env.clone(),
+                                opts.effective_env(),
                                 vec![],
                             )
                             .await;
-                            time::sleep(Duration::from_secs(backoff_secs)).await;
+                            self.clock.sleep(backoff).await;
                             continue;
                         } else {
                             info!("daemon {id} failed after {max_attempts} attempts");
-                            return Ok(IpcResponse::DaemonFailedWithCode { exit_code });
+                            return Ok(result);
                         }
                     }
                     other => return Ok(other),
@@ -249,7 +538,20 @@ impl Supervisor {
     }
 
     /// Run a daemon once (single attempt)
-    pub(crate) async fn run_once(&self, opts: RunOptions) -> Result<IpcResponse> {
+    pub(crate) async fn run_once(&self, mut opts: RunOptions) -> Result<IpcResponse> {
+        // A task is ready once it exits successfully, so ready checks
+        // (including the default ready delay) don't apply to it
+        if opts.kind.is_task() {
+            opts.ready_delay = None;
+            opts.ready_output = None;
+            opts.ready_http = None;
+            opts.ready_port = None;
+            opts.ready_cmd = None;
+            opts.ready_log_file = None;
+            opts.ready_unix_socket = None;
+            opts.ready_when = None;
+        }
+        let requested_at = std::time::Instant::now();
         let id = &opts.id;
         let original_cmd = opts.cmd.clone(); // Save original command for persistence
 
@@ -261,17 +563,40 @@ impl Supervisor {
             (None, None)
         };
 
+        let isolate_network = opts.isolate_network.unwrap_or(false);
+        if isolate_network && !cfg!(target_os = "linux") {
+            warn!("daemon {id}: isolate_network is only supported on Linux, ignoring it");
+        }
+        let isolate_network = isolate_network && cfg!(target_os = "linux");
+        // pasta accepts connections on the host port as soon as it starts, so
+        // a TCP check there would pass before the daemon listens
+        if isolate_network && opts.ready_port.is_some() {
+            return Ok(IpcResponse::DaemonFailed {
+                error: "ready_port can't be used with isolate_network; use ready_http, ready_cmd or ready_output instead".to_string(),
+            });
+        }
+
         // Check port availability and apply auto-bump if configured
         let expected_ports = opts
             .port
             .as_ref()
             .map(|p| p.expect.clone())
             .unwrap_or_default();
+        if opts.kill_conflicts {
+            let ports = expected_ports
+                .iter()
+                .copied()
+                .chain(opts.ready_port.as_ref().and_then(|p| p.as_port()))
+                .filter(|&p| p != 0);
+            self.kill_port_conflicts(id, ports).await;
+        }
         let (resolved_ports, effective_ready_port) = if !expected_ports.is_empty() {
             let port_cfg = opts.port.as_ref().unwrap();
+            // An isolated daemon never sees its host ports, so they can always
+            // be bumped without the daemon noticing
             match check_ports_available(
                 &expected_ports,
-                port_cfg.auto_bump(),
+                port_cfg.auto_bump() || isolate_network,
                 port_cfg.max_bump_attempts(),
             )
             .await
@@ -292,14 +617,20 @@ impl Supervisor {
                         } else {
                             Some(configured_port)
                         }
-                    } else if opts.ready_output.is_none()
+                    } else if !opts.kind.is_task()
+                        && !isolate_network
+                        && opts.ready_output.is_none()
                         && opts.ready_http.is_none()
                         && opts.ready_cmd.is_none()
+                        && opts.ready_log_file.is_none()
+                        && opts.ready_unix_socket.is_none()
+                        && opts.ready_when.is_none()
                         && opts.ready_delay.is_none()
                     {
                         // No other ready check configured — use the first expected port as a
                         // TCP port readiness check so the daemon is considered ready once it
-                        // starts listening.  Skip port 0 (ephemeral port request).
+                        // starts listening.  Skip port 0 (ephemeral port request), and
+                        // isolated daemons, whose host port is pasta's.
                         resolved.first().copied().filter(|&p| p != 0)
                     } else {
                         // Another ready check is configured (output/http/cmd/delay).
@@ -384,11 +715,22 @@ impl Supervisor {
             .clone()
             .unwrap_or_else(|| shell_words::join(&original_cmd));
 
+        // Environment and dir of the `mise run` task the daemon runs, if any
+        let mut mise_task = None;
         let (program, args) = if opts.mise.unwrap_or(settings().general.mise) {
             match settings().resolve_mise_bin() {
                 Some(mise_bin) => {
                     let mise_bin_str = mise_bin.to_string_lossy().to_string();
                     info!("daemon {id}: wrapping command with mise ({mise_bin_str})");
+                    if let Some(task) = opts.run.as_deref().and_then(super::mise::task_name) {
+                        match super::mise::resolve(&mise_bin, &task, &opts.dir.0).await {
+                            Ok(resolved) => {
+                                debug!("daemon {id}: using env of mise task {task}");
+                                mise_task = Some(resolved);
+                            }
+                            Err(e) => warn!("daemon {id}: failed to read mise task {task}: {e}"),
+                        }
+                    }
                     let mut args = vec!["x".to_string(), "--".to_string()];
                     args.push(shell_program.clone());
                     args.extend(shell_args.iter().cloned());
@@ -407,6 +749,23 @@ impl Supervisor {
             args.push(run_script);
             (shell_program.clone(), args)
         };
+        let (program, args) = if isolate_network {
+            let Some(pasta) = super::netns::find_pasta() else {
+                return Ok(IpcResponse::DaemonFailed {
+                    error: "isolate_network requires pasta (from passt) on PATH".to_string(),
+                });
+            };
+            let forwards = resolved_ports
+                .iter()
+                .copied()
+                .zip(expected_ports.iter().copied())
+                .filter(|&(host, _)| host != 0)
+                .collect::<Vec<_>>();
+            info!("daemon {id}: isolating network, forwarding host ports {forwards:?}");
+            super::netns::wrap(&pasta, &forwards, program, args)
+        } else {
+            (program, args)
+        };
         #[cfg(unix)]
         let run_identity = match resolve_effective_run_identity(opts.user.as_deref()) {
             Ok(identity) => identity,
@@ -480,24 +839,41 @@ impl Supervisor {
             cmd.env("PATH", path);
         }
 
-        // Apply custom environment variables from config
+        // Advertise a color-capable terminal to PTY daemons. Set before the
+        // user env so a daemon's `env.TERM` still wins.
+        #[cfg(unix)]
+        if pty_pair.is_some() {
+            cmd.env("TERM", super::pty::PTY_TERM);
+        }
+
+        // Apply custom environment variables from config, then any one-time
+        // `start --env` override on top
         if let Some(ref env_vars) = opts.env {
             cmd.envs(env_vars);
         }
+        if let Some(ref env_vars) = opts.env_override {
+            cmd.envs(env_vars);
+        }
 
         // Inject pitchfork metadata env vars AFTER user env so they can't be overwritten
         cmd.env("PITCHFORK_DAEMON_ID", id.qualified());
         cmd.env("PITCHFORK_DAEMON_NAMESPACE", id.namespace());
         cmd.env("PITCHFORK_RETRY_COUNT", opts.retry_count.to_string());
 
-        // Inject the resolved ports for the daemon to use
-        if !resolved_ports.is_empty() {
+        // Inject the resolved ports for the daemon to use. An isolated daemon
+        // listens on its expected ports; the resolved ones are forwarded to them.
+        let daemon_ports = if isolate_network {
+            &expected_ports
+        } else {
+            &resolved_ports
+        };
+        if !daemon_ports.is_empty() {
             // Set PORT to the first port for backward compatibility
             // When there's only one port, both PORT and PORT0 will be set to the same value.
             // This follows the convention used by many deployment platforms (Heroku, etc.).
-            cmd.env("PORT", resolved_ports[0].to_string());
+            cmd.env("PORT", daemon_ports[0].to_string());
             // Set individual ports as PORT0, PORT1, etc.
-            for (i, port) in resolved_ports.iter().enumerate() {
+            for (i, port) in daemon_ports.iter().enumerate() {
                 cmd.env(format!("PORT{i}"), port.to_string());
             }
         }
@@ -505,10 +881,17 @@ impl Supervisor {
         // Inject proxy-related environment variables
         inject_proxy_env(&mut cmd, &opts.slug);
 
+        #[cfg(unix)]
+        if let Some(limits) = &opts.limits {
+            check_resource_limits(limits)?;
+        }
+
         #[cfg(unix)]
         {
             let run_identity = run_identity.clone();
             let use_pty = pty_pair.is_some();
+            let umask = opts.umask;
+            let limits = opts.limits;
             unsafe {
                 cmd.pre_exec(move || {
                     nix::unistd::setsid().map_err(nix_to_io_error)?;
@@ -529,13 +912,41 @@ impl Supervisor {
                         }
                     }
 
+                    // Before switching user, so a root supervisor can still
+                    // raise hard limits.
+                    if let Some(limits) = &limits {
+                        apply_resource_limits(limits)?;
+                    }
+                    if let Some(umask) = umask {
+                        libc::umask(umask.0 as libc::mode_t);
+                    }
+
                     apply_run_identity(&run_identity)?;
                     Ok(())
                 });
             }
         }
 
+        // Note the log file's length before spawning so only lines written by
+        // this run can mark it ready
+        let log_file_tail = opts
+            .ready_log_file
+            .as_ref()
+            .map(|f| LogFileTail::new(opts.dir.0.join(&f.path)));
+        // Relative socket paths are relative to the daemon's dir
+        let ready_unix_socket = opts.ready_unix_socket.as_ref().map(|s| opts.dir.0.join(s));
+        let oom_baseline = oom::OomBaseline::capture();
+        // Windows has no setsid: start suspended so the daemon can be put in
+        // a job object before it spawns anything
+        #[cfg(windows)]
+        cmd.creation_flags(crate::job_object::SPAWN_FLAGS);
         let mut child = cmd.spawn().into_diagnostic()?;
+        #[cfg(windows)]
+        if let (Some(pid), Some(handle)) = (child.id(), child.raw_handle()) {
+            crate::job_object::adopt(pid, handle);
+        }
+        let spawned_at = std::time::Instant::now();
+        let start_latency = spawned_at.duration_since(requested_at);
         let pid = match child.id() {
             Some(p) => p,
             None => {
@@ -546,10 +957,33 @@ impl Supervisor {
             }
         };
         info!("started daemon {id} with pid {pid}");
+        events::record(id, "start", Some(format!("pid {pid}")));
+        // Auto-started from the shell hook, which sums these up after `cd`
+        let started_msg = (opts.shell_pid.is_some() && opts.retry_count == 0).then(|| {
+            match resolved_ports.first() {
+                Some(port) => format!("started {id} (port {port})"),
+                None => format!("started {id}"),
+            }
+        });
         PROCS.refresh_pids(&[pid]);
+        // With a ready check the daemon stays `starting` until the check
+        // passes; the monitor task below flips it to `running`.
+        let has_ready_check = opts.ready_output.is_some()
+            || opts.ready_http.is_some()
+            || opts.ready_cmd.is_some()
+            || opts.ready_log_file.is_some()
+            || opts.ready_unix_socket.is_some()
+            || opts.ready_when.is_some()
+            || opts.ready_delay.is_some()
+            || effective_ready_port.is_some();
+        let initial_status = if has_ready_check {
+            DaemonStatus::Starting
+        } else {
+            DaemonStatus::Running
+        };
         let daemon = self
             .upsert_daemon(
-                UpsertDaemonOpts::from_run_options(&opts, DaemonStatus::Running)
+                UpsertDaemonOpts::from_run_options(&opts, initial_status)
                     .set(|o| {
                         o.pid = Some(pid);
                         o.cmd = Some(original_cmd);
@@ -558,6 +992,9 @@ impl Supervisor {
                             template: None,
                             timeout: opts.ready_port.as_ref().and_then(|rp| rp.timeout),
                         });
+                        o.ready_unix_socket = ready_unix_socket
+                            .as_ref()
+                            .map(|p| p.to_string_lossy().to_string());
                         o.port = crate::config_types::PortConfig::from_parts(
                             expected_ports,
                             opts.port.as_ref().map(|p| p.bump).unwrap_or_default(),
@@ -567,10 +1004,16 @@ impl Supervisor {
                     .build(),
             )
             .await?;
+        if let Some(msg) = started_msg {
+            self.add_notification(Some(id), log::LevelFilter::Info, msg)
+                .await;
+        }
 
         let id_clone = id.clone();
         let ready_delay = opts.ready_delay;
         let ready_output = opts.ready_output.clone();
+        let fail_output = opts.fail_output.clone();
+        let expect_long_running = !opts.kind.is_task() && opts.expect_long_running == Some(true);
         let ready_http = opts.ready_http.clone();
         let ready_port = effective_ready_port;
         let implicit_ready_port = ready_port.map(|p| ReadyPort {
@@ -580,10 +1023,30 @@ impl Supervisor {
         });
         let ready_port_config = opts.ready_port.clone().or(implicit_ready_port);
         let ready_cmd = opts.ready_cmd.clone();
+        let ready_log_file = opts.ready_log_file.clone();
+        let ready_when = opts.ready_when.clone();
+        let wait_timeout = opts.wait_timeout;
         let daemon_dir = opts.dir.0.clone();
         let hook_retry_count = opts.retry_count;
         let hook_retry = opts.retry;
-        let hook_daemon_env = opts.env.clone();
+        let success_exit_codes = opts.success_exit_codes.clone();
+        let no_retry_exit_codes = opts.no_retry_exit_codes.clone();
+        // Hooks and `ready_cmd` see the same environment as a `mise run`
+        // task, with the daemon's own env on top. `ready_cmd` also runs in
+        // the task's dir.
+        let hook_daemon_env = match &mise_task {
+            Some(task) => {
+                let mut env = task.env.clone();
+                env.extend(opts.effective_env().unwrap_or_default());
+                Some(env)
+            }
+            None => opts.effective_env(),
+        };
+        let ready_cmd_env = mise_task.as_ref().and(hook_daemon_env.clone());
+        let ready_cmd_dir = mise_task
+            .and_then(|task| task.dir)
+            .unwrap_or_else(|| daemon_dir.clone());
+        let ready_cmd_user = opts.user.clone();
         let on_output_hook = opts.on_output_hook.clone();
         // Whether this daemon has any port-related config — used to skip the
         // active_port detection task for daemons that never bind a port (e.g. `sleep 60`).
@@ -603,26 +1066,68 @@ impl Supervisor {
         // Prepare output readers before spawning the monitoring task.
         // In PTY mode, we read from the PTY master FD.
         // In pipe mode, we read from separate stdout/stderr pipes.
+        let log_encoding = opts.log_encoding.unwrap_or_default();
+        // Shared by the readers, so stdout and stderr count towards one limit
+        let limiter = LogLimiter::from_settings().map(Arc::new);
+        // Readiness is matched after the limit, so it mustn't drop these
+        let keep = ready_output
+            .iter()
+            .map(|o| o.pattern.as_str())
+            .chain(fail_output.as_deref())
+            .filter_map(get_or_compile_regex)
+            .collect::<Vec<_>>();
+        // A copy of each output descriptor, in case the supervisor stops
+        // with --keep-daemons and hands them to the relay
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsFd;
+            let fds = match &pty_pair {
+                Some(p) => vec![(LogStream::Stdout, p.master.as_fd())],
+                None => [
+                    child
+                        .stdout
+                        .as_ref()
+                        .map(|s| (LogStream::Stdout, s.as_fd())),
+                    child
+                        .stderr
+                        .as_ref()
+                        .map(|s| (LogStream::Stderr, s.as_fd())),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            };
+            detach::keep_output(id, pid, fds);
+        }
         #[cfg(unix)]
         let pty_reader = pty_pair.map(|p| {
-            tokio::io::BufReader::new(tokio::fs::File::from_std(std::fs::File::from(p.master)))
-                .lines()
+            OutputLines::new(
+                tokio::io::BufReader::new(tokio::fs::File::from_std(std::fs::File::from(p.master))),
+                log_encoding,
+            )
+            .limited(limiter.clone(), id.clone(), keep.clone())
         });
         #[cfg(not(unix))]
-        let pty_reader: Option<tokio::io::Lines<tokio::io::BufReader<tokio::fs::File>>> = None;
+        let pty_reader: Option<OutputLines<tokio::io::BufReader<tokio::fs::File>>> = None;
         let stdout_reader = if pty_reader.is_none() {
-            child
-                .stdout
-                .take()
-                .map(|s| tokio::io::BufReader::new(s).lines())
+            child.stdout.take().map(|s| {
+                OutputLines::new(tokio::io::BufReader::new(s), log_encoding).limited(
+                    limiter.clone(),
+                    id.clone(),
+                    keep.clone(),
+                )
+            })
         } else {
             None
         };
         let stderr_reader = if pty_reader.is_none() {
-            child
-                .stderr
-                .take()
-                .map(|s| tokio::io::BufReader::new(s).lines())
+            child.stderr.take().map(|s| {
+                OutputLines::new(tokio::io::BufReader::new(s), log_encoding).limited(
+                    limiter.clone(),
+                    id.clone(),
+                    keep.clone(),
+                )
+            })
         } else {
             None
         };
@@ -634,8 +1139,11 @@ impl Supervisor {
         tokio::spawn(async move {
             let id = id_clone;
 
-            // Merge all output sources (PTY master OR stdout+stderr) into a single channel.
-            let (output_tx, mut output_rx) = tokio::sync::mpsc::channel::<String>(256);
+            // Merge all output sources (PTY master OR stdout+stderr) into a single channel,
+            // tagging each line with the stream it came from.
+            // The channel is bounded, so a reader waits for room before taking
+            // more output; the readers also apply `logs.rate_limit`.
+            let (output_tx, mut output_rx) = tokio::sync::mpsc::channel::<(String, LogStream)>(256);
 
             if let Some(mut reader) = pty_reader {
                 // PTY mode: single merged stream from the master.
@@ -647,7 +1155,7 @@ impl Supervisor {
                         if line.ends_with('\r') {
                             line.pop();
                         }
-                        if output_tx.send(line).await.is_err() {
+                        if output_tx.send((line, LogStream::Stdout)).await.is_err() {
                             break;
                         }
                     }
@@ -661,7 +1169,7 @@ impl Supervisor {
                     let tx = output_tx.clone();
                     tokio::spawn(async move {
                         while let Ok(Some(line)) = stdout.next_line().await {
-                            if tx.send(line).await.is_err() {
+                            if tx.send((line, LogStream::Stdout)).await.is_err() {
                                 break;
                             }
                         }
@@ -671,7 +1179,7 @@ impl Supervisor {
                     let tx = output_tx.clone();
                     tokio::spawn(async move {
                         while let Ok(Some(line)) = stderr.next_line().await {
-                            if tx.send(line).await.is_err() {
+                            if tx.send((line, LogStream::Stderr)).await.is_err() {
                                 break;
                             }
                         }
@@ -685,26 +1193,96 @@ impl Supervisor {
                 .log_format
                 .clone()
                 .unwrap_or_else(|| crate::settings::settings().logs.log_format.clone());
-            let parse_line = move |line: &str| crate::log_parse::parse(line, &log_format);
+            let level_regex = opts
+                .log_level_regex
+                .as_deref()
+                .and_then(get_or_compile_regex);
+            let parse_line = move |line: &str, stream: LogStream| {
+                let mut parsed = crate::log_parse::parse(line, &log_format);
+                if parsed.level.is_none()
+                    && let Some(re) = &level_regex
+                {
+                    let line_clean = console::strip_ansi_codes(line);
+                    parsed.level = crate::log_parse::level_from_regex(re, &line_clean);
+                }
+                crate::log_parse::ParsedLog { stream, ..parsed }
+            };
+
+            // Lines matching `log_filter` are dropped before they are logged.
+            // The count is reset for each run and saved on the flush tick.
+            let log_filter: Vec<Regex> = opts
+                .log_filter
+                .iter()
+                .filter_map(|p| get_or_compile_regex(p))
+                .collect();
+            let is_filtered =
+                |line_clean: &str| log_filter.iter().any(|re| re.is_match(line_clean));
+            let mut lines_filtered: u64 = 0;
+            let mut lines_filtered_saved: u64 = 0;
+            // Lines dropped by the rate limit are counted by the readers
+            let lines_dropped = || limiter.as_ref().map_or(0, |l| l.dropped());
+            let mut lines_dropped_saved: u64 = 0;
+            {
+                let mut state_file = SUPERVISOR.state_file.lock().await;
+                state_file.set_log_lines_filtered(&id, 0);
+                state_file.set_log_lines_dropped(&id, 0);
+            }
 
             const LOG_BATCH_SIZE: usize = 100;
             const LOG_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
-            let mut log_buffer: Vec<crate::log_parse::ParsedLog> =
-                Vec::with_capacity(LOG_BATCH_SIZE);
+            // Each line with the time it was read
+            let mut log_buffer: Vec<(
+                chrono::DateTime<chrono::Local>,
+                crate::log_parse::ParsedLog,
+            )> = Vec::with_capacity(LOG_BATCH_SIZE);
             let mut log_flush_interval = tokio::time::interval(LOG_FLUSH_INTERVAL);
             log_flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
-            let flush_logs =
-                |buffer: &mut Vec<crate::log_parse::ParsedLog>| -> Option<tokio::task::JoinHandle<()>> {
-                    if buffer.is_empty() {
-                        return None;
+            let recent_capacity =
+                usize::try_from(crate::settings::settings().logs.recent_lines).unwrap_or(0);
+            let sink = LogSink::get();
+            let flush_logs = |buffer: &mut Vec<(
+                chrono::DateTime<chrono::Local>,
+                crate::log_parse::ParsedLog,
+            )>|
+             -> Option<tokio::task::JoinHandle<()>> {
+                if buffer.is_empty() {
+                    return None;
                 }
                 let store = Arc::clone(&log_store);
                 let id = id.clone();
-                let batch = std::mem::take(buffer);
+                let captured = std::mem::take(buffer);
                 Some(tokio::task::spawn_blocking(move || {
-                    if let Err(e) = store.append_structured_batch(&id, &batch) {
-                        error!("Failed to write batch to log for daemon {id}: {e}");
+                    if recent_capacity > 0 {
+                        // Tagged with the clear generation they were logged
+                        // under, so a later `logs --clear` drops them
+                        let generation =
+                            store.last_clear_generation(&id).ok().flatten().unwrap_or(0);
+                        RECENT_LOGS.push_batch(&id, &captured, generation, recent_capacity);
+                    }
+                    let batch = captured
+                        .into_iter()
+                        .map(|(_, line)| line)
+                        .collect::<Vec<_>>();
+                    if sink.journal() {
+                        journal::send_batch(&id, &batch);
+                    }
+                    if !sink.sqlite() {
+                        return;
+                    }
+                    let transition = DISK_FULL.append(&id, batch, |id, batch| {
+                        store.append_structured_batch(id, batch)
+                    });
+                    if let Some(transition) = transition {
+                        let level = match transition {
+                            Transition::Degraded => log::LevelFilter::Warn,
+                            Transition::Recovered { .. } => log::LevelFilter::Info,
+                        };
+                        tokio::runtime::Handle::current().spawn(async move {
+                            SUPERVISOR
+                                .add_notification(None, level, transition.to_string())
+                                .await;
+                        });
                     }
                 }))
             };
@@ -714,9 +1292,12 @@ impl Supervisor {
             // Setup readiness checking
             let mut ready_notified = false;
             let mut ready_tx = ready_tx;
+            // Whether a `start` is waiting on readiness, and so reports early failures itself
+            let has_waiter = ready_tx.is_some();
             let ready_pattern = ready_output
                 .as_ref()
                 .and_then(|o| get_or_compile_regex(&o.pattern));
+            let fail_pattern = fail_output.as_deref().and_then(get_or_compile_regex);
             // Track whether we've already spawned the active_port detection task
             let mut active_port_spawned = false;
 
@@ -746,14 +1327,30 @@ impl Supervisor {
             // Last time the on_output hook fired; None means it has never fired.
             let mut on_output_last_fired: Option<std::time::Instant> = None;
 
-            let mut delay_timer =
-                ready_delay.map(|secs| Box::pin(time::sleep(Duration::from_secs(secs))));
+            let mut delay_timer = ready_delay.map(|delay| Box::pin(time::sleep(delay)));
+
+            // Overall bound on the ready checks (`start --wait-timeout`), and
+            // what to report when the daemon doesn't become ready in time
+            let ready_started = std::time::Instant::now();
+            let mut wait_deadline = wait_timeout.map(|d| Box::pin(time::sleep(d)));
+            let ready_checks = describe_ready_checks(
+                ready_output.as_ref(),
+                ready_port,
+                ready_unix_socket.as_deref(),
+                ready_http.as_ref(),
+                ready_cmd.as_ref(),
+                ready_log_file.as_ref(),
+                ready_when.as_ref(),
+                ready_delay,
+            );
 
             // Track exhaustion of timed checks
             let mut http_exhausted = false;
             let mut cmd_exhausted = false;
             let mut port_exhausted = false;
             let mut output_exhausted = false;
+            let mut log_file_exhausted = false;
+            let mut when_exhausted = false;
 
             // Get settings for intervals
             let s = settings();
@@ -789,6 +1386,11 @@ impl Supervisor {
                 .and_then(|p| p.timeout)
                 .map(|d| Box::pin(time::sleep(d)));
 
+            // Setup unix socket readiness check interval (no deadline of its own)
+            let mut unix_socket_check_interval = ready_unix_socket
+                .as_ref()
+                .map(|_| tokio::time::interval(ready_check_interval));
+
             // Setup command readiness check state. Probes are spawned one at a time;
             // a non-zero result triggers a respawn delay, and a timeout stops the probe.
             let mut cmd_probe: Option<CmdProbe> = None;
@@ -798,9 +1400,50 @@ impl Supervisor {
                 .and_then(|c| c.timeout)
                 .map(|d| Box::pin(time::sleep(d)));
             if let Some(ref cmd) = ready_cmd {
-                cmd_probe = Some(spawn_cmd_probe(&id, &cmd.run, daemon_dir.as_path()));
+                cmd_probe = Some(spawn_cmd_probe(
+                    &id,
+                    &cmd.run,
+                    ready_cmd_dir.as_path(),
+                    ready_cmd_env.as_ref(),
+                    ready_cmd_attempt_timeout(cmd),
+                    ready_cmd_user.as_deref(),
+                ));
             }
 
+            // Setup log file readiness check: tail the file on each interval tick
+            let mut log_file_check = log_file_tail.zip(
+                ready_log_file
+                    .as_ref()
+                    .and_then(|f| get_or_compile_regex(&f.pattern)),
+            );
+            let mut log_file_check_interval = ready_log_file
+                .as_ref()
+                .map(|_| tokio::time::interval(ready_check_interval));
+            let mut log_file_deadline = ready_log_file
+                .as_ref()
+                .and_then(|f| f.timeout)
+                .map(|d| Box::pin(time::sleep(d)));
+
+            // Setup daemon readiness check: follow the upstream daemon's status
+            // changes, subscribing before the first look so none slips past
+            let ready_when_upstream =
+                ready_when
+                    .as_ref()
+                    .and_then(|w| match w.daemon_id(id.namespace()) {
+                        Ok(upstream) => Some((upstream, w.state)),
+                        Err(e) => {
+                            warn!("daemon {id}: invalid ready_when daemon: {e}");
+                            None
+                        }
+                    });
+            let upstream_qualified = ready_when_upstream.as_ref().map(|(u, _)| u.qualified());
+            let mut status_rx = ready_when_upstream.as_ref().map(|_| events::subscribe());
+            let mut when_first_check = true;
+            let mut when_deadline = ready_when
+                .as_ref()
+                .and_then(|w| w.timeout)
+                .map(|d| Box::pin(time::sleep(d)));
+
             // Use a channel to communicate process exit status
             let (exit_tx, mut exit_rx) =
                 tokio::sync::mpsc::channel::<std::io::Result<std::process::ExitStatus>>(1);
@@ -809,6 +1452,8 @@ impl Supervisor {
             let child_pid = child.id().unwrap_or(0);
             tokio::spawn(async move {
                 let result = child.wait().await;
+                #[cfg(windows)]
+                crate::job_object::forget(child_pid);
                 // On non-Linux Unix (e.g. macOS) the zombie reaper may win the
                 // race and consume the exit status via waitpid(None, WNOHANG)
                 // before Tokio's child.wait() gets to it. When that happens,
@@ -865,12 +1510,21 @@ impl Supervisor {
                 && ready_http.is_none()
                 && ready_port.is_none()
                 && ready_cmd.is_none()
+                && ready_log_file.is_none()
+                && ready_unix_socket.is_none()
+                && ready_when.is_none()
                 && delay_timer.is_none()
             {
                 active_port_spawned = true;
                 detect_and_store_active_port(id.clone(), daemon_pid);
             }
 
+            // With output always waiting, the biased select below would never
+            // get past it to the timers. After this many lines in a row the
+            // output branch sits out one round.
+            const OUTPUT_STREAK: u32 = 64;
+            let mut output_streak: u32 = 0;
+
             loop {
                 // biased: evaluate in exit → output → delay order so that
                 // process exit pre-empts both buffered output and the delay
@@ -886,10 +1540,17 @@ impl Supervisor {
                                 // Check if process exited successfully
                                 let is_success = exit_status.as_ref()
                                     .and_then(|r| r.as_ref().ok())
-                                    .map(|s| s.success())
+                                    .map(|s| is_clean_exit(s, &success_exit_codes))
                                     .unwrap_or(false);
 
-                                if is_success {
+                                if is_success && expect_long_running {
+                                    let exit_code = exit_status.as_ref()
+                                        .and_then(|r| r.as_ref().ok())
+                                        .and_then(|s| s.code())
+                                        .unwrap_or(0);
+                                    debug!("daemon {id} exited successfully before ready check, but expect_long_running is set");
+                                    let _ = tx.send(Err(ReadyFailure::ExitedEarly(exit_code)));
+                                } else if is_success {
                                     debug!("daemon {id} exited successfully before ready check, sending success notification");
                                     let _ = tx.send(Ok(()));
                                 } else {
@@ -897,7 +1558,7 @@ impl Supervisor {
                                         .and_then(|r| r.as_ref().ok())
                                         .and_then(|s| s.code());
                                     debug!("daemon {id} exited with failure before ready check, sending failure notification with exit_code: {exit_code:?}");
-                                    let _ = tx.send(Err(exit_code));
+                                    let _ = tx.send(Err(ReadyFailure::Exited(exit_code)));
                                 }
                             }
                         } else {
@@ -905,18 +1566,38 @@ impl Supervisor {
                         }
                         break;
                     },
-                    Some(line) = output_rx.recv() => {
-                        let parsed = parse_line(&line);
-                        log_buffer.push(parsed);
-                        if log_buffer.len() >= LOG_BATCH_SIZE {
-                            let _ = flush_logs(&mut log_buffer);
-                        }
-                        trace!("output: {id} {line}");
-
+                    Some((line, stream)) = output_rx.recv(), if output_streak < OUTPUT_STREAK => {
+                        output_streak += 1;
                         // Strip ANSI for pattern matching so user-written patterns
                         // work regardless of whether the process emits color codes.
                         let line_clean = console::strip_ansi_codes(&line).to_string();
 
+                        if is_filtered(&line_clean) {
+                            lines_filtered += 1;
+                        } else {
+                            log_buffer.push((chrono::Local::now(), parse_line(&line, stream)));
+                            if log_buffer.len() >= LOG_BATCH_SIZE {
+                                let _ = flush_logs(&mut log_buffer);
+                            }
+                        }
+                        trace!("output: {id} {line}");
+
+                        // A known fatal error fails the start without waiting
+                        // for the ready checks to time out
+                        if !ready_notified
+                            && let Some(ref pattern) = fail_pattern
+                            && pattern.is_match(&line_clean)
+                        {
+                            error!("daemon {id}: output matched fail_output, failing: {line_clean}");
+                            stop_cmd_probe_state(&mut cmd_probe);
+                            if let Some(tx) = ready_tx.take() {
+                                let _ = tx.send(Err(ReadyFailure::FailOutput(line_clean.trim().to_string())));
+                            }
+                            let stop_cfg = opts.stop_signal.unwrap_or_default();
+                            let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
+                            break;
+                        }
+
                         // Check if output matches ready pattern
                         if !ready_notified
                             && !output_exhausted
@@ -932,6 +1613,7 @@ impl Supervisor {
                             }
                             info!("daemon {id} ready: output matched pattern");
                             ready_notified = true;
+                            mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
                             if let Some(tx) = ready_tx.take() {
                                 let _ = tx.send(Ok(()));
                             }
@@ -941,6 +1623,8 @@ impl Supervisor {
                             cmd_deadline = None;
                             port_deadline = None;
                             output_deadline = None;
+                            when_deadline = None;
+                            log_file_deadline = None;
                             if !active_port_spawned && has_port_config {
                                 active_port_spawned = true;
                                 detect_and_store_active_port(id.clone(), daemon_pid);
@@ -987,12 +1671,17 @@ impl Supervisor {
                             http_exhausted,
                             ready_cmd.as_ref(),
                             cmd_exhausted,
+                            ready_log_file.as_ref(),
+                            log_file_exhausted,
+                            ready_unix_socket.as_deref(),
+                            ready_when.as_ref(),
+                            when_exhausted,
                         );
                         if !any_remaining {
                             error!("daemon {id}: all readiness checks exhausted, failing");
                             stop_cmd_probe_state(&mut cmd_probe);
                             if let Some(tx) = ready_tx.take() {
-                                let _ = tx.send(Err(Some(124)));
+                                let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
                             }
                             let stop_cfg = opts.stop_signal.unwrap_or_default();
                             let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
@@ -1018,12 +1707,17 @@ impl Supervisor {
                             http_exhausted,
                             ready_cmd.as_ref(),
                             cmd_exhausted,
+                            ready_log_file.as_ref(),
+                            log_file_exhausted,
+                            ready_unix_socket.as_deref(),
+                            ready_when.as_ref(),
+                            when_exhausted,
                         );
                         if !any_remaining {
                             error!("daemon {id}: all readiness checks exhausted, failing");
                             stop_cmd_probe_state(&mut cmd_probe);
                             if let Some(tx) = ready_tx.take() {
-                                let _ = tx.send(Err(Some(124)));
+                                let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
                             }
                             let stop_cfg = opts.stop_signal.unwrap_or_default();
                             let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
@@ -1038,10 +1732,11 @@ impl Supervisor {
                         }
                     }, if !ready_notified && ready_http.is_some() && !http_exhausted => {
                         if let (Some(http), Some(client)) = (&ready_http, &http_client) {
-                            match client.get(&http.url).send().await {
-                                Ok(response) if http.accepts_status(response.status().as_u16()) => {
-                                    info!("daemon {id} ready: HTTP check passed (status {})", response.status());
+                            match super::health::check_http(client, http).await {
+                                Ok(status) => {
+                                    info!("daemon {id} ready: HTTP check passed (status {status})");
                                     ready_notified = true;
+                                    mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
                                     if let Some(tx) = ready_tx.take() {
                                         let _ = tx.send(Ok(()));
                                     }
@@ -1052,16 +1747,15 @@ impl Supervisor {
                                     cmd_deadline = None;
                                     port_deadline = None;
                                     output_deadline = None;
+                                    when_deadline = None;
+                                    log_file_deadline = None;
                                     if !active_port_spawned && has_port_config {
                                         active_port_spawned = true;
                                         detect_and_store_active_port(id.clone(), daemon_pid);
                                     }
                                 }
-                                Ok(response) => {
-                                    trace!("daemon {id} HTTP check: status {} (not ready)", response.status());
-                                }
                                 Err(e) => {
-                                    trace!("daemon {id} HTTP check failed: {e}");
+                                    trace!("daemon {id} HTTP check: {e} (not ready)");
                                 }
                             }
                         }
@@ -1086,12 +1780,17 @@ impl Supervisor {
                             http_exhausted,
                             ready_cmd.as_ref(),
                             cmd_exhausted,
+                            ready_log_file.as_ref(),
+                            log_file_exhausted,
+                            ready_unix_socket.as_deref(),
+                            ready_when.as_ref(),
+                            when_exhausted,
                         );
                         if !any_remaining {
                             error!("daemon {id}: all readiness checks exhausted, failing");
                             stop_cmd_probe_state(&mut cmd_probe);
                             if let Some(tx) = ready_tx.take() {
-                                let _ = tx.send(Err(Some(124)));
+                                let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
                             }
                             let stop_cfg = opts.stop_signal.unwrap_or_default();
                             let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
@@ -1110,6 +1809,7 @@ impl Supervisor {
                                 Ok(_) => {
                                     info!("daemon {id} ready: TCP port {port} is listening");
                                     ready_notified = true;
+                                    mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
                                     if let Some(tx) = ready_tx.take() {
                                         let _ = tx.send(Ok(()));
                                     }
@@ -1121,6 +1821,8 @@ impl Supervisor {
                                     http_deadline = None;
                                     cmd_deadline = None;
                                     output_deadline = None;
+                                    when_deadline = None;
+                                    log_file_deadline = None;
                                     if !active_port_spawned && has_port_config {
                                         active_port_spawned = true;
                                         // ready_port check just TCP-connected to this
@@ -1154,6 +1856,39 @@ impl Supervisor {
                             }
                         }
                     }
+                    _ = async {
+                        if let Some(ref mut interval) = unix_socket_check_interval {
+                            interval.tick().await;
+                        } else {
+                            std::future::pending::<()>().await;
+                        }
+                    }, if !ready_notified && ready_unix_socket.is_some() => {
+                        if let Some(ref socket) = ready_unix_socket {
+                            if super::health::unix_socket_listening(socket).await {
+                                info!("daemon {id} ready: unix socket {} is listening", socket.display());
+                                ready_notified = true;
+                                mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
+                                if let Some(tx) = ready_tx.take() {
+                                    let _ = tx.send(Ok(()));
+                                }
+                                fire_hook(HookType::OnReady, id.clone(), daemon_dir.clone(), hook_retry_count, hook_daemon_env.clone(), vec![]).await;
+                                unix_socket_check_interval = None;
+                                stop_cmd_probe_state(&mut cmd_probe);
+                                http_deadline = None;
+                                cmd_deadline = None;
+                                port_deadline = None;
+                                output_deadline = None;
+                                when_deadline = None;
+                                log_file_deadline = None;
+                                if !active_port_spawned && has_port_config {
+                                    active_port_spawned = true;
+                                    detect_and_store_active_port(id.clone(), daemon_pid);
+                                }
+                            } else {
+                                trace!("daemon {id} unix socket check: {} not listening yet", socket.display());
+                            }
+                        }
+                    }
                     _ = async {
                         if let Some(ref mut delay) = cmd_respawn_delay {
                             delay.await;
@@ -1162,7 +1897,7 @@ impl Supervisor {
                         }
                     }, if !ready_notified && ready_cmd.is_some() && !cmd_exhausted && cmd_probe.is_none() => {
                         if let Some(ref cmd) = ready_cmd {
-                            cmd_probe = Some(spawn_cmd_probe(&id, &cmd.run, daemon_dir.as_path()));
+                            cmd_probe = Some(spawn_cmd_probe(&id, &cmd.run, ready_cmd_dir.as_path(), ready_cmd_env.as_ref(), ready_cmd_attempt_timeout(cmd), ready_cmd_user.as_deref()));
                         }
                         cmd_respawn_delay = None;
                     }
@@ -1181,6 +1916,7 @@ impl Supervisor {
                             Ok(Ok(status)) if status.success() => {
                                 info!("daemon {id} ready: readiness command succeeded");
                                 ready_notified = true;
+                                mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
                                 if let Some(tx) = ready_tx.take() {
                                     let _ = tx.send(Ok(()));
                                 }
@@ -1190,6 +1926,8 @@ impl Supervisor {
                                 http_deadline = None;
                                 port_deadline = None;
                                 output_deadline = None;
+                                when_deadline = None;
+                                log_file_deadline = None;
                                 if !active_port_spawned && has_port_config {
                                     active_port_spawned = true;
                                     detect_and_store_active_port(id.clone(), daemon_pid);
@@ -1222,11 +1960,171 @@ impl Supervisor {
                             http_exhausted,
                             ready_cmd.as_ref(),
                             cmd_exhausted,
+                            ready_log_file.as_ref(),
+                            log_file_exhausted,
+                            ready_unix_socket.as_deref(),
+                            ready_when.as_ref(),
+                            when_exhausted,
                         );
                         if !any_remaining {
                             error!("daemon {id}: all readiness checks exhausted, failing");
                             if let Some(tx) = ready_tx.take() {
-                                let _ = tx.send(Err(Some(124)));
+                                let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
+                            }
+                            let stop_cfg = opts.stop_signal.unwrap_or_default();
+                            let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
+                            break;
+                        }
+                    }
+                    _ = async {
+                        if let Some(ref mut interval) = log_file_check_interval {
+                            interval.tick().await;
+                        } else {
+                            std::future::pending::<()>().await;
+                        }
+                    }, if !ready_notified && ready_log_file.is_some() && !log_file_exhausted => {
+                        if let Some((ref mut tail, ref pattern)) = log_file_check
+                            && tail.poll(pattern).await
+                        {
+                            info!("daemon {id} ready: {} matched pattern", tail.path().display());
+                            ready_notified = true;
+                            mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
+                            if let Some(tx) = ready_tx.take() {
+                                let _ = tx.send(Ok(()));
+                            }
+                            fire_hook(HookType::OnReady, id.clone(), daemon_dir.clone(), hook_retry_count, hook_daemon_env.clone(), vec![]).await;
+                            log_file_check = None;
+                            log_file_check_interval = None;
+                            log_file_deadline = None;
+                            stop_cmd_probe_state(&mut cmd_probe);
+                            cmd_deadline = None;
+                            http_deadline = None;
+                            port_deadline = None;
+                            output_deadline = None;
+                            when_deadline = None;
+                            if !active_port_spawned && has_port_config {
+                                active_port_spawned = true;
+                                detect_and_store_active_port(id.clone(), daemon_pid);
+                            }
+                        }
+                    }
+                    _ = async {
+                        if let Some(ref mut deadline) = log_file_deadline {
+                            deadline.await;
+                        } else {
+                            std::future::pending::<()>().await;
+                        }
+                    }, if !ready_notified && ready_log_file.is_some() => {
+                        log_file_exhausted = true;
+                        log_file_deadline = None;
+                        log_file_check_interval = None;
+                        warn!("daemon {id}: log file readiness check timed out");
+                        let any_remaining = any_ready_check_remaining(
+                            ready_output.as_ref(),
+                            output_exhausted,
+                            ready_port_config.as_ref(),
+                            port_exhausted,
+                            ready_http.as_ref(),
+                            http_exhausted,
+                            ready_cmd.as_ref(),
+                            cmd_exhausted,
+                            ready_log_file.as_ref(),
+                            log_file_exhausted,
+                            ready_unix_socket.as_deref(),
+                            ready_when.as_ref(),
+                            when_exhausted,
+                        );
+                        if !any_remaining {
+                            error!("daemon {id}: all readiness checks exhausted, failing");
+                            stop_cmd_probe_state(&mut cmd_probe);
+                            if let Some(tx) = ready_tx.take() {
+                                let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
+                            }
+                            let stop_cfg = opts.stop_signal.unwrap_or_default();
+                            let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
+                            break;
+                        }
+                    }
+                    _ = async {
+                        // The upstream may already be there, so look once
+                        // before waiting for a status change
+                        if when_first_check {
+                            return;
+                        }
+                        match status_rx.as_mut() {
+                            Some(rx) => loop {
+                                match rx.recv().await {
+                                    Ok(events::StreamEvent::Status { id: changed, .. })
+                                        if upstream_qualified.as_ref() == Some(&changed) => break,
+                                    Ok(_) => {}
+                                    // Missed some events; check the state file
+                                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => break,
+                                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                                        std::future::pending::<()>().await
+                                    }
+                                }
+                            },
+                            None => std::future::pending::<()>().await,
+                        }
+                    }, if !ready_notified && status_rx.is_some() && !when_exhausted => {
+                        when_first_check = false;
+                        if let Some((ref upstream, state)) = ready_when_upstream {
+                            if upstream_reached(upstream, state).await {
+                                info!("daemon {id} ready: daemon {upstream} is {state}");
+                                ready_notified = true;
+                                mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
+                                if let Some(tx) = ready_tx.take() {
+                                    let _ = tx.send(Ok(()));
+                                }
+                                fire_hook(HookType::OnReady, id.clone(), daemon_dir.clone(), hook_retry_count, hook_daemon_env.clone(), vec![]).await;
+                                status_rx = None;
+                                when_deadline = None;
+                                stop_cmd_probe_state(&mut cmd_probe);
+                                cmd_deadline = None;
+                                http_deadline = None;
+                                port_deadline = None;
+                                output_deadline = None;
+                                log_file_deadline = None;
+                                if !active_port_spawned && has_port_config {
+                                    active_port_spawned = true;
+                                    detect_and_store_active_port(id.clone(), daemon_pid);
+                                }
+                            } else {
+                                trace!("daemon {id} daemon check: {upstream} is not {state} yet");
+                            }
+                        }
+                    }
+                    _ = async {
+                        if let Some(ref mut deadline) = when_deadline {
+                            deadline.await;
+                        } else {
+                            std::future::pending::<()>().await;
+                        }
+                    }, if !ready_notified && ready_when.is_some() => {
+                        when_exhausted = true;
+                        when_deadline = None;
+                        status_rx = None;
+                        warn!("daemon {id}: daemon readiness check timed out");
+                        let any_remaining = any_ready_check_remaining(
+                            ready_output.as_ref(),
+                            output_exhausted,
+                            ready_port_config.as_ref(),
+                            port_exhausted,
+                            ready_http.as_ref(),
+                            http_exhausted,
+                            ready_cmd.as_ref(),
+                            cmd_exhausted,
+                            ready_log_file.as_ref(),
+                            log_file_exhausted,
+                            ready_unix_socket.as_deref(),
+                            ready_when.as_ref(),
+                            when_exhausted,
+                        );
+                        if !any_remaining {
+                            error!("daemon {id}: all readiness checks exhausted, failing");
+                            stop_cmd_probe_state(&mut cmd_probe);
+                            if let Some(tx) = ready_tx.take() {
+                                let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
                             }
                             let stop_cfg = opts.stop_signal.unwrap_or_default();
                             let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
@@ -1240,7 +2138,7 @@ impl Supervisor {
                             std::future::pending::<()>().await;
                         }
                     } => {
-                        if !ready_notified && ready_pattern.is_none() && ready_http.is_none() && ready_port.is_none() && ready_cmd.is_none() {
+                        if !ready_notified && ready_pattern.is_none() && ready_http.is_none() && ready_port.is_none() && ready_cmd.is_none() && ready_log_file.is_none() && ready_unix_socket.is_none() && ready_when.is_none() {
                             // Check if the process already exited or is exiting before
                             // declaring it ready. On Windows, sleep(0) fires before
                             // child.wait() detects the exit, causing pitchfork start to
@@ -1256,6 +2154,7 @@ impl Supervisor {
                                 } else {
                                     info!("daemon {id} ready: delay elapsed");
                                     ready_notified = true;
+                                    mark_ready(&id, daemon_pid, start_latency, spawned_at).await;
                                     if let Some(tx) = ready_tx.take() {
                                         let _ = tx.send(Ok(()));
                                     }
@@ -1265,6 +2164,8 @@ impl Supervisor {
                             // Clear all deadlines — no other checks are configured
                             // when delay fires as readiness, but clear defensively.
                             output_deadline = None;
+                            when_deadline = None;
+                            log_file_deadline = None;
                             http_deadline = None;
                             cmd_deadline = None;
                             port_deadline = None;
@@ -1277,8 +2178,43 @@ impl Supervisor {
                             detect_and_store_active_port(id.clone(), daemon_pid);
                         }
                     }
+                    _ = async {
+                        if let Some(ref mut deadline) = wait_deadline {
+                            deadline.await;
+                        } else {
+                            std::future::pending::<()>().await;
+                        }
+                    }, if !ready_notified && ready_tx.is_some() => {
+                        error!("daemon {id}: not ready within the wait timeout, failing");
+                        stop_cmd_probe_state(&mut cmd_probe);
+                        if let Some(tx) = ready_tx.take() {
+                            let _ = tx.send(Err(ReadyFailure::timed_out(&ready_checks, ready_started.elapsed())));
+                        }
+                        let stop_cfg = opts.stop_signal.unwrap_or_default();
+                        let _ = PROCS.kill_process_group_async(daemon_pid, stop_cfg.signal.into(), stop_cfg.timeout).await;
+                        break;
+                    }
                     _ = log_flush_interval.tick() => {
                         let _ = flush_logs(&mut log_buffer);
+                        if lines_filtered != lines_filtered_saved {
+                            lines_filtered_saved = lines_filtered;
+                            SUPERVISOR
+                                .state_file
+                                .lock()
+                                .await
+                                .set_log_lines_filtered(&id, lines_filtered);
+                        }
+                        if lines_dropped() != lines_dropped_saved {
+                            lines_dropped_saved = lines_dropped();
+                            SUPERVISOR
+                                .state_file
+                                .lock()
+                                .await
+                                .set_log_lines_dropped(&id, lines_dropped_saved);
+                        }
+                    }
+                    _ = std::future::ready(()), if output_streak >= OUTPUT_STREAK => {
+                        output_streak = 0;
                     }
                 }
             }
@@ -1314,12 +2250,16 @@ impl Supervisor {
                 if now >= drain_deadline {
                     break;
                 }
-                let Ok(Some(line)) =
+                let Ok(Some((line, stream))) =
                     tokio::time::timeout(drain_deadline - now, output_rx.recv()).await
                 else {
                     break;
                 };
-                log_buffer.push(parse_line(&line));
+                if is_filtered(&console::strip_ansi_codes(&line)) {
+                    lines_filtered += 1;
+                    continue;
+                }
+                log_buffer.push((chrono::Local::now(), parse_line(&line, stream)));
             }
             // Flush any remaining log lines (including drained) before the process exits.
             // Await the flush to guarantee all buffered logs are persisted before cleanup.
@@ -1331,6 +2271,8 @@ impl Supervisor {
             {
                 let mut state_file = SUPERVISOR.state_file.lock().await;
                 state_file.clear_active_port(&id);
+                state_file.set_log_lines_filtered(&id, lines_filtered);
+                state_file.set_log_lines_dropped(&id, lines_dropped());
             }
 
             // Get the final exit status
@@ -1346,6 +2288,8 @@ impl Supervisor {
                     }
                 }
             };
+            #[cfg(unix)]
+            detach::forget_output(&id, pid);
             let current_daemon = SUPERVISOR.get_daemon(&id).await;
 
             // Signal that this monitoring task is processing its exit path.
@@ -1392,21 +2336,87 @@ impl Supervisor {
                     .is_some_and(|d| d.status.is_stopping());
 
             // --- Phase 1: Determine exit_code, exit_reason, and update daemon state ---
-            let (exit_code, exit_reason) = match (&exit_status, is_stopping) {
-                (Ok(status), true) => {
-                    // Intentional stop (by pitchfork). status.code() returns None
-                    // on Unix when killed by signal (e.g. SIGTERM); use -1 to
-                    // distinguish from a clean exit code 0.
-                    (status.code().unwrap_or(-1), "stop")
-                }
-                (Ok(status), false) if status.success() => (status.code().unwrap_or(-1), "exit"),
-                (Ok(status), false) => (status.code().unwrap_or(-1), "fail"),
-                (Err(_), true) => {
-                    // child.wait() error while stopping (e.g. sysinfo reaped the process)
-                    (-1, "stop")
-                }
-                (Err(_), false) => (-1, "fail"),
+            let signalled = PROCS.take_signalled(pid);
+            let exit_reason =
+                exit_reason(&exit_status, is_stopping, signalled, &success_exit_codes);
+            // status.code() returns None on Unix when killed by signal (e.g.
+            // SIGTERM); use -1 to distinguish from a clean exit code 0.
+            let exit_code = exit_status
+                .as_ref()
+                .ok()
+                .and_then(|s| s.code())
+                .unwrap_or(-1);
+            if exit_reason == "killed" {
+                let signal = exit_signal(&exit_status).unwrap_or_default();
+                warn!("daemon {id} was killed by signal {signal}, which pitchfork did not send");
+                events::record(
+                    &id,
+                    "killed",
+                    Some(format!("signal {signal} not sent by pitchfork")),
+                );
+            }
+
+            // Every exit goes into the history, including intentional stops
+            let exit = ExitRecord {
+                at: chrono::Local::now(),
+                code: exit_code,
+                signal: exit_signal(&exit_status),
+                runtime_secs: spawned_at.elapsed().as_secs(),
+                reason: exit_reason.to_string(),
             };
+            SUPERVISOR.state_file.lock().await.record_exit(&id, exit);
+
+            // A SIGKILL nobody in pitchfork sent may be the kernel OOM killer.
+            let oom_killed = matches!(exit_reason, "fail" | "killed")
+                && exit_status.as_ref().is_ok_and(|s| {
+                    // Children of a shell wrapper are still in the process
+                    // table until its next refresh
+                    let mut pids = PROCS.all_children(pid);
+                    pids.push(pid);
+                    oom::was_oom_killed(s, &pids, oom_baseline)
+                });
+            let no_retry = matches!(exit_reason, "fail" | "killed")
+                && no_retry_exit_codes.contains(&exit_code);
+            if no_retry {
+                info!("daemon {id} exited with code {exit_code}, which is in no_retry_exit_codes");
+            }
+            let retries_exhausted = no_retry || hook_retry_count >= hook_retry.count();
+            let exited_early = expect_long_running
+                && exit_reason == "exit"
+                && exited_too_early(ready_notified, spawned_at.elapsed());
+            if exited_early {
+                warn!("daemon {id} {}", exited_early_message(exit_code));
+            }
+            if oom_killed {
+                warn!("daemon {id} was {}", oom::OOM_MESSAGE);
+                let msg = if retries_exhausted {
+                    format!("daemon {id} was {}", oom::OOM_MESSAGE)
+                } else {
+                    format!("daemon {id} was {}, retrying", oom::OOM_MESSAGE)
+                };
+                SUPERVISOR
+                    .add_notification(Some(&id), log::LevelFilter::Warn, msg)
+                    .await;
+            }
+            // Failures nobody is waiting on, e.g. a crash long after startup,
+            // would otherwise only show up in `pitchfork list`
+            if (exited_early
+                || matches!(exit_reason, "fail" | "killed") && retries_exhausted && !oom_killed)
+                && (ready_notified || !has_waiter)
+            {
+                let msg = match exit_signal(&exit_status) {
+                    _ if exited_early => {
+                        format!("daemon {id} {}", exited_early_message(exit_code))
+                    }
+                    Some(signal) if exit_reason == "killed" => {
+                        format!("daemon {id} was killed by signal {signal}")
+                    }
+                    _ => format!("daemon {id} failed with exit code {exit_code}"),
+                };
+                SUPERVISOR
+                    .add_notification(Some(&id), log::LevelFilter::Error, msg)
+                    .await;
+            }
 
             // Update daemon state unless stop() already did it (won the race),
             // OR the daemon was intentionally stopped before the drain
@@ -1418,12 +2428,25 @@ impl Supervisor {
                     info!("daemon {id} exited with status {status}");
                 }
                 let (new_status, last_exit_success) = match exit_reason {
-                    "stop" | "exit" => (
+                    "stop" => (
                         DaemonStatus::Stopped,
                         exit_status.as_ref().map(|s| s.success()).unwrap_or(true),
                     ),
+                    "exit" if exited_early => {
+                        (DaemonStatus::Failed(exited_early_message(exit_code)), false)
+                    }
+                    // Exit code 0 or one of success_exit_codes
+                    "exit" => (DaemonStatus::Stopped, true),
+                    // Once retries are used up, record the OOM kill as the
+                    // final status; until then stay Errored so retry picks it up.
+                    _ if oom_killed && retries_exhausted => {
+                        (DaemonStatus::Failed(oom::OOM_MESSAGE.to_string()), false)
+                    }
                     _ => (DaemonStatus::Errored(exit_code), false),
                 };
+                // The retry watcher waits out the backoff before the next attempt
+                let next_retry_at = (new_status.is_errored() && !retries_exhausted)
+                    .then(|| SUPERVISOR.clock.now() + retry_backoff(hook_retry_count));
                 if let Err(e) = SUPERVISOR
                     .upsert_daemon(
                         UpsertDaemonOpts::builder(id.clone())
@@ -1431,6 +2454,7 @@ impl Supervisor {
                                 o.pid = None;
                                 o.status = new_status;
                                 o.last_exit_success = Some(last_exit_success);
+                                o.next_retry_at = next_retry_at;
                             })
                             .build(),
                     )
@@ -1447,15 +2471,20 @@ impl Supervisor {
             ];
 
             // Determine which hooks to fire based on exit reason
-            let hooks_to_fire: Vec<HookType> = match exit_reason {
+            let mut hooks_to_fire: Vec<HookType> = match exit_reason {
                 "stop" => vec![HookType::OnStop, HookType::OnExit],
+                "exit" if exited_early => vec![HookType::OnFail, HookType::OnExit],
                 "exit" => vec![HookType::OnExit],
-                // "fail": fire on_fail + on_exit only when retries are exhausted
-                _ if hook_retry_count >= hook_retry.count() => {
+                // "fail"/"killed": fire on_fail + on_exit only when retries are exhausted
+                _ if retries_exhausted => {
                     vec![HookType::OnFail, HookType::OnExit]
                 }
                 _ => vec![],
             };
+            // on_oom fires on every OOM kill, including ones that will be retried
+            if oom_killed {
+                hooks_to_fire.insert(0, HookType::OnOom);
+            }
 
             for hook_type in hooks_to_fire {
                 fire_hook(
@@ -1477,10 +2506,25 @@ impl Supervisor {
                     info!("daemon {id} is ready");
                     Ok(IpcResponse::DaemonReady { daemon })
                 }
-                Ok(Err(exit_code)) => {
+                Ok(Err(ReadyFailure::Exited(exit_code))) => {
                     error!("daemon {id} failed before becoming ready");
                     Ok(IpcResponse::DaemonFailedWithCode { exit_code })
                 }
+                Ok(Err(ReadyFailure::TimedOut(reason))) => {
+                    error!("daemon {id} not ready: {reason}");
+                    Ok(IpcResponse::DaemonNotReady { reason })
+                }
+                Ok(Err(ReadyFailure::ExitedEarly(exit_code))) => {
+                    let error = exited_early_message(exit_code);
+                    error!("daemon {id} {error}");
+                    Ok(IpcResponse::DaemonFailed { error })
+                }
+                Ok(Err(ReadyFailure::FailOutput(line))) => {
+                    error!("daemon {id} failed to start: {line}");
+                    Ok(IpcResponse::DaemonFailed {
+                        error: format!("output matched fail_output: {line}"),
+                    })
+                }
                 Err(_) => {
                     error!("readiness channel closed unexpectedly for daemon {id}");
                     Ok(IpcResponse::DaemonStart { daemon })
@@ -1491,6 +2535,53 @@ impl Supervisor {
         }
     }
 
+    /// Terminate whatever is listening on `ports` so `id` can bind them
+    /// (`kill_conflicts`). Conflicting pitchfork daemons are stopped through
+    /// the supervisor so their state stays consistent.
+    async fn kill_port_conflicts(&self, id: &DaemonId, ports: impl IntoIterator<Item = u16>) {
+        for port in ports {
+            let Some((pid, process)) = detect_port_conflict(port).await else {
+                continue;
+            };
+            if pid == 0 || pid == std::process::id() {
+                warn!("daemon {id}: port {port} is held by {process}, which cannot be killed");
+                continue;
+            }
+            let owner = self
+                .active_daemons()
+                .await
+                .into_iter()
+                .find(|d| d.local_pid() == Some(pid));
+            let result = match owner {
+                Some(owner) => {
+                    info!(
+                        "daemon {id}: stopping daemon {} which holds port {port}",
+                        owner.id
+                    );
+                    self.stop(&owner.id).await.map(|_| ())
+                }
+                None => {
+                    info!("daemon {id}: killing {process} (pid {pid}) which holds port {port}");
+                    PROCS
+                        .kill_async(pid, StopSignal::default().into(), None)
+                        .await
+                        .map(|_| ())
+                }
+            };
+            if let Err(e) = result {
+                warn!("daemon {id}: failed to free port {port}: {e}");
+                continue;
+            }
+            // The port can stay bound briefly after the process exits
+            for _ in 0..20 {
+                if !is_port_in_use(port).await {
+                    break;
+                }
+                time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+
     /// Stop a running daemon
     pub async fn stop(&self, id: &DaemonId) -> Result<IpcResponse> {
         let pitchfork_id = DaemonId::pitchfork();
@@ -1502,6 +2593,11 @@ impl Supervisor {
         info!("stopping daemon: {id}");
         if let Some(daemon) = self.get_daemon(id).await {
             trace!("daemon to stop: {daemon}");
+            if let Some(host) = daemon.remote_host() {
+                return Ok(IpcResponse::Error(format!(
+                    "daemon {id} is running on host {host}; stop it there"
+                )));
+            }
             if let Some(pid) = daemon.pid {
                 trace!("killing pid: {pid}");
                 if PROCS.is_running(pid) {
@@ -1517,7 +2613,8 @@ impl Supervisor {
                     .await?;
 
                     // Kill the entire process group atomically (daemon PID == PGID
-                    // because we called setsid() at spawn time)
+                    // because we called setsid() at spawn time; on Windows the
+                    // daemon's job object plays that role)
                     let stop_cfg = daemon.stop_signal.unwrap_or_default();
                     let stop_signal: i32 = stop_cfg.signal.into();
                     if let Err(e) = PROCS
@@ -1579,6 +2676,16 @@ impl Supervisor {
                     return Ok(IpcResponse::DaemonWasNotRunning);
                 }
                 Ok(IpcResponse::Ok)
+            } else if daemon.waiting_for.is_some() {
+                // Not spawned yet; clearing waiting_for ends the wait in run()
+                info!("daemon {id} stopped while waiting for a requirement");
+                self.upsert_daemon(
+                    UpsertDaemonOpts::builder(id.clone())
+                        .set(|o| o.status = DaemonStatus::Stopped)
+                        .build(),
+                )
+                .await?;
+                Ok(IpcResponse::Ok)
             } else {
                 debug!("daemon {id} not running");
                 Ok(IpcResponse::DaemonNotRunning)
@@ -1726,6 +2833,98 @@ fn apply_run_identity(identity: &RunIdentity) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Run `command` as the daemon with `user` (see [`RunOptions::user`]) runs,
+/// so that a `ready_cmd` or hook never gets more rights than its daemon, e.g.
+/// one started by another user through a shared supervisor
+pub(super) fn run_as_daemon_user(
+    command: &mut tokio::process::Command,
+    user: Option<&str>,
+) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let identity = resolve_effective_run_identity(user)?;
+        if identity != RunIdentity::Inherit {
+            unsafe {
+                command.pre_exec(move || apply_run_identity(&identity));
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (command, user);
+    Ok(())
+}
+
+/// `(name, resource, value)` for each limit set in `limits`.
+#[cfg(unix)]
+fn resource_limit_table(
+    limits: &ResourceLimits,
+) -> [(&'static str, RlimitResource, Option<u64>); 5] {
+    [
+        ("nofile", libc::RLIMIT_NOFILE, limits.nofile),
+        ("core", libc::RLIMIT_CORE, limits.core),
+        ("nproc", libc::RLIMIT_NPROC, limits.nproc),
+        ("stack", libc::RLIMIT_STACK, limits.stack),
+        ("memlock", libc::RLIMIT_MEMLOCK, limits.memlock),
+    ]
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+/// Fail early, with a readable error, when a limit needs a higher hard limit
+/// than an unprivileged supervisor can grant. The same condition in
+/// `pre_exec` would only surface as a bare EPERM from spawn.
+#[cfg(unix)]
+fn check_resource_limits(limits: &ResourceLimits) -> Result<()> {
+    if nix::unistd::geteuid().is_root() {
+        return Ok(());
+    }
+    for (name, resource, value) in resource_limit_table(limits) {
+        let Some(value) = value else { continue };
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+            continue;
+        }
+        if current.rlim_max != libc::RLIM_INFINITY && value as libc::rlim_t > current.rlim_max {
+            miette::bail!(
+                "limits.{name} = {value} exceeds the hard limit of {}; lower it or run the supervisor as root",
+                current.rlim_max
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Apply resource limits in the child. Runs in `pre_exec`, so it only makes
+/// raw syscalls and does not allocate.
+#[cfg(unix)]
+fn apply_resource_limits(limits: &ResourceLimits) -> std::io::Result<()> {
+    for (_, resource, value) in resource_limit_table(limits) {
+        let Some(value) = value else { continue };
+        let value = value as libc::rlim_t;
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        limit.rlim_cur = value;
+        if limit.rlim_max != libc::RLIM_INFINITY && limit.rlim_max < value {
+            limit.rlim_max = value;
+        }
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
 #[cfg(unix)]
 impl RunIdentity {
     fn matches(&self, uid: nix::unistd::Uid, gid: nix::unistd::Gid) -> bool {
@@ -2227,6 +3426,11 @@ mod ready_check_tests {
             Some(&http),
             false,
             None,
+            false,
+            None,
+            false,
+            None,
+            None,
             false
         ));
         assert!(any_ready_check_remaining(
@@ -2237,6 +3441,11 @@ mod ready_check_tests {
             None,
             false,
             Some(&cmd),
+            false,
+            None,
+            false,
+            None,
+            None,
             false
         ));
         assert!(any_ready_check_remaining(
@@ -2247,7 +3456,27 @@ mod ready_check_tests {
             Some(&http),
             true,
             Some(&cmd),
-            true
+            true,
+            None,
+            false,
+            None,
+            None,
+            false
+        ));
+        assert!(any_ready_check_remaining(
+            None,
+            false,
+            None,
+            false,
+            Some(&http),
+            true,
+            None,
+            false,
+            None,
+            false,
+            Some(Path::new("/tmp/app.sock")),
+            None,
+            false
         ));
     }
 
@@ -2255,12 +3484,13 @@ mod ready_check_tests {
     fn any_ready_check_remaining_exhausted_timed_checks() {
         let http = ReadyHttp {
             url: "http://localhost/health".to_string(),
-            status: vec![],
             timeout: Some(Duration::from_secs(5)),
+            ..ReadyHttp::default()
         };
         let cmd = ReadyCmd {
             run: "true".to_string(),
             timeout: Some(Duration::from_secs(5)),
+            attempt_timeout: None,
         };
 
         assert!(any_ready_check_remaining(
@@ -2271,6 +3501,11 @@ mod ready_check_tests {
             Some(&http),
             false,
             Some(&cmd),
+            false,
+            None,
+            false,
+            None,
+            None,
             false
         ));
         assert!(!any_ready_check_remaining(
@@ -2281,14 +3516,120 @@ mod ready_check_tests {
             Some(&http),
             true,
             Some(&cmd),
-            true
+            true,
+            None,
+            false,
+            None,
+            None,
+            false
+        ));
+    }
+
+    #[test]
+    fn test_ready_timeout_reason() {
+        let http = ReadyHttp::new("http://localhost/health");
+        let checks =
+            describe_ready_checks(None, Some(5432), None, Some(&http), None, None, None, None);
+        assert_eq!(checks, "TCP port 5432 or HTTP http://localhost/health");
+        assert_eq!(
+            describe_ready_checks(
+                None,
+                None,
+                Some(Path::new("/tmp/app.sock")),
+                None,
+                None,
+                None,
+                None,
+                None
+            ),
+            "unix socket /tmp/app.sock"
+        );
+        let when = ReadyWhen {
+            daemon: "db".to_string(),
+            state: ReadyWhenState::Ready,
+            timeout: None,
+        };
+        assert_eq!(
+            describe_ready_checks(None, None, None, None, None, None, Some(&when), None),
+            "daemon db ready"
+        );
+        assert_eq!(
+            describe_ready_checks(
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Duration::from_secs(3))
+            ),
+            "delay (3s)"
+        );
+
+        let ReadyFailure::TimedOut(reason) =
+            ReadyFailure::timed_out(&checks, Duration::from_millis(10_400))
+        else {
+            panic!("expected a timeout");
+        };
+        assert_eq!(
+            reason,
+            "gave up waiting for TCP port 5432 or HTTP http://localhost/health after 10s"
+        );
+    }
+
+    #[test]
+    fn test_exited_too_early() {
+        let secs = Duration::from_secs;
+        assert!(exited_too_early(true, secs(2)));
+        assert!(!exited_too_early(true, LONG_RUNNING_GRACE));
+        // Never became ready, however long its ready checks took
+        assert!(exited_too_early(false, secs(60)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_clean_exit() {
+        use std::os::unix::process::ExitStatusExt;
+        let exit = |code: i32| std::process::ExitStatus::from_raw(code << 8);
+        assert!(is_clean_exit(&exit(0), &[]));
+        assert!(!is_clean_exit(&exit(2), &[]));
+        assert!(is_clean_exit(&exit(2), &[0, 2]));
+        assert!(!is_clean_exit(&exit(3), &[0, 2]));
+        // Killed by a signal: no exit code to match
+        assert!(!is_clean_exit(
+            &std::process::ExitStatus::from_raw(libc::SIGTERM),
+            &[0, 2]
         ));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_reason() {
+        use std::os::unix::process::ExitStatusExt;
+        let exit = |code: i32| Ok(std::process::ExitStatus::from_raw(code << 8));
+        let signal = |sig: i32| Ok(std::process::ExitStatus::from_raw(sig));
+        assert_eq!(exit_reason(&exit(0), false, false, &[]), "exit");
+        assert_eq!(exit_reason(&exit(1), false, false, &[]), "fail");
+        assert_eq!(exit_reason(&signal(libc::SIGTERM), true, true, &[]), "stop");
+        // A signal nobody in pitchfork sent
+        assert_eq!(
+            exit_reason(&signal(libc::SIGTERM), false, false, &[]),
+            "killed"
+        );
+        // A signal pitchfork sent without stopping, e.g. a resource limit kill
+        assert_eq!(
+            exit_reason(&signal(libc::SIGKILL), false, true, &[]),
+            "fail"
+        );
+        let err = Err(std::io::Error::other("wait failed"));
+        assert_eq!(exit_reason(&err, false, false, &[]), "fail");
+    }
+
     #[tokio::test]
     async fn spawn_cmd_probe_reports_success() {
         let id = DaemonId::new("global", "probe-test");
-        let probe = spawn_cmd_probe(&id, "true", &std::env::temp_dir());
+        let probe = spawn_cmd_probe(&id, "true", &std::env::temp_dir(), None, None, None);
         let status = probe.result_rx.await.unwrap().unwrap();
         assert!(status.success());
     }
@@ -2296,7 +3637,7 @@ mod ready_check_tests {
     #[tokio::test]
     async fn spawn_cmd_probe_stops_on_request() {
         let id = DaemonId::new("global", "probe-test");
-        let probe = spawn_cmd_probe(&id, "sleep 30", &std::env::temp_dir());
+        let probe = spawn_cmd_probe(&id, "sleep 30", &std::env::temp_dir(), None, None, None);
         let CmdProbe {
             cancel_tx,
             result_rx,
@@ -2305,4 +3646,50 @@ mod ready_check_tests {
         let status = result_rx.await.unwrap().unwrap();
         assert!(!status.success());
     }
+
+    #[tokio::test]
+    async fn spawn_cmd_probe_kills_run_past_timeout() {
+        let id = DaemonId::new("global", "probe-test");
+        let started = std::time::Instant::now();
+        let probe = spawn_cmd_probe(
+            &id,
+            "sleep 30",
+            &std::env::temp_dir(),
+            None,
+            Some(Duration::from_millis(200)),
+            None,
+        );
+        let err = probe.result_rx.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn non_admin_probe_and_hook_run_as_caller() {
+        // Only a root supervisor can be shared and switch users
+        if !nix::unistd::Uid::effective().is_root() {
+            return;
+        }
+        let Ok(Some(nobody)) = nix::unistd::User::from_name("nobody") else {
+            return;
+        };
+        let caller = crate::supervisor::users::Caller {
+            name: nobody.name.clone(),
+            admin: false,
+        };
+        let mut opts = RunOptions::default();
+        caller.claim(&mut opts, None);
+        let check = format!("test \"$(id -u)\" = {}", nobody.uid);
+
+        let id = DaemonId::new("global", "probe-test");
+        let dir = std::path::Path::new("/");
+        let probe = spawn_cmd_probe(&id, &check, dir, None, None, opts.user.as_deref());
+        assert!(probe.result_rx.await.unwrap().unwrap().success());
+
+        let mut hook = hooks::hook_command(&check).unwrap();
+        hook.current_dir(dir);
+        run_as_daemon_user(&mut hook, opts.user.as_deref()).unwrap();
+        assert!(hook.status().await.unwrap().success());
+    }
 }
@@ -0,0 +1,224 @@
+//! Per-daemon output rate limiting
+//!
+//! A daemon printing 100k lines a second keeps its output task busy parsing
+//! and storing lines, which takes time away from every other daemon. Each
+//! daemon's output readers share a token bucket refilled at
+//! `logs.rate_limit` bytes per second (with up to one second of burst). What
+//! happens to lines over the limit depends on `logs.overflow`:
+//!
+//! - `drop`: the line is read and discarded, and counted in the daemon's
+//!   `log_lines_dropped`. The daemon itself is never slowed down.
+//! - `throttle`: the reader waits for the bucket to refill before taking the
+//!   next line, so the pipe fills up and the daemon blocks on writes, the
+//!   same way it would writing to a slow terminal.
+//!
+//! Either way the first line over the limit logs a warning and records a
+//! `throttled` event, at most once a minute.
+
+use super::events;
+use crate::daemon_id::DaemonId;
+use crate::settings::settings;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Least time between two warnings for the same daemon
+const WARN_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LogOverflow {
+    Drop,
+    Throttle,
+}
+
+/// What to do with a line
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Admit {
+    Pass,
+    Drop,
+    Wait(Duration),
+}
+
+pub(crate) struct LogLimiter {
+    /// Bytes per second, also the bucket's capacity
+    rate: u64,
+    overflow: LogOverflow,
+    bucket: Mutex<Bucket>,
+    dropped: AtomicU64,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+    /// Whether the last line was over the limit
+    limited: bool,
+    warned: Option<Instant>,
+}
+
+impl LogLimiter {
+    /// The limiter configured by `logs.rate_limit`/`logs.overflow`, or
+    /// `None` when the rate limit is off.
+    pub(crate) fn from_settings() -> Option<Self> {
+        let s = settings();
+        let rate = u64::try_from(s.logs.rate_limit).unwrap_or(0);
+        if rate == 0 {
+            return None;
+        }
+        let overflow = match s.logs.overflow.as_str() {
+            "drop" => LogOverflow::Drop,
+            "throttle" => LogOverflow::Throttle,
+            other => {
+                warn!("unknown logs.overflow {other:?}, expected drop or throttle");
+                LogOverflow::Drop
+            }
+        };
+        Some(Self::new(rate, overflow))
+    }
+
+    pub(super) fn new(rate: u64, overflow: LogOverflow) -> Self {
+        Self {
+            rate,
+            overflow,
+            bucket: Mutex::new(Bucket {
+                tokens: rate as f64,
+                refilled: Instant::now(),
+                limited: false,
+                warned: None,
+            }),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Lines dropped so far
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count_dropped(&self, lines: u64) {
+        self.dropped.fetch_add(lines, Ordering::Relaxed);
+    }
+
+    /// Whether lines are being dropped and the bucket is still empty, so
+    /// output can be discarded without looking at each line
+    pub(crate) fn exhausted(&self) -> bool {
+        if self.overflow != LogOverflow::Drop {
+            return false;
+        }
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, Instant::now());
+        bucket.limited && bucket.tokens < 1.0
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let rate = self.rate as f64;
+        let elapsed = now.saturating_duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.refilled = now;
+    }
+
+    /// Account for a line of `len` bytes read from daemon `id`. Returns
+    /// whether to keep it, after waiting out the limit in throttle mode.
+    pub(crate) async fn admit(&self, id: &DaemonId, len: usize) -> bool {
+        let (admit, started) = self.take(len, Instant::now());
+        if started {
+            let rate = humanbyte::to_string(self.rate, humanbyte::Format::IEC);
+            let message = match self.overflow {
+                LogOverflow::Drop => format!("output over {rate}/s, dropping lines"),
+                LogOverflow::Throttle => format!("output over {rate}/s, slowing reads"),
+            };
+            warn!("daemon {id}: {message} (logs.rate_limit)");
+            events::record(id, "throttled", Some(message));
+        }
+        match admit {
+            Admit::Pass => true,
+            Admit::Drop => {
+                self.count_dropped(1);
+                false
+            }
+            Admit::Wait(wait) => {
+                tokio::time::sleep(wait).await;
+                true
+            }
+        }
+    }
+
+    /// Take `len` bytes from the bucket. Also returns whether this line
+    /// starts a run of lines over the limit that should be warned about.
+    fn take(&self, len: usize, now: Instant) -> (Admit, bool) {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, now);
+        let rate = self.rate as f64;
+        // Lines longer than a second's worth would never fit otherwise
+        let cost = (len as f64).min(rate);
+        let admit = if bucket.tokens >= cost {
+            bucket.tokens -= cost;
+            Admit::Pass
+        } else {
+            match self.overflow {
+                LogOverflow::Drop => Admit::Drop,
+                LogOverflow::Throttle => {
+                    let wait = Duration::from_secs_f64((cost - bucket.tokens) / rate);
+                    bucket.tokens -= cost;
+                    Admit::Wait(wait)
+                }
+            }
+        };
+        let limited = admit != Admit::Pass;
+        let started = limited
+            && !bucket.limited
+            && bucket
+                .warned
+                .is_none_or(|at| now.duration_since(at) >= WARN_INTERVAL);
+        if started {
+            bucket.warned = Some(now);
+        }
+        bucket.limited = limited;
+        (admit, started)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_over_limit() {
+        let limiter = LogLimiter::new(100, LogOverflow::Drop);
+        let now = Instant::now();
+        assert_eq!(limiter.take(60, now), (Admit::Pass, false));
+        assert_eq!(limiter.take(60, now), (Admit::Drop, true));
+        assert_eq!(limiter.take(60, now), (Admit::Drop, false));
+        assert_eq!(limiter.take(30, now), (Admit::Pass, false));
+        // Half a second refills half the bucket
+        let later = now + Duration::from_millis(500);
+        assert_eq!(limiter.take(50, later), (Admit::Pass, false));
+        // Warned about recently
+        assert_eq!(limiter.take(50, later), (Admit::Drop, false));
+        let later = now + WARN_INTERVAL;
+        assert_eq!(limiter.take(100, later), (Admit::Pass, false));
+        assert_eq!(limiter.take(50, later), (Admit::Drop, true));
+    }
+
+    #[test]
+    fn test_throttle_over_limit() {
+        let limiter = LogLimiter::new(100, LogOverflow::Throttle);
+        let now = Instant::now();
+        assert_eq!(limiter.take(100, now), (Admit::Pass, false));
+        assert_eq!(
+            limiter.take(50, now),
+            (Admit::Wait(Duration::from_millis(500)), true)
+        );
+        // The wait was paid for up front
+        let later = now + Duration::from_millis(500);
+        assert_eq!(
+            limiter.take(50, later),
+            (Admit::Wait(Duration::from_millis(500)), false)
+        );
+    }
+
+    #[test]
+    fn test_long_line_fits() {
+        let limiter = LogLimiter::new(100, LogOverflow::Drop);
+        assert_eq!(limiter.take(1000, Instant::now()), (Admit::Pass, false));
+    }
+}
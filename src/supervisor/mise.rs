@@ -0,0 +1,133 @@
+//! Integration with `mise run` tasks
+//!
+//! When a daemon's `run` is a `mise run <task>` command and mise is enabled
+//! for it (`mise = true` or `general.mise`), the supervisor asks mise for the
+//! environment and directory the task runs with, so `ready_cmd` probes and
+//! hooks see the same `PATH` and variables as the task itself.
+
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long to wait for `mise env` / `mise task info` before giving up.
+const MISE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Environment and directory of a `mise run` task, as reported by mise.
+#[derive(Debug, Default)]
+pub(crate) struct MiseTask {
+    /// mise's environment for the daemon dir, with the task's own `env` on top
+    pub(crate) env: IndexMap<String, String>,
+    /// The task's `dir`, if it sets one
+    pub(crate) dir: Option<PathBuf>,
+}
+
+/// The task a `run` command starts, for `mise run <task>` or `mise r <task>`
+/// (optionally behind `exec`).
+pub(crate) fn task_name(run: &str) -> Option<String> {
+    let words = shell_words::split(run).ok()?;
+    let mut words = words.iter().map(String::as_str).peekable();
+    words.next_if_eq(&"exec");
+    let program = words.next()?;
+    if Path::new(program).file_name()? != "mise" {
+        return None;
+    }
+    if !matches!(words.next()?, "run" | "r") {
+        return None;
+    }
+    words
+        .find(|w| !w.starts_with('-'))
+        .filter(|w| !w.contains(['&', '|', ';']))
+        .map(str::to_string)
+}
+
+/// Ask mise for the environment and directory `task` runs with in `dir`.
+pub(crate) async fn resolve(mise_bin: &Path, task: &str, dir: &Path) -> crate::Result<MiseTask> {
+    let mut env: IndexMap<String, String> =
+        serde_json::from_slice(&mise_json(mise_bin, &["env", "--json"], dir).await?)
+            .map_err(|e| miette::miette!("failed to parse `mise env --json`: {e}"))?;
+    let info: serde_json::Value =
+        serde_json::from_slice(&mise_json(mise_bin, &["task", "info", "--json", task], dir).await?)
+            .map_err(|e| miette::miette!("failed to parse `mise task info --json`: {e}"))?;
+    if let Some(task_env) = info.get("env").and_then(|e| e.as_object()) {
+        for (key, value) in task_env {
+            if let Some(value) = value.as_str() {
+                env.insert(key.clone(), value.to_string());
+            }
+        }
+    }
+    let task_dir = info
+        .get("dir")
+        .and_then(|d| d.as_str())
+        // Unrendered templates like "{{config_root}}/web" can't be used as-is
+        .filter(|d| !d.is_empty() && !d.contains("{{"))
+        .map(|d| dir.join(d));
+    Ok(MiseTask { env, dir: task_dir })
+}
+
+async fn mise_json(mise_bin: &Path, args: &[&str], dir: &Path) -> crate::Result<Vec<u8>> {
+    let output = tokio::process::Command::new(mise_bin)
+        .args(args)
+        .current_dir(dir)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(MISE_TIMEOUT, output)
+        .await
+        .map_err(|_| miette::miette!("`mise {}` timed out", args.join(" ")))?
+        .map_err(|e| miette::miette!("failed to run `mise {}`: {e}", args.join(" ")))?;
+    if !output.status.success() {
+        miette::bail!(
+            "`mise {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_name() {
+        assert_eq!(task_name("mise run dev").as_deref(), Some("dev"));
+        assert_eq!(
+            task_name("exec mise r docs:dev").as_deref(),
+            Some("docs:dev")
+        );
+        assert_eq!(
+            task_name("/usr/local/bin/mise run --force api -- --port 3000").as_deref(),
+            Some("api")
+        );
+        assert_eq!(task_name("mise x -- node server.js"), None);
+        assert_eq!(task_name("npm run dev"), None);
+        assert_eq!(task_name("mise run"), None);
+        assert_eq!(task_name("mise run 'unclosed"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_resolve_merges_task_env_and_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let fake_mise = tmp.path().join("mise");
+        std::fs::write(
+            &fake_mise,
+            r#"#!/bin/sh
+case "$1" in
+  env) echo '{"PATH":"/mise/bin:/usr/bin","NODE_ENV":"production"}' ;;
+  task) echo '{"name":"dev","dir":"web","env":{"NODE_ENV":"development"}}' ;;
+esac
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake_mise, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let task = resolve(&fake_mise, "dev", tmp.path()).await.unwrap();
+        assert_eq!(task.env["PATH"], "/mise/bin:/usr/bin");
+        assert_eq!(task.env["NODE_ENV"], "development");
+        assert_eq!(task.dir, Some(tmp.path().join("web")));
+    }
+}
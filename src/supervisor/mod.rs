@@ -3,19 +3,52 @@
 //! This module is split into focused submodules:
 //! - `state`: State access layer (get/set operations)
 //! - `lifecycle`: Daemon start/stop operations
+//! - `mise`: Environment of `mise run` tasks for ready checks and hooks
 //! - `autostop`: Autostop logic and boot daemon startup
+//! - `cascade`: Restarting dependents with `restart_with_dependencies`
+//! - `clock`: Time source for cron and retry backoff (fakeable in tests)
+//! - `events`: Recent lifecycle events per daemon
+//! - `drift`: Flagging daemons whose binary or working directory changed on disk
+//! - `ready_file`: Readiness by tailing a log file the daemon writes itself
+//! - `requires`: Waiting for external services before spawning
 //! - `retry`: Retry logic with backoff
 //! - `watchers`: Background tasks (interval, cron, file watching)
+//! - `oom`: Detection of daemons killed by the kernel OOM killer
+//! - `output`: Reading daemon output as lines, whatever its encoding
+//! - `log_limit`: Rate limiting the output of chatty daemons
+//! - `alerts`: Resource usage alerts raised without stopping the daemon
+//! - `notify`: Notification delivery to a file or the desktop
+//! - `users`: Supervisors shared between users through a socket group
 //! - `ipc_handlers`: IPC request dispatch
+//! - `ipc_log`: Recent IPC requests, for `pitchfork debug ipc`
 
+mod alerts;
 mod autostop;
-mod hooks;
+mod cascade;
+mod clock;
+mod cron_lease;
+pub(crate) mod detach;
+mod drift;
+pub(crate) mod events;
+mod health;
+pub(crate) mod hooks;
 mod ipc_handlers;
+mod ipc_log;
 mod lifecycle;
+mod log_limit;
+pub(crate) mod mise;
+mod netns;
+mod notify;
+mod oom;
+mod output;
 #[cfg(unix)]
 mod pty;
+mod ready_file;
+mod requires;
 mod retry;
+pub(crate) mod startup;
 mod state;
+pub(crate) mod users;
 mod watchers;
 
 use crate::daemon_id::DaemonId;
@@ -64,10 +97,21 @@ pub(crate) use state::UpsertDaemonOpts;
 
 pub struct Supervisor {
     pub(crate) state_file: Mutex<StateFile>,
-    pub(crate) pending_notifications: Mutex<Vec<(log::LevelFilter, String)>>,
+    /// Notifications not yet shown, with the owner of the daemon they are
+    /// about (`None` for everyone)
+    pub(crate) pending_notifications: Mutex<Vec<(Option<String>, log::LevelFilter, String)>>,
     pub(crate) last_refreshed_at: Mutex<time::Instant>,
     /// Map of daemon ID to scheduled autostop time
     pub(crate) pending_autostops: Mutex<HashMap<DaemonId, time::Instant>>,
+    /// Latest directory reported by each shell (keyed by shell PID) that has
+    /// not been applied yet, with the user whose daemons it affects (`None`
+    /// for all). Drained by the shell dir watcher.
+    pub(crate) pending_shell_dirs: Mutex<HashMap<u32, (PathBuf, Option<String>)>>,
+    /// Sequence number of the latest directory change queued per shell, for
+    /// dropping changes that arrive out of order
+    pub(crate) shell_dir_seqs: Mutex<HashMap<u32, u64>>,
+    /// Signalled whenever `pending_shell_dirs` gains an entry
+    pub(crate) shell_dir_changed: Notify,
     /// Handle for graceful IPC server shutdown
     pub(crate) ipc_shutdown: Mutex<Option<IpcServerHandle>>,
     /// Tracks in-flight hook tasks so shutdown can wait for them to complete
@@ -76,6 +120,8 @@ pub struct Supervisor {
     /// and hook registration completion). Used by `close()` to know when it is
     /// safe to drain `hook_tasks`.
     pub(crate) active_monitors: AtomicU32,
+    /// Number of boot daemons started by `start_boot_daemons`
+    pub(crate) boot_daemons_started: AtomicU32,
     /// Signalled by each monitoring task after it finishes registering hooks
     /// (or decides it has nothing to register). `close()` waits on this.
     pub(crate) monitor_done: Notify,
@@ -92,6 +138,8 @@ pub struct Supervisor {
     pub(crate) lan_monitor_task: Mutex<Option<JoinHandle<()>>>,
     /// Cancellation token for the background state flush task.
     pub(crate) flush_cancel: std::sync::Mutex<Option<tokio_util::sync::CancellationToken>>,
+    /// Time source for cron schedules and retry backoff
+    pub(crate) clock: Box<dyn clock::Clock>,
 }
 
 pub(crate) fn interval_duration() -> Duration {
@@ -101,15 +149,23 @@ pub(crate) fn interval_duration() -> Duration {
 pub static SUPERVISOR: Lazy<Supervisor> =
     Lazy::new(|| Supervisor::new().expect("Error creating supervisor"));
 
-pub fn start_if_not_running() -> Result<()> {
+/// Whether the supervisor recorded in the state file is alive
+pub fn is_running() -> bool {
     let sf = StateFile::get();
-    if let Some(d) = sf.daemons.get(&DaemonId::pitchfork())
-        && let Some(pid) = d.pid
-        && PROCS.is_running(pid)
-    {
-        return Ok(());
+    sf.daemons
+        .get(&DaemonId::pitchfork())
+        .and_then(|d| d.local_pid())
+        .is_some_and(|pid| PROCS.is_running(pid))
+}
+
+/// Start the supervisor in the background unless it's already running.
+/// Returns true if it was started.
+pub fn start_if_not_running() -> Result<bool> {
+    if is_running() {
+        return Ok(false);
     }
-    start_in_background()
+    start_in_background()?;
+    Ok(true)
 }
 
 pub fn start_in_background() -> Result<()> {
@@ -123,6 +179,7 @@ pub fn start_in_background() -> Result<()> {
     }
     #[cfg(unix)]
     fix_state_dir_permissions();
+    let marker = startup::new_marker();
 
     // On Unix, use duct with stderr redirected to the log file.
     #[cfg(unix)]
@@ -132,12 +189,18 @@ pub fn start_in_background() -> Result<()> {
             .append(true)
             .open(log_file)
             .into_diagnostic()?;
-        cmd!(&*env::PITCHFORK_BIN, "supervisor", "run")
-            .stdin_null()
-            .stdout_null()
-            .stderr_file(stderr_file)
-            .start()
-            .into_diagnostic()?;
+        cmd!(
+            &*env::PITCHFORK_BIN,
+            "supervisor",
+            "run",
+            "--startup-marker",
+            &marker
+        )
+        .stdin_null()
+        .stdout_null()
+        .stderr_file(stderr_file)
+        .start()
+        .into_diagnostic()?;
     }
 
     // On Windows, use CreateProcessW directly with bInheritHandles=FALSE.
@@ -168,9 +231,13 @@ pub fn start_in_background() -> Result<()> {
         si.cb = std::mem::size_of::<STARTUPINFOW>() as u32;
 
         let bin_path = &*env::PITCHFORK_BIN;
-        let mut cmd_line: Vec<u16> = format!("\"{}\" supervisor run\0", bin_path.to_string_lossy())
-            .encode_utf16()
-            .collect();
+        let mut cmd_line: Vec<u16> = format!(
+            "\"{}\" supervisor run --startup-marker \"{}\"\0",
+            bin_path.to_string_lossy(),
+            marker.to_string_lossy()
+        )
+        .encode_utf16()
+        .collect();
 
         let mut pi: PROCESS_INFORMATION = unsafe { std::mem::zeroed() };
         let ok = unsafe {
@@ -241,26 +308,45 @@ fn should_remove_liveness_session(
 
 impl Supervisor {
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            state_file: Mutex::new(StateFile::read(&*env::PITCHFORK_STATE_FILE).unwrap_or_else(
-                |e| {
-                    warn!("failed to read state file, starting with empty state: {e}");
-                    StateFile::new(env::PITCHFORK_STATE_FILE.clone())
-                },
-            )),
+        let state_file = StateFile::read(&*env::PITCHFORK_STATE_FILE).unwrap_or_else(|e| {
+            warn!("failed to read state file, starting with empty state: {e}");
+            StateFile::new(env::PITCHFORK_STATE_FILE.clone())
+        });
+        Ok(Self::with_state_file(state_file))
+    }
+
+    /// A supervisor managing the daemons in `state_file`, without starting
+    /// any of its tasks
+    pub(crate) fn with_state_file(state_file: StateFile) -> Self {
+        Self {
+            state_file: Mutex::new(state_file),
             last_refreshed_at: Mutex::new(time::Instant::now()),
             pending_notifications: Mutex::new(vec![]),
             pending_autostops: Mutex::new(HashMap::new()),
+            pending_shell_dirs: Mutex::new(HashMap::new()),
+            shell_dir_seqs: Mutex::new(HashMap::new()),
+            shell_dir_changed: Notify::new(),
             ipc_shutdown: Mutex::new(None),
             hook_tasks: Mutex::new(Vec::new()),
             active_monitors: AtomicU32::new(0),
+            boot_daemons_started: AtomicU32::new(0),
             monitor_done: Notify::new(),
             proxy_cancel: Mutex::new(None),
             proxy_task: Mutex::new(None),
             mdns_publisher: Mutex::new(None),
             lan_monitor_task: Mutex::new(None),
             flush_cancel: std::sync::Mutex::new(None),
-        })
+            clock: Box::new(clock::SystemClock),
+        }
+    }
+
+    /// This supervisor reading the time from `clock`
+    #[cfg(test)]
+    pub(crate) fn with_clock(self, clock: impl clock::Clock + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            ..self
+        }
     }
 
     pub async fn start(
@@ -276,6 +362,9 @@ impl Supervisor {
         // from reading/writing state or connecting to the IPC socket.
         #[cfg(unix)]
         fix_state_dir_permissions();
+        startup::self_check()?;
+
+        crate::fault::enable_from_env();
 
         let pid = std::process::id();
         // Ensure PROCS has data for the supervisor PID before upsert_daemon reads title()
@@ -293,6 +382,8 @@ impl Supervisor {
         // before we take over so we don't end up with duplicate processes
         // holding the same ports.
         cleanup_orphaned_daemons(self).await;
+        self.adopt_detached_daemons().await;
+        self.clear_stale_requirement_waits().await?;
 
         self.upsert_daemon(
             UpsertDaemonOpts::builder(DaemonId::pitchfork())
@@ -325,6 +416,9 @@ impl Supervisor {
         }
 
         self.cron_watch()?;
+        self.health_watch()?;
+        self.drift_watch()?;
+        self.shell_dir_watch();
         self.signals()?;
         self.daemon_file_watch()?;
 
@@ -449,7 +543,8 @@ impl Supervisor {
                 }
                 Ok(Err(msg)) => {
                     error!("{msg}");
-                    self.add_notification(log::LevelFilter::Error, msg).await;
+                    self.add_notification(None, log::LevelFilter::Error, msg)
+                        .await;
                 }
                 Err(_) => {
                     // Sender dropped without sending — serve() panicked or
@@ -466,6 +561,7 @@ impl Supervisor {
         });
 
         let (ipc, ipc_handle) = IpcServer::new()?;
+        startup::mark(startup::StartupState::Ready);
         *self.ipc_shutdown.lock().await = Some(ipc_handle);
         self.start_state_flush_task();
         self.conn_watch(ipc).await
@@ -641,6 +737,26 @@ impl Supervisor {
         }
     }
 
+    /// With fault injection enabled, maybe SIGKILL one running daemon
+    /// (see [`crate::fault`]).
+    async fn inject_daemon_kill(&self) {
+        let daemons = self.active_daemons().await;
+        let Some(daemon) = crate::fault::daemon_to_kill(daemons.len()).map(|i| &daemons[i]) else {
+            return;
+        };
+        let Some(pid) = daemon.local_pid() else {
+            return;
+        };
+        warn!("fault injection: killing daemon {} (pid {pid})", daemon.id);
+        #[cfg(unix)]
+        let signal = libc::SIGKILL;
+        #[cfg(not(unix))]
+        let signal = 9;
+        if let Err(e) = PROCS.kill_async(pid, signal, None).await {
+            warn!("fault injection: failed to kill daemon {}: {e}", daemon.id);
+        }
+    }
+
     pub(crate) async fn refresh(&self) -> Result<()> {
         trace!("refreshing");
 
@@ -736,11 +852,13 @@ impl Supervisor {
         }
 
         for dir in dirs_to_leave {
-            self.leave_dir(&dir).await?;
+            self.leave_dir(&dir, None).await?;
         }
 
+        self.inject_daemon_kill().await;
         self.check_retry().await?;
         self.process_pending_autostops().await?;
+        self.revert_expired_env_overrides().await;
 
         Ok(())
     }
@@ -783,7 +901,7 @@ impl Supervisor {
                     .await
                     .daemons
                     .values()
-                    .filter_map(|d| d.pid)
+                    .filter_map(|d| d.local_pid())
                     .collect();
                 // Reap all available zombie children that are NOT managed
                 Self::reap_unmanaged_zombies(&managed_pids).await;
@@ -938,11 +1056,19 @@ impl Supervisor {
 
     async fn handle_signal(&self) {
         info!("received signal, stopping");
-        self.close().await;
+        self.close(false).await;
         exit(0)
     }
 
-    pub(crate) async fn close(&self) {
+    /// Stop the supervisor at a client's request. With `keep_daemons` its
+    /// daemons are left running for the next supervisor to adopt.
+    pub(crate) async fn shutdown(&self, keep_daemons: bool) {
+        info!("shutdown requested, stopping");
+        self.close(keep_daemons).await;
+        exit(0)
+    }
+
+    pub(crate) async fn close(&self, keep_daemons: bool) {
         // Signal the proxy server to stop accepting new connections
         // and drain in-flight ones, *before* stopping daemons so the
         // proxy has time to finish forwarding active requests.
@@ -972,11 +1098,15 @@ impl Supervisor {
 
         let pitchfork_id = DaemonId::pitchfork();
         let active = self.active_daemons().await;
-        let active_ids: Vec<DaemonId> = active
+        // With keep_daemons, daemons that haven't been spawned yet (or are
+        // already stopping) are still stopped
+        let (detach, stop): (Vec<_>, Vec<_>) = active
             .iter()
-            .filter(|d| d.id != pitchfork_id)
-            .map(|d| d.id.clone())
-            .collect();
+            .filter(|d| d.id != pitchfork_id && d.is_local())
+            .partition(|d| keep_daemons && d.status.is_up() && d.pid.is_some());
+        let detach_ids: Vec<DaemonId> = detach.iter().map(|d| d.id.clone()).collect();
+        let active_ids: Vec<DaemonId> = stop.iter().map(|d| d.id.clone()).collect();
+        self.detach_daemons(&detach_ids).await;
 
         // Stop daemons in reverse dependency order.
         // If dependency resolution fails (e.g. config changed), fall back to
@@ -1058,11 +1188,24 @@ impl Supervisor {
         let _ = fs::remove_dir_all(&*env::IPC_SOCK_DIR);
     }
 
-    pub(crate) async fn add_notification(&self, level: log::LevelFilter, message: String) {
+    /// Queue a notification for the CLI, about daemon `id` or the whole
+    /// supervisor (`None`)
+    pub(crate) async fn add_notification(
+        &self,
+        id: Option<&DaemonId>,
+        level: log::LevelFilter,
+        message: String,
+    ) {
+        events::notification(level, &message);
+        notify::deliver(level, &message);
+        let owner = match id {
+            Some(id) => self.get_daemon(id).await.and_then(|d| d.owner),
+            None => None,
+        };
         self.pending_notifications
             .lock()
             .await
-            .push((level, message));
+            .push((owner, level, message));
     }
 }
 
@@ -1248,6 +1391,9 @@ fn chmod_safe_subtrees(state_dir: &std::path::Path) {
 /// process handles, orphan termination also fails closed because the PID/PGID
 /// cannot be pinned between identity validation and signaling.
 ///
+/// Daemons left running on purpose by `supervisor stop --keep-daemons` are
+/// not orphans; they are adopted instead.
+///
 /// This is gated by the `supervisor.cleanup_orphans` setting (default: true).
 async fn cleanup_orphaned_daemons(supervisor: &Supervisor) {
     if !settings().supervisor.cleanup_orphans {
@@ -1259,7 +1405,9 @@ async fn cleanup_orphaned_daemons(supervisor: &Supervisor) {
         state
             .daemons
             .values()
-            .filter(|d| d.id != DaemonId::pitchfork() && d.pid.is_some())
+            .filter(|d| {
+                d.id != DaemonId::pitchfork() && d.local_pid().is_some() && !d.status.is_detached()
+            })
             .cloned()
             .collect()
     };
@@ -0,0 +1,78 @@
+//! Network isolation for daemons with `isolate_network = true` (Linux only).
+//!
+//! The daemon runs under `pasta` (from passt), which puts it in its own user
+//! and network namespace with outbound connectivity. Each configured port is
+//! forwarded from a free host port to the port the daemon expects, so two
+//! daemons can both listen on 3000 inside their own namespaces.
+
+use std::path::{Path, PathBuf};
+
+/// Find the `pasta` binary on the PATH the supervisor was started with.
+pub(crate) fn find_pasta() -> Option<PathBuf> {
+    let path = crate::env::ORIGINAL_PATH.as_ref()?;
+    std::env::split_paths(path)
+        .map(|dir| dir.join("pasta"))
+        .find(|p| p.is_file())
+}
+
+/// Wrap `program args` so it runs in a new network namespace, with each
+/// `(host, daemon)` port pair forwarded from the host into the namespace.
+///
+/// Automatic forwarding is turned off in both directions: the daemon's
+/// `localhost` is its own, and only the listed ports are reachable from the
+/// host.
+pub(crate) fn wrap(
+    pasta: &Path,
+    forwards: &[(u16, u16)],
+    program: String,
+    args: Vec<String>,
+) -> (String, Vec<String>) {
+    let mut pasta_args = vec![
+        "--config-net".to_string(),
+        "--foreground".to_string(),
+        "--quiet".to_string(),
+    ];
+    if forwards.is_empty() {
+        pasta_args.extend(["-t".to_string(), "none".to_string()]);
+    }
+    for (host, daemon) in forwards {
+        pasta_args.extend(["-t".to_string(), format!("{host}:{daemon}")]);
+    }
+    for opt in ["-u", "-T", "-U"] {
+        pasta_args.extend([opt.to_string(), "none".to_string()]);
+    }
+    pasta_args.push("--".to_string());
+    pasta_args.push(program);
+    pasta_args.extend(args);
+    (pasta.to_string_lossy().to_string(), pasta_args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_forwards_host_ports_to_expected_ports() {
+        let (program, args) = wrap(
+            Path::new("/usr/bin/pasta"),
+            &[(3001, 3000), (9230, 9229)],
+            "sh".to_string(),
+            vec!["-c".to_string(), "node server.js".to_string()],
+        );
+        assert_eq!(program, "/usr/bin/pasta");
+        assert_eq!(
+            args.join(" "),
+            "--config-net --foreground --quiet -t 3001:3000 -t 9230:9229 \
+             -u none -T none -U none -- sh -c node server.js"
+        );
+    }
+
+    #[test]
+    fn test_wrap_without_ports_forwards_nothing() {
+        let (_, args) = wrap(Path::new("pasta"), &[], "sleep".to_string(), vec![]);
+        assert_eq!(
+            args.join(" "),
+            "--config-net --foreground --quiet -t none -u none -T none -U none -- sleep"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Notification delivery by the supervisor itself (`supervisor.notify`)
+//!
+//! Pending notifications are only shown when a command drains them over IPC.
+//! These channels deliver them as they happen: to the notification file read
+//! by `pitchfork notifications`, and/or as desktop notifications.
+
+use crate::settings::settings;
+use std::str::FromStr;
+
+#[derive(Debug, Default, PartialEq)]
+struct Channels {
+    file: bool,
+    os: bool,
+}
+
+impl Channels {
+    fn parse(value: &str) -> Self {
+        let mut channels = Self::default();
+        for channel in value.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            match channel {
+                "file" => channels.file = true,
+                "os" => channels.os = true,
+                other => warn!("unknown supervisor.notify channel {other:?}, expected file or os"),
+            }
+        }
+        channels
+    }
+}
+
+fn min_level() -> log::LevelFilter {
+    let value = &settings().supervisor.notify_level;
+    log::LevelFilter::from_str(value).unwrap_or_else(|_| {
+        warn!("unknown supervisor.notify_level {value:?}, using warn");
+        log::LevelFilter::Warn
+    })
+}
+
+/// Deliver a notification through the channels configured in `supervisor.notify`
+pub(crate) fn deliver(level: log::LevelFilter, message: &str) {
+    let channels = Channels::parse(&settings().supervisor.notify);
+    if !(channels.file || channels.os) || level > min_level() {
+        return;
+    }
+    if channels.file
+        && let Err(e) = crate::notification_file::append(level, message)
+    {
+        warn!("failed to save notification: {e}");
+    }
+    if channels.os {
+        // Don't hold up the caller on a notification daemon that is slow to answer
+        let message = message.to_string();
+        tokio::spawn(async move { os_notify(level, &message).await });
+    }
+}
+
+async fn os_notify(level: log::LevelFilter, message: &str) {
+    let title = format!("pitchfork {}", level.to_string().to_lowercase());
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut cmd = tokio::process::Command::new("osascript");
+        cmd.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_string(message),
+            applescript_string(&title)
+        ));
+        cmd
+    } else if cfg!(target_os = "linux") {
+        let urgency = match level {
+            log::LevelFilter::Error => "critical",
+            log::LevelFilter::Warn => "normal",
+            _ => "low",
+        };
+        let mut cmd = tokio::process::Command::new("notify-send");
+        cmd.args([
+            "--app-name=pitchfork",
+            "--urgency",
+            urgency,
+            &title,
+            message,
+        ]);
+        cmd
+    } else {
+        debug!("OS notifications are not supported on this platform");
+        return;
+    };
+    cmd.stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    match cmd.status().await {
+        Ok(status) if !status.success() => debug!("OS notification exited with {status}"),
+        Ok(_) => {}
+        Err(e) => debug!("failed to show OS notification: {e}"),
+    }
+}
+
+/// Quote a string for an AppleScript string literal
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_channels() {
+        assert_eq!(Channels::parse(""), Channels::default());
+        assert_eq!(
+            Channels::parse("file"),
+            Channels {
+                file: true,
+                os: false
+            }
+        );
+        assert_eq!(
+            Channels::parse(" os , file "),
+            Channels {
+                file: true,
+                os: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_applescript_string_escapes_quotes() {
+        assert_eq!(
+            applescript_string(r#"daemon "api" failed\n"#),
+            r#""daemon \"api\" failed\\n""#
+        );
+    }
+}
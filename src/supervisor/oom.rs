@@ -0,0 +1,188 @@
+//! Detection of daemons killed by the kernel OOM killer
+//!
+//! An OOM kill looks like any other SIGKILL from the outside: the daemon (or
+//! the shell wrapping it, which then exits 137) dies with no output. On Linux
+//! we call it an OOM kill only if the kernel log (`/dev/kmsg`) has a "Killed
+//! process <pid>" record, written since the daemon started, for the daemon
+//! or one of its child processes. Counters such as the cgroup `oom_kill`
+//! count are shared by every daemon and can't tell which one was killed.
+//! Reading the kernel log may be restricted (`kernel.dmesg_restrict`), in
+//! which case no exit is reported as an OOM kill.
+//!
+//! Other platforms have no OOM killer that reports this way, so detection
+//! always returns false there.
+
+use std::process::ExitStatus;
+
+/// Message used as the daemon's status and in notifications.
+pub(crate) const OOM_MESSAGE: &str = "killed: out of memory";
+
+/// State captured when a daemon is spawned, compared against at exit.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct OomBaseline {
+    /// Sequence number of the last kernel log record before the spawn
+    kmsg_seq: Option<u64>,
+}
+
+impl OomBaseline {
+    pub(crate) fn capture() -> Self {
+        let mut kmsg_seq = None;
+        read_kmsg(|record| {
+            kmsg_seq = kmsg_record_seq(record).or(kmsg_seq);
+            false
+        });
+        Self { kmsg_seq }
+    }
+}
+
+/// Whether the exit status is what an OOM kill produces: death by SIGKILL,
+/// or exit code 137 from a shell whose child was SIGKILLed.
+fn is_sigkill(status: &ExitStatus) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal() == Some(libc::SIGKILL) || status.code() == Some(128 + libc::SIGKILL)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        false
+    }
+}
+
+/// Decide whether a daemon that exited with `status` was OOM-killed.
+/// `pids` are the daemon's pid and those of its child processes.
+///
+/// Only call this for unexpected exits — pitchfork's own stop escalation
+/// also ends in SIGKILL.
+pub(crate) fn was_oom_killed(status: &ExitStatus, pids: &[u32], baseline: OomBaseline) -> bool {
+    is_sigkill(status) && read_kmsg(|record| kmsg_record_is_oom_kill(record, pids, baseline))
+}
+
+/// Call `f` with each record in the kernel log until it returns true.
+/// Returns whether it did; false if the log can't be read.
+#[cfg(target_os = "linux")]
+fn read_kmsg(mut f: impl FnMut(&str) -> bool) -> bool {
+    use std::io::Read;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let Ok(mut kmsg) = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open("/dev/kmsg")
+    else {
+        return false;
+    };
+    // Each read() returns exactly one record; EAGAIN marks the end of the
+    // buffer. Records that don't fit are skipped with EINVAL.
+    let mut buf = [0u8; 8192];
+    loop {
+        match kmsg.read(&mut buf) {
+            Ok(0) => return false,
+            Ok(n) => {
+                if f(&String::from_utf8_lossy(&buf[..n])) {
+                    return true;
+                }
+            }
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => continue,
+            Err(_) => return false,
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_kmsg(_f: impl FnMut(&str) -> bool) -> bool {
+    false
+}
+
+/// The sequence number of a kernel log record, its second field.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn kmsg_record_seq(record: &str) -> Option<u64> {
+    record.split(',').nth(1)?.parse().ok()
+}
+
+/// Match a kernel log record such as
+/// `6,1234,5678,-;Out of memory: Killed process 4242 (node) total-vm:...`
+/// (or the `Memory cgroup out of memory:` variant) against `pids`. Records
+/// from before `baseline` are about processes that had the pid earlier.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn kmsg_record_is_oom_kill(record: &str, pids: &[u32], baseline: OomBaseline) -> bool {
+    if let Some(before) = baseline.kmsg_seq
+        && kmsg_record_seq(record).is_none_or(|seq| seq <= before)
+    {
+        return false;
+    }
+    let message = record.split_once(';').map_or(record, |(_, msg)| msg);
+    if !message.contains("out of memory") && !message.contains("Out of memory") {
+        return false;
+    }
+    message
+        .split_once("Killed process ")
+        .and_then(|(_, rest)| rest.split_whitespace().next()?.parse::<u32>().ok())
+        .is_some_and(|killed| pids.contains(&killed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmsg_record_is_oom_kill() {
+        let any = OomBaseline::default();
+        let global = "3,812,9912345,-;Out of memory: Killed process 4242 (node) total-vm:1024kB";
+        assert!(kmsg_record_is_oom_kill(global, &[4242], any));
+        assert!(!kmsg_record_is_oom_kill(global, &[424], any));
+        // A child of the daemon was killed
+        assert!(kmsg_record_is_oom_kill(global, &[4200, 4242], any));
+
+        let memcg = "3,813,9912346,-;Memory cgroup out of memory: Killed process 77 (python3)";
+        assert!(kmsg_record_is_oom_kill(memcg, &[77], any));
+
+        let other = "6,814,9912347,-;audit: Killed process 4242 by policy";
+        assert!(!kmsg_record_is_oom_kill(other, &[4242], any));
+
+        // Written before the daemon started, about an earlier owner of the pid
+        let baseline = OomBaseline {
+            kmsg_seq: Some(812),
+        };
+        assert!(!kmsg_record_is_oom_kill(global, &[4242], baseline));
+        assert!(kmsg_record_is_oom_kill(memcg, &[77], baseline));
+    }
+
+    #[test]
+    fn test_only_killed_daemon_is_oom() {
+        // Two daemons SIGKILLed after the same baseline; the kernel log
+        // names only one of them
+        let baseline = OomBaseline {
+            kmsg_seq: Some(900),
+        };
+        let records = [
+            "6,901,9912345,-;oom-kill:constraint=CONSTRAINT_NONE,task=node,pid=4242",
+            "3,902,9912346,-;Out of memory: Killed process 4242 (node) total-vm:1024kB",
+            "6,903,9912347,-;systemd[1]: api.service: Main process exited, status=9/KILL",
+        ];
+        let oom = |pids: &[u32]| {
+            records
+                .iter()
+                .any(|record| kmsg_record_is_oom_kill(record, pids, baseline))
+        };
+        assert!(oom(&[4242]));
+        assert!(!oom(&[5151]));
+    }
+
+    #[test]
+    fn test_kmsg_record_seq() {
+        assert_eq!(kmsg_record_seq("3,812,9912345,-;Out of memory"), Some(812));
+        assert_eq!(kmsg_record_seq("garbage"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_sigkill() {
+        use std::os::unix::process::ExitStatusExt;
+        assert!(is_sigkill(&ExitStatus::from_raw(libc::SIGKILL)));
+        assert!(is_sigkill(&ExitStatus::from_raw(137 << 8)));
+        assert!(!is_sigkill(&ExitStatus::from_raw(libc::SIGTERM)));
+        assert!(!is_sigkill(&ExitStatus::from_raw(1 << 8)));
+    }
+}
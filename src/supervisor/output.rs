@@ -0,0 +1,236 @@
+//! Reading daemon output line by line
+//!
+//! `AsyncBufReadExt::lines` fails on the first invalid UTF-8 byte, which
+//! would stop the supervisor from reading the pipe at all and eventually
+//! block the daemon on a full pipe. [`OutputLines`] reads raw bytes instead
+//! and decodes each line with the daemon's `log_encoding`.
+//!
+//! It also applies the daemon's [`LogLimiter`]. While lines are being
+//! dropped, whole chunks of output are discarded without decoding them, so
+//! reading a flood of output costs little more than the read itself. Lines
+//! matching the daemon's `ready_output`/`fail_output` are let through
+//! regardless, in which case each line is decoded and matched first.
+
+use super::log_limit::LogLimiter;
+use crate::config_types::LogEncoding;
+use crate::daemon_id::DaemonId;
+use regex::Regex;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// Longest line kept in one piece; longer output (e.g. binary data without
+/// newlines) is split into lines of this size.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+pub(crate) struct OutputLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    encoding: LogEncoding,
+    limit: Option<(Arc<LogLimiter>, DaemonId)>,
+    /// Patterns of lines the limit never drops
+    keep: Vec<Regex>,
+    /// Whether the output up to the next newline belongs to a dropped line
+    discarding: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> OutputLines<R> {
+    pub(crate) fn new(reader: R, encoding: LogEncoding) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            encoding,
+            limit: None,
+            keep: Vec::new(),
+            discarding: false,
+        }
+    }
+
+    /// Apply `limiter`, if any, to the output of daemon `id`, except for
+    /// lines matching one of `keep`
+    pub(crate) fn limited(
+        mut self,
+        limiter: Option<Arc<LogLimiter>>,
+        id: DaemonId,
+        keep: Vec<Regex>,
+    ) -> Self {
+        self.limit = limiter.map(|limiter| (limiter, id));
+        self.keep = keep;
+        self
+    }
+
+    /// Next line without its `\n` or `\r\n` that the rate limit lets
+    /// through, or `None` at end of output
+    pub(crate) async fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        let Some((limiter, id)) = self.limit.clone() else {
+            return self.read_line().await;
+        };
+        loop {
+            // Every line has to be looked at while some must be kept
+            if self.keep.is_empty() && (self.discarding || limiter.exhausted()) {
+                if !self.discard_chunk(&limiter).await? {
+                    return Ok(None);
+                }
+                continue;
+            }
+            let Some(line) = self.read_line().await? else {
+                return Ok(None);
+            };
+            if self.kept(&line) || limiter.admit(&id, line.len()).await {
+                return Ok(Some(line));
+            }
+        }
+    }
+
+    /// Whether `line` matches a pattern the limit never drops
+    fn kept(&self, line: &str) -> bool {
+        if self.keep.is_empty() {
+            return false;
+        }
+        let line = console::strip_ansi_codes(line);
+        self.keep.iter().any(|re| re.is_match(&line))
+    }
+
+    /// Drop the buffered output up to its last newline without decoding it.
+    /// Returns false at end of output.
+    async fn discard_chunk(&mut self, limiter: &LogLimiter) -> std::io::Result<bool> {
+        let available = self.reader.fill_buf().await?;
+        if available.is_empty() {
+            if std::mem::take(&mut self.discarding) {
+                limiter.count_dropped(1);
+            }
+            return Ok(false);
+        }
+        let n = match available.iter().rposition(|&b| b == b'\n') {
+            Some(i) => {
+                let lines = available[..=i].iter().filter(|&&b| b == b'\n').count();
+                limiter.count_dropped(lines as u64);
+                self.discarding = false;
+                i + 1
+            }
+            None => {
+                self.discarding = true;
+                available.len()
+            }
+        };
+        self.reader.consume(n);
+        Ok(true)
+    }
+
+    async fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        self.buf.clear();
+        loop {
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                break;
+            }
+            let room = MAX_LINE_BYTES - self.buf.len();
+            if let Some(i) = available.iter().position(|&b| b == b'\n')
+                && i <= room
+            {
+                self.buf.extend_from_slice(&available[..i]);
+                self.reader.consume(i + 1);
+                if self.buf.last() == Some(&b'\r') {
+                    self.buf.pop();
+                }
+                return Ok(Some(self.encoding.decode(&self.buf)));
+            }
+            let n = available.len().min(room);
+            self.buf.extend_from_slice(&available[..n]);
+            self.reader.consume(n);
+            if self.buf.len() >= MAX_LINE_BYTES {
+                break;
+            }
+        }
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.encoding.decode(&self.buf)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read_all(input: &[u8], encoding: LogEncoding) -> Vec<String> {
+        let mut lines = OutputLines::new(input, encoding);
+        let mut out = vec![];
+        while let Some(line) = lines.next_line().await.unwrap() {
+            out.push(line);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_does_not_end_output() {
+        let input = b"before\n\xff\xfe binary \x80\nafter\r\nno newline";
+        assert_eq!(
+            read_all(input, LogEncoding::Utf8).await,
+            vec![
+                "before",
+                "\u{fffd}\u{fffd} binary \u{fffd}",
+                "after",
+                "no newline"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_escape_and_latin1_encodings() {
+        let input = b"caf\xe9 \xc3\xa9\n";
+        assert_eq!(
+            read_all(input, LogEncoding::Escape).await,
+            vec!["caf\\xe9 é"]
+        );
+        assert_eq!(read_all(input, LogEncoding::Latin1).await, vec!["café Ã©"]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_lines_are_dropped() {
+        use super::super::log_limit::LogOverflow;
+
+        let limiter = Arc::new(LogLimiter::new(10, LogOverflow::Drop));
+        let input = b"line1\nline2\nline3\nline4\nline5\nline6";
+        let mut lines = OutputLines::new(&input[..], LogEncoding::Utf8).limited(
+            Some(limiter.clone()),
+            DaemonId::new("test", "spam"),
+            vec![],
+        );
+        let mut out = vec![];
+        while let Some(line) = lines.next_line().await.unwrap() {
+            out.push(line);
+        }
+        assert_eq!(out, vec!["line1", "line2"]);
+        assert_eq!(limiter.dropped(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_keeps_ready_lines() {
+        use super::super::log_limit::LogOverflow;
+
+        let limiter = Arc::new(LogLimiter::new(10, LogOverflow::Drop));
+        let input = b"line1\nline2\nline3\n\x1b[32mlistening\x1b[0m\nline5";
+        let keep = vec![Regex::new("^listening$").unwrap()];
+        let mut lines = OutputLines::new(&input[..], LogEncoding::Utf8).limited(
+            Some(limiter.clone()),
+            DaemonId::new("test", "spam"),
+            keep,
+        );
+        let mut out = vec![];
+        while let Some(line) = lines.next_line().await.unwrap() {
+            out.push(line);
+        }
+        assert_eq!(out, vec!["line1", "line2", "\x1b[32mlistening\x1b[0m"]);
+        assert_eq!(limiter.dropped(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_lines_are_split() {
+        let mut input = vec![0u8; MAX_LINE_BYTES + 10];
+        input.push(b'\n');
+        let lines = read_all(&input, LogEncoding::Utf8).await;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), MAX_LINE_BYTES);
+        assert_eq!(lines[1].len(), 10);
+    }
+}
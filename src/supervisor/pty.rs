@@ -5,7 +5,18 @@
 //! useful for programs that check `isatty()` or behave differently when
 //! connected to a terminal (e.g., colored output, interactive prompts).
 
-use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+/// Terminal width reported to PTY daemons. Wide enough that dev servers
+/// don't wrap URLs or progress output mid-line in the logs.
+pub const PTY_COLS: u16 = 160;
+/// Terminal height reported to PTY daemons.
+pub const PTY_ROWS: u16 = 48;
+
+/// `TERM` value set for PTY daemons unless the daemon's `env` overrides it.
+/// The supervisor usually runs detached without a `TERM` of its own, and many
+/// color libraries treat a missing or `dumb` terminal as "no color".
+pub const PTY_TERM: &str = "xterm-256color";
 
 /// A PTY master/slave pair.
 pub struct PtyPair {
@@ -17,15 +28,22 @@ pub struct PtyPair {
 
 /// Allocate a new PTY pair using `openpty(3)`.
 ///
-/// Note: `openpty(3)` does *not* set `FD_CLOEXEC` on the returned file
-/// descriptors. The slave FD is dup'd onto the child's stdio before exec,
-/// so the original slave fd leaks into the child. This is harmless in
-/// practice — the extra fd is simply unused — but if strict
-/// close-on-exec semantics are needed, set `FD_CLOEXEC` manually via
-/// `fcntl(fd, F_SETFD, FD_CLOEXEC)`.
+/// The terminal is sized to [`PTY_COLS`]x[`PTY_ROWS`]; a zero-sized window
+/// makes some tools fall back to non-interactive output.
+///
+/// `openpty(3)` does *not* set `FD_CLOEXEC`, so it is set here on both
+/// sides. The slave is dup'd onto the child's stdio before exec (which
+/// clears the flag on the duplicates), so neither original fd leaks into
+/// the daemon or into unrelated children spawned later.
 pub fn openpty() -> std::io::Result<PtyPair> {
     let mut master_fd: libc::c_int = -1;
     let mut slave_fd: libc::c_int = -1;
+    let mut winsize = libc::winsize {
+        ws_row: PTY_ROWS,
+        ws_col: PTY_COLS,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
 
     let ret = unsafe {
         libc::openpty(
@@ -33,7 +51,7 @@ pub fn openpty() -> std::io::Result<PtyPair> {
             &mut slave_fd,
             std::ptr::null_mut(),
             std::ptr::null_mut(),
-            std::ptr::null_mut(),
+            &raw mut winsize,
         )
     };
 
@@ -41,8 +59,40 @@ pub fn openpty() -> std::io::Result<PtyPair> {
         return Err(std::io::Error::last_os_error());
     }
 
-    Ok(PtyPair {
+    let pair = PtyPair {
         master: unsafe { OwnedFd::from_raw_fd(master_fd) },
         slave: unsafe { OwnedFd::from_raw_fd(slave_fd) },
-    })
+    };
+    set_cloexec(&pair.master)?;
+    set_cloexec(&pair.slave)?;
+    Ok(pair)
+}
+
+fn set_cloexec(fd: &OwnedFd) -> std::io::Result<()> {
+    let ret = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openpty_sets_window_size_and_cloexec() {
+        let pair = openpty().unwrap();
+
+        let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+        let ret = unsafe { libc::ioctl(pair.master.as_raw_fd(), libc::TIOCGWINSZ, &mut ws) };
+        assert_eq!(ret, 0);
+        assert_eq!(ws.ws_col, PTY_COLS);
+        assert_eq!(ws.ws_row, PTY_ROWS);
+
+        for fd in [&pair.master, &pair.slave] {
+            let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) };
+            assert!(flags & libc::FD_CLOEXEC != 0);
+        }
+    }
 }
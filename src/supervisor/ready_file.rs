@@ -0,0 +1,128 @@
+//! Log file readiness (`ready_log_file`)
+//!
+//! For daemons that write their own log files instead of stdout, readiness is
+//! detected by tailing the file and matching new lines against a pattern. The
+//! tail starts at the file's length when the daemon is spawned, so lines left
+//! over from a previous run cannot mark the new one ready.
+
+use regex::Regex;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Longest unterminated line kept between polls before it is matched as-is
+const MAX_PARTIAL_LINE: usize = 64 * 1024;
+
+pub(crate) struct LogFileTail {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl LogFileTail {
+    /// Start tailing `path` from its current end, or from the beginning once
+    /// it is created.
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            path,
+            offset,
+            partial: Vec::new(),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Read lines appended since the last poll and report whether any of them
+    /// matches `pattern`.
+    pub(crate) async fn poll(&mut self, pattern: &Regex) -> bool {
+        let Ok(mut file) = tokio::fs::File::open(&self.path).await else {
+            return false;
+        };
+        let Ok(len) = file.metadata().await.map(|m| m.len()) else {
+            return false;
+        };
+        if len < self.offset {
+            // Truncated or rotated: start over from the top
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset || file.seek(SeekFrom::Start(self.offset)).await.is_err() {
+            return false;
+        }
+        let mut buf = Vec::new();
+        let Ok(n) = file.take(len - self.offset).read_to_end(&mut buf).await else {
+            return false;
+        };
+        self.offset += n as u64;
+        self.partial.extend_from_slice(&buf);
+
+        let complete: Vec<u8> = match self.partial.iter().rposition(|&b| b == b'\n') {
+            Some(end) => self.partial.drain(..=end).collect(),
+            None if self.partial.len() > MAX_PARTIAL_LINE => std::mem::take(&mut self.partial),
+            None => return false,
+        };
+        String::from_utf8_lossy(&complete)
+            .lines()
+            .any(|line| pattern.is_match(&console::strip_ansi_codes(line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &std::path::Path, s: &str) {
+        let mut f = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        f.write_all(s.as_bytes()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ignores_existing_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server.log");
+        append(&path, "Listening on 8080\n");
+        let re = Regex::new("Listening on").unwrap();
+
+        let mut tail = LogFileTail::new(path.clone());
+        assert!(!tail.poll(&re).await);
+        append(&path, "booting\n");
+        assert!(!tail.poll(&re).await);
+        append(&path, "Listening on 8080\n");
+        assert!(tail.poll(&re).await);
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_file_and_complete_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("server.log");
+        let re = Regex::new("^ready$").unwrap();
+
+        let mut tail = LogFileTail::new(path.clone());
+        assert!(!tail.poll(&re).await);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        append(&path, "rea");
+        assert!(!tail.poll(&re).await);
+        append(&path, "dy\n");
+        assert!(tail.poll(&re).await);
+    }
+
+    #[tokio::test]
+    async fn test_restarts_after_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server.log");
+        append(&path, "old line that is fairly long\n");
+        let re = Regex::new("ready").unwrap();
+
+        let mut tail = LogFileTail::new(path.clone());
+        std::fs::write(&path, "ready\n").unwrap();
+        assert!(tail.poll(&re).await);
+    }
+}
@@ -0,0 +1,149 @@
+//! External service preconditions (`requires`)
+//!
+//! Before a daemon with `requires` is spawned, each listed TCP address or HTTP
+//! URL is polled until it answers. Meanwhile the daemon shows as `waiting`
+//! with `waiting_for` naming the service, and it fails if the service is still
+//! unreachable when that requirement's timeout runs out.
+
+use super::Supervisor;
+use crate::Result;
+use crate::daemon::RunOptions;
+use crate::daemon_status::DaemonStatus;
+use crate::pitchfork_toml::{Requirement, RequirementTarget};
+use crate::settings::settings;
+use crate::supervisor::state::UpsertDaemonOpts;
+use std::time::Duration;
+use tokio::time;
+
+/// Result of waiting for a daemon's requirements
+#[derive(Debug)]
+pub(crate) enum Requirements {
+    Met,
+    /// A requirement stayed unreachable; the message says which
+    Unreachable(String),
+    /// The daemon was stopped while waiting
+    Cancelled(String),
+}
+
+impl Supervisor {
+    /// Wait until every service in `opts.requires` is reachable, checking
+    /// them in order.
+    pub(crate) async fn wait_for_requirements(&self, opts: &RunOptions) -> Result<Requirements> {
+        if opts.requires.is_empty() {
+            return Ok(Requirements::Met);
+        }
+        let id = &opts.id;
+        let s = settings();
+        let interval = s.supervisor_ready_check_interval();
+        let probe_timeout = s.supervisor_http_client_timeout();
+        let client = reqwest::Client::builder()
+            .timeout(probe_timeout)
+            .build()
+            .unwrap_or_default();
+
+        for req in &opts.requires {
+            if is_reachable(req, &client, probe_timeout).await {
+                continue;
+            }
+            info!("daemon {id} waiting for {req}");
+            self.upsert_daemon(
+                UpsertDaemonOpts::from_run_options(opts, DaemonStatus::Waiting)
+                    .set(|o| o.waiting_for = Some(req.to_string()))
+                    .build(),
+            )
+            .await?;
+            let deadline = time::Instant::now() + req.timeout();
+            loop {
+                if time::Instant::now() >= deadline {
+                    let msg = format!(
+                        "{req} not reachable after {}",
+                        humantime::format_duration(req.timeout())
+                    );
+                    warn!("daemon {id}: {msg}");
+                    return Ok(Requirements::Unreachable(msg));
+                }
+                time::sleep(interval.min(deadline - time::Instant::now())).await;
+                // `pitchfork stop` clears waiting_for to abort the wait
+                if self
+                    .get_daemon(id)
+                    .await
+                    .is_none_or(|d| d.waiting_for.is_none())
+                {
+                    info!("daemon {id} stopped while waiting for {req}");
+                    return Ok(Requirements::Cancelled(format!(
+                        "stopped while waiting for {req}"
+                    )));
+                }
+                if is_reachable(req, &client, probe_timeout).await {
+                    info!("daemon {id}: {req} is reachable");
+                    break;
+                }
+            }
+        }
+        Ok(Requirements::Met)
+    }
+
+    /// Waits don't survive a supervisor restart; reset daemons the previous
+    /// supervisor was still waiting on so they can be started again.
+    pub(crate) async fn clear_stale_requirement_waits(&self) -> Result<()> {
+        let stale: Vec<_> = {
+            let state = self.state_file.lock().await;
+            state
+                .daemons
+                .values()
+                .filter(|d| d.waiting_for.is_some())
+                .map(|d| d.id.clone())
+                .collect()
+        };
+        for id in stale {
+            info!("daemon {id} was waiting for a requirement when the supervisor exited");
+            self.upsert_daemon(
+                UpsertDaemonOpts::builder(id)
+                    .set(|o| o.status = DaemonStatus::Stopped)
+                    .build(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+async fn is_reachable(req: &Requirement, client: &reqwest::Client, timeout: Duration) -> bool {
+    match &req.target {
+        RequirementTarget::Tcp(addr) => matches!(
+            time::timeout(timeout, tokio::net::TcpStream::connect(addr.as_str())).await,
+            Ok(Ok(_))
+        ),
+        // Any answer short of a server error means the service is up; a 404
+        // or 401 still proves something is listening.
+        RequirementTarget::Http(url) => client
+            .get(url)
+            .send()
+            .await
+            .is_ok_and(|rsp| !rsp.status().is_server_error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tcp(addr: String) -> Requirement {
+        Requirement {
+            target: RequirementTarget::Tcp(addr),
+            timeout: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_reachable() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let client = reqwest::Client::new();
+        let timeout = Duration::from_secs(1);
+        assert!(is_reachable(&tcp(addr.clone()), &client, timeout).await);
+        drop(listener);
+        assert!(!is_reachable(&tcp(addr), &client, timeout).await);
+    }
+}
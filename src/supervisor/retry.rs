@@ -4,26 +4,45 @@
 
 use super::Supervisor;
 use super::hooks::{HookType, fire_hook};
+use crate::daemon::Daemon;
 use crate::daemon_id::DaemonId;
+use crate::ipc::IpcResponse;
 use crate::supervisor::state::UpsertDaemonOpts;
 use crate::{Result, env};
+use chrono::{DateTime, Local};
+use miette::bail;
+use std::time::Duration;
+
+/// Longest wait between retry attempts
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(3600);
+
+/// How long to wait before retrying after failed attempt number `attempt`
+/// (0-based): 1s, 2s, 4s, ... capped at [`MAX_RETRY_BACKOFF`].
+pub(super) fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_secs(2u64.saturating_pow(attempt)).min(MAX_RETRY_BACKOFF)
+}
+
+/// Whether the retry watcher should retry `daemon` at `now`: it is errored,
+/// not running, has retries remaining and its backoff has elapsed.
+fn retry_due(daemon: &Daemon, now: DateTime<Local>) -> bool {
+    daemon.retry_pending() && daemon.next_retry_at.is_none_or(|at| at <= now)
+}
 
 impl Supervisor {
     /// Check for daemons that need retrying and attempt to restart them
     pub(crate) async fn check_retry(&self) -> Result<()> {
+        // Retries left pending while paused resume afterwards
+        if self.state_file.lock().await.paused {
+            return Ok(());
+        }
+        let now = self.clock.now();
         // Collect only IDs of daemons that need retrying (avoids cloning entire Daemon structs)
         let ids_to_retry: Vec<DaemonId> = {
             let state_file = self.state_file.lock().await;
             state_file
                 .daemons
                 .iter()
-                .filter(|(_id, d)| {
-                    // Daemon is errored, not currently running, and has retries remaining
-                    d.status.is_errored()
-                        && d.pid.is_none()
-                        && d.retry.count() > 0
-                        && d.retry_count < d.retry.count()
-                })
+                .filter(|(_id, d)| retry_due(d, now))
                 .map(|(id, _d)| id.clone())
                 .collect()
         };
@@ -34,59 +53,163 @@ impl Supervisor {
             let daemon = {
                 let state_file = self.state_file.lock().await;
                 match state_file.daemons.get(&id) {
-                    Some(d)
-                        if d.status.is_errored()
-                            && d.pid.is_none()
-                            && d.retry.count() > 0
-                            && d.retry_count < d.retry.count() =>
-                    {
-                        d.clone()
-                    }
+                    Some(d) if retry_due(d, now) => d.clone(),
                     _ => continue, // Daemon was removed or no longer needs retry
                 }
             };
-            info!(
-                "retrying daemon {} ({}/{} attempts)",
-                id,
-                daemon.retry_count + 1,
-                daemon.retry.count()
-            );
-
-            // Use the persisted command from daemon state
-            let cmd = match daemon.cmd.clone() {
-                Some(cmd) => cmd,
-                None => {
-                    warn!("no run command found in state for daemon {id}, cannot retry");
-                    // Mark as exhausted to prevent infinite retry loop, preserving error status
-                    self.upsert_daemon(
-                        UpsertDaemonOpts::builder(id)
-                            .set(|o| {
-                                o.status = daemon.status.clone();
-                                o.retry_count = Some(daemon.retry.count());
-                            })
-                            .build(),
-                    )
-                    .await?;
-                    continue;
-                }
-            };
-            let dir = daemon.dir.clone().unwrap_or_else(|| env::CWD.clone());
-            fire_hook(
-                HookType::OnRetry,
-                id.clone(),
-                dir.clone(),
-                daemon.retry_count + 1,
-                daemon.env.clone(),
-                vec![],
-            )
-            .await;
-            let mut retry_opts = daemon.to_run_options(cmd);
-            retry_opts.retry_count = daemon.retry_count + 1;
-            if let Err(e) = self.run(retry_opts).await {
+            if let Err(e) = self.retry_daemon(id.clone(), daemon).await {
                 error!("failed to retry daemon {id}: {e}");
             }
         }
 
         Ok(())
     }
+
+    /// Retry an errored or failed daemon right away, skipping the retry
+    /// watcher's interval and backoff (`pitchfork retry`).
+    pub(crate) async fn retry_now(&self, id: &DaemonId) -> Result<IpcResponse> {
+        let Some(daemon) = self.get_daemon(id).await else {
+            bail!("daemon '{id}' not found");
+        };
+        if daemon.pid.is_some() && !daemon.status.is_stopped() {
+            return Ok(IpcResponse::DaemonAlreadyRunning);
+        }
+        if !daemon.status.is_errored() && !daemon.status.is_failed() {
+            bail!("daemon '{id}' has not failed; use `pitchfork start` to start it");
+        }
+        self.retry_daemon(id.clone(), daemon).await
+    }
+
+    /// Clear pending retries of an errored daemon, leaving it errored.
+    /// Returns false if no retries were pending.
+    pub(crate) async fn cancel_retry(&self, id: &DaemonId) -> Result<bool> {
+        let Some(daemon) = self.get_daemon(id).await else {
+            bail!("daemon '{id}' not found");
+        };
+        if !daemon.retry_pending() {
+            return Ok(false);
+        }
+        info!("cancelling pending retries of daemon {id}");
+        self.upsert_daemon(
+            UpsertDaemonOpts::builder(id.clone())
+                .set(|o| {
+                    o.status = daemon.status.clone();
+                    o.retry_count = Some(daemon.retry.count());
+                })
+                .build(),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Start the next retry attempt of `daemon` from its persisted command.
+    async fn retry_daemon(&self, id: DaemonId, daemon: Daemon) -> Result<IpcResponse> {
+        info!(
+            "retrying daemon {} ({}/{} attempts)",
+            id,
+            daemon.retry_count + 1,
+            daemon.retry.count()
+        );
+
+        // Use the persisted command from daemon state
+        let cmd = match daemon.cmd.clone() {
+            Some(cmd) => cmd,
+            None => {
+                // Mark as exhausted to prevent infinite retry loop, preserving error status
+                self.upsert_daemon(
+                    UpsertDaemonOpts::builder(id.clone())
+                        .set(|o| {
+                            o.status = daemon.status.clone();
+                            o.retry_count = Some(daemon.retry.count());
+                        })
+                        .build(),
+                )
+                .await?;
+                bail!("no run command found in state for daemon {id}, cannot retry");
+            }
+        };
+        let dir = daemon.dir.clone().unwrap_or_else(|| env::CWD.clone());
+        fire_hook(
+            HookType::OnRetry,
+            id.clone(),
+            dir.clone(),
+            daemon.retry_count + 1,
+            daemon.effective_env(),
+            vec![],
+        )
+        .await;
+        let mut retry_opts = daemon.to_run_options(cmd);
+        retry_opts.retry_count = daemon.retry_count + 1;
+        self.run(retry_opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::clock::VirtualClock;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_retry_backoff() {
+        let secs = Duration::from_secs;
+        assert_eq!(retry_backoff(0), secs(1));
+        assert_eq!(retry_backoff(3), secs(8));
+        assert_eq!(retry_backoff(12), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_backoff(u32::MAX), MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn test_retry_due_waits_for_backoff() {
+        let now = chrono::Local.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut daemon = Daemon {
+            status: crate::daemon_status::DaemonStatus::Errored(1),
+            retry: crate::config_types::Retry(3),
+            retry_count: 1,
+            next_retry_at: Some(now + retry_backoff(1)),
+            ..Default::default()
+        };
+        assert!(!retry_due(&daemon, now));
+        assert!(retry_due(&daemon, now + retry_backoff(1)));
+
+        // Entries written before next_retry_at existed retry right away
+        daemon.next_retry_at = None;
+        assert!(retry_due(&daemon, now));
+
+        daemon.retry_count = 3;
+        assert!(!retry_due(&daemon, now));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_check_retry_waits_for_backoff_in_virtual_time() {
+        let temp = tempfile::tempdir().unwrap();
+        let start = chrono::Local.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let supervisor = Supervisor::with_state_file(crate::state_file::StateFile::new(
+            temp.path().join("state.toml"),
+        ))
+        .with_clock(VirtualClock::new(start));
+        // Without a command, a retry attempt only marks the retries as used up
+        let id = DaemonId::new("proj", "api");
+        let daemon = Daemon {
+            id: id.clone(),
+            status: crate::daemon_status::DaemonStatus::Errored(1),
+            retry: crate::config_types::Retry(3),
+            retry_count: 1,
+            next_retry_at: Some(start + retry_backoff(1)),
+            ..Default::default()
+        };
+        supervisor
+            .state_file
+            .lock()
+            .await
+            .daemons
+            .insert(id.clone(), daemon);
+
+        supervisor.check_retry().await.unwrap();
+        assert_eq!(supervisor.get_daemon(&id).await.unwrap().retry_count, 1);
+
+        supervisor.clock.sleep(retry_backoff(1)).await;
+        supervisor.check_retry().await.unwrap();
+        assert_eq!(supervisor.get_daemon(&id).await.unwrap().retry_count, 3);
+    }
 }
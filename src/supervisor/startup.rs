@@ -0,0 +1,133 @@
+//! Startup handshake between a client that spawns the supervisor and the
+//! supervisor itself.
+//!
+//! The client picks a marker file in the temp dir and passes it to
+//! `supervisor run --startup-marker`. The supervisor writes `starting` to it
+//! right away, then `ready` once its IPC socket is bound or `failed: <reason>`
+//! if startup fails. A client waiting for the socket checks the marker, so it
+//! can report why the supervisor didn't come up instead of timing out. The
+//! marker lives in the temp dir because an unwritable state dir is one of the
+//! failures to report.
+
+use crate::Result;
+use crate::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The marker this process writes (supervisor) or reads (client)
+static MARKER: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StartupState {
+    Starting,
+    Ready,
+    Failed(String),
+}
+
+impl StartupState {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim_end() {
+            "starting" => Some(Self::Starting),
+            "ready" => Some(Self::Ready),
+            s => s
+                .strip_prefix("failed: ")
+                .map(|reason| Self::Failed(reason.to_string())),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            Self::Starting => "starting\n".to_string(),
+            Self::Ready => "ready\n".to_string(),
+            // Keep the marker to one line
+            Self::Failed(reason) => format!("failed: {}\n", reason.replace('\n', " ")),
+        }
+    }
+}
+
+/// Client side: a fresh marker path for a supervisor about to be spawned
+pub(crate) fn new_marker() -> PathBuf {
+    let path = MARKER
+        .get_or_init(|| {
+            std::env::temp_dir().join(format!("pitchfork-startup-{}.status", std::process::id()))
+        })
+        .clone();
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Supervisor side: the marker passed by the client that spawned us
+pub(crate) fn set_marker(path: &Path) {
+    let _ = MARKER.set(path.to_path_buf());
+}
+
+/// Supervisor side: record how startup is going
+pub(crate) fn mark(state: StartupState) {
+    let Some(path) = MARKER.get() else {
+        return;
+    };
+    // Only `starting` creates the marker. Once the client has connected and
+    // removed it, later errors are not startup failures.
+    let line = state.to_line();
+    let result = if state == StartupState::Starting {
+        std::fs::write(path, line)
+    } else {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+    };
+    if let Err(e) = result {
+        debug!("failed to write startup marker {}: {e}", path.display());
+    }
+}
+
+/// Client side: why the supervisor this process spawned failed to start
+pub(crate) fn failure() -> Option<String> {
+    let raw = std::fs::read_to_string(MARKER.get()?).ok()?;
+    match StartupState::parse(&raw)? {
+        StartupState::Failed(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+/// Client side: remove the marker once the supervisor is reachable
+pub(crate) fn clear() {
+    if let Some(path) = MARKER.get() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Checks the state dir can be written before anything else touches it, so
+/// the failure names the directory instead of a file deep inside it
+pub(crate) fn self_check() -> Result<()> {
+    let dir = &*env::PITCHFORK_STATE_DIR;
+    let probe = dir.join(format!(".startup-check-{}", std::process::id()));
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| miette::miette!("{} on {}", e.kind(), dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_roundtrip() {
+        for state in [
+            StartupState::Starting,
+            StartupState::Ready,
+            StartupState::Failed("permission denied on /var/lib/pitchfork".to_string()),
+        ] {
+            assert_eq!(StartupState::parse(&state.to_line()), Some(state));
+        }
+        assert_eq!(
+            StartupState::parse(&StartupState::Failed("a\nb".to_string()).to_line()),
+            Some(StartupState::Failed("a b".to_string()))
+        );
+        assert_eq!(StartupState::parse("garbage"), None);
+    }
+}
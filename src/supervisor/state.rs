@@ -3,25 +3,36 @@
 //! All state getter/setter operations for daemons, shell directories, and notifications.
 
 use super::Supervisor;
+use super::events;
+use super::users;
 use crate::Result;
 use crate::daemon::Daemon;
 use crate::daemon::RunOptions;
 use crate::daemon_id::DaemonId;
 use crate::daemon_status::DaemonStatus;
+use crate::log_store::sqlite::LOG_STORE;
+use crate::pitchfork_toml::Alerts;
 use crate::pitchfork_toml::CpuLimit;
 use crate::pitchfork_toml::CronRetrigger;
+use crate::pitchfork_toml::DaemonKind;
 use crate::pitchfork_toml::MemoryLimit;
 use crate::pitchfork_toml::PitchforkToml;
 use crate::pitchfork_toml::PortConfig;
 use crate::pitchfork_toml::ReadyCmd;
 use crate::pitchfork_toml::ReadyHttp;
+use crate::pitchfork_toml::ReadyLogFile;
 use crate::pitchfork_toml::ReadyOutput;
 use crate::pitchfork_toml::ReadyPort;
+use crate::pitchfork_toml::ReadyWhen;
+use crate::pitchfork_toml::Requirement;
+use crate::pitchfork_toml::ResourceLimits;
 use crate::pitchfork_toml::Retry;
 use crate::pitchfork_toml::StopConfig;
+use crate::pitchfork_toml::Umask;
 use crate::pitchfork_toml::WatchMode;
 use crate::procs::PROCS;
 use indexmap::IndexMap;
+use miette::IntoDiagnostic;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
@@ -37,18 +48,30 @@ pub(crate) struct UpsertDaemonOpts {
     pub dir: Option<PathBuf>,
     pub cmd: Option<Vec<String>>,
     pub run: Option<String>,
+    pub kind: Option<DaemonKind>,
     pub autostop: bool,
     pub cron_schedule: Option<String>,
     pub cron_retrigger: Option<CronRetrigger>,
     pub cron_immediate: Option<bool>,
+    pub cron_exclusive: Option<bool>,
     pub last_exit_success: Option<bool>,
     pub retry: Option<Retry>,
     pub retry_count: Option<u32>,
-    pub ready_delay: Option<u64>,
+    /// Next background retry of an errored daemon; not inherited from the
+    /// existing entry.
+    pub next_retry_at: Option<chrono::DateTime<chrono::Local>>,
+    pub success_exit_codes: Option<Vec<i32>>,
+    pub no_retry_exit_codes: Option<Vec<i32>>,
+    pub ready_delay: Option<std::time::Duration>,
     pub ready_output: Option<ReadyOutput>,
     pub ready_http: Option<ReadyHttp>,
     pub ready_port: Option<ReadyPort>,
     pub ready_cmd: Option<ReadyCmd>,
+    pub ready_log_file: Option<ReadyLogFile>,
+    pub ready_unix_socket: Option<String>,
+    pub ready_when: Option<ReadyWhen>,
+    pub fail_output: Option<String>,
+    pub expect_long_running: Option<bool>,
     /// Port configuration
     pub port: Option<PortConfig>,
     /// Resolved ports actually used after auto-bump (may differ from expected)
@@ -60,25 +83,56 @@ pub(crate) struct UpsertDaemonOpts {
     /// Whether to proxy this daemon (None = use global proxy.enable setting).
     pub proxy: Option<bool>,
     pub depends: Option<Vec<DaemonId>>,
+    pub requires: Option<Vec<Requirement>>,
+    /// Requirement being waited on; not inherited from the existing entry.
+    pub waiting_for: Option<String>,
     pub env: Option<IndexMap<String, String>>,
+    /// One-time env override; `None` keeps the existing one, `Some` replaces it
+    /// (with `Some(None)` clearing it when a run starts without `--env`).
+    pub env_override: Option<Option<IndexMap<String, String>>>,
+    /// Expiry of `env_override`; only applied when `env_override` is `Some`.
+    pub env_override_until: Option<chrono::DateTime<chrono::Local>>,
     pub watch: Option<Vec<String>>,
     pub watch_mode: Option<WatchMode>,
     pub watch_base_dir: Option<PathBuf>,
     pub mise: Option<bool>,
     /// Unix user to run this daemon as
     pub user: Option<String>,
+    /// User who started the daemon on a shared supervisor
+    pub owner: Option<String>,
     /// Memory limit for the daemon process
     pub memory_limit: Option<MemoryLimit>,
     /// CPU usage limit as a percentage
     pub cpu_limit: Option<CpuLimit>,
+    /// Resource usage thresholds that raise an alert
+    pub alerts: Option<Alerts>,
     /// Unix signal to send for graceful shutdown
     pub stop_signal: Option<StopConfig>,
+    /// File mode creation mask
+    pub umask: Option<Umask>,
+    /// Resource limits
+    pub limits: Option<ResourceLimits>,
     /// Archive hook command invoked before retention prunes this daemon's logs.
     pub archive_hook: Option<String>,
     /// Log format for this daemon.
     pub log_format: Option<String>,
+    /// Regex classifying the level of lines the log format doesn't.
+    pub log_level_regex: Option<String>,
+    /// Patterns for output lines dropped before they are logged.
+    pub log_filter: Option<Vec<String>>,
+    pub log_encoding: Option<crate::config_types::LogEncoding>,
     /// Allocate a pseudo-terminal for the daemon process.
     pub pty: Option<bool>,
+    /// Run the daemon in its own network namespace.
+    pub isolate_network: Option<bool>,
+    /// Restart the daemon when its config entry changes.
+    pub restart_on_config_change: Option<bool>,
+    /// Restart the daemon when the executable it runs changes.
+    pub restart_on_binary_change: Option<bool>,
+    /// Restart the daemon when one of its `depends` is restarted.
+    pub restart_with_dependencies: Option<bool>,
+    /// Keep the daemon's state entry when `pitchfork clean` runs.
+    pub sticky: Option<bool>,
     /// True for config-only cron daemons auto-registered into state.
     pub config_registered: bool,
 }
@@ -123,31 +177,55 @@ impl UpsertDaemonOpts {
             o.dir = Some(opts.dir.0.clone());
             o.cmd = Some(opts.cmd.clone());
             o.run = opts.run.clone();
+            o.kind = Some(opts.kind);
             o.autostop = opts.autostop;
             o.cron_schedule = opts.cron_schedule.clone();
             o.cron_retrigger = opts.cron_retrigger;
             o.cron_immediate = opts.cron_immediate;
+            o.cron_exclusive = opts.cron_exclusive;
             o.retry = Some(opts.retry);
             o.retry_count = Some(opts.retry_count);
+            o.success_exit_codes = Some(opts.success_exit_codes.clone());
+            o.no_retry_exit_codes = Some(opts.no_retry_exit_codes.clone());
             o.ready_delay = opts.ready_delay;
             o.ready_output = opts.ready_output.clone();
             o.ready_http = opts.ready_http.clone();
             o.ready_port = opts.ready_port.clone();
             o.ready_cmd = opts.ready_cmd.clone();
+            o.ready_log_file = opts.ready_log_file.clone();
+            o.ready_unix_socket = opts.ready_unix_socket.clone();
+            o.ready_when = opts.ready_when.clone();
+            o.fail_output = opts.fail_output.clone();
+            o.expect_long_running = opts.expect_long_running;
             o.port = opts.port.clone();
             o.depends = Some(opts.depends.clone());
+            o.requires = Some(opts.requires.clone());
             o.env = opts.env.clone();
+            o.env_override = Some(opts.env_override.clone());
+            o.env_override_until = opts.env_override_until;
             o.watch = Some(opts.watch.clone());
             o.watch_mode = Some(opts.watch_mode);
             o.watch_base_dir = opts.watch_base_dir.clone();
             o.mise = opts.mise;
             o.user = opts.user.clone();
+            o.owner = opts.owner.clone();
             o.memory_limit = opts.memory_limit;
             o.cpu_limit = opts.cpu_limit;
+            o.alerts = opts.alerts;
             o.stop_signal = opts.stop_signal;
+            o.umask = opts.umask;
+            o.limits = opts.limits;
             o.pty = opts.pty;
+            o.isolate_network = opts.isolate_network;
+            o.restart_on_config_change = opts.restart_on_config_change;
+            o.restart_on_binary_change = opts.restart_on_binary_change;
+            o.restart_with_dependencies = opts.restart_with_dependencies;
+            o.sticky = opts.sticky;
             o.archive_hook = opts.archive_hook.clone();
             o.log_format = opts.log_format.clone();
+            o.log_level_regex = opts.log_level_regex.clone();
+            o.log_filter = Some(opts.log_filter.clone());
+            o.log_encoding = opts.log_encoding;
         })
     }
 }
@@ -176,6 +254,13 @@ impl Supervisor {
         );
         let mut state_file = self.state_file.lock().await;
         let existing = state_file.daemons.get(&opts.id);
+        let previous_status = existing.map(|d| d.status.clone());
+        let (env_override, env_override_until) = match opts.env_override {
+            Some(env_override) => (env_override, opts.env_override_until),
+            None => existing
+                .map(|d| (d.env_override.clone(), d.env_override_until))
+                .unwrap_or_default(),
+        };
         let daemon = Daemon {
             id: opts.id.clone(),
             // title/start_time identify the process for orphan cleanup after a
@@ -198,12 +283,16 @@ impl Supervisor {
                 })
             }),
             pid: opts.pid,
+            host: opts.pid.map(|_| crate::env::HOSTNAME.clone()),
             status: opts.status,
             shell_pid: opts.shell_pid,
             autostop: opts.autostop || existing.is_some_and(|d| d.autostop),
             dir: opts.dir.or(existing.and_then(|d| d.dir.clone())),
             cmd: opts.cmd.or(existing.and_then(|d| d.cmd.clone())),
             run: opts.run.or(existing.and_then(|d| d.run.clone())),
+            kind: opts
+                .kind
+                .unwrap_or_else(|| existing.map(|d| d.kind).unwrap_or_default()),
             cron_schedule: opts
                 .cron_schedule
                 .or(existing.and_then(|d| d.cron_schedule.clone())),
@@ -213,7 +302,12 @@ impl Supervisor {
             cron_immediate: opts
                 .cron_immediate
                 .or(existing.and_then(|d| d.cron_immediate)),
+            cron_exclusive: opts
+                .cron_exclusive
+                .or(existing.and_then(|d| d.cron_exclusive)),
             last_cron_triggered: existing.and_then(|d| d.last_cron_triggered),
+            exits: existing.map(|d| d.exits.clone()).unwrap_or_default(),
+            startups: existing.map(|d| d.startups.clone()).unwrap_or_default(),
             last_exit_success: opts
                 .last_exit_success
                 .or(existing.and_then(|d| d.last_exit_success)),
@@ -223,6 +317,17 @@ impl Supervisor {
             retry_count: opts
                 .retry_count
                 .unwrap_or(existing.map(|d| d.retry_count).unwrap_or(0)),
+            next_retry_at: opts.next_retry_at,
+            success_exit_codes: opts.success_exit_codes.unwrap_or_else(|| {
+                existing
+                    .map(|d| d.success_exit_codes.clone())
+                    .unwrap_or_default()
+            }),
+            no_retry_exit_codes: opts.no_retry_exit_codes.unwrap_or_else(|| {
+                existing
+                    .map(|d| d.no_retry_exit_codes.clone())
+                    .unwrap_or_default()
+            }),
             ready_delay: opts.ready_delay.or(existing.and_then(|d| d.ready_delay)),
             ready_output: opts
                 .ready_output
@@ -236,6 +341,21 @@ impl Supervisor {
             ready_cmd: opts
                 .ready_cmd
                 .or(existing.and_then(|d| d.ready_cmd.clone())),
+            ready_log_file: opts
+                .ready_log_file
+                .or(existing.and_then(|d| d.ready_log_file.clone())),
+            ready_unix_socket: opts
+                .ready_unix_socket
+                .or(existing.and_then(|d| d.ready_unix_socket.clone())),
+            ready_when: opts
+                .ready_when
+                .or(existing.and_then(|d| d.ready_when.clone())),
+            fail_output: opts
+                .fail_output
+                .or(existing.and_then(|d| d.fail_output.clone())),
+            expect_long_running: opts
+                .expect_long_running
+                .or(existing.and_then(|d| d.expect_long_running)),
             port: opts.port.or_else(|| existing.and_then(|d| d.port.clone())),
             resolved_port: if opts.resolved_port.is_empty() {
                 existing
@@ -247,7 +367,13 @@ impl Supervisor {
             depends: opts
                 .depends
                 .unwrap_or_else(|| existing.map(|d| d.depends.clone()).unwrap_or_default()),
+            requires: opts
+                .requires
+                .unwrap_or_else(|| existing.map(|d| d.requires.clone()).unwrap_or_default()),
+            waiting_for: opts.waiting_for,
             env: opts.env.or(existing.and_then(|d| d.env.clone())),
+            env_override,
+            env_override_until,
             watch: opts
                 .watch
                 .unwrap_or_else(|| existing.map(|d| d.watch.clone()).unwrap_or_default()),
@@ -259,6 +385,7 @@ impl Supervisor {
                 .or(existing.and_then(|d| d.watch_base_dir.clone())),
             mise: opts.mise.or(existing.and_then(|d| d.mise)),
             user: opts.user.or(existing.and_then(|d| d.user.clone())),
+            owner: opts.owner.or(existing.and_then(|d| d.owner.clone())),
             proxy: opts.proxy.or(existing.and_then(|d| d.proxy)),
             // active_port is intentionally NOT inherited from the existing daemon.
             // When a daemon restarts, the new process has not yet bound a port, so
@@ -269,17 +396,53 @@ impl Supervisor {
             slug: opts.slug.or(existing.and_then(|d| d.slug.clone())),
             memory_limit: opts.memory_limit.or(existing.and_then(|d| d.memory_limit)),
             cpu_limit: opts.cpu_limit.or(existing.and_then(|d| d.cpu_limit)),
+            alerts: opts.alerts.or(existing.and_then(|d| d.alerts)),
             stop_signal: opts.stop_signal.or(existing.and_then(|d| d.stop_signal)),
+            umask: opts.umask.or(existing.and_then(|d| d.umask)),
+            limits: opts.limits.or(existing.and_then(|d| d.limits)),
             archive_hook: opts
                 .archive_hook
                 .or(existing.and_then(|d| d.archive_hook.clone())),
             log_format: opts
                 .log_format
                 .or(existing.and_then(|d| d.log_format.clone())),
+            log_level_regex: opts
+                .log_level_regex
+                .or(existing.and_then(|d| d.log_level_regex.clone())),
+            log_filter: opts
+                .log_filter
+                .or(existing.map(|d| d.log_filter.clone()))
+                .unwrap_or_default(),
+            log_encoding: opts.log_encoding.or(existing.and_then(|d| d.log_encoding)),
+            log_lines_filtered: existing.map(|d| d.log_lines_filtered).unwrap_or_default(),
+            log_lines_dropped: existing.map(|d| d.log_lines_dropped).unwrap_or_default(),
+            // An alert belongs to the process that breached the threshold
+            alert: existing
+                .filter(|d| opts.pid.is_some() && d.pid == opts.pid)
+                .and_then(|d| d.alert.clone()),
+            // So does drift: a new process starts from what's on disk now
+            drift: existing
+                .filter(|d| opts.pid.is_some() && d.pid == opts.pid)
+                .and_then(|d| d.drift.clone()),
+            watch_fallback: existing.and_then(|d| d.watch_fallback.clone()),
             pty: opts.pty.or(existing.and_then(|d| d.pty)),
+            isolate_network: opts
+                .isolate_network
+                .or(existing.and_then(|d| d.isolate_network)),
+            restart_on_config_change: opts
+                .restart_on_config_change
+                .or(existing.and_then(|d| d.restart_on_config_change)),
+            restart_on_binary_change: opts
+                .restart_on_binary_change
+                .or(existing.and_then(|d| d.restart_on_binary_change)),
+            restart_with_dependencies: opts
+                .restart_with_dependencies
+                .or(existing.and_then(|d| d.restart_with_dependencies)),
+            sticky: opts.sticky.or(existing.and_then(|d| d.sticky)),
             config_registered: opts.config_registered,
         };
         state_file.insert_daemon(&opts.id, daemon.clone());
+        events::status_changed(&opts.id, previous_status.as_ref(), &daemon.status);
         Ok(daemon)
     }
 
@@ -296,19 +459,42 @@ impl Supervisor {
         Ok(result)
     }
 
-    /// Disable a daemon (add to disabled set)
-    pub async fn disable(&self, id: &DaemonId) -> Result<bool> {
-        info!("disabling daemon: {id}");
+    /// Disable a daemon (add to disabled set), or with `auto` only its
+    /// automatic starts (add to the auto-disabled set)
+    pub async fn disable(&self, id: &DaemonId, auto: bool) -> Result<bool> {
+        if auto {
+            info!("disabling autostart for daemon: {id}");
+        } else {
+            info!("disabling daemon: {id}");
+        }
         let config = PitchforkToml::all_merged_all_namespaces()?;
         let mut state_file = self.state_file.lock().await;
         let exists = state_file.daemons.contains_key(id) || config.daemons.contains_key(id);
         if !exists {
             return Err(miette::miette!("daemon '{}' not found", id));
         }
-        let result = state_file.disable_daemon(id);
+        let result = if auto {
+            state_file.disable_daemon_autostart(id)
+        } else {
+            state_file.disable_daemon(id)
+        };
         Ok(result)
     }
 
+    /// Pause or resume automatic starts and restarts (`pitchfork supervisor
+    /// pause`). Returns true if the supervisor wasn't already in that state.
+    pub async fn set_paused(&self, paused: bool) -> Result<bool> {
+        let changed = self.state_file.lock().await.set_paused(paused);
+        if changed {
+            if paused {
+                info!("pausing automatic starts and restarts");
+            } else {
+                info!("resuming automatic starts and restarts");
+            }
+        }
+        Ok(changed)
+    }
+
     /// Get a daemon by ID
     pub(crate) async fn get_daemon(&self, id: &DaemonId) -> Option<Daemon> {
         self.state_file.lock().await.daemons.get(id).cloned()
@@ -353,6 +539,8 @@ impl Supervisor {
 
     /// Remove a shell PID from tracking
     pub(crate) async fn remove_shell_pid(&self, shell_pid: u32) -> Result<()> {
+        // A new shell reusing the PID counts its changes from the start
+        self.shell_dir_seqs.lock().await.remove(&shell_pid);
         let mut state_file = self.state_file.lock().await;
         state_file.remove_shell_dir(shell_pid);
         Ok(())
@@ -371,18 +559,72 @@ impl Supervisor {
         )
     }
 
-    /// Get pending notifications and clear the queue
-    pub(crate) async fn get_notifications(&self) -> Vec<(log::LevelFilter, String)> {
-        self.pending_notifications.lock().await.drain(..).collect()
+    /// Take the pending notifications for `user` (all for `None`) off the
+    /// queue. Other users' notifications stay queued for them.
+    pub(crate) async fn get_notifications(
+        &self,
+        user: Option<&str>,
+    ) -> Vec<(log::LevelFilter, String)> {
+        let mut pending = self.pending_notifications.lock().await;
+        let (taken, kept) = std::mem::take(&mut *pending)
+            .into_iter()
+            .partition(|(owner, ..)| user.is_none() || owner.is_none() || owner.as_deref() == user);
+        *pending = kept;
+        taken
+            .into_iter()
+            .map(|(_, level, message)| (level, message))
+            .collect()
     }
 
-    /// Clean up daemons that have no PID
-    pub(crate) async fn clean(&self) -> Result<()> {
+    /// Clean up daemons that have no PID, except sticky ones and `keep`.
+    /// Only `user`'s own daemons are cleaned, or everyone's for `None`.
+    pub(crate) async fn clean(&self, keep: &[DaemonId], user: Option<&str>) -> Result<()> {
+        // A daemon marked sticky in config since its last run is kept too
+        let config_sticky: HashSet<DaemonId> = PitchforkToml::all_merged_all_namespaces()
+            .map(|pt| {
+                pt.daemons
+                    .into_iter()
+                    .filter(|(_, d)| d.sticky == Some(true))
+                    .map(|(id, _)| id)
+                    .collect()
+            })
+            .unwrap_or_default();
         let mut state_file = self.state_file.lock().await;
-        state_file.retain_daemons(|_id, d| d.pid.is_some());
+        state_file.retain_daemons(|id, d| {
+            d.pid.is_some()
+                || d.sticky == Some(true)
+                || config_sticky.contains(id)
+                || keep.contains(id)
+                || !users::owned_by(d, user)
+        });
         Ok(())
     }
 
+    /// Move a stopped daemon's state and logs to the ID it was renamed to.
+    /// Fails without moving either if the new ID already has state or logs.
+    pub(crate) async fn rename_daemon(&self, from: &DaemonId, to: &DaemonId) -> Result<()> {
+        {
+            let mut state_file = self.state_file.lock().await;
+            for id in [from, to] {
+                if state_file.daemons.get(id).is_some_and(|d| d.pid.is_some()) {
+                    miette::bail!("daemon {id} is running, stop it before renaming");
+                }
+            }
+            state_file.rename_daemon(from, to)?;
+        }
+        let (log_from, log_to) = (from.clone(), to.clone());
+        let renamed =
+            tokio::task::spawn_blocking(move || LOG_STORE.rename_daemon(&log_from, &log_to))
+                .await
+                .into_diagnostic()
+                .and_then(|renamed| renamed);
+        if renamed.is_err() {
+            // Nothing has the old ID's state now, so moving it back can't conflict
+            let _ = self.state_file.lock().await.rename_daemon(to, from);
+        }
+        renamed
+    }
+
     /// Return the union of active directories from shell tracking and project
     /// sessions. These are the directories that should keep auto-stop daemons
     /// alive.
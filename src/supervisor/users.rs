@@ -0,0 +1,318 @@
+//! Shared supervisors (`settings.supervisor.socket_group`)
+//!
+//! A supervisor started as root with a `socket_group` lets members of that
+//! group connect to its IPC socket. Each connection is identified by the
+//! socket's peer credentials; daemons started over it record the user as
+//! their `owner` in the state file and run as that user. Users only see and
+//! manage their own daemons, while root (and the supervisor's own user) sees
+//! everything and can pick one user's view with `pitchfork --user <name>`.
+
+use crate::Result;
+use crate::daemon::{Daemon, RunOptions};
+use crate::daemon_id::DaemonId;
+use crate::ipc::server::IpcPeer;
+use crate::settings::settings;
+use std::collections::BTreeMap;
+
+/// Whether the supervisor is shared between users
+pub(crate) fn shared() -> bool {
+    cfg!(unix) && !settings().supervisor.socket_group.trim().is_empty()
+}
+
+/// GID of `settings.supervisor.socket_group`, or `None` when the supervisor
+/// is not shared
+#[cfg(unix)]
+pub(crate) fn socket_group_id() -> Result<Option<u32>> {
+    let s = settings();
+    let group = s.supervisor.socket_group.trim();
+    if group.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(gid) = group.parse::<u32>() {
+        return Ok(Some(gid));
+    }
+    match nix::unistd::Group::from_name(group) {
+        Ok(Some(g)) => Ok(Some(g.gid.as_raw())),
+        Ok(None) => miette::bail!("supervisor.socket_group: no such group '{group}'"),
+        Err(err) => miette::bail!("supervisor.socket_group: failed to look up '{group}': {err}"),
+    }
+}
+
+/// The user behind an IPC connection to a shared supervisor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Caller {
+    pub name: String,
+    /// Root or the supervisor's own user: sees and manages every daemon
+    pub admin: bool,
+}
+
+impl Caller {
+    /// Identify the user behind a connection, or `None` when the supervisor
+    /// is not shared. A connection without peer credentials is treated as an
+    /// unknown, unprivileged user.
+    pub(crate) fn from_peer(peer: IpcPeer) -> Option<Self> {
+        if !shared() {
+            return None;
+        }
+        let Some(uid) = peer.uid else {
+            warn!("IPC connection without peer credentials on a shared supervisor");
+            return Some(Self {
+                name: "unknown".to_string(),
+                admin: false,
+            });
+        };
+        Some(Self {
+            name: user_name(uid),
+            admin: is_admin_uid(uid),
+        })
+    }
+
+    /// The user whose daemons this caller looks at: `user` from `--user`,
+    /// or `None` for all daemons (admins without `--user`).
+    pub(crate) fn view(&self, user: Option<&str>) -> Result<Option<String>> {
+        match user {
+            Some(user) if user != self.name && !self.admin => {
+                miette::bail!("only root can view the daemons of user '{user}'")
+            }
+            Some(user) => Ok(Some(user.to_string())),
+            None if self.admin => Ok(None),
+            None => Ok(Some(self.name.clone())),
+        }
+    }
+
+    /// Whether the caller may start, stop or otherwise act on a daemon.
+    /// A daemon without an owner can be claimed while it is not running.
+    pub(crate) fn may_manage(&self, daemon: Option<&Daemon>) -> bool {
+        if self.admin {
+            return true;
+        }
+        match daemon {
+            None => true,
+            Some(d) => match &d.owner {
+                Some(owner) => *owner == self.name,
+                None => d.pid.is_none(),
+            },
+        }
+    }
+
+    /// Record who a daemon is started for and run it as that user. An admin
+    /// restarting another user's daemon keeps it running as that user.
+    pub(crate) fn claim(&self, opts: &mut RunOptions, existing_owner: Option<String>) {
+        let owner = match existing_owner {
+            Some(owner) if self.admin => owner,
+            _ => self.name.clone(),
+        };
+        if !self.admin || owner != self.name {
+            opts.user = Some(owner.clone());
+        }
+        opts.owner = Some(owner);
+    }
+}
+
+/// Whether `daemon` is shown in the view of `user` (`None` for all daemons).
+/// Daemons without an owner, e.g. started at boot, are shown to everyone.
+pub(crate) fn visible_to(daemon: &Daemon, user: Option<&str>) -> bool {
+    match (user, &daemon.owner) {
+        (None, _) | (_, None) => true,
+        (Some(user), Some(owner)) => user == owner,
+    }
+}
+
+/// Whether daemon `id` is shown in the view of `user`. IDs without state,
+/// e.g. disabled before they ever ran, are shown to everyone.
+pub(crate) fn id_visible_to(
+    daemons: &BTreeMap<DaemonId, Daemon>,
+    id: &DaemonId,
+    user: Option<&str>,
+) -> bool {
+    daemons.get(id).is_none_or(|d| visible_to(d, user))
+}
+
+/// Whether `daemon` was started by `user`, or any daemon for `None`. Unlike
+/// [`visible_to`], daemons without an owner aren't any user's own.
+pub(crate) fn owned_by(daemon: &Daemon, user: Option<&str>) -> bool {
+    user.is_none_or(|user| daemon.owner.as_deref() == Some(user))
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    nix::unistd::User::from_uid(nix::unistd::Uid::from_raw(uid))
+        .ok()
+        .flatten()
+        .map(|u| u.name)
+        .unwrap_or_else(|| uid.to_string())
+}
+
+#[cfg(not(unix))]
+fn user_name(uid: u32) -> String {
+    uid.to_string()
+}
+
+#[cfg(unix)]
+fn is_admin_uid(uid: u32) -> bool {
+    uid == 0 || uid == nix::unistd::Uid::effective().as_raw()
+}
+
+#[cfg(not(unix))]
+fn is_admin_uid(_uid: u32) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caller(name: &str, admin: bool) -> Caller {
+        Caller {
+            name: name.to_string(),
+            admin,
+        }
+    }
+
+    fn daemon(owner: Option<&str>, pid: Option<u32>) -> Daemon {
+        Daemon {
+            owner: owner.map(str::to_string),
+            pid,
+            ..Daemon::default()
+        }
+    }
+
+    #[test]
+    fn test_view() {
+        let alice = caller("alice", false);
+        assert_eq!(alice.view(None).unwrap(), Some("alice".to_string()));
+        assert_eq!(
+            alice.view(Some("alice")).unwrap(),
+            Some("alice".to_string())
+        );
+        assert!(alice.view(Some("bob")).is_err());
+
+        let root = caller("root", true);
+        assert_eq!(root.view(None).unwrap(), None);
+        assert_eq!(root.view(Some("bob")).unwrap(), Some("bob".to_string()));
+    }
+
+    #[test]
+    fn test_may_manage() {
+        let alice = caller("alice", false);
+        assert!(alice.may_manage(None));
+        assert!(alice.may_manage(Some(&daemon(Some("alice"), Some(1)))));
+        assert!(!alice.may_manage(Some(&daemon(Some("bob"), None))));
+        assert!(alice.may_manage(Some(&daemon(None, None))));
+        assert!(!alice.may_manage(Some(&daemon(None, Some(1)))));
+        assert!(caller("root", true).may_manage(Some(&daemon(Some("bob"), Some(1)))));
+    }
+
+    #[test]
+    fn test_claim() {
+        let mut opts = RunOptions::default();
+        caller("alice", false).claim(&mut opts, None);
+        assert_eq!(opts.owner.as_deref(), Some("alice"));
+        assert_eq!(opts.user.as_deref(), Some("alice"));
+
+        // An admin starting its own daemon keeps the configured user
+        let mut opts = RunOptions {
+            user: Some("postgres".to_string()),
+            ..RunOptions::default()
+        };
+        caller("root", true).claim(&mut opts, None);
+        assert_eq!(opts.owner.as_deref(), Some("root"));
+        assert_eq!(opts.user.as_deref(), Some("postgres"));
+
+        // ...but restarting bob's daemon runs it as bob
+        let mut opts = RunOptions::default();
+        caller("root", true).claim(&mut opts, Some("bob".to_string()));
+        assert_eq!(opts.owner.as_deref(), Some("bob"));
+        assert_eq!(opts.user.as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn test_visible_to() {
+        assert!(visible_to(&daemon(Some("bob"), None), None));
+        assert!(visible_to(&daemon(None, None), Some("alice")));
+        assert!(visible_to(&daemon(Some("alice"), None), Some("alice")));
+        assert!(!visible_to(&daemon(Some("bob"), None), Some("alice")));
+    }
+
+    #[test]
+    fn test_owned_by() {
+        assert!(owned_by(&daemon(Some("bob"), None), None));
+        assert!(owned_by(&daemon(None, None), None));
+        assert!(owned_by(&daemon(Some("alice"), None), Some("alice")));
+        assert!(!owned_by(&daemon(Some("bob"), None), Some("alice")));
+        assert!(!owned_by(&daemon(None, None), Some("alice")));
+    }
+
+    #[tokio::test]
+    async fn test_user_only_touches_own_daemons() {
+        let temp = tempfile::tempdir().unwrap();
+        let supervisor = crate::supervisor::Supervisor::with_state_file(
+            crate::state_file::StateFile::new(temp.path().join("state.toml")),
+        );
+        let ids: Vec<_> = [Some("alice"), Some("bob"), None]
+            .into_iter()
+            .map(|owner| {
+                let id = DaemonId::new("proj", owner.unwrap_or("boot"));
+                let daemon = Daemon {
+                    id: id.clone(),
+                    dir: Some(temp.path().to_path_buf()),
+                    ..daemon(owner, None)
+                };
+                (id, daemon)
+            })
+            .collect();
+        {
+            let mut state = supervisor.state_file.lock().await;
+            let mut pending = supervisor.pending_autostops.lock().await;
+            for (id, daemon) in &ids {
+                state.daemons.insert(id.clone(), daemon.clone());
+                pending.insert(id.clone(), tokio::time::Instant::now());
+            }
+        }
+
+        supervisor
+            .cancel_pending_autostops_for_dir(temp.path(), Some("alice"))
+            .await;
+        let pending = supervisor.pending_autostops.lock().await.clone();
+        assert!(!pending.contains_key(&ids[0].0));
+        assert!(pending.contains_key(&ids[1].0));
+        assert!(pending.contains_key(&ids[2].0));
+
+        supervisor.clean(&[], Some("alice")).await.unwrap();
+        let state = supervisor.state_file.lock().await;
+        assert!(!state.daemons.contains_key(&ids[0].0));
+        assert!(state.daemons.contains_key(&ids[1].0));
+        assert!(state.daemons.contains_key(&ids[2].0));
+        assert!(!id_visible_to(&state.daemons, &ids[1].0, Some("alice")));
+        assert!(id_visible_to(&state.daemons, &ids[2].0, Some("alice")));
+        assert!(id_visible_to(&state.daemons, &ids[0].0, Some("alice")));
+    }
+
+    #[tokio::test]
+    async fn test_notifications_stay_with_their_user() {
+        let temp = tempfile::tempdir().unwrap();
+        let supervisor = crate::supervisor::Supervisor::with_state_file(
+            crate::state_file::StateFile::new(temp.path().join("state.toml")),
+        );
+        let level = log::LevelFilter::Info;
+        *supervisor.pending_notifications.lock().await = vec![
+            (Some("alice".to_string()), level, "alice's".to_string()),
+            (Some("bob".to_string()), level, "bob's".to_string()),
+            (None, level, "everyone's".to_string()),
+        ];
+        let messages = |notifications: Vec<(log::LevelFilter, String)>| {
+            notifications
+                .into_iter()
+                .map(|(_, m)| m)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            messages(supervisor.get_notifications(Some("alice")).await),
+            ["alice's", "everyone's"]
+        );
+        assert_eq!(
+            messages(supervisor.get_notifications(None).await),
+            ["bob's"]
+        );
+    }
+}
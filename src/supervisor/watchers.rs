@@ -5,25 +5,146 @@
 //! - Cron scheduling
 //! - File watching for daemon auto-restart
 
-use super::{SUPERVISOR, Supervisor, UpsertDaemonOpts, interval_duration};
+use super::alerts::Breach;
+use super::cron_lease::{self, Acquire};
+use super::{SUPERVISOR, Supervisor, UpsertDaemonOpts, events, interval_duration};
+use crate::daemon::{Daemon, RunOptions};
 use crate::daemon_id::DaemonId;
 use crate::daemon_status::DaemonStatus;
 use crate::ipc::IpcResponse;
 use crate::log_store::sqlite::LOG_STORE;
 use crate::log_store::{ArchiveHook, LogStore, RetentionPolicy};
-use crate::pitchfork_toml::{PitchforkToml, WatchMode};
+use crate::pitchfork_toml::{PitchforkToml, PitchforkTomlDaemon, WatchMode};
 use crate::procs::PROCS;
 use crate::settings::settings;
-use crate::watch_files::{WatchFiles, expand_watch_patterns, path_matches_patterns};
+use crate::watch_files::{WatchFiles, expand_watch_patterns, is_exhausted, path_matches_patterns};
 use crate::{Result, env};
 use notify::RecursiveMode;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use tokio::time;
 
 type WatchConfig = (DaemonId, Vec<String>, PathBuf, WatchMode);
 
+/// Shortest time an exclusive cron run holds its lease without renewal.
+const LEASE_MIN_TTL: Duration = Duration::from_secs(60);
+
+/// Minimum gap between wall-clock and monotonic time treated as a suspend.
+const CLOCK_JUMP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Returns how far the wall clock moved ahead of the monotonic clock between
+/// two interval ticks, if that exceeds [`CLOCK_JUMP_THRESHOLD`].
+///
+/// On Linux and macOS the monotonic clock stops while the machine sleeps, so a
+/// large difference means the system was suspended (or the clock was set
+/// forward). Windows' monotonic clock keeps counting through sleep, so no
+/// jump is seen there.
+fn clock_jump(mono_elapsed: Duration, wall_elapsed: Duration) -> Option<Duration> {
+    let jump = wall_elapsed.checked_sub(mono_elapsed)?;
+    (jump >= CLOCK_JUMP_THRESHOLD).then_some(jump)
+}
+
+/// Whether a daemon's recorded pid was reused by a process started at
+/// another time. An unknown start time on either side keeps the daemon.
+fn pid_reused(
+    running: bool,
+    recorded_start_time: Option<u64>,
+    current_start_time: Option<u64>,
+) -> bool {
+    running
+        && matches!(
+            (recorded_start_time, current_start_time),
+            (Some(recorded), Some(current)) if recorded != current
+        )
+}
+
+/// How the due times of a cron schedule relate to the current check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CronDue {
+    /// Nothing was scheduled since the last trigger.
+    None,
+    /// A scheduled time fell within the last couple of check intervals.
+    OnTime,
+    /// Scheduled times were missed (machine asleep, supervisor not running)
+    /// and none is recent.
+    Missed,
+}
+
+fn cron_due(
+    schedule: &cron::Schedule,
+    check_since: chrono::DateTime<chrono::Local>,
+    now: chrono::DateTime<chrono::Local>,
+    grace: Duration,
+) -> CronDue {
+    let grace = chrono::Duration::from_std(grace).unwrap_or(chrono::Duration::seconds(20));
+    let recent_since = check_since.max(now - grace);
+    if schedule
+        .after(&recent_since)
+        .take_while(|t| *t <= now)
+        .next()
+        .is_some()
+    {
+        CronDue::OnTime
+    } else if schedule
+        .after(&check_since)
+        .take_while(|t| *t <= now)
+        .next()
+        .is_some()
+    {
+        CronDue::Missed
+    } else {
+        CronDue::None
+    }
+}
+
+/// What the cron watcher does for one daemon at one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CronStep {
+    /// Nothing is due.
+    Wait,
+    /// First check of a schedule without `immediate`: record `now` as the
+    /// last trigger so the next scheduled time fires, without running now.
+    Anchor,
+    /// Runs were missed and `cron_catch_up = "skip"`: record `now` without
+    /// running.
+    SkipMissed,
+    /// A scheduled time is due: record `now` and run.
+    Trigger,
+    /// Runs were missed: record `now` and run once for all of them.
+    CatchUp,
+}
+
+/// Decide what to do for a schedule last triggered at `last_triggered`.
+///
+/// Pure so that tests can drive it with a virtual clock; the caller persists
+/// `last_cron_triggered` and starts the daemon.
+fn cron_step(
+    schedule: &cron::Schedule,
+    last_triggered: Option<chrono::DateTime<chrono::Local>>,
+    immediate: bool,
+    now: chrono::DateTime<chrono::Local>,
+    grace: Duration,
+    skip_missed: bool,
+) -> CronStep {
+    // Look for a scheduled time that has passed since the last trigger.
+    let check_since = match last_triggered {
+        Some(t) => t,
+        // immediate=true: a scheduled time within the last 10 seconds before
+        // startup triggers immediately.
+        None if immediate => now - chrono::Duration::seconds(10),
+        None => return CronStep::Anchor,
+    };
+    // Runs missed entirely (e.g. while the machine slept) collapse into at
+    // most one trigger, governed by `supervisor.cron_catch_up`.
+    match cron_due(schedule, check_since, now, grace) {
+        CronDue::None => CronStep::Wait,
+        CronDue::OnTime => CronStep::Trigger,
+        CronDue::Missed if skip_missed => CronStep::SkipMissed,
+        CronDue::Missed => CronStep::CatchUp,
+    }
+}
+
 /// Build an optional archive hook from the configured settings.
 fn build_archive_hook(config: &crate::settings::SettingsLogsArchiveHook) -> Option<ArchiveHook> {
     let command = config.command.trim();
@@ -67,6 +188,7 @@ fn unwatch_removed_dirs(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn watch_new_dirs(
     wf: &mut Option<WatchFiles>,
     watched: &HashSet<PathBuf>,
@@ -75,6 +197,7 @@ fn watch_new_dirs(
     dir_to_daemons: &HashMap<PathBuf, Vec<DaemonId>>,
     auto_dirs: Option<&HashSet<PathBuf>>,
     failed_dirs: &mut HashSet<PathBuf>,
+    exhausted: Option<&mut Option<String>>,
 ) -> HashSet<PathBuf> {
     let Some(wf) = wf.as_mut() else {
         return HashSet::new();
@@ -89,6 +212,14 @@ fn watch_new_dirs(
             daemon_ids
         );
         if let Err(e) = wf.watch(dir, RecursiveMode::Recursive) {
+            // Out of watches: the caller moves every directory to polling,
+            // so there is no point trying the rest
+            if is_exhausted(&e)
+                && let Some(exhausted) = exhausted
+            {
+                *exhausted = Some(e.to_string());
+                break;
+            }
             let should_fallback = auto_dirs.is_some_and(|dirs| dirs.contains(dir));
             if should_fallback {
                 warn!(
@@ -130,6 +261,148 @@ impl Supervisor {
             .collect()
     }
 
+    /// Watch configs for daemons with `restart_on_config_change`: every
+    /// existing config file that can define or override the daemon.
+    pub(crate) async fn get_config_watch_configs(&self) -> Vec<WatchConfig> {
+        let daemons: Vec<(DaemonId, PathBuf, WatchMode)> = {
+            let state = self.state_file.lock().await;
+            state
+                .daemons
+                .values()
+                .filter(|d| d.restart_on_config_change == Some(true))
+                .filter_map(|d| Some((d.id.clone(), d.watch_base_dir.clone()?, d.watch_mode)))
+                .collect()
+        };
+        daemons
+            .into_iter()
+            .map(|(id, base_dir, watch_mode)| {
+                let patterns = PitchforkToml::list_paths_from(&base_dir)
+                    .iter()
+                    .filter(|p| p.exists())
+                    .map(|p| globset::escape(&p.to_string_lossy()))
+                    .collect();
+                (id, patterns, base_dir, watch_mode)
+            })
+            .collect()
+    }
+
+    /// Watch configs for daemons with `restart_on_binary_change`: the
+    /// executable their `run` command starts, which also takes the place of
+    /// the base directory.
+    pub(crate) async fn get_binary_watch_configs(&self) -> Vec<WatchConfig> {
+        let state = self.state_file.lock().await;
+        state
+            .daemons
+            .values()
+            .filter(|d| d.restart_on_binary_change == Some(true))
+            .filter_map(|d| {
+                let exe = daemon_executable(d)?;
+                let pattern = globset::escape(&exe.to_string_lossy());
+                Some((d.id.clone(), vec![pattern], exe, d.watch_mode))
+            })
+            .collect()
+    }
+
+    /// Restart running daemons with `restart_on_binary_change` whose
+    /// executable changed. `binary_versions` holds the version of each
+    /// executable last seen; a file that was only touched is hashed again
+    /// and doesn't restart the daemon if its contents are the same.
+    async fn restart_for_binary_change(
+        &self,
+        changed_paths: &[PathBuf],
+        binary_watch_configs: &[WatchConfig],
+        binary_versions: &mut HashMap<DaemonId, BinaryVersion>,
+    ) {
+        for (id, patterns, exe, _) in binary_watch_configs {
+            if !changed_paths
+                .iter()
+                .any(|p| path_matches_patterns(p, patterns, exe))
+            {
+                continue;
+            }
+            let Some(old) = binary_versions.get(id) else {
+                continue;
+            };
+            let Some(mtime) = file_mtime(exe) else {
+                // Removed, or being replaced; the new file is seen when it lands
+                debug!("Executable {} of daemon {id} is gone", exe.display());
+                continue;
+            };
+            if old.mtime == mtime {
+                continue;
+            }
+            let Some(new) = BinaryVersion::read(exe.clone()).await else {
+                continue;
+            };
+            let changed = new.hash != old.hash;
+            binary_versions.insert(id.clone(), new);
+            if !changed {
+                trace!("Executable of daemon {id} was touched but is unchanged");
+                continue;
+            }
+            let reason = format!("after its executable {} changed", exe.display());
+            if let Err(e) = self.restart_running_daemon(id, &reason).await {
+                error!("Failed to restart daemon {id} after executable change: {e}");
+            }
+        }
+    }
+
+    /// Restart running daemons with `restart_on_config_change` whose config
+    /// entry differs from `config_entries`, the entries they were last seen
+    /// with. Changes to other daemons in the same file are ignored.
+    async fn restart_for_config_change(
+        &self,
+        changed_paths: &[PathBuf],
+        config_watch_configs: &[WatchConfig],
+        config_entries: &mut HashMap<DaemonId, String>,
+    ) {
+        for (id, patterns, base_dir, _) in config_watch_configs {
+            let Some(path) = changed_paths
+                .iter()
+                .find(|p| path_matches_patterns(p, patterns, base_dir))
+            else {
+                continue;
+            };
+            let daemon = match config_entry(id, base_dir) {
+                Ok(Some(daemon)) => daemon,
+                Ok(None) => {
+                    debug!("Daemon {id} is no longer configured, not restarting");
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        "Not restarting daemon {id}: failed to read config after {} changed: {e}",
+                        path.display()
+                    );
+                    continue;
+                }
+            };
+            let entry = format!("{daemon:?}");
+            if config_entries.get(id) == Some(&entry) {
+                trace!("Config entry for daemon {id} is unchanged");
+                continue;
+            }
+            config_entries.insert(id.clone(), entry);
+
+            let run_opts = match crate::ipc::batch::build_run_options(id, &daemon, None) {
+                Ok(run_opts) => run_opts,
+                Err(e) => {
+                    error!("Failed to restart daemon {id} after config change: {e}");
+                    continue;
+                }
+            };
+            let reason = format!("after its config changed in {}", path.display());
+            match self
+                .restart_running_daemon_with(id, &reason, Some(run_opts))
+                .await
+            {
+                Ok(true) => Self::spawn_restart_cascade(id.clone(), vec![]),
+                Ok(false) => {}
+                Err(e) => error!("Failed to restart daemon {id} after config change: {e}"),
+            }
+        }
+    }
+
     async fn restart_for_changed_paths(
         &self,
         changed_paths: Vec<PathBuf>,
@@ -151,7 +424,7 @@ impl Supervisor {
         }
 
         for id in daemons_to_restart {
-            if let Err(e) = self.restart_watched_daemon(&id).await {
+            if let Err(e) = self.restart_running_daemon(&id, "on file change").await {
                 error!("Failed to restart daemon {id} after file change: {e}");
             }
         }
@@ -164,18 +437,35 @@ impl Supervisor {
             // Track consecutive CPU-over-limit samples per daemon.
             // Kept outside the state file because it is ephemeral runtime data.
             let mut cpu_violation_counts: HashMap<DaemonId, u32> = HashMap::new();
+            // Daemons currently over one of their `alerts` thresholds.
+            let mut alert_breaches: HashMap<DaemonId, Breach> = HashMap::new();
             // Run log retention check no more than once per hour.
             let mut last_retention_check = tokio::time::Instant::now() - Duration::from_secs(3600);
+            let mut last_tick = (time::Instant::now(), SystemTime::now());
             loop {
                 interval.tick().await;
+                let tick = (time::Instant::now(), SystemTime::now());
+                let jump = tick
+                    .1
+                    .duration_since(last_tick.1)
+                    .ok()
+                    .and_then(|wall| clock_jump(tick.0 - last_tick.0, wall));
+                last_tick = tick;
+                if let Some(jump) = jump {
+                    // CPU samples spanning the suspend are meaningless, and
+                    // daemons often burst right after resume.
+                    cpu_violation_counts.clear();
+                    alert_breaches.clear();
+                    SUPERVISOR.handle_clock_jump(jump).await;
+                }
                 if SUPERVISOR.last_refreshed_at.lock().await.elapsed() > interval_duration()
                     && let Err(err) = SUPERVISOR.refresh().await
                 {
                     error!("failed to refresh: {err}");
                 }
-                // Check resource limits (CPU and memory) for all running daemons
+                // Check resource limits and alerts (CPU and memory) for all running daemons
                 if let Err(err) = SUPERVISOR
-                    .check_resource_limits(&mut cpu_violation_counts)
+                    .check_resource_limits(&mut cpu_violation_counts, &mut alert_breaches)
                     .await
                 {
                     error!("failed to check resource limits: {err}");
@@ -196,25 +486,89 @@ impl Supervisor {
         Ok(())
     }
 
+    /// React to a wall-clock jump detected by the interval watcher, usually a
+    /// resume from sleep.
+    ///
+    /// Daemon PIDs are re-read from the process table before the regular
+    /// refresh acts on them. Cron runs missed during the suspend are left to
+    /// the cron watcher, which applies `supervisor.cron_catch_up` to them.
+    async fn handle_clock_jump(&self, jump: Duration) {
+        info!(
+            "wall clock jumped {}s ahead (system resumed from sleep?), re-verifying daemons",
+            jump.as_secs()
+        );
+        self.reverify_daemon_pids().await;
+        if let Err(err) = self.refresh().await {
+            error!("failed to refresh after clock jump: {err}");
+        }
+    }
+
+    /// Refresh the process table for every daemon PID in state and mark any
+    /// daemon whose pid now belongs to another process as stopped, without
+    /// treating it as a crash. Daemons that exited while the machine slept
+    /// are left to their monitoring task, which handles them like any other
+    /// exit.
+    async fn reverify_daemon_pids(&self) {
+        let daemons: Vec<(DaemonId, u32, Option<u64>)> = {
+            let pitchfork_id = DaemonId::pitchfork();
+            let state = self.state_file.lock().await;
+            state
+                .daemons
+                .values()
+                .filter(|d| d.id != pitchfork_id)
+                .filter_map(|d| d.local_pid().map(|pid| (d.id.clone(), pid, d.start_time)))
+                .collect()
+        };
+        if daemons.is_empty() {
+            return;
+        }
+        let pids: Vec<u32> = daemons.iter().map(|(_, pid, _)| *pid).collect();
+        PROCS.refresh_pids(&pids);
+        for (id, pid, start_time) in daemons {
+            let running = PROCS.is_running(pid);
+            if !running {
+                // A real exit: its monitoring task (or the watcher of an
+                // adopted daemon) sees it and runs crash and retry handling
+                debug!("daemon {id} (pid {pid}) exited during clock jump");
+                continue;
+            }
+            if !pid_reused(running, start_time, PROCS.start_time(pid)) {
+                debug!("daemon {id} (pid {pid}) verified after clock jump");
+                continue;
+            }
+            let reason = format!("pid {pid} belongs to a different process after clock jump");
+            warn!("daemon {id}: {reason}");
+            // The daemon's own exit went unseen, so there is nothing to
+            // retry; it is only marked stopped
+            super::reset_daemon_state(self, &id).await;
+            events::record(&id, "exit", Some(reason));
+        }
+    }
+
     /// Check resource limits (CPU and memory) for all running daemons.
     ///
     /// For each daemon with a `memory_limit` or `cpu_limit` configured, this method
     /// reads the current RSS / CPU% from sysinfo and kills the daemon if it exceeds
     /// the configured threshold. The kill is done without setting `Stopping` status,
     /// so the monitor task treats it as a failure (`Errored`), which allows retry
-    /// logic to kick in if configured.
+    /// logic to kick in if configured. Daemons that keep running then have the
+    /// same sample checked against their `alerts`.
     async fn check_resource_limits(
         &self,
         cpu_violation_counts: &mut HashMap<DaemonId, u32>,
+        alert_breaches: &mut HashMap<DaemonId, Breach>,
     ) -> Result<()> {
-        // Quick check: does any daemon have resource limits configured?
+        let is_monitored =
+            |d: &Daemon| d.memory_limit.is_some() || d.cpu_limit.is_some() || d.alerts.is_some();
+        // Quick check: does any daemon have resource limits or alerts configured?
         // This avoids acquiring the state lock on every tick when no limits are set.
         let daemons: Vec<_> = {
             let pitchfork_id = DaemonId::pitchfork();
             let state = self.state_file.lock().await;
-            let has_any_limits = state.daemons.values().any(|d| {
-                d.id != pitchfork_id && (d.memory_limit.is_some() || d.cpu_limit.is_some())
-            });
+            let has_any_limits = state
+                .daemons
+                .values()
+                .any(|d| d.id != pitchfork_id && is_monitored(d));
             if !has_any_limits {
                 return Ok(());
             }
@@ -223,9 +577,9 @@ impl Supervisor {
                 .values()
                 .filter(|d| {
                     d.id != pitchfork_id
-                        && d.pid.is_some()
-                        && d.status.is_running()
-                        && (d.memory_limit.is_some() || d.cpu_limit.is_some())
+                        && d.local_pid().is_some()
+                        && d.status.is_up()
+                        && is_monitored(d)
                 })
                 .cloned()
                 .collect()
@@ -286,6 +640,7 @@ impl Supervisor {
                         );
                         cpu_violation_counts.remove(&daemon.id);
                         self.stop_for_resource_violation(&daemon.id, pid).await;
+                        continue;
                     } else {
                         debug!(
                             "daemon {} (pid {}) CPU {:.1}% > {}% ({}/{} consecutive violations)",
@@ -297,10 +652,13 @@ impl Supervisor {
                     cpu_violation_counts.remove(&daemon.id);
                 }
             }
+
+            self.check_alerts(daemon, pid, stats, alert_breaches).await;
         }
 
         // Prune counters for daemons that are no longer running/tracked
         cpu_violation_counts.retain(|id, _| active_ids.contains(id));
+        alert_breaches.retain(|id, _| active_ids.contains(id));
 
         Ok(())
     }
@@ -566,16 +924,28 @@ impl Supervisor {
 
     /// Check cron schedules and trigger daemons as needed
     pub(crate) async fn check_cron_schedules(&self) -> Result<()> {
-        use cron::Schedule;
-        use std::str::FromStr;
-
         // Register config-only cron daemons into state so the cron watcher
         // can see them. Without this, daemons defined in config with `cron`
         // but never started (no `boot_start`, no manual `pitchfork start`)
         // are invisible to the cron checker.
         self.register_config_cron_daemons().await?;
+        self.trigger_cron_daemons().await
+    }
+
+    /// Trigger the cron daemons in state whose schedule is due
+    async fn trigger_cron_daemons(&self) -> Result<()> {
+        use cron::Schedule;
+        use std::str::FromStr;
 
-        let now = chrono::Local::now();
+        let now = self.clock.now();
+        // A scheduled time within two check intervals counts as on time;
+        // anything older was missed.
+        let catch_up_grace = settings().supervisor_cron_check_interval() * 2;
+        let skip_missed = settings().supervisor.cron_catch_up == "skip";
+        // Exclusive runs hold their lease for a few checks past the last
+        // renewal, so a crashed supervisor doesn't block the others for long.
+        let lease_ttl = (settings().supervisor_cron_check_interval() * 3).max(LEASE_MIN_TTL);
+        let lease_holder = cron_lease::holder();
 
         // Collect only IDs of daemons with cron schedules (avoids cloning entire HashMap)
         let cron_daemon_ids: Vec<DaemonId> = {
@@ -610,39 +980,51 @@ impl Supervisor {
                     }
                 };
 
-                // Check if we should trigger: look for a scheduled time that has passed
-                // since our last trigger.
-                let check_since = match daemon.last_cron_triggered {
-                    Some(t) => t,
-                    None => {
-                        if daemon.cron_immediate.unwrap_or(false) {
-                            // immediate=true: restore the old look-back behavior.
-                            // A scheduled time within the last 10 seconds before startup
-                            // will trigger immediately.
-                            now - chrono::Duration::seconds(10)
-                        } else {
-                            // immediate=false (default): anchor last_cron_triggered to now
-                            // so the next scheduled time is picked up, without firing now.
-                            let mut state_file = self.state_file.lock().await;
-                            if state_file.set_last_cron_triggered(&id, now)
-                                && let Err(e) = state_file.write()
-                            {
-                                error!(
-                                    "failed to persist last_cron_triggered for daemon {id}: {e}"
-                                );
-                            }
-                            continue;
+                let exclusive = daemon.cron_exclusive.unwrap_or(false);
+                let dir = daemon.dir.clone().unwrap_or_else(|| env::CWD.clone());
+                let lease_path = cron_lease::lease_path(&dir, &id);
+                if exclusive {
+                    let r = if daemon.pid.is_some() {
+                        cron_lease::renew(&lease_path, &lease_holder, now, lease_ttl)
+                    } else {
+                        cron_lease::release(&lease_path, &lease_holder, now)
+                    };
+                    if let Err(e) = r {
+                        warn!("failed to update cron lease {}: {e}", lease_path.display());
+                    }
+                }
+
+                let step = cron_step(
+                    &schedule,
+                    daemon.last_cron_triggered,
+                    daemon.cron_immediate.unwrap_or(false),
+                    now,
+                    catch_up_grace,
+                    skip_missed,
+                );
+                let should_trigger = match step {
+                    CronStep::Wait => false,
+                    CronStep::Anchor | CronStep::SkipMissed => {
+                        if step == CronStep::SkipMissed {
+                            info!(
+                                "cron: skipping missed runs of daemon {id} (cron_catch_up = skip)"
+                            );
+                        }
+                        let mut state_file = self.state_file.lock().await;
+                        if state_file.set_last_cron_triggered(&id, now)
+                            && let Err(e) = state_file.write()
+                        {
+                            error!("failed to persist last_cron_triggered for daemon {id}: {e}");
                         }
+                        false
+                    }
+                    CronStep::Trigger => true,
+                    CronStep::CatchUp => {
+                        info!("cron: catching up missed runs of daemon {id} with a single trigger");
+                        true
                     }
                 };
 
-                // Find if there's a scheduled time between check_since and now
-                let should_trigger = schedule
-                    .after(&check_since)
-                    .take_while(|t| *t <= now)
-                    .next()
-                    .is_some();
-
                 if should_trigger {
                     // Update last_cron_triggered to prevent re-triggering the same event.
                     // This write is synchronous and critical: deferring it to the
@@ -656,6 +1038,17 @@ impl Supervisor {
                         {
                             error!("failed to persist last_cron_triggered for daemon {id}: {e}");
                         }
+                        // The schedule keeps advancing while disabled or
+                        // paused, so enabling again doesn't catch up on
+                        // skipped runs
+                        if state_file.paused {
+                            info!("cron: supervisor is paused, skipping trigger of daemon {id}");
+                            continue;
+                        }
+                        if !state_file.autostart_allowed(&id) {
+                            info!("cron: daemon {id} is disabled, skipping trigger");
+                            continue;
+                        }
                     }
 
                     let should_run = match retrigger {
@@ -681,6 +1074,43 @@ impl Supervisor {
                         }
                     };
 
+                    if should_run && exclusive {
+                        // The scheduled time being fired, so that a run fired
+                        // elsewhere for it is recognized
+                        let slot = schedule
+                            .after(
+                                &daemon
+                                    .last_cron_triggered
+                                    .unwrap_or(now - chrono::Duration::seconds(10)),
+                            )
+                            .take_while(|t| *t <= now)
+                            .last()
+                            .unwrap_or(now);
+                        match cron_lease::acquire(&lease_path, &lease_holder, slot, now, lease_ttl)
+                        {
+                            Ok(Acquire::Acquired) => {}
+                            Ok(Acquire::Held(holder)) => {
+                                info!(
+                                    "cron: daemon {id} is still running under {holder}, skipping trigger"
+                                );
+                                continue;
+                            }
+                            Ok(Acquire::AlreadyFired(holder)) => {
+                                info!(
+                                    "cron: daemon {id} was already triggered by {holder}, skipping trigger"
+                                );
+                                continue;
+                            }
+                            Err(e) => {
+                                warn!(
+                                    "cron: failed to take lease {} for exclusive daemon {id}, skipping trigger: {e}",
+                                    lease_path.display()
+                                );
+                                continue;
+                            }
+                        }
+                    }
+
                     if should_run {
                         info!("cron: triggering daemon {id} (retrigger: {retrigger:?})");
                         // Use the persisted command from daemon state
@@ -691,7 +1121,6 @@ impl Supervisor {
                                 continue;
                             }
                         };
-                        let dir = daemon.dir.clone().unwrap_or_else(|| env::CWD.clone());
                         // Use force: true for Always retrigger to ensure restart
                         let force =
                             matches!(retrigger, crate::pitchfork_toml::CronRetrigger::Always);
@@ -764,15 +1193,27 @@ impl Supervisor {
         tokio::spawn(async move {
             let debounce = settings().supervisor_file_watch_debounce();
             let poll_interval = settings().supervisor_watch_poll_interval();
+            let fallback_poll_interval = crate::settings::Settings::parse_duration(
+                &settings().supervisor.watch_fallback_poll_interval,
+            )
+            .unwrap_or(poll_interval);
 
             let mut native_wf: Option<WatchFiles> = None;
             let mut poll_wf: Option<WatchFiles> = None;
+            // Polls directories native watching failed for
+            let mut fallback_wf: Option<WatchFiles> = None;
             let mut native_creation_failed = false;
             let mut poll_creation_failed = false;
+            let mut fallback_creation_failed = false;
             let mut watched_native_dirs = HashSet::new();
             let mut watched_poll_dirs = HashSet::new();
+            let mut watched_fallback_dirs = HashSet::new();
+            // Set once the OS runs out of native watches. The native watcher
+            // then misses changes in some directories, so every directory is
+            // polled by `fallback_wf` for the rest of the supervisor's run.
+            let mut native_exhausted: Option<String> = None;
             // Directories that previously failed native watch in auto mode and
-            // are permanently tracked by the poll watcher. Maps dir → set of
+            // are permanently tracked by the fallback poll watcher. Maps dir → set of
             // daemon IDs that originally triggered the fallback, so entries for
             // removed daemons are pruned even when a different daemon uses the
             // same dir (which should get a fresh native-watch attempt).
@@ -781,12 +1222,53 @@ impl Supervisor {
             // warn-level logs on every loop iteration.
             let mut failed_native_watch_dirs: HashSet<PathBuf> = HashSet::new();
             let mut failed_poll_watch_dirs: HashSet<PathBuf> = HashSet::new();
+            let mut failed_fallback_watch_dirs: HashSet<PathBuf> = HashSet::new();
+            // Config entry of each `restart_on_config_change` daemon as last
+            // seen, so only changes to its own entry restart it.
+            let mut config_entries: HashMap<DaemonId, String> = HashMap::new();
+            // Executable of each `restart_on_binary_change` daemon as last
+            // seen, so a rebuild that produces the same binary is ignored.
+            let mut binary_versions: HashMap<DaemonId, BinaryVersion> = HashMap::new();
 
             info!("File watcher started");
 
             loop {
+                if let Some(reason) = &native_exhausted
+                    && native_wf.take().is_some()
+                {
+                    warn!(
+                        "Native file watching ran out of watches ({reason}), polling all watched \
+                         directories instead. Raise fs.inotify.max_user_watches (Linux) or the \
+                         open file limit (macOS) and restart the supervisor to watch natively again"
+                    );
+                    watched_native_dirs.clear();
+                    failed_native_watch_dirs.clear();
+                }
+
                 // Refresh watch configurations from state
                 let watch_configs = SUPERVISOR.get_all_watch_configs().await;
+                let config_watch_configs = SUPERVISOR.get_config_watch_configs().await;
+                config_entries.retain(|id, _| config_watch_configs.iter().any(|(c, ..)| c == id));
+                for (id, _, base_dir, _) in &config_watch_configs {
+                    if !config_entries.contains_key(id)
+                        && let Ok(Some(daemon)) = config_entry(id, base_dir)
+                    {
+                        config_entries.insert(id.clone(), format!("{daemon:?}"));
+                    }
+                }
+                let binary_watch_configs = SUPERVISOR.get_binary_watch_configs().await;
+                binary_versions.retain(|id, v| {
+                    binary_watch_configs
+                        .iter()
+                        .any(|(c, _, exe, _)| c == id && *exe == v.path)
+                });
+                for (id, _, exe, _) in &binary_watch_configs {
+                    if !binary_versions.contains_key(id)
+                        && let Some(version) = BinaryVersion::read(exe.clone()).await
+                    {
+                        binary_versions.insert(id.clone(), version);
+                    }
+                }
 
                 // Collect required directories grouped by watch mode
                 let mut required_native_dirs = HashSet::new();
@@ -794,7 +1276,11 @@ impl Supervisor {
                 let mut required_auto_dirs = HashSet::new();
                 let mut dir_to_daemons: HashMap<PathBuf, Vec<DaemonId>> = HashMap::new();
 
-                for (id, patterns, base_dir, watch_mode) in &watch_configs {
+                for (id, patterns, base_dir, watch_mode) in watch_configs
+                    .iter()
+                    .chain(&config_watch_configs)
+                    .chain(&binary_watch_configs)
+                {
                     match expand_watch_patterns(patterns, base_dir) {
                         Ok(dirs) => {
                             for dir in dirs {
@@ -833,8 +1319,15 @@ impl Supervisor {
                     .collect();
 
                 // AUTO mode prefers native when available; otherwise use poll.
-                let mut target_native_dirs = required_native_dirs;
+                let mut target_native_dirs = HashSet::new();
                 let mut target_poll_dirs = required_poll_dirs;
+                let mut target_fallback_dirs = HashSet::new();
+
+                if native_exhausted.is_some() {
+                    target_fallback_dirs.extend(required_native_dirs);
+                } else {
+                    target_native_dirs = required_native_dirs;
+                }
 
                 if !required_auto_dirs.is_empty() {
                     // AUTO mode prefers native; route auto dirs to the native target
@@ -843,8 +1336,8 @@ impl Supervisor {
                     // Directories that previously fell back to poll are routed there
                     // directly to avoid repeated native-watch failure + warn logging.
                     for dir in &required_auto_dirs {
-                        if auto_fallback_dirs.contains_key(dir) {
-                            target_poll_dirs.insert(dir.clone());
+                        if native_exhausted.is_some() || auto_fallback_dirs.contains_key(dir) {
+                            target_fallback_dirs.insert(dir.clone());
                         } else {
                             target_native_dirs.insert(dir.clone());
                         }
@@ -886,16 +1379,21 @@ impl Supervisor {
                             &dir_to_daemons,
                             Some(&auto_only_dirs),
                             &mut failed_native_watch_dirs,
+                            Some(&mut native_exhausted),
                         );
+                        if native_exhausted.is_some() {
+                            // Start over: the top of the loop drops the native
+                            // watcher and routes everything to polling
+                            continue;
+                        }
                     } else {
-                        target_poll_dirs.extend(target_native_dirs.iter().cloned());
-                        target_native_dirs.clear();
+                        target_fallback_dirs.extend(target_native_dirs.drain());
                     }
                 }
 
                 if !new_fallback_dirs.is_empty() {
                     target_native_dirs.retain(|d| !new_fallback_dirs.contains(d));
-                    target_poll_dirs.extend(new_fallback_dirs.iter().cloned());
+                    target_fallback_dirs.extend(new_fallback_dirs.iter().cloned());
                     for dir in &new_fallback_dirs {
                         let daemon_ids = dir_to_daemons
                             .get(dir)
@@ -937,18 +1435,89 @@ impl Supervisor {
                             &dir_to_daemons,
                             None,
                             &mut failed_poll_watch_dirs,
+                            None,
                         );
                     } else {
                         target_poll_dirs.clear();
                     }
                 }
 
+                // Daemons with a directory native watching failed for are
+                // shown as polling in `status`
+                let fallback_reason = if native_exhausted.is_some() {
+                    "native watch limit reached"
+                } else {
+                    "native watch failed"
+                };
+                let fallback_daemons: HashSet<DaemonId> = target_fallback_dirs
+                    .iter()
+                    .filter_map(|dir| dir_to_daemons.get(dir))
+                    .flatten()
+                    .cloned()
+                    .collect();
+
+                // Directories an explicit poll daemon also uses are already polled
+                target_fallback_dirs.retain(|d| !target_poll_dirs.contains(d));
+                unwatch_removed_dirs(
+                    &mut fallback_wf,
+                    &watched_fallback_dirs,
+                    &target_fallback_dirs,
+                    "fallback poll",
+                );
+
+                // Watch new fallback directories
+                if !target_fallback_dirs.is_empty() {
+                    if fallback_wf.is_none() {
+                        match WatchFiles::new(debounce, WatchMode::Poll, fallback_poll_interval) {
+                            Ok(wf) => {
+                                fallback_wf = Some(wf);
+                                fallback_creation_failed = false;
+                            }
+                            Err(e) => {
+                                if fallback_creation_failed {
+                                    debug!("Fallback poll file watcher still unavailable: {e}");
+                                } else {
+                                    fallback_creation_failed = true;
+                                    error!("Failed to create fallback polling file watcher: {e}");
+                                }
+                            }
+                        }
+                    }
+
+                    if fallback_wf.is_some() {
+                        let _ = watch_new_dirs(
+                            &mut fallback_wf,
+                            &watched_fallback_dirs,
+                            &target_fallback_dirs,
+                            "fallback poll",
+                            &dir_to_daemons,
+                            None,
+                            &mut failed_fallback_watch_dirs,
+                            None,
+                        );
+                    } else {
+                        target_fallback_dirs.clear();
+                    }
+                }
+
                 // Only record dirs that were actually registered with an active watcher.
                 // If native_wf is None, nothing was registered natively — clearing
                 // target_native_dirs above ensures watched_native_dirs stays empty,
                 // so the next iteration won't skip re-registration if native recovers.
                 watched_native_dirs = target_native_dirs;
                 watched_poll_dirs = target_poll_dirs;
+                watched_fallback_dirs = target_fallback_dirs;
+
+                {
+                    let mut state_file = SUPERVISOR.state_file.lock().await;
+                    let ids: Vec<DaemonId> = state_file.daemons.keys().cloned().collect();
+                    for id in ids {
+                        let reason = fallback_daemons
+                            .contains(&id)
+                            .then(|| fallback_reason.to_string());
+                        state_file.set_watch_fallback(&id, reason);
+                    }
+                }
 
                 // Prune stale auto-fallback entries: keep a dir only if at least
                 // one of the daemon IDs that originally triggered the fallback is
@@ -966,15 +1535,33 @@ impl Supervisor {
 
                 // Wait for file changes or a refresh interval
                 let watch_interval = settings().supervisor_watch_interval();
+                let (native_rx, native_exhausted_rx) = match native_wf.as_mut() {
+                    Some(wf) => (Some(&mut wf.rx), Some(&mut wf.exhausted_rx)),
+                    None => (None, None),
+                };
                 tokio::select! {
                     native_changes = async {
-                        match native_wf.as_mut() {
-                            Some(wf) => wf.rx.recv().await,
+                        match native_rx {
+                            Some(rx) => rx.recv().await,
                             None => std::future::pending::<Option<Vec<PathBuf>>>().await,
                         }
                     } => {
                         if let Some(changed_paths) = native_changes {
                             debug!("File changes detected (native): {changed_paths:?}");
+                            SUPERVISOR
+                                .restart_for_config_change(
+                                    &changed_paths,
+                                    &config_watch_configs,
+                                    &mut config_entries,
+                                )
+                                .await;
+                            SUPERVISOR
+                                .restart_for_binary_change(
+                                    &changed_paths,
+                                    &binary_watch_configs,
+                                    &mut binary_versions,
+                                )
+                                .await;
                             SUPERVISOR
                                 .restart_for_changed_paths(changed_paths, &watch_configs)
                                 .await;
@@ -988,11 +1575,64 @@ impl Supervisor {
                     } => {
                         if let Some(changed_paths) = poll_changes {
                             debug!("File changes detected (poll): {changed_paths:?}");
+                            SUPERVISOR
+                                .restart_for_config_change(
+                                    &changed_paths,
+                                    &config_watch_configs,
+                                    &mut config_entries,
+                                )
+                                .await;
+                            SUPERVISOR
+                                .restart_for_binary_change(
+                                    &changed_paths,
+                                    &binary_watch_configs,
+                                    &mut binary_versions,
+                                )
+                                .await;
                             SUPERVISOR
                                 .restart_for_changed_paths(changed_paths, &watch_configs)
                                 .await;
                         }
                     }
+                    fallback_changes = async {
+                        match fallback_wf.as_mut() {
+                            Some(wf) => wf.rx.recv().await,
+                            None => std::future::pending::<Option<Vec<PathBuf>>>().await,
+                        }
+                    } => {
+                        if let Some(changed_paths) = fallback_changes {
+                            debug!("File changes detected (fallback poll): {changed_paths:?}");
+                            SUPERVISOR
+                                .restart_for_config_change(
+                                    &changed_paths,
+                                    &config_watch_configs,
+                                    &mut config_entries,
+                                )
+                                .await;
+                            SUPERVISOR
+                                .restart_for_binary_change(
+                                    &changed_paths,
+                                    &binary_watch_configs,
+                                    &mut binary_versions,
+                                )
+                                .await;
+                            SUPERVISOR
+                                .restart_for_changed_paths(changed_paths, &watch_configs)
+                                .await;
+                        }
+                    }
+                    // The native watcher couldn't add a watch for a new
+                    // subdirectory; the next iteration switches to polling
+                    exhausted = async {
+                        match native_exhausted_rx {
+                            Some(rx) => rx.recv().await,
+                            None => std::future::pending::<Option<String>>().await,
+                        }
+                    } => {
+                        if let Some(reason) = exhausted {
+                            native_exhausted = Some(reason);
+                        }
+                    }
                     _ = tokio::time::sleep(watch_interval) => {
                         // Periodically refresh watch configs to pick up new daemons
                         trace!("Refreshing file watch configurations");
@@ -1004,40 +1644,97 @@ impl Supervisor {
         Ok(())
     }
 
-    /// Restart a daemon that is being watched for file changes.
-    /// Only restarts if the daemon is currently running.
-    pub(crate) async fn restart_watched_daemon(&self, id: &DaemonId) -> Result<()> {
+    /// Restart running daemons whose `pitchfork debug enable --duration`
+    /// window has passed, so they go back to their configured env.
+    pub(crate) async fn revert_expired_env_overrides(&self) {
+        let now = self.clock.now();
+        let expired: Vec<DaemonId> = {
+            let mut state_file = self.state_file.lock().await;
+            let ids: Vec<DaemonId> = state_file
+                .daemons
+                .values()
+                .filter(|d| d.env_override_until.is_some_and(|until| until <= now))
+                .map(|d| d.id.clone())
+                .collect();
+            ids.into_iter()
+                .filter(|id| state_file.clear_env_override(id))
+                .collect()
+        };
+        for id in expired {
+            info!("env override for daemon {id} expired");
+            tokio::spawn(async move {
+                if let Err(e) = SUPERVISOR
+                    .restart_running_daemon(&id, "after its env override expired")
+                    .await
+                {
+                    error!("Failed to restart daemon {id} after its env override expired: {e}");
+                }
+            });
+        }
+    }
+
+    /// Restart a daemon from its state, e.g. after a watched file changed.
+    /// Only restarts if the daemon is currently running; `reason` completes
+    /// the log messages ("Restarting daemon api on file change") and is
+    /// recorded as a `restart` event. Dependents with
+    /// `restart_with_dependencies` are restarted after it.
+    pub(crate) async fn restart_running_daemon(&self, id: &DaemonId, reason: &str) -> Result<()> {
+        if self.restart_running_daemon_with(id, reason, None).await? {
+            Self::spawn_restart_cascade(id.clone(), vec![]);
+        }
+        Ok(())
+    }
+
+    /// Like [`restart_running_daemon`](Self::restart_running_daemon), but
+    /// restarting with `run_opts` (e.g. rebuilt from changed config) instead
+    /// of the options in its state, and without restarting its dependents.
+    /// Returns whether the daemon was restarted.
+    pub(crate) async fn restart_running_daemon_with(
+        &self,
+        id: &DaemonId,
+        reason: &str,
+        run_opts: Option<RunOptions>,
+    ) -> Result<bool> {
         // Check if daemon is running
         let daemon = self.get_daemon(id).await;
         let Some(daemon) = daemon else {
             warn!("Daemon {id} not found in state, cannot restart");
-            return Ok(());
+            return Ok(false);
         };
 
-        let is_running = daemon.pid.is_some() && daemon.status.is_running();
+        let is_running = daemon.pid.is_some() && daemon.status.is_up();
 
         if !is_running {
-            debug!("Daemon {id} is not running, skipping restart on file change");
-            return Ok(());
+            debug!("Daemon {id} is not running, skipping restart {reason}");
+            return Ok(false);
         }
 
         // Check if daemon is disabled
-        let is_disabled = self.state_file.lock().await.disabled.contains(id);
+        let (is_disabled, paused) = {
+            let state_file = self.state_file.lock().await;
+            (state_file.disabled.contains(id), state_file.paused)
+        };
         if is_disabled {
-            debug!("Daemon {id} is disabled, skipping restart on file change");
-            return Ok(());
+            debug!("Daemon {id} is disabled, skipping restart {reason}");
+            return Ok(false);
+        }
+        if paused {
+            info!("Supervisor is paused, not restarting daemon {id} {reason}");
+            return Ok(false);
         }
 
-        info!("Restarting daemon {id} due to file change");
+        info!("Restarting daemon {id} {reason}");
 
         // Use values from the daemon state to rebuild RunOptions
-        let cmd = match &daemon.cmd {
-            Some(cmd) => cmd.clone(),
-            None => {
+        let mut run_opts = match (run_opts, &daemon.cmd) {
+            (Some(run_opts), _) => run_opts,
+            (None, Some(cmd)) => daemon.to_run_options(cmd.clone()),
+            (None, None) => {
                 error!("Daemon {id} has no command in state, cannot restart");
-                return Ok(());
+                return Ok(false);
             }
         };
+        events::record(id, "restart", Some(reason.to_string()));
 
         // Stop the daemon first
         let _ = self.stop(id).await;
@@ -1046,23 +1743,363 @@ impl Supervisor {
         time::sleep(settings().supervisor_restart_delay()).await;
 
         // Restart the daemon
-        let mut run_opts = daemon.to_run_options(cmd);
         run_opts.force = true;
         run_opts.retry_count = 0;
-        run_opts.wait_ready = false; // Don't block on file-triggered restarts
+        run_opts.wait_ready = false; // Don't block on background restarts
 
         match self.run(run_opts).await {
             Ok(IpcResponse::DaemonStart { .. }) | Ok(IpcResponse::DaemonReady { .. }) => {
-                info!("Successfully restarted daemon {id} after file change");
+                info!("Successfully restarted daemon {id} {reason}");
+                Ok(true)
             }
             Ok(other) => {
                 warn!("Unexpected response when restarting daemon {id}: {other:?}");
+                Ok(false)
             }
             Err(e) => {
                 error!("Failed to restart daemon {id}: {e}");
+                Ok(false)
             }
         }
+    }
+}
 
-        Ok(())
+/// A daemon's entry merged from the config files under `base_dir`, or None
+/// if it's no longer defined there.
+fn config_entry(id: &DaemonId, base_dir: &Path) -> Result<Option<PitchforkTomlDaemon>> {
+    Ok(PitchforkToml::all_merged_from(base_dir)?
+        .daemons
+        .shift_remove(id))
+}
+
+/// The executable a daemon's command starts: the first word after any
+/// `NAME=value` assignments and `exec`, resolved against the daemon's
+/// directory if it is a path and looked up in its `PATH` otherwise.
+fn daemon_executable(daemon: &Daemon) -> Option<PathBuf> {
+    let path = daemon
+        .env
+        .as_ref()
+        .and_then(|env| env.get("PATH").cloned())
+        .or_else(|| env::ORIGINAL_PATH.clone());
+    resolve_executable(
+        daemon.cmd.as_deref()?,
+        daemon.dir.as_deref(),
+        path.as_deref(),
+    )
+}
+
+fn resolve_executable(cmd: &[String], dir: Option<&Path>, path: Option<&str>) -> Option<PathBuf> {
+    let is_assignment = |word: &str| {
+        word.split_once('=').is_some_and(|(name, _)| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    };
+    let program = cmd
+        .iter()
+        .find(|word| !is_assignment(word) && *word != "exec")?;
+    let exe = if program.contains('/') {
+        let exe = dir.map_or_else(|| PathBuf::from(program), |dir| dir.join(program));
+        exe.is_file().then_some(exe)?
+    } else {
+        std::env::split_paths(path?)
+            .map(|dir| dir.join(program))
+            .find(|exe| exe.is_file())?
+    };
+    // Watch the file a symlink points to, as that is what a build replaces
+    Some(exe.canonicalize().unwrap_or(exe))
+}
+
+/// A daemon executable's contents when last seen.
+struct BinaryVersion {
+    path: PathBuf,
+    mtime: SystemTime,
+    hash: String,
+}
+
+impl BinaryVersion {
+    async fn read(path: PathBuf) -> Option<Self> {
+        tokio::task::spawn_blocking(move || {
+            let mtime = file_mtime(&path)?;
+            let hash = match xx::hash::file_hash_sha256(&path) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    debug!("Failed to hash {}: {e}", path.display());
+                    return None;
+                }
+            };
+            Some(Self { path, mtime, hash })
+        })
+        .await
+        .ok()?
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::clock::{Clock, VirtualClock};
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_resolve_executable() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let bin = root.join("bin");
+        let target = root.join("proj/target/debug");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(bin.join("node"), "").unwrap();
+        std::fs::write(target.join("server"), "").unwrap();
+        let cmd = |s: &str| shell_words::split(s).unwrap();
+        let path = bin.to_string_lossy().to_string();
+        let resolve = |s: &str| resolve_executable(&cmd(s), Some(&root.join("proj")), Some(&path));
+
+        assert_eq!(
+            resolve("./target/debug/server --port 3000"),
+            Some(target.join("server"))
+        );
+        assert_eq!(
+            resolve("RUST_LOG=debug exec target/debug/server"),
+            Some(target.join("server"))
+        );
+        assert_eq!(resolve("node server.js"), Some(bin.join("node")));
+        assert_eq!(resolve("deno run server.ts"), None);
+        assert_eq!(resolve("./target/release/server"), None);
+    }
+
+    #[test]
+    fn test_config_entry_only_changes_with_own_entry() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("proj");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pitchfork.toml");
+        let id = DaemonId::new("proj", "api");
+        let entry = || format!("{:?}", config_entry(&id, &dir).unwrap().unwrap());
+
+        std::fs::write(
+            &path,
+            "[daemons.api]\nrun = \"sleep 10\"\n\n[daemons.worker]\nrun = \"sleep 20\"\n",
+        )
+        .unwrap();
+        let before = entry();
+
+        // Another daemon and formatting changing leave the entry as is
+        std::fs::write(
+            &path,
+            "# comment\n[daemons.api]\nrun = 'sleep 10'\n\n[daemons.worker]\nrun = \"sleep 30\"\n",
+        )
+        .unwrap();
+        assert_eq!(entry(), before);
+
+        std::fs::write(
+            &path,
+            "[daemons.api]\nrun = \"sleep 10\"\nenv = { PORT = \"3000\" }\n",
+        )
+        .unwrap();
+        assert_ne!(entry(), before);
+
+        std::fs::write(&path, "[daemons.worker]\nrun = \"sleep 20\"\n").unwrap();
+        assert!(config_entry(&id, &dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_clock_jump() {
+        let secs = Duration::from_secs;
+        assert_eq!(clock_jump(secs(10), secs(10)), None);
+        assert_eq!(clock_jump(secs(10), secs(25)), None);
+        assert_eq!(clock_jump(secs(10), secs(3610)), Some(secs(3600)));
+        // Wall clock set backwards is not a suspend.
+        assert_eq!(clock_jump(secs(10), secs(0)), None);
+    }
+
+    #[test]
+    fn test_pid_reused() {
+        assert!(!pid_reused(true, Some(100), Some(100)));
+        // The pid now belongs to a process started later
+        assert!(pid_reused(true, Some(100), Some(200)));
+        // Exits are up to the monitoring task
+        assert!(!pid_reused(false, Some(100), None));
+        assert!(!pid_reused(false, None, None));
+        assert!(!pid_reused(true, None, Some(200)));
+        assert!(!pid_reused(true, Some(100), None));
+    }
+
+    #[tokio::test]
+    async fn test_reverify_resets_reused_pids() {
+        let temp = tempfile::tempdir().unwrap();
+        let supervisor = Supervisor::with_state_file(crate::state_file::StateFile::new(
+            temp.path().join("state.toml"),
+        ));
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        exited.wait().unwrap();
+        let exited_pid = exited.id();
+        let daemon = |name: &str, pid: u32, start_time: u64| Daemon {
+            id: DaemonId::new("proj", name),
+            pid: Some(pid),
+            start_time: Some(start_time),
+            status: DaemonStatus::Running,
+            retry: crate::config_types::Retry(3),
+            ..Default::default()
+        };
+        // This test's own pid stands in for one the OS gave to a new process
+        PROCS.refresh_pids(&[std::process::id()]);
+        let own_start_time = PROCS.start_time(std::process::id()).unwrap();
+        let daemons = [
+            daemon("alive", std::process::id(), own_start_time),
+            daemon("reused", std::process::id(), own_start_time + 1),
+            daemon("exited", exited_pid, 1),
+        ];
+        {
+            let mut state = supervisor.state_file.lock().await;
+            for daemon in daemons {
+                state.daemons.insert(daemon.id.clone(), daemon);
+            }
+        }
+
+        supervisor.reverify_daemon_pids().await;
+        let alive = supervisor
+            .get_daemon(&DaemonId::new("proj", "alive"))
+            .await
+            .unwrap();
+        assert_eq!(alive.pid, Some(std::process::id()));
+        assert!(alive.status.is_running());
+        let reused = supervisor
+            .get_daemon(&DaemonId::new("proj", "reused"))
+            .await
+            .unwrap();
+        assert_eq!(reused.pid, None);
+        assert!(reused.status.is_stopped());
+        assert_eq!(reused.retry_count, 0);
+        assert_eq!(reused.next_retry_at, None);
+        // Left for its monitoring task to handle as a crash
+        let exited = supervisor
+            .get_daemon(&DaemonId::new("proj", "exited"))
+            .await
+            .unwrap();
+        assert_eq!(exited.pid, Some(exited_pid));
+        assert!(exited.status.is_running());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cron_fires_on_schedule_in_virtual_time() {
+        let temp = tempfile::tempdir().unwrap();
+        let start = chrono::Local
+            .with_ymd_and_hms(2024, 1, 1, 10, 0, 30)
+            .unwrap();
+        let supervisor = Supervisor::with_state_file(crate::state_file::StateFile::new(
+            temp.path().join("state.toml"),
+        ))
+        .with_clock(VirtualClock::new(start));
+        // Without a command, a trigger only records when it fired
+        let id = DaemonId::new("proj", "report");
+        let daemon = Daemon {
+            id: id.clone(),
+            status: DaemonStatus::Stopped,
+            cron_schedule: Some("0 */5 * * * *".to_string()),
+            cron_retrigger: Some(crate::pitchfork_toml::CronRetrigger::Finish),
+            ..Default::default()
+        };
+        supervisor
+            .state_file
+            .lock()
+            .await
+            .daemons
+            .insert(id.clone(), daemon);
+
+        // An hour of a five-minute schedule, without waiting an hour
+        let mut triggers = vec![];
+        let mut ticker = time::interval(settings().supervisor_cron_check_interval());
+        for _ in 0..360 {
+            ticker.tick().await;
+            supervisor.trigger_cron_daemons().await.unwrap();
+            let last = supervisor
+                .get_daemon(&id)
+                .await
+                .unwrap()
+                .last_cron_triggered;
+            triggers.extend(last.filter(|t| triggers.last() != Some(t)));
+        }
+        // The first check only anchors the schedule
+        assert_eq!(triggers.remove(0), start);
+        assert_eq!(triggers.len(), 12);
+        for (t, minute) in triggers.iter().zip((5..60).step_by(5)) {
+            assert_eq!(t.format("%H:%M").to_string(), format!("10:{minute:02}"));
+        }
+        assert_eq!(triggers[11].format("%H:%M").to_string(), "11:00");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cron_missed_runs_in_virtual_time() {
+        let at = |h, m, s| chrono::Local.with_ymd_and_hms(2024, 1, 1, h, m, s).unwrap();
+        let schedule = cron::Schedule::from_str("0 * * * * *").unwrap();
+        let clock = VirtualClock::new(at(10, 0, 30));
+        let grace = Duration::from_secs(20);
+        let last = Some(clock.now());
+
+        // Suspended for two hours: every run in between was missed
+        time::advance(Duration::from_secs(7200)).await;
+        let now = clock.now();
+        assert_eq!(now, at(12, 0, 30));
+        assert_eq!(
+            cron_step(&schedule, last, false, now, grace, false),
+            CronStep::CatchUp
+        );
+        assert_eq!(
+            cron_step(&schedule, last, false, now, grace, true),
+            CronStep::SkipMissed
+        );
+    }
+
+    #[test]
+    fn test_cron_step_first_check() {
+        let at = |h, m, s| chrono::Local.with_ymd_and_hms(2024, 1, 1, h, m, s).unwrap();
+        let schedule = cron::Schedule::from_str("0 * * * * *").unwrap();
+        let grace = Duration::from_secs(20);
+        assert_eq!(
+            cron_step(&schedule, None, false, at(10, 0, 5), grace, false),
+            CronStep::Anchor
+        );
+        assert_eq!(
+            cron_step(&schedule, None, true, at(10, 0, 5), grace, false),
+            CronStep::Trigger
+        );
+        assert_eq!(
+            cron_step(&schedule, None, true, at(10, 0, 30), grace, false),
+            CronStep::Wait
+        );
+    }
+
+    #[test]
+    fn test_cron_due() {
+        // Every minute, on the minute.
+        let schedule = cron::Schedule::from_str("0 * * * * *").unwrap();
+        let grace = Duration::from_secs(20);
+        let at = |h, m, s| chrono::Local.with_ymd_and_hms(2024, 1, 1, h, m, s).unwrap();
+
+        assert_eq!(
+            cron_due(&schedule, at(10, 0, 5), at(10, 0, 50), grace),
+            CronDue::None
+        );
+        assert_eq!(
+            cron_due(&schedule, at(10, 0, 50), at(10, 1, 5), grace),
+            CronDue::OnTime
+        );
+        // Asleep from 10:00:50 to 12:30:30: every minute in between was missed.
+        assert_eq!(
+            cron_due(&schedule, at(10, 0, 50), at(12, 30, 30), grace),
+            CronDue::Missed
+        );
+        // Woke at 12:30:10, so 12:30:00 still counts as on time.
+        assert_eq!(
+            cron_due(&schedule, at(10, 0, 50), at(12, 30, 10), grace),
+            CronDue::OnTime
+        );
     }
 }
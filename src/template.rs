@@ -1,8 +1,9 @@
 //! Tera template rendering for pitchfork.toml configuration fields.
 //!
 //! Allows `run`, `env` values, `hooks.*`, and the readiness fields (`ready_cmd`,
-//! `ready_http`, `ready_port`, `ready_output`) to use Tera templates like
-//! `{{ daemons.redis.ports[0] }}` to reference computed values from other daemons.
+//! `ready_http`, `ready_port`, `ready_output`, `ready_log_file`, `ready_unix_socket`)
+//! to use Tera templates like `{{ daemons.redis.ports[0] }}` to reference
+//! computed values from other daemons.
 //!
 //! Templates are resolved level-by-level along the dependency order: each level
 //! can reference daemons from previous levels (which have already started and
@@ -49,6 +50,9 @@ pub struct TemplateContext {
     /// values have been rendered, so it is `None` during env-value rendering
     /// itself (preventing self-reference cycles).
     env: Option<IndexMap<String, String>>,
+    /// `PITCHFORK_<NAME>_PORT` variables for the daemon's dependencies that
+    /// have resolved ports, injected into its env at render time
+    dependency_env: IndexMap<String, String>,
 }
 
 impl TemplateContext {
@@ -82,6 +86,13 @@ impl TemplateContext {
             dir,
         };
 
+        let dependency_env = daemon_config
+            .depends
+            .iter()
+            .filter_map(|dep| Some((dep, resolved_daemons.get(dep)?)))
+            .flat_map(|(dep, ports)| dependency_port_env(dep, ports))
+            .collect();
+
         let mut daemon_states = HashMap::new();
         for (dep_id, ports) in resolved_daemons {
             if let Some(config) = daemon_configs.get(dep_id) {
@@ -115,6 +126,7 @@ impl TemplateContext {
             self_state,
             daemon_states,
             env: None,
+            dependency_env,
         }
     }
 
@@ -193,6 +205,31 @@ fn daemon_state_to_json(state: &DaemonTemplateState) -> serde_json::Value {
 
 /// Convert a DaemonId into a template key using `namespace.name` format.
 /// E.g. `myproj/redis` -> `myproj.redis`
+/// `PITCHFORK_<NAME>_PORT` (first port) and `PITCHFORK_<NAME>_PORT0..N` for a
+/// dependency, mirroring the daemon's own `PORT`/`PORT0..N`
+fn dependency_port_env(dep: &DaemonId, ports: &[u16]) -> Vec<(String, String)> {
+    let name: String = dep
+        .name()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let prefix = format!("PITCHFORK_{name}_PORT");
+    let mut vars = Vec::new();
+    if let Some(port) = ports.first() {
+        vars.push((prefix.clone(), port.to_string()));
+    }
+    for (i, port) in ports.iter().enumerate() {
+        vars.push((format!("{prefix}{i}"), port.to_string()));
+    }
+    vars
+}
+
 fn qualified_key(id: &DaemonId) -> String {
     format!("{}.{}", id.namespace(), id.name())
 }
@@ -216,6 +253,29 @@ fn build_proxy_url(slug: Option<&str>, s: &crate::settings::Settings) -> Option<
 // Env merge + render helpers
 // ---------------------------------------------------------------------------
 
+/// Layer the `[env_templates]` a daemon opts into over the top-level `[env]`,
+/// in the order it lists them. The result is the set of defaults that the
+/// daemon's own `env` then overrides (see [`render_daemon_templates`]).
+pub fn env_defaults(
+    top_env: Option<&IndexMap<String, String>>,
+    env_templates: &IndexMap<String, IndexMap<String, String>>,
+    names: &[String],
+) -> Result<Option<IndexMap<String, String>>, RenderError> {
+    if names.is_empty() {
+        return Ok(top_env.cloned());
+    }
+    let mut env = top_env.cloned().unwrap_or_default();
+    for name in names {
+        let template = env_templates
+            .get(name)
+            .ok_or_else(|| RenderError::UnknownEnvTemplate { name: name.clone() })?;
+        for (k, v) in template {
+            env.insert(k.clone(), v.clone());
+        }
+    }
+    Ok(Some(env))
+}
+
 /// Merge top-level env with per-daemon env. Per-daemon values win on conflicts.
 pub(crate) fn merge_env(
     top: Option<&IndexMap<String, String>>,
@@ -272,8 +332,9 @@ pub fn render_template(template: &str, context: &TemplateContext) -> Result<Stri
 
 /// Render all template-enabled fields of a daemon config.
 ///
-/// Top-level `env` (from `[env]` in pitchfork.toml) is merged into the daemon's
-/// own `env` as defaults — per-daemon values win on key conflicts. Env values
+/// `top_env` (the `[env]` table, with any `[env_templates]` the daemon uses
+/// layered on by [`env_defaults`]) is merged into the daemon's own `env` as
+/// defaults — per-daemon values win on key conflicts. Env values
 /// are rendered first (with a context that excludes `env` itself, preventing
 /// self-reference cycles), then the rendered env is exposed as `{{ env.X }}`
 /// for the remaining fields (`run`, `hooks`, `ready_*`).
@@ -294,6 +355,16 @@ pub fn render_daemon_templates(
     let rendered_env = render_env(top_env, config.env.as_ref(), context)?;
     config.env = rendered_env;
 
+    // Dependencies' resolved ports, unless the config sets the same variable
+    if !context.dependency_env.is_empty() {
+        let env = config.env.get_or_insert_with(IndexMap::new);
+        for (k, v) in &context.dependency_env {
+            if !env.contains_key(k) {
+                env.insert(k.clone(), v.clone());
+            }
+        }
+    }
+
     // Phase 2: expose the rendered env on the context as the authoritative
     // state, so to_tera_context() (used by TemplateRenderer::new) includes it.
     if let Some(ref env) = config.env {
@@ -326,6 +397,14 @@ pub fn render_daemon_templates(
                 .on_exit
                 .as_deref()
                 .and_then(|t| renderer.render(t).ok()),
+            on_oom: hooks
+                .on_oom
+                .as_deref()
+                .and_then(|t| renderer.render(t).ok()),
+            on_alert: hooks
+                .on_alert
+                .as_deref()
+                .and_then(|t| renderer.render(t).ok()),
             on_output: hooks.on_output.as_ref().and_then(|hook| {
                 renderer
                     .render(&hook.run)
@@ -337,6 +416,7 @@ pub fn render_daemon_templates(
                         debounce: hook.debounce.clone(),
                     })
             }),
+            timeout: hooks.timeout,
         };
         config.hooks = Some(rendered);
     }
@@ -345,6 +425,7 @@ pub fn render_daemon_templates(
         config.ready_cmd = Some(crate::pitchfork_toml::ReadyCmd {
             run: renderer.render(&cmd.run)?,
             timeout: cmd.timeout,
+            attempt_timeout: cmd.attempt_timeout,
         });
     }
 
@@ -356,12 +437,39 @@ pub fn render_daemon_templates(
         });
     }
 
+    if let Some(ref pattern) = config.fail_output {
+        config.fail_output = Some(renderer.render(pattern)?);
+    }
+
     if let Some(ref http) = config.ready_http {
         let mut http = http.clone();
         http.url = renderer.render(&http.url)?;
+        for value in http.headers.values_mut() {
+            *value = renderer.render(value)?;
+        }
         config.ready_http = Some(http);
     }
 
+    if let Some(ref debug_env) = config.debug_env {
+        config.debug_env = Some(
+            debug_env
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), renderer.render(v)?)))
+                .collect::<Result<_, RenderError>>()?,
+        );
+    }
+
+    if let Some(ref socket) = config.ready_unix_socket {
+        config.ready_unix_socket = Some(renderer.render(socket)?);
+    }
+
+    if let Some(ref log_file) = config.ready_log_file {
+        let mut log_file = log_file.clone();
+        log_file.path = renderer.render(&log_file.path)?;
+        log_file.pattern = renderer.render(&log_file.pattern)?;
+        config.ready_log_file = Some(log_file);
+    }
+
     if let Some(ref ready_port) = config.ready_port
         && let Some(ref template) = ready_port.template
     {
@@ -464,6 +572,8 @@ pub enum RenderError {
         "ready_port template {template:?} rendered to {rendered:?}, expected a port number (1-65535)"
     )]
     InvalidPort { template: String, rendered: String },
+    #[error("env template '{name}' is not defined in [env_templates]")]
+    UnknownEnvTemplate { name: String },
 }
 
 // ---------------------------------------------------------------------------
@@ -667,6 +777,44 @@ mod tests {
         assert_eq!(env["GRAM_URL"], "redis://6379");
     }
 
+    #[test]
+    fn test_env_templates_layered_between_top_level_and_daemon_env() {
+        let mut ctx = make_context_with_daemon("redis", vec![6379]);
+        let top_env = IndexMap::from([
+            ("LOG_LEVEL".to_string(), "info".to_string()),
+            ("REGION".to_string(), "local".to_string()),
+        ]);
+        let templates = IndexMap::from([
+            (
+                "redis".to_string(),
+                IndexMap::from([(
+                    "REDIS_URL".to_string(),
+                    "redis://localhost:{{ daemons.redis.port }}".to_string(),
+                )]),
+            ),
+            (
+                "verbose".to_string(),
+                IndexMap::from([("LOG_LEVEL".to_string(), "debug".to_string())]),
+            ),
+        ]);
+        let mut config = PitchforkTomlDaemon {
+            run: "echo".to_string(),
+            env_templates: vec!["redis".to_string(), "verbose".to_string()],
+            env: Some(IndexMap::from([("REGION".to_string(), "eu".to_string())])),
+            ..Default::default()
+        };
+        let defaults = env_defaults(Some(&top_env), &templates, &config.env_templates).unwrap();
+        render_daemon_templates(&mut config, &mut ctx, defaults.as_ref()).unwrap();
+        let env = config.env.unwrap();
+        assert_eq!(env["REDIS_URL"], "redis://localhost:6379");
+        // a template overrides [env], the daemon's env overrides both
+        assert_eq!(env["LOG_LEVEL"], "debug");
+        assert_eq!(env["REGION"], "eu");
+
+        let err = env_defaults(None, &templates, &["missing".to_string()]).unwrap_err();
+        assert!(matches!(err, RenderError::UnknownEnvTemplate { name } if name == "missing"));
+    }
+
     #[test]
     fn test_env_exposed_in_context_for_other_fields() {
         let mut ctx = make_context_with_daemon("redis", vec![6379]);
@@ -679,6 +827,43 @@ mod tests {
         assert_eq!(config.run, "echo localhost");
     }
 
+    #[test]
+    fn test_dependency_ports_injected_into_env() {
+        let db = DaemonId::new("myproj", "db");
+        let cache = DaemonId::new("myproj", "redis-cache");
+        let config = PitchforkTomlDaemon {
+            run: "echo $PITCHFORK_DB_PORT".to_string(),
+            depends: vec![db.clone(), cache.clone()],
+            env: Some(IndexMap::from([(
+                "PITCHFORK_REDIS_CACHE_PORT".to_string(),
+                "1234".to_string(),
+            )])),
+            ..Default::default()
+        };
+        let resolved = HashMap::from([
+            (db.clone(), vec![5432]),
+            (cache.clone(), vec![6379]),
+            (DaemonId::new("myproj", "other"), vec![8080]),
+        ]);
+        let configs = IndexMap::from([
+            (db, make_daemon_config("postgres")),
+            (cache, make_daemon_config("redis-server")),
+        ]);
+        let id = DaemonId::new("myproj", "api");
+        let mut ctx = TemplateContext::new(&id, &config, &resolved, &configs);
+        let mut rendered = config.clone();
+        render_daemon_templates(&mut rendered, &mut ctx, None).unwrap();
+
+        let env = rendered.env.unwrap();
+        assert_eq!(env["PITCHFORK_DB_PORT"], "5432");
+        assert_eq!(env["PITCHFORK_DB_PORT0"], "5432");
+        // Configured values win
+        assert_eq!(env["PITCHFORK_REDIS_CACHE_PORT"], "1234");
+        assert_eq!(env["PITCHFORK_REDIS_CACHE_PORT0"], "6379");
+        // Only dependencies are exposed
+        assert!(!env.contains_key("PITCHFORK_OTHER_PORT"));
+    }
+
     #[test]
     fn test_env_values_cannot_self_reference() {
         let mut ctx = make_context_with_daemon("redis", vec![6379]);
@@ -707,12 +892,15 @@ mod tests {
                 on_retry: None,
                 on_stop: None,
                 on_exit: None,
+                on_oom: None,
+                on_alert: None,
                 on_output: Some(crate::config_types::OnOutputHook {
                     run: "curl http://localhost:{{ daemons.redis.port }}".to_string(),
                     filter: Some("ready".to_string()),
                     regex: None,
                     debounce: None,
                 }),
+                timeout: None,
             }),
             ..Default::default()
         };
@@ -737,7 +925,11 @@ mod tests {
             ready_http: Some(ReadyHttp {
                 url: "http://localhost:{{ daemons.redis.port }}/health".to_string(),
                 status: vec![200, 401],
-                timeout: None,
+                headers: IndexMap::from([(
+                    "X-Port".to_string(),
+                    "{{ daemons.redis.port }}".to_string(),
+                )]),
+                ..Default::default()
             }),
             ready_port: Some(ReadyPort::from_template("{{ daemons.redis.port }}")),
             ..Default::default()
@@ -756,6 +948,7 @@ mod tests {
         let http = config.ready_http.unwrap();
         assert_eq!(http.url, "http://localhost:6379/health");
         assert_eq!(http.status, vec![200, 401]);
+        assert_eq!(http.headers["X-Port"], "6379");
         assert_eq!(config.ready_port, Some(ReadyPort::new(6379)));
     }
 
@@ -800,7 +993,10 @@ mod tests {
                 on_retry: None,
                 on_stop: None,
                 on_exit: None,
+                on_oom: None,
+                on_alert: None,
                 on_output: None,
+                timeout: None,
             }),
             ..Default::default()
         };
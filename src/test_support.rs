@@ -0,0 +1,322 @@
+//! Isolated supervisors for integration tests.
+//!
+//! Enabled with the `test-support` feature. [`TestSupervisor`] gives each test
+//! its own temporary HOME, state, logs and config directories (the same
+//! isolation the bats suite sets up in `test/test_helper/common_setup.bash`),
+//! starts a supervisor there, and stops it again when dropped:
+//!
+//! ```ignore
+//! use pitchfork_cli::test_support::TestSupervisor;
+//! use std::time::Duration;
+//!
+//! let sup = TestSupervisor::builder()
+//!     .bin(env!("CARGO_BIN_EXE_pitchfork"))
+//!     .start()
+//!     .unwrap();
+//! sup.write_config(r#"
+//! [daemons.api]
+//! run = "echo listening; sleep 60"
+//! ready_output = "listening"
+//! "#).unwrap();
+//! sup.pitchfork(["start", "api"]).success().unwrap();
+//! sup.wait_for_running("api", Duration::from_secs(10)).unwrap();
+//! sup.assert_logs_contain("api", "listening").unwrap();
+//! ```
+//!
+//! The supervisor runs as a separate `pitchfork` process: daemon paths are
+//! resolved once per process, so isolating an in-process supervisor per test
+//! isn't possible.
+
+use crate::Result;
+use crate::daemon::Daemon;
+use crate::daemon_status::DaemonStatus;
+use crate::state_file::StateFile;
+use miette::{IntoDiagnostic, bail, ensure};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// How often `wait_for_*` helpers re-check state
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Builder for [`TestSupervisor`]
+#[derive(Debug, Default)]
+pub struct TestSupervisorBuilder {
+    bin: Option<PathBuf>,
+    env: Vec<(String, String)>,
+}
+
+impl TestSupervisorBuilder {
+    /// The `pitchfork` binary to run. Defaults to `$PITCHFORK_TEST_BIN`, then
+    /// `pitchfork` on `PATH`. Cargo integration tests in this crate can pass
+    /// `env!("CARGO_BIN_EXE_pitchfork")`.
+    pub fn bin(mut self, bin: impl Into<PathBuf>) -> Self {
+        self.bin = Some(bin.into());
+        self
+    }
+
+    /// Extra environment for the supervisor and every `pitchfork` command,
+    /// e.g. `("PITCHFORK_AUTOSTOP_DELAY", "0s")`.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Create the isolated directories and start the supervisor.
+    pub fn start(self) -> Result<TestSupervisor> {
+        let bin = self
+            .bin
+            .or_else(|| std::env::var_os("PITCHFORK_TEST_BIN").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("pitchfork"));
+        let home = tempfile::tempdir().into_diagnostic()?;
+        // The IPC socket lives in the state dir; keep its path short to stay
+        // under the 108-byte Unix socket path limit.
+        let state = tempfile::Builder::new()
+            .prefix("pf-test-")
+            .tempdir_in(short_tmp_dir())
+            .into_diagnostic()?;
+        let project_dir = home.path().join("project");
+        let logs_dir = state.path().join("logs");
+        let config_dir = home.path().join(".config").join("pitchfork");
+        for dir in [&project_dir, &logs_dir, &config_dir] {
+            std::fs::create_dir_all(dir).into_diagnostic()?;
+        }
+
+        let mut env = vec![
+            ("HOME".to_string(), path_str(home.path())),
+            ("TMPDIR".to_string(), path_str(home.path())),
+            ("PITCHFORK_STATE_DIR".to_string(), path_str(state.path())),
+            ("PITCHFORK_LOGS_DIR".to_string(), path_str(&logs_dir)),
+            ("PITCHFORK_CONFIG_DIR".to_string(), path_str(&config_dir)),
+            ("PITCHFORK_SHELL".to_string(), "sh -c".to_string()),
+            ("PITCHFORK_WATCH_INTERVAL".to_string(), "100ms".to_string()),
+            ("PITCHFORK_LOG".to_string(), "debug".to_string()),
+        ];
+        env.extend(self.env);
+
+        let sup = TestSupervisor {
+            bin,
+            env,
+            project_dir,
+            state_dir: state.path().to_path_buf(),
+            _home: home,
+            _state: state,
+        };
+        // Output goes to a file rather than a pipe: the background supervisor
+        // would otherwise inherit the pipe and keep it open.
+        sup.command(["supervisor", "start", "--force"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .into_diagnostic()?;
+        Ok(sup)
+    }
+}
+
+/// A supervisor running in its own temporary directories. Stopped, and its
+/// directories removed, when dropped.
+#[derive(Debug)]
+pub struct TestSupervisor {
+    bin: PathBuf,
+    env: Vec<(String, String)>,
+    project_dir: PathBuf,
+    state_dir: PathBuf,
+    _home: tempfile::TempDir,
+    _state: tempfile::TempDir,
+}
+
+/// Output of a `pitchfork` command run by [`TestSupervisor::pitchfork`]
+#[derive(Debug)]
+pub struct CommandOutput {
+    pub output: Output,
+}
+
+impl CommandOutput {
+    pub fn stdout(&self) -> String {
+        String::from_utf8_lossy(&self.output.stdout).to_string()
+    }
+
+    pub fn stderr(&self) -> String {
+        String::from_utf8_lossy(&self.output.stderr).to_string()
+    }
+
+    /// Fail unless the command exited successfully.
+    pub fn success(self) -> Result<Self> {
+        ensure!(
+            self.output.status.success(),
+            "pitchfork exited with {}\nstdout:\n{}\nstderr:\n{}",
+            self.output.status,
+            self.stdout(),
+            self.stderr()
+        );
+        Ok(self)
+    }
+
+    /// Fail unless the command exited unsuccessfully.
+    pub fn failure(self) -> Result<Self> {
+        ensure!(
+            !self.output.status.success(),
+            "pitchfork unexpectedly succeeded\nstdout:\n{}",
+            self.stdout()
+        );
+        Ok(self)
+    }
+}
+
+impl TestSupervisor {
+    pub fn builder() -> TestSupervisorBuilder {
+        TestSupervisorBuilder::default()
+    }
+
+    /// Start with the default settings and the binary from `$PITCHFORK_TEST_BIN`
+    /// or `PATH`.
+    pub fn start() -> Result<Self> {
+        Self::builder().start()
+    }
+
+    /// Working directory for commands; [`Self::write_config`] writes its
+    /// `pitchfork.toml` here.
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    pub fn state_dir(&self) -> &Path {
+        &self.state_dir
+    }
+
+    /// Write `pitchfork.toml` in the project directory.
+    pub fn write_config(&self, toml: &str) -> Result<()> {
+        std::fs::write(self.project_dir.join("pitchfork.toml"), toml).into_diagnostic()
+    }
+
+    /// A `pitchfork` command with this supervisor's environment, run from
+    /// the project directory.
+    pub fn command<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut cmd = Command::new(&self.bin);
+        cmd.args(args)
+            .current_dir(&self.project_dir)
+            .envs(self.env.iter().map(|(k, v)| (k, v)));
+        cmd
+    }
+
+    /// Run `pitchfork` with `args` and capture its output.
+    pub fn pitchfork<I, S>(&self, args: I) -> CommandOutput
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = self
+            .command(args)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run {}: {e}", self.bin.display()));
+        CommandOutput { output }
+    }
+
+    /// The supervisor's current state file.
+    pub fn state(&self) -> Result<StateFile> {
+        StateFile::read(self.state_dir.join("state.toml"))
+    }
+
+    /// A daemon's state, looked up by qualified ID or by short name.
+    pub fn daemon(&self, id: &str) -> Result<Option<Daemon>> {
+        Ok(self
+            .state()?
+            .daemons
+            .into_values()
+            .find(|d| d.id.qualified() == id || d.id.name() == id))
+    }
+
+    /// Wait until `check` holds for the daemon's status.
+    pub fn wait_for_status(
+        &self,
+        id: &str,
+        timeout: Duration,
+        check: impl Fn(&DaemonStatus) -> bool,
+    ) -> Result<Daemon> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let daemon = self.daemon(id)?;
+            if let Some(daemon) = &daemon
+                && check(&daemon.status)
+            {
+                return Ok(daemon.clone());
+            }
+            if Instant::now() >= deadline {
+                match daemon {
+                    Some(d) => bail!("timed out waiting for {id}, status is {}", d.status),
+                    None => bail!("timed out waiting for {id}, it is not in the state file"),
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    pub fn wait_for_running(&self, id: &str, timeout: Duration) -> Result<Daemon> {
+        self.wait_for_status(id, timeout, DaemonStatus::is_running)
+    }
+
+    /// The daemon's logs as printed by `pitchfork logs --raw`.
+    pub fn logs(&self, id: &str) -> Result<String> {
+        Ok(self
+            .pitchfork(["logs", id, "--raw", "--no-pager"])
+            .success()?
+            .stdout())
+    }
+
+    /// Fail unless the daemon's logs contain `needle`.
+    pub fn assert_logs_contain(&self, id: &str, needle: &str) -> Result<()> {
+        let logs = self.logs(id)?;
+        ensure!(
+            logs.contains(needle),
+            "logs for {id} don't contain {needle:?}:\n{logs}"
+        );
+        Ok(())
+    }
+
+    /// Wait until the daemon's logs contain `needle`.
+    pub fn wait_for_logs(&self, id: &str, needle: &str, timeout: Duration) -> Result<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let logs = self.logs(id)?;
+            if logs.contains(needle) {
+                return Ok(logs);
+            }
+            if Instant::now() >= deadline {
+                bail!("timed out waiting for {needle:?} in logs for {id}:\n{logs}");
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for TestSupervisor {
+    fn drop(&mut self) {
+        let _ = self
+            .command(["supervisor", "stop"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(unix)]
+fn short_tmp_dir() -> PathBuf {
+    PathBuf::from("/tmp")
+}
+
+#[cfg(not(unix))]
+fn short_tmp_dir() -> PathBuf {
+    std::env::temp_dir()
+}
@@ -2,23 +2,31 @@ use crate::Result;
 use crate::daemon::Daemon;
 use crate::daemon_id::DaemonId;
 use crate::daemon_list::DaemonListEntry;
+use crate::ipc::batch::PortConflict;
 use crate::ipc::client::IpcClient;
-use crate::log_store::LogStore;
+use crate::log_store::recent::RecentLogLine;
 use crate::log_store::sqlite::LOG_STORE;
+use crate::log_store::{LogEntry, LogStore};
 use crate::pitchfork_toml::{
     CronRetrigger, PitchforkToml, PitchforkTomlAuto, PitchforkTomlCron, PitchforkTomlDaemon,
-    ReadyCmd, ReadyHttp, ReadyOutput, ReadyPort, Retry, namespace_from_path,
+    ReadyCmd, ReadyHttp, ReadyLogFile, ReadyOutput, ReadyPort, ReadyWhen, Retry,
+    namespace_from_path,
 };
-use crate::procs::{PROCS, ProcessStats};
+use crate::procs::{PROCS, ProcessStats, ProcessUsage};
 use crate::settings::settings;
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
+use indexmap::IndexMap;
 use listeners::Listener;
+use miette::IntoDiagnostic;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Most lines the log view loads
+pub const TUI_LOG_LIMIT: usize = 5000;
+
 /// Convert character index to byte index for UTF-8 strings
 fn char_to_byte_index(s: &str, char_idx: usize) -> usize {
     s.char_indices()
@@ -79,6 +87,24 @@ impl StatsHistory {
     }
 }
 
+/// A row of the dashboard's daemon table
+pub enum DashboardRow<'a> {
+    /// Header of a project group, shown when the table is grouped by source config
+    Group(DaemonGroup<'a>),
+    Daemon(&'a Daemon),
+}
+
+/// Daemons defined by the same config file, or, for daemons not in any loaded
+/// config, sharing a namespace
+pub struct DaemonGroup<'a> {
+    /// Key in `App::collapsed_groups`: the config path, or the namespace
+    pub key: String,
+    pub namespace: &'a str,
+    pub config_path: Option<&'a Path>,
+    pub daemons: Vec<&'a Daemon>,
+    pub collapsed: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum View {
     Dashboard,
@@ -89,6 +115,7 @@ pub enum View {
     Details,
     ConfigEditor,
     ConfigFileSelect,
+    Palette,
 }
 
 /// Edit mode for the config editor
@@ -104,7 +131,6 @@ pub enum FormFieldValue {
     Text(String),
     OptionalText(Option<String>),
     Number(u32),
-    OptionalNumber(Option<u64>),
     OptionalPort(Option<ReadyPort>),
     #[allow(dead_code)]
     Boolean(bool),
@@ -168,19 +194,6 @@ impl FormField {
         }
     }
 
-    fn optional_number(name: &'static str, label: &'static str, help: &'static str) -> Self {
-        Self {
-            name,
-            label,
-            value: FormFieldValue::OptionalNumber(None),
-            required: false,
-            help_text: help,
-            error: None,
-            editing: false,
-            cursor: 0,
-        }
-    }
-
     fn optional_port(name: &'static str, label: &'static str, help: &'static str) -> Self {
         Self {
             name,
@@ -252,8 +265,6 @@ impl FormField {
             FormFieldValue::OptionalText(Some(s)) => s.clone(),
             FormFieldValue::OptionalText(None) => String::new(),
             FormFieldValue::Number(n) => n.to_string(),
-            FormFieldValue::OptionalNumber(Some(n)) => n.to_string(),
-            FormFieldValue::OptionalNumber(None) => String::new(),
             FormFieldValue::OptionalPort(Some(p)) => p.to_string(),
             FormFieldValue::OptionalPort(None) => String::new(),
             FormFieldValue::StringList(v) => v.join(", "),
@@ -288,9 +299,6 @@ impl FormField {
                     }
                 }
             }
-            FormFieldValue::OptionalNumber(opt) => {
-                *opt = text.parse().ok();
-            }
             FormFieldValue::OptionalPort(opt) => {
                 let trimmed = text.trim();
                 if trimmed.is_empty() {
@@ -326,7 +334,6 @@ impl FormField {
             FormFieldValue::Text(_)
                 | FormFieldValue::OptionalText(_)
                 | FormFieldValue::Number(_)
-                | FormFieldValue::OptionalNumber(_)
                 | FormFieldValue::OptionalPort(_)
                 | FormFieldValue::StringList(_)
         )
@@ -349,12 +356,28 @@ pub struct EditorState {
     pub scroll_offset: usize,
     /// Preserved config field for ready_cmd (no form UI yet)
     preserved_ready_cmd: Option<ReadyCmd>,
-    /// Preserved ready_http statuses (no form UI yet)
-    preserved_ready_http_status: Option<Vec<u16>>,
-    /// Preserved ready_http timeout (no form UI yet)
-    preserved_ready_http_timeout: Option<std::time::Duration>,
+    /// Preserved config field for ready_log_file (no form UI yet)
+    preserved_ready_log_file: Option<ReadyLogFile>,
+    /// Preserved config field for ready_unix_socket (no form UI yet)
+    preserved_ready_unix_socket: Option<String>,
+    /// Preserved config field for ready_when (no form UI yet)
+    preserved_ready_when: Option<ReadyWhen>,
+    /// Preserved config field for fail_output (no form UI yet)
+    preserved_fail_output: Option<String>,
+    /// Preserved config field for expect_long_running (no form UI yet)
+    preserved_expect_long_running: Option<bool>,
+    /// Preserved config field for debug_env (no form UI yet)
+    preserved_debug_env: Option<IndexMap<String, String>>,
+    /// Preserved config field for env_templates (no form UI yet)
+    preserved_env_templates: Vec<String>,
+    /// Preserved ready_http fields other than the URL (no form UI yet)
+    preserved_ready_http: Option<ReadyHttp>,
     /// Preserved ready_output timeout (no form UI yet)
     preserved_ready_output_timeout: Option<std::time::Duration>,
+    /// Preserved config field for aliases (no form UI yet)
+    preserved_aliases: Vec<String>,
+    /// Preserved cron exclusive flag (no form UI yet)
+    preserved_cron_exclusive: bool,
 }
 
 impl EditorState {
@@ -371,9 +394,17 @@ impl EditorState {
             unsaved_changes: false,
             scroll_offset: 0,
             preserved_ready_cmd: None,
-            preserved_ready_http_status: None,
-            preserved_ready_http_timeout: None,
+            preserved_ready_log_file: None,
+            preserved_ready_unix_socket: None,
+            preserved_ready_when: None,
+            preserved_fail_output: None,
+            preserved_expect_long_running: None,
+            preserved_debug_env: None,
+            preserved_env_templates: Vec::new(),
+            preserved_ready_http: None,
             preserved_ready_output_timeout: None,
+            preserved_aliases: Vec::new(),
+            preserved_cron_exclusive: false,
         }
     }
 
@@ -392,12 +423,17 @@ impl EditorState {
             unsaved_changes: false,
             scroll_offset: 0,
             preserved_ready_cmd: config.ready_cmd.clone(),
-            preserved_ready_http_status: config
-                .ready_http
-                .as_ref()
-                .and_then(|h| (!h.status.is_empty()).then(|| h.status.clone())),
-            preserved_ready_http_timeout: config.ready_http.as_ref().and_then(|h| h.timeout),
+            preserved_ready_log_file: config.ready_log_file.clone(),
+            preserved_ready_unix_socket: config.ready_unix_socket.clone(),
+            preserved_ready_when: config.ready_when.clone(),
+            preserved_fail_output: config.fail_output.clone(),
+            preserved_expect_long_running: config.expect_long_running,
+            preserved_debug_env: config.debug_env.clone(),
+            preserved_env_templates: config.env_templates.clone(),
+            preserved_ready_http: config.ready_http.clone(),
             preserved_ready_output_timeout: config.ready_output.as_ref().and_then(|o| o.timeout),
+            preserved_aliases: config.aliases.clone(),
+            preserved_cron_exclusive: config.cron.as_ref().is_some_and(|c| c.exclusive),
         }
     }
 
@@ -430,10 +466,10 @@ impl EditorState {
                 "Number of retry attempts on failure (0 = no retries).",
                 0,
             ),
-            FormField::optional_number(
+            FormField::optional_text(
                 "ready_delay",
-                "Ready Delay (ms)",
-                "Milliseconds to wait before considering daemon ready.",
+                "Ready Delay",
+                "Time to wait before considering daemon ready (e.g. 3, 1500ms, 2m).",
             ),
             FormField::optional_text(
                 "ready_output",
@@ -501,7 +537,13 @@ impl EditorState {
                 }
                 "auto" => field.value = FormFieldValue::AutoBehavior(config.auto.clone()),
                 "retry" => field.value = FormFieldValue::Number(config.retry.count()),
-                "ready_delay" => field.value = FormFieldValue::OptionalNumber(config.ready_delay),
+                "ready_delay" => {
+                    field.value = FormFieldValue::OptionalText(
+                        config
+                            .ready_delay
+                            .map(|d| humantime::format_duration(d).to_string()),
+                    );
+                }
                 "ready_output" => {
                     field.value = FormFieldValue::OptionalText(
                         config.ready_output.as_ref().map(|o| o.pattern.clone()),
@@ -553,7 +595,15 @@ impl EditorState {
 
     pub fn to_daemon_config(&self) -> PitchforkTomlDaemon {
         let mut config = PitchforkTomlDaemon {
+            aliases: self.preserved_aliases.clone(),
             ready_cmd: self.preserved_ready_cmd.clone(),
+            ready_log_file: self.preserved_ready_log_file.clone(),
+            ready_unix_socket: self.preserved_ready_unix_socket.clone(),
+            ready_when: self.preserved_ready_when.clone(),
+            fail_output: self.preserved_fail_output.clone(),
+            expect_long_running: self.preserved_expect_long_running,
+            env_templates: self.preserved_env_templates.clone(),
+            debug_env: self.preserved_debug_env.clone(),
             path: Some(self.config_path.clone()),
             ..PitchforkTomlDaemon::default()
         };
@@ -581,7 +631,11 @@ impl EditorState {
                 }
                 ("auto", FormFieldValue::AutoBehavior(v)) => config.auto = v.clone(),
                 ("retry", FormFieldValue::Number(n)) => config.retry = Retry(*n),
-                ("ready_delay", FormFieldValue::OptionalNumber(n)) => config.ready_delay = *n,
+                ("ready_delay", FormFieldValue::OptionalText(s)) => {
+                    config.ready_delay = s
+                        .as_deref()
+                        .and_then(|s| crate::pitchfork_toml::parse_duration(s).ok());
+                }
                 ("ready_output", FormFieldValue::OptionalText(s)) => {
                     config.ready_output = s.clone().map(|pattern| ReadyOutput {
                         pattern,
@@ -591,8 +645,7 @@ impl EditorState {
                 ("ready_http", FormFieldValue::OptionalText(s)) => {
                     config.ready_http = s.clone().map(|url| ReadyHttp {
                         url,
-                        status: self.preserved_ready_http_status.clone().unwrap_or_default(),
-                        timeout: self.preserved_ready_http_timeout,
+                        ..self.preserved_ready_http.clone().unwrap_or_default()
                     })
                 }
                 ("ready_port", FormFieldValue::OptionalPort(p)) => config.ready_port = p.clone(),
@@ -615,6 +668,7 @@ impl EditorState {
                 schedule,
                 retrigger: cron_retrigger,
                 immediate: cron_immediate,
+                exclusive: self.preserved_cron_exclusive,
             });
         }
 
@@ -751,7 +805,7 @@ impl EditorState {
             && field.cursor > 0
         {
             let mut text = field.get_text();
-            // Value-backed fields (OptionalNumber/OptionalPort) drop invalid
+            // Value-backed fields (OptionalPort) drop invalid
             // input in set_text, so the cursor can be past the derived text.
             field.cursor = field.cursor.min(text.chars().count());
             if field.cursor == 0 {
@@ -770,6 +824,65 @@ impl EditorState {
         }
     }
 
+    /// The ID being edited, qualified with the config file's namespace when
+    /// only a name was entered.
+    fn qualified_daemon_id(&self) -> Result<DaemonId> {
+        if self.daemon_id.contains('/') {
+            DaemonId::parse(&self.daemon_id)
+        } else {
+            let ns = namespace_from_path(&self.config_path)?;
+            DaemonId::try_new(&ns, &self.daemon_id)
+        }
+    }
+
+    /// The daemon as a `[daemons.<name>]` TOML table for editing as text.
+    ///
+    /// When editing, this is the daemon as saved in its config file, so fields
+    /// the form can't show (env tables, hooks, ...) are included. When
+    /// creating, it's built from the form.
+    pub fn to_toml(&self) -> Result<String> {
+        let (id, daemon) = match &self.mode {
+            EditMode::Edit { original_id } => {
+                let id = DaemonId::parse(original_id)?;
+                let daemon = PitchforkToml::read(&self.config_path)?
+                    .daemons
+                    .shift_remove(&id)
+                    .ok_or_else(|| {
+                        miette::miette!("daemon '{id}' not found in {}", self.config_path.display())
+                    })?;
+                (id, daemon)
+            }
+            EditMode::Create if self.daemon_id.is_empty() => {
+                let ns = namespace_from_path(&self.config_path)?;
+                (DaemonId::try_new(&ns, "new")?, self.to_daemon_config())
+            }
+            EditMode::Create => (self.qualified_daemon_id()?, self.to_daemon_config()),
+        };
+        let mut config = PitchforkToml::new(self.config_path.clone());
+        config.daemons.insert(id, daemon);
+        config.to_toml_string()
+    }
+
+    /// Parse a daemon edited as text by [`to_toml()`](Self::to_toml), checked
+    /// the same way as the config file it will be saved into.
+    pub fn parse_toml(&self, content: &str) -> Result<(DaemonId, PitchforkTomlDaemon)> {
+        let config = PitchforkToml::parse_str(content, &self.config_path)?;
+        let table: toml::Table = toml::from_str(content).into_diagnostic()?;
+        if let Some(key) = table.keys().find(|k| *k != "daemons") {
+            miette::bail!("only a [daemons.<name>] table can be edited here, found '{key}'");
+        }
+        let mut daemons = config.daemons.into_iter();
+        match (daemons.next(), daemons.next()) {
+            (Some((id, daemon)), None) => {
+                if daemon.run.trim().is_empty() {
+                    miette::bail!("daemon '{}' needs a run command", id.name());
+                }
+                Ok((id, daemon))
+            }
+            _ => miette::bail!("expected exactly one [daemons.<name>] table"),
+        }
+    }
+
     pub fn validate(&mut self) -> bool {
         let mut valid = true;
 
@@ -809,6 +922,12 @@ impl EditorState {
                     field.error = Some("Must start with http:// or https://".to_string());
                     valid = false;
                 }
+                ("ready_delay", FormFieldValue::OptionalText(Some(s))) => {
+                    if let Err(e) = crate::pitchfork_toml::parse_duration(s) {
+                        field.error = Some(e);
+                        valid = false;
+                    }
+                }
                 _ => {}
             }
         }
@@ -824,6 +943,38 @@ pub struct ConfigFileSelector {
     pub selected: usize,
 }
 
+/// A command that can be run from the command palette
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteCommand {
+    Start(DaemonId),
+    Stop(DaemonId),
+    Restart(DaemonId),
+    Enable(DaemonId),
+    Disable(DaemonId),
+    Logs(DaemonId),
+    EditConfig(DaemonId),
+    JumpTo(DaemonId),
+    NewDaemon,
+    GroupBySource,
+    Network,
+    Refresh,
+    Help,
+}
+
+/// An entry shown in the command palette
+#[derive(Debug, Clone)]
+pub struct PaletteItem {
+    pub label: String,
+    pub command: PaletteCommand,
+}
+
+/// State for the `:` command palette
+#[derive(Debug, Clone, Default)]
+pub struct CommandPalette {
+    pub query: String,
+    pub selected: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum PendingAction {
     Stop(DaemonId),
@@ -834,8 +985,39 @@ pub enum PendingAction {
     BatchRestart(Vec<DaemonId>),
     BatchDisable(Vec<DaemonId>),
     // Config editor actions
-    DeleteDaemon { id: String, config_path: PathBuf },
+    DeleteDaemon {
+        id: String,
+        config_path: PathBuf,
+    },
     DiscardEditorChanges,
+    /// A start/restart failed because a port was taken: kill the process
+    /// holding it or bump to a free port
+    PortConflict {
+        id: DaemonId,
+        conflict: PortConflict,
+        restart: bool,
+    },
+}
+
+impl PendingAction {
+    /// The daemons affected by a batch operation
+    pub fn batch_ids(&self) -> Option<&[DaemonId]> {
+        match self {
+            PendingAction::BatchStop(ids)
+            | PendingAction::BatchRestart(ids)
+            | PendingAction::BatchDisable(ids) => Some(ids),
+            _ => None,
+        }
+    }
+}
+
+/// Details shown in the confirm overlay of a batch operation
+#[derive(Debug, Clone, Default)]
+pub struct BatchConfirm {
+    /// Running daemons outside the batch that depend on a daemon in it
+    pub dependents: HashMap<DaemonId, Vec<DaemonId>>,
+    /// First visible row of the affected daemon list
+    pub scroll: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -890,7 +1072,15 @@ pub struct App {
     pub view: View,
     pub prev_view: View,
     pub log_content: Vec<String>,
+    // Level of each `log_content` line, when it was classified on ingest
+    pub log_levels: Vec<Option<String>>,
+    // Whether the daemon's lines are classified on ingest, so lines without
+    // a level are plain rather than guessed from their text
+    pub log_levels_classified: bool,
     pub log_daemon_id: Option<DaemonId>,
+    // Daemon whose log view was just opened, for the main loop to fetch its
+    // recent lines from the supervisor
+    pub pending_log_load: Option<DaemonId>,
     pub log_scroll: usize,
     pub log_follow: bool, // Auto-scroll to bottom as new lines appear
     pub message: Option<String>,
@@ -898,6 +1088,7 @@ pub struct App {
     pub process_stats: HashMap<u32, ProcessStats>, // PID -> stats
     pub stats_history: HashMap<DaemonId, StatsHistory>, // daemon_id -> history
     pub pending_action: Option<PendingAction>,
+    pub batch_confirm: BatchConfirm,
     pub loading_text: Option<String>,
     pub search_query: String,
     pub search_active: bool,
@@ -913,16 +1104,28 @@ pub struct App {
     pub details_daemon_id: Option<DaemonId>,
     // Whether logs are expanded to fill the screen (hides charts)
     pub logs_expanded: bool,
+    // Whether the details overlay lists each process of the daemon's tree
+    pub show_process_breakdown: bool,
     // Multi-select state
     pub multi_select: HashSet<DaemonId>,
     // Config-only daemons (defined in pitchfork.toml but not currently active)
     pub config_daemon_ids: HashSet<DaemonId>,
+    // Configured aliases, matched by the daemon search
+    pub daemon_aliases: HashMap<DaemonId, Vec<String>>,
+    // Config file each daemon is defined in, for grouping by project
+    pub daemon_sources: HashMap<DaemonId, PathBuf>,
     // Whether to show config-only daemons in the list
     pub show_available: bool,
+    // Whether the daemon table is grouped by source config
+    pub group_by_source: bool,
+    // Groups (by `DaemonGroup::key`) whose daemons are hidden
+    pub collapsed_groups: HashSet<String>,
     // Config editor state
     pub editor_state: Option<EditorState>,
     // Config file selector state
     pub file_selector: Option<ConfigFileSelector>,
+    // Command palette state
+    pub palette: Option<CommandPalette>,
     // Network view state
     pub network_listeners: Vec<Listener>,
     pub network_search_query: String,
@@ -942,7 +1145,10 @@ impl App {
             view: View::Dashboard,
             prev_view: View::Dashboard,
             log_content: Vec::new(),
+            log_levels: Vec::new(),
+            log_levels_classified: false,
             log_daemon_id: None,
+            pending_log_load: None,
             log_scroll: 1,
             log_follow: true,
             message: None,
@@ -950,6 +1156,7 @@ impl App {
             process_stats: HashMap::new(),
             stats_history: HashMap::new(),
             pending_action: None,
+            batch_confirm: BatchConfirm::default(),
             loading_text: None,
             search_query: String::new(),
             search_active: false,
@@ -961,11 +1168,17 @@ impl App {
             log_search_current: 0,
             details_daemon_id: None,
             logs_expanded: false,
+            show_process_breakdown: false,
             multi_select: HashSet::new(),
             config_daemon_ids: HashSet::new(),
+            daemon_aliases: HashMap::new(),
+            daemon_sources: HashMap::new(),
             show_available: true, // Show available daemons by default
+            group_by_source: false,
+            collapsed_groups: HashSet::new(),
             editor_state: None,
             file_selector: None,
+            palette: None,
             network_listeners: Vec::new(),
             network_search_query: String::new(),
             network_search_active: false,
@@ -977,11 +1190,63 @@ impl App {
     }
 
     pub fn confirm_action(&mut self, action: PendingAction) {
+        self.batch_confirm = BatchConfirm {
+            dependents: action
+                .batch_ids()
+                .map(|ids| self.running_dependents(ids))
+                .unwrap_or_default(),
+            scroll: 0,
+        };
         self.pending_action = Some(action);
         self.prev_view = self.view;
         self.view = View::Confirm;
     }
 
+    /// Running daemons outside `ids` that depend on a daemon in `ids`, keyed
+    /// by the daemon they depend on
+    fn running_dependents(&self, ids: &[DaemonId]) -> HashMap<DaemonId, Vec<DaemonId>> {
+        let Ok(config) = PitchforkToml::all_merged_all_namespaces() else {
+            return HashMap::new();
+        };
+        let mut dependents: HashMap<DaemonId, Vec<DaemonId>> = HashMap::new();
+        for (dependent, daemon_config) in &config.daemons {
+            let running = self
+                .daemons
+                .iter()
+                .any(|d| d.id == *dependent && (d.status.is_up() || d.status.is_waiting()));
+            if !running || ids.contains(dependent) {
+                continue;
+            }
+            for id in daemon_config.depends.iter().filter(|id| ids.contains(id)) {
+                dependents
+                    .entry(id.clone())
+                    .or_default()
+                    .push(dependent.clone());
+            }
+        }
+        dependents
+    }
+
+    /// Whether the config editor holds unsaved changes for `id`
+    pub fn has_unsaved_changes(&self, id: &DaemonId) -> bool {
+        self.editor_state
+            .as_ref()
+            .is_some_and(|e| e.unsaved_changes && e.daemon_id == id.to_string())
+    }
+
+    pub fn scroll_batch_confirm(&mut self, delta: isize) {
+        let len = self
+            .pending_action
+            .as_ref()
+            .and_then(PendingAction::batch_ids)
+            .map_or(0, |ids| ids.len());
+        self.batch_confirm.scroll = self
+            .batch_confirm
+            .scroll
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
     pub fn cancel_confirm(&mut self) {
         self.pending_action = None;
         self.view = self.prev_view;
@@ -1036,8 +1301,11 @@ impl App {
                 .daemons
                 .iter()
                 .filter_map(|d| {
-                    matcher
-                        .fuzzy_match(&d.id.qualified(), &self.search_query)
+                    let aliases = self.daemon_aliases.get(&d.id).into_iter().flatten();
+                    std::iter::once(d.id.qualified())
+                        .chain(aliases.cloned())
+                        .filter_map(|name| matcher.fuzzy_match(&name, &self.search_query))
+                        .max()
                         .map(|score| (d, score))
                 })
                 .collect();
@@ -1056,12 +1324,15 @@ impl App {
                 }
                 SortColumn::Status => {
                     let status_order = |d: &Daemon| match &d.status {
-                        crate::daemon_status::DaemonStatus::Running => 0,
-                        crate::daemon_status::DaemonStatus::Waiting => 1,
-                        crate::daemon_status::DaemonStatus::Stopping => 2,
-                        crate::daemon_status::DaemonStatus::Stopped => 3,
-                        crate::daemon_status::DaemonStatus::Errored(_) => 4,
-                        crate::daemon_status::DaemonStatus::Failed(_) => 5,
+                        crate::daemon_status::DaemonStatus::Running
+                        | crate::daemon_status::DaemonStatus::Detached => 0,
+                        crate::daemon_status::DaemonStatus::Unhealthy => 1,
+                        crate::daemon_status::DaemonStatus::Starting => 2,
+                        crate::daemon_status::DaemonStatus::Waiting => 3,
+                        crate::daemon_status::DaemonStatus::Stopping => 4,
+                        crate::daemon_status::DaemonStatus::Stopped => 5,
+                        crate::daemon_status::DaemonStatus::Errored(_) => 6,
+                        crate::daemon_status::DaemonStatus::Failed(_) => 7,
                     };
                     status_order(a).cmp(&status_order(b))
                 }
@@ -1128,25 +1399,113 @@ impl App {
         self.selected = 0;
     }
 
-    pub fn selected_daemon(&self) -> Option<&Daemon> {
+    /// Rows of the daemon table: the filtered daemons, under a header per
+    /// source config when grouping is on. Groups are ordered by namespace and
+    /// keep the sort order within; collapsed groups show only their header,
+    /// except while searching.
+    pub fn dashboard_rows(&self) -> Vec<DashboardRow<'_>> {
         let filtered = self.filtered_daemons();
-        filtered.get(self.selected).copied()
+        if !self.group_by_source {
+            return filtered.into_iter().map(DashboardRow::Daemon).collect();
+        }
+
+        let mut groups: IndexMap<String, DaemonGroup<'_>> = IndexMap::new();
+        for daemon in filtered {
+            let key = self.group_key(&daemon.id);
+            groups
+                .entry(key.clone())
+                .or_insert_with(|| DaemonGroup {
+                    collapsed: self.search_query.is_empty() && self.collapsed_groups.contains(&key),
+                    key,
+                    namespace: daemon.id.namespace(),
+                    config_path: self.daemon_sources.get(&daemon.id).map(PathBuf::as_path),
+                    daemons: Vec::new(),
+                })
+                .daemons
+                .push(daemon);
+        }
+        groups.sort_by(|_, a, _, b| a.namespace.cmp(b.namespace).then(a.key.cmp(&b.key)));
+
+        let mut rows = Vec::new();
+        for group in groups.into_values() {
+            let members = if group.collapsed {
+                Vec::new()
+            } else {
+                group.daemons.clone()
+            };
+            rows.push(DashboardRow::Group(group));
+            rows.extend(members.into_iter().map(DashboardRow::Daemon));
+        }
+        rows
+    }
+
+    /// Key of the group a daemon is listed under when grouping by source config
+    fn group_key(&self, id: &DaemonId) -> String {
+        self.daemon_sources
+            .get(id)
+            .map_or_else(|| id.namespace().to_string(), |p| p.display().to_string())
+    }
+
+    pub fn selected_daemon(&self) -> Option<&Daemon> {
+        match self.dashboard_rows().into_iter().nth(self.selected) {
+            Some(DashboardRow::Daemon(daemon)) => Some(daemon),
+            _ => None,
+        }
+    }
+
+    /// The group whose header is under the cursor
+    pub fn selected_group(&self) -> Option<DaemonGroup<'_>> {
+        match self.dashboard_rows().into_iter().nth(self.selected) {
+            Some(DashboardRow::Group(group)) => Some(group),
+            _ => None,
+        }
+    }
+
+    /// Daemons a batch action applies to: the multi-selection, or every daemon
+    /// of the group whose header is under the cursor
+    pub fn batch_target_ids(&self) -> Option<Vec<DaemonId>> {
+        if self.has_selection() {
+            return Some(self.selected_daemon_ids());
+        }
+        self.selected_group()
+            .map(|group| group.daemons.iter().map(|d| d.id.clone()).collect())
     }
 
     pub fn select_next(&mut self) {
-        let count = self.filtered_daemons().len();
+        let count = self.dashboard_row_count();
         if count > 0 {
             self.selected = (self.selected + 1) % count;
         }
     }
 
     pub fn select_prev(&mut self) {
-        let count = self.filtered_daemons().len();
+        let count = self.dashboard_row_count();
         if count > 0 {
             self.selected = self.selected.checked_sub(1).unwrap_or(count - 1);
         }
     }
 
+    /// Toggle grouping the daemon table by source config, keeping the cursor
+    /// on the same daemon
+    pub fn toggle_group_by_source(&mut self) {
+        let current = self.selected_daemon().map(|d| d.id.clone());
+        self.group_by_source = !self.group_by_source;
+        self.selected = 0;
+        if let Some(id) = current {
+            self.jump_to_daemon(&id);
+        }
+    }
+
+    /// Collapse or expand the group whose header is under the cursor
+    pub fn toggle_group_collapsed(&mut self) {
+        let Some(key) = self.selected_group().map(|g| g.key) else {
+            return;
+        };
+        if !self.collapsed_groups.remove(&key) {
+            self.collapsed_groups.insert(key);
+        }
+    }
+
     // Log follow mode
     pub fn toggle_log_follow(&mut self) {
         self.log_follow = !self.log_follow;
@@ -1220,6 +1579,11 @@ impl App {
         self.process_stats.get(&pid)
     }
 
+    /// Each process of the tree rooted at `pid`, as of the last refresh
+    pub fn get_process_breakdown(&self, pid: u32) -> Vec<ProcessUsage> {
+        PROCS.get_process_breakdown(pid)
+    }
+
     fn refresh_process_stats(&mut self) {
         let pids: Vec<u32> = self.daemons.iter().filter_map(|d| d.pid).collect();
         if !pids.is_empty() {
@@ -1258,10 +1622,21 @@ impl App {
         self.daemons.clear();
         self.disabled.clear();
         self.config_daemon_ids.clear();
+        self.daemon_aliases.clear();
+        self.daemon_sources.clear();
 
         for entry in all_entries {
             let daemon_id = entry.daemon.id.clone();
 
+            if let Some(path) = entry.config_path {
+                self.daemon_sources.insert(daemon_id.clone(), path);
+            }
+
+            if !entry.aliases.is_empty() {
+                self.daemon_aliases
+                    .insert(daemon_id.clone(), entry.aliases.clone());
+            }
+
             if entry.is_disabled {
                 self.disabled.push(daemon_id.clone());
             }
@@ -1278,7 +1653,7 @@ impl App {
         self.refresh_process_stats();
         self.clear_stale_message();
 
-        let total_count = self.total_daemon_count();
+        let total_count = self.dashboard_row_count();
         if total_count > 0 && self.selected >= total_count {
             self.selected = total_count - 1;
         }
@@ -1375,9 +1750,13 @@ impl App {
         self.show_available = !self.show_available;
     }
 
-    /// Get total daemon count (for selection bounds)
-    fn total_daemon_count(&self) -> usize {
-        self.filtered_daemons().len()
+    pub fn toggle_process_breakdown(&mut self) {
+        self.show_process_breakdown = !self.show_process_breakdown;
+    }
+
+    /// Number of rows in the daemon table (for selection bounds)
+    pub fn dashboard_row_count(&self) -> usize {
+        self.dashboard_rows().len()
     }
 
     pub fn scroll_logs_down(&mut self) {
@@ -1436,7 +1815,11 @@ impl App {
         if !self.log_search_query.is_empty() {
             let query = self.log_search_query.to_lowercase();
             for (i, line) in self.log_content.iter().enumerate() {
-                if line.to_lowercase().contains(&query) {
+                // Match against the visible text, not the ANSI color codes.
+                if console::strip_ansi_codes(line)
+                    .to_lowercase()
+                    .contains(&query)
+                {
                     self.log_search_matches.push(i);
                 }
             }
@@ -1490,37 +1873,75 @@ impl App {
     pub fn view_daemon_details(&mut self, daemon_id: &DaemonId) {
         self.log_daemon_id = Some(daemon_id.clone());
         self.logs_expanded = false; // Start with charts visible
-        self.load_logs(daemon_id);
+        // The lines are filled in from the supervisor's recent lines, see
+        // `apply_recent_logs`
+        self.log_content.clear();
+        self.log_levels.clear();
+        self.pending_log_load = Some(daemon_id.clone());
         self.view = View::Logs; // Logs view is now the full daemon details view
     }
 
-    fn load_logs(&mut self, daemon_id: &DaemonId) {
-        const TUI_LOG_LIMIT: usize = 5000;
-        let prev_len = self.log_content.len();
-
-        self.log_content = match LOG_STORE.query(&crate::log_store::LogQuery {
-            daemon_ids: vec![daemon_id.qualified()],
-            from: None,
-            to: None,
-            limit: Some(TUI_LOG_LIMIT),
-            order_desc: true,
-            after_id: None,
-            message_filters: Vec::new(),
-            field_filters: Vec::new(),
-            include_structured: false,
-        }) {
-            Ok(entries) if !entries.is_empty() => {
-                let mut lines: Vec<String> = entries
+    /// Show the recent lines the supervisor keeps in memory for the daemon
+    /// whose log view was opened, or its log store history if it has none
+    pub fn apply_recent_logs(&mut self, daemon_id: &DaemonId, lines: Result<Vec<RecentLogLine>>) {
+        if self.log_daemon_id.as_ref() != Some(daemon_id) {
+            return;
+        }
+        match lines {
+            Ok(lines) if !lines.is_empty() => {
+                let entries = lines
                     .into_iter()
-                    .map(|e| {
-                        let ts = e.timestamp.format("%H:%M:%S").to_string();
-                        format!("{} {}", ts, e.message)
-                    })
+                    .map(|l| l.into_log_entry(daemon_id))
                     .collect();
-                lines.reverse();
-                lines
+                self.show_logs(daemon_id, entries);
+            }
+            Ok(_) => self.load_logs(daemon_id),
+            Err(e) => {
+                debug!("recent logs unavailable from supervisor: {e}");
+                self.load_logs(daemon_id);
             }
-            _ => vec!["No logs available".to_string()],
+        }
+    }
+
+    fn load_logs(&mut self, daemon_id: &DaemonId) {
+        // Keeps a few huge lines from being loaded in full
+        const TUI_LOG_BYTES: usize = 4 * 1024 * 1024;
+        let entries = LOG_STORE
+            .query(&crate::log_store::LogQuery {
+                daemon_ids: vec![daemon_id.qualified()],
+                from: None,
+                to: None,
+                limit: Some(TUI_LOG_LIMIT),
+                max_bytes: Some(TUI_LOG_BYTES),
+                order_desc: true,
+                after_id: None,
+                message_filters: Vec::new(),
+                field_filters: Vec::new(),
+                include_structured: true,
+            })
+            .unwrap_or_default();
+        self.show_logs(daemon_id, entries.into_iter().rev().collect());
+    }
+
+    /// Replace the log view's lines with `entries`, oldest first
+    fn show_logs(&mut self, daemon_id: &DaemonId, entries: Vec<LogEntry>) {
+        let prev_len = self.log_content.len();
+        self.log_levels_classified = self
+            .daemons
+            .iter()
+            .find(|d| &d.id == daemon_id)
+            .is_some_and(|d| d.classifies_log_levels());
+
+        (self.log_content, self.log_levels) = if entries.is_empty() {
+            (vec!["No logs available".to_string()], vec![None])
+        } else {
+            entries
+                .into_iter()
+                .map(|e| {
+                    let ts = e.timestamp.format("%H:%M:%S").to_string();
+                    (format!("{} {}", ts, e.message), e.level)
+                })
+                .unzip()
         };
 
         // Auto-scroll to bottom when in follow mode
@@ -1537,10 +1958,155 @@ impl App {
         self.view = View::Help;
     }
 
+    // Command palette
+
+    pub fn open_palette(&mut self) {
+        self.palette = Some(CommandPalette::default());
+        self.view = View::Palette;
+    }
+
+    pub fn close_palette(&mut self) {
+        self.palette = None;
+        self.view = View::Dashboard;
+    }
+
+    pub fn palette_push(&mut self, c: char) {
+        if let Some(palette) = &mut self.palette {
+            palette.query.push(c);
+            palette.selected = 0;
+        }
+    }
+
+    pub fn palette_pop(&mut self) {
+        if let Some(palette) = &mut self.palette {
+            palette.query.pop();
+            palette.selected = 0;
+        }
+    }
+
+    pub fn palette_next(&mut self) {
+        let count = self.palette_items().len();
+        if let Some(palette) = &mut self.palette
+            && count > 0
+        {
+            palette.selected = (palette.selected + 1) % count;
+        }
+    }
+
+    pub fn palette_prev(&mut self) {
+        let count = self.palette_items().len();
+        if let Some(palette) = &mut self.palette
+            && count > 0
+        {
+            palette.selected = palette.selected.checked_sub(1).unwrap_or(count - 1);
+        }
+    }
+
+    /// The palette command currently highlighted, if any
+    pub fn selected_palette_command(&self) -> Option<PaletteCommand> {
+        let selected = self.palette.as_ref()?.selected;
+        self.palette_items()
+            .into_iter()
+            .nth(selected)
+            .map(|item| item.command)
+    }
+
+    /// All commands applicable to the current daemons, fuzzy-filtered by the palette query.
+    /// Commands for the selected daemon are listed first.
+    pub fn palette_items(&self) -> Vec<PaletteItem> {
+        let mut items = Vec::new();
+        let selected_id = self.selected_daemon().map(|d| d.id.clone());
+        let mut daemons: Vec<&Daemon> = self.daemons.iter().collect();
+        daemons.sort_by_key(|d| (Some(&d.id) != selected_id.as_ref(), d.id.qualified()));
+
+        let mut push = |label: String, command: PaletteCommand| {
+            items.push(PaletteItem { label, command });
+        };
+        for d in daemons {
+            let id = &d.id;
+            let name = id.qualified();
+            if d.status.is_stopped() || d.status.is_errored() || d.status.is_failed() {
+                push(format!("start {name}"), PaletteCommand::Start(id.clone()));
+            }
+            if d.status.is_up() || d.status.is_waiting() {
+                push(format!("stop {name}"), PaletteCommand::Stop(id.clone()));
+                push(
+                    format!("restart {name}"),
+                    PaletteCommand::Restart(id.clone()),
+                );
+            }
+            if self.is_disabled(id) {
+                push(format!("enable {name}"), PaletteCommand::Enable(id.clone()));
+            } else {
+                push(
+                    format!("disable {name}"),
+                    PaletteCommand::Disable(id.clone()),
+                );
+            }
+            push(format!("logs {name}"), PaletteCommand::Logs(id.clone()));
+            push(
+                format!("edit {name}"),
+                PaletteCommand::EditConfig(id.clone()),
+            );
+            push(format!("jump {name}"), PaletteCommand::JumpTo(id.clone()));
+        }
+        push("new daemon".to_string(), PaletteCommand::NewDaemon);
+        let grouping = if self.group_by_source {
+            "ungroup daemons"
+        } else {
+            "group daemons by project"
+        };
+        push(grouping.to_string(), PaletteCommand::GroupBySource);
+        push("network ports".to_string(), PaletteCommand::Network);
+        push("refresh".to_string(), PaletteCommand::Refresh);
+        push("help".to_string(), PaletteCommand::Help);
+
+        let query = self
+            .palette
+            .as_ref()
+            .map(|p| p.query.as_str())
+            .unwrap_or_default();
+        if query.is_empty() {
+            return items;
+        }
+        let matcher = SkimMatcherV2::default();
+        let mut scored: Vec<_> = items
+            .into_iter()
+            .filter_map(|item| {
+                matcher
+                    .fuzzy_match(&item.label, query)
+                    .map(|score| (item, score))
+            })
+            .collect();
+        scored.sort_by_key(|s| std::cmp::Reverse(s.1));
+        scored.into_iter().map(|(item, _)| item).collect()
+    }
+
+    /// Move the dashboard selection to a daemon, clearing the search or
+    /// expanding its group if either hides it
+    pub fn jump_to_daemon(&mut self, daemon_id: &DaemonId) {
+        let position = |app: &Self| {
+            app.dashboard_rows()
+                .iter()
+                .position(|row| matches!(row, DashboardRow::Daemon(d) if d.id == *daemon_id))
+        };
+        if position(self).is_none() {
+            self.clear_search();
+        }
+        if position(self).is_none() {
+            let key = self.group_key(daemon_id);
+            self.collapsed_groups.remove(&key);
+        }
+        if let Some(idx) = position(self) {
+            self.selected = idx;
+        }
+    }
+
     pub fn back_to_dashboard(&mut self) {
         self.view = View::Dashboard;
         self.log_daemon_id = None;
         self.log_content.clear();
+        self.log_levels.clear();
         self.log_scroll = 1;
     }
 
@@ -1548,11 +2114,7 @@ impl App {
     pub fn stats(&self) -> (usize, usize, usize, usize, usize) {
         let available = self.config_daemon_ids.len();
         let total = self.daemons.len();
-        let running = self
-            .daemons
-            .iter()
-            .filter(|d| d.status.is_running())
-            .count();
+        let running = self.daemons.iter().filter(|d| d.status.is_up()).count();
         // Don't count config-only daemons as stopped
         let stopped = self
             .daemons
@@ -1653,9 +2215,57 @@ impl App {
         let daemon_config = editor.to_daemon_config();
 
         // Parse daemon ID from string
-        let daemon_id = DaemonId::parse(&editor.daemon_id)
+        let daemon_id = editor
+            .qualified_daemon_id()
             .map_err(|e| miette::miette!("Invalid daemon ID: {}", e))?;
 
+        self.save_daemon_config(daemon_id, daemon_config)
+    }
+
+    /// Edit the daemon as TOML in `$VISUAL`/`$EDITOR` and save it once it
+    /// parses and validates. If it doesn't, the error is shown and the user
+    /// can edit again or discard. The terminal must be out of raw mode.
+    /// Returns Ok(true) if saved.
+    pub fn edit_editor_toml(&mut self) -> Result<bool> {
+        let editor = self
+            .editor_state
+            .as_ref()
+            .ok_or_else(|| miette::miette!("No editor state"))?;
+        if matches!(editor.mode, EditMode::Edit { .. }) && editor.unsaved_changes {
+            self.set_message("Save or discard form changes before editing as TOML");
+            return Ok(false);
+        }
+
+        let original = editor.to_toml()?;
+        let path = std::env::temp_dir().join(format!(
+            "pitchfork-{}-{}.toml",
+            editor.daemon_id.replace('/', "--"),
+            std::process::id()
+        ));
+        let edited = edit_until_valid(editor, &path, &original);
+        let _ = std::fs::remove_file(&path);
+        match edited? {
+            Some((daemon_id, daemon_config)) => self.save_daemon_config(daemon_id, daemon_config),
+            None => {
+                self.set_message("No changes saved");
+                Ok(false)
+            }
+        }
+    }
+
+    /// Write a daemon into the editor's config file, replacing (and possibly
+    /// renaming) the daemon being edited. Returns Ok(false) if another daemon
+    /// already has the ID.
+    fn save_daemon_config(
+        &mut self,
+        daemon_id: DaemonId,
+        daemon_config: PitchforkTomlDaemon,
+    ) -> Result<bool> {
+        let editor = self
+            .editor_state
+            .as_mut()
+            .ok_or_else(|| miette::miette!("No editor state"))?;
+
         // Read existing config (or create new)
         let mut config = PitchforkToml::read(&editor.config_path)?;
 
@@ -1685,14 +2295,13 @@ impl App {
         }
 
         // Insert/update daemon
-        config.daemons.insert(daemon_id, daemon_config);
+        config.daemons.insert(daemon_id.clone(), daemon_config);
 
         // Write back
         config.write()?;
 
         editor.unsaved_changes = false;
-        let daemon_id = editor.daemon_id.clone();
-        self.set_message(format!("Saved daemon '{daemon_id}'"));
+        self.set_message(format!("Saved daemon '{}'", daemon_id.name()));
 
         Ok(true)
     }
@@ -1728,3 +2337,51 @@ impl Default for App {
         Self::new()
     }
 }
+
+/// Open `path` in the user's editor until its content is unchanged from
+/// `original` (`None`) or is a valid daemon table.
+fn edit_until_valid(
+    editor: &EditorState,
+    path: &std::path::Path,
+    original: &str,
+) -> Result<Option<(DaemonId, PitchforkTomlDaemon)>> {
+    let mut content = original.to_string();
+    loop {
+        std::fs::write(path, &content).into_diagnostic()?;
+        run_editor(path)?;
+        content = std::fs::read_to_string(path).into_diagnostic()?;
+        if content == original {
+            return Ok(None);
+        }
+        match editor.parse_toml(&content) {
+            Ok(parsed) => return Ok(Some(parsed)),
+            Err(e) => {
+                eprintln!("{e:?}");
+                let choice = crate::ui::prompt::choose(
+                    "The daemon config is invalid.",
+                    &[('e', "edit again"), ('d', "discard changes")],
+                );
+                if choice != Some('e') {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+fn run_editor(path: &std::path::Path) -> Result<()> {
+    let mut cmd = shell_words::split(&crate::env::EDITOR).into_diagnostic()?;
+    if cmd.is_empty() {
+        miette::bail!("no editor configured, set $EDITOR");
+    }
+    let program = cmd.remove(0);
+    let status = std::process::Command::new(&program)
+        .args(cmd)
+        .arg(path)
+        .status()
+        .map_err(|e| miette::miette!("failed to run editor '{program}': {e}"))?;
+    if !status.success() {
+        miette::bail!("editor '{program}' exited with {status}");
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::Result;
 use crate::daemon_id::DaemonId;
-use crate::tui::app::{App, EditMode, PendingAction, View};
+use crate::ipc::batch::ConflictResolution;
+use crate::tui::app::{App, EditMode, PaletteCommand, PendingAction, View};
 use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseButton, MouseEventKind};
 use miette::IntoDiagnostic;
 
@@ -10,6 +11,8 @@ pub enum Action {
     Enable(DaemonId),
     Refresh,
     ConfirmPending,
+    /// Retry the start in a pending port conflict
+    ResolvePortConflict(ConflictResolution),
     // Batch actions (operate on multi-select)
     BatchStart(Vec<DaemonId>),
     BatchEnable(Vec<DaemonId>),
@@ -17,6 +20,8 @@ pub enum Action {
     OpenEditorNew,
     OpenEditorEdit(DaemonId),
     SaveConfig,
+    /// Edit the daemon as TOML in an external editor
+    EditToml,
     DeleteDaemon {
         id: String,
         config_path: std::path::PathBuf,
@@ -42,6 +47,7 @@ pub fn handle_event(app: &mut App) -> Result<Option<Action>> {
                 View::Details => handle_details_event(app, key.code),
                 View::ConfigEditor => handle_config_editor_event(app, key.code, key.modifiers),
                 View::ConfigFileSelect => handle_file_select_event(app, key.code),
+                View::Palette => handle_palette_event(app, key.code, key.modifiers),
             }
         }
         Event::Mouse(mouse) => {
@@ -62,6 +68,12 @@ pub fn handle_event(app: &mut App) -> Result<Option<Action>> {
                     }
                     Ok(None)
                 }
+                View::Palette => {
+                    if matches!(mouse.kind, MouseEventKind::Down(MouseButton::Left)) {
+                        app.close_palette();
+                    }
+                    Ok(None)
+                }
                 View::Network | View::ConfigEditor | View::ConfigFileSelect => Ok(None),
             }
         }
@@ -111,6 +123,10 @@ fn handle_dashboard_event(
             app.show_help();
             Ok(None)
         }
+        KeyCode::Char(':') => {
+            app.open_palette();
+            Ok(None)
+        }
         KeyCode::Char('j') | KeyCode::Down => {
             app.select_next();
             Ok(None)
@@ -137,10 +153,9 @@ fn handle_dashboard_event(
             Ok(None)
         }
         KeyCode::Char('s') => {
-            // Batch start if multi-select is active
-            if app.has_selection() {
-                let ids: Vec<DaemonId> = app
-                    .selected_daemon_ids()
+            // Batch start if multi-select is active or a group is under the cursor
+            if let Some(targets) = app.batch_target_ids() {
+                let ids: Vec<DaemonId> = targets
                     .into_iter()
                     .filter(|id| {
                         app.daemons
@@ -168,15 +183,14 @@ fn handle_dashboard_event(
         }
         KeyCode::Char('x') => {
             // Stop requires confirmation
-            if app.has_selection() {
-                let ids: Vec<DaemonId> = app
-                    .selected_daemon_ids()
+            if let Some(targets) = app.batch_target_ids() {
+                let ids: Vec<DaemonId> = targets
                     .into_iter()
                     .filter(|id| {
                         app.daemons
                             .iter()
                             .find(|d| d.id == *id)
-                            .map(|d| d.status.is_running() || d.status.is_waiting())
+                            .map(|d| d.status.is_up() || d.status.is_waiting())
                             .unwrap_or(false)
                     })
                     .collect();
@@ -184,7 +198,7 @@ fn handle_dashboard_event(
                     app.confirm_action(PendingAction::BatchStop(ids));
                 }
             } else if let Some(daemon) = app.selected_daemon()
-                && (daemon.status.is_running() || daemon.status.is_waiting())
+                && (daemon.status.is_up() || daemon.status.is_waiting())
             {
                 app.confirm_action(PendingAction::Stop(daemon.id.clone()));
             }
@@ -192,12 +206,12 @@ fn handle_dashboard_event(
         }
         KeyCode::Char('r') => {
             // Restart requires confirmation (for running daemons)
-            if app.has_selection() {
+            if let Some(targets) = app.batch_target_ids() {
                 let mut to_restart = Vec::new();
                 let mut to_start = Vec::new();
-                for id in app.selected_daemon_ids() {
+                for id in targets {
                     if let Some(d) = app.daemons.iter().find(|d| d.id == id) {
-                        if d.status.is_running() || d.status.is_waiting() {
+                        if d.status.is_up() || d.status.is_waiting() {
                             to_restart.push(id);
                         } else {
                             to_start.push(id);
@@ -211,7 +225,7 @@ fn handle_dashboard_event(
                     return Ok(Some(Action::BatchStart(to_start)));
                 }
             } else if let Some(daemon) = app.selected_daemon() {
-                if daemon.status.is_running() || daemon.status.is_waiting() {
+                if daemon.status.is_up() || daemon.status.is_waiting() {
                     app.confirm_action(PendingAction::Restart(daemon.id.clone()));
                 } else {
                     // If not running, just start it (no confirmation needed)
@@ -221,9 +235,8 @@ fn handle_dashboard_event(
             Ok(None)
         }
         KeyCode::Char('e') => {
-            if app.has_selection() {
-                let ids: Vec<DaemonId> = app
-                    .selected_daemon_ids()
+            if let Some(targets) = app.batch_target_ids() {
+                let ids: Vec<DaemonId> = targets
                     .into_iter()
                     .filter(|id| app.is_disabled(id))
                     .collect();
@@ -239,9 +252,8 @@ fn handle_dashboard_event(
         }
         KeyCode::Char('d') => {
             // Disable requires confirmation
-            if app.has_selection() {
-                let ids: Vec<DaemonId> = app
-                    .selected_daemon_ids()
+            if let Some(targets) = app.batch_target_ids() {
+                let ids: Vec<DaemonId> = targets
                     .into_iter()
                     .filter(|id| !app.is_disabled(id))
                     .collect();
@@ -259,9 +271,16 @@ fn handle_dashboard_event(
             if let Some(daemon) = app.selected_daemon() {
                 let id = daemon.id.clone();
                 app.view_daemon_details(&id);
+            } else {
+                app.toggle_group_collapsed();
             }
             Ok(None)
         }
+        // Group the table by source config
+        KeyCode::Char('g') => {
+            app.toggle_group_by_source();
+            Ok(None)
+        }
         KeyCode::Char('R') => Ok(Some(Action::Refresh)),
         // Config editor
         KeyCode::Char('n') => {
@@ -304,6 +323,83 @@ fn handle_dashboard_event(
     }
 }
 
+fn handle_palette_event(
+    app: &mut App,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+) -> Result<Option<Action>> {
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        match key {
+            KeyCode::Char('n') => app.palette_next(),
+            KeyCode::Char('p') => app.palette_prev(),
+            _ => {}
+        }
+        return Ok(None);
+    }
+
+    match key {
+        KeyCode::Esc => {
+            app.close_palette();
+            Ok(None)
+        }
+        KeyCode::Down | KeyCode::Tab => {
+            app.palette_next();
+            Ok(None)
+        }
+        KeyCode::Up | KeyCode::BackTab => {
+            app.palette_prev();
+            Ok(None)
+        }
+        KeyCode::Backspace => {
+            if app.palette.as_ref().is_some_and(|p| p.query.is_empty()) {
+                app.close_palette();
+            } else {
+                app.palette_pop();
+            }
+            Ok(None)
+        }
+        KeyCode::Char(c) => {
+            app.palette_push(c);
+            Ok(None)
+        }
+        KeyCode::Enter => {
+            let command = app.selected_palette_command();
+            app.close_palette();
+            match command {
+                Some(command) => run_palette_command(app, command),
+                None => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Run a palette command through the same flows as its dashboard keybinding
+fn run_palette_command(app: &mut App, command: PaletteCommand) -> Result<Option<Action>> {
+    match command {
+        PaletteCommand::Start(id) => return Ok(Some(Action::Start(id))),
+        PaletteCommand::Stop(id) => app.confirm_action(PendingAction::Stop(id)),
+        PaletteCommand::Restart(id) => app.confirm_action(PendingAction::Restart(id)),
+        PaletteCommand::Enable(id) => return Ok(Some(Action::Enable(id))),
+        PaletteCommand::Disable(id) => app.confirm_action(PendingAction::Disable(id)),
+        PaletteCommand::Logs(id) => {
+            app.jump_to_daemon(&id);
+            app.view_daemon_details(&id);
+        }
+        PaletteCommand::EditConfig(id) => return Ok(Some(Action::OpenEditorEdit(id))),
+        PaletteCommand::JumpTo(id) => app.jump_to_daemon(&id),
+        PaletteCommand::NewDaemon => return Ok(Some(Action::OpenEditorNew)),
+        PaletteCommand::GroupBySource => app.toggle_group_by_source(),
+        PaletteCommand::Network => {
+            app.view = View::Network;
+            app.prev_view = View::Dashboard;
+        }
+        PaletteCommand::Refresh => return Ok(Some(Action::Refresh)),
+        PaletteCommand::Help => app.show_help(),
+    }
+    Ok(None)
+}
+
 fn handle_search_input(app: &mut App, key: KeyCode) -> Result<Option<Action>> {
     match key {
         KeyCode::Esc => {
@@ -567,6 +663,10 @@ fn handle_details_event(app: &mut App, key: KeyCode) -> Result<Option<Action>> {
             app.hide_details();
             Ok(None)
         }
+        KeyCode::Char('p') => {
+            app.toggle_process_breakdown();
+            Ok(None)
+        }
         _ => Ok(None),
     }
 }
@@ -582,11 +682,43 @@ fn handle_help_event(app: &mut App, key: KeyCode) -> Result<Option<Action>> {
 }
 
 fn handle_confirm_event(app: &mut App, key: KeyCode) -> Result<Option<Action>> {
+    if matches!(app.pending_action, Some(PendingAction::PortConflict { .. })) {
+        return match key {
+            KeyCode::Char('k') | KeyCode::Char('K') => {
+                Ok(Some(Action::ResolvePortConflict(ConflictResolution::Kill)))
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                Ok(Some(Action::ResolvePortConflict(ConflictResolution::Bump)))
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app.cancel_confirm();
+                Ok(None)
+            }
+            _ => Ok(None),
+        };
+    }
     match key {
         KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
             // User confirmed - execute the pending action
             Ok(Some(Action::ConfirmPending))
         }
+        // Scroll the list of affected daemons of a batch operation
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.scroll_batch_confirm(1);
+            Ok(None)
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.scroll_batch_confirm(-1);
+            Ok(None)
+        }
+        KeyCode::PageDown => {
+            app.scroll_batch_confirm(10);
+            Ok(None)
+        }
+        KeyCode::PageUp => {
+            app.scroll_batch_confirm(-10);
+            Ok(None)
+        }
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
             // User cancelled
             app.cancel_confirm();
@@ -609,8 +741,7 @@ fn handle_dashboard_mouse(app: &mut App, kind: MouseEventKind, row: u16) -> Resu
             let table_start = 8_u16;
             if row >= table_start {
                 let clicked_index = (row - table_start) as usize;
-                let filtered_count = app.filtered_daemons().len();
-                if clicked_index < filtered_count {
+                if clicked_index < app.dashboard_row_count() {
                     app.selected = clicked_index;
                 }
             }
@@ -667,6 +798,9 @@ fn handle_config_editor_event(
     if modifiers.contains(KeyModifiers::CONTROL) && key == KeyCode::Char('s') {
         return Ok(Some(Action::SaveConfig));
     }
+    if modifiers.contains(KeyModifiers::CONTROL) && key == KeyCode::Char('e') {
+        return Ok(Some(Action::EditToml));
+    }
 
     // Handle text input mode
     if editor.is_editing() {
@@ -7,6 +7,7 @@ use crate::daemon_id::DaemonId;
 use crate::daemon_list::DaemonListEntry;
 use crate::ipc::batch::{StartOptions, StartResult, StopResult};
 use crate::ipc::client::IpcClient;
+use crate::log_store::recent::RecentLogLine;
 use crate::settings::settings;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -61,6 +62,10 @@ enum TaskResult {
     BatchDisable {
         count: usize,
     },
+    RecentLogs {
+        id: DaemonId,
+        result: crate::Result<Vec<RecentLogLine>>,
+    },
     Refresh {
         result: crate::Result<Vec<DaemonListEntry>>,
         /// Whether this refresh completing should clear `in_flight`.
@@ -143,6 +148,14 @@ async fn run_app(
                     app.stop_loading();
                     in_flight = false;
                     match result {
+                        Ok(r) if !r.port_conflicts().is_empty() => {
+                            let (id, conflict) = r.port_conflicts().remove(0);
+                            app.confirm_action(app::PendingAction::PortConflict {
+                                id,
+                                conflict,
+                                restart: false,
+                            });
+                        }
                         Ok(r) if r.any_failed => {
                             app.set_message(format!("Failed to start {id}"));
                         }
@@ -172,6 +185,14 @@ async fn run_app(
                     app.stop_loading();
                     in_flight = false;
                     match result {
+                        Ok(r) if !r.port_conflicts().is_empty() => {
+                            let (id, conflict) = r.port_conflicts().remove(0);
+                            app.confirm_action(app::PendingAction::PortConflict {
+                                id,
+                                conflict,
+                                restart: true,
+                            });
+                        }
                         Ok(r) if r.any_failed => {
                             app.set_message(format!("Failed to restart {id}"));
                         }
@@ -289,6 +310,9 @@ async fn run_app(
                     }
                     last_refresh = std::time::Instant::now();
                 }
+                TaskResult::RecentLogs { id, result } => {
+                    app.apply_recent_logs(&id, result);
+                }
                 TaskResult::RefreshNetwork(listeners) => {
                     app.apply_network_refresh(listeners);
                 }
@@ -389,6 +413,19 @@ async fn run_app(
                         }
                     }
                 }
+                event::Action::EditToml => {
+                    suspend_terminal(terminal)?;
+                    let result = app.edit_editor_toml();
+                    resume_terminal(terminal)?;
+                    match result {
+                        Ok(true) => {
+                            app.close_editor();
+                            spawn_refresh(Arc::clone(client), tx.clone(), false);
+                        }
+                        Ok(false) => {}
+                        Err(e) => app.set_message(format!("Edit failed: {e}")),
+                    }
+                }
                 event::Action::DeleteDaemon { id, config_path } => {
                     app.confirm_action(app::PendingAction::DeleteDaemon { id, config_path });
                 }
@@ -495,14 +532,53 @@ async fn run_app(
                             app::PendingAction::DiscardEditorChanges => {
                                 app.close_editor();
                             }
+                            // Resolved with ResolvePortConflict instead
+                            app::PendingAction::PortConflict { .. } => {}
                         }
                     }
                 }
+                event::Action::ResolvePortConflict(resolution) if !in_flight => {
+                    if let Some(app::PendingAction::PortConflict { id, restart, .. }) =
+                        app.take_pending_action()
+                    {
+                        in_flight = true;
+                        app.start_loading(format!("Starting {id}..."));
+                        let client = Arc::clone(client);
+                        let tx = tx.clone();
+                        tokio::spawn(async move {
+                            // The conflicting daemon is already stopped, so a
+                            // restart doesn't need to force again.
+                            let mut opts = StartOptions {
+                                quiet: true,
+                                ..StartOptions::default()
+                            };
+                            resolution.apply(&mut opts);
+                            let result =
+                                client.start_daemons(std::slice::from_ref(&id), opts).await;
+                            let _ = if restart {
+                                tx.send(TaskResult::Restart { id, result })
+                            } else {
+                                tx.send(TaskResult::Start { id, result })
+                            };
+                        });
+                    }
+                }
                 // Ignore IPC actions when in_flight (navigation/local actions fall through)
                 _ => {}
             }
         }
 
+        // A log view was opened: fill it from the supervisor's recent lines
+        // rather than reading the log store on the UI thread
+        if let Some(id) = app.pending_log_load.take() {
+            let client = Arc::clone(client);
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let result = client.get_recent_logs(id.clone(), app::TUI_LOG_LIMIT).await;
+                let _ = tx.send(TaskResult::RecentLogs { id, result });
+            });
+        }
+
         // Auto-refresh daemon list (skip if IPC operation in flight)
         if last_refresh.elapsed() >= refresh_rate && !in_flight {
             let is_network = app.view == app::View::Network;
@@ -533,6 +609,30 @@ async fn run_app(
     Ok(())
 }
 
+/// Hand the terminal over to a child process such as an editor.
+fn suspend_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    disable_raw_mode().into_diagnostic()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )
+    .into_diagnostic()?;
+    terminal.show_cursor().into_diagnostic()
+}
+
+/// Take the terminal back after [`suspend_terminal`] and redraw from scratch.
+fn resume_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    enable_raw_mode().into_diagnostic()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )
+    .into_diagnostic()?;
+    terminal.clear().into_diagnostic()
+}
+
 fn spawn_refresh(
     client: Arc<IpcClient>,
     tx: tokio::sync::mpsc::UnboundedSender<TaskResult>,
@@ -1,8 +1,11 @@
+use crate::ansi::{self, AnsiColor, AnsiStyle};
 use crate::daemon_id::DaemonId;
 use crate::daemon_status::DaemonStatus;
+use crate::ipc::batch::PortConflict;
 use crate::pitchfork_toml::{CronRetrigger, PitchforkToml, PitchforkTomlAuto};
 use crate::tui::app::{
-    App, EditMode, FormFieldValue, PendingAction, SortColumn, StatsHistory, View,
+    App, DaemonGroup, DashboardRow, EditMode, FormFieldValue, PendingAction, SortColumn,
+    StatsHistory, View,
 };
 use listeners::Listener;
 use ratatui::{
@@ -67,6 +70,7 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         View::Details => draw_details_overlay(f, app),
         View::ConfigEditor => draw_config_editor_overlay(f, app),
         View::ConfigFileSelect => draw_file_select_overlay(f, app),
+        View::Palette => draw_palette_overlay(f, app),
         _ => {}
     }
 
@@ -137,7 +141,8 @@ fn draw_main(f: &mut Frame, area: Rect, app: &mut App) {
         | View::Confirm
         | View::Details
         | View::ConfigEditor
-        | View::ConfigFileSelect => draw_daemon_table(f, area, app),
+        | View::ConfigFileSelect
+        | View::Palette => draw_daemon_table(f, area, app),
         View::Logs => draw_logs(f, area, app),
         View::Network => draw_network(f, area, app),
         View::Help => draw_daemon_table(f, area, app), // Help is an overlay
@@ -222,8 +227,18 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
         }
     };
 
-    let rows = filtered.iter().enumerate().map(|(i, daemon)| {
+    let dashboard_rows = app.dashboard_rows();
+    // Group headers put their config path in the last (Error) column; keep
+    // its file name visible by trimming from the front.
+    let source_width = (usize::from(table_area.width) * 18 / 100).max(12);
+    let rows = dashboard_rows.iter().enumerate().map(|(i, row)| {
         let cursor_here = i == app.selected;
+        let daemon = match row {
+            DashboardRow::Group(group) => {
+                return group_row(group, cursor_here, show_checkbox, source_width);
+            }
+            DashboardRow::Daemon(daemon) => *daemon,
+        };
         let is_multi_selected = app.is_selected(&daemon.id);
         let disabled = app.is_disabled(&daemon.id);
         let is_config_only = app.is_config_only(&daemon.id);
@@ -243,7 +258,10 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
         // instead, so drop the per-row prefix and lead with the daemon name.
         let ns_style = name_style.add_modifier(Modifier::DIM);
         let mut name_spans = Vec::new();
-        if shared_namespace.is_none() {
+        // Grouped rows are indented under a header that names the namespace.
+        if app.group_by_source {
+            name_spans.push(Span::raw("  "));
+        } else if shared_namespace.is_none() {
             name_spans.push(Span::styled(daemon.id.namespace(), ns_style));
             name_spans.push(Span::styled("/", ns_style));
         }
@@ -281,7 +299,20 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
             .map(|s| s.uptime_display())
             .unwrap_or_else(|| "-".to_string());
 
-        let error = daemon.status.error_message().unwrap_or_default();
+        // The error column doubles as the alert badge while there is no error
+        let error_cell = match (daemon.error_message(), &daemon.alert) {
+            (Some(error), _) => {
+                let text = match daemon.retry_summary(chrono::Local::now()) {
+                    Some(retry) => format!("{error} (retry {retry})"),
+                    None => error,
+                };
+                Cell::from(text).style(Style::default().fg(RED))
+            }
+            (None, Some(alert)) => {
+                Cell::from(format!("⚠ {alert}")).style(Style::default().fg(YELLOW))
+            }
+            (None, None) => Cell::from(""),
+        };
 
         let row_style = if is_multi_selected {
             Style::default().bg(Color::Rgb(40, 40, 20)) // Yellow-ish for multi-select
@@ -309,7 +340,7 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
             cpu_cell,
             mem_cell,
             Cell::from(uptime).style(Style::default().fg(GRAY)),
-            Cell::from(error).style(Style::default().fg(RED)),
+            error_cell,
         ]);
 
         Row::new(cells).style(row_style).height(1)
@@ -347,7 +378,7 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
         " Daemons ".to_string()
     };
     // Rows drop their namespace prefix when it's shared; surface it once here.
-    if let Some(ns) = shared_namespace {
+    if let Some(ns) = shared_namespace.filter(|_| !app.group_by_source) {
         title = format!("{} — {ns} ", title.trim_end());
     }
 
@@ -369,8 +400,8 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
 
     // Render scrollbar if there are more items than visible
     let visible_rows = table_area.height.saturating_sub(3) as usize; // -3 for borders and header
-    if filtered.len() > visible_rows {
-        let mut scrollbar_state = ScrollbarState::new(filtered.len()).position(app.selected);
+    if dashboard_rows.len() > visible_rows {
+        let mut scrollbar_state = ScrollbarState::new(dashboard_rows.len()).position(app.selected);
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("▲"))
             .end_symbol(Some("▼"))
@@ -388,6 +419,46 @@ fn draw_daemon_table(f: &mut Frame, area: Rect, app: &App) {
     }
 }
 
+/// Header row for a group of daemons sharing a source config: the namespace
+/// under Name, how many are up under Status, and the config path in the last column
+fn group_row<'a>(
+    group: &DaemonGroup<'a>,
+    cursor_here: bool,
+    show_checkbox: bool,
+    source_width: usize,
+) -> Row<'a> {
+    let up = group
+        .daemons
+        .iter()
+        .filter(|d| d.status.is_up() || d.status.is_waiting())
+        .count();
+    let marker = if group.collapsed { "▸ " } else { "▾ " };
+    let name_line = Line::from(vec![
+        Span::styled(marker, Style::default().fg(ORANGE)),
+        Span::styled(group.namespace, Style::default().fg(ORANGE).bold()),
+    ]);
+    let up_color = if up == 0 { GRAY } else { GREEN };
+    let source = group
+        .config_path
+        .map(|p| truncate_path_end(&p.display().to_string(), source_width))
+        .unwrap_or_else(|| "(not in a loaded config)".to_string());
+
+    let column_count = if show_checkbox { 8 } else { 7 };
+    let offset = usize::from(show_checkbox);
+    let mut cells = vec![Cell::from(""); column_count];
+    cells[offset] = Cell::from(name_line);
+    cells[offset + 2] =
+        Cell::from(format!("{up}/{} up", group.daemons.len())).style(Style::default().fg(up_color));
+    cells[column_count - 1] = Cell::from(source).style(Style::default().fg(GRAY));
+
+    let row_style = if cursor_here {
+        Style::default().bg(Color::Rgb(50, 20, 20))
+    } else {
+        Style::default().bg(Color::Rgb(25, 25, 25))
+    };
+    Row::new(cells).style(row_style).height(1)
+}
+
 fn draw_search_bar(f: &mut Frame, area: Rect, app: &App) {
     let search_text = if app.search_active {
         format!("/{}_", app.search_query)
@@ -417,10 +488,13 @@ fn draw_search_bar(f: &mut Frame, area: Rect, app: &App) {
 
 fn status_display(status: &DaemonStatus) -> (String, Color) {
     match status {
+        DaemonStatus::Starting => ("starting".to_string(), YELLOW),
         DaemonStatus::Running => ("running".to_string(), GREEN),
+        DaemonStatus::Unhealthy => ("unhealthy".to_string(), ORANGE),
         DaemonStatus::Stopped => ("stopped".to_string(), GRAY),
         DaemonStatus::Waiting => ("waiting".to_string(), YELLOW),
         DaemonStatus::Stopping => ("stopping".to_string(), YELLOW),
+        DaemonStatus::Detached => ("detached".to_string(), CYAN),
         DaemonStatus::Failed(_) => ("failed".to_string(), RED),
         DaemonStatus::Errored(code) if *code != -1 => (format!("errored ({code})"), RED),
         DaemonStatus::Errored(_) => ("errored".to_string(), RED),
@@ -766,6 +840,15 @@ fn draw_stats_panel(f: &mut Frame, area: Rect, app: &App, daemon_id: &str) {
                 Style::default().fg(memory_color(stats.memory_bytes)),
             ),
         ]));
+        if stats.process_count > 1 {
+            lines.push(Line::from(vec![
+                Span::styled("Procs:  ", Style::default().fg(GRAY)),
+                Span::styled(
+                    stats.process_count.to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ]));
+        }
         lines.push(Line::from(vec![
             Span::styled("Disk R: ", Style::default().fg(GRAY)),
             Span::styled(stats.disk_read_display(), Style::default().fg(GREEN)),
@@ -1048,7 +1131,21 @@ fn draw_log_panel(f: &mut Frame, area: Rect, app: &App, daemon_id: &str) {
     } else {
         String::new()
     };
-    let title = format!(" Logs: {daemon_id}{follow_indicator}{search_indicator} ");
+    let (errors, warnings) =
+        app.log_levels
+            .iter()
+            .fold((0, 0), |(e, w), level| match level.as_deref() {
+                Some("error") => (e + 1, w),
+                Some("warn") => (e, w + 1),
+                _ => (e, w),
+            });
+    let level_indicator = match (errors, warnings) {
+        (0, 0) => String::new(),
+        (e, 0) => format!(" [{e} errors]"),
+        (0, w) => format!(" [{w} warnings]"),
+        (e, w) => format!(" [{e} errors, {w} warnings]"),
+    };
+    let title = format!(" Logs: {daemon_id}{level_indicator}{follow_indicator}{search_indicator} ");
 
     let log_skip = app.log_scroll.saturating_sub(LOG_VIEWPORT_MAX_LINES);
     let log_take = app.log_scroll.clamp(1, LOG_VIEWPORT_MAX_LINES);
@@ -1060,7 +1157,6 @@ fn draw_log_panel(f: &mut Frame, area: Rect, app: &App, daemon_id: &str) {
         .enumerate()
         .skip(log_skip)
         .take(log_take)
-        .map(|(line_idx, line)| (line_idx, clean_log_line(line)))
         .map(|(line_idx, line)| highlight_log_line(line, line_idx, app))
         .collect();
     if visible_lines.len() < LOG_VIEWPORT_MAX_LINES {
@@ -1180,25 +1276,47 @@ fn draw_log_search_bar(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(search_bar, area);
 }
 
-fn clean_log_line(line: &str) -> std::borrow::Cow<'_, str> {
-    let replacements = [
-        ("\x1b[2J", ""), // Clear screen
-        ("\t", "    "),  // Replace tabs with spaces
-    ];
+/// Map an ANSI color onto the terminal's own palette (indexed) or truecolor.
+fn ansi_color(color: AnsiColor) -> Color {
+    match color {
+        AnsiColor::Indexed(n) => Color::Indexed(n),
+        AnsiColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
 
-    // using COW can avoid one allocation for the first replacement
-    // if there are no replacements, it will not allocate at all
-    let mut cow_line = std::borrow::Cow::Borrowed(line);
-    for (target, replacement) in &replacements {
-        if cow_line.contains(target) {
-            cow_line = std::borrow::Cow::Owned(cow_line.replace(target, replacement));
-        }
+/// Layer the SGR attributes of a log span over the line's base style.
+/// Colors emitted by the daemon win over level-based coloring, but the
+/// search highlight background is kept so matches stay visible.
+fn apply_ansi_style(base: Style, ansi: &AnsiStyle, keep_bg: bool) -> Style {
+    let mut style = base;
+    if let Some(fg) = ansi.fg {
+        style = style.fg(ansi_color(fg));
+    }
+    if let Some(bg) = ansi.bg
+        && !keep_bg
+    {
+        style = style.bg(ansi_color(bg));
+    }
+    if ansi.bold {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if ansi.dim {
+        style = style.add_modifier(Modifier::DIM);
+    }
+    if ansi.italic {
+        style = style.add_modifier(Modifier::ITALIC);
     }
-    cow_line
+    if ansi.underline {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    if ansi.reverse {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
 }
 
 /// Highlight a log line with syntax coloring and search match highlighting
-fn highlight_log_line(line: std::borrow::Cow<str>, line_idx: usize, app: &App) -> Line<'static> {
+fn highlight_log_line(line: &str, line_idx: usize, app: &App) -> Line<'static> {
     let is_match = app.log_search_matches.contains(&line_idx);
     let is_current_match = app
         .log_search_matches
@@ -1206,19 +1324,43 @@ fn highlight_log_line(line: std::borrow::Cow<str>, line_idx: usize, app: &App) -
         .map(|&idx| idx == line_idx)
         .unwrap_or(false);
 
-    // Determine base style based on log level
-    let line_lower = line.to_lowercase();
-    let base_style = if line_lower.contains("error")
-        || line_lower.contains("fatal")
-        || line_lower.contains("panic")
-    {
-        Style::default().fg(RED)
-    } else if line_lower.contains("warn") {
-        Style::default().fg(YELLOW)
-    } else if line_lower.contains("debug") || line_lower.contains("trace") {
-        Style::default().fg(DARK_GRAY)
-    } else {
-        Style::default().fg(Color::White)
+    // Log lines are "<HH:MM:SS> <message>"; the timestamp is added by the TUI
+    // and never contains escape codes, so split it off before ANSI parsing.
+    let (timestamp, message) = match line.split_once(' ') {
+        Some((ts, msg)) if ts.len() == 8 && ts.chars().all(|c| c.is_ascii_digit() || c == ':') => {
+            (Some(ts), msg)
+        }
+        _ => (None, line),
+    };
+    let ansi_spans = ansi::parse_spans(message);
+    let plain: String = ansi_spans.iter().map(|s| s.text.as_str()).collect();
+
+    // Determine base style based on log level. Use the level classified on
+    // ingest when the daemon has one; only guess from the text otherwise.
+    let level = match app.log_levels.get(line_idx).and_then(|l| l.as_deref()) {
+        Some(level) => Some(level),
+        None if app.log_levels_classified => None,
+        None => {
+            let line_lower = plain.to_lowercase();
+            if line_lower.contains("error")
+                || line_lower.contains("fatal")
+                || line_lower.contains("panic")
+            {
+                Some("error")
+            } else if line_lower.contains("warn") {
+                Some("warn")
+            } else if line_lower.contains("debug") || line_lower.contains("trace") {
+                Some("debug")
+            } else {
+                None
+            }
+        }
+    };
+    let base_style = match level {
+        Some("error") => Style::default().fg(RED),
+        Some("warn") => Style::default().fg(YELLOW),
+        Some("debug" | "trace") => Style::default().fg(DARK_GRAY),
+        _ => Style::default().fg(Color::White),
     };
 
     // Apply search highlight
@@ -1230,30 +1372,16 @@ fn highlight_log_line(line: std::borrow::Cow<str>, line_idx: usize, app: &App) -
         base_style
     };
 
-    // Highlight timestamps (common patterns like 2024-01-15 or HH:MM:SS)
-    let mut spans = Vec::new();
-
-    // Simple timestamp detection at start of line (use char-based iteration for UTF-8 safety)
-    let chars: Vec<char> = line.chars().collect();
-    if chars.len() >= 10 {
-        let potential_date: String = chars[..10].iter().collect();
-        if potential_date.chars().filter(|c| *c == '-').count() == 2
-            && potential_date
-                .chars()
-                .filter(|c| c.is_ascii_digit())
-                .count()
-                == 8
-        {
-            spans.push(Span::styled(potential_date, Style::default().fg(GRAY)));
-            let remaining: String = chars[10..].iter().collect();
-            if !remaining.is_empty() {
-                spans.push(Span::styled(remaining, style));
-            }
-        } else {
-            spans.push(Span::styled(line.to_string(), style));
-        }
-    } else {
-        spans.push(Span::styled(line.to_string(), style));
+    let mut spans = Vec::with_capacity(ansi_spans.len() + 2);
+    if let Some(ts) = timestamp {
+        spans.push(Span::styled(ts.to_string(), Style::default().fg(GRAY)));
+        spans.push(Span::styled(" ", style));
+    }
+    for span in ansi_spans {
+        spans.push(Span::styled(
+            span.text,
+            apply_ansi_style(style, &span.style, is_match),
+        ));
     }
 
     Line::from(spans)
@@ -1277,8 +1405,11 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
         View::Dashboard if !app.search_query.is_empty() => {
             "/:search  q/Esc:clear  j/k:nav  Space:select  s:start  a:toggle-avail  p:ports  ?:help"
         }
+        View::Dashboard if app.selected_group().is_some() => {
+            "Enter:collapse  s:start all  x:stop all  r:restart all  g:ungroup  ?:help"
+        }
         View::Dashboard => {
-            "/:search  q/Esc:quit  j/k:nav  Space:select  s:start  a:toggle-avail  p:ports  ?:help"
+            "/:search  ::commands  q/Esc:quit  j/k:nav  Space:select  s:start  g:group  ?:help"
         }
         View::Logs if app.log_search_active => "Type to search  Enter:finish  Esc:clear",
         View::Logs if !app.log_search_query.is_empty() => {
@@ -1296,10 +1427,24 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
         }
         View::Network => "/:search  q/Esc:back  j/k:nav  g/G:top/btm  r:refresh",
         View::Help => "q/Esc/?:close",
+        View::Confirm if matches!(app.pending_action, Some(PendingAction::PortConflict { .. })) => {
+            "k:kill process  b:next free port  n/Esc:cancel"
+        }
+        View::Confirm
+            if app
+                .pending_action
+                .as_ref()
+                .is_some_and(|a| a.batch_ids().is_some()) =>
+        {
+            "y/Enter:confirm  n/Esc:cancel  j/k:scroll"
+        }
         View::Confirm => "y/Enter:confirm  n/Esc:cancel",
-        View::Details => "q/Esc/i:close",
-        View::ConfigEditor => "Tab/j/k:nav  Enter:edit  Ctrl+S:save  Esc:cancel  D:delete",
+        View::Details => "q/Esc/i:close  p:processes",
+        View::ConfigEditor => {
+            "Tab/j/k:nav  Enter:edit  Ctrl+S:save  Ctrl+E:edit TOML  Esc:cancel  D:delete"
+        }
         View::ConfigFileSelect => "j/k:nav  Enter:select  Esc:cancel",
+        View::Palette => "Type to filter  ↑/↓:nav  Enter:run  Esc:close",
     };
 
     let footer = Paragraph::new(help_text)
@@ -1332,6 +1477,8 @@ fn draw_help_overlay(f: &mut Frame) {
         Line::from("  S           Cycle sort column"),
         Line::from("  o           Toggle sort order"),
         Line::from("  a           Toggle available daemons"),
+        Line::from("  g           Group daemons by project config"),
+        Line::from("  Enter       Collapse/expand group (on a group header)"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Multi-select",
@@ -1346,6 +1493,7 @@ fn draw_help_overlay(f: &mut Frame) {
             Style::default().fg(RED).bold(),
         )]),
         Line::from("  s           Start stopped daemon(s)"),
+        Line::from("              (on a group header: the whole group)"),
         Line::from("  x           Stop running daemon(s)"),
         Line::from("  r           Restart daemon(s)"),
         Line::from("  e           Enable disabled daemon(s)"),
@@ -1363,6 +1511,7 @@ fn draw_help_overlay(f: &mut Frame) {
             "General",
             Style::default().fg(RED).bold(),
         )]),
+        Line::from("  :           Open command palette"),
         Line::from("  p           Show network ports view"),
         Line::from("  ?           Toggle this help"),
         Line::from("  q           Quit / Go back"),
@@ -1432,23 +1581,38 @@ fn draw_loading_overlay(f: &mut Frame, app: &App) {
 }
 
 fn draw_confirm_overlay(f: &mut Frame, app: &App) {
+    if let Some(action) = &app.pending_action
+        && let Some(ids) = action.batch_ids()
+    {
+        draw_batch_confirm(f, app, action, ids);
+        return;
+    }
+
     let area = centered_rect(50, 30, f.area());
 
     // Clear the background
     f.render_widget(Clear, area);
 
+    if let Some(PendingAction::PortConflict { id, conflict, .. }) = &app.pending_action {
+        draw_port_conflict(f, area, id, conflict);
+        return;
+    }
+
     let (action_text, target_text) = match &app.pending_action {
         Some(PendingAction::Stop(id)) => ("Stop", format!("daemon '{id}'")),
         Some(PendingAction::Restart(id)) => ("Restart", format!("daemon '{id}'")),
         Some(PendingAction::Disable(id)) => ("Disable", format!("daemon '{id}'")),
-        Some(PendingAction::BatchStop(ids)) => ("Stop", format!("{} daemons", ids.len())),
-        Some(PendingAction::BatchRestart(ids)) => ("Restart", format!("{} daemons", ids.len())),
-        Some(PendingAction::BatchDisable(ids)) => ("Disable", format!("{} daemons", ids.len())),
         Some(PendingAction::DeleteDaemon { id, .. }) => {
             ("Delete", format!("daemon '{id}' from config"))
         }
         Some(PendingAction::DiscardEditorChanges) => ("Discard", "unsaved changes".to_string()),
-        None => ("Unknown", "unknown".to_string()),
+        Some(
+            PendingAction::PortConflict { .. }
+            | PendingAction::BatchStop(_)
+            | PendingAction::BatchRestart(_)
+            | PendingAction::BatchDisable(_),
+        )
+        | None => ("Unknown", "unknown".to_string()),
     };
 
     let text = vec![
@@ -1487,6 +1651,151 @@ fn draw_confirm_overlay(f: &mut Frame, app: &App) {
     f.render_widget(confirm, area);
 }
 
+/// Confirm overlay for batch operations: lists every affected daemon with its
+/// current status, flagging unsaved editor changes and running dependents
+fn draw_batch_confirm(f: &mut Frame, app: &App, action: &PendingAction, ids: &[DaemonId]) {
+    let area = centered_rect(60, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let action_text = match action {
+        PendingAction::BatchStop(_) => "Stop",
+        PendingAction::BatchRestart(_) => "Restart",
+        _ => "Disable",
+    };
+
+    let block = Block::default()
+        .title(" Confirm ")
+        .title_style(Style::default().fg(ORANGE).bold())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(RED))
+        .style(Style::default().bg(Color::Rgb(30, 20, 20)));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+    let heading = Paragraph::new(Line::from(vec![
+        Span::styled(action_text, Style::default().fg(ORANGE).bold()),
+        Span::raw(" "),
+        Span::styled(
+            format!("{} daemons", ids.len()),
+            Style::default().fg(Color::White).bold(),
+        ),
+        Span::raw("?"),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(heading, chunks[0]);
+
+    let visible = chunks[1].height as usize;
+    let scroll = app
+        .batch_confirm
+        .scroll
+        .min(ids.len().saturating_sub(visible));
+    let lines: Vec<Line> = ids
+        .iter()
+        .skip(scroll)
+        .take(visible)
+        .map(|id| {
+            let (status, color) = app
+                .daemons
+                .iter()
+                .find(|d| d.id == *id)
+                .map(|d| status_display(&d.status))
+                .unwrap_or_else(|| ("unknown".to_string(), GRAY));
+            let mut spans = vec![
+                Span::styled(format!(" {id}"), Style::default().fg(Color::White)),
+                Span::raw("  "),
+                Span::styled(status, Style::default().fg(color)),
+            ];
+            if app.has_unsaved_changes(id) {
+                spans.push(Span::styled(
+                    "  unsaved changes",
+                    Style::default().fg(ORANGE).bold(),
+                ));
+            }
+            if let Some(dependents) = app.batch_confirm.dependents.get(id) {
+                let names: Vec<String> = dependents.iter().map(|d| d.to_string()).collect();
+                spans.push(Span::styled(
+                    format!("  needed by {}", names.join(", ")),
+                    Style::default().fg(YELLOW),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+
+    if ids.len() > visible {
+        let mut scrollbar_state = ScrollbarState::new(ids.len()).position(scroll);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("▲"))
+            .end_symbol(Some("▼"))
+            .track_symbol(Some("│"))
+            .thumb_symbol("█")
+            .style(Style::default().fg(GRAY));
+        f.render_stateful_widget(scrollbar, chunks[1], &mut scrollbar_state);
+    }
+
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("y", Style::default().fg(GREEN).bold()),
+        Span::raw(" / "),
+        Span::styled("Enter", Style::default().fg(GREEN).bold()),
+        Span::raw(" to confirm, "),
+        Span::styled("n", Style::default().fg(RED).bold()),
+        Span::raw(" / "),
+        Span::styled("Esc", Style::default().fg(RED).bold()),
+        Span::raw(" to cancel"),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(footer, chunks[2]);
+}
+
+fn draw_port_conflict(f: &mut Frame, area: Rect, id: &DaemonId, conflict: &PortConflict) {
+    let text = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(id.to_string(), Style::default().fg(Color::White).bold()),
+            Span::raw(": port "),
+            Span::styled(
+                conflict.port.to_string(),
+                Style::default().fg(ORANGE).bold(),
+            ),
+            Span::raw(" is in use"),
+        ]),
+        Line::from(format!("by {} (PID {})", conflict.process, conflict.pid)),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("k", Style::default().fg(RED).bold()),
+            Span::raw(" kill it and start, "),
+            Span::styled("b", Style::default().fg(GREEN).bold()),
+            Span::raw(" use the next free port, "),
+            Span::styled("Esc", Style::default().fg(GRAY).bold()),
+            Span::raw(" to cancel"),
+        ]),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title(" Port Conflict ")
+                .title_style(Style::default().fg(ORANGE).bold())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(RED)),
+        )
+        .style(Style::default().bg(Color::Rgb(30, 20, 20)));
+
+    f.render_widget(dialog, area);
+}
+
 fn draw_details_overlay(f: &mut Frame, app: &App) {
     let area = centered_rect(70, 80, f.area());
 
@@ -1538,9 +1847,12 @@ fn draw_details_overlay(f: &mut Frame, app: &App) {
                 format!("{:?}", d.status),
                 Style::default().fg(match &d.status {
                     crate::daemon_status::DaemonStatus::Running => GREEN,
+                    crate::daemon_status::DaemonStatus::Unhealthy => ORANGE,
                     crate::daemon_status::DaemonStatus::Stopped => GRAY,
+                    crate::daemon_status::DaemonStatus::Starting => YELLOW,
                     crate::daemon_status::DaemonStatus::Waiting => YELLOW,
                     crate::daemon_status::DaemonStatus::Stopping => YELLOW,
+                    crate::daemon_status::DaemonStatus::Detached => CYAN,
                     _ => RED,
                 }),
             ),
@@ -1563,16 +1875,63 @@ fn draw_details_overlay(f: &mut Frame, app: &App) {
                     Span::styled("Uptime: ", Style::default().fg(GRAY)),
                     Span::styled(stats.uptime_display(), Style::default().fg(Color::White)),
                 ]));
+
+                if app.show_process_breakdown {
+                    for p in app.get_process_breakdown(pid) {
+                        lines.push(Line::from(vec![
+                            Span::raw("  ".repeat(p.depth + 1)),
+                            Span::styled(format!("{} ", p.pid), Style::default().fg(GRAY)),
+                            Span::styled(p.name.clone(), Style::default().fg(Color::White)),
+                            Span::raw("  "),
+                            Span::styled(
+                                p.cpu_display(),
+                                Style::default().fg(cpu_color(p.cpu_percent)),
+                            ),
+                            Span::raw("  "),
+                            Span::styled(
+                                p.memory_display(),
+                                Style::default().fg(memory_color(p.memory_bytes)),
+                            ),
+                        ]));
+                    }
+                } else if stats.process_count > 1 {
+                    lines.push(Line::from(vec![Span::styled(
+                        format!(
+                            "  totals across {} processes (p: per-process breakdown)",
+                            stats.process_count
+                        ),
+                        Style::default().fg(GRAY).italic(),
+                    )]));
+                }
             }
         }
 
-        if let Some(err) = d.status.error_message() {
+        if let Some(err) = d.error_message() {
             lines.push(Line::from(""));
             lines.push(Line::from(vec![
                 Span::styled("Error: ", Style::default().fg(RED)),
                 Span::styled(err, Style::default().fg(RED)),
             ]));
         }
+
+        if !d.exits.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![Span::styled(
+                "Last exits",
+                Style::default().fg(RED).bold(),
+            )]));
+            let now = chrono::Local::now();
+            for exit in d.exits.iter().rev().take(5) {
+                let color = if exit.reason == "fail" { RED } else { GRAY };
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("{}: ", crate::ui::time::ago(exit.at, now)),
+                        Style::default().fg(GRAY),
+                    ),
+                    Span::styled(exit.to_string(), Style::default().fg(color)),
+                ]));
+            }
+        }
     }
 
     // Config info
@@ -1627,6 +1986,16 @@ fn draw_details_overlay(f: &mut Frame, app: &App) {
                 ),
                 Span::raw(")"),
             ]));
+            let now = chrono::Local::now();
+            if let Some(next) = crate::ui::time::next_cron_fire(&cron.schedule, now) {
+                lines.push(Line::from(vec![
+                    Span::styled("Next run: ", Style::default().fg(GRAY)),
+                    Span::styled(
+                        crate::ui::time::until(next, now),
+                        Style::default().fg(Color::White),
+                    ),
+                ]));
+            }
         }
 
         if cfg.retry.count() > 0 {
@@ -1644,7 +2013,10 @@ fn draw_details_overlay(f: &mut Frame, app: &App) {
         if let Some(delay) = cfg.ready_delay {
             lines.push(Line::from(vec![
                 Span::styled("Ready delay: ", Style::default().fg(GRAY)),
-                Span::styled(format!("{delay}s"), Style::default().fg(Color::White)),
+                Span::styled(
+                    humantime::format_duration(delay).to_string(),
+                    Style::default().fg(Color::White),
+                ),
             ]));
         }
 
@@ -1846,10 +2218,10 @@ fn draw_config_editor_overlay(f: &mut Frame, app: &App) {
     } else {
         match &editor.mode {
             EditMode::Create => {
-                "Tab/j/k: Navigate | Enter: Edit | Space: Toggle | Ctrl+S: Save | q: Cancel"
+                "Tab/j/k: Navigate | Enter: Edit | Space: Toggle | Ctrl+S: Save | Ctrl+E: Edit TOML | q: Cancel"
             }
             EditMode::Edit { .. } => {
-                "Tab/j/k: Navigate | Enter: Edit | Space: Toggle | Ctrl+S: Save | D: Delete | q: Cancel"
+                "Tab/j/k: Navigate | Enter: Edit | Space: Toggle | Ctrl+S: Save | Ctrl+E: Edit TOML | D: Delete | q: Cancel"
             }
         }
     };
@@ -1890,16 +2262,6 @@ fn render_field_value(field: &crate::tui::app::FormField, is_editing: bool) -> L
             };
             Line::from(vec![Span::raw("    "), display])
         }
-        FormFieldValue::OptionalNumber(opt) => {
-            let display = match opt {
-                Some(n) => Span::styled(format!("{n}{cursor}"), Style::default().fg(Color::White)),
-                None if is_editing => {
-                    Span::styled(cursor.to_string(), Style::default().fg(Color::White))
-                }
-                None => Span::styled("(not set)", Style::default().fg(DARK_GRAY).italic()),
-            };
-            Line::from(vec![Span::raw("    "), display])
-        }
         FormFieldValue::OptionalPort(opt) => {
             let display = match opt {
                 Some(p) => Span::styled(format!("{p}{cursor}"), Style::default().fg(Color::White)),
@@ -1981,6 +2343,61 @@ fn render_field_value(field: &crate::tui::app::FormField, is_editing: bool) -> L
     }
 }
 
+fn draw_palette_overlay(f: &mut Frame, app: &App) {
+    let palette = match &app.palette {
+        Some(p) => p,
+        None => return,
+    };
+
+    let area = centered_rect(60, 50, f.area());
+    f.render_widget(Clear, area);
+
+    let items = app.palette_items();
+    let mut lines: Vec<Line> = vec![
+        Line::from(vec![
+            Span::styled(":", Style::default().fg(ORANGE).bold()),
+            Span::styled(palette.query.as_str(), Style::default().fg(Color::White)),
+            Span::styled("█", Style::default().fg(ORANGE)),
+        ]),
+        Line::from(""),
+    ];
+
+    // Keep the highlighted command in view
+    let visible = (area.height as usize).saturating_sub(4).max(1);
+    let offset = palette.selected.saturating_sub(visible - 1);
+    for (i, item) in items.iter().enumerate().skip(offset).take(visible) {
+        let is_selected = i == palette.selected;
+        let indicator = if is_selected { "▶ " } else { "  " };
+        let style = if is_selected {
+            Style::default().fg(ORANGE).bold()
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(indicator, Style::default().fg(ORANGE)),
+            Span::styled(item.label.as_str(), style),
+        ]));
+    }
+    if items.is_empty() {
+        lines.push(Line::from(vec![Span::styled(
+            "  No matching commands",
+            Style::default().fg(GRAY),
+        )]));
+    }
+
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(" Commands ")
+                .title_style(Style::default().fg(ORANGE).bold())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(RED)),
+        )
+        .style(Style::default().bg(Color::Rgb(20, 20, 20)));
+
+    f.render_widget(popup, area);
+}
+
 fn draw_file_select_overlay(f: &mut Frame, app: &App) {
     let selector = match &app.file_selector {
         Some(s) => s,
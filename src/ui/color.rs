@@ -0,0 +1,62 @@
+//! Central decision of whether CLI output is colored.
+//!
+//! [`init`] runs once after argument parsing and records the decision in
+//! `console`'s global flags, which `console::style`, the `ui::style` helpers
+//! and tables all consult. Code that builds its own escape sequences should
+//! check [`stdout_enabled`] / [`stderr_enabled`] rather than testing for a
+//! terminal itself, so `--color always` is honored when output is piped.
+
+use std::io::IsTerminal;
+
+/// Value of the global `--color` flag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always color, even when output is piped
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Apply `choice` to stdout and stderr.
+///
+/// When stderr is not a terminal, progress output also switches to plain
+/// text lines instead of redrawn spinners.
+pub fn init(choice: ColorChoice) {
+    let stdout_tty = std::io::stdout().is_terminal();
+    let stderr_tty = std::io::stderr().is_terminal();
+    let (stdout, stderr) = match choice {
+        ColorChoice::Always => (true, true),
+        ColorChoice::Never => (false, false),
+        ColorChoice::Auto => (auto(stdout_tty), auto(stderr_tty)),
+    };
+    console::set_colors_enabled(stdout);
+    console::set_colors_enabled_stderr(stderr);
+    if !stderr_tty {
+        clx::progress::set_output(clx::progress::ProgressOutput::Text);
+    }
+}
+
+/// `auto` colors a terminal unless `NO_COLOR` is set (to anything but an
+/// empty string, per <https://no-color.org>) or `TERM=dumb`.
+/// `CLICOLOR_FORCE` forces color on, matching `console`'s own behavior.
+fn auto(is_tty: bool) -> bool {
+    if std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+    is_tty
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        && !std::env::var("TERM").is_ok_and(|term| term == "dumb")
+}
+
+/// Whether output written to stdout should be colored
+pub fn stdout_enabled() -> bool {
+    console::colors_enabled()
+}
+
+/// Whether output written to stderr should be colored
+pub fn stderr_enabled() -> bool {
+    console::colors_enabled_stderr()
+}
@@ -1,2 +1,5 @@
+pub(crate) mod color;
+pub(crate) mod prompt;
 pub(crate) mod style;
 pub(crate) mod table;
+pub(crate) mod time;
@@ -0,0 +1,36 @@
+//! Minimal interactive prompts for CLI commands
+
+use std::io::{BufRead, IsTerminal, Write};
+
+/// Whether the user can answer a prompt: stdin and stderr are terminals.
+pub(crate) fn interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Ask `question` on stderr and read a one-letter answer from stdin.
+///
+/// `choices` are `(key, label)` pairs; the first is the default for an empty
+/// answer. Asks again on an unknown answer. Returns `None` if stdin is closed.
+pub(crate) fn choose(question: &str, choices: &[(char, &str)]) -> Option<char> {
+    let options = choices
+        .iter()
+        .map(|(key, label)| format!("[{key}] {label}"))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let mut stdin = std::io::stdin().lock();
+    loop {
+        eprint!("{question}\n  {options}: ");
+        let _ = std::io::stderr().flush();
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer).ok()? == 0 {
+            return None;
+        }
+        let answer = answer.trim().to_lowercase();
+        let Some(first) = answer.chars().next() else {
+            return choices.first().map(|(key, _)| *key);
+        };
+        if let Some((key, _)) = choices.iter().find(|(key, _)| *key == first) {
+            return Some(*key);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::Result;
-use comfy_table::Table;
+use comfy_table::{Cell, ContentArrangement, Table};
 
 /// Print a comfy-table, removing leading/trailing row padding.
 ///
@@ -10,7 +10,15 @@ use comfy_table::Table;
 /// `str::trim` doesn't work on rows with ANSI color codes (from `Cell::fg`)
 /// because the trailing padding sits inside the color/reset sequence and
 /// isn't recognized as trailing whitespace.
-pub fn print_table(table: Table) -> Result<()> {
+///
+/// Cell colors follow the global color decision (`--color`, `NO_COLOR`)
+/// rather than comfy-table's own terminal check.
+pub fn print_table(mut table: Table) -> Result<()> {
+    if super::color::stdout_enabled() {
+        table.enforce_styling();
+    } else {
+        table.force_no_tty();
+    }
     let table = table.to_string();
     for line in table.lines() {
         println!("{}", trim_ansi_line(line));
@@ -70,3 +78,183 @@ fn trim_ansi_line(line: &str) -> String {
     }
     result
 }
+
+/// A column of a [`ColumnTable`], picked by `name` with `--columns` and
+/// `--sort`
+pub struct Column<T> {
+    pub name: &'static str,
+    /// Header row text, empty for a headerless column
+    pub header: &'static str,
+    pub cell: fn(&T) -> Cell,
+    /// Sort key for `--sort`; rows without one sort last
+    pub sort_key: fn(&T) -> Option<SortKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    Number(u64),
+    Text(String),
+}
+
+/// Table over a fixed set of columns, showing the ones picked with
+/// `--columns` (or the command's defaults) and sorting rows with `--sort`.
+pub struct ColumnTable<'a, T> {
+    all: &'a [Column<T>],
+    shown: Vec<&'a Column<T>>,
+    sort: Option<&'a Column<T>>,
+    reverse: bool,
+    header: bool,
+}
+
+impl<'a, T> ColumnTable<'a, T> {
+    /// Show the `selected` columns in that order, or `defaults` if none
+    /// are selected
+    pub fn new(all: &'a [Column<T>], selected: &[String], defaults: &[&str]) -> Result<Self> {
+        let shown: Result<Vec<_>> = if selected.is_empty() {
+            defaults.iter().map(|name| find(all, name)).collect()
+        } else {
+            selected.iter().map(|name| find(all, name)).collect()
+        };
+        Ok(Self {
+            all,
+            shown: shown?,
+            sort: None,
+            reverse: false,
+            header: true,
+        })
+    }
+
+    /// Sort rows by the column named `name`, which doesn't have to be shown
+    pub fn sort_by(mut self, name: Option<&str>, reverse: bool) -> Result<Self> {
+        self.sort = name.map(|name| find(self.all, name)).transpose()?;
+        self.reverse = reverse;
+        Ok(self)
+    }
+
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Whether the column named `name` is shown or sorted by, for rows
+    /// whose values are costly to compute
+    pub fn uses(&self, name: &str) -> bool {
+        self.shown.iter().chain(&self.sort).any(|c| c.name == name)
+    }
+
+    fn build(&self, mut rows: Vec<T>) -> Table {
+        if let Some(column) = self.sort {
+            rows.sort_by_cached_key(|row| {
+                let key = (column.sort_key)(row);
+                (key.is_none(), key)
+            });
+            if self.reverse {
+                let missing = rows
+                    .iter()
+                    .position(|row| (column.sort_key)(row).is_none())
+                    .unwrap_or(rows.len());
+                rows[..missing].reverse();
+            }
+        }
+        let mut table = Table::new();
+        table
+            .load_preset(comfy_table::presets::NOTHING)
+            .set_content_arrangement(ContentArrangement::Disabled);
+        if self.header {
+            table.set_header(self.shown.iter().map(|c| c.header));
+        }
+        for row in &rows {
+            table.add_row(self.shown.iter().map(|c| (c.cell)(row)));
+        }
+        table
+    }
+
+    pub fn print(&self, rows: Vec<T>) -> Result<()> {
+        print_table(self.build(rows))
+    }
+}
+
+fn find<'a, T>(all: &'a [Column<T>], name: &str) -> Result<&'a Column<T>> {
+    match all.iter().find(|c| c.name == name) {
+        Some(column) => Ok(column),
+        None => miette::bail!(
+            "unknown column '{name}', expected one of: {}",
+            all.iter().map(|c| c.name).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row(&'static str, Option<u64>);
+
+    const COLUMNS: &[Column<Row>] = &[
+        Column {
+            name: "name",
+            header: "Name",
+            cell: |r| Cell::new(r.0),
+            sort_key: |r| Some(SortKey::Text(r.0.to_string())),
+        },
+        Column {
+            name: "size",
+            header: "Size",
+            cell: |r| Cell::new(r.1.map(|n| n.to_string()).unwrap_or_default()),
+            sort_key: |r| r.1.map(SortKey::Number),
+        },
+    ];
+
+    fn rows() -> Vec<Row> {
+        vec![Row("b", Some(10)), Row("c", None), Row("a", Some(9))]
+    }
+
+    fn render(table: &ColumnTable<Row>) -> Vec<String> {
+        let mut t = table.build(rows());
+        t.force_no_tty();
+        t.to_string()
+            .lines()
+            .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn test_columns_default_and_selected() {
+        let table = ColumnTable::new(COLUMNS, &[], &["name"]).unwrap();
+        assert_eq!(render(&table), vec!["Name", "b", "c", "a"]);
+
+        let selected = vec!["size".to_string(), "name".to_string()];
+        let table = ColumnTable::new(COLUMNS, &selected, &["name"])
+            .unwrap()
+            .header(false);
+        assert_eq!(render(&table), vec!["10 b", "c", "9 a"]);
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let err = ColumnTable::new(COLUMNS, &["pid".to_string()], &["name"])
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "unknown column 'pid', expected one of: name, size"
+        );
+    }
+
+    #[test]
+    fn test_sort_keeps_missing_values_last() {
+        let table = ColumnTable::new(COLUMNS, &[], &["name"])
+            .unwrap()
+            .header(false)
+            .sort_by(Some("size"), false)
+            .unwrap();
+        assert!(table.uses("size"));
+        assert_eq!(render(&table), vec!["a", "b", "c"]);
+
+        let table = table.sort_by(Some("size"), true).unwrap();
+        assert_eq!(render(&table), vec!["b", "a", "c"]);
+
+        let table = table.sort_by(Some("name"), true).unwrap();
+        assert_eq!(render(&table), vec!["c", "b", "a"]);
+    }
+}
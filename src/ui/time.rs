@@ -0,0 +1,126 @@
+//! Human-readable durations and relative times
+//!
+//! Shared by CLI output, the TUI and the web API so uptimes and cron times
+//! read the same everywhere. JSON output carries the raw seconds alongside
+//! these strings.
+
+use chrono::{DateTime, Local};
+use std::str::FromStr;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// Compact two-unit duration: `45s`, `3m 12s`, `2h 5m`, `4d 1h`.
+pub fn duration_secs(secs: u64) -> String {
+    if secs < MINUTE {
+        format!("{secs}s")
+    } else if secs < HOUR {
+        format!("{}m {}s", secs / MINUTE, secs % MINUTE)
+    } else if secs < DAY {
+        format!("{}h {}m", secs / HOUR, (secs % HOUR) / MINUTE)
+    } else {
+        format!("{}d {}h", secs / DAY, (secs % DAY) / HOUR)
+    }
+}
+
+/// Sub-minute durations with millisecond precision (`850ms`, `4.2s`),
+/// longer ones as `duration_secs`.
+pub fn duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else if ms < MINUTE * 1000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        duration_secs(ms / 1000)
+    }
+}
+
+/// Largest whole unit only (`3m`, `2h`), for relative times where the
+/// second unit is noise.
+fn coarse_secs(secs: u64) -> String {
+    if secs < MINUTE {
+        format!("{secs}s")
+    } else if secs < HOUR {
+        format!("{}m", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{}h", secs / HOUR)
+    } else {
+        format!("{}d", secs / DAY)
+    }
+}
+
+/// Seconds from `from` to `to`, clamped at zero.
+pub fn secs_between(from: DateTime<Local>, to: DateTime<Local>) -> u64 {
+    u64::try_from((to - from).num_seconds()).unwrap_or(0)
+}
+
+/// A past time relative to `now`: `3m ago`, or `just now` within a second.
+pub fn ago(when: DateTime<Local>, now: DateTime<Local>) -> String {
+    match secs_between(when, now) {
+        0 => "just now".to_string(),
+        secs => format!("{} ago", coarse_secs(secs)),
+    }
+}
+
+/// A future time relative to `now`: `in 5m`, or `now` if it has passed.
+pub fn until(when: DateTime<Local>, now: DateTime<Local>) -> String {
+    match secs_between(now, when) {
+        0 => "now".to_string(),
+        secs => format!("in {}", coarse_secs(secs)),
+    }
+}
+
+/// Next time `schedule` fires after `now`, or `None` if the expression is
+/// invalid or never fires again.
+pub fn next_cron_fire(schedule: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    cron::Schedule::from_str(schedule).ok()?.after(&now).next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_duration_secs() {
+        assert_eq!(duration_secs(0), "0s");
+        assert_eq!(duration_secs(59), "59s");
+        assert_eq!(duration_secs(60), "1m 0s");
+        assert_eq!(duration_secs(192), "3m 12s");
+        assert_eq!(duration_secs(2 * 3600 + 5 * 60 + 9), "2h 5m");
+        assert_eq!(duration_secs(4 * 86400 + 3600 + 1), "4d 1h");
+    }
+
+    #[test]
+    fn test_duration_ms() {
+        assert_eq!(duration_ms(850), "850ms");
+        assert_eq!(duration_ms(4_200), "4.2s");
+        assert_eq!(duration_ms(59_990), "60.0s");
+        assert_eq!(duration_ms(192_000), "3m 12s");
+    }
+
+    #[test]
+    fn test_ago_and_until() {
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(ago(now, now), "just now");
+        assert_eq!(ago(now - Duration::seconds(42), now), "42s ago");
+        assert_eq!(ago(now - Duration::seconds(3 * 60 + 59), now), "3m ago");
+        assert_eq!(ago(now - Duration::hours(26), now), "1d ago");
+        // Clock skew: a "past" time slightly in the future is just now
+        assert_eq!(ago(now + Duration::seconds(5), now), "just now");
+
+        assert_eq!(until(now + Duration::minutes(5), now), "in 5m");
+        assert_eq!(until(now + Duration::hours(2), now), "in 2h");
+        assert_eq!(until(now - Duration::seconds(1), now), "now");
+    }
+
+    #[test]
+    fn test_next_cron_fire() {
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 12, 0, 30).unwrap();
+        let next = next_cron_fire("0 */5 * * * *", now).unwrap();
+        assert_eq!(next, Local.with_ymd_and_hms(2026, 3, 1, 12, 5, 0).unwrap());
+        assert_eq!(until(next, now), "in 4m");
+        assert!(next_cron_fire("not a schedule", now).is_none());
+    }
+}
@@ -11,6 +11,10 @@ use std::time::Duration;
 
 pub struct WatchFiles {
     pub rx: tokio::sync::mpsc::Receiver<Vec<PathBuf>>,
+    /// Errors after which the backend stops seeing some changes because the
+    /// OS ran out of watches (see [`is_exhausted`]), e.g. when watches for
+    /// new subdirectories can't be added
+    pub exhausted_rx: tokio::sync::mpsc::Receiver<String>,
     backend: WatchFilesBackend,
 }
 
@@ -23,29 +27,41 @@ impl WatchFiles {
     pub fn new(duration: Duration, mode: WatchMode, poll_interval: Duration) -> Result<Self> {
         let h = tokio::runtime::Handle::current();
         let (tx, rx) = tokio::sync::mpsc::channel(256);
+        let (exhausted_tx, exhausted_rx) = tokio::sync::mpsc::channel(16);
         let make_callback = |tx: tokio::sync::mpsc::Sender<Vec<PathBuf>>,
+                             exhausted_tx: tokio::sync::mpsc::Sender<String>,
                              h: tokio::runtime::Handle| {
             move |res: DebounceEventResult| {
                 let tx = tx.clone();
+                let exhausted_tx = exhausted_tx.clone();
                 h.spawn(async move {
-                    if let Ok(ev) = res {
-                        let paths = ev
-                            .into_iter()
-                            .filter(|e| {
-                                matches!(
-                                    e.kind,
-                                    EventKind::Modify(_)
-                                        | EventKind::Create(_)
-                                        | EventKind::Remove(_)
-                                )
-                            })
-                            .flat_map(|e| e.paths.clone())
-                            .unique()
-                            .collect_vec();
-                        if !paths.is_empty() {
-                            // Ignore send errors - receiver may be dropped during shutdown
-                            let _ = tx.send(paths).await;
+                    let ev = match res {
+                        Ok(ev) => ev,
+                        Err(errors) => {
+                            for e in errors {
+                                if is_exhausted(&e) {
+                                    let _ = exhausted_tx.send(e.to_string()).await;
+                                } else {
+                                    log::debug!("file watcher error: {e}");
+                                }
+                            }
+                            return;
                         }
+                    };
+                    let paths = ev
+                        .into_iter()
+                        .filter(|e| {
+                            matches!(
+                                e.kind,
+                                EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                            )
+                        })
+                        .flat_map(|e| e.paths.clone())
+                        .unique()
+                        .collect_vec();
+                    if !paths.is_empty() {
+                        // Ignore send errors - receiver may be dropped during shutdown
+                        let _ = tx.send(paths).await;
                     }
                 });
             }
@@ -56,7 +72,7 @@ impl WatchFiles {
                 new_debouncer_opt(
                     duration,
                     None,
-                    make_callback(tx.clone(), h.clone()),
+                    make_callback(tx.clone(), exhausted_tx.clone(), h.clone()),
                     FileIdMap::new(),
                     Config::default(),
                 )
@@ -66,7 +82,7 @@ impl WatchFiles {
                 new_debouncer_opt(
                     duration,
                     None,
-                    make_callback(tx.clone(), h.clone()),
+                    make_callback(tx.clone(), exhausted_tx.clone(), h.clone()),
                     FileIdMap::new(),
                     Config::default().with_poll_interval(poll_interval),
                 )
@@ -80,17 +96,19 @@ impl WatchFiles {
             }
         };
 
-        Ok(Self { backend, rx })
+        Ok(Self {
+            backend,
+            rx,
+            exhausted_rx,
+        })
     }
 
-    pub fn watch(&mut self, path: &Path, recursive_mode: RecursiveMode) -> Result<()> {
+    /// Start watching `path`. The notify error is kept so callers can tell
+    /// an exhausted watch limit ([`is_exhausted`]) from a bad path.
+    pub fn watch(&mut self, path: &Path, recursive_mode: RecursiveMode) -> notify::Result<()> {
         match &mut self.backend {
-            WatchFilesBackend::Native(debouncer) => {
-                debouncer.watch(path, recursive_mode).into_diagnostic()
-            }
-            WatchFilesBackend::Poll(debouncer) => {
-                debouncer.watch(path, recursive_mode).into_diagnostic()
-            }
+            WatchFilesBackend::Native(debouncer) => debouncer.watch(path, recursive_mode),
+            WatchFilesBackend::Poll(debouncer) => debouncer.watch(path, recursive_mode),
         }
     }
 
@@ -102,6 +120,21 @@ impl WatchFiles {
     }
 }
 
+/// Whether a watch error means the OS ran out of watches or file
+/// descriptors (e.g. `fs.inotify.max_user_watches` on Linux, or the open
+/// file limit for kqueue on macOS) rather than a problem with the path.
+pub fn is_exhausted(err: &notify::Error) -> bool {
+    match &err.kind {
+        notify::ErrorKind::MaxFilesWatch => true,
+        #[cfg(unix)]
+        notify::ErrorKind::Io(e) => matches!(
+            e.raw_os_error(),
+            Some(libc::ENOSPC | libc::EMFILE | libc::ENFILE)
+        ),
+        _ => false,
+    }
+}
+
 /// Normalize a path by attempting to canonicalize it. If that fails, it attempts
 /// to resolve it as an absolute path. This helps ensure that different relative
 /// paths to the same directory are deduplicated.
@@ -508,4 +541,19 @@ mod tests {
         let dir = dirs.iter().next().unwrap();
         assert!(dir.is_absolute());
     }
+
+    #[test]
+    fn test_is_exhausted() {
+        assert!(is_exhausted(&notify::Error::new(
+            notify::ErrorKind::MaxFilesWatch
+        )));
+        #[cfg(unix)]
+        assert!(is_exhausted(&notify::Error::io(
+            std::io::Error::from_raw_os_error(libc::ENOSPC)
+        )));
+        assert!(!is_exhausted(&notify::Error::path_not_found()));
+        assert!(!is_exhausted(&notify::Error::io(std::io::Error::from(
+            std::io::ErrorKind::PermissionDenied
+        ))));
+    }
 }
@@ -0,0 +1,89 @@
+//! CPU and memory history for the daemon page charts.
+//!
+//! While the web server runs, running daemons are sampled every
+//! [`SAMPLE_INTERVAL`] and the last [`MAX_SAMPLES`] samples are kept per
+//! daemon. History starts when the web server does and is not persisted.
+
+use crate::daemon_id::DaemonId;
+use crate::procs::PROCS;
+use crate::supervisor::SUPERVISOR;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, Once};
+use std::time::Duration;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 10 minutes of history at the default interval
+const MAX_SAMPLES: usize = 300;
+
+static HISTORY: Lazy<Mutex<HashMap<DaemonId, VecDeque<Sample>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static START: Once = Once::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    /// Unix timestamp in seconds
+    at: i64,
+    cpu_percent: f32,
+    memory_bytes: u64,
+}
+
+/// Start the background sampler. Safe to call more than once (the web UI
+/// and the standalone API server share it).
+pub(super) fn start() {
+    START.call_once(|| {
+        tokio::spawn(async {
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+            loop {
+                interval.tick().await;
+                sample().await;
+            }
+        });
+    });
+}
+
+async fn sample() {
+    let running: Vec<(DaemonId, u32)> = SUPERVISOR
+        .active_daemons()
+        .await
+        .into_iter()
+        .filter_map(|d| Some((d.id.clone(), d.local_pid()?)))
+        .collect();
+    let pids: Vec<u32> = running.iter().map(|(_, pid)| *pid).collect();
+    let stats = if pids.is_empty() {
+        HashMap::new()
+    } else {
+        PROCS.refresh_and_get_batch_stats(&pids)
+    };
+    let at = chrono::Local::now().timestamp();
+
+    let mut history = HISTORY.lock().unwrap();
+    // Forget daemons that are no longer running so history doesn't grow
+    // with every daemon ever started.
+    history.retain(|id, _| running.iter().any(|(running_id, _)| running_id == id));
+    for (id, pid) in running {
+        let Some(s) = stats.get(&pid) else { continue };
+        let samples = history.entry(id).or_default();
+        if samples.len() >= MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(Sample {
+            at,
+            cpu_percent: s.cpu_percent,
+            memory_bytes: s.memory_bytes,
+        });
+    }
+}
+
+/// Samples for `id`, oldest first.
+pub(super) fn history(id: &DaemonId) -> Vec<Sample> {
+    HISTORY
+        .lock()
+        .unwrap()
+        .get(id)
+        .map(|samples| samples.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -1,3 +1,4 @@
+mod metrics;
 mod routes;
 mod server;
 mod static_files;
@@ -1,10 +1,18 @@
-use axum::{extract::Path, response::Json};
-use serde::Serialize;
+use axum::{
+    extract::{Path, Query},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::daemon_list::{DaemonListEntry, get_all_daemons_direct, get_daemon_direct};
 use crate::daemon_status::DaemonStatus;
+use crate::ipc::batch::{ConflictResolution, RunResult, StartOptions};
+use crate::pitchfork_toml::PitchforkToml;
 use crate::procs::PROCS;
 use crate::supervisor::SUPERVISOR;
+use crate::supervisor::events::{self, DaemonEvent};
+use crate::ui::time;
+use crate::web::metrics::{self, Sample};
 
 /// Serializable daemon entry for the API
 #[derive(Serialize)]
@@ -12,6 +20,8 @@ pub struct ApiDaemonEntry {
     id: ApiDaemonId,
     title: Option<String>,
     pid: Option<u32>,
+    /// Host the daemon is running on, when that isn't this machine
+    host: Option<String>,
     shell_pid: Option<u32>,
     status: ApiDaemonStatus,
     dir: Option<String>,
@@ -19,6 +29,8 @@ pub struct ApiDaemonEntry {
     cron_schedule: Option<String>,
     last_exit_success: Option<bool>,
     retry_count: u32,
+    /// Pending retry attempt and when it happens, e.g. `2/5 in 14s`
+    next_retry: Option<String>,
     resolved_port: Vec<u16>,
     active_port: Option<u16>,
     slug: Option<String>,
@@ -28,7 +40,15 @@ pub struct ApiDaemonEntry {
     cpu_percent: Option<f32>,
     memory_bytes: Option<u64>,
     uptime_secs: Option<u64>,
+    /// Processes counted in `cpu_percent` and `memory_bytes`
+    process_count: Option<usize>,
+    /// Humanized `uptime_secs`, e.g. `3m 12s`
+    uptime: Option<String>,
+    next_cron_secs: Option<u64>,
+    /// Humanized `next_cron_secs`, e.g. `in 5m`
+    next_cron: Option<String>,
     proxy_url: Option<String>,
+    /// `ready_delay` in whole seconds
     ready_delay: Option<u64>,
     ready_output: Option<String>,
     ready_http_url: Option<String>,
@@ -44,9 +64,23 @@ pub struct ApiDaemonEntry {
     user: Option<String>,
     memory_limit: Option<String>,
     cpu_limit: Option<String>,
+    /// Resource `alerts` threshold the daemon is currently over
+    alert: Option<String>,
+    /// Why the running process no longer matches what's on disk
+    drift: Option<String>,
+    /// Spawn-to-ready time of the latest run that became ready
+    ready_ms: Option<u64>,
+    /// 95th percentile of `ready_ms` over recent runs
+    ready_p95_ms: Option<u64>,
+    /// Humanized `ready_ms`/`ready_p95_ms`, e.g. `ready in 4.2s (p95 6.1s)`
+    startup: Option<String>,
     stop_signal: Option<String>,
     stop_timeout: Option<String>,
     pty: Option<bool>,
+    isolate_network: Option<bool>,
+    restart_on_config_change: Option<bool>,
+    restart_on_binary_change: Option<bool>,
+    restart_with_dependencies: Option<bool>,
     proxy: Option<bool>,
 }
 
@@ -65,8 +99,12 @@ pub enum ApiDaemonStatus {
     Failed { message: String },
     #[serde(rename = "waiting")]
     Waiting,
+    #[serde(rename = "starting")]
+    Starting,
     #[serde(rename = "running")]
     Running,
+    #[serde(rename = "unhealthy")]
+    Unhealthy,
     #[serde(rename = "stopping")]
     Stopping,
     #[serde(rename = "errored")]
@@ -95,7 +133,11 @@ fn api_status(status: &DaemonStatus, is_available: bool) -> ApiDaemonStatus {
             message: msg.clone(),
         },
         DaemonStatus::Waiting => ApiDaemonStatus::Waiting,
-        DaemonStatus::Running => ApiDaemonStatus::Running,
+        DaemonStatus::Starting => ApiDaemonStatus::Starting,
+        // Detached daemons are adopted as soon as a supervisor starts, so
+        // the web UI never sees one
+        DaemonStatus::Running | DaemonStatus::Detached => ApiDaemonStatus::Running,
+        DaemonStatus::Unhealthy => ApiDaemonStatus::Unhealthy,
         DaemonStatus::Stopping => ApiDaemonStatus::Stopping,
         DaemonStatus::Errored(code) => ApiDaemonStatus::Errored { code: *code },
         DaemonStatus::Stopped => ApiDaemonStatus::Stopped,
@@ -111,9 +153,13 @@ fn entry_to_api(
 ) -> ApiDaemonEntry {
     let d = &entry.daemon;
     let cmd = d.cmd.as_ref().map(|c| c.join(" "));
-    let (cpu, mem, uptime) = d
-        .pid
-        .and_then(|pid| stats_map.get(&pid))
+    let now = chrono::Local::now();
+    let next_cron = d
+        .cron_schedule
+        .as_deref()
+        .and_then(|schedule| time::next_cron_fire(schedule, now));
+    let stats = d.pid.and_then(|pid| stats_map.get(&pid));
+    let (cpu, mem, uptime) = stats
         .map(|s| {
             (
                 Some(s.cpu_percent),
@@ -127,6 +173,7 @@ fn entry_to_api(
         id: api_id(&entry.id),
         title: d.title.clone(),
         pid: d.pid,
+        host: d.remote_host().map(str::to_string),
         shell_pid: d.shell_pid,
         status: api_status(&d.status, entry.is_available),
         dir: d.dir.as_ref().map(|p| p.to_string_lossy().to_string()),
@@ -134,12 +181,13 @@ fn entry_to_api(
         cron_schedule: d.cron_schedule.clone(),
         last_exit_success: d.last_exit_success,
         retry_count: d.retry_count,
-        resolved_port: if d.status.is_running() {
+        next_retry: d.retry_summary(now),
+        resolved_port: if d.status.is_up() {
             d.resolved_port.clone()
         } else {
             Vec::new()
         },
-        active_port: if d.status.is_running() {
+        active_port: if d.status.is_up() {
             d.active_port
         } else {
             None
@@ -151,7 +199,11 @@ fn entry_to_api(
         cpu_percent: cpu,
         memory_bytes: mem,
         uptime_secs: uptime,
-        proxy_url: if d.status.is_running() {
+        process_count: stats.map(|s| s.process_count),
+        uptime: uptime.map(time::duration_secs),
+        next_cron_secs: next_cron.map(|next| time::secs_between(now, next)),
+        next_cron: next_cron.map(|next| time::until(next, now)),
+        proxy_url: if d.status.is_up() {
             let slug = crate::pitchfork_toml::PitchforkToml::find_slug_for_daemon_in_registry(
                 &entry.id,
                 global_slugs,
@@ -160,7 +212,7 @@ fn entry_to_api(
         } else {
             None
         },
-        ready_delay: d.ready_delay,
+        ready_delay: d.ready_delay.map(|delay| delay.as_secs()),
         ready_output: d.ready_output.as_ref().map(|o| o.pattern.clone()),
         ready_http_url: d.ready_http.as_ref().map(|r| r.url.clone()),
         ready_port: d.ready_port.as_ref().and_then(|p| p.as_port()),
@@ -195,11 +247,20 @@ fn entry_to_api(
         user: d.user.clone(),
         memory_limit: d.memory_limit.map(|m| m.to_string()),
         cpu_limit: d.cpu_limit.map(|c| format!("{:.1}%", c.0)),
+        alert: d.alert.clone(),
+        drift: d.drift.clone(),
+        ready_ms: d.startups.last().map(|s| s.ready_ms),
+        ready_p95_ms: d.ready_p95_ms(),
+        startup: d.startup_summary(),
         stop_signal: d.stop_signal.map(|s| s.signal.name().to_string()),
         stop_timeout: d
             .stop_signal
             .and_then(|s| s.timeout.map(|d| humantime::format_duration(d).to_string())),
         pty: d.pty,
+        isolate_network: d.isolate_network,
+        restart_on_config_change: d.restart_on_config_change,
+        restart_on_binary_change: d.restart_on_binary_change,
+        restart_with_dependencies: d.restart_with_dependencies,
         proxy: d.proxy,
     }
 }
@@ -260,8 +321,97 @@ pub async fn show(Path(id): Path<String>) -> Result<Json<ApiDaemonEntry>, axum::
     )))
 }
 
+pub async fn metrics(Path(id): Path<String>) -> Result<Json<Vec<Sample>>, axum::http::StatusCode> {
+    let daemon_id =
+        crate::daemon_id::DaemonId::parse(&id).map_err(|_| axum::http::StatusCode::BAD_REQUEST)?;
+    Ok(Json(metrics::history(&daemon_id)))
+}
+
+pub async fn events(
+    Path(id): Path<String>,
+) -> Result<Json<Vec<DaemonEvent>>, axum::http::StatusCode> {
+    let daemon_id =
+        crate::daemon_id::DaemonId::parse(&id).map_err(|_| axum::http::StatusCode::BAD_REQUEST)?;
+    Ok(Json(events::recent(&daemon_id)))
+}
+
+/// The daemon's section of the config file that defines it
+#[derive(Serialize)]
+pub struct ApiDaemonConfig {
+    path: Option<String>,
+    toml: Option<String>,
+}
+
+pub async fn config(
+    Path(id): Path<String>,
+) -> Result<Json<ApiDaemonConfig>, axum::http::StatusCode> {
+    let daemon_id =
+        crate::daemon_id::DaemonId::parse(&id).map_err(|_| axum::http::StatusCode::BAD_REQUEST)?;
+
+    let pt = PitchforkToml::all_merged_all_namespaces().map_err(|e| {
+        log::error!("Failed to load config: {e}");
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    // Ad-hoc daemons (`pitchfork run`) have no config
+    let Some(path) = pt.daemons.get(&daemon_id).and_then(|d| d.path.clone()) else {
+        return Ok(Json(ApiDaemonConfig {
+            path: None,
+            toml: None,
+        }));
+    };
+
+    let toml = daemon_config_toml(&path, daemon_id.name());
+    Ok(Json(ApiDaemonConfig {
+        path: Some(path.to_string_lossy().to_string()),
+        toml,
+    }))
+}
+
+/// Render `[daemons.<name>]` from `path` as it is written there.
+fn daemon_config_toml(path: &std::path::Path, name: &str) -> Option<String> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    let doc: toml::Table = toml::from_str(&raw).ok()?;
+    let section = doc.get("daemons")?.as_table()?.get(name)?.clone();
+    let mut daemons = toml::Table::new();
+    daemons.insert(name.to_string(), section);
+    let mut out = toml::Table::new();
+    out.insert("daemons".to_string(), toml::Value::Table(daemons));
+    toml::to_string(&out).ok()
+}
+
+#[derive(Deserialize)]
+pub struct StartQuery {
+    /// Resolve a port conflict reported by a previous attempt
+    on_conflict: Option<ConflictResolution>,
+}
+
+impl StartQuery {
+    fn overrides(&self) -> Option<StartOptions> {
+        let resolution = self.on_conflict?;
+        let mut opts = StartOptions::default();
+        resolution.apply(&mut opts);
+        Some(opts)
+    }
+}
+
+/// JSON for a start/restart result. A port conflict is included so the UI
+/// can offer to kill the process holding the port or bump to a free one.
+fn run_result_json(result: RunResult, already_running_error: bool) -> serde_json::Value {
+    let mut json = serde_json::json!({"ok": result.started});
+    if let Some(msg) = result.error_message {
+        json["error"] = serde_json::Value::String(msg);
+    } else if already_running_error && !result.started {
+        json["error"] = serde_json::Value::String("daemon is already running".into());
+    }
+    if let Some(conflict) = result.port_conflict {
+        json["port_conflict"] = serde_json::json!(conflict);
+    }
+    json
+}
+
 pub async fn start(
     Path(id): Path<String>,
+    Query(query): Query<StartQuery>,
 ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
     let daemon_id =
         crate::daemon_id::DaemonId::parse(&id).map_err(|_| axum::http::StatusCode::BAD_REQUEST)?;
@@ -273,16 +423,11 @@ pub async fn start(
             axum::http::StatusCode::SERVICE_UNAVAILABLE
         })?;
 
-    match client.start_daemon(&daemon_id, None).await {
-        Ok(result) => {
-            let mut json = serde_json::json!({"ok": result.started});
-            if let Some(msg) = result.error_message {
-                json["error"] = serde_json::Value::String(msg);
-            } else if !result.started {
-                json["error"] = serde_json::Value::String("daemon is already running".into());
-            }
-            Ok(Json(json))
-        }
+    match client
+        .start_daemon(&daemon_id, query.overrides().as_ref())
+        .await
+    {
+        Ok(result) => Ok(Json(run_result_json(result, true))),
         Err(e) => {
             log::error!("Failed to start daemon: {e}");
             Ok(Json(serde_json::json!({
@@ -326,6 +471,7 @@ pub async fn stop(
 
 pub async fn restart(
     Path(id): Path<String>,
+    Query(query): Query<StartQuery>,
 ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
     let daemon_id =
         crate::daemon_id::DaemonId::parse(&id).map_err(|_| axum::http::StatusCode::BAD_REQUEST)?;
@@ -337,14 +483,11 @@ pub async fn restart(
             axum::http::StatusCode::SERVICE_UNAVAILABLE
         })?;
 
-    match client.restart_daemon(&daemon_id, None).await {
-        Ok(result) => {
-            let mut json = serde_json::json!({"ok": result.started});
-            if let Some(msg) = result.error_message {
-                json["error"] = serde_json::Value::String(msg);
-            }
-            Ok(Json(json))
-        }
+    match client
+        .restart_daemon(&daemon_id, query.overrides().as_ref())
+        .await
+    {
+        Ok(result) => Ok(Json(run_result_json(result, false))),
         Err(e) => {
             log::error!("Failed to restart daemon: {e}");
             Ok(Json(serde_json::json!({
@@ -416,3 +559,34 @@ pub async fn disable(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daemon_config_toml_extracts_one_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pitchfork.toml");
+        std::fs::write(
+            &path,
+            r#"
+namespace = "proj"
+
+[daemons.api]
+run = "npm start"
+depends = ["db"]
+
+[daemons.db]
+run = "postgres"
+"#,
+        )
+        .unwrap();
+
+        let toml = daemon_config_toml(&path, "api").unwrap();
+        assert!(toml.contains("[daemons.api]"));
+        assert!(toml.contains(r#"run = "npm start""#));
+        assert!(!toml.contains("postgres"));
+        assert!(daemon_config_toml(&path, "missing").is_none());
+    }
+}
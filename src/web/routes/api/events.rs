@@ -0,0 +1,55 @@
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use serde::Deserialize;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::daemon_id::DaemonId;
+use crate::supervisor::events;
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    /// Only send events about this daemon (notifications are skipped)
+    daemon: Option<String>,
+}
+
+/// Push daemon status changes, lifecycle events and supervisor notifications
+/// as Server-Sent Events, as they happen. Each event's data is a JSON object
+/// tagged with `type`.
+pub async fn stream(
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let daemon = query
+        .daemon
+        .map(|id| DaemonId::parse(&id).map(|id| id.qualified()))
+        .transpose()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let mut rx = events::subscribe();
+
+    let stream = async_stream::stream! {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    yield Ok(Event::default().event("lagged").data(missed.to_string()));
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if let Some(ref daemon) = daemon
+                && event.daemon_id() != Some(daemon.as_str())
+            {
+                continue;
+            }
+            match Event::default().event(event.name()).json_data(&event) {
+                Ok(sse) => yield Ok(sse),
+                Err(e) => log::warn!("failed to encode event for stream: {e}"),
+            }
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -2,20 +2,91 @@ use axum::{
     body::Body,
     extract::{Path, Query},
     http::StatusCode,
-    response::Response,
+    response::{IntoResponse, Json, Response},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 
+use crate::ansi::strip_pty_controls;
+use crate::cli::logs::parse_time_input;
 use crate::daemon_id::DaemonId;
+use crate::log_store::recent::recent_lines;
 use crate::log_store::sqlite::LOG_STORE;
-use crate::log_store::{LogQuery, LogStore};
+use crate::log_store::{FieldFilter, LogQuery, LogStore, LogStream, MessageFilter};
+
+/// Most entries a single search returns
+const SEARCH_MAX_LIMIT: usize = 1000;
 
 #[derive(Deserialize)]
 pub struct TailQuery {
     lines: Option<usize>,
 }
 
+/// The last `lines` log lines (default 50) as a JSON array, oldest first,
+/// formatted like the `tail` stream. Used for the daemon page's log preview.
+///
+/// Served from the supervisor's in-memory recent lines when they cover the
+/// request, and from the log store otherwise.
+pub async fn recent(
+    Path(id): Path<String>,
+    Query(query): Query<TailQuery>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    let daemon_id = DaemonId::parse(&id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let qualified = daemon_id.qualified();
+    let limit = query.lines.unwrap_or(50);
+
+    match recent_lines(&daemon_id, limit).await {
+        Ok(lines) if lines.len() >= limit => {
+            return Ok(Json(
+                lines
+                    .into_iter()
+                    .map(|l| {
+                        let ts = l.timestamp.format("%Y-%m-%d %H:%M:%S");
+                        format!("{ts} {msg}", msg = strip_pty_controls(&l.message))
+                    })
+                    .collect(),
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => log::debug!("recent logs unavailable for {daemon_id}: {e}"),
+    }
+
+    let entries = tokio::task::spawn_blocking(move || {
+        LOG_STORE.query(&LogQuery {
+            daemon_ids: vec![qualified],
+            from: None,
+            to: None,
+            limit: Some(limit),
+            max_bytes: None,
+            order_desc: true,
+            after_id: None,
+            message_filters: Vec::new(),
+            field_filters: Vec::new(),
+            include_structured: false,
+        })
+    })
+    .await
+    .map_err(|e| {
+        log::warn!("log query task panicked for {daemon_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .map_err(|e| {
+        log::warn!("failed to query logs for {daemon_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(
+        entries
+            .into_iter()
+            .rev()
+            .map(|e| {
+                let ts = e.timestamp.format("%Y-%m-%d %H:%M:%S");
+                format!("{ts} {msg}", msg = strip_pty_controls(&e.message))
+            })
+            .collect(),
+    ))
+}
+
 pub async fn tail(Path(id): Path<String>, Query(query): Query<TailQuery>) -> Response<Body> {
     let daemon_id = match DaemonId::parse(&id) {
         Ok(id) => id,
@@ -40,6 +111,7 @@ pub async fn tail(Path(id): Path<String>, Query(query): Query<TailQuery>) -> Res
                 from: None,
                 to: None,
                 limit: Some(history_lines),
+                max_bytes: None,
                 order_desc: true,
                 after_id: None,
                 message_filters: Vec::new(),
@@ -78,7 +150,7 @@ pub async fn tail(Path(id): Path<String>, Query(query): Query<TailQuery>) -> Res
         .rev()
         .map(|e| {
             let ts = e.timestamp.format("%Y-%m-%d %H:%M:%S");
-            format!("{ts} {msg}\n", msg = e.message)
+            format!("{ts} {msg}\n", msg = strip_pty_controls(&e.message))
         })
         .collect();
 
@@ -125,6 +197,7 @@ pub async fn tail(Path(id): Path<String>, Query(query): Query<TailQuery>) -> Res
                     from: None,
                     to: None,
                     limit: Some(BATCH_SIZE),
+                    max_bytes: None,
                     order_desc: false,
                     after_id: Some(last_id),
                     message_filters: Vec::new(),
@@ -139,7 +212,7 @@ pub async fn tail(Path(id): Path<String>, Query(query): Query<TailQuery>) -> Res
             for entry in entries {
                 last_id = entry.id;
                 let ts = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
-                yield Ok::<Vec<u8>, Infallible>(format!("{ts} {msg}\n", msg = entry.message).into_bytes());
+                yield Ok::<Vec<u8>, Infallible>(format!("{ts} {msg}\n", msg = strip_pty_controls(&entry.message)).into_bytes());
             }
         }
     };
@@ -150,3 +223,132 @@ pub async fn tail(Path(id): Path<String>, Query(query): Query<TailQuery>) -> Res
         .body(Body::from_stream(stream))
         .unwrap()
 }
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    /// Case-insensitive substring the message must contain
+    q: Option<String>,
+    /// Comma-separated qualified daemon IDs; all daemons when omitted
+    daemon: Option<String>,
+    /// Minimum level, e.g. `warn` for warnings and errors
+    level: Option<String>,
+    /// Start of the time range, in any form `pitchfork logs --since` takes
+    since: Option<String>,
+    /// End of the time range, in any form `pitchfork logs --until` takes
+    until: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ApiLogSearch {
+    /// Matching entries, newest first
+    entries: Vec<ApiLogEntry>,
+    /// More entries matched than `limit`
+    truncated: bool,
+}
+
+#[derive(Serialize)]
+pub struct ApiLogEntry {
+    id: i64,
+    daemon_id: String,
+    timestamp: String,
+    level: Option<String>,
+    /// Message extracted from a structured line
+    msg: Option<String>,
+    logger: Option<String>,
+    stream: LogStream,
+    message: String,
+}
+
+fn bad_request(error: impl std::fmt::Display) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": error.to_string() })),
+    )
+        .into_response()
+}
+
+/// Search the logs of every daemon at once, filtered by daemon, minimum
+/// level, time range and message text.
+pub async fn search(Query(query): Query<SearchQuery>) -> Response {
+    let daemon_ids = match query
+        .daemon
+        .iter()
+        .flat_map(|d| d.split(','))
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| DaemonId::parse(d).map(|id| id.qualified()))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(ids) => ids,
+        Err(e) => return bad_request(e),
+    };
+    let level = query.level.filter(|l| !l.is_empty());
+    if let Some(level) = &level
+        && crate::log_store::levels_at_or_above(level).is_empty()
+    {
+        return bad_request(format!(
+            "unknown level '{level}', expected one of: trace, debug, info, warn, error"
+        ));
+    }
+    let time = |s: Option<String>, is_since| {
+        s.filter(|s| !s.trim().is_empty())
+            .map(|s| parse_time_input(&s, is_since))
+            .transpose()
+    };
+    let (from, to) = match (time(query.since, true), time(query.until, false)) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return bad_request(e),
+    };
+    let limit = query.limit.unwrap_or(200).clamp(1, SEARCH_MAX_LIMIT);
+
+    let log_query = LogQuery {
+        daemon_ids,
+        from,
+        to,
+        // One extra row tells whether the results were cut off
+        limit: Some(limit + 1),
+        max_bytes: None,
+        order_desc: true,
+        after_id: None,
+        message_filters: query
+            .q
+            .filter(|q| !q.is_empty())
+            .map(MessageFilter::contains)
+            .into_iter()
+            .collect(),
+        field_filters: level.map(FieldFilter::LevelMin).into_iter().collect(),
+        include_structured: true,
+    };
+    let entries = match tokio::task::spawn_blocking(move || LOG_STORE.query(&log_query)).await {
+        Ok(Ok(entries)) => entries,
+        Ok(Err(e)) => {
+            log::warn!("failed to search logs: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        Err(e) => {
+            log::warn!("log search task panicked: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let truncated = entries.len() > limit;
+    Json(ApiLogSearch {
+        entries: entries
+            .into_iter()
+            .take(limit)
+            .map(|e| ApiLogEntry {
+                id: e.id,
+                daemon_id: e.daemon_id,
+                timestamp: e.timestamp.to_rfc3339(),
+                level: e.level,
+                msg: e.msg,
+                logger: e.logger,
+                stream: e.stream,
+                message: strip_pty_controls(&e.message),
+            })
+            .collect(),
+        truncated,
+    })
+    .into_response()
+}
@@ -1,4 +1,5 @@
 pub mod daemons;
+pub mod events;
 pub mod logs;
 pub mod namespaces;
 pub mod processes;
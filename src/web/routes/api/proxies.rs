@@ -46,7 +46,7 @@ pub async fn list() -> Json<Vec<ApiProxyWorktreeEntry>> {
                     .pid
                     .and_then(|pid| PROCS.get_stats(pid))
                     .map(|s| s.uptime_secs);
-                (key, (port, d.status.is_running(), d.pid, uptime))
+                (key, (port, d.status.is_up(), d.pid, uptime))
             })
             .collect()
     };
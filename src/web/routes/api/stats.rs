@@ -43,7 +43,7 @@ pub async fn stats() -> Json<ApiStats> {
 
     let running = user_daemons
         .iter()
-        .filter(|(_, d)| d.status.is_running() && !d.config_registered)
+        .filter(|(_, d)| d.status.is_up() && !d.config_registered)
         .count();
     let stopped = user_daemons
         .iter()
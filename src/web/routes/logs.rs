@@ -37,6 +37,7 @@ pub async fn stream_sse(
                 from: None,
                 to: None,
                 limit: Some(1),
+                max_bytes: None,
                 order_desc: true,
                 after_id: None,
                 message_filters: Vec::new(),
@@ -82,6 +83,7 @@ pub async fn stream_sse(
                     from: None,
                     to: None,
                     limit: Some(BATCH_SIZE),
+                    max_bytes: None,
                     order_desc: false,
                     after_id: Some(last_id),
                     message_filters: Vec::new(),
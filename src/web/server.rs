@@ -76,6 +76,19 @@ fn api_router(token: String) -> Router {
             "/api/daemons/{id}/restart",
             post(routes::api::daemons::restart),
         )
+        .route(
+            "/api/daemons/{id}/metrics",
+            get(routes::api::daemons::metrics),
+        )
+        .route(
+            "/api/daemons/{id}/events",
+            get(routes::api::daemons::events),
+        )
+        .route(
+            "/api/daemons/{id}/config",
+            get(routes::api::daemons::config),
+        )
+        .route("/api/daemons/{id}/logs", get(routes::api::logs::recent))
         .route(
             "/api/daemons/{id}/enable",
             post(routes::api::daemons::enable),
@@ -84,7 +97,9 @@ fn api_router(token: String) -> Router {
             "/api/daemons/{id}/disable",
             post(routes::api::daemons::disable),
         )
+        .route("/api/logs/search", get(routes::api::logs::search))
         .route("/api/logs/{id}/tail", get(routes::api::logs::tail))
+        .route("/api/events/stream", get(routes::api::events::stream))
         .route("/api/namespaces", get(routes::api::namespaces::list))
         .route("/api/namespaces", post(routes::api::namespaces::register))
         .route(
@@ -172,6 +187,7 @@ pub async fn serve(port: u16, web_path: Option<String>) -> Result<()> {
 
     set_static_token(token.clone());
     set_static_base(base_path.clone());
+    super::metrics::start();
 
     let inner = api_router(token.clone()).fallback(static_handler);
 
@@ -225,6 +241,7 @@ pub async fn serve_api(port: u16, _web_path: Option<String>) -> Result<()> {
     }
 
     let app = api_router(token);
+    super::metrics::start();
 
     let (listener, _actual_port) = try_bind(bind_address, port, port_attempts).await?;
     let actual_addr = listener.local_addr().unwrap();
@@ -49,37 +49,66 @@ fn inject_into_index_html(data: &[u8]) -> Body {
     Body::from(replaced.into_bytes())
 }
 
+/// Cache policy for `index.html`, which carries the per-server token and base path.
+const NO_STORE: &str = "no-store, no-cache, must-revalidate";
+
+/// Cache policy for vite's content-hashed bundle output under `assets/`.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Pick a `Cache-Control` value for an embedded asset path.
+///
+/// Files under `assets/` have a content hash in their name so they can be
+/// cached forever; everything else (logo, favicon) is revalidated on each load
+/// so a theme or branding change shows up after a supervisor upgrade.
+fn cache_control_for(path: &str) -> &'static str {
+    if path == "index.html" {
+        NO_STORE
+    } else if path.starts_with("assets/") {
+        IMMUTABLE
+    } else {
+        "no-cache"
+    }
+}
+
+fn not_found() -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("404 Not Found"))
+        .unwrap()
+}
+
+fn index_response() -> Response {
+    match Assets::get("index.html") {
+        Some(content) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .header(header::CACHE_CONTROL, NO_STORE)
+            .body(inject_into_index_html(&content.data))
+            .unwrap(),
+        None => not_found(),
+    }
+}
+
 /// Serve static files from the embedded Vue SPA dist folder.
 ///
 /// Any request path is first tried as a static file. If not found,
 /// falls back to `index.html` so the SPA router can handle client-side routes.
 /// When serving `index.html`, replaces placeholders with the actual token and base path.
 pub async fn static_handler(uri: Uri, headers: HeaderMap) -> impl IntoResponse {
-    let mut path = uri.path().trim_start_matches('/');
-    if path.is_empty() {
-        path = "index.html";
+    let path = uri.path().trim_start_matches('/');
+    if path.is_empty() || path == "index.html" {
+        return index_response();
     }
 
     match Assets::get(path) {
         Some(content) => {
             let mime = mime_guess::from_path(path).first_or_octet_stream();
-            let data = content.data.to_vec();
-
-            let body = if path == "index.html" {
-                inject_into_index_html(&data)
-            } else {
-                Body::from(data)
-            };
-
-            let is_index = path == "index.html";
-            let mut builder = Response::builder()
+            Response::builder()
                 .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, mime.as_ref());
-            if is_index {
-                builder =
-                    builder.header(header::CACHE_CONTROL, "no-store, no-cache, must-revalidate");
-            }
-            builder.body(body).unwrap()
+                .header(header::CONTENT_TYPE, mime.as_ref())
+                .header(header::CACHE_CONTROL, cache_control_for(path))
+                .body(Body::from(content.data.into_owned()))
+                .unwrap()
         }
         None => {
             // SPA fallback: return index.html for unknown paths (client-side routing).
@@ -90,29 +119,24 @@ pub async fn static_handler(uri: Uri, headers: HeaderMap) -> impl IntoResponse {
                 .get(header::ACCEPT)
                 .and_then(|v| v.to_str().ok())
                 .is_some_and(|v| v.contains("text/html"));
-            if !accepts_html {
-                return Response::builder()
-                    .status(StatusCode::NOT_FOUND)
-                    .body(Body::from("404 Not Found"))
-                    .unwrap();
-            }
-            match Assets::get("index.html") {
-                Some(content) => {
-                    let data = content.data.to_vec();
-                    let body = inject_into_index_html(&data);
-
-                    Response::builder()
-                        .status(StatusCode::OK)
-                        .header(header::CONTENT_TYPE, "text/html")
-                        .header(header::CACHE_CONTROL, "no-store, no-cache, must-revalidate")
-                        .body(body)
-                        .unwrap()
-                }
-                None => Response::builder()
-                    .status(StatusCode::NOT_FOUND)
-                    .body(Body::from("404 Not Found"))
-                    .unwrap(),
+            if accepts_html {
+                index_response()
+            } else {
+                not_found()
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_control_for() {
+        assert_eq!(cache_control_for("index.html"), NO_STORE);
+        assert_eq!(cache_control_for("assets/index-3f9a1c.js"), IMMUTABLE);
+        assert_eq!(cache_control_for("assets/index-3f9a1c.css"), IMMUTABLE);
+        assert_eq!(cache_control_for("img/logo.png"), "no-cache");
+    }
+}
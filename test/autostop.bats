@@ -145,3 +145,64 @@ EOF
   run pitchfork status project/immediate_stop
   refute_output --partial "running"
 }
+
+@test "rapid directory changes are batched to the final directory" {
+  export PITCHFORK_AUTOSTOP_DELAY=0s
+
+  pitchfork supervisor start --force >/dev/null 2>&1
+
+  create_pitchfork_toml <<TOML
+namespace = "project"
+
+[daemons.batched]
+run = "sleep 120"
+auto = ["stop"]
+ready_delay = 1
+TOML
+
+  local project_dir other_dir
+  project_dir="$PWD"
+  other_dir="$(mktemp -d /tmp/pf-autostop-other-XXXXXX)"
+
+  run pitchfork cd --shell-pid $$
+  assert_success
+
+  run pitchfork start batched --shell-pid $$
+  assert_success
+  wait_for_status batched running
+  sleep 1
+
+  # Leave and come straight back: both updates land in one batch, so the
+  # shell never counts as having left and nothing is stopped even with no
+  # autostop delay.
+  cd "$other_dir"
+  pitchfork cd --shell-pid $$
+  cd "$project_dir"
+  pitchfork cd --shell-pid $$
+
+  sleep 2
+
+  run pitchfork status project/batched
+  assert_success
+  assert_output --partial "running"
+}
+
+@test "cd prints a summary of auto-started daemons" {
+  create_pitchfork_toml <<EOF
+namespace = "project"
+
+[daemons.summary_svc]
+run = "sleep 120"
+auto = ["start"]
+ready_delay = 1
+EOF
+
+  run pitchfork cd --shell-pid $$
+  assert_success
+  assert_output --partial "pitchfork: started summary_svc"
+
+  # Nothing new to start, so nothing to report
+  run pitchfork cd --shell-pid $$
+  assert_success
+  refute_output --partial "pitchfork: started"
+}
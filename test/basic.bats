@@ -187,6 +187,196 @@ EOF
   pitchfork stop test_status
 }
 
+@test "status command lists the last exits" {
+  create_pitchfork_toml <<EOF
+[daemons.flaky]
+run = 'exit 3'
+retry = 1
+EOF
+
+  run pitchfork start flaky
+  assert_failure
+  wait_for_status flaky errored
+
+  run pitchfork status flaky
+  assert_failure 2
+  assert_output --partial "Last exits:"
+  assert_output --partial "fail (code 3) after 0s"
+
+  run pitchfork status flaky --json
+  assert_failure 2
+  assert_output --partial '"code": 3'
+}
+
+@test "external kill is recorded apart from a requested stop" {
+  create_pitchfork_toml <<EOF
+[daemons.victim]
+run = "sleep 60"
+ready_delay = 1
+retry = 0
+EOF
+
+  pitchfork start victim
+  local pid
+  pid=$(get_daemon_pid victim)
+  [[ -n "$pid" ]]
+  kill -TERM "$pid"
+  wait_for_status victim errored
+
+  run pitchfork status victim
+  assert_output --partial "killed (signal 15)"
+
+  run pitchfork list
+  assert_output --partial "killed externally (signal 15)"
+
+  pitchfork start victim
+  pitchfork stop victim
+  run pitchfork status victim
+  assert_output --partial "stop (signal 15)"
+}
+
+@test "status exit code reflects daemon health" {
+  create_pitchfork_toml <<EOF
+[daemons.healthy]
+run = "sleep 10"
+
+[daemons.broken]
+run = 'exit 3'
+EOF
+
+  run pitchfork status -q healthy
+  assert_failure 1
+  assert_output ""
+
+  run pitchfork start healthy
+  assert_success
+  run pitchfork status --quiet healthy
+  assert_success
+  assert_output ""
+
+  run pitchfork start broken
+  assert_failure
+  wait_for_status broken errored
+  run pitchfork status -q broken
+  assert_failure 2
+  assert_output ""
+
+  pitchfork stop healthy
+  run pitchfork status -q healthy
+  assert_failure 1
+}
+
+@test "start --env overrides config env for one run only" {
+  create_pitchfork_toml <<'EOF'
+[daemons.env_override]
+run = 'echo "greeting=$GREETING mode=$MODE"; sleep 10'
+ready_output = "greeting="
+env = { GREETING = "config", MODE = "base" }
+EOF
+
+  run pitchfork start env_override --env GREETING=override
+  assert_success
+
+  run pitchfork logs env_override --raw
+  assert_output --partial "greeting=override mode=base"
+
+  run pitchfork status env_override
+  assert_success
+  assert_output --partial "Env override (this run): GREETING"
+
+  pitchfork stop env_override
+  pitchfork logs env_override --clear
+  run pitchfork start env_override
+  assert_success
+
+  run pitchfork logs env_override --raw
+  assert_output --partial "greeting=config mode=base"
+  refute_output --partial "greeting=override"
+
+  run pitchfork status env_override
+  refute_output --partial "Env override"
+
+  pitchfork stop env_override
+}
+
+@test "restart shows what changed in the config since the last run" {
+  create_pitchfork_toml <<'EOF'
+[daemons.config_diff]
+run = "sleep 60"
+ready_delay = 1
+env = { GREETING = "hello", OLD = "1" }
+EOF
+
+  run pitchfork start config_diff
+  assert_success
+  refute_output --partial "env:"
+
+  create_pitchfork_toml <<'EOF'
+[daemons.config_diff]
+run = "sleep 61"
+ready_delay = 1
+env = { GREETING = "hi", NEW = "1" }
+EOF
+
+  run pitchfork restart config_diff
+  assert_success
+  assert_output --partial "command: sleep 60 → sleep 61"
+  assert_output --partial "env: ~GREETING, +NEW, -OLD"
+
+  # Unchanged config: nothing to report
+  run pitchfork start config_diff --force
+  assert_success
+  refute_output --partial "env:"
+
+  pitchfork stop config_diff
+}
+
+@test "debug enable applies debug_env and reverts after the duration" {
+  export PITCHFORK_INTERVAL=1s
+  create_pitchfork_toml <<'EOF'
+[daemons.debuggable]
+run = 'echo "level=$LOG_LEVEL"; sleep 30'
+ready_output = "level="
+env = { LOG_LEVEL = "info" }
+debug_env = { LOG_LEVEL = "debug" }
+EOF
+
+  run pitchfork debug enable debuggable --duration 3s
+  assert_success
+  assert_output --partial "Debug logging enabled for"
+
+  run pitchfork logs debuggable --raw
+  assert_output --partial "level=debug"
+
+  run pitchfork status debuggable
+  assert_output --partial "Env override (until"
+
+  wait_for_logs debuggable "level=info" 15
+
+  run pitchfork status debuggable
+  assert_success
+  refute_output --partial "Env override"
+
+  pitchfork stop debuggable
+}
+
+@test "debug enable requires debug_env" {
+  create_pitchfork_toml <<'EOF'
+[daemons.plain]
+run = "sleep 30"
+EOF
+
+  run pitchfork debug enable plain
+  assert_failure
+  assert_output --partial "has no debug_env configured"
+}
+
+@test "start --env rejects values without '='" {
+  run pitchfork start some_daemon --env NOVALUE
+  assert_failure
+  assert_output --partial "Expected KEY=value"
+}
+
 # ============================================================================
 # Retry tests
 # ============================================================================
@@ -268,6 +458,68 @@ EOF
   pitchfork stop retry_success
 }
 
+@test "no_retry_exit_codes stops retrying immediately" {
+  create_pitchfork_toml <<EOF
+[daemons.no_retry]
+run = 'echo attempt; exit 64'
+ready_delay = 1
+retry = 3
+no_retry_exit_codes = [64]
+EOF
+
+  local start_time elapsed
+  start_time=$(date +%s)
+  run pitchfork start no_retry
+  elapsed=$(($(date +%s) - start_time))
+
+  assert_failure
+  [[ $elapsed -lt 3 ]]
+
+  wait_for_logs no_retry "attempt" 5
+  run pitchfork logs no_retry --raw
+  local count
+  count=$(grep -c "attempt" <<< "$output")
+  [[ $count -eq 1 ]]
+}
+
+@test "retry command starts another attempt immediately" {
+  create_pitchfork_toml <<EOF
+[daemons.manual_retry]
+run = 'echo attempt; exit 1'
+retry = 0
+EOF
+
+  run pitchfork start manual_retry
+  assert_failure
+  wait_for_logs manual_retry "attempt" 5
+
+  run pitchfork retry --cancel manual_retry
+  assert_success
+  assert_output --partial "no pending retries"
+
+  run pitchfork retry manual_retry
+  assert_success
+
+  sleep 1
+  run pitchfork logs manual_retry --raw
+  local count
+  count=$(grep -c "attempt" <<< "$output")
+  [[ $count -eq 2 ]]
+}
+
+@test "success_exit_codes treats exit code as clean" {
+  create_pitchfork_toml <<EOF
+[daemons.nothing_to_do]
+run = 'exit 2'
+retry = 3
+success_exit_codes = [0, 2]
+EOF
+
+  run pitchfork start nothing_to_do
+  assert_success
+  wait_for_status nothing_to_do stopped
+}
+
 # ============================================================================
 # Ready check tests
 # ============================================================================
@@ -479,6 +731,50 @@ EOF
   pitchfork stop port_test
 }
 
+@test "daemon shows starting until its ready check passes" {
+  create_pitchfork_toml <<EOF
+[daemons.slow_ready]
+run = "sleep 60"
+ready_delay = 3
+EOF
+
+  pitchfork start slow_ready &
+  wait_for_status slow_ready starting 5
+  wait
+  wait_for_status slow_ready running 5
+
+  pitchfork stop slow_ready
+}
+
+@test "failing health check marks daemon unhealthy until it recovers" {
+  kill_port 18085
+  export PITCHFORK_HEALTH_CHECK_INTERVAL=1s
+  local http_script
+  http_script="$(script_path http_server.py)"
+
+  create_pitchfork_toml <<EOF
+[daemons.backend]
+run = 'python3 -u $http_script 0 18085'
+ready_port = 18085
+
+[daemons.frontend]
+run = "sleep 60"
+ready_http = "http://localhost:18085/health"
+EOF
+
+  run pitchfork start backend frontend
+  assert_success
+  wait_for_status frontend running 5
+
+  pitchfork stop backend
+  wait_for_status frontend unhealthy 10
+
+  pitchfork start backend
+  wait_for_status frontend running 10
+
+  pitchfork stop frontend backend
+}
+
 @test "ready port timeout fails daemon" {
   kill_port 18083
 
@@ -527,6 +823,84 @@ EOF
   wait_for_status output_timeout_test errored
 }
 
+@test "fail_output fails the start as soon as an error is printed" {
+  create_pitchfork_toml <<EOF
+[daemons.fail_output_test]
+run = "echo 'Error: listen EADDRINUSE :::3000'; sleep 60"
+ready_output = "listening"
+fail_output = "EADDRINUSE|panicked at"
+retry = 0
+EOF
+
+  local start_time elapsed
+  start_time=$(date +%s)
+  run pitchfork start fail_output_test
+  elapsed=$(($(date +%s) - start_time))
+
+  assert_failure
+  assert_output --partial "output matched fail_output: Error: listen EADDRINUSE :::3000"
+  [[ $elapsed -lt 10 ]]
+
+  wait_for_status fail_output_test errored
+}
+
+@test "expect_long_running fails a daemon that exits cleanly right away" {
+  create_pitchfork_toml <<EOF
+[daemons.long_running_test]
+run = "echo 'forked into the background'"
+expect_long_running = true
+EOF
+
+  run pitchfork start long_running_test
+  assert_failure
+  assert_output --partial "expect_long_running is set"
+
+  wait_for_status long_running_test failed
+}
+
+@test "start --show-output errors only prints stderr lines" {
+  create_pitchfork_toml <<EOF
+[daemons.show_output_test]
+run = "sh -c 'echo chatty stdout line; echo broken stderr line >&2; sleep 30'"
+ready_delay = 1
+EOF
+
+  run pitchfork start show_output_test --show-output errors
+  assert_success
+  assert_output --partial "broken stderr line"
+  refute_output --partial "chatty stdout line"
+
+  run pitchfork start show_output_test -f --show-output none
+  assert_success
+  refute_output --partial "broken stderr line"
+
+  pitchfork stop show_output_test
+}
+
+@test "start --wait-timeout bounds an unbounded ready check" {
+  kill_port 18086
+
+  create_pitchfork_toml <<EOF
+[daemons.wait_timeout_test]
+run = "echo 'waiting for database'; sleep 30"
+ready_port = 18086
+retry = 0
+EOF
+
+  local start_time elapsed
+  start_time=$(date +%s)
+  run pitchfork start wait_timeout_test --wait-timeout 2s
+  elapsed=$(($(date +%s) - start_time))
+
+  assert_failure
+  assert_output --partial "not ready: gave up waiting for TCP port 18086 after 2s"
+  assert_output --partial "last output: waiting for database"
+  [[ $elapsed -ge 2 ]]
+  [[ $elapsed -lt 30 ]]
+
+  wait_for_status wait_timeout_test errored
+}
+
 @test "ready cmd check waits for command to succeed" {
   local marker
   marker="$TEST_TEMP_DIR/ready_marker"
@@ -611,6 +985,64 @@ EOF
   pitchfork stop cmd_output_race
 }
 
+@test "ready log file check waits for a new matching line" {
+  mkdir -p logs
+  echo "Listening on 8080" > logs/server.log
+
+  create_pitchfork_toml <<EOF
+[daemons.log_file_test]
+run = "sleep 2; echo 'Listening on 8080' >> logs/server.log; sleep 60"
+ready_log_file = { path = "logs/server.log", pattern = "Listening on" }
+EOF
+
+  local start_time elapsed
+  start_time=$(date +%s)
+  run pitchfork start log_file_test
+  elapsed=$(($(date +%s) - start_time))
+
+  # The line left over from before the start must not count
+  assert_success
+  [[ $elapsed -ge 2 ]]
+  [[ $elapsed -lt 30 ]]
+
+  wait_for_status log_file_test running
+  pitchfork stop log_file_test
+}
+
+@test "ready log file timeout fails daemon" {
+  create_pitchfork_toml <<EOF
+[daemons.log_file_timeout]
+run = "echo booting >> server.log; sleep 30"
+ready_log_file = { path = "server.log", pattern = "READY", timeout = "3s" }
+retry = 0
+EOF
+
+  run pitchfork start log_file_timeout
+  assert_failure
+
+  wait_for_status log_file_timeout errored
+}
+
+@test "ready unix socket check waits for the socket" {
+  create_pitchfork_toml <<EOF
+[daemons.unix_socket_test]
+run = "sleep 2; python3 -c 'import socket,time; s=socket.socket(socket.AF_UNIX); s.bind(\"app.sock\"); s.listen(); time.sleep(60)'"
+ready_unix_socket = "app.sock"
+EOF
+
+  local start_time elapsed
+  start_time=$(date +%s)
+  run pitchfork start unix_socket_test
+  elapsed=$(($(date +%s) - start_time))
+
+  assert_success
+  [[ $elapsed -ge 2 ]]
+  [[ $elapsed -lt 30 ]]
+
+  wait_for_status unix_socket_test running
+  pitchfork stop unix_socket_test
+}
+
 # ============================================================================
 # Dir and env tests
 # ============================================================================
@@ -880,3 +1312,60 @@ EOF
 
   pitchfork stop --all
 }
+
+@test "--color controls colored list output" {
+  create_pitchfork_toml <<EOF
+[daemons.color_daemon]
+run = "sleep 60"
+ready_delay = 1
+EOF
+
+  run pitchfork start color_daemon
+  assert_success
+
+  # `run` captures output through a pipe, so auto mode is plain
+  run pitchfork list
+  assert_success
+  refute_output --partial $'\e['
+
+  run pitchfork list --color always
+  assert_success
+  assert_output --partial $'\e['
+
+  NO_COLOR=1 run pitchfork status color_daemon
+  assert_success
+  refute_output --partial $'\e['
+
+  pitchfork stop color_daemon
+}
+
+@test "list --columns and --sort customize the table" {
+  create_pitchfork_toml <<EOF
+[daemons.bravo]
+run = "sleep 60"
+ready_delay = 1
+
+[daemons.alpha]
+run = "sleep 60"
+ready_delay = 1
+
+[groups.web]
+daemons = ["alpha"]
+EOF
+
+  run pitchfork start alpha bravo
+  assert_success
+
+  run pitchfork list --columns id,pid,groups --sort id --reverse
+  assert_success
+  [[ "${lines[0]}" == *bravo* ]]
+  [[ "${lines[1]}" == *alpha* ]]
+  assert_line --regexp "alpha +[0-9]+ +web"
+  refute_output --partial "running"
+
+  run pitchfork list --columns id,nope
+  assert_failure
+  assert_output --partial "unknown column 'nope'"
+
+  pitchfork stop --all
+}
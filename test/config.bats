@@ -108,3 +108,44 @@ EOF
   # Clean up
   run pitchfork stop test-server || true
 }
+
+@test "config add rejects an id that already exists unless --force" {
+  run pitchfork daemons add api --run "npm start"
+  assert_success
+
+  run pitchfork daemons add api --run "bun run server"
+  assert_failure
+  assert_output --partial "already exists"
+
+  run read_toml
+  assert_output --partial 'run = "npm start"'
+
+  run pitchfork daemons add api --run "bun run server" --force
+  assert_success
+
+  run read_toml
+  assert_output --partial 'run = "bun run server"'
+  refute_output --partial 'run = "npm start"'
+}
+
+@test "config add --stdin-json adds daemons from a JSON array" {
+  run bash -c 'echo '"'"'[{"id":"api","run":"npm start","retry":3,"depends":["db"]},{"id":"db","run":"postgres","ready_port":5432}]'"'"' | pitchfork daemons add --stdin-json'
+  assert_success
+  assert_output --partial "/api to "
+  assert_output --partial "/db to "
+
+  run read_toml
+  assert_output --partial "[daemons.api]"
+  assert_output --partial 'run = "npm start"'
+  assert_output --partial 'retry = 3'
+  assert_output --partial 'depends = ["db"]'
+  assert_output --partial "[daemons.db]"
+  assert_output --partial 'ready_port = 5432'
+}
+
+@test "config add --stdin-json writes nothing if any daemon is invalid" {
+  run bash -c 'echo '"'"'[{"id":"api","run":"npm start"},{"id":"db"}]'"'"' | pitchfork daemons add --stdin-json'
+  assert_failure
+
+  assert [ ! -f pitchfork.toml ]
+}
@@ -318,3 +318,83 @@ EOF
 
   pitchfork stop override_test
 }
+
+@test "umask and limits are applied to the daemon process" {
+  create_pitchfork_toml <<'EOF2'
+[daemons.limited]
+run = "echo umask=$(umask) nofile=$(ulimit -n); sleep 60"
+umask = "027"
+limits = { nofile = 256 }
+EOF2
+
+  run pitchfork start limited
+  assert_success
+  wait_for_logs limited "nofile=" 5
+
+  run pitchfork logs limited --raw
+  assert_output --partial "umask=0027"
+  assert_output --partial "nofile=256"
+
+  pitchfork stop limited
+}
+
+@test "ready_delay accepts humantime durations" {
+  create_pitchfork_toml <<EOF
+[daemons.delayed]
+run = "sleep 60"
+ready_delay = "500ms"
+EOF
+
+  local start_time elapsed
+  start_time=$(date +%s)
+  run pitchfork start delayed
+  elapsed=$(($(date +%s) - start_time))
+  assert_success
+  [[ $elapsed -lt 3 ]]
+  wait_for_status delayed running
+
+  pitchfork stop delayed
+}
+
+@test "invalid duration in config is rejected" {
+  create_pitchfork_toml <<EOF
+[daemons.delayed]
+run = "sleep 60"
+ready_delay = "soon"
+EOF
+
+  run pitchfork start delayed
+  assert_failure
+  assert_output --partial "invalid duration"
+}
+
+@test "aliases resolve to the daemon" {
+  create_pitchfork_toml <<EOF
+[daemons.frontend_dev_server]
+run = "sleep 60"
+aliases = ["web"]
+ready_delay = 1
+EOF
+
+  run pitchfork start web
+  assert_success
+  wait_for_status frontend_dev_server running
+
+  run pitchfork stop web
+  assert_success
+}
+
+@test "alias conflicting with a daemon name is rejected" {
+  create_pitchfork_toml <<EOF
+[daemons.web]
+run = "sleep 60"
+
+[daemons.api]
+run = "sleep 60"
+aliases = ["web"]
+EOF
+
+  run pitchfork start api
+  assert_failure
+  assert_output --partial "conflicts with daemon 'web'"
+}
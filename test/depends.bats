@@ -204,6 +204,54 @@ EOF
   pitchfork stop --all
 }
 
+@test "restart_with_dependencies restarts dependents after their dependency" {
+  create_pitchfork_toml <<EOF
+[daemons.db]
+run = "echo db_started; sleep 60"
+ready_delay = 1
+
+[daemons.api]
+run = "echo api_started; sleep 60"
+depends = ["db"]
+restart_with_dependencies = true
+ready_delay = 1
+
+[daemons.worker]
+run = "echo worker_started; sleep 60"
+depends = ["db"]
+ready_delay = 1
+EOF
+
+  run pitchfork start api worker
+  assert_success
+
+  run pitchfork restart db
+  assert_success
+
+  # The cascade runs in the supervisor once db is ready again
+  local api_count=0
+  for _ in $(seq 1 20); do
+    api_count=$(read_logs api | grep -c "api_started" || true)
+    [[ $api_count -ge 2 ]] && break
+    sleep 0.5
+  done
+  [[ $api_count -eq 2 ]]
+
+  run read_logs worker
+  local worker_count
+  worker_count=$(grep -c "worker_started" <<< "$output" || true)
+  [[ $worker_count -eq 1 ]]
+
+  # Restarting both doesn't restart api a second time through the cascade
+  run pitchfork restart db api
+  assert_success
+  sleep 3
+  api_count=$(read_logs api | grep -c "api_started" || true)
+  [[ $api_count -eq 3 ]]
+
+  pitchfork stop --all
+}
+
 @test "stop --all stops all daemons" {
   create_pitchfork_toml <<EOF
 [daemons.db]
@@ -291,3 +339,94 @@ EOF
   run pitchfork stop --all
   assert_success
 }
+
+@test "start fails when a required service is unreachable" {
+  create_pitchfork_toml <<EOF
+[daemons.api]
+run = "echo api started && sleep 30"
+requires = [{ tcp = "127.0.0.1:1", timeout = "1s" }]
+ready_delay = 1
+EOF
+
+  run pitchfork start api
+  assert_failure
+  assert_output --partial "tcp 127.0.0.1:1 not reachable after 1s"
+
+  run pitchfork status api
+  assert_output --partial "failed"
+
+  run pitchfork logs api
+  refute_output --partial "api started"
+}
+
+@test "start_priority and start_delay order daemons in the same level" {
+  create_pitchfork_toml <<EOF
+[daemons.alpha]
+run = "echo alpha >> $TEST_TEMP_DIR/order && sleep 30"
+start_delay = "1s"
+ready_delay = 1
+
+[daemons.zulu]
+run = "echo zulu >> $TEST_TEMP_DIR/order && sleep 30"
+start_priority = 10
+ready_delay = 1
+EOF
+
+  run pitchfork start alpha zulu
+  assert_success
+
+  run cat "$TEST_TEMP_DIR/order"
+  assert_output "zulu
+alpha"
+
+  pitchfork stop --all
+}
+
+@test "dependents start only after a task completes" {
+  create_pitchfork_toml <<EOF
+[daemons.migrate]
+run = "sleep 2 && echo run >> runs"
+kind = "task"
+
+[daemons.api]
+run = "test -f runs && echo api ready && sleep 30"
+depends = ["migrate"]
+ready_output = "api ready"
+EOF
+
+  run pitchfork start api
+  assert_success
+
+  [[ "$(get_daemon_status migrate)" == *completed* ]]
+  [[ "$(get_daemon_status api)" == *running* ]]
+
+  run pitchfork list --status completed --hide-header
+  assert_output --partial "migrate"
+  refute_output --partial "api"
+
+  # A completed task isn't run again for its dependents
+  run pitchfork restart api
+  assert_success
+  [[ "$(wc -l < runs)" -eq 1 ]]
+
+  pitchfork stop --all
+}
+
+@test "dependents are not started when a task fails" {
+  create_pitchfork_toml <<EOF
+[daemons.migrate]
+run = "exit 3"
+kind = "task"
+
+[daemons.api]
+run = "sleep 30"
+depends = ["migrate"]
+EOF
+
+  run pitchfork start api
+  assert_failure
+
+  [[ "$(get_daemon_status migrate)" == *errored* ]]
+  run pitchfork list --status running --hide-header
+  refute_output --partial "api"
+}
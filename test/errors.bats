@@ -130,3 +130,31 @@ EOF
   assert_success
   assert_output ""
 }
+
+@test "crashes after startup are saved for pitchfork notifications" {
+  export PITCHFORK_NOTIFY=file
+  create_pitchfork_toml <<EOF
+[daemons.crasher]
+run = "sh -c 'sleep 2; exit 3'"
+ready_delay = 1
+EOF
+
+  run pitchfork start crasher
+  assert_success
+  wait_for_status crasher errored
+
+  run pitchfork notifications --count
+  assert_success
+  assert_output "1"
+
+  run pitchfork notifications
+  assert_success
+  assert_output --partial "crasher failed with exit code 3"
+
+  run pitchfork notifications ack
+  assert_success
+
+  run pitchfork notifications --count
+  assert_success
+  assert_output ""
+}
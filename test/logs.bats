@@ -199,6 +199,29 @@ EOF
   pitchfork stop n_limit_test
 }
 
+@test "logs --tail-bytes shows only the last lines that fit" {
+  local slowly_output
+  slowly_output="$(script_path slowly_output.sh)"
+
+  create_pitchfork_toml <<EOF
+[daemons.tail_bytes_test]
+run = "bash $slowly_output 0.1 10"
+ready_delay = 0
+EOF
+
+  pitchfork start tail_bytes_test
+  wait_for_logs tail_bytes_test "Output 10/10" 15
+
+  # "Output 9/10\n" and "Output 10/10\n" are 25 bytes, one more line is 37
+  run pitchfork logs tail_bytes_test --tail-bytes 30 --raw
+  assert_success
+  assert_output --partial "Output 9/10"
+  assert_output --partial "Output 10/10"
+  refute_output --partial "Output 8/10"
+
+  pitchfork stop tail_bytes_test
+}
+
 @test "logs without -n outputs directly to stdout in non-interactive mode" {
   local slowly_output
   slowly_output="$(script_path slowly_output.sh)"
@@ -275,6 +298,36 @@ EOF
   pitchfork stop grepper
 }
 
+@test "logs --stdout and --stderr select one stream" {
+  create_pitchfork_toml <<EOF
+[daemons.streams]
+run = "echo to-out; echo to-err >&2; echo done; sleep 60"
+ready_output = "done"
+EOF
+
+  pitchfork start streams
+  wait_for_logs streams "done" 10
+
+  run pitchfork logs streams --stdout --raw
+  assert_success
+  assert_output --partial "to-out"
+  [[ "$output" != *"to-err"* ]]
+
+  run pitchfork logs streams --stderr --raw
+  assert_success
+  assert_output --partial "to-err"
+  [[ "$output" != *"to-out"* ]]
+
+  run pitchfork logs streams --raw
+  assert_output --partial "to-out"
+  assert_output --partial "to-err"
+
+  run pitchfork logs streams --stdout --stderr
+  assert_failure
+
+  pitchfork stop streams
+}
+
 @test "logs --grep with multiple patterns (OR logic)" {
   create_pitchfork_toml <<EOF
 [daemons.grepper]
@@ -536,6 +589,66 @@ EOF
   pitchfork stop tailer
 }
 
+# ============================================================================
+# log_filter tests
+# ============================================================================
+
+@test "log_filter drops matching lines and counts them in status" {
+  create_pitchfork_toml <<EOF
+[daemons.banner]
+run = "echo 'Compiled 1 module'; echo 'Compiled 2 modules'; echo 'server ready'; sleep 60"
+ready_output = "server ready"
+
+[daemons.banner.logs]
+log_filter = ["^Compiled "]
+EOF
+
+  pitchfork start banner
+  wait_for_logs banner "server ready" 10
+  sleep 0.5
+
+  run pitchfork logs banner --raw
+  assert_success
+  assert_output --partial "server ready"
+  [[ "$output" != *"Compiled"* ]]
+
+  run pitchfork status banner
+  assert_success
+  assert_output --partial "Filtered log lines: 2"
+
+  pitchfork stop banner
+}
+
+@test "invalid UTF-8 output doesn't stop logging" {
+  create_pitchfork_toml <<'EOF'
+[daemons.binary]
+run = 'printf "before\n\377\376 binary\n"; echo after; sleep 60'
+ready_output = "after"
+
+[daemons.escaped]
+run = 'printf "caf\351\n"; echo done; sleep 60'
+ready_output = "done"
+
+[daemons.escaped.logs]
+log_encoding = "escape"
+EOF
+
+  run pitchfork start binary escaped
+  assert_success
+
+  run pitchfork logs binary --raw
+  assert_success
+  assert_output --partial "before"
+  assert_output --partial "�� binary"
+  assert_output --partial "after"
+
+  run pitchfork logs escaped --raw
+  assert_success
+  assert_output --partial 'caf\xe9'
+
+  pitchfork stop binary escaped
+}
+
 # ============================================================================
 # SSE tests
 # ============================================================================
@@ -205,3 +205,65 @@ EOF
   done
   run pitchfork stop env_bump || true
 }
+
+@test "dependency ports are injected into dependent daemons" {
+  local port=45810
+  local marker="$TEST_TEMP_DIR/dep_port_marker"
+
+  create_pitchfork_toml <<EOF
+[daemons.db]
+run = "sleep 30"
+port = $port
+ready_delay = 1
+
+[daemons.api]
+run = "bash -c 'echo DB_PORT=\$PITCHFORK_DB_PORT > $marker; sleep 30'"
+depends = ["db"]
+EOF
+
+  run pitchfork start api
+  assert_success
+
+  wait_for_file "$marker"
+  run cat "$marker"
+  assert_output "DB_PORT=$port"
+
+  run pitchfork stop api db || true
+}
+
+@test "isolate_network lets two daemons use the same port" {
+  [[ "$(uname)" == "Linux" ]] || skip "network namespaces are Linux only"
+  command -v pasta >/dev/null 2>&1 || skip "pasta not installed"
+  local port=45820
+
+  create_pitchfork_toml <<EOF
+[daemons.iso_a]
+run = "python3 -u -m http.server \$PORT"
+port = $port
+isolate_network = true
+ready_output = "Serving HTTP"
+
+[daemons.iso_b]
+run = "python3 -u -m http.server \$PORT"
+port = $port
+isolate_network = true
+ready_output = "Serving HTTP"
+EOF
+
+  run pitchfork start iso_a
+  assert_success
+  run pitchfork start iso_b
+  assert_success
+
+  # Both listen on the same port in their namespaces; the host port of
+  # the second one is bumped
+  run curl -sf "http://127.0.0.1:$port/"
+  assert_success
+  run curl -sf "http://127.0.0.1:$((port + 1))/"
+  assert_success
+
+  run pitchfork list --json
+  assert_output --partial "$((port + 1))"
+
+  run pitchfork stop iso_a iso_b || true
+}
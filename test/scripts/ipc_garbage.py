@@ -0,0 +1,55 @@
+#!/usr/bin/env python3
+"""Send undecodable requests to the supervisor's IPC socket for e2e tests.
+
+Performs the ConnectV2 handshake announcing length-prefixed frames and
+protocol errors, then sends garbage frames and prints the name of each
+response, or "closed" once the supervisor hangs up.
+
+Usage: ipc_garbage.py <socket_path> <garbage_count>
+"""
+import json
+import socket
+import struct
+import sys
+
+sock_path = sys.argv[1]
+count = int(sys.argv[2]) if len(sys.argv) > 2 else 1
+
+sock = socket.socket(socket.AF_UNIX)
+sock.connect(sock_path)
+stream = sock.makefile("rb")
+
+
+def send(body, prefixed=True):
+    if prefixed:
+        sock.sendall(b"\x02" + struct.pack(">I", len(body)) + body)
+    else:
+        sock.sendall(body + b"\0")
+
+
+def recv():
+    first = stream.read(1)
+    if not first:
+        return "closed"
+    if first == b"\x02":
+        (length,) = struct.unpack(">I", stream.read(4))
+        body = stream.read(length)
+    else:
+        body = first
+        while not body.endswith(b"\0"):
+            byte = stream.read(1)
+            if not byte:
+                break
+            body += byte
+        body = body.rstrip(b"\0")
+    msg = json.loads(body)
+    return msg if isinstance(msg, str) else next(iter(msg))
+
+
+handshake = {"ConnectV2": {"version": "0", "features": ["length-prefix", "protocol-error"]}}
+send(json.dumps(handshake).encode(), prefixed=False)
+print(recv())
+for _ in range(count):
+    send(b"\x81\x00not a request\x00")
+    print(recv())
+print(recv())
@@ -0,0 +1,63 @@
+#!/usr/bin/env bats
+
+setup() {
+  load test_helper/common_setup
+  _common_setup
+}
+
+teardown() {
+  _common_teardown
+}
+
+@test "snapshot restore starts missing and stops extra daemons" {
+  create_pitchfork_toml <<EOF
+[daemons.snap_a]
+run = "sleep 60"
+ready_delay = 1
+
+[daemons.snap_b]
+run = "sleep 60"
+ready_delay = 1
+EOF
+
+  run pitchfork start snap_a
+  assert_success
+  run pitchfork run snap_adhoc --delay 1 -- sleep 60
+  assert_success
+
+  run pitchfork snapshot save work
+  assert_success
+
+  run pitchfork snapshot list
+  assert_success
+  assert_output --partial "work"
+  assert_output --partial "snap_adhoc"
+
+  pitchfork stop snap_a snap_adhoc
+  run pitchfork start snap_b
+  assert_success
+
+  run pitchfork snapshot restore work
+  assert_success
+  wait_for_status snap_a running
+  wait_for_status snap_adhoc running
+  wait_for_status snap_b stopped
+
+  pitchfork stop snap_a snap_adhoc
+}
+
+@test "snapshot save refuses to overwrite without --force" {
+  run pitchfork snapshot save dup
+  assert_success
+  run pitchfork snapshot save dup
+  assert_failure
+  assert_output --partial "already exists"
+  run pitchfork snapshot save dup --force
+  assert_success
+}
+
+@test "snapshot restore fails for unknown snapshot" {
+  run pitchfork snapshot restore missing
+  assert_failure
+  assert_output --partial "not found"
+}
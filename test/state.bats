@@ -205,6 +205,36 @@ EOF
   pitchfork stop stays_running
 }
 
+@test "clean keeps sticky daemons and --keep daemons" {
+  create_pitchfork_toml <<EOF
+[daemons.sticky_job]
+run = 'bash $(script_path fail.sh) 0'
+retry = 0
+sticky = true
+
+[daemons.kept_job]
+run = 'bash $(script_path fail.sh) 0'
+retry = 0
+
+[daemons.dropped_job]
+run = 'bash $(script_path fail.sh) 0'
+retry = 0
+EOF
+
+  pitchfork start sticky_job kept_job dropped_job || true
+  wait_for_status sticky_job errored
+  wait_for_status kept_job errored
+  wait_for_status dropped_job errored
+
+  run pitchfork clean --keep kept_job
+  assert_success
+
+  run read_state
+  assert_output --partial "sticky_job"
+  assert_output --partial "kept_job"
+  refute_output --partial "dropped_job"
+}
+
 @test "shell directory registration and removal" {
   # Ensure supervisor is running
   pitchfork supervisor start
@@ -269,3 +299,155 @@ EOF
 
   pitchfork stop toggle_test
 }
+
+@test "disable --auto still allows manual start" {
+  create_pitchfork_toml <<EOF
+[daemons.auto_off]
+run = "sleep 10"
+EOF
+
+  run pitchfork disable --auto auto_off
+  assert_success
+  wait_for_state "auto_disabled"
+
+  run pitchfork list
+  assert_output --partial "autostart disabled"
+
+  run pitchfork start auto_off
+  assert_success
+  wait_for_status auto_off running
+
+  run pitchfork enable auto_off
+  assert_success
+  run pitchfork list
+  refute_output --partial "autostart disabled"
+
+  pitchfork stop auto_off
+}
+
+@test "debug state reports and repairs stale daemon entries" {
+  cat > "$PITCHFORK_STATE_DIR/state.toml" <<'EOF2'
+[daemons."proj/ghost"]
+id = "proj/ghost"
+pid = 4000000
+autostop = false
+retry = 0
+retry_count = 0
+status = "running"
+
+[daemons."proj/nopid"]
+id = "proj/nopid"
+autostop = false
+retry = 0
+retry_count = 0
+status = "running"
+EOF2
+
+  run pitchfork debug state
+  assert_failure
+  assert_output --partial "proj/ghost: marked active but pid 4000000 is not running"
+  assert_output --partial "proj/nopid: marked active but has no pid"
+
+  run pitchfork debug state --repair
+  assert_success
+  assert_output --partial "Repaired proj/ghost"
+
+  run read_state
+  refute_output --partial "4000000"
+  refute_output --partial 'status = "running"'
+
+  run pitchfork debug state
+  assert_success
+  assert_output --partial "No problems found"
+}
+
+@test "debug state drops unparseable daemon entries" {
+  cat > "$PITCHFORK_STATE_DIR/state.toml" <<'EOF2'
+[daemons."proj/ok"]
+id = "proj/ok"
+autostop = false
+retry = 0
+retry_count = 0
+status = "stopped"
+
+[daemons."proj/bad"]
+id = "proj/bad"
+autostop = "maybe"
+status = "stopped"
+EOF2
+
+  run pitchfork debug state
+  assert_failure
+  assert_output --partial "proj/bad:"
+  refute_output --partial "proj/ok:"
+
+  run pitchfork debug state --repair
+  assert_success
+  assert_file_exists "$PITCHFORK_STATE_DIR/state.toml.bak"
+
+  run read_state
+  assert_output --partial "proj/ok"
+  refute_output --partial "proj/bad"
+}
+
+@test "state export and import restore daemons on a fresh state" {
+  create_pitchfork_toml <<EOF
+[daemons.moved]
+run = "sleep 60"
+ready_delay = 1
+EOF
+
+  run pitchfork start moved
+  assert_success
+  pitchfork disable moved
+
+  pitchfork state export > "$BATS_TEST_TMPDIR/state.json"
+  run cat "$BATS_TEST_TMPDIR/state.json"
+  assert_output --partial '"moved'
+  assert_output --partial '"pid": null'
+
+  run pitchfork state import "$BATS_TEST_TMPDIR/state.json"
+  assert_failure
+  assert_output --partial "supervisor is running"
+
+  pitchfork stop moved
+  pitchfork supervisor stop
+  sleep 1
+  rm "$PITCHFORK_STATE_DIR/state.toml"
+
+  run pitchfork state import "$BATS_TEST_TMPDIR/state.json"
+  assert_success
+  assert_output --partial "imported 1 daemon(s)"
+
+  run read_state
+  assert_output --regexp 'disabled = \[.*moved'
+}
+
+@test "state import skips daemons no config defines unless --force" {
+  cat > "$BATS_TEST_TMPDIR/state.json" <<'EOF2'
+{
+  "version": 1,
+  "exported_at": "2026-01-01T00:00:00Z",
+  "daemons": {
+    "proj/ghost": {
+      "id": "proj/ghost",
+      "status": "stopped",
+      "autostop": false,
+      "retry": 0,
+      "retry_count": 0
+    }
+  }
+}
+EOF2
+
+  run pitchfork state import "$BATS_TEST_TMPDIR/state.json"
+  assert_success
+  assert_output --partial "skipping 1 daemon(s)"
+  assert_output --partial "imported 0 daemon(s)"
+
+  run pitchfork state import --force "$BATS_TEST_TMPDIR/state.json"
+  assert_success
+  assert_output --partial "imported 1 daemon(s)"
+  run read_state
+  assert_output --partial "proj/ghost"
+}
@@ -688,3 +688,63 @@ EOF
 
   pitchfork stop jq_level_combo
 }
+
+# ============================================================================
+# Group D: level classification for plain-text output
+# ============================================================================
+
+@test "syslog log_format reads the level from the priority prefix" {
+  cat > "$PWD/emit.sh" <<'EOF'
+#!/usr/bin/env bash
+printf '%s\n' '<6>info_line' '<3>error_line'
+sleep 3600
+EOF
+  chmod +x "$PWD/emit.sh"
+
+  create_pitchfork_toml <<EOF
+[daemons.syslog_level]
+run = "bash $PWD/emit.sh"
+ready_output = "error_line"
+
+[daemons.syslog_level.logs]
+log_format = "syslog"
+EOF
+
+  pitchfork start syslog_level
+  wait_for_logs syslog_level "error_line" 10
+
+  run pitchfork logs syslog_level --level error --raw --no-timestamp
+  assert_success
+  assert_output --partial "error_line"
+  refute_output --partial "info_line"
+
+  pitchfork stop syslog_level
+}
+
+@test "log_level_regex classifies plain-text lines" {
+  cat > "$PWD/emit.sh" <<'EOF'
+#!/usr/bin/env bash
+printf '%s\n' '[INFO] GET /error-page 200' '[WARNING] disk almost full'
+sleep 3600
+EOF
+  chmod +x "$PWD/emit.sh"
+
+  create_pitchfork_toml <<'EOF'
+[daemons.level_regex]
+run = "bash emit.sh"
+ready_output = "disk almost full"
+
+[daemons.level_regex.logs]
+log_level_regex = '^\[(?P<level>\w+)\]'
+EOF
+
+  pitchfork start level_regex
+  wait_for_logs level_regex "disk almost full" 10
+
+  run pitchfork logs level_regex --level warn --raw --no-timestamp
+  assert_success
+  assert_output --partial "disk almost full"
+  refute_output --partial "/error-page"
+
+  pitchfork stop level_regex
+}
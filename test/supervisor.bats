@@ -45,6 +45,64 @@ get_supervisor_pid() {
   pid_after="$(get_supervisor_pid)"
   [[ -n "$pid_after" ]]
   [[ "$pid_before" != "$pid_after" ]]
+  assert_output --partial "started supervisor v"
+  assert_output --partial "(pid $pid_after)"
+
+  run pitchfork list
+  assert_success
+}
+
+@test "auto-starting the supervisor prints a banner once" {
+  create_pitchfork_toml <<EOF
+[daemons.api]
+run = "sleep 10"
+EOF
+  pitchfork supervisor stop 2>/dev/null || true
+
+  run pitchfork start api
+  assert_success
+  assert_output --partial "started supervisor v"
+  assert_output --partial "0 boot daemon(s) started"
+
+  run pitchfork stop api
+  assert_success
+  refute_output --partial "started supervisor"
+}
+
+@test "PITCHFORK_IPC_TIMEOUT fails commands against a hung supervisor" {
+  run pitchfork supervisor start
+  assert_success
+
+  local sup_pid
+  sup_pid="$(get_supervisor_pid)"
+  [[ -n "$sup_pid" ]]
+
+  kill -STOP "$sup_pid"
+  local start_time elapsed
+  start_time=$(date +%s)
+  PITCHFORK_IPC_TIMEOUT=1s run pitchfork list
+  elapsed=$(($(date +%s) - start_time))
+  kill -CONT "$sup_pid"
+
+  assert_failure
+  assert_output --partial "timed out"
+  [[ $elapsed -lt 15 ]]
+
+  run pitchfork list
+  assert_success
+}
+
+@test "undecodable IPC requests get protocol errors without disturbing the supervisor" {
+  run pitchfork supervisor start
+  assert_success
+
+  run python3 "$(script_path ipc_garbage.py)" "$PITCHFORK_STATE_DIR/sock/main.sock" 3
+  assert_success
+  assert_line --index 0 "ConnectOk"
+  assert_line --index 1 "ProtocolError"
+  assert_line --index 3 "ProtocolError"
+  # dropped after three undecodable requests in a row
+  assert_line --index 4 "closed"
 
   run pitchfork list
   assert_success
@@ -108,6 +166,92 @@ get_supervisor_pid() {
   kill_port 18998
 }
 
+@test "api events stream pushes daemon status changes" {
+  kill_port 18997
+
+  pitchfork supervisor stop 2>/dev/null || true
+  sleep 1
+  pitchfork supervisor run --web-port 18997 --force &
+  local sup_pid=$!
+  sleep 3
+
+  create_pitchfork_toml <<EOF
+[daemons.streamed]
+run = "sleep 60"
+ready_delay = 1
+EOF
+
+  local events_file="$TEST_TEMP_DIR/events.txt"
+  curl -sN --max-time 8 http://127.0.0.1:18997/api/events/stream > "$events_file" &
+  local curl_pid=$!
+  sleep 1
+
+  run pitchfork start streamed
+  assert_success
+  pitchfork stop streamed
+  wait "$curl_pid" 2>/dev/null || true
+
+  run cat "$events_file"
+  assert_output --partial "event: status"
+  assert_output --partial '"status":"running"'
+  assert_output --partial '"status":"stopped"'
+  assert_output --partial "event: event"
+  assert_output --partial '"kind":"start"'
+
+  kill "$sup_pid" 2>/dev/null || true
+  wait "$sup_pid" 2>/dev/null || true
+  kill_port 18997
+}
+
+@test "api log search finds errors across daemons" {
+  kill_port 18996
+
+  pitchfork supervisor stop 2>/dev/null || true
+  sleep 1
+  pitchfork supervisor run --web-port 18996 --force &
+  local sup_pid=$!
+  sleep 3
+
+  create_pitchfork_toml <<EOF
+[daemons.search_a]
+run = "echo '{\"level\":\"error\",\"msg\":\"db down\"}'; echo '{\"level\":\"info\",\"msg\":\"a ready\"}'; sleep 60"
+ready_output = "a ready"
+
+[daemons.search_a.logs]
+log_format = "json"
+
+[daemons.search_b]
+run = "echo '{\"level\":\"error\",\"msg\":\"cache down\"}'; echo '{\"level\":\"info\",\"msg\":\"b ready\"}'; sleep 60"
+ready_output = "b ready"
+
+[daemons.search_b.logs]
+log_format = "json"
+EOF
+
+  pitchfork start search_a search_b
+  wait_for_logs search_a "a ready" 10
+  wait_for_logs search_b "b ready" 10
+
+  run curl -s "http://127.0.0.1:18996/api/logs/search?level=error&since=30m"
+  assert_success
+  assert_output --partial "db down"
+  assert_output --partial "cache down"
+  refute_output --partial "a ready"
+
+  run curl -s "http://127.0.0.1:18996/api/logs/search?level=error&q=cache"
+  assert_success
+  assert_output --partial "cache down"
+  refute_output --partial "db down"
+
+  run curl -s -o /dev/null -w "%{http_code}" "http://127.0.0.1:18996/api/logs/search?level=loud"
+  assert_output "400"
+
+  pitchfork stop search_a search_b
+  kill "$sup_pid" 2>/dev/null || true
+  wait "$sup_pid" 2>/dev/null || true
+  kill_port 18996
+}
+
 @test "orphaned daemons are cleaned up on supervisor restart" {
   if [[ "$(uname -s)" != "Linux" && "$(uname -s)" != MINGW* && "$(uname -s)" != MSYS* ]]; then
     skip "secure process-group termination is unavailable on this Unix platform"
@@ -568,6 +712,19 @@ EOF
   wait_for_status adhoc1 stopped
 }
 
+@test "pitchfork run --ci runs --then command and stops the daemon" {
+  run pitchfork run ci_ok --ci --delay 1 --then 'echo then-ran' -- sleep 60
+  assert_success
+  assert_output --partial "then-ran"
+  wait_for_status ci_ok stopped
+}
+
+@test "pitchfork run --ci exits with the --then command status" {
+  run pitchfork run ci_fail --ci --delay 1 --then 'exit 7' -- sleep 60
+  assert_failure 7
+  wait_for_status ci_fail stopped
+}
+
 @test "supervisor stop cleans up all running daemons" {
 
   create_pitchfork_toml <<EOF
@@ -609,3 +766,29 @@ EOF
   assert_success
   assert_output --partial "stopped"
 }
+
+@test "list shows stale state without starting the supervisor" {
+  create_pitchfork_toml <<EOF
+[daemons.stale_test]
+run = "sleep 60"
+EOF
+
+  run pitchfork start stale_test
+  assert_success
+  wait_for_status stale_test running
+
+  pitchfork supervisor stop
+  sleep 1
+
+  run pitchfork list
+  assert_success
+  assert_output --partial "stale_test"
+  assert_output --partial "stale (supervisor not running)"
+
+  run pitchfork list --no-start --json
+  assert_success
+  assert_output --partial '"stale": true'
+
+  run pitchfork supervisor status
+  assert_failure
+}
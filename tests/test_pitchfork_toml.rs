@@ -210,7 +210,7 @@ ready_cmd = "test -f /tmp/ready"
     let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
     let daemon = get_daemon_by_name(&pt, "ready_daemon").unwrap();
 
-    assert_eq!(daemon.ready_delay, Some(5000));
+    assert_eq!(daemon.ready_delay, Some(Duration::from_secs(5000)));
     assert_eq!(
         daemon.ready_output,
         Some(pitchfork_toml::ReadyOutput::new("Server is ready"))
@@ -331,6 +331,54 @@ ready_http = { url = "http://localhost:8080/health", status = [99] }
     );
 }
 
+/// Test daemon with an HTTP ready check sending a custom request
+#[test]
+fn test_daemon_with_ready_http_request() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.ready_daemon]
+run = "echo 'server starting'"
+ready_http = { url = "http://localhost:8080/health", method = "post", headers = { Authorization = "Bearer abc" }, expect_status = [200, 204], expect_body_regex = "ok" }
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&pt, "ready_daemon").unwrap();
+    let ready_http = daemon.ready_http.as_ref().unwrap();
+
+    assert_eq!(ready_http.method(), "POST");
+    assert_eq!(ready_http.headers["Authorization"], "Bearer abc");
+    assert_eq!(ready_http.status, vec![200, 204]);
+    assert_eq!(ready_http.expect_body_regex.as_deref(), Some("ok"));
+
+    Ok(())
+}
+
+/// Test HTTP ready check with an invalid body regex is rejected
+#[test]
+fn test_daemon_with_invalid_ready_http_body_regex() {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.ready_daemon]
+run = "echo 'server starting'"
+ready_http = { url = "http://localhost:8080/health", expect_body_regex = "(" }
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let err = pitchfork_toml::PitchforkToml::read(&toml_path).unwrap_err();
+    let err = format!("{err:?}");
+    assert!(
+        err.contains("invalid ready_http expect_body_regex"),
+        "unexpected error: {err}"
+    );
+}
+
 /// Test daemon with structured command ready check including a timeout
 #[test]
 fn test_daemon_with_ready_cmd_object() -> Result<()> {
@@ -355,6 +403,304 @@ ready_cmd = { run = "test -f /tmp/ready", timeout = "5s" }
     Ok(())
 }
 
+/// Test daemon with a log file ready check, and that it survives a write-read round trip
+#[test]
+fn test_daemon_with_ready_log_file() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.ready_daemon]
+run = "./server"
+ready_log_file = { path = "logs/server.log", pattern = "Listening on", timeout = "30s" }
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&pt, "ready_daemon").unwrap();
+    let ready_log_file = daemon.ready_log_file.as_ref().unwrap();
+    assert_eq!(ready_log_file.path, "logs/server.log");
+    assert_eq!(ready_log_file.pattern, "Listening on");
+    assert_eq!(ready_log_file.timeout, Some(Duration::from_secs(30)));
+
+    pt.write()?;
+    let reread = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&reread, "ready_daemon").unwrap();
+    assert_eq!(daemon.ready_log_file.as_ref(), Some(ready_log_file));
+
+    Ok(())
+}
+
+/// Test that duration fields accept humantime strings as well as bare seconds
+#[test]
+fn test_daemon_duration_values() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.fast]
+run = "./server"
+ready_delay = "1500ms"
+start_delay = 2
+ready_output = { pattern = "ready", timeout = 30 }
+time_retention = 86400
+
+[daemons.slow]
+run = "./server"
+ready_delay = 3
+start_delay = "1m"
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let fast = get_daemon_by_name(&pt, "fast").unwrap();
+    assert_eq!(fast.ready_delay, Some(Duration::from_millis(1500)));
+    assert_eq!(fast.start_delay.map(|d| d.0), Some(Duration::from_secs(2)));
+    assert_eq!(
+        fast.ready_output.as_ref().unwrap().timeout,
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(fast.time_retention.as_deref(), Some("86400s"));
+    let slow = get_daemon_by_name(&pt, "slow").unwrap();
+    assert_eq!(slow.ready_delay, Some(Duration::from_secs(3)));
+    assert_eq!(slow.start_delay.map(|d| d.0), Some(Duration::from_secs(60)));
+
+    // Whole seconds are written back as a bare number, anything finer as a string
+    pt.write()?;
+    let written = fs::read_to_string(&toml_path).unwrap();
+    assert!(written.contains("ready_delay = \"1s 500ms\""));
+    assert!(written.contains("ready_delay = 3\n"));
+    let reread = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let fast = get_daemon_by_name(&reread, "fast").unwrap();
+    assert_eq!(fast.ready_delay, Some(Duration::from_millis(1500)));
+
+    Ok(())
+}
+
+/// Test that an unparseable duration is rejected when the config is read
+#[test]
+fn test_daemon_invalid_duration_value() {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    for value in [r#""soon""#, r#""-5s""#, "-1"] {
+        fs::write(
+            &toml_path,
+            format!("[daemons.api]\nrun = \"./server\"\nready_delay = {value}\n"),
+        )
+        .unwrap();
+        assert!(
+            pitchfork_toml::PitchforkToml::read(&toml_path).is_err(),
+            "ready_delay = {value} should be rejected"
+        );
+    }
+}
+
+/// Test daemon with a unix socket ready check, and that it survives a write-read round trip
+#[test]
+fn test_daemon_with_ready_unix_socket() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.ready_daemon]
+run = "./server"
+ready_unix_socket = "run/app.sock"
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&pt, "ready_daemon").unwrap();
+    assert_eq!(daemon.ready_unix_socket.as_deref(), Some("run/app.sock"));
+
+    pt.write()?;
+    let reread = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&reread, "ready_daemon").unwrap();
+    assert_eq!(daemon.ready_unix_socket.as_deref(), Some("run/app.sock"));
+
+    Ok(())
+}
+
+/// Test daemons that are ready when another daemon is, and that it survives a write-read round trip
+#[test]
+fn test_daemon_with_ready_when() -> Result<()> {
+    use pitchfork_cli::config_types::ReadyWhenState;
+
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.proxy]
+run = "./proxy"
+ready_when = { daemon = "db", state = "ready" }
+
+[daemons.sidecar]
+run = "./sidecar"
+ready_when = { daemon = "infra/db", state = "started", timeout = "1m" }
+
+[daemons.db]
+run = "./db"
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let proxy = get_daemon_by_name(&pt, "proxy").unwrap();
+    let when = proxy.ready_when.as_ref().unwrap();
+    assert_eq!(when.state, ReadyWhenState::Ready);
+    assert_eq!(when.timeout, None);
+    let db_id = pt.daemons.keys().find(|k| k.name() == "db").unwrap();
+    let namespace = db_id.namespace();
+    assert_eq!(&when.daemon_id(namespace)?, db_id);
+    let sidecar = get_daemon_by_name(&pt, "sidecar").unwrap();
+    let when = sidecar.ready_when.as_ref().unwrap();
+    assert_eq!(when.state, ReadyWhenState::Started);
+    assert_eq!(when.timeout, Some(Duration::from_secs(60)));
+    assert_eq!(when.daemon_id(namespace)?.qualified(), "infra/db");
+
+    pt.write()?;
+    let reread = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let proxy = get_daemon_by_name(&reread, "proxy").unwrap();
+    assert_eq!(proxy.ready_when.as_ref().unwrap().daemon, "db");
+    let sidecar = get_daemon_by_name(&reread, "sidecar").unwrap();
+    assert_eq!(
+        sidecar.ready_when.as_ref().unwrap().timeout,
+        Some(Duration::from_secs(60))
+    );
+
+    // A daemon can't wait on itself
+    fs::write(
+        &toml_path,
+        r#"
+[daemons.proxy]
+run = "./proxy"
+ready_when = { daemon = "proxy" }
+"#,
+    )
+    .unwrap();
+    assert!(pitchfork_toml::PitchforkToml::read(&toml_path).is_err());
+
+    Ok(())
+}
+
+/// Test per-daemon log_timezone parsing and formatting
+#[test]
+fn test_daemon_with_log_timezone() -> Result<()> {
+    use pitchfork_cli::config_types::LogTimezone;
+
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.api]
+run = "./server"
+
+[daemons.api.logs]
+log_timezone = "Asia/Kolkata"
+
+[daemons.worker]
+run = "./worker"
+
+[daemons.worker.logs]
+log_timezone = "utc"
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let api = get_daemon_by_name(&pt, "api").unwrap();
+    let tz = api.logs.as_ref().unwrap().log_timezone.unwrap();
+    assert_eq!(tz.to_string(), "Asia/Kolkata");
+    let worker = get_daemon_by_name(&pt, "worker").unwrap();
+    assert_eq!(
+        worker.logs.as_ref().unwrap().log_timezone,
+        Some(LogTimezone::Utc)
+    );
+
+    let ts = chrono::DateTime::parse_from_rfc3339("2026-01-15T10:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Local);
+    assert_eq!(tz.format(&ts, "%Y-%m-%d %H:%M"), "2026-01-15 15:30");
+    assert_eq!(
+        LogTimezone::Utc.format(&ts, "%Y-%m-%d %H:%M"),
+        "2026-01-15 10:00"
+    );
+
+    pt.write()?;
+    let reread = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let api = get_daemon_by_name(&reread, "api").unwrap();
+    assert_eq!(api.logs.as_ref().unwrap().log_timezone, Some(tz));
+
+    fs::write(
+        &toml_path,
+        "[daemons.api]\nrun = \"./server\"\n\n[daemons.api.logs]\nlog_timezone = \"Mars/Olympus\"\n",
+    )
+    .unwrap();
+    assert!(pitchfork_toml::PitchforkToml::read(&toml_path).is_err());
+
+    Ok(())
+}
+
+/// Test log_encoding in a daemon's logs table
+#[test]
+fn test_daemon_with_log_encoding() -> Result<()> {
+    use pitchfork_cli::config_types::LogEncoding;
+
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.legacy]
+run = "./legacy"
+
+[daemons.legacy.logs]
+log_encoding = "latin1"
+
+[daemons.api]
+run = "./server"
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let legacy = get_daemon_by_name(&pt, "legacy").unwrap();
+    let encoding = legacy.logs.as_ref().unwrap().log_encoding;
+    assert_eq!(encoding, Some(LogEncoding::Latin1));
+    assert_eq!(encoding.unwrap().decode(b"caf\xe9"), "café");
+    let api = get_daemon_by_name(&pt, "api").unwrap();
+    assert!(api.logs.is_none());
+    assert_eq!(LogEncoding::default().decode(b"caf\xe9"), "caf\u{fffd}");
+
+    fs::write(
+        &toml_path,
+        "[daemons.api]\nrun = \"./server\"\n\n[daemons.api.logs]\nlog_encoding = \"ebcdic\"\n",
+    )
+    .unwrap();
+    assert!(pitchfork_toml::PitchforkToml::read(&toml_path).is_err());
+
+    Ok(())
+}
+
+/// Test that a log file ready check requires both path and pattern
+#[test]
+fn test_daemon_with_ready_log_file_missing_pattern() {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.ready_daemon]
+run = "./server"
+ready_log_file = { path = "logs/server.log" }
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    assert!(pitchfork_toml::PitchforkToml::read(&toml_path).is_err());
+}
+
 /// Test daemon with structured HTTP ready check including a timeout
 #[test]
 fn test_daemon_with_ready_http_timeout() -> Result<()> {
@@ -739,7 +1085,7 @@ ready_output = "ready to accept connections"
     let db = get_daemon_by_name(&merged, "database").unwrap();
     assert_eq!(db.run, "postgres -D ./data");
     assert_eq!(db.retry.count(), 10);
-    assert_eq!(db.ready_delay, Some(3000));
+    assert_eq!(db.ready_delay, Some(Duration::from_secs(3000)));
     assert_eq!(
         db.ready_output,
         Some(pitchfork_toml::ReadyOutput::new(
@@ -906,6 +1252,37 @@ depends = []
     Ok(())
 }
 
+/// Test restart_with_dependencies parsing
+#[test]
+fn test_daemon_with_restart_with_dependencies() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.db]
+run = "postgres"
+
+[daemons.api]
+run = "npm run server"
+depends = ["db"]
+restart_with_dependencies = true
+"#;
+
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let api = get_daemon_by_name(&pt, "api").unwrap();
+    assert_eq!(api.restart_with_dependencies, Some(true));
+    assert_eq!(
+        get_daemon_by_name(&pt, "db")
+            .unwrap()
+            .restart_with_dependencies,
+        None
+    );
+
+    Ok(())
+}
+
 /// Test that retry can be a boolean (true = infinite, false = 0)
 #[test]
 fn test_retry_boolean_values() -> Result<()> {
@@ -1298,6 +1675,59 @@ run = "echo local"
     assert_eq!(pt.daemons.get(&local_only_key).unwrap().run, "echo local");
 }
 
+/// A local.toml entry only needs the fields it overrides; the rest come from
+/// the sibling pitchfork.toml
+#[test]
+fn test_local_toml_partial_daemon_override() {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+    let local_path = temp_dir.path().join("pitchfork.local.toml");
+    let ns = temp_dir.path().file_name().unwrap().to_str().unwrap();
+
+    fs::write(
+        &toml_path,
+        r#"
+[daemons.api]
+run = "npm run server"
+ready_port = 3000
+env = { NODE_ENV = "production", LOG_LEVEL = "info" }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &local_path,
+        r#"
+[daemons.api]
+env = { LOG_LEVEL = "debug" }
+"#,
+    )
+    .unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::all_merged_from(temp_dir.path()).unwrap();
+    let api = &pt.daemons[&DaemonId::parse(&format!("{ns}/api")).unwrap()];
+    assert_eq!(api.run, "npm run server");
+    assert_eq!(api.ready_port, Some(pitchfork_toml::ReadyPort::new(3000)));
+    let env = api.env.as_ref().unwrap();
+    assert_eq!(env["NODE_ENV"], "production");
+    assert_eq!(env["LOG_LEVEL"], "debug");
+    assert_eq!(api.path.as_deref(), Some(local_path.as_path()));
+    assert_eq!(api.overrides.as_deref(), Some(toml_path.as_path()));
+
+    // Writing the local file back keeps it down to the overrides
+    let mut local = pitchfork_toml::PitchforkToml::read(&local_path).unwrap();
+    local
+        .daemons
+        .get_mut(&DaemonId::parse(&format!("{ns}/api")).unwrap())
+        .unwrap()
+        .run = "npm run dev".to_string();
+    local.write().unwrap();
+    let written: toml::Table = toml::from_str(&fs::read_to_string(&local_path).unwrap()).unwrap();
+    let api = written["daemons"]["api"].as_table().unwrap();
+    assert_eq!(api.len(), 2, "unexpected fields: {api:?}");
+    assert_eq!(api["run"].as_str(), Some("npm run dev"));
+    assert_eq!(api["env"]["LOG_LEVEL"].as_str(), Some("debug"));
+}
+
 // =============================================================================
 // Tests for get_local_configured_daemons and get_global_configured_daemons
 // =============================================================================
@@ -2519,3 +2949,169 @@ run = "echo"
 
     Ok(())
 }
+
+/// Test that [env_templates] parse, merge across files and must be defined.
+#[test]
+fn test_env_templates_parsed_and_validated() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    fs::write(
+        &toml_path,
+        r#"
+[env_templates.postgres]
+DATABASE_URL = "postgres://localhost:{{ daemons.db.port }}/app"
+
+[daemons.api]
+run = "npm start"
+env_templates = ["postgres"]
+"#,
+    )
+    .unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    assert_eq!(
+        pt.env_templates["postgres"]["DATABASE_URL"],
+        "postgres://localhost:{{ daemons.db.port }}/app"
+    );
+    let daemon = get_daemon_by_name(&pt, "api").unwrap();
+    assert_eq!(daemon.env_templates, vec!["postgres".to_string()]);
+
+    // A later definition replaces the whole template
+    let mut merged = pitchfork_toml::PitchforkToml::default();
+    merged.merge(pt);
+    merged.merge(pitchfork_toml::PitchforkToml::parse_str(
+        "[env_templates.postgres]\nPGHOST = \"db.internal\"\n",
+        &temp_dir.path().join("other.toml"),
+    )?);
+    assert_eq!(merged.env_templates["postgres"].len(), 1);
+    assert_eq!(merged.env_templates["postgres"]["PGHOST"], "db.internal");
+
+    // Referencing an undefined template fails once the config is merged
+    fs::write(
+        &toml_path,
+        r#"
+[daemons.api]
+run = "npm start"
+env_templates = ["postgress"]
+"#,
+    )
+    .unwrap();
+    let err = pitchfork_toml::PitchforkToml::all_merged_from(temp_dir.path()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("uses undefined env template 'postgress'"),
+        "unexpected error: {err}"
+    );
+
+    Ok(())
+}
+
+/// Test umask and resource limits parsing
+#[test]
+fn test_umask_and_limits_parsed() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.db]
+run = "./bin/db"
+umask = "027"
+limits = { nofile = 65536, core = 0 }
+"#;
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&pt, "db").unwrap();
+    assert_eq!(daemon.umask, Some(pitchfork_toml::Umask(0o027)));
+    let limits = daemon.limits.unwrap();
+    assert_eq!(limits.nofile, Some(65536));
+    assert_eq!(limits.core, Some(0));
+    assert_eq!(limits.nproc, None);
+
+    // Round trip keeps the octal form
+    pt.write()?;
+    let raw = fs::read_to_string(&toml_path).unwrap();
+    assert!(raw.contains(r#"umask = "027""#));
+
+    Ok(())
+}
+
+/// Test that invalid umask values are rejected
+#[test]
+fn test_invalid_umask_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    for umask in ["\"089\"", "\"\"", "\"077777\""] {
+        let toml_content = format!("[daemons.db]\nrun = \"./bin/db\"\numask = {umask}\n");
+        fs::write(&toml_path, toml_content).unwrap();
+        assert!(
+            pitchfork_toml::PitchforkToml::read(&toml_path).is_err(),
+            "umask {umask} should be rejected"
+        );
+    }
+}
+
+/// Test requires parsing and round trip
+#[test]
+fn test_requires_parsed() -> Result<()> {
+    use pitchfork_toml::RequirementTarget;
+
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    let toml_content = r#"
+[daemons.api]
+run = "./bin/api"
+requires = [{ tcp = "localhost:5432" }, { http = "http://localhost:9200", timeout = "2m" }]
+"#;
+    fs::write(&toml_path, toml_content).unwrap();
+
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&pt, "api").unwrap();
+    assert_eq!(daemon.requires.len(), 2);
+    assert_eq!(
+        daemon.requires[0].target,
+        RequirementTarget::Tcp("localhost:5432".into())
+    );
+    assert_eq!(
+        daemon.requires[0].timeout(),
+        pitchfork_toml::Requirement::DEFAULT_TIMEOUT
+    );
+    assert_eq!(
+        daemon.requires[1].target,
+        RequirementTarget::Http("http://localhost:9200".into())
+    );
+    assert_eq!(daemon.requires[1].timeout, Some(Duration::from_secs(120)));
+
+    pt.write()?;
+    let pt = pitchfork_toml::PitchforkToml::read(&toml_path)?;
+    let daemon = get_daemon_by_name(&pt, "api").unwrap();
+    assert_eq!(daemon.requires[1].timeout, Some(Duration::from_secs(120)));
+
+    Ok(())
+}
+
+/// Test that malformed requires entries are rejected
+#[test]
+fn test_invalid_requires_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let toml_path = temp_dir.path().join("pitchfork.toml");
+
+    for entry in [
+        r#"{ tcp = "localhost" }"#,
+        r#"{ tcp = ":5432" }"#,
+        r#"{ http = "localhost:9200" }"#,
+        r#"{ tcp = "localhost:5432", http = "http://localhost:9200" }"#,
+        r#"{ timeout = "5s" }"#,
+        r#"{ tcp = "localhost:5432", timeout = "soon" }"#,
+    ] {
+        let toml_content = format!("[daemons.api]\nrun = \"./bin/api\"\nrequires = [{entry}]\n");
+        fs::write(&toml_path, toml_content).unwrap();
+        assert!(
+            pitchfork_toml::PitchforkToml::read(&toml_path).is_err(),
+            "requires {entry} should be rejected"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Exercises the `test-support` fixture against the real binary.
+#![cfg(feature = "test-support")]
+
+use pitchfork_cli::test_support::TestSupervisor;
+use std::time::Duration;
+
+fn supervisor() -> TestSupervisor {
+    TestSupervisor::builder()
+        .bin(env!("CARGO_BIN_EXE_pitchfork"))
+        .start()
+        .unwrap()
+}
+
+#[test]
+fn test_supervisor_runs_daemon_in_isolation() {
+    let sup = supervisor();
+    sup.write_config(
+        r#"
+[daemons.greeter]
+run = "echo hello from greeter; sleep 60"
+ready_output = "hello from greeter"
+"#,
+    )
+    .unwrap();
+
+    sup.pitchfork(["start", "greeter"]).success().unwrap();
+    let daemon = sup
+        .wait_for_running("greeter", Duration::from_secs(10))
+        .unwrap();
+    assert!(daemon.pid.is_some());
+    sup.wait_for_logs("greeter", "hello from greeter", Duration::from_secs(5))
+        .unwrap();
+
+    // Each fixture has its own state: a second one sees no daemons
+    let other = supervisor();
+    assert!(other.daemon("greeter").unwrap().is_none());
+    assert_ne!(sup.state_dir(), other.state_dir());
+
+    sup.pitchfork(["stop", "greeter"]).success().unwrap();
+    sup.wait_for_status("greeter", Duration::from_secs(10), |s| !s.is_running())
+        .unwrap();
+}
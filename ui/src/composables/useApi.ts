@@ -1,6 +1,16 @@
 import { ref, shallowRef, watchEffect, type Ref } from 'vue'
 import { toast } from 'vue-sonner'
-import type { DaemonEntry, DaemonStats, NamespaceEntry, ProcessTree } from '@/types/api'
+import type {
+  ConflictResolution,
+  DaemonConfig,
+  DaemonEntry,
+  DaemonEvent,
+  DaemonStats,
+  MetricSample,
+  NamespaceEntry,
+  PortConflict,
+  ProcessTree,
+} from '@/types/api'
 
 const API_BASE = (() => {
   const base = (window as any).__PITCHFORK_BASE__ as string | undefined
@@ -19,6 +29,13 @@ function getAuthHeaders(): Record<string, string> {
   return headers
 }
 
+/** Error for a failed API call, carrying the response body */
+export class ApiError extends Error {
+  constructor(message: string, public data: unknown) {
+    super(message)
+  }
+}
+
 export async function api<T>(path: string, init?: RequestInit): Promise<T> {
   const res = await fetch(`${API_BASE}${path}`, {
     ...init,
@@ -32,7 +49,7 @@ export async function api<T>(path: string, init?: RequestInit): Promise<T> {
     const msg = data && typeof data === 'object' && 'error' in data
       ? String(data.error)
       : `HTTP ${res.status}`
-    throw new Error(msg)
+    throw new ApiError(msg, data)
   }
   return data as T
 }
@@ -112,10 +129,16 @@ function daemonName(id: string): string {
   return id.split('.').pop() ?? id
 }
 
+function portConflict(e: unknown): PortConflict | null {
+  if (!(e instanceof ApiError) || !e.data || typeof e.data !== 'object') return null
+  return (e.data as { port_conflict?: PortConflict }).port_conflict ?? null
+}
+
 async function toastAction(
   name: string,
   verb: string,
   action: () => Promise<boolean>,
+  resolveConflict?: (resolution: ConflictResolution) => void,
 ) {
   const pastTense: Record<string, string> = {
     Start: 'started',
@@ -133,7 +156,19 @@ async function toastAction(
     }
   } catch (e: any) {
     toast.dismiss(toastId)
-    toast.error(`${verb} ${name} failed`, { duration: 4000, description: e.message ?? 'unknown error' })
+    const conflict = portConflict(e)
+    if (conflict && resolveConflict) {
+      // Offer the same choices as `pitchfork start`: kill the process
+      // holding the port, or move the daemon to the next free port.
+      toast.error(`${verb} ${name} failed: port ${conflict.port} is in use`, {
+        duration: 15000,
+        description: `${conflict.process} (PID ${conflict.pid}) is listening on it`,
+        action: { label: 'Kill it', onClick: () => resolveConflict('kill') },
+        cancel: { label: 'Use next free port', onClick: () => resolveConflict('bump') },
+      })
+    } else {
+      toast.error(`${verb} ${name} failed`, { duration: 4000, description: e.message ?? 'unknown error' })
+    }
     throw e
   }
 }
@@ -156,20 +191,24 @@ export function useDaemonActions() {
     }
   }
 
-  function start(id: string) {
+  function conflictQuery(resolution?: ConflictResolution): string {
+    return resolution ? `?on_conflict=${resolution}` : ''
+  }
+
+  function start(id: string, resolution?: ConflictResolution): Promise<void> {
     return toastAction(daemonName(id), 'Start', wrap(id, () =>
-      api(`/daemons/${encodeURIComponent(id)}/start`, { method: 'POST' }),
-    ))
+      api(`/daemons/${encodeURIComponent(id)}/start${conflictQuery(resolution)}`, { method: 'POST' }),
+    ), resolution ? undefined : (r) => { start(id, r).catch(() => {}) })
   }
   function stop(id: string) {
     return toastAction(daemonName(id), 'Stop', wrap(id, () =>
       api(`/daemons/${encodeURIComponent(id)}/stop`, { method: 'POST' }),
     ))
   }
-  function restart(id: string) {
+  function restart(id: string, resolution?: ConflictResolution): Promise<void> {
     return toastAction(daemonName(id), 'Restart', wrap(id, () =>
-      api(`/daemons/${encodeURIComponent(id)}/restart`, { method: 'POST' }),
-    ))
+      api(`/daemons/${encodeURIComponent(id)}/restart${conflictQuery(resolution)}`, { method: 'POST' }),
+    ), resolution ? undefined : (r) => { restart(id, r).catch(() => {}) })
   }
   function enable(id: string) {
     return toastAction(daemonName(id), 'Enable', wrap(id, () =>
@@ -353,3 +392,53 @@ export function useProcessTree(id: Ref<string>, pollInterval = 3000) {
 
   return { tree, loading, error, refresh: fetchTree }
 }
+
+/** Poll a per-daemon endpoint, discarding responses for a stale `id`. */
+function useDaemonResource<T>(id: Ref<string>, path: (id: string) => string, initial: T, pollInterval: number) {
+  const data = shallowRef<T>(initial)
+  const error = ref<string | null>(null)
+  let nonce = 0
+
+  async function fetchData() {
+    const current = ++nonce
+    try {
+      error.value = null
+      const d = await api<T>(path(encodeURIComponent(id.value)))
+      if (current !== nonce) return
+      data.value = d
+    } catch (e: any) {
+      if (current !== nonce) return
+      error.value = e.message ?? 'Unknown error'
+    }
+  }
+
+  watchEffect((onCleanup) => {
+    if (!id.value) return
+    data.value = initial
+    fetchData()
+    const interval = setInterval(fetchData, pollInterval)
+    onCleanup(() => clearInterval(interval))
+  })
+
+  return { data, error, refresh: fetchData }
+}
+
+export function useDaemonMetrics(id: Ref<string>, pollInterval = 3000) {
+  const { data, error } = useDaemonResource<MetricSample[]>(id, (i) => `/daemons/${i}/metrics`, [], pollInterval)
+  return { samples: data, error }
+}
+
+export function useDaemonEvents(id: Ref<string>, pollInterval = 3000) {
+  const { data, error } = useDaemonResource<DaemonEvent[]>(id, (i) => `/daemons/${i}/events`, [], pollInterval)
+  return { events: data, error }
+}
+
+export function useDaemonConfig(id: Ref<string>, pollInterval = 10000) {
+  const { data, error } = useDaemonResource<DaemonConfig | null>(id, (i) => `/daemons/${i}/config`, null, pollInterval)
+  return { config: data, error }
+}
+
+export function useDaemonLogTail(id: Ref<string>, lines = 30, pollInterval = 3000) {
+  const { data, error } = useDaemonResource<string[]>(id, (i) => `/daemons/${i}/logs?lines=${lines}`, [], pollInterval)
+  return { lines: data, error }
+}
@@ -0,0 +1,39 @@
+import { ref, watch } from 'vue'
+
+export type Theme = 'dark' | 'light'
+
+const STORAGE_KEY = 'pitchfork-theme'
+
+function readStoredTheme(): Theme {
+  try {
+    return localStorage.getItem(STORAGE_KEY) === 'light' ? 'light' : 'dark'
+  } catch {
+    // localStorage can throw in private browsing or when storage is disabled
+    return 'dark'
+  }
+}
+
+function applyTheme(theme: Theme) {
+  document.documentElement.dataset.theme = theme
+  document.querySelector('meta[name="color-scheme"]')?.setAttribute('content', theme)
+}
+
+// Shared across all callers so the nav toggle and the toaster stay in sync.
+const theme = ref<Theme>(readStoredTheme())
+applyTheme(theme.value)
+
+watch(theme, (value) => {
+  applyTheme(value)
+  try {
+    localStorage.setItem(STORAGE_KEY, value)
+  } catch {
+    // Persisting is best-effort; the theme still applies for this session.
+  }
+})
+
+export function useTheme() {
+  function toggleTheme() {
+    theme.value = theme.value === 'dark' ? 'light' : 'dark'
+  }
+  return { theme, toggleTheme }
+}
@@ -1,5 +1,6 @@
 import { createApp } from 'vue'
 import 'vue-sonner/style.css'
+import './styles/theme.less'
 import { Toaster } from 'vue-sonner'
 import App from './App.vue'
 import router from './router'
@@ -3,6 +3,7 @@ import HomeView from '@/views/HomeView.vue'
 import DaemonDetailView from '@/views/DaemonDetailView.vue'
 import LogView from '@/views/LogView.vue'
 import ProxiesView from '@/views/ProxiesView.vue'
+import SearchView from '@/views/SearchView.vue'
 
 const rawBase = (window as any).__PITCHFORK_BASE__ as string | undefined
 const base = rawBase && rawBase !== '__PF_BASE_PLACEHOLDER__' ? rawBase : undefined
@@ -14,6 +15,7 @@ const router = createRouter({
     { path: '/daemon/:id', name: 'daemon', component: DaemonDetailView, props: true },
     { path: '/logs/:id', name: 'logs', component: LogView, props: true },
     { path: '/proxies', name: 'proxies', component: ProxiesView },
+    { path: '/search', name: 'search', component: SearchView },
   ],
 })
 
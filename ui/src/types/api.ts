@@ -7,7 +7,9 @@ export interface DaemonId {
 export type DaemonStatus =
   | { type: 'failed'; message: string }
   | { type: 'waiting' }
+  | { type: 'starting' }
   | { type: 'running' }
+  | { type: 'unhealthy' }
   | { type: 'stopping' }
   | { type: 'errored'; code: number }
   | { type: 'stopped' }
@@ -18,18 +20,27 @@ export interface DaemonEntry {
   status: DaemonStatus
   is_available: boolean
   pid: number | null
+  host: string | null
   shell_pid: number | null
   uptime_secs: number | null
+  process_count: number | null
+  uptime: string | null
   active_port: number | null
   resolved_port: number[]
   slug: string | null
   autostop: boolean | null
   retry_count: number
+  next_retry: string | null
   is_disabled: boolean | null
   cpu_percent: number | null
   memory_bytes: number | null
   memory_limit: string | null
   cpu_limit: string | null
+  alert: string | null
+  drift: string | null
+  ready_ms: number | null
+  ready_p95_ms: number | null
+  startup: string | null
   stop_signal: string | null
   stop_timeout: number | null
   restart_policy: string | null
@@ -44,10 +55,15 @@ export interface DaemonEntry {
   ready_cmd: string | null
   proxy_url: string | null
   pty: boolean | null
+  isolate_network: boolean | null
+  restart_on_config_change: boolean | null
+  restart_on_binary_change: boolean | null
   proxy: boolean | null
   depends: string[]
   env: string[] | null
   cron_schedule: string | null
+  next_cron_secs: number | null
+  next_cron: string | null
   command: string | null
   dir: string | null
   mise: boolean | null
@@ -103,3 +119,51 @@ export interface ProcessTree {
   status: string
   children: ProcessTree[]
 }
+
+export interface MetricSample {
+  /** Unix timestamp in seconds */
+  at: number
+  cpu_percent: number
+  memory_bytes: number
+}
+
+export interface DaemonEvent {
+  at: string
+  kind: 'start' | 'ready' | 'retry' | 'fail' | 'stop' | 'exit' | 'oom' | 'timeout' | 'stale' | 'throttled' | 'config'
+  message: string | null
+}
+
+export interface DaemonConfig {
+  path: string | null
+  toml: string | null
+}
+
+/** A process holding a port a daemon needs, returned by start/restart */
+export interface PortConflict {
+  port: number
+  process: string
+  pid: number
+}
+
+/** How to retry a start that hit a port conflict */
+export type ConflictResolution = 'kill' | 'bump'
+
+/** One line returned by the cross-daemon log search */
+export interface LogSearchEntry {
+  id: number
+  daemon_id: string
+  /** RFC 3339 timestamp */
+  timestamp: string
+  level: string | null
+  msg: string | null
+  logger: string | null
+  stream: 'stdout' | 'stderr'
+  message: string
+}
+
+export interface LogSearchResult {
+  /** Newest first */
+  entries: LogSearchEntry[]
+  /** More entries matched than the requested limit */
+  truncated: boolean
+}
@@ -8,11 +8,15 @@ export interface ParsedLogLine {
 // (src/web/routes/api/logs.rs: `{ts} {msg}`); keep the message verbatim.
 const LOG_PREFIX_RE = /^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}) (.*)$/
 
-// Match all CSI sequences (ESC[...X)
-const CSI_PATTERN = /\x1b\[[\d;?]*[A-Za-z]/g
+// Match all CSI sequences (ESC[...X). The tail API already drops non-SGR
+// controls server-side (src/ansi.rs: strip_pty_controls); this is a fallback.
+const CSI_PATTERN = /\x1b\[[\d;:?]*[A-Za-z]/g
 
-// Match color CSI sequences only (ESC[...m)
-const COLOR_CSI = /\x1b\[([\d;]*)m/g
+// Match OSC sequences (ESC]...BEL or ESC]...ESC\), e.g. window titles and hyperlinks
+const OSC_PATTERN = /\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)/g
+
+// Match color CSI sequences only (ESC[...m); `:` separates truecolor sub-params
+const COLOR_CSI = /\x1b\[([\d;:]*)m/g
 
 // Standard 16 colors
 const COLORS_16: Record<number, string> = {
@@ -100,6 +104,8 @@ function preprocess(text: string): string {
   text = text.replace(/\x07/g, '')
   // Remove backspace
   text = text.replace(/\x08/g, '')
+  // Strip OSC sequences (window title, hyperlinks)
+  text = text.replace(OSC_PATTERN, '')
   // Strip non-color CSI sequences (cursor move, clear screen, etc.)
   text = text.replace(CSI_PATTERN, (match) => {
     if (match.endsWith('m')) return match
@@ -108,32 +114,53 @@ function preprocess(text: string): string {
   return text
 }
 
-function parseColorParams(params: number[]): {
+interface ColorChanges {
   reset?: boolean
   fg?: string
   bg?: string
   bold?: boolean
   dim?: boolean
-} {
-  const result: {
-    reset?: boolean
-    fg?: string
-    bg?: string
-    bold?: boolean
-    dim?: boolean
-  } = {}
+  italic?: boolean
+  underline?: boolean
+  reverse?: boolean
+}
+
+function parseColorParams(params: number[]): ColorChanges {
+  const result: ColorChanges = {}
 
   for (let i = 0; i < params.length; i++) {
     const p = params[i]
     if (p === 0) {
-      result.reset = true
+      // Attributes after a reset in the same sequence still apply
+      Object.assign(result, {
+        reset: true,
+        fg: undefined,
+        bg: undefined,
+        bold: undefined,
+        dim: undefined,
+        italic: undefined,
+        underline: undefined,
+        reverse: undefined,
+      })
     } else if (p === 1) {
       result.bold = true
     } else if (p === 2) {
       result.dim = true
+    } else if (p === 3) {
+      result.italic = true
+    } else if (p === 4) {
+      result.underline = true
+    } else if (p === 7) {
+      result.reverse = true
     } else if (p === 22) {
       result.bold = false
       result.dim = false
+    } else if (p === 23) {
+      result.italic = false
+    } else if (p === 24) {
+      result.underline = false
+    } else if (p === 27) {
+      result.reverse = false
     } else if (p === 39) {
       // Default foreground — clear explicit color so it inherits from parent
       result.fg = ''
@@ -165,32 +192,45 @@ interface ColorState {
   bg: string
   bold: boolean
   dim: boolean
+  italic: boolean
+  underline: boolean
+  reverse: boolean
+}
+
+const DEFAULT_STATE: ColorState = {
+  fg: '',
+  bg: '',
+  bold: false,
+  dim: false,
+  italic: false,
+  underline: false,
+  reverse: false,
 }
 
 function ansiToHtml(text: string): string {
   let html = ''
   let lastIndex = 0
-  let state: ColorState = { fg: '', bg: '', bold: false, dim: false }
+  let state: ColorState = { ...DEFAULT_STATE }
 
   let match: RegExpExecArray | null
   while ((match = COLOR_CSI.exec(text)) !== null) {
     const segment = text.slice(lastIndex, match.index)
     if (segment) html += renderSegment(segment, state)
 
-    const params = match[1].split(';').map((s) => {
+    const params = match[1].split(/[;:]/).map((s) => {
       const n = Number(s)
       return Number.isNaN(n) ? 0 : n
     })
     const changes = parseColorParams(params)
 
-    if (changes.reset) {
-      state = { fg: '', bg: '', bold: false, dim: false }
-    } else {
-      if (changes.fg !== undefined) state.fg = changes.fg
-      if (changes.bg !== undefined) state.bg = changes.bg
-      if (changes.bold !== undefined) state.bold = changes.bold
-      if (changes.dim !== undefined) state.dim = changes.dim
-    }
+    if (changes.reset) state = { ...DEFAULT_STATE }
+    if (changes.fg !== undefined) state.fg = changes.fg
+    if (changes.bg !== undefined) state.bg = changes.bg
+    if (changes.bold !== undefined) state.bold = changes.bold
+    if (changes.dim !== undefined) state.dim = changes.dim
+    if (changes.italic !== undefined) state.italic = changes.italic
+    if (changes.underline !== undefined) state.underline = changes.underline
+    if (changes.reverse !== undefined) state.reverse = changes.reverse
 
     lastIndex = COLOR_CSI.lastIndex
   }
@@ -202,12 +242,22 @@ function ansiToHtml(text: string): string {
 }
 
 function renderSegment(text: string, state: ColorState): string {
-  const styles: string[] = []
-  if (state.fg) styles.push(`color:${state.fg}`)
-  else styles.push('color:rgba(255,255,255,0.85)')
-  if (state.bg) styles.push(`background-color:${state.bg}`)
+  const isDefault = (Object.keys(DEFAULT_STATE) as (keyof ColorState)[])
+    .every((k) => state[k] === DEFAULT_STATE[k])
+  if (isDefault) return escapeHtml(text)
+  // Unset colors fall back to the theme's text/background via CSS variables
+  // so plain output stays readable in both the dark and light theme.
+  let fg = state.fg || 'rgba(var(--pf-fg), 0.85)'
+  let bg = state.bg
+  if (state.reverse) {
+    ;[fg, bg] = [bg || 'rgb(var(--pf-bg))', fg]
+  }
+  const styles: string[] = [`color:${fg}`]
+  if (bg) styles.push(`background-color:${bg}`)
   if (state.bold) styles.push('font-weight:700')
   if (state.dim) styles.push('opacity:0.5')
+  if (state.italic) styles.push('font-style:italic')
+  if (state.underline) styles.push('text-decoration:underline')
   return `<span style="${styles.join(';')}">${escapeHtml(text)}</span>`
 }
 